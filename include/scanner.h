@@ -0,0 +1,40 @@
+/*
+ * C interface of the scanner library, built with the `ffi` feature:
+ *
+ *     cargo rustc --release --lib --features ffi --crate-type staticlib
+ *
+ * Strings returned by the library are owned by the caller and must be
+ * released with scanner_string_free. See src/ffi.rs.
+ */
+
+#ifndef SCANNER_H
+#define SCANNER_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define SCANNER_FFI_API_VERSION 1
+
+/* Version of the interface implemented by the library */
+uint32_t scanner_ffi_api_version(void);
+
+/*
+ * Scan a directory with the default options, returning the report as a JSON
+ * string (the document sent by --push), or NULL on failure.
+ */
+char *scanner_scan(const char *root);
+
+/* Error of the last scan on this thread, or NULL if it succeeded or none ran */
+char *scanner_last_error(void);
+
+/* Release a string returned by the library; NULL is ignored */
+void scanner_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SCANNER_H */
@@ -0,0 +1,271 @@
+//! Aggregation of classified dependencies per package and application
+//!
+//! The [`Classifier`](crate::analyzer::Classifier) emits one entry per
+//! finding, so an installed package, its lockfile entry and its manifest range
+//! end up in three separate entries and mismatch detection never sees them
+//! together. The [`Aggregator`] merges the HAS, SHOULD and CAN entries of the
+//! same package within the same application into a single entry.
+//!
+//! Entries are grouped by ecosystem, name and application root, so the
+//! application roots must be assigned first (see
+//! [`ApplicationLinker::assign_application_roots`](crate::analyzer::ApplicationLinker::assign_application_roots)).
+//! Entries without an application root are left as they are. When a group has
+//! several entries for one classification, the one closest to the application
+//! root is merged (e.g. the hoisted `node_modules` copy); identical duplicates
+//! are dropped and entries with other versions are kept separately.
+
+use crate::models::{ClassifiedDependency, Ecosystem};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Merger of per-finding entries into one entry per package and application
+pub struct Aggregator;
+
+impl Aggregator {
+    /// Create a new Aggregator
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Merge entries of the same package and application
+    ///
+    /// Output order follows the first entry of each group in the input.
+    pub fn aggregate(&self, dependencies: Vec<ClassifiedDependency>) -> Vec<ClassifiedDependency> {
+        let mut groups: Vec<Vec<ClassifiedDependency>> = Vec::new();
+        let mut index: HashMap<(Ecosystem, String, PathBuf), usize> = HashMap::new();
+        let mut results = Vec::new();
+
+        for dep in dependencies {
+            let Some(root) = dep.application_root.clone() else {
+                results.push(vec![dep]);
+                continue;
+            };
+            let key = (dep.ecosystem, dep.name.clone(), root);
+            match index.get(&key) {
+                Some(&i) => groups[i].push(dep),
+                None => {
+                    index.insert(key, groups.len());
+                    groups.push(vec![dep]);
+                    results.push(Vec::new());
+                }
+            }
+        }
+
+        // Groups are merged in place of their placeholder to keep input order
+        let mut merged_groups = groups.into_iter().map(|group| self.merge_group(group));
+        results
+            .into_iter()
+            .flat_map(|entries| {
+                if entries.is_empty() {
+                    merged_groups.next().unwrap_or_default()
+                } else {
+                    entries
+                }
+            })
+            .collect()
+    }
+
+    /// Merge one group, returning the merged entry followed by leftovers
+    fn merge_group(&self, mut group: Vec<ClassifiedDependency>) -> Vec<ClassifiedDependency> {
+        // Prefer the entry closest to the application root for each classification
+        group.sort_by_key(entry_depth);
+
+        let mut merged: Option<ClassifiedDependency> = None;
+        let mut leftovers = Vec::new();
+
+        for dep in group {
+            let Some(target) = merged.as_mut() else {
+                merged = Some(dep);
+                continue;
+            };
+
+            let conflicts = dep.classifications.iter().any(|(classification, version)| {
+                target
+                    .get_version(*classification)
+                    .is_some_and(|existing| existing != version)
+            });
+            if conflicts {
+                leftovers.push(dep);
+                continue;
+            }
+
+            for (classification, version) in &dep.classifications {
+                if target.has_classification(*classification) {
+                    continue;
+                }
+                let source = dep
+                    .get_source_file(*classification)
+                    .cloned()
+                    .unwrap_or_default();
+                target.add_classification(*classification, version.clone(), source);
+                if let Some(span) = dep.source_spans.get(classification) {
+                    target.source_spans.insert(*classification, *span);
+                }
+            }
+            if target.installed_path.is_none() {
+                target.installed_path = dep.installed_path.clone();
+                target.package_name_path = dep.package_name_path.clone();
+                target.installed_integrity = dep.installed_integrity.clone();
+                target.modified_files = dep.modified_files.clone();
+                target.install_scripts = dep.install_scripts.clone();
+                target.native_extensions = dep.native_extensions.clone();
+                target.entry_points = dep.entry_points.clone();
+                target.license = dep.license.clone();
+            }
+            if target.resolved.is_none() {
+                target.resolved = dep.resolved.clone();
+            }
+            if target.integrity.is_none() {
+                target.integrity = dep.integrity.clone();
+            }
+            if target.alias.is_none() {
+                target.alias = dep.alias.clone();
+            }
+            if target.provenance.is_none() {
+                target.provenance = dep.provenance.clone();
+            }
+            if target.source_kind.is_registry() {
+                target.source_kind = dep.source_kind.clone();
+            }
+            if target.parent_package.is_none() {
+                target.parent_package = dep.parent_package.clone();
+            }
+            for child in &dep.dependencies {
+                if !target.dependencies.contains(child) {
+                    target.dependencies.push(child.clone());
+                }
+            }
+        }
+
+        merged.into_iter().chain(leftovers).collect()
+    }
+}
+
+impl Default for Aggregator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Path depth of an entry's primary source (installed path, lockfile or manifest)
+fn entry_depth(dep: &ClassifiedDependency) -> usize {
+    dep.installed_path
+        .as_ref()
+        .or_else(|| {
+            dep.primary_classification()
+                .and_then(|c| dep.get_source_file(c))
+        })
+        .map(|path| path.components().count())
+        .unwrap_or(usize::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Classification;
+
+    fn entry(
+        name: &str,
+        classification: Classification,
+        version: &str,
+        source: &str,
+    ) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new(name.to_string(), Ecosystem::Node);
+        dep.add_classification(classification, version.to_string(), PathBuf::from(source));
+        if classification == Classification::Has {
+            dep.installed_path = Some(PathBuf::from(source));
+        }
+        dep.application_root = Some(PathBuf::from("/app"));
+        dep
+    }
+
+    #[test]
+    fn test_merges_classifications_per_application() {
+        let mut other_app = entry(
+            "react",
+            Classification::Can,
+            "^17.0.0",
+            "/other/package.json",
+        );
+        other_app.application_root = Some(PathBuf::from("/other"));
+        let deps = vec![
+            entry(
+                "react",
+                Classification::Has,
+                "18.2.0",
+                "/app/node_modules/react",
+            ),
+            entry(
+                "react",
+                Classification::Should,
+                "18.1.0",
+                "/app/package-lock.json",
+            ),
+            entry("react", Classification::Can, "^18.0.0", "/app/package.json"),
+            other_app,
+        ];
+
+        let aggregated = Aggregator::new().aggregate(deps);
+
+        assert_eq!(aggregated.len(), 2);
+        let react = &aggregated[0];
+        assert_eq!(react.get_version(Classification::Has), Some("18.2.0"));
+        assert_eq!(react.get_version(Classification::Should), Some("18.1.0"));
+        assert_eq!(react.get_version(Classification::Can), Some("^18.0.0"));
+        assert_eq!(
+            react.get_source_file(Classification::Can),
+            Some(&PathBuf::from("/app/package.json"))
+        );
+        assert_eq!(
+            react.installed_path,
+            Some(PathBuf::from("/app/node_modules/react"))
+        );
+        assert_eq!(
+            aggregated[1].application_root,
+            Some(PathBuf::from("/other"))
+        );
+    }
+
+    #[test]
+    fn test_keeps_other_versions_and_drops_duplicates() {
+        let deps = vec![
+            entry(
+                "ms",
+                Classification::Has,
+                "2.0.0",
+                "/app/node_modules/debug/node_modules/ms",
+            ),
+            entry("ms", Classification::Has, "2.1.3", "/app/node_modules/ms"),
+            entry("ms", Classification::Can, "^2.1.0", "/app/package.json"),
+            entry("ms", Classification::Can, "^2.1.0", "/app/package.json"),
+        ];
+
+        let aggregated = Aggregator::new().aggregate(deps);
+
+        assert_eq!(aggregated.len(), 2);
+        // The hoisted copy is merged with the manifest range
+        assert_eq!(
+            aggregated[0].get_version(Classification::Has),
+            Some("2.1.3")
+        );
+        assert_eq!(
+            aggregated[0].get_version(Classification::Can),
+            Some("^2.1.0")
+        );
+        assert_eq!(
+            aggregated[1].get_version(Classification::Has),
+            Some("2.0.0")
+        );
+        assert!(!aggregated[1].has_classification(Classification::Can));
+    }
+
+    #[test]
+    fn test_entries_without_application_are_kept() {
+        let mut dep = entry("left-pad", Classification::Can, "1.0.0", "/x/package.json");
+        dep.application_root = None;
+
+        let aggregated = Aggregator::new().aggregate(vec![dep.clone(), dep]);
+
+        assert_eq!(aggregated.len(), 2);
+    }
+}
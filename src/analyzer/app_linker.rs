@@ -1,11 +1,17 @@
 //! Application linker for associating dependencies with their declaring applications
 //!
 //! This module finds the nearest manifest file (application root) for each
-//! installed package and links them together.
+//! installed package and links them together. Scans without installed
+//! packages create applications from the discovered manifests instead.
+//!
+//! Applications nested inside another of the same ecosystem (packages of a
+//! monorepo) are linked to it: each records its `parent` and `children`, and
+//! each dependency the roots of the applications enclosing its own.
 
-use crate::models::{Application, ClassifiedDependency, Ecosystem};
+use crate::indexer::DiscoveredFile;
+use crate::models::{Application, Classification, ClassifiedDependency, Ecosystem, FileType};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -42,8 +48,8 @@ impl ApplicationLinker {
             }
         }
 
-        // Group dependencies by application root
-        let mut apps: HashMap<PathBuf, Application> = HashMap::new();
+        // Group dependencies by application root, in root path order
+        let mut apps: BTreeMap<PathBuf, Application> = BTreeMap::new();
 
         for dep in dependencies {
             if let Some(root_path) = &dep.application_root {
@@ -64,7 +70,176 @@ impl ApplicationLinker {
             }
         }
 
-        apps.into_values().collect()
+        let mut apps: Vec<Application> = apps
+            .into_values()
+            .map(|mut app| {
+                app.sort_dependencies();
+                app
+            })
+            .collect();
+        self.link_hierarchy(&mut apps);
+        apps
+    }
+
+    /// Create an application for every directory with a manifest and attach
+    /// each dependency to the nearest one of its ecosystem
+    ///
+    /// Installed entries are placed by their installed path and locked or
+    /// declared entries by the lockfile or manifest they came from, so
+    /// declared-only scans and Rust projects still produce applications.
+    /// Applications without dependencies are kept.
+    pub fn link_from_manifests(
+        &self,
+        files: &[DiscoveredFile],
+        dependencies: Vec<ClassifiedDependency>,
+    ) -> Vec<Application> {
+        self.link_from_files(
+            files.iter().filter(|f| f.file_type == FileType::Manifest),
+            dependencies,
+        )
+    }
+
+    /// Create an application for every directory with a lockfile and attach
+    /// each dependency to the nearest one of its ecosystem
+    ///
+    /// Used by lockfiles-only scans, which never collect manifests; the
+    /// application is still named after the manifest next to the lockfile.
+    pub fn link_from_lockfiles(
+        &self,
+        files: &[DiscoveredFile],
+        dependencies: Vec<ClassifiedDependency>,
+    ) -> Vec<Application> {
+        self.link_from_files(
+            files.iter().filter(|f| f.file_type == FileType::Lockfile),
+            dependencies,
+        )
+    }
+
+    /// Create an application for the directory of every given file and
+    /// attach each dependency to the nearest one of its ecosystem
+    fn link_from_files<'a>(
+        &self,
+        files: impl Iterator<Item = &'a DiscoveredFile>,
+        dependencies: Vec<ClassifiedDependency>,
+    ) -> Vec<Application> {
+        let mut apps: BTreeMap<(PathBuf, Ecosystem), Application> = BTreeMap::new();
+        for file in files {
+            let key = (file.directory.clone(), file.ecosystem);
+            apps.entry(key).or_insert_with(|| {
+                let name = self
+                    .manifest_name(&file.directory, file.ecosystem)
+                    .or_else(|| {
+                        let dir_name = file.directory.file_name()?;
+                        Some(dir_name.to_string_lossy().to_string())
+                    })
+                    .unwrap_or_else(|| "unknown".to_string());
+                let manifest_path = self
+                    .find_manifest_file(&file.directory, file.ecosystem)
+                    .unwrap_or_else(|| file.path.clone());
+                Application::new(name, file.directory.clone(), manifest_path, file.ecosystem)
+            });
+        }
+
+        for mut dep in dependencies {
+            let path = dep
+                .installed_path
+                .as_ref()
+                .or_else(|| dep.get_source_file(Classification::Should))
+                .or_else(|| dep.get_source_file(Classification::Can))
+                .cloned();
+            let key = path.and_then(|path| {
+                path.ancestors()
+                    .skip(1)
+                    .map(|dir| (dir.to_path_buf(), dep.ecosystem))
+                    .find(|key| apps.contains_key(key))
+            });
+            let Some(app) = key.and_then(|key| apps.get_mut(&key)) else {
+                continue;
+            };
+            dep.application_root = Some(app.root_path.clone());
+            dep.application_name = Some(app.name.clone());
+            app.add_dependency(dep);
+        }
+
+        let mut apps: Vec<Application> = apps
+            .into_values()
+            .map(|mut app| {
+                app.sort_dependencies();
+                app
+            })
+            .collect();
+        self.link_hierarchy(&mut apps);
+        apps
+    }
+
+    /// Link each application to the nearest enclosing one of its ecosystem,
+    /// and record on every dependency the roots of the applications
+    /// enclosing its own
+    pub fn link_hierarchy(&self, applications: &mut [Application]) {
+        let roots: HashMap<(PathBuf, Ecosystem), usize> = applications
+            .iter()
+            .enumerate()
+            .map(|(index, app)| ((app.root_path.clone(), app.ecosystem), index))
+            .collect();
+        let parents: Vec<Option<usize>> = applications
+            .iter()
+            .map(|app| {
+                app.root_path
+                    .ancestors()
+                    .skip(1)
+                    .find_map(|dir| roots.get(&(dir.to_path_buf(), app.ecosystem)).copied())
+            })
+            .collect();
+
+        for app in applications.iter_mut() {
+            app.parent = None;
+            app.children.clear();
+        }
+        for (index, parent) in parents.iter().enumerate() {
+            if let Some(parent) = *parent {
+                let root = applications[index].root_path.clone();
+                applications[index].parent = Some(applications[parent].root_path.clone());
+                applications[parent].children.push(root);
+            }
+        }
+
+        for index in 0..applications.len() {
+            let mut chain = Vec::new();
+            let mut next = parents[index];
+            while let Some(parent) = next {
+                chain.push(applications[parent].root_path.clone());
+                next = parents[parent];
+            }
+            for dep in &mut applications[index].dependencies {
+                dep.enclosing_applications = chain.clone();
+            }
+        }
+    }
+
+    /// Set the application root and name of each dependency
+    ///
+    /// Installed entries are resolved from their installed path and declared
+    /// entries from the lockfile or manifest they came from.
+    pub fn assign_application_roots(&self, dependencies: &mut [ClassifiedDependency]) {
+        let mut manifest_cache: HashMap<PathBuf, Option<(PathBuf, String, Ecosystem)>> =
+            HashMap::new();
+
+        for dep in dependencies {
+            let path = dep
+                .installed_path
+                .as_ref()
+                .or_else(|| dep.get_source_file(Classification::Should))
+                .or_else(|| dep.get_source_file(Classification::Can))
+                .cloned();
+            if let Some(path) = path {
+                if let Some((root_path, app_name, _ecosystem)) =
+                    self.find_application_root(&path, &mut manifest_cache)
+                {
+                    dep.application_root = Some(root_path);
+                    dep.application_name = Some(app_name);
+                }
+            }
+        }
     }
 
     /// Find the application root for an installed package
@@ -115,6 +290,17 @@ impl ApplicationLinker {
         }
     }
 
+    /// Name declared by the manifest of an ecosystem in a directory
+    fn manifest_name(&self, dir: &Path, ecosystem: Ecosystem) -> Option<String> {
+        let found = match ecosystem {
+            Ecosystem::Node => self.check_node_manifest(dir),
+            Ecosystem::Python => self.check_python_manifest(dir),
+            Ecosystem::Rust => self.check_rust_manifest(dir),
+            Ecosystem::Os => None,
+        };
+        found.map(|(name, _)| name)
+    }
+
     /// Check for Node.js manifest (package.json)
     fn check_node_manifest(&self, dir: &Path) -> Option<(String, Ecosystem)> {
         let package_json = dir.join("package.json");
@@ -217,6 +403,8 @@ impl ApplicationLinker {
                     None
                 }
             }
+            // OS packages belong to the system, not to an application
+            Ecosystem::Os => None,
         }
     }
 }
@@ -395,4 +583,166 @@ version = "1.0.0"
         // Should return empty since no application root was found
         assert_eq!(apps.len(), 0);
     }
+
+    #[test]
+    fn test_assign_application_roots() {
+        let temp_dir = TempDir::new().unwrap();
+        let app_root = temp_dir.path().join("myapp");
+        fs::create_dir_all(app_root.join("node_modules/react")).unwrap();
+        fs::write(
+            app_root.join("package.json"),
+            r#"{"name": "myapp", "version": "1.0.0"}"#,
+        )
+        .unwrap();
+
+        let mut installed = ClassifiedDependency::new("react".to_string(), Ecosystem::Node);
+        installed.installed_path = Some(app_root.join("node_modules/react"));
+        let mut declared = ClassifiedDependency::new("react".to_string(), Ecosystem::Node);
+        declared.add_classification(
+            Classification::Can,
+            "^18.0.0".to_string(),
+            app_root.join("package.json"),
+        );
+        let mut deps = vec![installed, declared];
+
+        ApplicationLinker::new().assign_application_roots(&mut deps);
+
+        for dep in &deps {
+            assert_eq!(dep.application_root.as_ref(), Some(&app_root));
+            assert_eq!(dep.application_name.as_deref(), Some("myapp"));
+        }
+    }
+
+    #[test]
+    fn test_link_from_manifests() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for (file, content) in [
+            ("Cargo.toml", "[workspace]\nmembers = [\"crates/core\"]\n"),
+            ("Cargo.lock", ""),
+            (
+                "crates/core/Cargo.toml",
+                "[package]\nname = \"core\"\n\n[dependencies]\nserde = \"1\"\n",
+            ),
+            ("api/requirements.txt", "flask==3.0.0\n"),
+        ] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        let index = crate::indexer::index_tree(
+            root,
+            &[],
+            crate::indexer::ScanMode::DeclaredOnly,
+            false,
+            &crate::indexer::WalkOptions::default(),
+        );
+
+        let declared = |name: &str, ecosystem, classification, file: &str| {
+            let mut dep = ClassifiedDependency::new(name.to_string(), ecosystem);
+            dep.add_classification(classification, "1.0.0".to_string(), root.join(file));
+            dep
+        };
+        let deps = vec![
+            declared(
+                "serde",
+                Ecosystem::Rust,
+                Classification::Should,
+                "Cargo.lock",
+            ),
+            declared(
+                "serde",
+                Ecosystem::Rust,
+                Classification::Can,
+                "crates/core/Cargo.toml",
+            ),
+            declared(
+                "flask",
+                Ecosystem::Python,
+                Classification::Can,
+                "api/requirements.txt",
+            ),
+            declared(
+                "orphan",
+                Ecosystem::Node,
+                Classification::Can,
+                "package.json",
+            ),
+        ];
+
+        let apps = ApplicationLinker::new().link_from_manifests(&index.files, deps);
+
+        let names: Vec<_> = apps.iter().map(|a| a.name.as_str()).collect();
+        let workspace_name = root.file_name().unwrap().to_str().unwrap();
+        assert_eq!(names, vec![workspace_name, "api", "core"]);
+        assert_eq!(apps[0].ecosystem, Ecosystem::Rust);
+        assert!(apps[0].has_dependency("serde"));
+        assert_eq!(apps[0].manifest_path, root.join("Cargo.toml"));
+        assert_eq!(apps[1].ecosystem, Ecosystem::Python);
+        assert_eq!(apps[1].manifest_path, root.join("api/requirements.txt"));
+        let flask = apps[1].find_dependency("flask").unwrap();
+        assert_eq!(flask.application_name.as_deref(), Some("api"));
+        assert_eq!(
+            apps[2].get_dependencies()[0].get_version(Classification::Can),
+            Some("1.0.0")
+        );
+        assert_eq!(apps[2].root_path, root.join("crates/core"));
+    }
+
+    #[test]
+    fn test_link_nested_applications() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("monorepo");
+        for (dir, name) in [
+            ("", "monorepo"),
+            ("packages/web", "web"),
+            ("packages/web/plugins/auth", "auth"),
+            ("packages/api", "api"),
+        ] {
+            fs::create_dir_all(root.join(dir).join("node_modules/lodash")).unwrap();
+            fs::write(
+                root.join(dir).join("package.json"),
+                format!(r#"{{"name": "{}", "version": "1.0.0"}}"#, name),
+            )
+            .unwrap();
+        }
+
+        let deps = [
+            "",
+            "packages/web",
+            "packages/web/plugins/auth",
+            "packages/api",
+        ]
+        .iter()
+        .map(|dir| {
+            let mut dep = ClassifiedDependency::new("lodash".to_string(), Ecosystem::Node);
+            dep.installed_path = Some(root.join(dir).join("node_modules/lodash"));
+            dep
+        })
+        .collect();
+        let apps = ApplicationLinker::new().link_to_applications(deps);
+
+        let names: Vec<_> = apps.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["monorepo", "api", "web", "auth"]);
+        assert_eq!(apps[0].parent, None);
+        assert_eq!(
+            apps[0].children,
+            vec![root.join("packages/api"), root.join("packages/web")]
+        );
+        assert_eq!(apps[2].parent, Some(root.clone()));
+        assert_eq!(
+            apps[2].children,
+            vec![root.join("packages/web/plugins/auth")]
+        );
+        assert_eq!(apps[3].parent, Some(root.join("packages/web")));
+
+        // Dependencies keep the nearest application and record the chain
+        let auth_dep = &apps[3].dependencies[0];
+        assert_eq!(auth_dep.application_name.as_deref(), Some("auth"));
+        assert_eq!(
+            auth_dep.enclosing_applications,
+            vec![root.join("packages/web"), root.clone()]
+        );
+        assert!(apps[0].dependencies[0].enclosing_applications.is_empty());
+    }
 }
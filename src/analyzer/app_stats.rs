@@ -0,0 +1,407 @@
+//! Dependency counts and duplication per application
+//!
+//! The [`AppStatsAnalyzer`] sums up each application for dedupe work:
+//!
+//! - how many distinct packages it pulls in, and how many of them it declares
+//!   directly (the roots of its [`DependencyGraph`](crate::analyzer::DependencyGraph))
+//! - packages present at several versions, with where each version is
+//!   installed or locked
+//! - the direct dependencies with the largest transitive closure
+//!
+//! Duplicates are counted among installed (HAS) versions when the package is
+//! installed and among locked (SHOULD) versions otherwise, so an installed
+//! copy that differs from the lockfile is a version mismatch, not a duplicate.
+//!
+//! Packages installed below another package (`node_modules/a/node_modules/b`)
+//! are linked to that package as an application of its own; see
+//! [`install_trees`] for how they are folded back into the application whose
+//! `node_modules` they live in.
+
+use crate::analyzer::GraphBuilder;
+use crate::models::{Application, Classification, ClassifiedDependency, Ecosystem};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Number of heaviest subtrees reported per application by default
+const DEFAULT_MAX_SUBTREES: usize = 10;
+
+/// One version of a duplicated package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct DuplicateVersion {
+    /// Installed or locked version
+    pub version: String,
+
+    /// Installation directories, or lockfiles when not installed
+    pub locations: Vec<PathBuf>,
+}
+
+/// A package present at several versions within one application
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct DuplicatePackage {
+    /// Package name
+    pub name: String,
+
+    /// Versions, in version string order
+    pub versions: Vec<DuplicateVersion>,
+}
+
+/// A direct dependency and the packages it pulls in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Subtree {
+    /// Package name
+    pub name: String,
+
+    /// Installed, locked or declared version
+    pub version: Option<String>,
+
+    /// Number of packages it depends on, directly or transitively
+    pub size: usize,
+}
+
+/// Dependency counts and duplication of one application
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ApplicationStats {
+    /// Application name
+    pub application_name: String,
+
+    /// Application root directory
+    pub application_root: PathBuf,
+
+    /// Ecosystem
+    pub ecosystem: Ecosystem,
+
+    /// Distinct packages (by name)
+    pub total: usize,
+
+    /// Packages the application depends on directly
+    pub direct: usize,
+
+    /// Packages only pulled in by other packages
+    pub transitive: usize,
+
+    /// Packages present at several versions
+    pub duplicates: Vec<DuplicatePackage>,
+
+    /// Direct dependencies with the most transitive packages, largest first
+    pub heaviest_subtrees: Vec<Subtree>,
+}
+
+/// Analyzer computing [`ApplicationStats`]
+pub struct AppStatsAnalyzer {
+    max_subtrees: usize,
+}
+
+impl AppStatsAnalyzer {
+    /// Create an analyzer reporting the ten heaviest subtrees
+    pub fn new() -> Self {
+        Self {
+            max_subtrees: DEFAULT_MAX_SUBTREES,
+        }
+    }
+
+    /// Report at most this many subtrees per application
+    pub fn with_max_subtrees(mut self, max_subtrees: usize) -> Self {
+        self.max_subtrees = max_subtrees;
+        self
+    }
+
+    /// Compute the stats of every application with its install tree, in
+    /// application order
+    pub fn analyze(&self, applications: &[Application]) -> Vec<ApplicationStats> {
+        install_trees(applications)
+            .into_iter()
+            .map(|(app, dependencies)| self.analyze_dependencies(app, &dependencies))
+            .collect()
+    }
+
+    /// Compute the stats of one application from its own dependencies
+    pub fn analyze_one(&self, application: &Application) -> ApplicationStats {
+        self.analyze_dependencies(application, &application.dependencies)
+    }
+
+    fn analyze_dependencies(
+        &self,
+        application: &Application,
+        dependencies: &[ClassifiedDependency],
+    ) -> ApplicationStats {
+        let graph = GraphBuilder::new()
+            .build(dependencies)
+            .into_iter()
+            .find(|graph| graph.ecosystem == application.ecosystem);
+
+        let (total, direct, mut heaviest_subtrees) = match &graph {
+            Some(graph) => {
+                let roots = graph.roots();
+                let subtrees: Vec<Subtree> = roots
+                    .iter()
+                    .map(|root| Subtree {
+                        name: root.name.clone(),
+                        version: root.version.clone(),
+                        size: graph.descendants(&root.name).len(),
+                    })
+                    .filter(|subtree| subtree.size > 0)
+                    .collect();
+                (graph.len(), roots.len(), subtrees)
+            }
+            None => (0, 0, Vec::new()),
+        };
+        heaviest_subtrees.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+        heaviest_subtrees.truncate(self.max_subtrees);
+
+        ApplicationStats {
+            application_name: application.name.clone(),
+            application_root: application.root_path.clone(),
+            ecosystem: application.ecosystem,
+            total,
+            direct,
+            transitive: total - direct,
+            duplicates: find_duplicates(dependencies),
+            heaviest_subtrees,
+        }
+    }
+}
+
+impl Default for AppStatsAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Applications with the dependencies of their whole install tree
+///
+/// An application rooted inside a `node_modules` directory is an installed
+/// package; its dependencies are added to the application of the same
+/// ecosystem owning the outermost `node_modules` (with their application
+/// root rewritten to it) and it is not listed itself. Installed packages
+/// without such an owner stay applications of their own.
+pub fn install_trees(
+    applications: &[Application],
+) -> Vec<(&Application, Vec<ClassifiedDependency>)> {
+    let hosts: HashMap<(&Path, Ecosystem), usize> = applications
+        .iter()
+        .enumerate()
+        .filter(|(_, app)| install_owner(&app.root_path).is_none())
+        .map(|(i, app)| ((app.root_path.as_path(), app.ecosystem), i))
+        .collect();
+
+    let mut trees: Vec<Option<(&Application, Vec<ClassifiedDependency>)>> =
+        vec![None; applications.len()];
+    for (i, app) in applications.iter().enumerate() {
+        let host = install_owner(&app.root_path)
+            .and_then(|owner| hosts.get(&(owner, app.ecosystem)).copied())
+            .unwrap_or(i);
+        let host_app = &applications[host];
+        let tree = trees[host].get_or_insert_with(|| (host_app, Vec::new()));
+        tree.1
+            .extend(app.dependencies.iter().cloned().map(|mut dep| {
+                dep.application_root = Some(host_app.root_path.clone());
+                dep
+            }));
+    }
+    trees.into_iter().flatten().collect()
+}
+
+/// Directory containing the outermost `node_modules` of a path, if any
+fn install_owner(path: &Path) -> Option<&Path> {
+    path.ancestors()
+        .filter(|ancestor| {
+            ancestor
+                .file_name()
+                .is_some_and(|name| name == "node_modules")
+        })
+        .last()
+        .and_then(Path::parent)
+}
+
+/// Installed and locked versions of one package with their locations
+#[derive(Default)]
+struct Versions {
+    installed: BTreeMap<String, BTreeSet<PathBuf>>,
+    locked: BTreeMap<String, BTreeSet<PathBuf>>,
+}
+
+/// Packages with several installed versions, or several locked versions
+/// when not installed
+fn find_duplicates(dependencies: &[ClassifiedDependency]) -> Vec<DuplicatePackage> {
+    let mut packages: BTreeMap<&str, Versions> = BTreeMap::new();
+    for dep in dependencies {
+        let versions = packages.entry(&dep.name).or_default();
+        if let Some(version) = dep.get_version(Classification::Has) {
+            let location = dep
+                .installed_path
+                .as_ref()
+                .or_else(|| dep.get_source_file(Classification::Has));
+            let locations = versions.installed.entry(version.to_string()).or_default();
+            locations.extend(location.cloned());
+        }
+        if let Some(version) = dep.get_version(Classification::Should) {
+            let locations = versions.locked.entry(version.to_string()).or_default();
+            locations.extend(dep.get_source_file(Classification::Should).cloned());
+        }
+    }
+
+    packages
+        .into_iter()
+        .filter_map(|(name, versions)| {
+            let versions = if versions.installed.is_empty() {
+                versions.locked
+            } else {
+                versions.installed
+            };
+            (versions.len() > 1).then(|| DuplicatePackage {
+                name: name.to_string(),
+                versions: versions
+                    .into_iter()
+                    .map(|(version, locations)| DuplicateVersion {
+                        version,
+                        locations: locations.into_iter().collect(),
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn installed(name: &str, version: &str, path: &str, children: &[&str]) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new(name.to_string(), Ecosystem::Node);
+        dep.add_classification(
+            Classification::Has,
+            version.to_string(),
+            PathBuf::from(format!("/app/{}/package.json", path)),
+        );
+        dep.installed_path = Some(PathBuf::from(format!("/app/{}", path)));
+        dep.application_root = Some(PathBuf::from("/app"));
+        dep.dependencies = children.iter().map(|c| c.to_string()).collect();
+        dep
+    }
+
+    fn declared(name: &str) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new(name.to_string(), Ecosystem::Node);
+        dep.add_classification(
+            Classification::Can,
+            "*".to_string(),
+            PathBuf::from("/app/package.json"),
+        );
+        dep.application_root = Some(PathBuf::from("/app"));
+        dep
+    }
+
+    #[test]
+    fn test_application_stats() {
+        let mut app = Application::new(
+            "web".to_string(),
+            PathBuf::from("/app"),
+            PathBuf::from("/app/package.json"),
+            Ecosystem::Node,
+        );
+        for dep in [
+            declared("express"),
+            declared("lodash"),
+            installed(
+                "express",
+                "4.18.2",
+                "node_modules/express",
+                &["body-parser", "qs"],
+            ),
+            installed("body-parser", "1.20.1", "node_modules/body-parser", &["qs"]),
+            installed("qs", "6.11.0", "node_modules/qs", &[]),
+            installed(
+                "qs",
+                "6.5.3",
+                "node_modules/body-parser/node_modules/qs",
+                &[],
+            ),
+            installed("lodash", "4.17.21", "node_modules/lodash", &[]),
+        ] {
+            app.add_dependency(dep);
+        }
+
+        let stats = AppStatsAnalyzer::new().analyze_one(&app);
+
+        assert_eq!(stats.application_name, "web");
+        assert_eq!(stats.total, 4);
+        assert_eq!(stats.direct, 2);
+        assert_eq!(stats.transitive, 2);
+        assert_eq!(stats.duplicates.len(), 1);
+        let qs = &stats.duplicates[0];
+        assert_eq!(qs.name, "qs");
+        assert_eq!(qs.versions[0].version, "6.11.0");
+        assert_eq!(
+            qs.versions[1].locations,
+            vec![PathBuf::from(
+                "/app/node_modules/body-parser/node_modules/qs"
+            )]
+        );
+        assert_eq!(
+            stats.heaviest_subtrees,
+            vec![Subtree {
+                name: "express".to_string(),
+                version: Some("4.18.2".to_string()),
+                size: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_nested_packages_fold_into_application() {
+        let application = |name: &str, root: &str, deps: Vec<ClassifiedDependency>| {
+            let mut app = Application::new(
+                name.to_string(),
+                PathBuf::from(root),
+                PathBuf::from(format!("{}/package.json", root)),
+                Ecosystem::Node,
+            );
+            for mut dep in deps {
+                dep.application_root = Some(PathBuf::from(root));
+                app.add_dependency(dep);
+            }
+            app
+        };
+        let apps = vec![
+            application(
+                "web",
+                "/app",
+                vec![installed("qs", "6.11.0", "node_modules/qs", &[])],
+            ),
+            application(
+                "express",
+                "/app/node_modules/express",
+                vec![installed(
+                    "qs",
+                    "6.5.3",
+                    "node_modules/express/node_modules/qs",
+                    &[],
+                )],
+            ),
+            application("tool", "/tools/node_modules/tool", Vec::new()),
+        ];
+
+        let stats = AppStatsAnalyzer::new().analyze(&apps);
+
+        let names: Vec<&str> = stats.iter().map(|s| s.application_name.as_str()).collect();
+        assert_eq!(names, vec!["web", "tool"]);
+        assert_eq!(stats[0].total, 1);
+        assert_eq!(stats[0].duplicates.len(), 1);
+        assert_eq!(stats[0].duplicates[0].versions.len(), 2);
+    }
+
+    #[test]
+    fn test_mismatch_is_not_a_duplicate() {
+        let mut locked = ClassifiedDependency::new("qs".to_string(), Ecosystem::Node);
+        locked.add_classification(
+            Classification::Should,
+            "6.11.1".to_string(),
+            PathBuf::from("/app/package-lock.json"),
+        );
+        locked.application_root = Some(PathBuf::from("/app"));
+        let deps = vec![installed("qs", "6.11.0", "node_modules/qs", &[]), locked];
+
+        assert!(find_duplicates(&deps).is_empty());
+    }
+}
@@ -0,0 +1,404 @@
+//! Bundled and vendored copies of packages
+//!
+//! A package copied into a repository or built into a bundle is installed by
+//! no package manager: no manifest declares it and no lockfile pins it, so the
+//! classified results never show it. The [`BundleDetector`] walks the scanned
+//! trees for such copies:
+//!
+//! - `vendor_dir`: packages in a `vendor`, `vendored`, `third_party` or
+//!   `third-party` directory, named and versioned by their own `package.json`,
+//!   `pyproject.toml`, `Cargo.toml`, `PKG-INFO` or `__version__`
+//! - `python_vendor`: packages in the `_vendor` directory of a Python package
+//!   (pip, setuptools), versioned by its `vendor.txt` when present
+//! - `js_banner`: libraries built into a JavaScript bundle (`dist/`,
+//!   `build/`, `*.min.js`, ...), found from their license banners such as
+//!   `/*! jQuery v3.6.0 | (c) OpenJS Foundation */`
+//!
+//! `node_modules` directories are not walked, as the packages there bundle
+//! their own dependencies by design. Directories without a recognizable
+//! package are skipped, and a banner needs a `v`-prefixed version to count.
+
+use crate::models::Ecosystem;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Directories never walked
+const SKIPPED_DIRS: &[&str] = &[".git", "node_modules", "__pycache__", "target", ".nx"];
+
+/// Directories holding vendored copies of packages
+const VENDOR_DIRS: &[&str] = &["vendor", "vendored", "third_party", "third-party"];
+
+/// Directories holding built JavaScript bundles
+const BUNDLE_DIRS: &[&str] = &["dist", "build", "public", "static", "assets"];
+
+/// Most files and directories visited per root
+const MAX_ENTRIES: usize = 200_000;
+
+/// Larger bundles are not read
+const MAX_BUNDLE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// How a copy of a package was bundled
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleKind {
+    /// Copied into a vendor or third-party directory
+    VendorDir,
+    /// Vendored into a Python package's `_vendor` directory
+    PythonVendor,
+    /// Built into a JavaScript bundle
+    JsBanner,
+}
+
+impl fmt::Display for BundleKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BundleKind::VendorDir => write!(f, "vendor_dir"),
+            BundleKind::PythonVendor => write!(f, "python_vendor"),
+            BundleKind::JsBanner => write!(f, "js_banner"),
+        }
+    }
+}
+
+/// A copy of a package outside any package manager's control
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct BundledPackage {
+    /// Package name
+    pub name: String,
+
+    /// Version, if the copy records one
+    pub version: Option<String>,
+
+    /// Ecosystem
+    pub ecosystem: Ecosystem,
+
+    /// How it was bundled
+    pub kind: BundleKind,
+
+    /// Directory of the vendored copy, or the bundle holding it
+    pub path: PathBuf,
+}
+
+/// Detector of vendored and bundled copies of packages
+#[derive(Debug, Clone, Default)]
+pub struct BundleDetector;
+
+impl BundleDetector {
+    /// Create a new BundleDetector
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Find the bundled packages under the roots, ordered by path and name
+    pub fn detect(&self, roots: &[PathBuf]) -> Vec<BundledPackage> {
+        let mut bundled = Vec::new();
+        for root in roots {
+            let mut visited = 0;
+            let mut dirs = vec![root.clone()];
+            while let Some(dir) = dirs.pop() {
+                let Ok(entries) = fs::read_dir(&dir) else {
+                    continue;
+                };
+                for entry in entries.flatten() {
+                    visited += 1;
+                    if visited > MAX_ENTRIES {
+                        break;
+                    }
+                    let Ok(file_type) = entry.file_type() else {
+                        continue;
+                    };
+                    let name = entry.file_name();
+                    let name = name.to_string_lossy();
+                    let path = entry.path();
+                    if file_type.is_dir() {
+                        if SKIPPED_DIRS.contains(&name.as_ref()) {
+                            continue;
+                        }
+                        if name == "_vendor" {
+                            bundled.extend(vendored(&path, BundleKind::PythonVendor));
+                        } else if VENDOR_DIRS.contains(&name.as_ref()) {
+                            bundled.extend(vendored(&path, BundleKind::VendorDir));
+                        }
+                        dirs.push(path);
+                    } else if file_type.is_file() && is_bundle(root, &path) {
+                        bundled.extend(banners(&path));
+                    }
+                }
+            }
+        }
+        bundled.sort_by(|a, b| (&a.path, &a.name, &a.version).cmp(&(&b.path, &b.name, &b.version)));
+        bundled.dedup();
+        bundled
+    }
+}
+
+/// Packages of a vendor directory
+fn vendored(dir: &Path, kind: BundleKind) -> Vec<BundledPackage> {
+    // pip's `vendor.txt` pins the vendored versions like a requirements file
+    let pinned: HashMap<String, String> = fs::read_to_string(dir.join("vendor.txt"))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split('#').next()?.trim().split_once("=="))
+        .map(|(name, version)| (module_name(name), version.trim().to_string()))
+        .collect();
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut packages = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let identified = if path.is_dir() {
+            identify_dir(&path)
+        } else {
+            identify_module(&path)
+        };
+        let Some((name, mut version, ecosystem)) = identified else {
+            continue;
+        };
+        if version.is_none() {
+            version = pinned.get(&module_name(&name)).cloned();
+        }
+        packages.push(BundledPackage {
+            name,
+            version,
+            ecosystem,
+            kind,
+            path,
+        });
+    }
+    packages
+}
+
+/// Name, version and ecosystem of a package directory, from its manifest or
+/// its `__init__.py`
+fn identify_dir(dir: &Path) -> Option<(String, Option<String>, Ecosystem)> {
+    if let Some(manifest) = read_json(&dir.join("package.json")) {
+        let name = manifest["name"].as_str()?.to_string();
+        let version = manifest["version"].as_str().map(str::to_string);
+        return Some((name, version, Ecosystem::Node));
+    }
+    if let Some(cargo) = read_toml(&dir.join("Cargo.toml")) {
+        let package = cargo.get("package")?;
+        let name = package.get("name")?.as_str()?.to_string();
+        let version = package
+            .get("version")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        return Some((name, version, Ecosystem::Rust));
+    }
+    if let Some(project) = read_toml(&dir.join("pyproject.toml"))
+        .and_then(|pyproject| pyproject.get("project").cloned())
+    {
+        if let Some(name) = project.get("name").and_then(|n| n.as_str()) {
+            let version = project
+                .get("version")
+                .and_then(|v| v.as_str())
+                .map(str::to_string);
+            return Some((name.to_string(), version, Ecosystem::Python));
+        }
+    }
+    if let Ok(pkg_info) = fs::read_to_string(dir.join("PKG-INFO")) {
+        let field = |key: &str| {
+            pkg_info
+                .lines()
+                .find_map(|line| line.strip_prefix(key))
+                .map(|value| value.trim().to_string())
+        };
+        if let Some(name) = field("Name:") {
+            return Some((name, field("Version:"), Ecosystem::Python));
+        }
+    }
+    let init = dir.join("__init__.py");
+    if init.is_file() {
+        let name = dir.file_name()?.to_string_lossy().to_string();
+        let version = fs::read_to_string(&init)
+            .ok()
+            .and_then(|content| dunder_version(&content));
+        return Some((name, version, Ecosystem::Python));
+    }
+    None
+}
+
+/// Name and version of a single-file Python module (`six.py`)
+fn identify_module(path: &Path) -> Option<(String, Option<String>, Ecosystem)> {
+    if path.extension()? != "py" {
+        return None;
+    }
+    let name = path.file_stem()?.to_string_lossy().to_string();
+    if name.starts_with('_') {
+        return None;
+    }
+    let version = fs::read_to_string(path)
+        .ok()
+        .and_then(|content| dunder_version(&content));
+    Some((name, version, Ecosystem::Python))
+}
+
+/// `__version__ = "1.2.3"` of a Python module
+fn dunder_version(content: &str) -> Option<String> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| {
+        Regex::new(r#"(?m)^__version__\s*(?::\s*\w+\s*)?=\s*["']([^"']+)["']"#).unwrap()
+    });
+    pattern
+        .captures(content)
+        .map(|captures| captures[1].to_string())
+}
+
+/// Whether a file under a root is a built JavaScript bundle
+fn is_bundle(root: &Path, path: &Path) -> bool {
+    let Some(file_name) = path.file_name().map(|n| n.to_string_lossy()) else {
+        return false;
+    };
+    if !(file_name.ends_with(".js") || file_name.ends_with(".mjs") || file_name.ends_with(".cjs")) {
+        return false;
+    }
+    file_name.ends_with(".min.js")
+        || file_name.ends_with(".bundle.js")
+        || path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .components()
+            .any(|component| {
+                BUNDLE_DIRS.contains(&component.as_os_str().to_string_lossy().as_ref())
+            })
+}
+
+/// Libraries whose license banners a bundle carries
+fn banners(path: &Path) -> Vec<BundledPackage> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| {
+        // /*! jQuery v3.6.0 | ... */, /** @license React v17.0.2 ... */
+        Regex::new(r"/\*[*!]\s*(?:@license\s+)?(@?[A-Za-z][\w.-]*(?:/[\w.-]+)?)\s+v(\d+\.\d+\.\d+[\w.+-]*)")
+            .unwrap()
+    });
+    let too_large = fs::metadata(path).map_or(true, |m| m.len() > MAX_BUNDLE_BYTES);
+    let Some(content) = (!too_large).then(|| fs::read(path).ok()).flatten() else {
+        return Vec::new();
+    };
+    let content = String::from_utf8_lossy(&content);
+    pattern
+        .captures_iter(&content)
+        .map(|captures| BundledPackage {
+            name: banner_name(&captures[1]),
+            version: Some(captures[2].to_string()),
+            ecosystem: Ecosystem::Node,
+            kind: BundleKind::JsBanner,
+            path: path.to_path_buf(),
+        })
+        .collect()
+}
+
+/// npm name of a banner's library (`jQuery` → `jquery`, `Vue.js` → `vue`)
+fn banner_name(name: &str) -> String {
+    let name = name.to_lowercase();
+    name.strip_suffix(".js").unwrap_or(&name).to_string()
+}
+
+/// Name as Python imports and `vendor.txt` spell it
+fn module_name(name: &str) -> String {
+    name.trim().to_lowercase().replace(['-', '.'], "_")
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+fn read_toml(path: &Path) -> Option<toml::Value> {
+    toml::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_bundled() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let vendor = root.join("vendor");
+        fs::create_dir_all(vendor.join("left-pad")).unwrap();
+        fs::write(
+            vendor.join("left-pad/package.json"),
+            r#"{"name": "left-pad", "version": "1.3.0"}"#,
+        )
+        .unwrap();
+        fs::create_dir_all(vendor.join("docs")).unwrap();
+        fs::create_dir_all(root.join("third_party/libc")).unwrap();
+        fs::write(
+            root.join("third_party/libc/Cargo.toml"),
+            "[package]\nname = \"libc\"\nversion = \"0.2.150\"\n",
+        )
+        .unwrap();
+
+        let pip_vendor = root.join(".venv/lib/python3.12/site-packages/pip/_vendor");
+        fs::create_dir_all(pip_vendor.join("requests")).unwrap();
+        fs::write(pip_vendor.join("requests/__init__.py"), "import urllib3\n").unwrap();
+        fs::write(pip_vendor.join("six.py"), "__version__ = \"1.16.0\"\n").unwrap();
+        fs::write(
+            pip_vendor.join("vendor.txt"),
+            "requests==2.31.0\nsix==1.16.0\n",
+        )
+        .unwrap();
+
+        fs::create_dir_all(root.join("web/dist")).unwrap();
+        fs::write(
+            root.join("web/dist/app.js"),
+            "/*! jQuery v3.6.0 | (c) OpenJS Foundation */\n!function(){}\n\
+             /** @license React v17.0.2\n * react.production.min.js */\n",
+        )
+        .unwrap();
+        // Sources and installed packages are not bundles
+        fs::write(root.join("web/index.js"), "/*! jQuery v3.6.0 */").unwrap();
+        fs::create_dir_all(root.join("node_modules/x/vendor/y")).unwrap();
+        fs::write(
+            root.join("node_modules/x/vendor/y/package.json"),
+            r#"{"name": "y"}"#,
+        )
+        .unwrap();
+
+        let bundled = BundleDetector::new().detect(&[root.to_path_buf()]);
+        let found: Vec<(&str, Option<&str>, BundleKind)> = bundled
+            .iter()
+            .map(|b| (b.name.as_str(), b.version.as_deref(), b.kind))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("requests", Some("2.31.0"), BundleKind::PythonVendor),
+                ("six", Some("1.16.0"), BundleKind::PythonVendor),
+                ("libc", Some("0.2.150"), BundleKind::VendorDir),
+                ("left-pad", Some("1.3.0"), BundleKind::VendorDir),
+                ("jquery", Some("3.6.0"), BundleKind::JsBanner),
+                ("react", Some("17.0.2"), BundleKind::JsBanner),
+            ]
+        );
+        assert_eq!(bundled[0].ecosystem, Ecosystem::Python);
+        assert_eq!(bundled[2].ecosystem, Ecosystem::Rust);
+        assert_eq!(bundled[4].path, root.join("web/dist/app.js"));
+    }
+
+    #[test]
+    fn test_dunder_version() {
+        assert_eq!(
+            dunder_version("\"\"\"Doc\"\"\"\n__version__ = '2.0.1'\n"),
+            Some("2.0.1".to_string())
+        );
+        assert_eq!(
+            dunder_version("__version__: str = \"1.0\""),
+            Some("1.0".to_string())
+        );
+        assert_eq!(dunder_version("version = '1.0'"), None);
+    }
+}
@@ -9,9 +9,13 @@
 //! - **HAS**: From installed package parsers (node_modules, site-packages)
 //! - **SHOULD**: From lockfile parsers (package-lock.json, poetry.lock, etc.)
 //! - **CAN**: From manifest parsers (package.json, pyproject.toml, etc.)
+//!
+//! Packages reported by package providers are classified as the provider
+//! says (HAS or SHOULD) and keep the provider's name as their provenance.
 
 use crate::models::{
     Classification, ClassifiedDependency, DependencyRecord, FileType, InstalledPackage,
+    ProvidedPackage,
 };
 
 /// Classifier for assigning HAS/SHOULD/CAN classifications
@@ -40,6 +44,12 @@ impl Classifier {
             let mut dep = ClassifiedDependency::new(pkg.name.clone(), pkg.ecosystem);
             dep.add_classification(Classification::Has, pkg.version.clone(), pkg.path.clone());
             dep.installed_path = Some(pkg.path.clone());
+            dep.installed_integrity = pkg.integrity.clone();
+            dep.modified_files = pkg.modified_files.clone();
+            dep.install_scripts = pkg.install_scripts.clone();
+            dep.native_extensions = pkg.native_extensions.clone();
+            dep.entry_points = pkg.entry_points.clone();
+            dep.license = pkg.license.clone();
 
             // Set package_name_path from the installed path
             dep.package_name_path = Some(pkg.path.to_string_lossy().to_string());
@@ -55,25 +65,37 @@ impl Classifier {
         // Process dependency records (SHOULD and CAN classifications)
         // Each record gets its own entry
         for record in records {
-            let mut dep = ClassifiedDependency::new(record.name.clone(), record.ecosystem);
+            let mut dep = ClassifiedDependency::new(record.name.to_string(), record.ecosystem);
 
             // Set package_name_path from the source file
             dep.package_name_path = Some(record.source_file.to_string_lossy().to_string());
+            dep.alias = record.alias.clone();
+            let classification = match record.file_type {
+                FileType::Lockfile => Classification::Should,
+                FileType::Manifest => Classification::Can,
+            };
+            if let Some(span) = record.span {
+                dep.source_spans.insert(classification, span);
+            }
 
             match record.file_type {
                 FileType::Lockfile => {
                     dep.add_classification(
                         Classification::Should,
-                        record.version.clone(),
-                        record.source_file.clone(),
+                        record.version.to_string(),
+                        record.source_file.to_path_buf(),
                     );
+                    dep.resolved = record.resolved.clone();
+                    dep.integrity = record.integrity.clone();
+                    dep.dependencies = record.dependencies.iter().map(|d| d.to_string()).collect();
                 }
                 FileType::Manifest => {
                     dep.add_classification(
                         Classification::Can,
-                        record.version.clone(),
-                        record.source_file.clone(),
+                        record.version.to_string(),
+                        record.source_file.to_path_buf(),
                     );
+                    dep.source_kind = record.source_kind.clone();
                 }
             }
 
@@ -82,6 +104,27 @@ impl Classifier {
 
         results
     }
+
+    /// Classify packages reported by package providers, one entry each
+    pub fn classify_provided(&self, provided: Vec<ProvidedPackage>) -> Vec<ClassifiedDependency> {
+        provided
+            .into_iter()
+            .map(|pkg| {
+                let mut dep = ClassifiedDependency::new(pkg.name, pkg.ecosystem);
+                dep.add_classification(pkg.classification, pkg.version, pkg.location.clone());
+                dep.package_name_path = Some(pkg.location.to_string_lossy().to_string());
+                if pkg.classification == Classification::Has {
+                    dep.installed_path = Some(pkg.location);
+                    dep.installed_integrity = pkg.integrity;
+                } else {
+                    dep.integrity = pkg.integrity;
+                }
+                dep.dependencies = pkg.dependencies;
+                dep.provenance = Some(pkg.provenance);
+                dep
+            })
+            .collect()
+    }
 }
 
 impl Default for Classifier {
@@ -93,8 +136,8 @@ impl Default for Classifier {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{DependencyType, Ecosystem};
-    use std::path::PathBuf;
+    use crate::models::{DependencyType, Ecosystem, SourceKind};
+    use std::path::{Path, PathBuf};
 
     #[test]
     fn test_classify_has_only() {
@@ -125,12 +168,18 @@ mod tests {
         let classifier = Classifier::new();
 
         let records = vec![DependencyRecord {
-            name: "react".to_string(),
-            version: "18.2.0".to_string(),
-            source_file: PathBuf::from("/app/package-lock.json"),
+            name: "react".into(),
+            version: "18.2.0".into(),
+            source_file: Path::new("/app/package-lock.json").into(),
             dep_type: DependencyType::Runtime,
             ecosystem: Ecosystem::Node,
             file_type: FileType::Lockfile,
+            alias: None,
+            source_kind: SourceKind::Registry,
+            resolved: None,
+            integrity: None,
+            dependencies: Vec::new(),
+            span: None,
         }];
 
         let classified = classifier.classify(records, vec![]);
@@ -150,12 +199,18 @@ mod tests {
         let classifier = Classifier::new();
 
         let records = vec![DependencyRecord {
-            name: "react".to_string(),
-            version: "^18.0.0".to_string(),
-            source_file: PathBuf::from("/app/package.json"),
+            name: "react".into(),
+            version: "^18.0.0".into(),
+            source_file: Path::new("/app/package.json").into(),
             dep_type: DependencyType::Runtime,
             ecosystem: Ecosystem::Node,
             file_type: FileType::Manifest,
+            alias: None,
+            source_kind: SourceKind::Registry,
+            resolved: None,
+            integrity: None,
+            dependencies: Vec::new(),
+            span: None,
         }];
 
         let classified = classifier.classify(records, vec![]);
@@ -183,20 +238,32 @@ mod tests {
 
         let records = vec![
             DependencyRecord {
-                name: "react".to_string(),
-                version: "18.2.0".to_string(),
-                source_file: PathBuf::from("/app/package-lock.json"),
+                name: "react".into(),
+                version: "18.2.0".into(),
+                source_file: Path::new("/app/package-lock.json").into(),
                 dep_type: DependencyType::Runtime,
                 ecosystem: Ecosystem::Node,
                 file_type: FileType::Lockfile,
+                alias: None,
+                source_kind: SourceKind::Registry,
+                resolved: None,
+                integrity: None,
+                dependencies: Vec::new(),
+                span: None,
             },
             DependencyRecord {
-                name: "react".to_string(),
-                version: "^18.0.0".to_string(),
-                source_file: PathBuf::from("/app/package.json"),
+                name: "react".into(),
+                version: "^18.0.0".into(),
+                source_file: Path::new("/app/package.json").into(),
                 dep_type: DependencyType::Runtime,
                 ecosystem: Ecosystem::Node,
                 file_type: FileType::Manifest,
+                alias: None,
+                source_kind: SourceKind::Registry,
+                resolved: None,
+                integrity: None,
+                dependencies: Vec::new(),
+                span: None,
             },
         ];
 
@@ -342,4 +409,38 @@ mod tests {
         assert!(paths.contains(&&PathBuf::from("/app1/node_modules/react")));
         assert!(paths.contains(&&PathBuf::from("/app2/node_modules/react")));
     }
+
+    #[test]
+    fn test_classify_provided() {
+        let mut installed =
+            ProvidedPackage::installed("lodash", "4.17.21", Ecosystem::Node, "cache://npm/lodash");
+        installed.integrity = Some("sha512-abc".to_string());
+        installed.provenance = "artifact-cache".to_string();
+        let mut locked =
+            ProvidedPackage::locked("requests", "2.31.0", Ecosystem::Python, "nexus://pypi");
+        locked.provenance = "nexus".to_string();
+
+        let classified = Classifier::new().classify_provided(vec![installed, locked]);
+
+        assert_eq!(classified.len(), 2);
+        assert_eq!(
+            classified[0].get_version(Classification::Has),
+            Some("4.17.21")
+        );
+        assert_eq!(
+            classified[0].installed_path,
+            Some(PathBuf::from("cache://npm/lodash"))
+        );
+        assert_eq!(
+            classified[0].installed_integrity.as_deref(),
+            Some("sha512-abc")
+        );
+        assert_eq!(classified[0].provenance.as_deref(), Some("artifact-cache"));
+        assert_eq!(
+            classified[1].get_version(Classification::Should),
+            Some("2.31.0")
+        );
+        assert_eq!(classified[1].installed_path, None);
+        assert_eq!(classified[1].provenance.as_deref(), Some("nexus"));
+    }
 }
@@ -0,0 +1,218 @@
+//! Dependency confusion detection for internal packages
+//!
+//! Internal packages are published to a private registry. If a public
+//! registry hosts a package of the same name (typically with a higher
+//! version), package managers may install that one instead. The
+//! [`DependencyConfusionDetector`] takes the internal namespaces as globs
+//! (`@acme/*`, `acme-*`) and reports locked entries of matching packages whose
+//! resolved URL points at a public registry.
+//!
+//! Patterns without a `/` match the name or its part after an npm scope, like
+//! path globs match a path segment. PyPI names are normalized before matching.
+//! Entries without a resolved URL are skipped.
+
+use crate::indexer::Glob;
+use crate::models::purl::normalize_pypi_name;
+use crate::models::{Classification, ClassifiedDependency, Ecosystem, ScanError};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Public registry URLs per ecosystem, without scheme
+const PUBLIC_REGISTRIES: &[(Ecosystem, &str)] = &[
+    (Ecosystem::Node, "registry.npmjs.org/"),
+    (Ecosystem::Node, "registry.npmjs.com/"),
+    (Ecosystem::Node, "registry.yarnpkg.com/"),
+    (Ecosystem::Python, "pypi.org/"),
+    (Ecosystem::Python, "pypi.python.org/"),
+    (Ecosystem::Python, "files.pythonhosted.org/"),
+    (Ecosystem::Rust, "github.com/rust-lang/crates.io-index"),
+    (Ecosystem::Rust, "index.crates.io/"),
+    (Ecosystem::Rust, "static.crates.io/"),
+    (Ecosystem::Rust, "crates.io/"),
+];
+
+/// An internal package resolved from a public registry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfusionRisk {
+    /// Package name
+    pub name: String,
+
+    /// Ecosystem
+    pub ecosystem: Ecosystem,
+
+    /// Locked version
+    pub version: String,
+
+    /// Public URL the package was resolved from
+    pub resolved: String,
+
+    /// Lockfile the entry came from
+    pub source_file: PathBuf,
+
+    /// Internal namespace pattern the name matched
+    pub pattern: String,
+}
+
+/// Detector for internal packages resolved from public registries
+#[derive(Debug, Clone, Default)]
+pub struct DependencyConfusionDetector {
+    internal: Vec<Glob>,
+}
+
+impl DependencyConfusionDetector {
+    /// Create a detector without internal namespaces
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add internal namespace patterns
+    pub fn with_internal_patterns<S: AsRef<str>>(
+        mut self,
+        patterns: &[S],
+    ) -> Result<Self, ScanError> {
+        for pattern in patterns {
+            self.internal.push(Glob::new(pattern.as_ref())?);
+        }
+        Ok(self)
+    }
+
+    /// Check if any internal namespaces are configured
+    pub fn is_empty(&self) -> bool {
+        self.internal.is_empty()
+    }
+
+    /// Internal namespace pattern matching a package name
+    pub fn internal_pattern(&self, ecosystem: Ecosystem, name: &str) -> Option<&str> {
+        let name = match ecosystem {
+            Ecosystem::Python => normalize_pypi_name(name),
+            _ => name.to_string(),
+        };
+        self.internal
+            .iter()
+            .find(|glob| glob.is_match(&name))
+            .map(Glob::as_str)
+    }
+
+    /// Find locked internal packages resolved from a public registry
+    pub fn check(&self, dependencies: &[ClassifiedDependency]) -> Vec<ConfusionRisk> {
+        let mut risks = Vec::new();
+        for dep in dependencies {
+            let (Some(version), Some(resolved)) =
+                (dep.get_version(Classification::Should), &dep.resolved)
+            else {
+                continue;
+            };
+            if !is_public_registry(dep.ecosystem, resolved) {
+                continue;
+            }
+            if let Some(pattern) = self.internal_pattern(dep.ecosystem, &dep.name) {
+                risks.push(ConfusionRisk {
+                    name: dep.name.clone(),
+                    ecosystem: dep.ecosystem,
+                    version: version.to_string(),
+                    resolved: resolved.clone(),
+                    source_file: dep
+                        .get_source_file(Classification::Should)
+                        .cloned()
+                        .unwrap_or_default(),
+                    pattern: pattern.to_string(),
+                });
+            }
+        }
+        risks
+    }
+}
+
+/// Whether a resolved URL points at a public registry of the ecosystem
+///
+/// Handles Cargo's `registry+` and `sparse+` source prefixes.
+pub fn is_public_registry(ecosystem: Ecosystem, resolved: &str) -> bool {
+    let url = resolved
+        .trim_start_matches("registry+")
+        .trim_start_matches("sparse+");
+    let Some((_, location)) = url.split_once("://") else {
+        return false;
+    };
+    let location = location.to_lowercase();
+    PUBLIC_REGISTRIES
+        .iter()
+        .any(|(eco, prefix)| *eco == ecosystem && location.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locked(name: &str, ecosystem: Ecosystem, resolved: Option<&str>) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new(name.to_string(), ecosystem);
+        dep.add_classification(
+            Classification::Should,
+            "9.9.9".to_string(),
+            PathBuf::from("/app/lockfile"),
+        );
+        dep.resolved = resolved.map(str::to_string);
+        dep
+    }
+
+    #[test]
+    fn test_check() {
+        let detector = DependencyConfusionDetector::new()
+            .with_internal_patterns(&["@acme/*", "acme-*"])
+            .unwrap();
+        let deps = vec![
+            locked(
+                "@acme/ui",
+                Ecosystem::Node,
+                Some("https://registry.npmjs.org/@acme/ui/-/ui-9.9.9.tgz"),
+            ),
+            locked(
+                "@acme/core",
+                Ecosystem::Node,
+                Some("https://npm.acme.internal/@acme/core/-/core-1.0.0.tgz"),
+            ),
+            locked(
+                "Acme_Client",
+                Ecosystem::Python,
+                Some("https://files.pythonhosted.org/packages/acme_client-9.9.9.whl"),
+            ),
+            locked(
+                "acme-tools",
+                Ecosystem::Rust,
+                Some("registry+https://github.com/rust-lang/crates.io-index"),
+            ),
+            locked("acme-cli", Ecosystem::Node, None),
+            locked(
+                "lodash",
+                Ecosystem::Node,
+                Some("https://registry.npmjs.org/lodash/-/lodash-4.17.21.tgz"),
+            ),
+        ];
+
+        let risks = detector.check(&deps);
+
+        let names: Vec<_> = risks.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["@acme/ui", "Acme_Client", "acme-tools"]);
+        assert_eq!(risks[0].pattern, "@acme/*");
+        assert_eq!(risks[1].pattern, "acme-*");
+    }
+
+    #[test]
+    fn test_is_public_registry() {
+        assert!(is_public_registry(
+            Ecosystem::Rust,
+            "sparse+https://index.crates.io/"
+        ));
+        assert!(is_public_registry(
+            Ecosystem::Node,
+            "https://registry.yarnpkg.com/react/-/react-18.2.0.tgz"
+        ));
+        assert!(!is_public_registry(
+            Ecosystem::Python,
+            "https://registry.npmjs.org/react/-/react-18.2.0.tgz"
+        ));
+        assert!(!is_public_registry(
+            Ecosystem::Node,
+            "git+ssh://git@github.com/acme/ui.git"
+        ));
+    }
+}
@@ -0,0 +1,262 @@
+//! Cross-source version consistency per application
+//!
+//! Classified entries hold one finding each, so the per-entry mismatch flags
+//! only fire after aggregation. The [`ConsistencyChecker`] instead collects,
+//! per application and package, every installed (HAS), locked (SHOULD) and
+//! declared (CAN) version and reports a [`VersionInconsistency`] when:
+//!
+//! - an installed version is not among the locked versions
+//! - no locked version satisfies a declared range
+//! - without a lockfile, no installed version satisfies a declared range
+//!
+//! Entries need their application root assigned (see
+//! [`ApplicationLinker::assign_application_roots`](crate::analyzer::ApplicationLinker::assign_application_roots));
+//! entries without one are skipped.
+
+use crate::analyzer::VersionMatcher;
+use crate::models::{Classification, ClassifiedDependency, Ecosystem};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::PathBuf;
+
+/// Kind of version inconsistency
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum InconsistencyKind {
+    /// An installed version is not locked
+    VersionMismatch,
+    /// No locked version satisfies a declared range
+    ConstraintViolation,
+    /// No installed version satisfies a declared range (no lockfile)
+    InstalledOutsideRange,
+}
+
+impl fmt::Display for InconsistencyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InconsistencyKind::VersionMismatch => write!(f, "version_mismatch"),
+            InconsistencyKind::ConstraintViolation => write!(f, "constraint_violation"),
+            InconsistencyKind::InstalledOutsideRange => write!(f, "installed_outside_range"),
+        }
+    }
+}
+
+/// A package whose versions disagree across sources within one application
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct VersionInconsistency {
+    /// Package name
+    pub name: String,
+
+    /// Ecosystem
+    pub ecosystem: Ecosystem,
+
+    /// Application root directory
+    pub application_root: PathBuf,
+
+    /// Application name
+    pub application_name: Option<String>,
+
+    /// What disagrees
+    pub kind: InconsistencyKind,
+
+    /// Installed versions (HAS)
+    pub installed: Vec<String>,
+
+    /// Locked versions (SHOULD)
+    pub locked: Vec<String>,
+
+    /// Declared ranges (CAN)
+    pub declared: Vec<String>,
+}
+
+/// Versions of one package in one application, by source
+#[derive(Default)]
+struct PackageVersions {
+    application_name: Option<String>,
+    installed: BTreeSet<String>,
+    locked: BTreeSet<String>,
+    declared: BTreeSet<String>,
+}
+
+/// Checker for version consistency across installed, locked and declared sources
+pub struct ConsistencyChecker {
+    version_matcher: VersionMatcher,
+}
+
+impl ConsistencyChecker {
+    /// Create a new ConsistencyChecker
+    pub fn new() -> Self {
+        Self {
+            version_matcher: VersionMatcher::new(),
+        }
+    }
+
+    /// Find inconsistencies, ordered by application, package and kind
+    pub fn check(&self, dependencies: &[ClassifiedDependency]) -> Vec<VersionInconsistency> {
+        let mut packages: BTreeMap<(PathBuf, String, String), (Ecosystem, PackageVersions)> =
+            BTreeMap::new();
+
+        for dep in dependencies {
+            let Some(root) = &dep.application_root else {
+                continue;
+            };
+            let key = (root.clone(), dep.name.clone(), dep.ecosystem.to_string());
+            let (_, versions) = packages
+                .entry(key)
+                .or_insert_with(|| (dep.ecosystem, PackageVersions::default()));
+            if versions.application_name.is_none() {
+                versions.application_name = dep.application_name.clone();
+            }
+            for (classification, version) in &dep.classifications {
+                let set = match classification {
+                    Classification::Has => &mut versions.installed,
+                    Classification::Should => &mut versions.locked,
+                    Classification::Can => &mut versions.declared,
+                };
+                set.insert(version.trim().to_string());
+            }
+        }
+
+        let mut findings = Vec::new();
+        for ((root, name, _), (ecosystem, versions)) in packages {
+            for kind in self.check_package(ecosystem, &versions) {
+                findings.push(VersionInconsistency {
+                    name: name.clone(),
+                    ecosystem,
+                    application_root: root.clone(),
+                    application_name: versions.application_name.clone(),
+                    kind,
+                    installed: versions.installed.iter().cloned().collect(),
+                    locked: versions.locked.iter().cloned().collect(),
+                    declared: versions.declared.iter().cloned().collect(),
+                });
+            }
+        }
+
+        findings
+    }
+
+    /// Inconsistencies of one package
+    fn check_package(
+        &self,
+        ecosystem: Ecosystem,
+        versions: &PackageVersions,
+    ) -> Vec<InconsistencyKind> {
+        let mut kinds = Vec::new();
+
+        if !versions.locked.is_empty()
+            && versions
+                .installed
+                .iter()
+                .any(|installed| !versions.locked.contains(installed))
+        {
+            kinds.push(InconsistencyKind::VersionMismatch);
+        }
+
+        // Nested copies may resolve other ranges, so a range only needs one match
+        let (candidates, kind) = if versions.locked.is_empty() {
+            (
+                &versions.installed,
+                InconsistencyKind::InstalledOutsideRange,
+            )
+        } else {
+            (&versions.locked, InconsistencyKind::ConstraintViolation)
+        };
+        if !candidates.is_empty()
+            && versions
+                .declared
+                .iter()
+                .any(|range| !self.any_satisfies(candidates, range, ecosystem))
+        {
+            kinds.push(kind);
+        }
+
+        kinds
+    }
+
+    /// Whether any version satisfies the range (unparseable ranges count as satisfied)
+    fn any_satisfies(
+        &self,
+        versions: &BTreeSet<String>,
+        range: &str,
+        ecosystem: Ecosystem,
+    ) -> bool {
+        versions.iter().any(|version| {
+            !self
+                .version_matcher
+                .detect_constraint_violation(version, range, ecosystem)
+        })
+    }
+}
+
+impl Default for ConsistencyChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, classification: Classification, version: &str) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new(name.to_string(), Ecosystem::Node);
+        dep.add_classification(classification, version.to_string(), PathBuf::from("/app"));
+        dep.application_root = Some(PathBuf::from("/app"));
+        dep.application_name = Some("web".to_string());
+        dep
+    }
+
+    #[test]
+    fn test_detects_inconsistencies_across_entries() {
+        let deps = vec![
+            entry("react", Classification::Has, "18.2.0"),
+            entry("react", Classification::Should, "18.1.0"),
+            entry("react", Classification::Can, "^17.0.0"),
+            entry("lodash", Classification::Has, "4.17.21"),
+            entry("lodash", Classification::Should, "4.17.21"),
+            entry("lodash", Classification::Can, "^4.17.0"),
+        ];
+
+        let findings = ConsistencyChecker::new().check(&deps);
+
+        let kinds: Vec<_> = findings.iter().map(|f| (f.name.as_str(), f.kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("react", InconsistencyKind::VersionMismatch),
+                ("react", InconsistencyKind::ConstraintViolation),
+            ]
+        );
+        assert_eq!(findings[0].installed, vec!["18.2.0"]);
+        assert_eq!(findings[0].locked, vec!["18.1.0"]);
+        assert_eq!(findings[0].application_name.as_deref(), Some("web"));
+    }
+
+    #[test]
+    fn test_nested_versions_and_missing_lockfile() {
+        let mut other_app = entry("ms", Classification::Has, "1.0.0");
+        other_app.application_root = Some(PathBuf::from("/other"));
+        let deps = vec![
+            // A nested copy of another version is fine when it is locked too
+            entry("ms", Classification::Has, "2.1.3"),
+            entry("ms", Classification::Has, "2.0.0"),
+            entry("ms", Classification::Should, "2.1.3"),
+            entry("ms", Classification::Should, "2.0.0"),
+            entry("ms", Classification::Can, "^2.1.0"),
+            other_app,
+            entry("debug", Classification::Has, "3.0.0"),
+            entry("debug", Classification::Can, "^4.0.0"),
+        ];
+
+        let findings = ConsistencyChecker::new().check(&deps);
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].name, "debug");
+        assert_eq!(findings[0].kind, InconsistencyKind::InstalledOutsideRange);
+    }
+}
@@ -0,0 +1,275 @@
+//! Content hashes of installed package directories
+//!
+//! The same package version installed on thousands of hosts should have the
+//! same files everywhere; a copy edited after install stands out once its
+//! content is compared across hosts. The [`ContentHasher`] sets
+//! `content_hash` on installed (HAS) entries: a SHA-256 over the sorted
+//! relative paths of the package's files and the SHA-256 of each file, as
+//! `sha256=<urlsafe base64>` like RECORD hashes.
+//!
+//! The files of an npm package are those of its directory, without the
+//! packages nested in its `node_modules`. Those of a Python distribution are
+//! the ones its `RECORD` lists, without the metadata directory and files
+//! outside site-packages (scripts, whose shebang names the interpreter), or
+//! those of its package directory when it has no `RECORD`. Bytecode
+//! (`__pycache__`, `.pyc`) is left out everywhere, as it embeds timestamps;
+//! symlinks are hashed by their target. Entries whose files can't be found
+//! get no hash.
+//!
+//! Files are streamed into the hash rather than read whole, and their reads
+//! go through the throttle of the current thread (see
+//! [`throttle::scoped`]).
+
+use super::phantom::package_key;
+use super::reachability::python_metadata_dirs;
+use crate::models::{Classification, ClassifiedDependency, Ecosystem};
+use crate::parsers::installed::parse_record_file;
+use crate::throttle::{self, IoThrottle};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Hasher of the files of installed packages
+#[derive(Debug, Clone, Default)]
+pub struct ContentHasher;
+
+impl ContentHasher {
+    /// Create a new ContentHasher
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Content hash of an installed entry
+    pub fn hash(&self, dep: &ClassifiedDependency) -> Option<String> {
+        let path = dep.installed_path.as_ref()?;
+        let files = match dep.ecosystem {
+            Ecosystem::Python => python_files(path, &dep.name)?,
+            _ => directory_files(path)?,
+        };
+        Some(digest_files(&files))
+    }
+
+    /// Set `content_hash` on the installed entries, returning how many got one
+    pub fn mark_hashes(&self, dependencies: &mut [ClassifiedDependency]) -> usize {
+        // The rayon tasks read on other threads, under the same throttle
+        let io_throttle = IoThrottle::current();
+        dependencies
+            .par_iter_mut()
+            .filter(|dep| dep.has_classification(Classification::Has))
+            .map(|dep| {
+                dep.content_hash = throttle::scoped(io_throttle.as_ref(), || self.hash(dep));
+                usize::from(dep.content_hash.is_some())
+            })
+            .sum()
+    }
+}
+
+/// Files of a Python distribution, by path relative to site-packages
+fn python_files(installed_path: &Path, name: &str) -> Option<Vec<(String, PathBuf)>> {
+    let site_packages = installed_path.parent()?;
+    let record = python_metadata_dirs(site_packages)
+        .get(&package_key(Ecosystem::Python, name))
+        .and_then(|dir| parse_record_file(&dir.join("RECORD")).ok());
+    let Some(entries) = record else {
+        return directory_files(installed_path);
+    };
+    let mut files: Vec<(String, PathBuf)> = entries
+        .into_iter()
+        .filter(|entry| {
+            let first = entry.path.split('/').next().unwrap_or_default();
+            first != ".."
+                && !first.ends_with(".dist-info")
+                && !entry.path.split('/').any(|part| part == "__pycache__")
+                && !entry.path.ends_with(".pyc")
+        })
+        .map(|entry| (entry.path.clone(), site_packages.join(&entry.path)))
+        .collect();
+    files.sort();
+    files.dedup();
+    Some(files)
+}
+
+/// Files of a package directory, by relative path, without nested
+/// `node_modules` and bytecode
+fn directory_files(dir: &Path) -> Option<Vec<(String, PathBuf)>> {
+    if !fs::symlink_metadata(dir).ok()?.is_dir() {
+        return None;
+    }
+    let mut files = Vec::new();
+    let mut dirs = vec![(String::new(), dir.to_path_buf())];
+    while let Some((prefix, current)) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let relative = format!("{}{}", prefix, name);
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                if name != "node_modules" && name != "__pycache__" {
+                    dirs.push((format!("{}/", relative), entry.path()));
+                }
+            } else if !name.ends_with(".pyc") {
+                files.push((relative, entry.path()));
+            }
+        }
+    }
+    files.sort();
+    Some(files)
+}
+
+/// SHA-256 over the relative path and SHA-256 of each file; missing files
+/// hash as empty so a deleted file changes the result
+fn digest_files(files: &[(String, PathBuf)]) -> String {
+    let io_throttle = IoThrottle::current();
+    let mut hasher = Sha256::new();
+    for (relative, path) in files {
+        let digest = match fs::read_link(path) {
+            Ok(target) => Sha256::digest(target.to_string_lossy().as_bytes()),
+            Err(_) => {
+                digest_file(path, io_throttle.as_ref()).unwrap_or_else(|_| Sha256::digest([]))
+            }
+        };
+        hasher.update(relative.as_bytes());
+        hasher.update([0]);
+        hasher.update(digest);
+    }
+    format!("sha256={}", URL_SAFE_NO_PAD.encode(hasher.finalize()))
+}
+
+/// SHA-256 of a file, streamed from disk
+fn digest_file(
+    path: &Path,
+    io_throttle: Option<&IoThrottle>,
+) -> io::Result<sha2::digest::Output<Sha256>> {
+    let mut file = File::open(path)?;
+    if let Some(throttle) = io_throttle {
+        throttle.acquire(1, file.metadata()?.len());
+    }
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn installed(path: PathBuf, name: &str, ecosystem: Ecosystem) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new(name.to_string(), ecosystem);
+        dep.add_classification(Classification::Has, "1.0.0".to_string(), path.clone());
+        dep.installed_path = Some(path);
+        dep
+    }
+
+    #[test]
+    fn test_node_content_hash() {
+        let temp_dir = TempDir::new().unwrap();
+        let hosts: Vec<PathBuf> = ["a", "b"]
+            .iter()
+            .map(|host| temp_dir.path().join(host).join("node_modules/lodash"))
+            .collect();
+        for (i, path) in hosts.iter().enumerate() {
+            fs::create_dir_all(path.join("fp")).unwrap();
+            fs::write(path.join("package.json"), r#"{"name": "lodash"}"#).unwrap();
+            fs::write(path.join("fp/map.js"), "module.exports = map;").unwrap();
+            // Nested packages are hashed on their own
+            fs::create_dir_all(path.join("node_modules/dep")).unwrap();
+            fs::write(path.join("node_modules/dep/index.js"), i.to_string()).unwrap();
+        }
+
+        let hasher = ContentHasher::new();
+        let mut deps: Vec<ClassifiedDependency> = hosts
+            .iter()
+            .map(|path| installed(path.clone(), "lodash", Ecosystem::Node))
+            .collect();
+        let mut declared = ClassifiedDependency::new("lodash".to_string(), Ecosystem::Node);
+        declared.add_classification(
+            Classification::Can,
+            "^4.0.0".to_string(),
+            temp_dir.path().join("package.json"),
+        );
+        deps.push(declared);
+
+        assert_eq!(hasher.mark_hashes(&mut deps), 2);
+        assert!(deps[0]
+            .content_hash
+            .as_ref()
+            .unwrap()
+            .starts_with("sha256="));
+        assert_eq!(deps[0].content_hash, deps[1].content_hash);
+        assert_eq!(deps[2].content_hash, None);
+
+        fs::write(hosts[1].join("fp/map.js"), "module.exports = steal;").unwrap();
+        assert_ne!(hasher.hash(&deps[0]), hasher.hash(&deps[1]));
+    }
+
+    #[test]
+    fn test_python_content_hash() {
+        let temp_dir = TempDir::new().unwrap();
+        let site_packages = temp_dir.path().join("site-packages");
+        let dist_info = site_packages.join("PyYAML-6.0.1.dist-info");
+        fs::create_dir_all(&dist_info).unwrap();
+        fs::create_dir_all(site_packages.join("yaml/__pycache__")).unwrap();
+        fs::write(
+            site_packages.join("yaml/__init__.py"),
+            "__version__ = '6.0.1'",
+        )
+        .unwrap();
+        fs::write(
+            site_packages.join("yaml/__pycache__/__init__.cpython-312.pyc"),
+            "bytecode",
+        )
+        .unwrap();
+        fs::write(dist_info.join("INSTALLER"), "pip").unwrap();
+        fs::write(
+            dist_info.join("RECORD"),
+            "yaml/__init__.py,sha256=x,21\n\
+             yaml/__pycache__/__init__.cpython-312.pyc,,\n\
+             PyYAML-6.0.1.dist-info/INSTALLER,sha256=y,3\n\
+             ../../../bin/yaml-tool,sha256=z,10\n",
+        )
+        .unwrap();
+
+        let dep = installed(site_packages.join("PyYAML"), "PyYAML", Ecosystem::Python);
+        let hasher = ContentHasher::new();
+        let before = hasher.hash(&dep).unwrap();
+
+        // Bytecode and install metadata are not content
+        fs::write(
+            site_packages.join("yaml/__pycache__/__init__.cpython-312.pyc"),
+            "recompiled",
+        )
+        .unwrap();
+        fs::write(dist_info.join("INSTALLER"), "uv").unwrap();
+        assert_eq!(hasher.hash(&dep).unwrap(), before);
+
+        fs::write(site_packages.join("yaml/__init__.py"), "import os").unwrap();
+        assert_ne!(hasher.hash(&dep).unwrap(), before);
+    }
+
+    #[test]
+    fn test_content_hash_reads_through_throttle() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("node_modules/lodash");
+        fs::create_dir_all(&path).unwrap();
+        fs::write(path.join("package.json"), r#"{"name": "lodash"}"#).unwrap();
+        fs::write(path.join("index.js"), "module.exports = {};").unwrap();
+        let mut deps = vec![installed(path, "lodash", Ecosystem::Node)];
+
+        let io_throttle = IoThrottle::new().with_max_files_per_sec(1000);
+        let hashed = throttle::scoped(Some(&io_throttle), || {
+            ContentHasher::new().mark_hashes(&mut deps)
+        });
+        assert_eq!(hashed, 1);
+        assert_eq!(io_throttle.files(), 2);
+        assert_eq!(io_throttle.bytes(), 38);
+    }
+}
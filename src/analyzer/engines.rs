@@ -0,0 +1,163 @@
+//! Runtime compatibility of installed packages
+//!
+//! npm packages declare the Node versions they run on in `engines.node`, and
+//! Python distributions theirs in `Requires-Python`; both end up in
+//! [`InstalledPackage::engines`]. The [`EngineChecker`] compares them with the
+//! runtime versions an application is deployed on (`node` → `18.19.0`,
+//! `python` → `3.11`) and reports every package declaring it does not support
+//! them. Runtimes without a target are not checked, and ranges the version
+//! matcher cannot read are taken as compatible.
+
+use crate::analyzer::VersionMatcher;
+use crate::models::{Ecosystem, InstalledPackage};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// An installed package declaring it does not support a target runtime
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct EngineIncompatibility {
+    /// Package name
+    pub name: String,
+
+    /// Installed version
+    pub version: String,
+
+    /// Ecosystem
+    pub ecosystem: Ecosystem,
+
+    /// Installation path
+    pub path: PathBuf,
+
+    /// Runtime (`node`, `python`)
+    pub runtime: String,
+
+    /// Runtime versions the package supports
+    pub required: String,
+
+    /// Target runtime version
+    pub target: String,
+}
+
+/// Checker of installed packages against target runtime versions
+pub struct EngineChecker {
+    targets: BTreeMap<String, String>,
+    version_matcher: VersionMatcher,
+}
+
+impl EngineChecker {
+    /// Create a new EngineChecker without targets
+    pub fn new() -> Self {
+        Self {
+            targets: BTreeMap::new(),
+            version_matcher: VersionMatcher::new(),
+        }
+    }
+
+    /// Check a runtime against a version (`node`, `18.19.0`)
+    pub fn with_target(mut self, runtime: impl Into<String>, version: impl Into<String>) -> Self {
+        self.targets.insert(runtime.into(), version.into());
+        self
+    }
+
+    /// Check runtimes against versions
+    pub fn with_targets(mut self, targets: &BTreeMap<String, String>) -> Self {
+        self.targets.extend(targets.clone());
+        self
+    }
+
+    /// Find installed packages not supporting a target runtime, in input
+    /// order
+    pub fn check(&self, installed: &[InstalledPackage]) -> Vec<EngineIncompatibility> {
+        let mut results = Vec::new();
+        for package in installed {
+            for (runtime, required) in &package.engines {
+                let Some(target) = self.targets.get(runtime) else {
+                    continue;
+                };
+                let Some(ecosystem) = runtime_ecosystem(runtime) else {
+                    continue;
+                };
+                let required = required.trim();
+                if let Ok(false) = self
+                    .version_matcher
+                    .satisfies_all(target, required, ecosystem)
+                {
+                    results.push(EngineIncompatibility {
+                        name: package.name.clone(),
+                        version: package.version.clone(),
+                        ecosystem: package.ecosystem,
+                        path: package.path.clone(),
+                        runtime: runtime.clone(),
+                        required: required.to_string(),
+                        target: target.clone(),
+                    });
+                }
+            }
+        }
+        results
+    }
+}
+
+impl Default for EngineChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Version scheme of a runtime
+fn runtime_ecosystem(runtime: &str) -> Option<Ecosystem> {
+    match runtime {
+        "node" => Some(Ecosystem::Node),
+        "python" => Some(Ecosystem::Python),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn installed(name: &str, ecosystem: Ecosystem, runtime: &str, range: &str) -> InstalledPackage {
+        let mut package = InstalledPackage::new(
+            name.to_string(),
+            "1.0.0".to_string(),
+            PathBuf::from(format!("/app/lib/{}", name)),
+            ecosystem,
+        );
+        package
+            .engines
+            .insert(runtime.to_string(), range.to_string());
+        package
+    }
+
+    #[test]
+    fn test_check_engines() {
+        let installed = vec![
+            installed("sharp", Ecosystem::Node, "node", "^18.17.0 || >=20.3.0"),
+            installed("undici", Ecosystem::Node, "node", ">=20.18.1"),
+            installed("legacy", Ecosystem::Node, "npm", ">=3"),
+            installed("numpy", Ecosystem::Python, "python", ">=3.12"),
+            installed("requests", Ecosystem::Python, "python", ">=3.8"),
+        ];
+
+        let found = EngineChecker::new()
+            .with_target("node", "18.19.0")
+            .with_target("python", "3.11")
+            .check(&installed);
+
+        let names: Vec<&str> = found.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["undici", "numpy"]);
+        assert_eq!(found[0].runtime, "node");
+        assert_eq!(found[0].required, ">=20.18.1");
+        assert_eq!(found[0].target, "18.19.0");
+        assert_eq!(found[1].ecosystem, Ecosystem::Python);
+    }
+
+    #[test]
+    fn test_no_targets() {
+        let installed = vec![installed("undici", Ecosystem::Node, "node", ">=20")];
+        assert!(EngineChecker::new().check(&installed).is_empty());
+    }
+}
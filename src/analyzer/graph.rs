@@ -0,0 +1,366 @@
+//! Per-application dependency graphs built from lockfile edges
+//!
+//! The [`TreeBuilder`](crate::analyzer::TreeBuilder) expands installed
+//! packages into trees, duplicating a subtree everywhere it is required and
+//! ignoring lockfiles. The [`GraphBuilder`] instead builds one directed graph
+//! per application and ecosystem from the edges every entry records (lockfile
+//! `dependencies`/`requires` and installed package metadata). Each package is
+//! a single shared node, so the graph is a DAG apart from genuine cycles:
+//!
+//! ```text
+//! express ──> body-parser ──> qs
+//!    └────────────────────────^
+//! ```
+//!
+//! Entries of the same package are merged into one node, and edges to
+//! packages the application does not lock or install are dropped. Direct
+//! dependencies are the packages the application's manifests declare; without
+//! a manifest, packages no other package depends on are used. Entries need
+//! their application root assigned (see
+//! [`ApplicationLinker::assign_application_roots`](crate::analyzer::ApplicationLinker::assign_application_roots)).
+
+use crate::models::purl::normalize_pypi_name;
+use crate::models::{Classification, ClassifiedDependency, Ecosystem};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::path::PathBuf;
+
+/// A package in a dependency graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphNode {
+    /// Package name as first seen
+    pub name: String,
+
+    /// Installed, locked or declared version
+    pub version: Option<String>,
+
+    /// Whether the application declares the package directly
+    pub direct: bool,
+
+    /// Indices of the packages this package depends on, ordered by name
+    pub children: Vec<usize>,
+
+    /// Indices of the packages depending on this package, ordered by name
+    pub parents: Vec<usize>,
+}
+
+/// Dependency graph of one application and ecosystem
+#[derive(Debug, Clone)]
+pub struct DependencyGraph {
+    /// Application root directory
+    pub application_root: PathBuf,
+
+    /// Application name, if known
+    pub application_name: Option<String>,
+
+    /// Ecosystem
+    pub ecosystem: Ecosystem,
+
+    nodes: Vec<GraphNode>,
+    index: HashMap<String, usize>,
+    roots: Vec<usize>,
+}
+
+impl DependencyGraph {
+    /// All packages, ordered by name
+    pub fn nodes(&self) -> &[GraphNode] {
+        &self.nodes
+    }
+
+    /// Number of packages
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Whether the graph has no packages
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Number of edges
+    pub fn edge_count(&self) -> usize {
+        self.nodes.iter().map(|n| n.children.len()).sum()
+    }
+
+    /// Look up a package by name (normalized for PyPI)
+    pub fn node(&self, name: &str) -> Option<&GraphNode> {
+        self.position(name).map(|i| &self.nodes[i])
+    }
+
+    /// Direct dependencies of the application
+    pub fn roots(&self) -> Vec<&GraphNode> {
+        self.roots.iter().map(|&i| &self.nodes[i]).collect()
+    }
+
+    /// Packages a package depends on
+    pub fn children(&self, name: &str) -> Vec<&GraphNode> {
+        self.node(name)
+            .map(|node| node.children.iter().map(|&i| &self.nodes[i]).collect())
+            .unwrap_or_default()
+    }
+
+    /// Packages depending on a package
+    pub fn parents(&self, name: &str) -> Vec<&GraphNode> {
+        self.node(name)
+            .map(|node| node.parents.iter().map(|&i| &self.nodes[i]).collect())
+            .unwrap_or_default()
+    }
+
+    /// Packages a package depends on, directly or transitively, nearest first
+    pub fn descendants(&self, name: &str) -> Vec<&GraphNode> {
+        self.reachable(name, |node| &node.children)
+    }
+
+    /// Packages depending on a package, directly or transitively, nearest first
+    pub fn ancestors(&self, name: &str) -> Vec<&GraphNode> {
+        self.reachable(name, |node| &node.parents)
+    }
+
+    /// Length of the shortest path from the application to a package
+    ///
+    /// Direct dependencies have depth 1. Returns `None` for unknown packages
+    /// and packages no direct dependency leads to.
+    pub fn depth(&self, name: &str) -> Option<usize> {
+        let target = self.position(name)?;
+        let mut depths = vec![None; self.nodes.len()];
+        let mut queue = VecDeque::new();
+        for &root in &self.roots {
+            depths[root] = Some(1);
+            queue.push_back(root);
+        }
+        while let Some(current) = queue.pop_front() {
+            if current == target {
+                break;
+            }
+            let depth = depths[current].unwrap_or_default();
+            for &child in &self.nodes[current].children {
+                if depths[child].is_none() {
+                    depths[child] = Some(depth + 1);
+                    queue.push_back(child);
+                }
+            }
+        }
+        depths[target]
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.index.get(&key(self.ecosystem, name)).copied()
+    }
+
+    /// Breadth-first walk along `next`, excluding the start node
+    fn reachable<F>(&self, name: &str, next: F) -> Vec<&GraphNode>
+    where
+        F: Fn(&GraphNode) -> &Vec<usize>,
+    {
+        let Some(start) = self.position(name) else {
+            return Vec::new();
+        };
+        let mut seen = vec![false; self.nodes.len()];
+        seen[start] = true;
+        let mut queue = VecDeque::from([start]);
+        let mut found = Vec::new();
+        while let Some(current) = queue.pop_front() {
+            for &i in next(&self.nodes[current]) {
+                if !seen[i] {
+                    seen[i] = true;
+                    found.push(&self.nodes[i]);
+                    queue.push_back(i);
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Merged entries of one package
+#[derive(Default)]
+struct Package {
+    name: String,
+    version: Option<String>,
+    declared: bool,
+    children: BTreeSet<String>,
+}
+
+/// Builder for per-application dependency graphs
+pub struct GraphBuilder;
+
+impl GraphBuilder {
+    /// Create a new GraphBuilder
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Build one graph per application root and ecosystem
+    ///
+    /// Graphs are ordered by application root, then ecosystem. Entries without
+    /// an application root are ignored.
+    pub fn build(&self, dependencies: &[ClassifiedDependency]) -> Vec<DependencyGraph> {
+        let mut groups: BTreeMap<(PathBuf, String), (Option<String>, Ecosystem)> = BTreeMap::new();
+        let mut packages: BTreeMap<(PathBuf, String), BTreeMap<String, Package>> = BTreeMap::new();
+        for dep in dependencies {
+            let Some(root) = &dep.application_root else {
+                continue;
+            };
+            let group = (root.clone(), dep.ecosystem.to_string());
+            let (application_name, _) =
+                groups.entry(group.clone()).or_insert((None, dep.ecosystem));
+            if application_name.is_none() {
+                application_name.clone_from(&dep.application_name);
+            }
+            let package = packages
+                .entry(group)
+                .or_default()
+                .entry(key(dep.ecosystem, &dep.name))
+                .or_insert_with(|| Package {
+                    name: dep.name.clone(),
+                    ..Package::default()
+                });
+            if package.version.is_none() || dep.has_classification(Classification::Has) {
+                package.version = dep.get_primary_version().map(str::to_string);
+            }
+            package.declared |= dep.has_classification(Classification::Can);
+            package
+                .children
+                .extend(dep.dependencies.iter().map(|c| key(dep.ecosystem, c)));
+        }
+
+        groups
+            .into_iter()
+            .map(|(group, (application_name, ecosystem))| {
+                let packages = packages.remove(&group).unwrap_or_default();
+                build_graph(group.0, application_name, ecosystem, packages)
+            })
+            .collect()
+    }
+}
+
+impl Default for GraphBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Turn the merged packages of one application into an indexed graph
+fn build_graph(
+    application_root: PathBuf,
+    application_name: Option<String>,
+    ecosystem: Ecosystem,
+    packages: BTreeMap<String, Package>,
+) -> DependencyGraph {
+    let index: HashMap<String, usize> = packages
+        .keys()
+        .enumerate()
+        .map(|(i, name)| (name.clone(), i))
+        .collect();
+
+    let mut nodes: Vec<GraphNode> = packages
+        .values()
+        .map(|package| GraphNode {
+            name: package.name.clone(),
+            version: package.version.clone(),
+            direct: package.declared,
+            children: package
+                .children
+                .iter()
+                .filter_map(|child| index.get(child).copied())
+                .collect(),
+            parents: Vec::new(),
+        })
+        .collect();
+    for parent in 0..nodes.len() {
+        for child in nodes[parent].children.clone() {
+            nodes[child].parents.push(parent);
+        }
+    }
+
+    let declared: Vec<usize> = (0..nodes.len()).filter(|&i| nodes[i].direct).collect();
+    let roots = if declared.is_empty() {
+        (0..nodes.len())
+            .filter(|&i| nodes[i].parents.is_empty())
+            .collect()
+    } else {
+        declared
+    };
+
+    DependencyGraph {
+        application_root,
+        application_name,
+        ecosystem,
+        nodes,
+        index,
+        roots,
+    }
+}
+
+/// Name used to match packages (normalized for PyPI)
+fn key(ecosystem: Ecosystem, name: &str) -> String {
+    match ecosystem {
+        Ecosystem::Python => normalize_pypi_name(name),
+        _ => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        name: &str,
+        classification: Classification,
+        children: &[&str],
+    ) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new(name.to_string(), Ecosystem::Node);
+        dep.add_classification(classification, "1.0.0".to_string(), PathBuf::from("/app"));
+        dep.application_root = Some(PathBuf::from("/app"));
+        dep.application_name = Some("web".to_string());
+        dep.dependencies = children.iter().map(|c| c.to_string()).collect();
+        dep
+    }
+
+    fn names(nodes: Vec<&GraphNode>) -> Vec<&str> {
+        nodes.into_iter().map(|n| n.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_build_shared_nodes() {
+        let deps = vec![
+            entry("express", Classification::Can, &[]),
+            entry("express", Classification::Should, &["body-parser", "qs"]),
+            entry("body-parser", Classification::Should, &["qs", "missing"]),
+            entry("qs", Classification::Should, &[]),
+        ];
+
+        let graphs = GraphBuilder::new().build(&deps);
+
+        assert_eq!(graphs.len(), 1);
+        let graph = &graphs[0];
+        assert_eq!(graph.application_name.as_deref(), Some("web"));
+        assert_eq!(graph.len(), 3);
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(names(graph.roots()), vec!["express"]);
+        assert_eq!(names(graph.parents("qs")), vec!["body-parser", "express"]);
+        assert_eq!(
+            names(graph.descendants("express")),
+            vec!["body-parser", "qs"]
+        );
+        assert_eq!(names(graph.ancestors("qs")), vec!["body-parser", "express"]);
+        assert_eq!(graph.depth("express"), Some(1));
+        assert_eq!(graph.depth("qs"), Some(2));
+        assert_eq!(graph.depth("missing"), None);
+    }
+
+    #[test]
+    fn test_build_cycle_without_manifest() {
+        let deps = vec![
+            entry("a", Classification::Should, &["b"]),
+            entry("b", Classification::Should, &["c"]),
+            entry("c", Classification::Should, &["b"]),
+        ];
+
+        let graph = &GraphBuilder::new().build(&deps)[0];
+
+        assert_eq!(names(graph.roots()), vec!["a"]);
+        assert_eq!(names(graph.descendants("b")), vec!["c"]);
+        assert_eq!(names(graph.ancestors("b")), vec!["a", "c"]);
+        assert_eq!(graph.depth("c"), Some(3));
+    }
+}
@@ -0,0 +1,239 @@
+//! Comparison of the packages of two hosts
+//!
+//! During incident scoping, the question is what one machine has that
+//! another, supposedly identical, one does not. The [`HostComparer`] takes
+//! the results of two scans and reports, for each application (matched by
+//! name), the package versions present on one host only. Versions are the
+//! installed ones, or the locked ones for entries with no installed version;
+//! declared ranges are not compared. Python names are compared normalized.
+//!
+//! When both results carry content hashes (`--content-hashes`), a package
+//! version installed on both hosts with different files is reported too.
+
+use super::phantom::package_key;
+use crate::models::{Classification, ClassifiedDependency, Ecosystem};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// How a package version differs between the two hosts
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum HostDiffKind {
+    /// Only on the first host
+    OnlyFirst,
+    /// Only on the second host
+    OnlySecond,
+    /// On both hosts, with different files
+    ContentDiffers,
+}
+
+impl fmt::Display for HostDiffKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HostDiffKind::OnlyFirst => write!(f, "only_first"),
+            HostDiffKind::OnlySecond => write!(f, "only_second"),
+            HostDiffKind::ContentDiffers => write!(f, "content_differs"),
+        }
+    }
+}
+
+/// A package version differing between the two hosts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct HostDifference {
+    /// Application name (empty for packages of no application, such as OS
+    /// packages)
+    pub application: String,
+
+    /// Ecosystem
+    pub ecosystem: Ecosystem,
+
+    /// Package name
+    pub name: String,
+
+    /// Installed or locked version
+    pub version: String,
+
+    /// How it differs
+    pub kind: HostDiffKind,
+
+    /// Content hashes on the first host
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub first_hashes: Vec<String>,
+
+    /// Content hashes on the second host
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub second_hashes: Vec<String>,
+}
+
+/// Package versions of one host: name and content hashes by application,
+/// ecosystem, normalized name and version
+type HostPackages = BTreeMap<(String, Ecosystem, String, String), (String, BTreeSet<String>)>;
+
+/// Comparer of the packages of two hosts
+#[derive(Debug, Clone, Default)]
+pub struct HostComparer;
+
+impl HostComparer {
+    /// Create a new HostComparer
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Package versions on one host only, or with different files, ordered
+    /// by application, ecosystem, name and version
+    pub fn compare(
+        &self,
+        first: &[ClassifiedDependency],
+        second: &[ClassifiedDependency],
+    ) -> Vec<HostDifference> {
+        let first = host_packages(first);
+        let second = host_packages(second);
+
+        let mut differences = Vec::new();
+        let keys: BTreeSet<_> = first.keys().chain(second.keys()).collect();
+        for key in keys {
+            let (application, ecosystem, _, version) = key;
+            let (name, kind, first_hashes, second_hashes) = match (first.get(key), second.get(key))
+            {
+                (Some((name, hashes)), None) => {
+                    (name, HostDiffKind::OnlyFirst, hashes, &BTreeSet::new())
+                }
+                (None, Some((name, hashes))) => {
+                    (name, HostDiffKind::OnlySecond, &BTreeSet::new(), hashes)
+                }
+                (Some((name, first_hashes)), Some((_, second_hashes)))
+                    if !first_hashes.is_empty()
+                        && !second_hashes.is_empty()
+                        && first_hashes != second_hashes =>
+                {
+                    (
+                        name,
+                        HostDiffKind::ContentDiffers,
+                        first_hashes,
+                        second_hashes,
+                    )
+                }
+                _ => continue,
+            };
+            differences.push(HostDifference {
+                application: application.clone(),
+                ecosystem: *ecosystem,
+                name: name.clone(),
+                version: version.clone(),
+                kind,
+                first_hashes: first_hashes.iter().cloned().collect(),
+                second_hashes: second_hashes.iter().cloned().collect(),
+            });
+        }
+        differences
+    }
+}
+
+fn host_packages(dependencies: &[ClassifiedDependency]) -> HostPackages {
+    let mut packages = HostPackages::new();
+    for dep in dependencies {
+        let Some(version) = dep
+            .get_version(Classification::Has)
+            .or_else(|| dep.get_version(Classification::Should))
+        else {
+            continue;
+        };
+        let (_, hashes) = packages
+            .entry((
+                dep.application_name.clone().unwrap_or_default(),
+                dep.ecosystem,
+                package_key(dep.ecosystem, &dep.name),
+                version.to_string(),
+            ))
+            .or_insert_with(|| (dep.name.clone(), BTreeSet::new()));
+        hashes.extend(dep.content_hash.clone());
+    }
+    packages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn dep(
+        application: &str,
+        name: &str,
+        classification: Classification,
+        version: &str,
+        content_hash: Option<&str>,
+    ) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new(name.to_string(), Ecosystem::Node);
+        dep.add_classification(classification, version.to_string(), PathBuf::from("/app"));
+        dep.application_name = Some(application.to_string()).filter(|a| !a.is_empty());
+        dep.content_hash = content_hash.map(str::to_string);
+        dep
+    }
+
+    #[test]
+    fn test_compare_hosts() {
+        let first = vec![
+            dep("web", "lodash", Classification::Has, "4.17.20", None),
+            dep(
+                "web",
+                "express",
+                Classification::Has,
+                "4.18.2",
+                Some("sha256=a"),
+            ),
+            dep("web", "left-pad", Classification::Should, "1.3.0", None),
+            dep("web", "react", Classification::Can, "^18.0.0", None),
+            dep(
+                "api",
+                "axios",
+                Classification::Has,
+                "1.6.0",
+                Some("sha256=b"),
+            ),
+        ];
+        let second = vec![
+            dep("web", "lodash", Classification::Has, "4.17.21", None),
+            dep(
+                "web",
+                "express",
+                Classification::Has,
+                "4.18.2",
+                Some("sha256=c"),
+            ),
+            dep("web", "left-pad", Classification::Has, "1.3.0", None),
+            dep("web", "react", Classification::Can, "^18.2.0", None),
+            // Hashed on one host only
+            dep("api", "axios", Classification::Has, "1.6.0", None),
+            dep("", "openssl", Classification::Has, "3.0.2", None),
+        ];
+
+        let differences = HostComparer::new().compare(&first, &second);
+        let found: Vec<(&str, &str, &str, HostDiffKind)> = differences
+            .iter()
+            .map(|d| {
+                (
+                    d.application.as_str(),
+                    d.name.as_str(),
+                    d.version.as_str(),
+                    d.kind,
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("", "openssl", "3.0.2", HostDiffKind::OnlySecond),
+                ("web", "express", "4.18.2", HostDiffKind::ContentDiffers),
+                ("web", "lodash", "4.17.20", HostDiffKind::OnlyFirst),
+                ("web", "lodash", "4.17.21", HostDiffKind::OnlySecond),
+            ]
+        );
+        assert_eq!(differences[1].first_hashes, ["sha256=a"]);
+        assert_eq!(differences[1].second_hashes, ["sha256=c"]);
+        assert!(differences[2].first_hashes.is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+//! Verification of installed npm packages against lockfile integrity hashes
+//!
+//! npm records the integrity of every package it installs, either as
+//! `_integrity` in the installed package.json (npm 6) or in the hidden
+//! lockfile `node_modules/.package-lock.json` (npm 7+). The
+//! [`IntegrityVerifier`] compares that hash with the integrity the
+//! application's lockfile holds for the same package and version, and sets
+//! `integrity_mismatch` on installed entries where they disagree: the install
+//! came from a different tarball than the one that was locked.
+//!
+//! Both values are [Subresource Integrity](https://www.w3.org/TR/SRI/)
+//! strings and may list several hashes. They are only compared when they
+//! share a hash algorithm. Tarball hashes cannot be recomputed from the
+//! extracted files, so installs without a recorded integrity are not checked.
+//! Entries need their application root assigned (see
+//! [`ApplicationLinker::assign_application_roots`](crate::analyzer::ApplicationLinker::assign_application_roots)).
+
+use crate::models::{Classification, ClassifiedDependency, Ecosystem};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/// Verifier for install-time integrity hashes of npm packages
+pub struct IntegrityVerifier;
+
+impl IntegrityVerifier {
+    /// Create a new IntegrityVerifier
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Set `integrity_mismatch` on installed entries whose integrity differs
+    /// from the lockfile, returning how many were flagged
+    pub fn mark_mismatches(&self, dependencies: &mut [ClassifiedDependency]) -> usize {
+        // Locked integrity per application, package and version
+        let mut locked: HashMap<(PathBuf, String, String), Vec<String>> = HashMap::new();
+        for dep in dependencies.iter() {
+            let (Some(root), Some(version), Some(integrity)) = (
+                &dep.application_root,
+                dep.get_version(Classification::Should),
+                &dep.integrity,
+            ) else {
+                continue;
+            };
+            if dep.ecosystem == Ecosystem::Node {
+                locked
+                    .entry((root.clone(), dep.name.clone(), version.to_string()))
+                    .or_default()
+                    .push(integrity.clone());
+            }
+        }
+
+        let mut count = 0;
+        for dep in dependencies.iter_mut() {
+            let (Some(root), Some(version), Some(installed)) = (
+                &dep.application_root,
+                dep.get_version(Classification::Has),
+                &dep.installed_integrity,
+            ) else {
+                continue;
+            };
+            let key = (root.clone(), dep.name.clone(), version.to_string());
+            let Some(expected) = locked.get(&key) else {
+                continue;
+            };
+            // Any lockfile of the application agreeing is enough
+            let comparable: Vec<bool> = expected
+                .iter()
+                .filter_map(|integrity| integrity_matches(installed, integrity))
+                .collect();
+            if !comparable.is_empty() && !comparable.contains(&true) {
+                dep.integrity_mismatch = true;
+                count += 1;
+            }
+        }
+        count
+    }
+}
+
+impl Default for IntegrityVerifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Compare two SRI strings, or `None` when they share no hash algorithm
+pub fn integrity_matches(a: &str, b: &str) -> Option<bool> {
+    let a = parse_sri(a);
+    let b = parse_sri(b);
+    let algorithms: HashSet<&str> = a.iter().map(|(algorithm, _)| *algorithm).collect();
+    let shared: Vec<_> = b
+        .iter()
+        .filter(|(algorithm, _)| algorithms.contains(algorithm))
+        .collect();
+    if shared.is_empty() {
+        return None;
+    }
+    Some(shared.iter().any(|hash| a.contains(hash)))
+}
+
+/// Split an SRI string into (algorithm, digest) pairs, dropping options
+fn parse_sri(sri: &str) -> Vec<(&str, &str)> {
+    sri.split_whitespace()
+        .filter_map(|hash| {
+            let hash = hash.split('?').next().unwrap_or(hash);
+            hash.split_once('-')
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        classification: Classification,
+        integrity: &str,
+        version: &str,
+    ) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new("lodash".to_string(), Ecosystem::Node);
+        dep.add_classification(classification, version.to_string(), PathBuf::from("/app"));
+        dep.application_root = Some(PathBuf::from("/app"));
+        match classification {
+            Classification::Has => dep.installed_integrity = Some(integrity.to_string()),
+            _ => dep.integrity = Some(integrity.to_string()),
+        }
+        dep
+    }
+
+    #[test]
+    fn test_mark_mismatches() {
+        let mut deps = vec![
+            entry(Classification::Should, "sha512-good", "4.17.21"),
+            entry(Classification::Has, "sha512-evil", "4.17.21"),
+            entry(Classification::Has, "sha1-abc sha512-good", "4.17.21"),
+            // Other version or no shared algorithm: nothing to compare
+            entry(Classification::Has, "sha512-other", "4.17.20"),
+            entry(Classification::Has, "sha1-abc", "4.17.21"),
+        ];
+
+        let count = IntegrityVerifier::new().mark_mismatches(&mut deps);
+
+        assert_eq!(count, 1);
+        let flags: Vec<_> = deps.iter().map(|d| d.integrity_mismatch).collect();
+        assert_eq!(flags, vec![false, true, false, false, false]);
+    }
+
+    #[test]
+    fn test_integrity_matches() {
+        assert_eq!(integrity_matches("sha512-a", "sha512-a"), Some(true));
+        assert_eq!(integrity_matches("sha512-a?opt", "sha512-a"), Some(true));
+        assert_eq!(integrity_matches("sha512-a", "sha512-b"), Some(false));
+        assert_eq!(integrity_matches("sha1-a", "sha512-a"), None);
+    }
+}
@@ -0,0 +1,269 @@
+//! Organization-wide inventory merged from several scans
+//!
+//! Each service, repository or host is usually scanned on its own, leaving one
+//! results file per scan. The [`InventoryMerger`] folds the dependencies of
+//! several results into one inventory holding every package version once,
+//! keyed by ecosystem, name and version (installed, else locked, else the
+//! declared range), with every place it was found: the source results file,
+//! and the application and classifications within it. "Which services run
+//! lodash 4.17.20?" then becomes a lookup instead of one query per file.
+//!
+//! Sources keep the host, scan root and start time of their scan when the
+//! results carry [`ScanMetadata`].
+
+use crate::models::{Classification, ClassifiedDependency, Ecosystem, ScanMetadata};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// A results file merged into an inventory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct InventorySource {
+    /// Results file
+    pub file: PathBuf,
+
+    /// Host the scan ran on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+
+    /// Scanned directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scan_root: Option<PathBuf>,
+
+    /// Scan start, RFC 3339 UTC
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+
+    /// Number of dependency entries read from the file
+    pub entries: usize,
+}
+
+impl InventorySource {
+    /// Source of a results file, described by its scan metadata if any
+    pub fn new(file: impl Into<PathBuf>, metadata: Option<&ScanMetadata>) -> Self {
+        Self {
+            file: file.into(),
+            hostname: metadata.and_then(|m| m.hostname.clone()),
+            scan_root: metadata.map(|m| m.scan_root.clone()),
+            started_at: metadata.map(|m| m.started_at.clone()),
+            entries: 0,
+        }
+    }
+}
+
+/// Where one source found a package version
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct InventoryOccurrence {
+    /// Results file the package was found in
+    pub source: PathBuf,
+
+    /// Application name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub application_name: Option<String>,
+
+    /// Application root directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub application_root: Option<PathBuf>,
+
+    /// Installed, locked and declared (HAS, SHOULD, CAN)
+    pub classifications: Vec<Classification>,
+
+    /// Security status recorded by the scan (INFECTED, MATCH_VERSION, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub security: Option<String>,
+}
+
+/// A package version found by one or more sources
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct InventoryEntry {
+    /// Package name
+    pub name: String,
+
+    /// Installed or locked version, or the declared range
+    pub version: String,
+
+    /// Ecosystem
+    pub ecosystem: Ecosystem,
+
+    /// Package URL
+    pub purl: String,
+
+    /// Every place the version was found, ordered by source and application
+    pub found_in: Vec<InventoryOccurrence>,
+}
+
+/// Deduplicated packages of several scans
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Inventory {
+    /// Merged results files, in the order they were added
+    pub sources: Vec<InventorySource>,
+
+    /// Package versions, ordered by ecosystem, name and version
+    pub packages: Vec<InventoryEntry>,
+}
+
+/// Merger of the dependencies of several scans into one inventory
+#[derive(Debug, Default)]
+pub struct InventoryMerger {
+    sources: Vec<InventorySource>,
+    packages: BTreeMap<(Ecosystem, String, String), InventoryEntry>,
+}
+
+impl InventoryMerger {
+    /// Create an empty merger
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the dependencies of one results file
+    pub fn add(
+        &mut self,
+        mut source: InventorySource,
+        dependencies: impl IntoIterator<Item = ClassifiedDependency>,
+    ) {
+        for dep in dependencies {
+            source.entries += 1;
+            let Some(version) = dep.get_primary_version().map(str::to_string) else {
+                continue;
+            };
+            let key = (dep.ecosystem, dep.name.clone(), version.clone());
+            let entry = self.packages.entry(key).or_insert_with(|| InventoryEntry {
+                name: dep.name.clone(),
+                version,
+                ecosystem: dep.ecosystem,
+                purl: dep.purl(),
+                found_in: Vec::new(),
+            });
+
+            let classifications = dep.get_classifications();
+            let existing = entry
+                .found_in
+                .iter_mut()
+                .find(|o| o.source == source.file && o.application_root == dep.application_root);
+            match existing {
+                Some(occurrence) => {
+                    for classification in classifications {
+                        if !occurrence.classifications.contains(&classification) {
+                            occurrence.classifications.push(classification);
+                        }
+                    }
+                    occurrence.classifications.sort();
+                    if occurrence.security.is_none() {
+                        occurrence.security = dep.security;
+                    }
+                }
+                None => entry.found_in.push(InventoryOccurrence {
+                    source: source.file.clone(),
+                    application_name: dep.application_name,
+                    application_root: dep.application_root,
+                    classifications,
+                    security: dep.security,
+                }),
+            }
+        }
+        self.sources.push(source);
+    }
+
+    /// Finish the inventory
+    pub fn finish(self) -> Inventory {
+        let order: BTreeMap<&PathBuf, usize> = self
+            .sources
+            .iter()
+            .enumerate()
+            .map(|(i, source)| (&source.file, i))
+            .collect();
+        let mut packages: Vec<InventoryEntry> = self.packages.into_values().collect();
+        for entry in &mut packages {
+            entry.found_in.sort_by(|a, b| {
+                (order.get(&a.source), &a.application_root)
+                    .cmp(&(order.get(&b.source), &b.application_root))
+            });
+        }
+        Inventory {
+            sources: self.sources,
+            packages,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dep(name: &str, app: &str, versions: &[(Classification, &str)]) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new(name.to_string(), Ecosystem::Node);
+        dep.application_name = Some(app.to_string());
+        dep.application_root = Some(PathBuf::from(format!("/srv/{}", app)));
+        for (classification, version) in versions {
+            dep.add_classification(
+                *classification,
+                version.to_string(),
+                PathBuf::from(format!("/srv/{}/package.json", app)),
+            );
+        }
+        dep
+    }
+
+    #[test]
+    fn test_merge_inventories() {
+        let mut merger = InventoryMerger::new();
+        merger.add(
+            InventorySource::new("api.json", None),
+            vec![
+                dep(
+                    "lodash",
+                    "api",
+                    &[
+                        (Classification::Has, "4.17.20"),
+                        (Classification::Can, "^4.17.0"),
+                    ],
+                ),
+                dep("express", "api", &[(Classification::Should, "4.18.2")]),
+            ],
+        );
+        let mut infected = dep("lodash", "web", &[(Classification::Should, "4.17.20")]);
+        infected.security = Some("INFECTED".to_string());
+        merger.add(
+            InventorySource::new("web.json", None),
+            vec![
+                infected,
+                dep("lodash", "web", &[(Classification::Has, "4.17.20")]),
+                dep("lodash", "admin", &[(Classification::Has, "4.17.21")]),
+            ],
+        );
+
+        let inventory = merger.finish();
+
+        assert_eq!(inventory.sources.len(), 2);
+        assert_eq!(inventory.sources[0].entries, 2);
+        assert_eq!(inventory.sources[1].entries, 3);
+        let packages: Vec<(&str, &str)> = inventory
+            .packages
+            .iter()
+            .map(|p| (p.name.as_str(), p.version.as_str()))
+            .collect();
+        assert_eq!(
+            packages,
+            vec![
+                ("express", "4.18.2"),
+                ("lodash", "4.17.20"),
+                ("lodash", "4.17.21")
+            ]
+        );
+        let lodash = &inventory.packages[1];
+        assert_eq!(lodash.purl, "pkg:npm/lodash@4.17.20");
+        assert_eq!(lodash.found_in.len(), 2);
+        assert_eq!(lodash.found_in[0].source, PathBuf::from("api.json"));
+        assert_eq!(
+            lodash.found_in[0].classifications,
+            vec![Classification::Has, Classification::Can]
+        );
+        assert_eq!(lodash.found_in[1].source, PathBuf::from("web.json"));
+        assert_eq!(
+            lodash.found_in[1].classifications,
+            vec![Classification::Has, Classification::Should]
+        );
+        assert_eq!(lodash.found_in[1].security.as_deref(), Some("INFECTED"));
+    }
+}
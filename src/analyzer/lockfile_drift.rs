@@ -0,0 +1,367 @@
+//! Lockfiles out of date with their manifests
+//!
+//! A lockfile is regenerated when its manifest changes; one that was not
+//! (edited manifest, lockfile left behind, or merged from another branch)
+//! installs something other than what the manifest declares. The
+//! [`LockfileDriftChecker`] compares each manifest with the lockfiles its
+//! package manager writes next to it (`package.json` with `package-lock.json`,
+//! `yarn.lock`, `pnpm-lock.yaml`; `pyproject.toml` with `poetry.lock` or
+//! `uv.lock`; `Cargo.toml` with `Cargo.lock`, ...) and reports:
+//!
+//! - `unlocked`: a declared package the lockfile does not contain
+//! - `outside_range`: no locked version of a declared package satisfies the
+//!   declared range
+//! - `undeclared`: a package the lockfile holds as a top-level dependency
+//!   (one no other locked package depends on) that the manifest no longer
+//!   declares
+//!
+//! Only lockfiles recording dependency edges are checked for undeclared
+//! packages, and local packages (the project itself, workspace members) are
+//! never undeclared. Overrides and peer dependencies, which package managers
+//! may leave out of the lockfile, are not compared, and ranges the version
+//! matcher cannot read are taken as satisfied. Manifests of workspace members
+//! sharing a lockfile in a parent directory are not paired with it.
+
+use crate::analyzer::VersionMatcher;
+use crate::models::purl::normalize_pypi_name;
+use crate::models::{DependencyRecord, DependencyType, Ecosystem, FileType, SourceKind};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Kind of difference between a manifest and its lockfile
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftKind {
+    /// Declared but not locked
+    Unlocked,
+    /// Locked outside the declared range
+    OutsideRange,
+    /// Locked at the top level but no longer declared
+    Undeclared,
+}
+
+impl fmt::Display for DriftKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DriftKind::Unlocked => write!(f, "unlocked"),
+            DriftKind::OutsideRange => write!(f, "outside_range"),
+            DriftKind::Undeclared => write!(f, "undeclared"),
+        }
+    }
+}
+
+/// A package on which a manifest and its lockfile disagree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct DriftEntry {
+    /// Package name
+    pub name: String,
+
+    /// How they disagree
+    pub kind: DriftKind,
+
+    /// Range the manifest declares
+    pub declared: Option<String>,
+
+    /// Versions the lockfile holds
+    pub locked: Vec<String>,
+}
+
+/// A lockfile out of date with the manifest next to it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct LockfileDrift {
+    /// Application root directory (the directory of the manifest)
+    pub application_root: PathBuf,
+
+    /// Ecosystem
+    pub ecosystem: Ecosystem,
+
+    /// Manifest
+    pub manifest: PathBuf,
+
+    /// Lockfile
+    pub lockfile: PathBuf,
+
+    /// Offending packages, ordered by name and kind
+    pub entries: Vec<DriftEntry>,
+}
+
+/// Checker of lockfiles against the manifests next to them
+pub struct LockfileDriftChecker {
+    version_matcher: VersionMatcher,
+}
+
+impl LockfileDriftChecker {
+    /// Create a new LockfileDriftChecker
+    pub fn new() -> Self {
+        Self {
+            version_matcher: VersionMatcher::new(),
+        }
+    }
+
+    /// Find lockfiles out of date with their manifests, ordered by manifest
+    /// and lockfile
+    pub fn check(&self, records: &[DependencyRecord]) -> Vec<LockfileDrift> {
+        let mut manifests: BTreeMap<&Path, Vec<&DependencyRecord>> = BTreeMap::new();
+        let mut lockfiles: BTreeMap<&Path, Vec<&DependencyRecord>> = BTreeMap::new();
+        for record in records {
+            let files = match record.file_type {
+                FileType::Manifest => &mut manifests,
+                FileType::Lockfile => &mut lockfiles,
+            };
+            files.entry(&record.source_file).or_default().push(record);
+        }
+
+        let mut results = Vec::new();
+        for (manifest, declared) in &manifests {
+            let (Some(dir), Some(filename)) = (manifest.parent(), manifest.file_name()) else {
+                continue;
+            };
+            for lockfile_name in lockfiles_of(&filename.to_string_lossy()) {
+                let lockfile = dir.join(lockfile_name);
+                let Some(locked) = lockfiles.get(lockfile.as_path()) else {
+                    continue;
+                };
+                let entries = self.compare(declared, locked);
+                if !entries.is_empty() {
+                    results.push(LockfileDrift {
+                        application_root: dir.to_path_buf(),
+                        ecosystem: declared[0].ecosystem,
+                        manifest: manifest.to_path_buf(),
+                        lockfile,
+                        entries,
+                    });
+                }
+            }
+        }
+        results
+    }
+
+    /// Packages on which a manifest and a lockfile disagree
+    fn compare(
+        &self,
+        declared: &[&DependencyRecord],
+        locked: &[&DependencyRecord],
+    ) -> Vec<DriftEntry> {
+        let mut versions: BTreeMap<String, BTreeSet<&str>> = BTreeMap::new();
+        for record in locked {
+            versions
+                .entry(key(record.ecosystem, &record.name))
+                .or_default()
+                .insert(&record.version);
+        }
+
+        let mut entries = Vec::new();
+        let mut declared_names = BTreeSet::new();
+        for record in declared {
+            if matches!(
+                record.dep_type,
+                DependencyType::Override | DependencyType::Peer
+            ) {
+                continue;
+            }
+            let name = key(record.ecosystem, &record.name);
+            declared_names.insert(name.clone());
+            let range = record.version.trim();
+            let Some(locked_versions) = versions.get(&name) else {
+                entries.push(DriftEntry {
+                    name: record.name.to_string(),
+                    kind: DriftKind::Unlocked,
+                    declared: Some(range.to_string()),
+                    locked: Vec::new(),
+                });
+                continue;
+            };
+            if record.source_kind != SourceKind::Registry || matches!(range, "" | "*") {
+                continue;
+            }
+            let satisfied = locked_versions.iter().any(|version| {
+                !matches!(
+                    self.version_matcher
+                        .satisfies_all(version, range, record.ecosystem),
+                    Ok(false)
+                )
+            });
+            if !satisfied {
+                entries.push(DriftEntry {
+                    name: record.name.to_string(),
+                    kind: DriftKind::OutsideRange,
+                    declared: Some(range.to_string()),
+                    locked: locked_versions.iter().map(|v| v.to_string()).collect(),
+                });
+            }
+        }
+
+        if locked.iter().any(|record| !record.dependencies.is_empty()) {
+            let required: BTreeSet<String> = locked
+                .iter()
+                .flat_map(|record| {
+                    record
+                        .dependencies
+                        .iter()
+                        .map(|dep| key(record.ecosystem, dep))
+                })
+                .collect();
+            for record in locked {
+                let name = key(record.ecosystem, &record.name);
+                if required.contains(&name) || declared_names.contains(&name) || is_local(record) {
+                    continue;
+                }
+                entries.push(DriftEntry {
+                    name: record.name.to_string(),
+                    kind: DriftKind::Undeclared,
+                    declared: None,
+                    locked: versions[&name].iter().map(|v| v.to_string()).collect(),
+                });
+                declared_names.insert(name);
+            }
+        }
+
+        entries.sort_by(|a, b| a.name.cmp(&b.name).then(a.kind.cmp(&b.kind)));
+        entries.dedup();
+        entries
+    }
+}
+
+impl Default for LockfileDriftChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lockfiles a package manager writes next to a manifest
+fn lockfiles_of(manifest: &str) -> &'static [&'static str] {
+    match manifest {
+        "package.json" => &[
+            "npm-shrinkwrap.json",
+            "package-lock.json",
+            "pnpm-lock.yaml",
+            "yarn.lock",
+        ],
+        "deno.json" | "deno.jsonc" => &["deno.lock"],
+        "pyproject.toml" => &["poetry.lock", "uv.lock"],
+        "Pipfile" => &["Pipfile.lock"],
+        "requirements.in" => &["requirements.txt"],
+        "Cargo.toml" => &["Cargo.lock"],
+        _ => &[],
+    }
+}
+
+/// Name a package is compared by (normalized for PyPI)
+fn key(ecosystem: Ecosystem, name: &str) -> String {
+    match ecosystem {
+        Ecosystem::Python => normalize_pypi_name(name),
+        _ => name.to_string(),
+    }
+}
+
+/// Whether a locked package is built from the project rather than fetched
+/// (no checksum, no download URL)
+fn is_local(record: &DependencyRecord) -> bool {
+    record.integrity.is_none()
+        && record
+            .resolved
+            .as_deref()
+            .is_none_or(|resolved| !resolved.contains("://"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Symbol;
+    use std::sync::Arc;
+
+    fn record(name: &str, version: &str, file: &str, file_type: FileType) -> DependencyRecord {
+        DependencyRecord {
+            name: name.into(),
+            version: version.into(),
+            source_file: Arc::from(Path::new(file)),
+            dep_type: DependencyType::Runtime,
+            ecosystem: Ecosystem::Node,
+            file_type,
+            alias: None,
+            source_kind: SourceKind::Registry,
+            resolved: None,
+            integrity: Some("sha512-x".to_string()),
+            dependencies: Vec::new(),
+            span: None,
+        }
+    }
+
+    fn locked(name: &str, version: &str, dependencies: &[&str]) -> DependencyRecord {
+        let mut record = record(name, version, "/app/package-lock.json", FileType::Lockfile);
+        record.dependencies = dependencies.iter().map(|d| Symbol::from(*d)).collect();
+        record
+    }
+
+    #[test]
+    fn test_check_lockfile_drift() {
+        let declared =
+            |name: &str, range: &str| record(name, range, "/app/package.json", FileType::Manifest);
+        let mut app = locked("app", "1.0.0", &["express"]);
+        app.integrity = None;
+        let records = vec![
+            declared("express", "^4.18.0"),
+            declared("lodash", "^4.17.21"),
+            declared("axios", "^1.6.0"),
+            locked("express", "4.18.2", &["qs"]),
+            locked("qs", "6.11.0", &[]),
+            locked("axios", "0.27.2", &[]),
+            locked("moment", "2.29.4", &[]),
+            app,
+            record(
+                "react",
+                "^18.2.0",
+                "/other/package.json",
+                FileType::Manifest,
+            ),
+        ];
+
+        let drift = LockfileDriftChecker::new().check(&records);
+
+        assert_eq!(drift.len(), 1);
+        assert_eq!(drift[0].application_root, PathBuf::from("/app"));
+        assert_eq!(drift[0].lockfile, PathBuf::from("/app/package-lock.json"));
+        let entries: Vec<(&str, DriftKind)> = drift[0]
+            .entries
+            .iter()
+            .map(|e| (e.name.as_str(), e.kind))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("axios", DriftKind::OutsideRange),
+                ("lodash", DriftKind::Unlocked),
+                ("moment", DriftKind::Undeclared),
+            ]
+        );
+        assert_eq!(drift[0].entries[0].locked, vec!["0.27.2"]);
+        assert_eq!(drift[0].entries[2].declared, None);
+    }
+
+    #[test]
+    fn test_python_names_are_normalized() {
+        let mut declared = record(
+            "Typing_Extensions",
+            ">=4.0",
+            "/app/pyproject.toml",
+            FileType::Manifest,
+        );
+        declared.ecosystem = Ecosystem::Python;
+        let mut locked = record(
+            "typing-extensions",
+            "4.9.0",
+            "/app/poetry.lock",
+            FileType::Lockfile,
+        );
+        locked.ecosystem = Ecosystem::Python;
+
+        assert!(LockfileDriftChecker::new()
+            .check(&[declared, locked])
+            .is_empty());
+    }
+}
@@ -0,0 +1,108 @@
+//! Detection of declared but not installed dependencies
+//!
+//! The inverse of [`PhantomDetector`](crate::analyzer::PhantomDetector): a
+//! package locked (SHOULD) or declared (CAN) by an application with no
+//! installed (HAS) copy anywhere under that application is flagged with
+//! `missing_installation`. This points at an incomplete or stale install.
+//!
+//! Only applications with at least one installed package of the same
+//! ecosystem are checked, so declared-only scans and applications that were
+//! never installed are not flagged wholesale. Entries need their application
+//! root assigned (see
+//! [`ApplicationLinker::assign_application_roots`](crate::analyzer::ApplicationLinker::assign_application_roots)).
+
+use crate::analyzer::phantom::package_key;
+use crate::models::{Classification, ClassifiedDependency, Ecosystem};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/// Detector for declared packages with no installed copy
+pub struct MissingInstallationDetector;
+
+impl MissingInstallationDetector {
+    /// Create a new MissingInstallationDetector
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Set `missing_installation` on declared entries that are not installed,
+    /// returning how many were flagged
+    pub fn mark_missing(&self, dependencies: &mut [ClassifiedDependency]) -> usize {
+        let mut installed: HashMap<(PathBuf, Ecosystem), HashSet<String>> = HashMap::new();
+        for dep in dependencies.iter() {
+            if let Some(root) = &dep.application_root {
+                if dep.has_classification(Classification::Has) {
+                    installed
+                        .entry((root.clone(), dep.ecosystem))
+                        .or_default()
+                        .insert(package_key(dep.ecosystem, &dep.name));
+                }
+            }
+        }
+
+        let mut count = 0;
+        for dep in dependencies.iter_mut() {
+            let Some(root) = &dep.application_root else {
+                continue;
+            };
+            if dep.has_classification(Classification::Has) {
+                continue;
+            }
+            let Some(names) = installed.get(&(root.clone(), dep.ecosystem)) else {
+                continue;
+            };
+            if !names.contains(&package_key(dep.ecosystem, &dep.name)) {
+                dep.missing_installation = true;
+                count += 1;
+            }
+        }
+        count
+    }
+}
+
+impl Default for MissingInstallationDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        name: &str,
+        classification: Classification,
+        root: &str,
+        ecosystem: Ecosystem,
+    ) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new(name.to_string(), ecosystem);
+        dep.add_classification(
+            classification,
+            "1.0.0".to_string(),
+            PathBuf::from(root).join("lockfile"),
+        );
+        dep.application_root = Some(PathBuf::from(root));
+        dep
+    }
+
+    #[test]
+    fn test_mark_missing() {
+        let mut deps = vec![
+            entry("react", Classification::Has, "/app", Ecosystem::Node),
+            entry("react", Classification::Should, "/app", Ecosystem::Node),
+            entry("lodash", Classification::Should, "/app", Ecosystem::Node),
+            entry("lodash", Classification::Can, "/app", Ecosystem::Node),
+            // Never installed, so not checked
+            entry("lodash", Classification::Should, "/other", Ecosystem::Node),
+            entry("Django", Classification::Has, "/app", Ecosystem::Python),
+            entry("django", Classification::Can, "/app", Ecosystem::Python),
+        ];
+
+        let count = MissingInstallationDetector::new().mark_missing(&mut deps);
+
+        assert_eq!(count, 2);
+        let missing: Vec<_> = deps.iter().map(|d| d.missing_installation).collect();
+        assert_eq!(missing, vec![false, false, true, true, false, false, false]);
+    }
+}
@@ -1,13 +1,75 @@
 //! Analyzer module for dependency classification and relationship building
 
+pub mod aggregator;
 pub mod app_linker;
+pub mod app_stats;
+pub mod bundled;
 pub mod classifier;
+pub mod confusion;
+pub mod consistency;
+pub mod content_hash;
+pub mod engines;
+pub mod graph;
+pub mod host_diff;
+pub mod integrity;
+pub mod inventory;
+pub mod lockfile_drift;
+pub mod missing;
+pub mod multiple_versions;
+pub mod overrides;
+pub mod peer_deps;
+pub mod phantom;
+pub mod policy;
+pub mod processes;
+pub mod reachability;
+pub mod registries;
+pub mod reverse_deps;
+pub mod risk;
+pub mod staleness;
+pub mod summarizer;
+pub mod suppression;
 pub mod tree_builder;
+pub mod tree_diff;
+pub mod typosquat;
 pub mod version_matcher;
 pub mod vuln_filter;
 
+pub use aggregator::Aggregator;
 pub use app_linker::ApplicationLinker;
+pub use app_stats::{
+    AppStatsAnalyzer, ApplicationStats, DuplicatePackage, DuplicateVersion, Subtree,
+};
+pub use bundled::{BundleDetector, BundleKind, BundledPackage};
 pub use classifier::Classifier;
+pub use confusion::{ConfusionRisk, DependencyConfusionDetector};
+pub use consistency::{ConsistencyChecker, InconsistencyKind, VersionInconsistency};
+pub use content_hash::ContentHasher;
+pub use engines::{EngineChecker, EngineIncompatibility};
+pub use graph::{DependencyGraph, GraphBuilder, GraphNode};
+pub use host_diff::{HostComparer, HostDiffKind, HostDifference};
+pub use integrity::IntegrityVerifier;
+pub use inventory::{
+    Inventory, InventoryEntry, InventoryMerger, InventoryOccurrence, InventorySource,
+};
+pub use lockfile_drift::{DriftEntry, DriftKind, LockfileDrift, LockfileDriftChecker};
+pub use missing::MissingInstallationDetector;
+pub use multiple_versions::{
+    InstalledVersion, MultipleVersions, MultipleVersionsDetector, VersionRequest,
+};
+pub use overrides::{OverriddenRequest, OverrideChecker, VersionOverride};
+pub use peer_deps::{PeerConflict, PeerConflictKind, PeerDependencyChecker, PeerRequest};
+pub use phantom::PhantomDetector;
+pub use policy::{ExitStatus, FailOn, Policy};
+pub use processes::{ProcessCorrelator, ProcessFiles};
+pub use reachability::{Reachability, ReachabilityAnalyzer};
+pub use registries::{Registry, RegistryConfig};
+pub use reverse_deps::{DependencyPath, PathStep, ReverseDependencyFinder};
+pub use risk::RiskFlagger;
+pub use staleness::{RegistryClient, RegistryMetadata, Staleness, StalenessChecker};
+pub use summarizer::Summarizer;
+pub use suppression::{Suppression, Suppressions};
 pub use tree_builder::TreeBuilder;
+pub use tree_diff::{ApplicationTreeDiff, ChangeKind, SubtreeChange, TreeDiffStatus, TreeDiffer};
+pub use typosquat::{TyposquatDetector, TyposquatKind, TyposquatWarning};
 pub use version_matcher::VersionMatcher;
-pub use vuln_filter::{InfectedPackageFilter, SecurityStatus};
+pub use vuln_filter::{InfectedPackageFilter, SecurityStatus, Severity};
@@ -0,0 +1,356 @@
+//! Packages installed at several versions within one application
+//!
+//! npm installs a second copy of a package under the dependent that needs an
+//! incompatible version (`node_modules/a/node_modules/b`), so one install tree
+//! can hold several versions of the same package. The
+//! [`MultipleVersionsDetector`] groups installed packages by the application
+//! owning their outermost `node_modules` (or by installation directory for
+//! other ecosystems) and reports every package found there at two or more
+//! versions, with where each version is installed and which ranges resolve
+//! to it:
+//!
+//! - ranges requested by installed packages, resolved like Node does, to the
+//!   copy in the nearest `node_modules` above the requesting package
+//! - ranges declared by the manifests of the application, resolved the same
+//!   way from the manifest's directory
+
+use crate::models::{DependencyRecord, DependencyType, Ecosystem, FileType, InstalledPackage};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// A range resolving to an installed version
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub struct VersionRequest {
+    /// Requesting package (`name@version`), or the manifest declaring the range
+    pub requested_by: String,
+
+    /// Requested range
+    pub range: String,
+}
+
+/// One of the installed versions of a package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct InstalledVersion {
+    /// Installed version
+    pub version: String,
+
+    /// Installation paths of the version
+    pub paths: Vec<PathBuf>,
+
+    /// Ranges resolving to the version
+    pub requested_by: Vec<VersionRequest>,
+}
+
+/// A package installed at several versions within one application
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct MultipleVersions {
+    /// Package name
+    pub name: String,
+
+    /// Ecosystem
+    pub ecosystem: Ecosystem,
+
+    /// Application root directory (the directory holding the outermost
+    /// `node_modules`, or the installation directory)
+    pub application_root: PathBuf,
+
+    /// Installed versions, in version string order
+    pub versions: Vec<InstalledVersion>,
+}
+
+/// Detector of packages installed at several versions
+pub struct MultipleVersionsDetector;
+
+impl MultipleVersionsDetector {
+    /// Create a new MultipleVersionsDetector
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Find packages installed at several versions, ordered by application
+    /// and package
+    pub fn detect(
+        &self,
+        records: &[DependencyRecord],
+        installed: &[InstalledPackage],
+    ) -> Vec<MultipleVersions> {
+        let mut groups: BTreeMap<(PathBuf, Ecosystem), Vec<&InstalledPackage>> = BTreeMap::new();
+        for package in installed {
+            let Some(root) = application_root(&package.path) else {
+                continue;
+            };
+            groups
+                .entry((root.to_path_buf(), package.ecosystem))
+                .or_default()
+                .push(package);
+        }
+
+        let mut results = Vec::new();
+        for ((root, ecosystem), packages) in groups {
+            let mut by_name: BTreeMap<&str, Vec<&InstalledPackage>> = BTreeMap::new();
+            for package in &packages {
+                by_name.entry(&package.name).or_default().push(package);
+            }
+            for (name, copies) in by_name {
+                let versions: BTreeSet<&str> = copies.iter().map(|p| p.version.as_str()).collect();
+                if versions.len() < 2 {
+                    continue;
+                }
+                results.push(self.describe(name, ecosystem, &root, &copies, &packages, records));
+            }
+        }
+        results
+    }
+
+    /// List the versions of one package with their paths and requests
+    fn describe(
+        &self,
+        name: &str,
+        ecosystem: Ecosystem,
+        root: &Path,
+        copies: &[&InstalledPackage],
+        packages: &[&InstalledPackage],
+        records: &[DependencyRecord],
+    ) -> MultipleVersions {
+        let mut versions: BTreeMap<&str, (BTreeSet<PathBuf>, BTreeSet<VersionRequest>)> =
+            BTreeMap::new();
+        for copy in copies {
+            versions
+                .entry(&copy.version)
+                .or_default()
+                .0
+                .insert(copy.path.clone());
+        }
+
+        let requested = packages.iter().filter_map(|p| {
+            let spec = p.find_dependency(name)?;
+            let request = VersionRequest {
+                requested_by: format!("{}@{}", p.name, p.version),
+                range: spec.version_constraint.trim().to_string(),
+            };
+            Some((p.path.as_path(), request))
+        });
+        let declared = records
+            .iter()
+            .filter(|r| {
+                r.name == *name
+                    && r.ecosystem == ecosystem
+                    && r.file_type == FileType::Manifest
+                    && r.dep_type != DependencyType::Override
+                    && r.source_file.starts_with(root)
+            })
+            .filter_map(|r| {
+                let request = VersionRequest {
+                    requested_by: r.source_file.display().to_string(),
+                    range: r.version.trim().to_string(),
+                };
+                Some((r.source_file.parent()?, request))
+            });
+        for (from, request) in requested.chain(declared) {
+            for copy in resolve(from, copies) {
+                if let Some((_, requests)) = versions.get_mut(copy.version.as_str()) {
+                    requests.insert(request.clone());
+                }
+            }
+        }
+
+        MultipleVersions {
+            name: name.to_string(),
+            ecosystem,
+            application_root: root.to_path_buf(),
+            versions: versions
+                .into_iter()
+                .map(|(version, (paths, requests))| InstalledVersion {
+                    version: version.to_string(),
+                    paths: paths.into_iter().collect(),
+                    requested_by: requests.into_iter().collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Default for MultipleVersionsDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Check if a directory is a `node_modules` directory
+fn is_node_modules(dir: &Path) -> bool {
+    dir.file_name().is_some_and(|name| name == "node_modules")
+}
+
+/// Directory holding the outermost `node_modules` of an installed package,
+/// or its installation directory
+pub(crate) fn application_root(path: &Path) -> Option<&Path> {
+    match path.ancestors().filter(|dir| is_node_modules(dir)).last() {
+        Some(modules) => modules.parent(),
+        None => path.parent(),
+    }
+}
+
+/// Directory a copy is visible from, with everything below it: the one
+/// holding its nearest `node_modules`, or its installation directory
+fn scope(path: &Path) -> Option<&Path> {
+    match path.ancestors().skip(1).find(|dir| is_node_modules(dir)) {
+        Some(modules) => modules.parent(),
+        None => path.parent(),
+    }
+}
+
+/// Copies a request from a directory resolves to: those in the nearest
+/// scope enclosing the directory
+pub(crate) fn resolve<'a>(
+    from: &Path,
+    copies: &[&'a InstalledPackage],
+) -> Vec<&'a InstalledPackage> {
+    let Some(nearest) = copies
+        .iter()
+        .filter_map(|copy| scope(&copy.path))
+        .filter(|scope| from.starts_with(scope))
+        .max_by_key(|scope| scope.components().count())
+    else {
+        return Vec::new();
+    };
+    copies
+        .iter()
+        .copied()
+        .filter(|copy| scope(&copy.path) == Some(nearest))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SourceKind;
+    use std::sync::Arc;
+
+    fn installed(
+        name: &str,
+        version: &str,
+        path: &str,
+        dependencies: &[(&str, &str)],
+    ) -> InstalledPackage {
+        let mut package = InstalledPackage::new(
+            name.to_string(),
+            version.to_string(),
+            PathBuf::from(path),
+            Ecosystem::Node,
+        );
+        for (dep, range) in dependencies {
+            package.add_dependency(dep.to_string(), range.to_string());
+        }
+        package
+    }
+
+    fn declared(name: &str, range: &str, file: &str) -> DependencyRecord {
+        DependencyRecord {
+            name: name.into(),
+            version: range.into(),
+            source_file: Arc::from(Path::new(file)),
+            dep_type: DependencyType::Runtime,
+            ecosystem: Ecosystem::Node,
+            file_type: FileType::Manifest,
+            alias: None,
+            source_kind: SourceKind::Registry,
+            resolved: None,
+            integrity: None,
+            dependencies: Vec::new(),
+            span: None,
+        }
+    }
+
+    fn request(requested_by: &str, range: &str) -> VersionRequest {
+        VersionRequest {
+            requested_by: requested_by.to_string(),
+            range: range.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_detect_nested_versions() {
+        let installed = vec![
+            installed("qs", "6.11.0", "/app/node_modules/qs", &[]),
+            installed(
+                "body-parser",
+                "1.20.1",
+                "/app/node_modules/body-parser",
+                &[("qs", "^6.11.0")],
+            ),
+            installed(
+                "request",
+                "2.88.2",
+                "/app/node_modules/request",
+                &[("qs", "~6.5.2")],
+            ),
+            installed(
+                "qs",
+                "6.5.3",
+                "/app/node_modules/request/node_modules/qs",
+                &[],
+            ),
+            installed("@scope/util", "1.0.0", "/app/node_modules/@scope/util", &[]),
+            installed("lodash", "4.17.21", "/other/node_modules/lodash", &[]),
+        ];
+        let records = vec![declared("qs", "^6.11.0", "/app/package.json")];
+
+        let found = MultipleVersionsDetector::new().detect(&records, &installed);
+
+        assert_eq!(found.len(), 1);
+        let qs = &found[0];
+        assert_eq!(qs.name, "qs");
+        assert_eq!(qs.application_root, PathBuf::from("/app"));
+        assert_eq!(qs.versions.len(), 2);
+        assert_eq!(qs.versions[0].version, "6.11.0");
+        assert_eq!(
+            qs.versions[0].requested_by,
+            vec![
+                request("/app/package.json", "^6.11.0"),
+                request("body-parser@1.20.1", "^6.11.0"),
+            ]
+        );
+        assert_eq!(qs.versions[1].version, "6.5.3");
+        assert_eq!(
+            qs.versions[1].paths,
+            vec![PathBuf::from("/app/node_modules/request/node_modules/qs")]
+        );
+        assert_eq!(
+            qs.versions[1].requested_by,
+            vec![request("request@2.88.2", "~6.5.2")]
+        );
+    }
+
+    #[test]
+    fn test_same_version_twice_is_not_flagged() {
+        let installed = vec![
+            installed("qs", "6.11.0", "/app/node_modules/qs", &[]),
+            installed(
+                "qs",
+                "6.11.0",
+                "/app/node_modules/request/node_modules/qs",
+                &[],
+            ),
+        ];
+
+        assert!(MultipleVersionsDetector::new()
+            .detect(&[], &installed)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_scope() {
+        assert_eq!(
+            scope(Path::new("/app/node_modules/@scope/util")),
+            Some(Path::new("/app"))
+        );
+        assert_eq!(
+            application_root(Path::new("/app/node_modules/a/node_modules/b")),
+            Some(Path::new("/app"))
+        );
+    }
+}
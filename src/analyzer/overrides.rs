@@ -0,0 +1,376 @@
+//! Effect of version overrides on the dependency graph
+//!
+//! npm `overrides`, Yarn `resolutions` and pnpm `pnpm.overrides` force a
+//! package to a version whatever range its dependents request. The
+//! [`OverrideChecker`] compares each override with what the application would
+//! otherwise resolve: the ranges declared for the package in the manifests
+//! under the overriding one and the ranges requested by packages installed
+//! there. A [`VersionOverride`] lists the requests the forced versions do not
+//! satisfy, so an override moving a package outside its dependents' ranges
+//! stands out, and [`OverrideChecker::mark_infected`] flags overrides pinning
+//! a version on the infected list.
+//!
+//! The forced version is the override's version when it is exact; for a
+//! range, it is each locked or installed version of the application within
+//! the range.
+
+use crate::analyzer::{InfectedPackageFilter, VersionMatcher};
+use crate::models::{
+    Classification, ClassifiedDependency, DependencyRecord, DependencyType, Ecosystem, FileType,
+    InstalledPackage,
+};
+use crate::version::AnyVersion;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// A version override and the requests it overrules
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct VersionOverride {
+    /// Package name
+    pub name: String,
+
+    /// Ecosystem
+    pub ecosystem: Ecosystem,
+
+    /// Application root directory (the directory of the overriding manifest)
+    pub application_root: PathBuf,
+
+    /// Manifest declaring the override
+    pub source_file: PathBuf,
+
+    /// Version or range the override sets
+    pub version: String,
+
+    /// Versions the override forces
+    pub forced: Vec<String>,
+
+    /// Locked versions (SHOULD)
+    pub locked: Vec<String>,
+
+    /// Installed versions (HAS)
+    pub installed: Vec<String>,
+
+    /// Requests the forced versions do not satisfy
+    pub overridden: Vec<OverriddenRequest>,
+
+    /// Whether a forced version is on the infected list
+    #[serde(default)]
+    pub infected: bool,
+}
+
+impl VersionOverride {
+    /// Whether the override forces a version its dependents do not request
+    pub fn changes_resolution(&self) -> bool {
+        !self.overridden.is_empty()
+    }
+}
+
+/// A version range an override overrules
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub struct OverriddenRequest {
+    /// Requesting package (`name@version`), or the manifest declaring the range
+    pub requested_by: String,
+
+    /// Requested range
+    pub range: String,
+}
+
+/// Checker comparing overrides with the ranges they overrule
+pub struct OverrideChecker {
+    version_matcher: VersionMatcher,
+}
+
+impl OverrideChecker {
+    /// Create a new OverrideChecker
+    pub fn new() -> Self {
+        Self {
+            version_matcher: VersionMatcher::new(),
+        }
+    }
+
+    /// Analyze override records against the other records and installed
+    /// packages, ordered by application and package
+    pub fn check(
+        &self,
+        overrides: &[DependencyRecord],
+        records: &[DependencyRecord],
+        installed: &[InstalledPackage],
+    ) -> Vec<VersionOverride> {
+        let mut results: Vec<VersionOverride> = overrides
+            .iter()
+            .filter(|o| o.dep_type == DependencyType::Override)
+            .map(|o| self.check_override(o, records, installed))
+            .collect();
+        results.sort_by(|a, b| {
+            (&a.application_root, &a.name, &a.source_file).cmp(&(
+                &b.application_root,
+                &b.name,
+                &b.source_file,
+            ))
+        });
+        results
+    }
+
+    /// Analyze one override
+    fn check_override(
+        &self,
+        record: &DependencyRecord,
+        records: &[DependencyRecord],
+        installed: &[InstalledPackage],
+    ) -> VersionOverride {
+        let ecosystem = record.ecosystem;
+        let root = record.source_file.parent().unwrap_or(Path::new(""));
+        let same_package = |r: &&DependencyRecord| {
+            r.name == record.name && r.ecosystem == ecosystem && r.source_file.starts_with(root)
+        };
+
+        let locked: BTreeSet<String> = records
+            .iter()
+            .filter(same_package)
+            .filter(|r| r.file_type == FileType::Lockfile)
+            .map(|r| r.version.trim().to_string())
+            .collect();
+        let in_app: Vec<&InstalledPackage> = installed
+            .iter()
+            .filter(|p| p.ecosystem == ecosystem && p.path.starts_with(root))
+            .collect();
+        let installed_versions: BTreeSet<String> = in_app
+            .iter()
+            .filter(|p| p.name == *record.name)
+            .map(|p| p.version.trim().to_string())
+            .collect();
+
+        let version = record.version.trim();
+        let forced: Vec<String> = if AnyVersion::parse(version, ecosystem).is_ok() {
+            vec![version.to_string()]
+        } else {
+            locked
+                .union(&installed_versions)
+                .filter(|v| {
+                    matches!(
+                        self.version_matcher.satisfies_all(v, version, ecosystem),
+                        Ok(true)
+                    )
+                })
+                .cloned()
+                .collect()
+        };
+
+        // Ranges declared in the manifests and requested by installed packages
+        let declared = records
+            .iter()
+            .filter(same_package)
+            .filter(|r| r.file_type == FileType::Manifest)
+            .filter(|r| r.dep_type != DependencyType::Override && r.source_kind.is_registry())
+            .map(|r| OverriddenRequest {
+                requested_by: r.source_file.display().to_string(),
+                range: r.version.trim().to_string(),
+            });
+        let requested = in_app.iter().filter_map(|p| {
+            p.find_dependency(&record.name)
+                .map(|spec| OverriddenRequest {
+                    requested_by: format!("{}@{}", p.name, p.version),
+                    range: spec.version_constraint.trim().to_string(),
+                })
+        });
+        let overridden: BTreeSet<OverriddenRequest> = declared
+            .chain(requested)
+            .filter(|request| {
+                forced.iter().any(|v| {
+                    matches!(
+                        self.version_matcher
+                            .satisfies_all(v, &request.range, ecosystem),
+                        Ok(false)
+                    )
+                })
+            })
+            .collect();
+
+        VersionOverride {
+            name: record.name.to_string(),
+            ecosystem,
+            application_root: root.to_path_buf(),
+            source_file: record.source_file.to_path_buf(),
+            version: version.to_string(),
+            forced,
+            locked: locked.into_iter().collect(),
+            installed: installed_versions.into_iter().collect(),
+            overridden: overridden.into_iter().collect(),
+            infected: false,
+        }
+    }
+
+    /// Set `infected` on overrides forcing a listed version, returning how many were flagged
+    pub fn mark_infected(
+        &self,
+        overrides: &mut [VersionOverride],
+        filter: &InfectedPackageFilter,
+    ) -> usize {
+        let mut flagged = 0;
+        for entry in overrides.iter_mut() {
+            entry.infected = entry.forced.iter().any(|version| {
+                let mut dep = ClassifiedDependency::new(entry.name.clone(), entry.ecosystem);
+                dep.add_classification(
+                    Classification::Should,
+                    version.clone(),
+                    entry.source_file.clone(),
+                );
+                filter.is_infected(&dep)
+            });
+            flagged += usize::from(entry.infected);
+        }
+        flagged
+    }
+}
+
+impl Default for OverrideChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::vuln_filter::InfectedPackage;
+    use crate::models::SourceKind;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    fn record(name: &str, version: &str, file: &str, dep_type: DependencyType) -> DependencyRecord {
+        let file_type = if file.ends_with("package.json") {
+            FileType::Manifest
+        } else {
+            FileType::Lockfile
+        };
+        DependencyRecord {
+            name: name.into(),
+            version: version.into(),
+            source_file: Arc::from(Path::new(file)),
+            dep_type,
+            ecosystem: Ecosystem::Node,
+            file_type,
+            alias: None,
+            source_kind: SourceKind::Registry,
+            resolved: None,
+            integrity: None,
+            dependencies: Vec::new(),
+            span: None,
+        }
+    }
+
+    fn installed(name: &str, version: &str, dependencies: &[(&str, &str)]) -> InstalledPackage {
+        let mut package = InstalledPackage::new(
+            name.to_string(),
+            version.to_string(),
+            PathBuf::from(format!("/app/node_modules/{}", name)),
+            Ecosystem::Node,
+        );
+        for (dep, range) in dependencies {
+            package.add_dependency(dep.to_string(), range.to_string());
+        }
+        package
+    }
+
+    #[test]
+    fn test_override_outside_requested_ranges() {
+        let overrides = vec![
+            record(
+                "minimist",
+                "1.2.6",
+                "/app/package.json",
+                DependencyType::Override,
+            ),
+            record(
+                "ms",
+                "^2.1.0",
+                "/app/package.json",
+                DependencyType::Override,
+            ),
+        ];
+        let records = vec![
+            record(
+                "minimist",
+                "1.2.6",
+                "/app/package-lock.json",
+                DependencyType::Runtime,
+            ),
+            record(
+                "ms",
+                "2.1.3",
+                "/app/package-lock.json",
+                DependencyType::Runtime,
+            ),
+            record("ms", "^2.0.0", "/app/package.json", DependencyType::Runtime),
+            // Another application is not affected
+            record(
+                "minimist",
+                "0.0.8",
+                "/other/package-lock.json",
+                DependencyType::Runtime,
+            ),
+        ];
+        let installed = vec![
+            installed("mkdirp", "0.5.1", &[("minimist", "0.0.8")]),
+            installed("optimist", "0.6.1", &[("minimist", "~1.2.0")]),
+            installed("minimist", "1.2.6", &[]),
+        ];
+
+        let results = OverrideChecker::new().check(&overrides, &records, &installed);
+
+        assert_eq!(results.len(), 2);
+        let minimist = &results[0];
+        assert_eq!(minimist.name, "minimist");
+        assert_eq!(minimist.application_root, PathBuf::from("/app"));
+        assert_eq!(minimist.forced, vec!["1.2.6"]);
+        assert_eq!(minimist.locked, vec!["1.2.6"]);
+        assert_eq!(minimist.installed, vec!["1.2.6"]);
+        assert_eq!(
+            minimist.overridden,
+            vec![OverriddenRequest {
+                requested_by: "mkdirp@0.5.1".to_string(),
+                range: "0.0.8".to_string(),
+            }]
+        );
+        assert!(minimist.changes_resolution());
+
+        // A range override forces the locked versions within it
+        let ms = &results[1];
+        assert_eq!(ms.forced, vec!["2.1.3"]);
+        assert!(!ms.changes_resolution());
+    }
+
+    #[test]
+    fn test_mark_infected_overrides() {
+        let overrides = vec![
+            record(
+                "event-stream",
+                "3.3.6",
+                "/app/package.json",
+                DependencyType::Override,
+            ),
+            record(
+                "lodash",
+                "4.17.21",
+                "/app/package.json",
+                DependencyType::Override,
+            ),
+        ];
+        let mut results = OverrideChecker::new().check(&overrides, &[], &[]);
+
+        let mut filter = InfectedPackageFilter::new();
+        filter.add_infected_package(InfectedPackage::new(
+            "event-stream".to_string(),
+            HashSet::from(["3.3.6".to_string()]),
+        ));
+        let flagged = OverrideChecker::new().mark_infected(&mut results, &filter);
+
+        assert_eq!(flagged, 1);
+        assert!(results[0].infected);
+        assert!(!results[1].infected);
+    }
+}
@@ -0,0 +1,290 @@
+//! Peer dependency conflicts of Node applications
+//!
+//! A package's `peerDependencies` are not installed for it: the application
+//! has to provide them, and npm only warns (or, with `--legacy-peer-deps`,
+//! stays silent) when it does not. The [`PeerDependencyChecker`] resolves
+//! each peer the way Node would from the requesting package, to the copy in
+//! the nearest `node_modules` above it, and reports per application and peer:
+//!
+//! - `missing`: a required peer is not installed at all
+//! - `unsatisfied`: the peer is installed but no requester's range accepts it
+//! - `conflicting`: some requesters accept the installed peer and others do
+//!   not, so no single version can please them all
+//!
+//! Optional peers (`peerDependenciesMeta`) may be absent but must match when
+//! installed. Ranges the version matcher cannot read are taken as satisfied.
+
+use crate::analyzer::multiple_versions::{application_root, resolve};
+use crate::analyzer::VersionMatcher;
+use crate::models::{Ecosystem, InstalledPackage};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Kind of peer dependency conflict
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerConflictKind {
+    /// A required peer is not installed
+    Missing,
+    /// No requester accepts the installed peer
+    Unsatisfied,
+    /// Requesters disagree on the installed peer
+    Conflicting,
+}
+
+impl fmt::Display for PeerConflictKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PeerConflictKind::Missing => write!(f, "missing"),
+            PeerConflictKind::Unsatisfied => write!(f, "unsatisfied"),
+            PeerConflictKind::Conflicting => write!(f, "conflicting"),
+        }
+    }
+}
+
+/// A package requiring a peer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PeerRequest {
+    /// Requesting package (`name@version`)
+    pub requested_by: String,
+
+    /// Required range
+    pub range: String,
+
+    /// Whether the peer may be absent
+    pub optional: bool,
+
+    /// Version the peer resolves to from the requesting package
+    pub resolved: Option<String>,
+
+    /// Whether the resolved version satisfies the range (or the optional
+    /// peer is absent)
+    pub satisfied: bool,
+}
+
+/// Unmet or conflicting requirements of one peer within an application
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PeerConflict {
+    /// Peer package name
+    pub name: String,
+
+    /// Ecosystem
+    pub ecosystem: Ecosystem,
+
+    /// Application root directory (the directory holding the outermost
+    /// `node_modules`)
+    pub application_root: PathBuf,
+
+    /// What is wrong
+    pub kind: PeerConflictKind,
+
+    /// Every package requiring the peer, ordered by requester
+    pub requests: Vec<PeerRequest>,
+}
+
+/// Checker of the peer dependencies of installed Node packages
+pub struct PeerDependencyChecker {
+    version_matcher: VersionMatcher,
+}
+
+impl PeerDependencyChecker {
+    /// Create a new PeerDependencyChecker
+    pub fn new() -> Self {
+        Self {
+            version_matcher: VersionMatcher::new(),
+        }
+    }
+
+    /// Find unmet or conflicting peer requirements, ordered by application
+    /// and peer
+    pub fn check(&self, installed: &[InstalledPackage]) -> Vec<PeerConflict> {
+        let mut copies: BTreeMap<(PathBuf, &str), Vec<&InstalledPackage>> = BTreeMap::new();
+        for package in installed.iter().filter(|p| p.ecosystem == Ecosystem::Node) {
+            if let Some(root) = application_root(&package.path) {
+                copies
+                    .entry((root.to_path_buf(), &package.name))
+                    .or_default()
+                    .push(package);
+            }
+        }
+
+        let mut requests: BTreeMap<(PathBuf, &str), Vec<PeerRequest>> = BTreeMap::new();
+        for package in installed.iter().filter(|p| p.ecosystem == Ecosystem::Node) {
+            let Some(root) = application_root(&package.path) else {
+                continue;
+            };
+            for peer in &package.peer_dependencies {
+                let key = (root.to_path_buf(), peer.name.as_str());
+                let candidates = copies.get(&key).map(Vec::as_slice).unwrap_or_default();
+                let resolved = resolve(&package.path, candidates)
+                    .first()
+                    .map(|copy| copy.version.clone());
+                let range = peer.version_constraint.trim();
+                let satisfied = match &resolved {
+                    Some(version) => !matches!(
+                        self.version_matcher
+                            .satisfies_all(version, range, Ecosystem::Node),
+                        Ok(false)
+                    ),
+                    None => peer.optional,
+                };
+                requests.entry(key).or_default().push(PeerRequest {
+                    requested_by: format!("{}@{}", package.name, package.version),
+                    range: range.to_string(),
+                    optional: peer.optional,
+                    resolved,
+                    satisfied,
+                });
+            }
+        }
+
+        requests
+            .into_iter()
+            .filter_map(|((root, name), mut requests)| {
+                let unmet = requests.iter().filter(|r| !r.satisfied).count();
+                let kind = if unmet == 0 {
+                    return None;
+                } else if requests.iter().all(|r| r.resolved.is_none()) {
+                    PeerConflictKind::Missing
+                } else if unmet == requests.len() {
+                    PeerConflictKind::Unsatisfied
+                } else {
+                    PeerConflictKind::Conflicting
+                };
+                requests.sort_by(|a, b| a.requested_by.cmp(&b.requested_by));
+                Some(PeerConflict {
+                    name: name.to_string(),
+                    ecosystem: Ecosystem::Node,
+                    application_root: root,
+                    kind,
+                    requests,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Default for PeerDependencyChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PeerDependency;
+
+    fn installed(
+        name: &str,
+        version: &str,
+        path: &str,
+        peers: &[(&str, &str)],
+    ) -> InstalledPackage {
+        let mut package = InstalledPackage::new(
+            name.to_string(),
+            version.to_string(),
+            PathBuf::from(path),
+            Ecosystem::Node,
+        );
+        package.peer_dependencies = peers
+            .iter()
+            .map(|(peer, range)| PeerDependency {
+                name: peer.to_string(),
+                version_constraint: range.to_string(),
+                optional: false,
+            })
+            .collect();
+        package
+    }
+
+    #[test]
+    fn test_check_peer_dependencies() {
+        let mut optional = installed("ui-kit", "1.0.0", "/app/node_modules/ui-kit", &[]);
+        optional.peer_dependencies.push(PeerDependency {
+            name: "vue".to_string(),
+            version_constraint: "^3".to_string(),
+            optional: true,
+        });
+        let installed = vec![
+            installed("react", "17.0.2", "/app/node_modules/react", &[]),
+            installed(
+                "react-dom",
+                "17.0.2",
+                "/app/node_modules/react-dom",
+                &[("react", "17.0.2")],
+            ),
+            installed(
+                "@testing-library/react",
+                "14.0.0",
+                "/app/node_modules/@testing-library/react",
+                &[("react", "^18.0.0")],
+            ),
+            installed(
+                "eslint-plugin-x",
+                "2.0.0",
+                "/app/node_modules/eslint-plugin-x",
+                &[("eslint", ">=8")],
+            ),
+            installed("redux", "5.0.0", "/app/node_modules/redux", &[]),
+            installed(
+                "react-redux",
+                "9.0.0",
+                "/app/node_modules/react-redux",
+                &[("redux", "^4.0.0")],
+            ),
+            optional,
+        ];
+
+        let conflicts = PeerDependencyChecker::new().check(&installed);
+
+        let kinds: Vec<(&str, PeerConflictKind)> = conflicts
+            .iter()
+            .map(|c| (c.name.as_str(), c.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("eslint", PeerConflictKind::Missing),
+                ("react", PeerConflictKind::Conflicting),
+                ("redux", PeerConflictKind::Unsatisfied),
+            ]
+        );
+        let react = &conflicts[1];
+        assert_eq!(react.application_root, PathBuf::from("/app"));
+        assert_eq!(react.requests.len(), 2);
+        assert_eq!(
+            react.requests[0].requested_by,
+            "@testing-library/react@14.0.0"
+        );
+        assert_eq!(react.requests[0].resolved.as_deref(), Some("17.0.2"));
+        assert!(!react.requests[0].satisfied);
+        assert!(react.requests[1].satisfied);
+    }
+
+    #[test]
+    fn test_peer_resolved_from_nested_copy() {
+        let installed = vec![
+            installed("react", "17.0.2", "/app/node_modules/react", &[]),
+            installed(
+                "react",
+                "18.2.0",
+                "/app/node_modules/widget/node_modules/react",
+                &[],
+            ),
+            installed(
+                "react-dom",
+                "18.2.0",
+                "/app/node_modules/widget/node_modules/react-dom",
+                &[("react", "^18.2.0")],
+            ),
+        ];
+
+        assert!(PeerDependencyChecker::new().check(&installed).is_empty());
+    }
+}
@@ -0,0 +1,223 @@
+//! Detection of undeclared ("phantom") installed dependencies
+//!
+//! A package installed in an application's `node_modules` or site-packages
+//! that none of the application's lockfiles or manifests account for points
+//! at a manual install or tampering. The [`PhantomDetector`] sets
+//! `is_phantom` on such HAS entries:
+//!
+//! - when the application has a lockfile, a package is accounted for if it is
+//!   locked or declared
+//! - without a lockfile, a package is accounted for if it is declared or
+//!   required by another package installed under the application
+//!
+//! Packages nested inside another package's `node_modules` are installed for
+//! their parent and never flagged, nor are Python's packaging tools (`pip`,
+//! `setuptools`, `wheel`). Entries need their application root assigned (see
+//! [`ApplicationLinker::assign_application_roots`](crate::analyzer::ApplicationLinker::assign_application_roots)).
+
+use crate::models::purl::normalize_pypi_name;
+use crate::models::{Classification, ClassifiedDependency, Ecosystem};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+/// Python packages installed into every environment
+const PYTHON_BOOTSTRAP_PACKAGES: &[&str] = &["pip", "setuptools", "wheel", "distribute"];
+
+/// Names an application's lockfiles and manifests account for
+#[derive(Default)]
+struct Declared {
+    locked: HashSet<String>,
+    declared: HashSet<String>,
+}
+
+/// Detector for installed packages no manifest or lockfile accounts for
+pub struct PhantomDetector;
+
+impl PhantomDetector {
+    /// Create a new PhantomDetector
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Set `is_phantom` on undeclared installed entries, returning how many were flagged
+    pub fn mark_phantoms(&self, dependencies: &mut [ClassifiedDependency]) -> usize {
+        let mut declared: HashMap<(PathBuf, Ecosystem), Declared> = HashMap::new();
+        for dep in dependencies.iter() {
+            let Some(root) = &dep.application_root else {
+                continue;
+            };
+            let entry = declared.entry((root.clone(), dep.ecosystem)).or_default();
+            let key = package_key(dep.ecosystem, &dep.name);
+            if dep.has_classification(Classification::Should) {
+                entry.locked.insert(key.clone());
+            }
+            if dep.has_classification(Classification::Can) {
+                entry.declared.insert(key);
+            }
+        }
+
+        // Requirements of installed packages, for applications without a lockfile
+        let installed: Vec<(&Path, Ecosystem, &[String])> = dependencies
+            .iter()
+            .filter_map(|dep| {
+                dep.installed_path
+                    .as_deref()
+                    .map(|path| (path, dep.ecosystem, dep.dependencies.as_slice()))
+            })
+            .collect();
+        let mut required: HashMap<(PathBuf, Ecosystem), HashSet<String>> = HashMap::new();
+
+        let mut phantoms = Vec::new();
+        for (index, dep) in dependencies.iter().enumerate() {
+            let (Some(root), Some(path)) = (&dep.application_root, &dep.installed_path) else {
+                continue;
+            };
+            if !dep.has_classification(Classification::Has) || is_nested(path) {
+                continue;
+            }
+            if dep.ecosystem == Ecosystem::Python
+                && PYTHON_BOOTSTRAP_PACKAGES.contains(&dep.name.to_lowercase().as_str())
+            {
+                continue;
+            }
+
+            let key = package_key(dep.ecosystem, &dep.name);
+            let group = (root.clone(), dep.ecosystem);
+            let names = declared.get(&group);
+            let accounted = match names {
+                Some(names) if !names.locked.is_empty() => {
+                    names.locked.contains(&key) || names.declared.contains(&key)
+                }
+                _ => {
+                    names.is_some_and(|names| names.declared.contains(&key))
+                        || required
+                            .entry(group)
+                            .or_insert_with(|| requirements_under(&installed, root, dep.ecosystem))
+                            .contains(&key)
+                }
+            };
+            if !accounted {
+                phantoms.push(index);
+            }
+        }
+
+        for &index in &phantoms {
+            dependencies[index].is_phantom = true;
+        }
+        phantoms.len()
+    }
+}
+
+impl Default for PhantomDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Name used for matching (PyPI names are normalized)
+pub(crate) fn package_key(ecosystem: Ecosystem, name: &str) -> String {
+    match ecosystem {
+        Ecosystem::Python => normalize_pypi_name(name),
+        _ => name.to_string(),
+    }
+}
+
+/// Whether a package is installed inside another package's `node_modules`
+fn is_nested(path: &Path) -> bool {
+    path.components()
+        .filter(|c| matches!(c, Component::Normal(name) if *name == "node_modules"))
+        .count()
+        > 1
+}
+
+/// Names required by packages installed under an application root
+fn requirements_under(
+    installed: &[(&Path, Ecosystem, &[String])],
+    root: &Path,
+    ecosystem: Ecosystem,
+) -> HashSet<String> {
+    installed
+        .iter()
+        .filter(|(path, eco, _)| *eco == ecosystem && path.starts_with(root))
+        .flat_map(|(_, _, requires)| requires.iter())
+        .map(|name| package_key(ecosystem, name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn installed(name: &str, ecosystem: Ecosystem, path: &str) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new(name.to_string(), ecosystem);
+        dep.add_classification(
+            Classification::Has,
+            "1.0.0".to_string(),
+            PathBuf::from(path),
+        );
+        dep.installed_path = Some(PathBuf::from(path));
+        dep.application_root = Some(PathBuf::from("/app"));
+        dep
+    }
+
+    fn declared(
+        name: &str,
+        ecosystem: Ecosystem,
+        classification: Classification,
+    ) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new(name.to_string(), ecosystem);
+        dep.add_classification(
+            classification,
+            "1.0.0".to_string(),
+            PathBuf::from("/app/manifest"),
+        );
+        dep.application_root = Some(PathBuf::from("/app"));
+        dep
+    }
+
+    #[test]
+    fn test_with_lockfile() {
+        let mut deps = vec![
+            installed("react", Ecosystem::Node, "/app/node_modules/react"),
+            installed("backdoor", Ecosystem::Node, "/app/node_modules/backdoor"),
+            installed(
+                "ms",
+                Ecosystem::Node,
+                "/app/node_modules/debug/node_modules/ms",
+            ),
+            declared("react", Ecosystem::Node, Classification::Should),
+        ];
+
+        let count = PhantomDetector::new().mark_phantoms(&mut deps);
+
+        assert_eq!(count, 1);
+        assert!(!deps[0].is_phantom);
+        assert!(deps[1].is_phantom);
+        // Nested installs belong to their parent package
+        assert!(!deps[2].is_phantom);
+    }
+
+    #[test]
+    fn test_without_lockfile_uses_requirements() {
+        let site = "/app/.venv/lib/python3.12/site-packages";
+        let mut requests = installed("requests", Ecosystem::Python, &format!("{}/requests", site));
+        requests.dependencies.push("charset-normalizer".to_string());
+        let mut deps = vec![
+            requests,
+            installed(
+                "charset_normalizer",
+                Ecosystem::Python,
+                &format!("{}/charset_normalizer", site),
+            ),
+            installed("pip", Ecosystem::Python, &format!("{}/pip", site)),
+            installed("colorama", Ecosystem::Python, &format!("{}/colorama", site)),
+            declared("Requests", Ecosystem::Python, Classification::Can),
+        ];
+
+        let count = PhantomDetector::new().mark_phantoms(&mut deps);
+
+        assert_eq!(count, 1);
+        assert!(deps[3].is_phantom);
+        assert!(!deps[0].is_phantom && !deps[1].is_phantom && !deps[2].is_phantom);
+    }
+}
@@ -0,0 +1,302 @@
+//! Failure policy and exit status for CI gating
+//!
+//! A [`Policy`] lists the findings that should fail a scan ([`FailOn`]) and
+//! maps a scan's results to an [`ExitStatus`], so CI can gate on the process
+//! exit code without parsing output files.
+//!
+//! | Code | Status                                       |
+//! |------|----------------------------------------------|
+//! | 0    | Clean                                        |
+//! | 1    | Scan errors (or parse errors in strict mode) |
+//! | 2    | Infected packages found                      |
+//! | 3    | Policy violations                            |
+
+use crate::analyzer::{
+    InconsistencyKind, InfectedPackageFilter, SecurityStatus, Suppressions, VersionInconsistency,
+};
+use crate::models::{ClassifiedDependency, ScanError};
+use std::fmt;
+use std::str::FromStr;
+
+/// A finding that fails the scan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FailOn {
+    /// An installed or locked version is on the infected list
+    Infected,
+    /// A declared range could resolve to an infected version
+    MatchVersion,
+    /// An installed version differs from the locked version
+    Mismatch,
+}
+
+impl FromStr for FailOn {
+    type Err = ScanError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "infected" => Ok(FailOn::Infected),
+            "match-version" => Ok(FailOn::MatchVersion),
+            "mismatch" => Ok(FailOn::Mismatch),
+            _ => Err(ScanError::InvalidPolicy(format!(
+                "unknown --fail-on condition '{}', use: infected, match-version or mismatch",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for FailOn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailOn::Infected => write!(f, "infected"),
+            FailOn::MatchVersion => write!(f, "match-version"),
+            FailOn::Mismatch => write!(f, "mismatch"),
+        }
+    }
+}
+
+/// Outcome of a scan as a process exit status
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    /// No failing findings
+    Clean,
+    /// The scan failed or was incomplete
+    ScanErrors,
+    /// A policy condition other than infected packages was met
+    PolicyViolation,
+    /// Infected packages were found
+    Infected,
+}
+
+impl ExitStatus {
+    /// Process exit code for the status
+    pub fn code(&self) -> u8 {
+        match self {
+            ExitStatus::Clean => 0,
+            ExitStatus::ScanErrors => 1,
+            ExitStatus::Infected => 2,
+            ExitStatus::PolicyViolation => 3,
+        }
+    }
+
+    /// Whether the status fails the scan
+    pub fn is_failure(&self) -> bool {
+        *self != ExitStatus::Clean
+    }
+}
+
+/// Findings that fail a scan
+///
+/// An empty policy never fails on findings; only scan errors are reported.
+/// Findings covered by a suppression never fail the scan.
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    fail_on: Vec<FailOn>,
+    suppressions: Suppressions,
+}
+
+impl Policy {
+    /// Create a policy that fails on nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fail the scan on a finding
+    pub fn with_fail_on(mut self, condition: FailOn) -> Self {
+        if !self.fail_on.contains(&condition) {
+            self.fail_on.push(condition);
+        }
+        self
+    }
+
+    /// Ignore findings covered by a suppression
+    pub fn with_suppressions(mut self, suppressions: Suppressions) -> Self {
+        self.suppressions = suppressions;
+        self
+    }
+
+    /// Whether the policy fails on a finding
+    pub fn fails_on(&self, condition: FailOn) -> bool {
+        self.fail_on.contains(&condition)
+    }
+
+    /// Evaluate classified dependencies against the policy
+    ///
+    /// Infected packages take precedence over other violations. Security
+    /// conditions never match without an infected list.
+    pub fn evaluate(
+        &self,
+        dependencies: &[ClassifiedDependency],
+        filter: Option<&InfectedPackageFilter>,
+    ) -> ExitStatus {
+        let mut status = ExitStatus::Clean;
+
+        for dep in dependencies {
+            if self.suppressions.is_suppressed(dep) {
+                continue;
+            }
+            let security = filter
+                .map(|f| f.get_security_status(dep))
+                .unwrap_or(SecurityStatus::None);
+
+            if self.fails_on(FailOn::Infected) && security == SecurityStatus::Infected {
+                return ExitStatus::Infected;
+            }
+            if (self.fails_on(FailOn::MatchVersion) && security == SecurityStatus::MatchVersion)
+                || (self.fails_on(FailOn::Mismatch) && dep.has_version_mismatch)
+            {
+                status = ExitStatus::PolicyViolation;
+            }
+        }
+
+        status
+    }
+
+    /// Evaluate cross-source version inconsistencies against the policy
+    ///
+    /// `mismatch` also fails on installed versions that are not locked.
+    pub fn evaluate_inconsistencies(&self, inconsistencies: &[VersionInconsistency]) -> ExitStatus {
+        let mismatched = inconsistencies.iter().any(|i| {
+            i.kind == InconsistencyKind::VersionMismatch
+                && !self.suppressions.is_inconsistency_suppressed(i)
+        });
+        if self.fails_on(FailOn::Mismatch) && mismatched {
+            ExitStatus::PolicyViolation
+        } else {
+            ExitStatus::Clean
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::vuln_filter::InfectedPackage;
+    use crate::analyzer::Suppression;
+    use crate::models::{Classification, Ecosystem};
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+
+    fn dependency(
+        name: &str,
+        classification: Classification,
+        version: &str,
+    ) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new(name.to_string(), Ecosystem::Node);
+        dep.add_classification(classification, version.to_string(), PathBuf::from("/app"));
+        dep
+    }
+
+    fn infected_filter() -> InfectedPackageFilter {
+        let mut filter = InfectedPackageFilter::new();
+        filter.add_infected_package(InfectedPackage::new(
+            "evil".to_string(),
+            HashSet::from(["1.0.0".to_string()]),
+        ));
+        filter
+    }
+
+    #[test]
+    fn test_parse_fail_on() {
+        assert_eq!("infected".parse::<FailOn>().unwrap(), FailOn::Infected);
+        assert_eq!(
+            "match-version".parse::<FailOn>().unwrap(),
+            FailOn::MatchVersion
+        );
+        assert_eq!("mismatch".parse::<FailOn>().unwrap(), FailOn::Mismatch);
+        assert!(matches!(
+            "typo".parse::<FailOn>(),
+            Err(ScanError::InvalidPolicy(_))
+        ));
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let filter = infected_filter();
+        let mut mismatched = dependency("react", Classification::Has, "18.0.0");
+        mismatched.has_version_mismatch = true;
+        let infected = dependency("evil", Classification::Has, "1.0.0");
+
+        let policy = Policy::new().with_fail_on(FailOn::Mismatch);
+        assert_eq!(
+            policy.evaluate(std::slice::from_ref(&mismatched), Some(&filter)),
+            ExitStatus::PolicyViolation
+        );
+
+        // Findings not in the policy don't fail the scan
+        assert_eq!(
+            Policy::new().evaluate(&[mismatched.clone(), infected.clone()], Some(&filter)),
+            ExitStatus::Clean
+        );
+
+        // Infected takes precedence over other violations
+        let policy = policy.with_fail_on(FailOn::Infected);
+        assert_eq!(
+            policy.evaluate(&[mismatched, infected.clone()], Some(&filter)),
+            ExitStatus::Infected
+        );
+        assert_eq!(policy.evaluate(&[infected], None), ExitStatus::Clean);
+
+        let mismatch = VersionInconsistency {
+            name: "react".to_string(),
+            ecosystem: Ecosystem::Node,
+            application_root: PathBuf::from("/app"),
+            application_name: None,
+            kind: InconsistencyKind::VersionMismatch,
+            installed: vec!["18.2.0".to_string()],
+            locked: vec!["18.1.0".to_string()],
+            declared: vec![],
+        };
+        assert_eq!(
+            policy.evaluate_inconsistencies(std::slice::from_ref(&mismatch)),
+            ExitStatus::PolicyViolation
+        );
+        assert_eq!(
+            Policy::new().evaluate_inconsistencies(&[mismatch]),
+            ExitStatus::Clean
+        );
+        assert_eq!(ExitStatus::Infected.code(), 2);
+    }
+
+    #[test]
+    fn test_evaluate_with_suppressions() {
+        let infected = dependency("evil", Classification::Has, "1.0.0");
+        let mut mismatched = dependency("react", Classification::Has, "18.0.0");
+        mismatched.has_version_mismatch = true;
+
+        let mut suppressions = Suppressions::new();
+        suppressions.add(Suppression::accept(&infected, "sandboxed"));
+        suppressions.add(Suppression::accept(&mismatched, "upgrade pending"));
+        let filter = infected_filter().with_suppressions(suppressions.clone());
+        let policy = Policy::new()
+            .with_fail_on(FailOn::Infected)
+            .with_fail_on(FailOn::Mismatch)
+            .with_suppressions(suppressions);
+
+        assert_eq!(
+            policy.evaluate(&[infected.clone(), mismatched], Some(&infected_filter())),
+            ExitStatus::Clean
+        );
+        assert!(!filter.is_infected(&infected));
+
+        let mut annotated = infected;
+        filter.annotate(&mut annotated);
+        assert_eq!(annotated.security.as_deref(), Some("NONE"));
+        assert_eq!(annotated.suppressed.as_deref(), Some("sandboxed"));
+
+        let mismatch = VersionInconsistency {
+            name: "react".to_string(),
+            ecosystem: Ecosystem::Node,
+            application_root: PathBuf::from("/app"),
+            application_name: None,
+            kind: InconsistencyKind::VersionMismatch,
+            installed: vec!["18.0.0".to_string()],
+            locked: vec!["17.0.2".to_string()],
+            declared: vec![],
+        };
+        assert_eq!(
+            policy.evaluate_inconsistencies(&[mismatch]),
+            ExitStatus::Clean
+        );
+    }
+}
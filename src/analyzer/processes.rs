@@ -0,0 +1,283 @@
+//! Correlation of installed packages with running processes (Linux)
+//!
+//! The [`ProcessCorrelator`] reads `/proc` for the files each process uses:
+//! file-backed memory mappings (`/proc/<pid>/maps`, which include native
+//! extensions and addons), open file descriptors (`/proc/<pid>/fd`) and
+//! command line arguments naming existing files (the script an interpreter
+//! runs). An installed (HAS) entry is running when one of those files lies
+//! in its installation directory or is one of its native extensions; the
+//! process IDs are recorded in `running_pids`, a RUNNING dimension above HAS
+//! for blast-radius assessment.
+//!
+//! Interpreters read pure Python and JavaScript modules once and close them,
+//! so a package without native code is only seen while a process keeps one
+//! of its files open or runs it as a script. Processes the scanner may not
+//! inspect (other users' without privileges) are skipped, and without `/proc`
+//! no process is found.
+
+use crate::models::ClassifiedDependency;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Files used by one running process
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessFiles {
+    /// Process ID
+    pub pid: u32,
+
+    /// Mapped, open and command line files, as absolute paths
+    pub files: BTreeSet<PathBuf>,
+}
+
+/// Correlator of installed packages with the processes using them
+#[derive(Debug, Clone)]
+pub struct ProcessCorrelator {
+    proc_root: PathBuf,
+}
+
+impl ProcessCorrelator {
+    /// Create a correlator reading `/proc`
+    pub fn new() -> Self {
+        Self {
+            proc_root: PathBuf::from("/proc"),
+        }
+    }
+
+    /// Read process information from another procfs mount
+    pub fn with_proc_root(mut self, proc_root: impl Into<PathBuf>) -> Self {
+        self.proc_root = proc_root.into();
+        self
+    }
+
+    /// Whether process information is available
+    pub fn is_supported(&self) -> bool {
+        self.proc_root.is_dir()
+    }
+
+    /// Collect the files of every process that can be inspected, by PID
+    pub fn snapshot(&self) -> Vec<ProcessFiles> {
+        let Ok(entries) = fs::read_dir(&self.proc_root) else {
+            return Vec::new();
+        };
+        let mut processes: Vec<ProcessFiles> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let pid = entry.file_name().to_str()?.parse::<u32>().ok()?;
+                let files = process_files(&entry.path());
+                (!files.is_empty()).then_some(ProcessFiles { pid, files })
+            })
+            .collect();
+        processes.sort_by_key(|process| process.pid);
+        processes
+    }
+
+    /// Set `running_pids` on installed entries used by the processes,
+    /// returning how many are running
+    pub fn mark_running(
+        &self,
+        processes: &[ProcessFiles],
+        dependencies: &mut [ClassifiedDependency],
+    ) -> usize {
+        // Installation directories and native extension files, by entry
+        let mut dirs: HashMap<PathBuf, Vec<usize>> = HashMap::new();
+        let mut extensions: HashMap<PathBuf, Vec<usize>> = HashMap::new();
+        for (index, dep) in dependencies.iter().enumerate() {
+            let Some(installed_path) = &dep.installed_path else {
+                continue;
+            };
+            dirs.entry(resolve(installed_path)).or_default().push(index);
+            // Native extensions are listed relative to the installation directory
+            if let Some(base) = installed_path.parent() {
+                for extension in &dep.native_extensions {
+                    extensions
+                        .entry(resolve(&base.join(extension)))
+                        .or_default()
+                        .push(index);
+                }
+            }
+        }
+
+        let mut pids: Vec<BTreeSet<u32>> = vec![BTreeSet::new(); dependencies.len()];
+        for process in processes {
+            for file in &process.files {
+                // Only the nearest package owns a file: `a/node_modules/b/x.js`
+                // runs `b`, not `a`
+                let owners = file
+                    .ancestors()
+                    .find_map(|dir| dirs.get(dir))
+                    .into_iter()
+                    .chain(extensions.get(file.as_path()));
+                for index in owners.flatten() {
+                    pids[*index].insert(process.pid);
+                }
+            }
+        }
+
+        let mut running = 0;
+        for (dep, pids) in dependencies.iter_mut().zip(pids) {
+            dep.running_pids = pids.into_iter().collect();
+            running += usize::from(!dep.running_pids.is_empty());
+        }
+        running
+    }
+}
+
+impl Default for ProcessCorrelator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Files a process maps, holds open or names on its command line
+fn process_files(process_dir: &Path) -> BTreeSet<PathBuf> {
+    let mut files = BTreeSet::new();
+
+    if let Ok(maps) = fs::read_to_string(process_dir.join("maps")) {
+        // `address perms offset dev inode path`; only the path contains a slash
+        for line in maps.lines() {
+            if let Some(start) = line.find('/') {
+                let path = &line[start..];
+                files.insert(PathBuf::from(path.trim_end_matches(" (deleted)")));
+            }
+        }
+    }
+
+    if let Ok(fds) = fs::read_dir(process_dir.join("fd")) {
+        files.extend(
+            fds.flatten()
+                .filter_map(|fd| fs::read_link(fd.path()).ok())
+                .filter(|target| target.is_absolute()),
+        );
+    }
+
+    if let Ok(cmdline) = fs::read(process_dir.join("cmdline")) {
+        let cwd = fs::read_link(process_dir.join("cwd")).ok();
+        for arg in cmdline.split(|&b| b == 0).filter(|arg| !arg.is_empty()) {
+            let arg = Path::new(std::str::from_utf8(arg).unwrap_or_default());
+            let path = match &cwd {
+                Some(cwd) if arg.is_relative() => cwd.join(arg),
+                _ => arg.to_path_buf(),
+            };
+            if path.is_absolute() && path.is_file() {
+                files.insert(path);
+            }
+        }
+    }
+
+    files
+}
+
+/// Resolve symlinks, as `/proc` reports resolved paths
+fn resolve(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Classification, Ecosystem};
+    use tempfile::TempDir;
+
+    fn installed(name: &str, path: &Path, ecosystem: Ecosystem) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new(name.to_string(), ecosystem);
+        dep.add_classification(Classification::Has, "1.0.0".to_string(), path.to_path_buf());
+        dep.installed_path = Some(path.to_path_buf());
+        dep
+    }
+
+    #[test]
+    fn test_mark_running_from_maps_and_cmdline() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+
+        let site_packages = root.join("venv/lib/python3.11/site-packages");
+        let extension = "numpy/core/_umath.cpython-311-x86_64-linux-gnu.so";
+        fs::create_dir_all(site_packages.join("numpy/core")).unwrap();
+        fs::write(site_packages.join(extension), "").unwrap();
+        let server = root.join("app/node_modules/server/index.js");
+        fs::create_dir_all(server.parent().unwrap()).unwrap();
+        fs::write(&server, "").unwrap();
+
+        // A fake procfs with a Python process mapping the extension and a
+        // Node process running a script from node_modules
+        let proc_root = root.join("proc");
+        fs::create_dir_all(proc_root.join("100")).unwrap();
+        fs::write(
+            proc_root.join("100/maps"),
+            format!(
+                "7f0000000000-7f0000001000 r-xp 00000000 08:01 1234 {}\n\
+                 7f0000002000-7f0000003000 rw-p 00000000 00:00 0 [heap]\n",
+                site_packages.join(extension).display()
+            ),
+        )
+        .unwrap();
+        fs::create_dir_all(proc_root.join("200")).unwrap();
+        fs::write(
+            proc_root.join("200/cmdline"),
+            format!("node\0{}\0--port\08080\0", server.display()),
+        )
+        .unwrap();
+        fs::create_dir_all(proc_root.join("self")).unwrap();
+
+        let correlator = ProcessCorrelator::new().with_proc_root(&proc_root);
+        assert!(correlator.is_supported());
+        let processes = correlator.snapshot();
+        assert_eq!(
+            processes.iter().map(|p| p.pid).collect::<Vec<_>>(),
+            vec![100, 200]
+        );
+
+        // Python installed paths name the distribution, not the module directory
+        let mut numpy = installed("numpy", &site_packages.join("NumPy"), Ecosystem::Python);
+        numpy.native_extensions = vec![extension.to_string()];
+        let mut deps = vec![
+            numpy,
+            installed(
+                "server",
+                &root.join("app/node_modules/server"),
+                Ecosystem::Node,
+            ),
+            installed("idle", &root.join("app/node_modules/idle"), Ecosystem::Node),
+        ];
+
+        let running = correlator.mark_running(&processes, &mut deps);
+
+        assert_eq!(running, 2);
+        assert_eq!(deps[0].running_pids, vec![100]);
+        assert_eq!(deps[1].running_pids, vec![200]);
+        assert!(deps[2].running_pids.is_empty());
+    }
+
+    #[test]
+    fn test_mark_running_nearest_package() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let outer = root.join("app/node_modules/a");
+        let inner = outer.join("node_modules/b");
+        fs::create_dir_all(&inner).unwrap();
+        fs::write(inner.join("index.js"), "").unwrap();
+        fs::write(outer.join("index.js"), "").unwrap();
+
+        let mut deps = vec![
+            installed("a", &outer, Ecosystem::Node),
+            installed("b", &inner, Ecosystem::Node),
+        ];
+        let processes = [
+            ProcessFiles {
+                pid: 300,
+                files: BTreeSet::from([inner.join("index.js")]),
+            },
+            ProcessFiles {
+                pid: 400,
+                files: BTreeSet::from([outer.join("index.js")]),
+            },
+        ];
+
+        let running = ProcessCorrelator::new().mark_running(&processes, &mut deps);
+
+        assert_eq!(running, 2);
+        assert_eq!(deps[0].running_pids, vec![400]);
+        assert_eq!(deps[1].running_pids, vec![300]);
+    }
+}
@@ -0,0 +1,723 @@
+//! Reachability of installed packages from an application's code
+//!
+//! An infected package deep in `node_modules` matters far more when the
+//! application loads it than when it only sits on disk. The
+//! [`ReachabilityAnalyzer`] reads an application's own code for the packages
+//! it imports and sets `reachable` on the installed (HAS) entries of the
+//! application: `true` for packages imported by its code or required by a
+//! reachable package, `false` for installed but unused ones.
+//!
+//! For Node applications, the code is followed from the entry points of the
+//! application's `package.json` (`main`, `module`, `bin`, `exports`) through
+//! its relative `import`, `export ... from` and `require()` statements; an
+//! application without an entry point on disk has all its JavaScript and
+//! TypeScript sources read instead. Packages whose commands the `scripts`
+//! run are reachable too. Imports are found by pattern, not by parsing, so
+//! commented-out imports count and computed ones (`require(name)`) do not;
+//! requirements of reachable packages are followed by name, which errs on the
+//! side of reachable.
+//!
+//! For Python applications, the `.py` sources of the application (outside
+//! virtual environments and `site-packages`) are read for `import`,
+//! `from ... import`, `importlib.import_module()` and `__import__()` of
+//! absolute modules. Top-level modules are mapped to the installed
+//! distributions providing them through the `top_level.txt` or `RECORD` of
+//! their metadata directory, so `import yaml` reaches PyYAML.
+//!
+//! Entries need their application root assigned (see
+//! [`ApplicationLinker::assign_application_roots`](crate::analyzer::ApplicationLinker::assign_application_roots)).
+
+use super::phantom::package_key;
+use crate::models::{Classification, ClassifiedDependency, Ecosystem};
+use crate::parsers::read_text;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Most source files read per application
+const MAX_SOURCE_FILES: usize = 20_000;
+
+/// Larger source files (bundles, generated code) are not read
+const MAX_SOURCE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Extensions of JavaScript and TypeScript sources, in resolution order
+const NODE_EXTENSIONS: &[&str] = &["js", "mjs", "cjs", "jsx", "ts", "tsx", "mts", "cts"];
+
+/// Directories never holding a Python application's own sources
+const PYTHON_SKIPPED_DIRS: &[&str] = &[
+    "__pycache__",
+    "node_modules",
+    "site-packages",
+    "dist-packages",
+];
+
+/// Packages reachable from the code of each application
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Reachability {
+    reachable: HashMap<(PathBuf, Ecosystem), HashSet<String>>,
+}
+
+impl Reachability {
+    /// Whether an installed package of an application is reachable from its
+    /// code, if the application was analyzed
+    pub fn is_reachable(&self, root: &Path, ecosystem: Ecosystem, name: &str) -> Option<bool> {
+        self.reachable
+            .get(&(root.to_path_buf(), ecosystem))
+            .map(|names| names.contains(&package_key(ecosystem, name)))
+    }
+
+    /// Number of analyzed applications
+    pub fn applications(&self) -> usize {
+        self.reachable.len()
+    }
+
+    /// Set `reachable` on the installed entries of the analyzed
+    /// applications, returning how many are unreachable
+    pub fn mark(&self, dependencies: &mut [ClassifiedDependency]) -> usize {
+        let mut unreachable = 0;
+        for dep in dependencies.iter_mut() {
+            if !dep.has_classification(Classification::Has) {
+                continue;
+            }
+            let Some(root) = &dep.application_root else {
+                continue;
+            };
+            dep.reachable = self.is_reachable(root, dep.ecosystem, &dep.name);
+            unreachable += usize::from(dep.reachable == Some(false));
+        }
+        unreachable
+    }
+}
+
+/// Analyzer of the packages an application's code imports
+#[derive(Debug, Clone, Default)]
+pub struct ReachabilityAnalyzer;
+
+impl ReachabilityAnalyzer {
+    /// Create a new ReachabilityAnalyzer
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Find the packages reachable from the code of the applications with
+    /// installed packages among the entries
+    pub fn analyze(&self, dependencies: &[ClassifiedDependency]) -> Reachability {
+        // Requirements of the installed packages, by application
+        let mut installed: HashMap<
+            (PathBuf, Ecosystem),
+            HashMap<String, Vec<&ClassifiedDependency>>,
+        > = HashMap::new();
+        for dep in dependencies {
+            let Some(root) = &dep.application_root else {
+                continue;
+            };
+            if !matches!(dep.ecosystem, Ecosystem::Node | Ecosystem::Python)
+                || !dep.has_classification(Classification::Has)
+            {
+                continue;
+            }
+            installed
+                .entry((root.clone(), dep.ecosystem))
+                .or_default()
+                .entry(package_key(dep.ecosystem, &dep.name))
+                .or_default()
+                .push(dep);
+        }
+
+        let mut reachability = Reachability::default();
+        for ((root, ecosystem), packages) in installed {
+            let imported = match ecosystem {
+                Ecosystem::Python => python_imports(&root, &packages),
+                _ => node_imports(&root, &packages),
+            };
+            let reachable = close_over_requirements(ecosystem, imported, &packages);
+            reachability.reachable.insert((root, ecosystem), reachable);
+        }
+        reachability
+    }
+}
+
+/// Add the requirements of reachable packages, transitively
+fn close_over_requirements(
+    ecosystem: Ecosystem,
+    roots: BTreeSet<String>,
+    packages: &HashMap<String, Vec<&ClassifiedDependency>>,
+) -> HashSet<String> {
+    let mut reachable = HashSet::new();
+    let mut queue: VecDeque<String> = roots.into_iter().collect();
+    while let Some(name) = queue.pop_front() {
+        if !reachable.insert(name.clone()) {
+            continue;
+        }
+        for dep in packages.get(name.as_str()).into_iter().flatten() {
+            queue.extend(
+                dep.dependencies
+                    .iter()
+                    .map(|required| package_key(ecosystem, required))
+                    .filter(|required| !reachable.contains(required)),
+            );
+        }
+    }
+    reachable
+}
+
+/// Packages a Node application imports from its code or runs from its
+/// scripts
+fn node_imports(
+    root: &Path,
+    packages: &HashMap<String, Vec<&ClassifiedDependency>>,
+) -> BTreeSet<String> {
+    let manifest = read_text(root.join("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .unwrap_or(Value::Null);
+
+    let mut entry_points: Vec<PathBuf> = Vec::new();
+    for field in ["main", "module", "bin", "exports"] {
+        collect_strings(&manifest[field], &mut |path| {
+            if let Some(file) = resolve_module(root, path) {
+                entry_points.push(file);
+            }
+        });
+    }
+    if manifest.get("main").is_none() {
+        entry_points.extend(resolve_module(root, "./index"));
+    }
+    if entry_points.is_empty() {
+        source_files(root, &mut entry_points);
+    }
+
+    let mut imported = BTreeSet::new();
+    let mut seen: HashSet<PathBuf> = entry_points.iter().cloned().collect();
+    let mut queue: VecDeque<PathBuf> = entry_points.into();
+    while let Some(file) = queue.pop_front() {
+        if seen.len() > MAX_SOURCE_FILES {
+            break;
+        }
+        let too_large = fs::metadata(&file).map_or(true, |m| m.len() > MAX_SOURCE_BYTES);
+        let Some(content) = (!too_large).then(|| read_text(&file).ok()).flatten() else {
+            continue;
+        };
+        let dir = file.parent().unwrap_or(root);
+        for specifier in node_specifiers(&content) {
+            if specifier.starts_with('.') {
+                if let Some(module) = resolve_module(dir, specifier) {
+                    if seen.insert(module.clone()) {
+                        queue.push_back(module);
+                    }
+                }
+            } else if let Some(name) = package_name(specifier) {
+                imported.insert(name.to_string());
+            }
+        }
+    }
+
+    // Commands the scripts run, from the packages' `bin`
+    let scripts: HashSet<&str> = manifest["scripts"]
+        .as_object()
+        .into_iter()
+        .flat_map(|scripts| scripts.values())
+        .filter_map(Value::as_str)
+        .flat_map(|script| script.split(|c: char| c.is_whitespace() || "&|;()".contains(c)))
+        .collect();
+    for (name, deps) in packages {
+        let runs = deps.iter().any(|dep| {
+            node_commands(dep)
+                .iter()
+                .any(|command| scripts.contains(command.as_str()))
+        });
+        if runs {
+            imported.insert(name.clone());
+        }
+    }
+    imported
+}
+
+/// Distributions (by normalized name) providing the modules a Python
+/// application's code imports
+fn python_imports(
+    root: &Path,
+    packages: &HashMap<String, Vec<&ClassifiedDependency>>,
+) -> BTreeSet<String> {
+    // Installed distributions by top-level module
+    let mut metadata_dirs: HashMap<PathBuf, HashMap<String, PathBuf>> = HashMap::new();
+    let mut providers: HashMap<String, Vec<&str>> = HashMap::new();
+    for (key, deps) in packages {
+        for dep in deps {
+            for module in python_modules(dep, &mut metadata_dirs) {
+                providers.entry(module).or_default().push(key);
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    python_source_files(root, &mut files);
+    let mut imported = BTreeSet::new();
+    for file in files {
+        let too_large = fs::metadata(&file).map_or(true, |m| m.len() > MAX_SOURCE_BYTES);
+        let Some(content) = (!too_large).then(|| read_text(&file).ok()).flatten() else {
+            continue;
+        };
+        for module in python_modules_imported(&content) {
+            let top_level = module.split('.').next().unwrap_or(module);
+            for key in providers.get(top_level).into_iter().flatten() {
+                imported.insert(key.to_string());
+            }
+        }
+    }
+    imported
+}
+
+/// Absolute modules a Python source file imports
+fn python_modules_imported(content: &str) -> Vec<&str> {
+    static PATTERNS: OnceLock<[Regex; 3]> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        [
+            // import x, y.z as w
+            Regex::new(r"(?m)^[ \t]*import[ \t]+([\w.]+(?:[ \t]+as[ \t]+\w+)?(?:[ \t]*,[ \t]*[\w.]+(?:[ \t]+as[ \t]+\w+)?)*)")
+                .unwrap(),
+            // from x.y import z (relative imports start with a dot)
+            Regex::new(r"(?m)^[ \t]*from[ \t]+(\w[\w.]*)[ \t]+import\b").unwrap(),
+            // importlib.import_module('x'), __import__('x')
+            Regex::new(r#"\b(?:import_module|__import__)\(\s*["'](\w[\w.]*)["']"#).unwrap(),
+        ]
+    });
+    patterns
+        .iter()
+        .flat_map(|pattern| pattern.captures_iter(content))
+        .filter_map(|captures| captures.get(1))
+        .flat_map(|modules| modules.as_str().split(','))
+        .filter_map(|module| module.split_whitespace().next())
+        .collect()
+}
+
+/// Top-level modules an installed Python distribution provides, from the
+/// `top_level.txt` or `RECORD` of its metadata directory; the name with
+/// dashes as underscores when neither is found
+fn python_modules(
+    dep: &ClassifiedDependency,
+    metadata_dirs: &mut HashMap<PathBuf, HashMap<String, PathBuf>>,
+) -> BTreeSet<String> {
+    let metadata_dir = dep
+        .installed_path
+        .as_ref()
+        .and_then(|path| path.parent())
+        .and_then(|site_packages| {
+            metadata_dirs
+                .entry(site_packages.to_path_buf())
+                .or_insert_with(|| python_metadata_dirs(site_packages))
+                .get(&package_key(Ecosystem::Python, &dep.name))
+                .cloned()
+        });
+
+    let mut modules = BTreeSet::new();
+    if let Some(dir) = metadata_dir {
+        if let Ok(content) = read_text(dir.join("top_level.txt")) {
+            modules.extend(
+                content
+                    .lines()
+                    .map(|line| line.trim().replace('/', "."))
+                    .filter(|line| !line.is_empty())
+                    .filter_map(|line| line.split('.').next().map(str::to_string)),
+            );
+        } else if let Ok(content) = read_text(dir.join("RECORD")) {
+            modules.extend(content.lines().filter_map(record_module));
+        }
+    }
+    if modules.is_empty() {
+        modules.insert(dep.name.to_lowercase().replace(['-', '.'], "_"));
+    }
+    modules
+}
+
+/// `.dist-info` and `.egg-info` directories of a site-packages directory,
+/// by normalized distribution name
+pub(super) fn python_metadata_dirs(site_packages: &Path) -> HashMap<String, PathBuf> {
+    let mut dirs = HashMap::new();
+    let Ok(entries) = fs::read_dir(site_packages) else {
+        return dirs;
+    };
+    for entry in entries.flatten() {
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        let Some(stem) = file_name
+            .strip_suffix(".dist-info")
+            .or_else(|| file_name.strip_suffix(".egg-info"))
+        else {
+            continue;
+        };
+        // name-version[-pyX.Y]; the name has its dashes escaped
+        let name = stem.split('-').next().unwrap_or(stem);
+        dirs.insert(package_key(Ecosystem::Python, name), entry.path());
+    }
+    dirs
+}
+
+/// Top-level module of a RECORD line (`yaml/__init__.py,sha256=...,123` →
+/// `yaml`), if the file is an importable module or package
+fn record_module(line: &str) -> Option<String> {
+    let path = line.trim_start_matches('"').split([',', '"']).next()?;
+    let first = path.split('/').next()?;
+    if first.is_empty() || first == ".." {
+        return None;
+    }
+    if path.contains('/') {
+        // Metadata (`name-1.0.dist-info`), data and cache directories hold
+        // no modules
+        let package = !first.contains(['-', '.']) && first != "__pycache__";
+        return package.then(|| first.to_string());
+    }
+    // Single-file modules and extensions (`six.py`, `_cffi.cpython-312-x86_64-linux-gnu.so`)
+    let (module, extension) = first.split_once('.')?;
+    let importable = matches!(extension.rsplit('.').next(), Some("py" | "so" | "pyd"));
+    importable.then(|| module.to_string())
+}
+
+/// Module specifiers of the imports, exports and requires of a source file
+fn node_specifiers(content: &str) -> Vec<&str> {
+    static PATTERNS: OnceLock<[Regex; 2]> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        [
+            // import x from 'y', import 'y', export * from 'y'
+            Regex::new(r#"\b(?:import|export)\s*(?:[\w$*{},\s]*?\bfrom\s*)?["']([^"'\n]+)["']"#)
+                .unwrap(),
+            // require('y'), require.resolve('y'), import('y')
+            Regex::new(r#"\b(?:require(?:\.resolve)?|import)\s*\(\s*["']([^"'\n]+)["']\s*\)"#)
+                .unwrap(),
+        ]
+    });
+    patterns
+        .iter()
+        .flat_map(|pattern| pattern.captures_iter(content))
+        .filter_map(|captures| captures.get(1))
+        .map(|specifier| specifier.as_str())
+        .collect()
+}
+
+/// Package of a bare specifier (`lodash/fp` → `lodash`, `@scope/pkg/x` →
+/// `@scope/pkg`); built-ins (`node:fs`) and URLs have none
+fn package_name(specifier: &str) -> Option<&str> {
+    if specifier.contains(':') || specifier.starts_with('/') || specifier.starts_with('#') {
+        return None;
+    }
+    let mut end = specifier.find('/').unwrap_or(specifier.len());
+    if specifier.starts_with('@') {
+        let rest = &specifier[end + 1..];
+        end += 1 + rest.find('/').unwrap_or(rest.len());
+        end = end.min(specifier.len());
+    }
+    Some(&specifier[..end]).filter(|name| !name.is_empty())
+}
+
+/// Resolve a relative module path to a source file, trying the extensions
+/// and directory index files
+fn resolve_module(dir: &Path, specifier: &str) -> Option<PathBuf> {
+    let path = dir.join(specifier);
+    if path.is_file() {
+        return is_node_source(&path).then_some(path);
+    }
+    let file_name = path.file_name()?.to_string_lossy().to_string();
+    NODE_EXTENSIONS
+        .iter()
+        .map(|extension| path.with_file_name(format!("{}.{}", file_name, extension)))
+        .chain(
+            NODE_EXTENSIONS
+                .iter()
+                .map(|extension| path.join(format!("index.{}", extension))),
+        )
+        .find(|candidate| candidate.is_file())
+}
+
+fn is_node_source(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|extension| NODE_EXTENSIONS.contains(&extension))
+        // Scripts of `bin` often have no extension
+        || path.extension().is_none()
+}
+
+/// JavaScript and TypeScript sources of an application, outside
+/// `node_modules` and hidden directories
+fn source_files(root: &Path, files: &mut Vec<PathBuf>) {
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if files.len() >= MAX_SOURCE_FILES {
+                return;
+            }
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                if name != "node_modules" && !name.starts_with('.') {
+                    dirs.push(entry.path());
+                }
+            } else if file_type.is_file()
+                && entry
+                    .path()
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|extension| NODE_EXTENSIONS.contains(&extension))
+            {
+                files.push(entry.path());
+            }
+        }
+    }
+    files.sort();
+}
+
+/// Python sources of an application, outside hidden directories and
+/// virtual environments
+fn python_source_files(root: &Path, files: &mut Vec<PathBuf>) {
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if files.len() >= MAX_SOURCE_FILES {
+                return;
+            }
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            if file_type.is_dir() {
+                let skipped = name.starts_with('.')
+                    || PYTHON_SKIPPED_DIRS.contains(&name.as_ref())
+                    || path.join("pyvenv.cfg").is_file();
+                if !skipped {
+                    dirs.push(path);
+                }
+            } else if file_type.is_file() && name.ends_with(".py") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+}
+
+/// Commands an installed npm package provides, from its `bin`
+fn node_commands(dep: &ClassifiedDependency) -> Vec<String> {
+    let Some(path) = dep.installed_path.as_ref() else {
+        return Vec::new();
+    };
+    let manifest = read_text(path.join("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .unwrap_or(Value::Null);
+    match &manifest["bin"] {
+        // A single command is named after the package, without its scope
+        Value::String(_) => vec![dep.name.rsplit('/').next().unwrap_or(&dep.name).to_string()],
+        Value::Object(commands) => commands.keys().cloned().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Call `f` with every string in a JSON value (conditional `exports` nest them)
+fn collect_strings(value: &Value, f: &mut impl FnMut(&str)) {
+    match value {
+        Value::String(s) => f(s),
+        Value::Array(values) => values.iter().for_each(|v| collect_strings(v, f)),
+        Value::Object(values) => values.values().for_each(|v| collect_strings(v, f)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn installed(root: &Path, name: &str, requires: &[&str]) -> ClassifiedDependency {
+        let path = root.join("node_modules").join(name);
+        let mut dep = ClassifiedDependency::new(name.to_string(), Ecosystem::Node);
+        dep.add_classification(Classification::Has, "1.0.0".to_string(), path.clone());
+        dep.installed_path = Some(path);
+        dep.application_root = Some(root.to_path_buf());
+        dep.dependencies = requires.iter().map(|r| r.to_string()).collect();
+        dep
+    }
+
+    #[test]
+    fn test_node_reachability() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("package.json"),
+            r#"{"main": "src/server.js", "scripts": {"test": "jest --ci"}}"#,
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("src/routes")).unwrap();
+        fs::write(
+            root.join("src/server.js"),
+            "const express = require('express');\nimport { api } from './routes';\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/routes/index.ts"),
+            "import type { Request } from '@types/express';\nexport * from \"lodash/fp\";\nconst fs = require('node:fs');\n",
+        )
+        .unwrap();
+        // Not reachable from the entry point
+        fs::write(root.join("src/unused.js"), "require('left-pad');").unwrap();
+        fs::create_dir_all(root.join("node_modules/jest")).unwrap();
+        fs::write(
+            root.join("node_modules/jest/package.json"),
+            r#"{"name": "jest", "bin": "./bin/jest.js"}"#,
+        )
+        .unwrap();
+
+        let mut dependencies = vec![
+            installed(root, "express", &["body-parser"]),
+            installed(root, "body-parser", &[]),
+            installed(root, "@types/express", &[]),
+            installed(root, "lodash", &[]),
+            installed(root, "jest", &[]),
+            installed(root, "left-pad", &[]),
+        ];
+        let mut declared = ClassifiedDependency::new("left-pad".to_string(), Ecosystem::Node);
+        declared.add_classification(
+            Classification::Can,
+            "^1.0.0".to_string(),
+            root.join("package.json"),
+        );
+        declared.application_root = Some(root.to_path_buf());
+        dependencies.push(declared);
+
+        let reachability = ReachabilityAnalyzer::new().analyze(&dependencies);
+        assert_eq!(reachability.applications(), 1);
+        assert_eq!(reachability.mark(&mut dependencies), 1);
+        let reachable: Vec<Option<bool>> = dependencies.iter().map(|d| d.reachable).collect();
+        assert_eq!(
+            reachable,
+            [
+                Some(true),
+                Some(true),
+                Some(true),
+                Some(true),
+                Some(true),
+                Some(false),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_node_reachability_without_entry_point() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("package.json"), r#"{"main": "dist/index.js"}"#).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/app.tsx"), "import React from 'react'").unwrap();
+
+        let mut dependencies = vec![installed(root, "react", &[]), installed(root, "vue", &[])];
+        let reachability = ReachabilityAnalyzer::new().analyze(&dependencies);
+        assert_eq!(reachability.mark(&mut dependencies), 1);
+        assert_eq!(dependencies[0].reachable, Some(true));
+        assert_eq!(dependencies[1].reachable, Some(false));
+    }
+
+    #[test]
+    fn test_python_reachability() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("pyproject.toml"), "[project]\nname = \"svc\"\n").unwrap();
+        fs::create_dir_all(root.join("app")).unwrap();
+        fs::write(
+            root.join("app/main.py"),
+            "import os, yaml\nfrom requests.adapters import HTTPAdapter\nfrom . import views\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("app/compat.py"),
+            "try:\n    import six as compat\nexcept ImportError:\n    pass\n",
+        )
+        .unwrap();
+
+        let site_packages = root.join(".venv/lib/python3.12/site-packages");
+        let dist_info = site_packages.join("PyYAML-6.0.1.dist-info");
+        fs::create_dir_all(&dist_info).unwrap();
+        fs::write(dist_info.join("top_level.txt"), "_yaml\nyaml\n").unwrap();
+        let dist_info = site_packages.join("six-1.16.0.dist-info");
+        fs::create_dir_all(&dist_info).unwrap();
+        fs::write(
+            dist_info.join("RECORD"),
+            "six.py,sha256=abc,34549\nsix-1.16.0.dist-info/RECORD,,\n",
+        )
+        .unwrap();
+        // Sources of the virtual environment are not the application's
+        fs::write(site_packages.join("black.py"), "import black").unwrap();
+
+        let installed = |name: &str, requires: &[&str]| {
+            let path = site_packages.join(name);
+            let mut dep = ClassifiedDependency::new(name.to_string(), Ecosystem::Python);
+            dep.add_classification(Classification::Has, "1.0".to_string(), path.clone());
+            dep.installed_path = Some(path);
+            dep.application_root = Some(root.to_path_buf());
+            dep.dependencies = requires.iter().map(|r| r.to_string()).collect();
+            dep
+        };
+        let mut dependencies = vec![
+            installed("PyYAML", &[]),
+            installed("requests", &["Charset_Normalizer"]),
+            installed("charset-normalizer", &[]),
+            installed("six", &[]),
+            installed("black", &[]),
+        ];
+
+        let reachability = ReachabilityAnalyzer::new().analyze(&dependencies);
+        assert_eq!(reachability.mark(&mut dependencies), 1);
+        let reachable: Vec<Option<bool>> = dependencies.iter().map(|d| d.reachable).collect();
+        assert_eq!(
+            reachable,
+            [Some(true), Some(true), Some(true), Some(true), Some(false)]
+        );
+        assert_eq!(
+            reachability.is_reachable(root, Ecosystem::Python, "pyyaml"),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_python_modules_imported() {
+        let content = "import a.b as c, d\nfrom e.f import g\nfrom .h import i\n\
+                       m = importlib.import_module('j.k')\n# import l\n";
+        assert_eq!(python_modules_imported(content), ["a.b", "d", "e.f", "j.k"]);
+    }
+
+    #[test]
+    fn test_record_module() {
+        assert_eq!(
+            record_module("yaml/__init__.py,sha256=x,1"),
+            Some("yaml".to_string())
+        );
+        assert_eq!(
+            record_module("_cffi_backend.cpython-312-x86_64-linux-gnu.so,sha256=x,1"),
+            Some("_cffi_backend".to_string())
+        );
+        assert_eq!(record_module("six-1.16.0.dist-info/RECORD,,"), None);
+        assert_eq!(record_module("../../../bin/black,sha256=x,1"), None);
+        assert_eq!(record_module("distutils-precedence.pth,sha256=x,1"), None);
+    }
+
+    #[test]
+    fn test_package_name() {
+        assert_eq!(package_name("lodash"), Some("lodash"));
+        assert_eq!(package_name("lodash/fp"), Some("lodash"));
+        assert_eq!(package_name("@babel/core"), Some("@babel/core"));
+        assert_eq!(package_name("@babel/core/lib/x"), Some("@babel/core"));
+        assert_eq!(package_name("node:fs"), None);
+        assert_eq!(package_name("#internal"), None);
+    }
+}
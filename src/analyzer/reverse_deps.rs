@@ -0,0 +1,227 @@
+//! Reverse dependency queries ("why is this package here?")
+//!
+//! Given a package name, the [`ReverseDependencyFinder`] lists every path
+//! from an application's direct dependencies to that package through the
+//! dependency edges of its entries, like `npm why` or `cargo tree -i`:
+//!
+//! ```text
+//! web > express@4.18.2 > body-parser@1.20.1 > qs@6.11.0
+//! ```
+//!
+//! Paths follow the per-application [`DependencyGraph`], so entries of the
+//! same package are merged and direct dependencies are the declared packages
+//! (or, without a manifest, packages nothing depends on). At most
+//! [`MAX_PATHS`] paths are returned per application. Entries need their application root
+//! assigned (see
+//! [`ApplicationLinker::assign_application_roots`](crate::analyzer::ApplicationLinker::assign_application_roots)).
+
+use crate::analyzer::graph::{DependencyGraph, GraphBuilder};
+use crate::models::{ClassifiedDependency, Ecosystem};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Maximum number of paths reported per application
+pub const MAX_PATHS: usize = 100;
+
+/// A package on a dependency path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathStep {
+    /// Package name
+    pub name: String,
+
+    /// Installed, locked or declared version
+    pub version: Option<String>,
+}
+
+impl fmt::Display for PathStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.version {
+            Some(version) => write!(f, "{}@{}", self.name, version),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// A path from an application's direct dependency to the queried package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependencyPath {
+    /// Application root directory
+    pub application_root: PathBuf,
+
+    /// Application name, if known
+    pub application_name: Option<String>,
+
+    /// Ecosystem
+    pub ecosystem: Ecosystem,
+
+    /// Packages from the direct dependency to the queried package
+    pub steps: Vec<PathStep>,
+}
+
+impl DependencyPath {
+    /// Whether the queried package is a direct dependency
+    pub fn is_direct(&self) -> bool {
+        self.steps.len() == 1
+    }
+}
+
+impl fmt::Display for DependencyPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let application = self
+            .application_name
+            .clone()
+            .unwrap_or_else(|| self.application_root.display().to_string());
+        write!(f, "{}", application)?;
+        for step in &self.steps {
+            write!(f, " > {}", step)?;
+        }
+        Ok(())
+    }
+}
+
+/// Finder for the dependency paths leading to a package
+pub struct ReverseDependencyFinder;
+
+impl ReverseDependencyFinder {
+    /// Create a new ReverseDependencyFinder
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Find all paths from application roots to a package
+    ///
+    /// Paths are ordered by application, then length.
+    pub fn find_paths(
+        &self,
+        dependencies: &[ClassifiedDependency],
+        package: &str,
+    ) -> Vec<DependencyPath> {
+        let mut paths = Vec::new();
+        for graph in GraphBuilder::new().build(dependencies) {
+            let Some(target) = graph.node(package) else {
+                continue;
+            };
+            let target = target.name.clone();
+
+            let mut found = Vec::new();
+            for root in graph.roots() {
+                let mut stack = vec![root.name.clone()];
+                let mut on_path = HashSet::from([root.name.clone()]);
+                walk(&graph, &target, &mut stack, &mut on_path, &mut found);
+            }
+            found.sort_by(|a: &Vec<String>, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+
+            paths.extend(found.into_iter().take(MAX_PATHS).map(|names| {
+                DependencyPath {
+                    application_root: graph.application_root.clone(),
+                    application_name: graph.application_name.clone(),
+                    ecosystem: graph.ecosystem,
+                    steps: names
+                        .into_iter()
+                        .map(|name| PathStep {
+                            version: graph.node(&name).and_then(|n| n.version.clone()),
+                            name,
+                        })
+                        .collect(),
+                }
+            }));
+        }
+        paths
+    }
+}
+
+impl Default for ReverseDependencyFinder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Depth-first search collecting cycle-free paths that end at `target`
+fn walk(
+    graph: &DependencyGraph,
+    target: &str,
+    stack: &mut Vec<String>,
+    on_path: &mut HashSet<String>,
+    found: &mut Vec<Vec<String>>,
+) {
+    let current = stack.last().cloned().unwrap_or_default();
+    if current == target {
+        found.push(stack.clone());
+        return;
+    }
+    if found.len() >= MAX_PATHS {
+        return;
+    }
+    for child in graph.children(&current) {
+        if on_path.insert(child.name.clone()) {
+            stack.push(child.name.clone());
+            walk(graph, target, stack, on_path, found);
+            stack.pop();
+            on_path.remove(&child.name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Classification;
+
+    fn entry(
+        name: &str,
+        classification: Classification,
+        children: &[&str],
+    ) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new(name.to_string(), Ecosystem::Node);
+        dep.add_classification(classification, "1.0.0".to_string(), PathBuf::from("/app"));
+        dep.application_root = Some(PathBuf::from("/app"));
+        dep.application_name = Some("web".to_string());
+        dep.dependencies = children.iter().map(|c| c.to_string()).collect();
+        dep
+    }
+
+    #[test]
+    fn test_find_paths() {
+        let deps = vec![
+            entry("express", Classification::Can, &[]),
+            entry("qs", Classification::Can, &[]),
+            entry("express", Classification::Has, &["body-parser", "qs"]),
+            entry("body-parser", Classification::Has, &["qs", "express"]),
+            entry("qs", Classification::Has, &[]),
+            entry("unrelated", Classification::Has, &[]),
+        ];
+
+        let paths = ReverseDependencyFinder::new().find_paths(&deps, "qs");
+
+        let rendered: Vec<_> = paths.iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            rendered,
+            vec![
+                "web > qs@1.0.0",
+                "web > express@1.0.0 > qs@1.0.0",
+                "web > express@1.0.0 > body-parser@1.0.0 > qs@1.0.0",
+            ]
+        );
+        assert!(paths[0].is_direct());
+        assert!(ReverseDependencyFinder::new()
+            .find_paths(&deps, "missing")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_find_paths_without_manifest() {
+        let deps = vec![
+            entry("flask", Classification::Has, &["werkzeug"]),
+            entry("werkzeug", Classification::Has, &["markupsafe"]),
+            entry("markupsafe", Classification::Has, &[]),
+        ];
+
+        let paths = ReverseDependencyFinder::new().find_paths(&deps, "markupsafe");
+
+        assert_eq!(paths.len(), 1);
+        let names: Vec<_> = paths[0].steps.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["flask", "werkzeug", "markupsafe"]);
+    }
+}
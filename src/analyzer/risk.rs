@@ -0,0 +1,86 @@
+//! Risk flags for installed packages
+//!
+//! Malicious packages usually run their payload on install or ship code that
+//! is hard to review. The [`RiskFlagger`] sets [`RiskFlag`]s on installed
+//! (HAS) entries so incident response can start with the packages that
+//! execute code:
+//!
+//! - [`RiskFlag::InstallScript`]: npm packages with `preinstall`, `install`
+//!   or `postinstall` scripts, including the implicit `node-gyp rebuild`
+//! - [`RiskFlag::NativeExtension`]: Python packages shipping compiled
+//!   extensions or shared libraries
+//! - [`RiskFlag::EntryPoint`]: Python packages installing console or GUI
+//!   commands
+//!
+//! Flags are only derived from the install-time data recorded by the
+//! installed-package parsers; declared and locked entries get none.
+
+use crate::models::{ClassifiedDependency, RiskFlag};
+
+/// Flagger for installed packages that run or ship executable code
+pub struct RiskFlagger;
+
+impl RiskFlagger {
+    /// Create a new RiskFlagger
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Risk flags of one entry, in flag order
+    pub fn flags(&self, dep: &ClassifiedDependency) -> Vec<RiskFlag> {
+        let mut flags = Vec::new();
+        if !dep.install_scripts.is_empty() {
+            flags.push(RiskFlag::InstallScript);
+        }
+        if !dep.native_extensions.is_empty() {
+            flags.push(RiskFlag::NativeExtension);
+        }
+        if !dep.entry_points.is_empty() {
+            flags.push(RiskFlag::EntryPoint);
+        }
+        flags
+    }
+
+    /// Set `risk_flags` on every entry, returning how many got at least one
+    pub fn mark_risks(&self, dependencies: &mut [ClassifiedDependency]) -> usize {
+        let mut flagged = 0;
+        for dep in dependencies.iter_mut() {
+            dep.risk_flags = self.flags(dep);
+            flagged += usize::from(!dep.risk_flags.is_empty());
+        }
+        flagged
+    }
+}
+
+impl Default for RiskFlagger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Ecosystem;
+
+    #[test]
+    fn test_mark_risks() {
+        let mut esbuild = ClassifiedDependency::new("esbuild".to_string(), Ecosystem::Node);
+        esbuild.install_scripts = vec!["postinstall".to_string()];
+        let mut numpy = ClassifiedDependency::new("numpy".to_string(), Ecosystem::Python);
+        numpy.native_extensions = vec!["numpy/core/_umath.so".to_string()];
+        numpy.entry_points = vec!["f2py".to_string()];
+        let lodash = ClassifiedDependency::new("lodash".to_string(), Ecosystem::Node);
+        let mut deps = vec![esbuild, numpy, lodash];
+
+        let flagged = RiskFlagger::new().mark_risks(&mut deps);
+
+        assert_eq!(flagged, 2);
+        assert_eq!(deps[0].risk_flags, vec![RiskFlag::InstallScript]);
+        assert_eq!(
+            deps[1].risk_flags,
+            vec![RiskFlag::NativeExtension, RiskFlag::EntryPoint]
+        );
+        assert!(deps[2].risk_flags.is_empty());
+    }
+}
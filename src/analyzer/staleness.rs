@@ -0,0 +1,662 @@
+//! Dependency age and staleness from registry metadata
+//!
+//! The [`RegistryClient`] fetches the latest version and the release dates of
+//! a package from its registry (npm, PyPI, crates.io, or the mirror of a
+//! [`RegistryConfig`]) and caches the answer on disk, one JSON file per package, for a day by default. Offline,
+//! only the cache is read, whatever its age, and packages missing from it are
+//! skipped.
+//!
+//! The [`StalenessChecker`] compares each installed or locked version with the
+//! latest stable release: how many major versions it is behind, how many
+//! minor release lines (`1.4` → `1.7` and `1.4` → `2.1` are three), and how
+//! many days passed between its release and the latest one.
+//!
+//! Both have `_async` variants for services awaiting them from an async
+//! runtime (see [`unblock`](crate::unblock)). A checker given a
+//! [`CancellationToken`] stops fetching once it is cancelled, and reports the
+//! packages checked until then.
+
+use super::registries::{Registry, RegistryConfig};
+use crate::cancel::CancellationToken;
+use crate::models::{Classification, ClassifiedDependency, Ecosystem, ScanError};
+use crate::unblock::unblock;
+use crate::version::AnyVersion;
+use rayon::prelude::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+
+/// Time allowed for one registry request
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Age after which cached metadata is fetched again
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Public registry of each ecosystem
+const DEFAULT_REGISTRIES: &[(Ecosystem, &str)] = &[
+    (Ecosystem::Node, "https://registry.npmjs.org"),
+    (Ecosystem::Python, "https://pypi.org"),
+    (Ecosystem::Rust, "https://crates.io"),
+];
+
+/// Latest version and release dates of a package
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistryMetadata {
+    /// Latest stable version
+    pub latest: String,
+
+    /// Release date (`YYYY-MM-DD`) of each published version
+    #[serde(default)]
+    pub released: BTreeMap<String, String>,
+}
+
+impl RegistryMetadata {
+    /// Read an npm packument (`GET /<name>`)
+    pub fn from_npm(document: &Value) -> Option<Self> {
+        let latest = document["dist-tags"]["latest"].as_str()?.to_string();
+        let released = document["time"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(version, _)| *version != "created" && *version != "modified")
+            .filter_map(|(version, time)| Some((version.clone(), date(time.as_str()?)?)))
+            .collect();
+        Some(Self { latest, released })
+    }
+
+    /// Read a PyPI JSON API document (`GET /pypi/<name>/json`)
+    pub fn from_pypi(document: &Value) -> Option<Self> {
+        let latest = document["info"]["version"].as_str()?.to_string();
+        let released = document["releases"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(version, files)| {
+                // A release is as old as its first uploaded file
+                let uploaded = files
+                    .as_array()?
+                    .iter()
+                    .filter_map(|file| file["upload_time_iso_8601"].as_str().and_then(date))
+                    .min()?;
+                Some((version.clone(), uploaded))
+            })
+            .collect();
+        Some(Self { latest, released })
+    }
+
+    /// Read a crates.io API document (`GET /api/v1/crates/<name>`)
+    pub fn from_crates_io(document: &Value) -> Option<Self> {
+        let info = &document["crate"];
+        let latest = info["max_stable_version"]
+            .as_str()
+            .or_else(|| info["max_version"].as_str())?
+            .to_string();
+        let released = document["versions"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|version| {
+                Some((
+                    version["num"].as_str()?.to_string(),
+                    date(version["created_at"].as_str()?)?,
+                ))
+            })
+            .collect();
+        Some(Self { latest, released })
+    }
+}
+
+/// Cached registry answer
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    /// When the metadata was fetched, in seconds since the Unix epoch
+    fetched_at: u64,
+    metadata: RegistryMetadata,
+}
+
+/// Client for package metadata of registries, with an on-disk cache
+#[derive(Debug, Clone)]
+pub struct RegistryClient {
+    registries: BTreeMap<Ecosystem, Registry>,
+    cache_dir: Option<PathBuf>,
+    max_age: Duration,
+    offline: bool,
+}
+
+impl Default for RegistryClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RegistryClient {
+    /// Create a client for the public registries, without a cache
+    pub fn new() -> Self {
+        Self {
+            registries: DEFAULT_REGISTRIES
+                .iter()
+                .map(|(ecosystem, url)| (*ecosystem, Registry::new(*url)))
+                .collect(),
+            cache_dir: None,
+            max_age: DEFAULT_MAX_AGE,
+            offline: false,
+        }
+    }
+
+    /// Use another registry (a mirror or proxy) for an ecosystem
+    pub fn with_registry(mut self, ecosystem: Ecosystem, registry: Registry) -> Self {
+        self.registries.insert(ecosystem, registry);
+        self
+    }
+
+    /// Use the registries of a configuration instead of the public ones
+    pub fn with_config(mut self, config: &RegistryConfig) -> Self {
+        for (ecosystem, registry) in config.iter() {
+            self.registries.insert(ecosystem, registry.clone());
+        }
+        self
+    }
+
+    /// Cache answers in a directory
+    pub fn with_cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Fetch cached answers again once they are older than this
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Only read the cache, never the network
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Default cache directory (`$XDG_CACHE_HOME/scanner/registry` or
+    /// `~/.cache/scanner/registry`)
+    pub fn default_cache_dir() -> Option<PathBuf> {
+        std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+            .map(|cache| cache.join("scanner").join("registry"))
+    }
+
+    /// Metadata of a package, from the cache or its registry
+    ///
+    /// Packages unknown to the registry, of ecosystems without one, and
+    /// missing from the cache when offline have no metadata.
+    pub fn fetch(
+        &self,
+        ecosystem: Ecosystem,
+        name: &str,
+    ) -> Result<Option<RegistryMetadata>, ScanError> {
+        let cached = self.read_cache(ecosystem, name);
+        if let Some(entry) = &cached {
+            if self.offline || age(entry.fetched_at) < self.max_age {
+                return Ok(Some(entry.metadata.clone()));
+            }
+        }
+        if self.offline {
+            return Ok(None);
+        }
+        let Some(url) = self.url(ecosystem, name) else {
+            return Ok(None);
+        };
+
+        debug!(url = %url, "Fetching registry metadata");
+        let mut request = ureq::get(&url)
+            .timeout(TIMEOUT)
+            .set("Accept", "application/json")
+            .set("User-Agent", concat!("scanner/", env!("CARGO_PKG_VERSION")));
+        if let Some(authorization) = self
+            .registries
+            .get(&ecosystem)
+            .and_then(Registry::authorization)
+        {
+            request = request.set("Authorization", &authorization);
+        }
+        let document: Value = match request.call() {
+            Ok(response) => serde_json::from_reader(response.into_reader()).map_err(|e| {
+                ScanError::Registry {
+                    url: url.clone(),
+                    message: e.to_string(),
+                }
+            })?,
+            Err(ureq::Error::Status(404, _)) => return Ok(None),
+            Err(e) => {
+                return Err(ScanError::Registry {
+                    url,
+                    message: e.to_string(),
+                })
+            }
+        };
+        let metadata = match ecosystem {
+            Ecosystem::Node => RegistryMetadata::from_npm(&document),
+            Ecosystem::Python => RegistryMetadata::from_pypi(&document),
+            Ecosystem::Rust => RegistryMetadata::from_crates_io(&document),
+            Ecosystem::Os => None,
+        };
+        if let Some(metadata) = &metadata {
+            self.write_cache(ecosystem, name, metadata);
+        }
+        Ok(metadata)
+    }
+
+    /// [`fetch`](Self::fetch) for async callers, on any runtime
+    ///
+    /// The request holds a thread of the shared [`unblock`] pool while it
+    /// runs. To look up many packages, await
+    /// [`StalenessChecker::check_async`] once instead of a `fetch_async` per
+    /// package.
+    pub async fn fetch_async(
+        &self,
+        ecosystem: Ecosystem,
+        name: &str,
+    ) -> Result<Option<RegistryMetadata>, ScanError> {
+        let client = self.clone();
+        let name = name.to_string();
+        unblock(move || client.fetch(ecosystem, &name)).await
+    }
+
+    /// Registry URL of a package's metadata
+    fn url(&self, ecosystem: Ecosystem, name: &str) -> Option<String> {
+        let registry = &self.registries.get(&ecosystem)?.url;
+        match ecosystem {
+            Ecosystem::Node => Some(format!("{}/{}", registry, name.replace('/', "%2f"))),
+            Ecosystem::Python => Some(format!("{}/pypi/{}/json", registry, name)),
+            Ecosystem::Rust => Some(format!("{}/api/v1/crates/{}", registry, name)),
+            Ecosystem::Os => None,
+        }
+    }
+
+    /// Cache file of a package
+    fn cache_path(&self, ecosystem: Ecosystem, name: &str) -> Option<PathBuf> {
+        let file_name = format!("{}.json", name.replace('/', "%2f"));
+        Some(
+            self.cache_dir
+                .as_ref()?
+                .join(ecosystem.to_string())
+                .join(file_name),
+        )
+    }
+
+    fn read_cache(&self, ecosystem: Ecosystem, name: &str) -> Option<CacheEntry> {
+        let content = fs::read_to_string(self.cache_path(ecosystem, name)?).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn write_cache(&self, ecosystem: Ecosystem, name: &str, metadata: &RegistryMetadata) {
+        let Some(path) = self.cache_path(ecosystem, name) else {
+            return;
+        };
+        let entry = CacheEntry {
+            fetched_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or(Duration::ZERO)
+                .as_secs(),
+            metadata: metadata.clone(),
+        };
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, serde_json::to_vec(&entry)?));
+        if let Err(e) = written {
+            warn!(path = %path.display(), "Failed to cache registry metadata: {}", e);
+        }
+    }
+}
+
+/// How far a dependency's version is behind the latest release
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Staleness {
+    /// Package name
+    pub name: String,
+
+    /// Ecosystem
+    pub ecosystem: Ecosystem,
+
+    /// Installed or locked version
+    pub version: String,
+
+    /// Latest stable version in the registry
+    pub latest: String,
+
+    /// Major versions between the version and the latest one
+    pub major_behind: u64,
+
+    /// Minor release lines published after the version's, up to the latest one
+    pub minor_behind: u64,
+
+    /// Release date of the version (`YYYY-MM-DD`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub released: Option<String>,
+
+    /// Release date of the latest version (`YYYY-MM-DD`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_released: Option<String>,
+
+    /// Days between the two releases
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub days_behind: Option<i64>,
+}
+
+impl Staleness {
+    /// Whether a newer release line than the version's exists
+    pub fn is_outdated(&self) -> bool {
+        self.major_behind > 0 || self.minor_behind > 0
+    }
+}
+
+/// Checker comparing dependency versions with their latest releases
+#[derive(Debug, Clone, Default)]
+pub struct StalenessChecker {
+    client: RegistryClient,
+    cancellation: CancellationToken,
+}
+
+impl StalenessChecker {
+    /// Create a checker fetching metadata with a registry client
+    pub fn new(client: RegistryClient) -> Self {
+        Self {
+            client,
+            cancellation: CancellationToken::new(),
+        }
+    }
+
+    /// Skip the remaining packages once the token is cancelled
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Staleness of each installed or locked version, one entry per package
+    /// version, sorted by ecosystem, name and version
+    ///
+    /// Packages whose metadata cannot be fetched are logged and skipped.
+    pub fn check(&self, dependencies: &[ClassifiedDependency]) -> Vec<Staleness> {
+        let mut versions: BTreeMap<(Ecosystem, String), BTreeSet<String>> = BTreeMap::new();
+        for dep in dependencies {
+            let version = dep
+                .get_version(Classification::Has)
+                .or_else(|| dep.get_version(Classification::Should));
+            if let Some(version) = version {
+                versions
+                    .entry((dep.ecosystem, dep.name.clone()))
+                    .or_default()
+                    .insert(version.to_string());
+            }
+        }
+
+        let packages: Vec<_> = versions.into_iter().collect();
+        packages
+            .par_iter()
+            .flat_map_iter(|((ecosystem, name), versions)| {
+                if self.cancellation.is_cancelled() {
+                    return Vec::new();
+                }
+                let metadata = match self.client.fetch(*ecosystem, name) {
+                    Ok(metadata) => metadata,
+                    Err(e) => {
+                        warn!(package = %name, ecosystem = %ecosystem, "{}", e);
+                        None
+                    }
+                };
+                versions
+                    .iter()
+                    .filter_map(move |version| {
+                        staleness(name, *ecosystem, version, metadata.as_ref()?)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// [`check`](Self::check) for async callers, on any runtime
+    ///
+    /// The check runs on one thread of the shared [`unblock`] pool, with its
+    /// lookups in parallel on rayon as in [`check`](Self::check). The
+    /// dependencies are moved to that thread, so they are taken by value.
+    pub async fn check_async(&self, dependencies: Vec<ClassifiedDependency>) -> Vec<Staleness> {
+        let checker = self.clone();
+        unblock(move || checker.check(&dependencies)).await
+    }
+}
+
+/// Compare a version with the latest release of its package
+fn staleness(
+    name: &str,
+    ecosystem: Ecosystem,
+    version: &str,
+    metadata: &RegistryMetadata,
+) -> Option<Staleness> {
+    let (major, minor) = release_line(&AnyVersion::parse(version, ecosystem).ok()?)?;
+    let (latest_major, latest_minor) =
+        release_line(&AnyVersion::parse(&metadata.latest, ecosystem).ok()?)?;
+
+    // Release lines of stable versions after the version's, up to the latest
+    let mut lines: BTreeSet<(u64, u64)> = metadata
+        .released
+        .keys()
+        .filter_map(|v| AnyVersion::parse(v, ecosystem).ok())
+        .filter(|v| !is_prerelease(v))
+        .filter_map(|v| release_line(&v))
+        .filter(|line| *line > (major, minor) && *line <= (latest_major, latest_minor))
+        .collect();
+    if (latest_major, latest_minor) > (major, minor) {
+        lines.insert((latest_major, latest_minor));
+    }
+
+    let released = metadata.released.get(version).cloned();
+    let latest_released = metadata.released.get(&metadata.latest).cloned();
+    let days_behind = match (&released, &latest_released) {
+        (Some(released), Some(latest)) => Some((days(latest)? - days(released)?).max(0)),
+        _ => None,
+    };
+    Some(Staleness {
+        name: name.to_string(),
+        ecosystem,
+        version: version.to_string(),
+        latest: metadata.latest.clone(),
+        major_behind: latest_major.saturating_sub(major),
+        minor_behind: lines.len() as u64,
+        released,
+        latest_released,
+        days_behind,
+    })
+}
+
+/// Major and minor number of a version
+fn release_line(version: &AnyVersion) -> Option<(u64, u64)> {
+    match version {
+        AnyVersion::Node(v) => Some((v.version().major, v.version().minor)),
+        AnyVersion::Rust(v) => Some((v.version().major, v.version().minor)),
+        AnyVersion::Python(v) => Some((
+            *v.release().first()?,
+            v.release().get(1).copied().unwrap_or(0),
+        )),
+        AnyVersion::Os(_) | AnyVersion::Other(_) => None,
+    }
+}
+
+fn is_prerelease(version: &AnyVersion) -> bool {
+    match version {
+        AnyVersion::Node(v) => !v.version().pre.is_empty(),
+        AnyVersion::Rust(v) => !v.version().pre.is_empty(),
+        AnyVersion::Python(v) => v.is_prerelease(),
+        AnyVersion::Os(_) | AnyVersion::Other(_) => false,
+    }
+}
+
+/// Date part (`YYYY-MM-DD`) of an RFC 3339 time
+fn date(time: &str) -> Option<String> {
+    let date = time.get(..10)?;
+    days(date).map(|_| date.to_string())
+}
+
+/// Days since 1970-01-01 of a `YYYY-MM-DD` date (Howard Hinnant's algorithm)
+fn days(date: &str) -> Option<i64> {
+    let mut parts = date.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: i64 = parts.next()?.parse().ok()?;
+    let day: i64 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    Some(era * 146_097 + day_of_era - 719_468)
+}
+
+/// Time since a Unix timestamp in seconds
+fn age(seconds: u64) -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH + Duration::from_secs(seconds))
+        .unwrap_or(Duration::ZERO)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unblock::block_on;
+    use serde_json::json;
+
+    fn locked(name: &str, ecosystem: Ecosystem, version: &str) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new(name.to_string(), ecosystem);
+        dep.add_classification(
+            Classification::Should,
+            version.to_string(),
+            PathBuf::from("lockfile"),
+        );
+        dep
+    }
+
+    #[test]
+    fn test_registry_documents() {
+        let npm = RegistryMetadata::from_npm(&json!({
+            "dist-tags": { "latest": "4.21.0" },
+            "time": {
+                "created": "2010-12-29T19:38:25.450Z",
+                "4.18.2": "2022-10-08T20:44:15.271Z",
+                "4.21.0": "2024-09-11T17:53:54.025Z"
+            }
+        }))
+        .unwrap();
+        assert_eq!(npm.latest, "4.21.0");
+        assert_eq!(npm.released.len(), 2);
+        assert_eq!(npm.released["4.18.2"], "2022-10-08");
+
+        let pypi = RegistryMetadata::from_pypi(&json!({
+            "info": { "version": "2.32.3" },
+            "releases": {
+                "2.31.0": [
+                    { "upload_time_iso_8601": "2023-05-22T15:12:44.175Z" },
+                    { "upload_time_iso_8601": "2023-05-22T15:12:42.313Z" }
+                ],
+                "2.32.3": [{ "upload_time_iso_8601": "2024-05-29T15:37:47.027Z" }],
+                "0.0.1": []
+            }
+        }))
+        .unwrap();
+        assert_eq!(pypi.latest, "2.32.3");
+        assert_eq!(pypi.released["2.31.0"], "2023-05-22");
+        assert!(!pypi.released.contains_key("0.0.1"));
+
+        let crates = RegistryMetadata::from_crates_io(&json!({
+            "crate": { "max_version": "2.0.0-rc.1", "max_stable_version": "1.0.210" },
+            "versions": [
+                { "num": "1.0.210", "created_at": "2024-09-06T21:11:49.227836+00:00" }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(crates.latest, "1.0.210");
+        assert_eq!(crates.released["1.0.210"], "2024-09-06");
+    }
+
+    #[test]
+    fn test_staleness() {
+        let metadata = RegistryMetadata {
+            latest: "2.1.0".to_string(),
+            released: BTreeMap::from([
+                ("1.4.0".to_string(), "2023-01-01".to_string()),
+                ("1.5.0".to_string(), "2023-03-01".to_string()),
+                ("1.6.0-beta.1".to_string(), "2023-04-01".to_string()),
+                ("2.0.0".to_string(), "2023-06-01".to_string()),
+                ("2.1.0".to_string(), "2024-01-01".to_string()),
+            ]),
+        };
+        let behind = staleness("pkg", Ecosystem::Node, "1.4.0", &metadata).unwrap();
+        assert_eq!(behind.major_behind, 1);
+        assert_eq!(behind.minor_behind, 3);
+        assert_eq!(behind.days_behind, Some(365));
+        assert!(behind.is_outdated());
+
+        let current = staleness("pkg", Ecosystem::Node, "2.1.0", &metadata).unwrap();
+        assert_eq!(current.minor_behind, 0);
+        assert_eq!(current.days_behind, Some(0));
+        assert!(!current.is_outdated());
+
+        assert!(staleness("pkg", Ecosystem::Node, "github:user/pkg", &metadata).is_none());
+    }
+
+    #[test]
+    fn test_offline_uses_cache() {
+        let cache = tempfile::tempdir().unwrap();
+        let client = RegistryClient::new()
+            .with_cache_dir(cache.path())
+            .with_offline(true);
+        client.write_cache(
+            Ecosystem::Python,
+            "requests",
+            &RegistryMetadata {
+                latest: "2.32.3".to_string(),
+                released: BTreeMap::from([
+                    ("2.31.0".to_string(), "2023-05-22".to_string()),
+                    ("2.32.3".to_string(), "2024-05-29".to_string()),
+                ]),
+            },
+        );
+
+        let metadata = block_on(client.fetch_async(Ecosystem::Python, "requests")).unwrap();
+        assert_eq!(metadata.unwrap().latest, "2.32.3");
+
+        let checker = StalenessChecker::new(client);
+        let dependencies = [
+            locked("requests", Ecosystem::Python, "2.31.0"),
+            locked("flask", Ecosystem::Python, "3.0.0"),
+        ];
+        let results = checker.check(&dependencies);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "requests");
+        assert_eq!(results[0].minor_behind, 1);
+        assert_eq!(results[0].days_behind, Some(373));
+        assert_eq!(
+            block_on(checker.check_async(dependencies.to_vec())),
+            results
+        );
+
+        // Nothing is fetched once cancelled
+        let cancellation = CancellationToken::new();
+        let checker = checker.with_cancellation(cancellation.clone());
+        cancellation.cancel();
+        assert!(checker.check(&dependencies).is_empty());
+    }
+
+    #[test]
+    fn test_days() {
+        assert_eq!(days("1970-01-01"), Some(0));
+        assert_eq!(days("2000-03-01"), Some(11_017));
+        assert_eq!(days("2024-13-01"), None);
+        assert_eq!(date("2024-09-06T21:11:49Z").as_deref(), Some("2024-09-06"));
+    }
+}
@@ -0,0 +1,181 @@
+//! Summary statistics for scan results
+//!
+//! The [`Summarizer`] turns classified dependencies, raw dependency records,
+//! security matches and phase timings into a [`ScanSummary`].
+
+use crate::analyzer::InfectedPackageFilter;
+use crate::models::{ClassifiedDependency, DependencyRecord, ScanSummary};
+use crate::progress::PhaseTimings;
+
+/// Builder of scan summary statistics
+pub struct Summarizer;
+
+impl Summarizer {
+    /// Create a new Summarizer
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Summarize classified dependencies
+    ///
+    /// Counts per ecosystem and classification plus version mismatch,
+    /// constraint violation, phantom, missing installation and integrity
+    /// mismatch totals.
+    pub fn summarize(&self, dependencies: &[ClassifiedDependency]) -> ScanSummary {
+        let mut summary = ScanSummary::new();
+        summary.total_dependencies = dependencies.len();
+
+        for dep in dependencies {
+            ScanSummary::increment(&mut summary.by_ecosystem, dep.ecosystem);
+            for classification in dep.classifications.keys() {
+                ScanSummary::increment(&mut summary.by_classification, classification);
+            }
+            if dep.has_version_mismatch {
+                summary.version_mismatches += 1;
+            }
+            if dep.has_constraint_violation {
+                summary.constraint_violations += 1;
+            }
+            if dep.is_phantom {
+                summary.phantom_dependencies += 1;
+            }
+            if dep.missing_installation {
+                summary.missing_installations += 1;
+            }
+            if dep.integrity_mismatch {
+                summary.integrity_mismatches += 1;
+            }
+            if !dep.modified_files.is_empty() {
+                summary.modified_packages += 1;
+            }
+            for flag in &dep.risk_flags {
+                ScanSummary::increment(&mut summary.by_risk_flag, flag);
+            }
+        }
+
+        summary
+    }
+
+    /// Count declared dependency records per dependency type
+    pub fn count_dependency_types(&self, summary: &mut ScanSummary, records: &[DependencyRecord]) {
+        for record in records {
+            ScanSummary::increment(&mut summary.by_dependency_type, record.dep_type);
+        }
+    }
+
+    /// Count dependencies per security status
+    pub fn count_security_statuses(
+        &self,
+        summary: &mut ScanSummary,
+        dependencies: &[ClassifiedDependency],
+        filter: &InfectedPackageFilter,
+    ) {
+        summary.by_security_status.clear();
+        for dep in dependencies {
+            ScanSummary::increment(
+                &mut summary.by_security_status,
+                filter.get_security_status(dep),
+            );
+        }
+    }
+
+    /// Replace the phase durations with the given timings
+    pub fn set_timings(&self, summary: &mut ScanSummary, timings: &PhaseTimings) {
+        summary.durations.clear();
+        summary.total_duration_ms = 0;
+        for timing in timings.iter() {
+            summary.add_duration(
+                timing.phase.key(),
+                timing.items,
+                timing.elapsed.as_millis() as u64,
+            );
+        }
+    }
+}
+
+impl Default for Summarizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::vuln_filter::InfectedPackage;
+    use crate::models::{Classification, DependencyType, Ecosystem, FileType, SourceKind};
+    use crate::progress::ScanPhase;
+    use std::collections::HashSet;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    fn dependency(
+        name: &str,
+        ecosystem: Ecosystem,
+        classification: Classification,
+    ) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new(name.to_string(), ecosystem);
+        dep.add_classification(classification, "1.0.0".to_string(), PathBuf::from("/app"));
+        dep
+    }
+
+    #[test]
+    fn test_summarize_counts() {
+        let mut mismatched = dependency("react", Ecosystem::Node, Classification::Has);
+        mismatched.has_version_mismatch = true;
+        let deps = vec![
+            mismatched,
+            dependency("react", Ecosystem::Node, Classification::Should),
+            dependency("requests", Ecosystem::Python, Classification::Can),
+        ];
+
+        let summary = Summarizer::new().summarize(&deps);
+
+        assert_eq!(summary.total_dependencies, 3);
+        assert_eq!(summary.by_ecosystem["node"], 2);
+        assert_eq!(summary.by_ecosystem["python"], 1);
+        assert_eq!(summary.by_classification["HAS"], 1);
+        assert_eq!(summary.by_classification["CAN"], 1);
+        assert_eq!(summary.version_mismatches, 1);
+        assert_eq!(summary.constraint_violations, 0);
+    }
+
+    #[test]
+    fn test_dependency_types_security_and_timings() {
+        let summarizer = Summarizer::new();
+        let mut summary = ScanSummary::new();
+
+        let records = vec![DependencyRecord {
+            name: "jest".into(),
+            version: "^29.0.0".into(),
+            source_file: Path::new("/app/package.json").into(),
+            dep_type: DependencyType::Development,
+            ecosystem: Ecosystem::Node,
+            file_type: FileType::Manifest,
+            alias: None,
+            source_kind: SourceKind::Registry,
+            resolved: None,
+            integrity: None,
+            dependencies: Vec::new(),
+            span: None,
+        }];
+        summarizer.count_dependency_types(&mut summary, &records);
+        assert_eq!(summary.by_dependency_type["development"], 1);
+
+        let deps = vec![
+            dependency("evil", Ecosystem::Node, Classification::Has),
+            dependency("react", Ecosystem::Node, Classification::Has),
+        ];
+        let mut filter = InfectedPackageFilter::new();
+        filter.add_infected_package(InfectedPackage::new("evil".to_string(), HashSet::new()));
+        summarizer.count_security_statuses(&mut summary, &deps, &filter);
+        assert_eq!(summary.by_security_status["INFECTED"], 1);
+        assert_eq!(summary.by_security_status["NONE"], 1);
+
+        let mut timings = PhaseTimings::new();
+        timings.record(ScanPhase::Discovery, 3, Duration::from_millis(40));
+        summarizer.set_timings(&mut summary, &timings);
+        assert_eq!(summary.durations[0].phase, "discovery");
+        assert_eq!(summary.total_duration_ms, 40);
+    }
+}
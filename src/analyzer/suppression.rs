@@ -0,0 +1,418 @@
+//! Suppressions of accepted findings
+//!
+//! A suppression file lists findings that were reviewed and accepted, so they
+//! stop re-flagging on every scan. Each entry names a package and optionally
+//! an exact version, a path (application root, lockfile or installation
+//! directory, matched as a prefix) and an ecosystem, with the reason it was
+//! accepted and the date it expires. The file is JSON or YAML, by extension:
+//!
+//! ```yaml
+//! - package: lodash
+//!   version: 4.17.20
+//!   path: /srv/legacy-app
+//!   reason: Template helpers are not reachable, replaced in the Q3 rewrite
+//!   expires: 2026-12-31
+//! ```
+//!
+//! The infected package filter gives suppressed dependencies no security
+//! status and records the reason instead, and the failure policy ignores
+//! them. Expired entries suppress nothing and are logged, so accepted risks
+//! come back for review.
+
+use crate::analyzer::VersionInconsistency;
+use crate::models::scan_metadata::rfc3339;
+use crate::models::{Classification, ClassifiedDependency, Ecosystem, ScanError};
+use crate::parsers::read_text;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::warn;
+
+/// An accepted finding
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Suppression {
+    /// Package name
+    pub package: String,
+
+    /// Exact version (any version when absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    /// Path prefix of the application, lockfile or installation directory
+    /// (anywhere when absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+
+    /// Ecosystem: node, python, rust or os (any when absent)
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "ecosystem_name"
+    )]
+    pub ecosystem: Option<Ecosystem>,
+
+    /// Why the finding was accepted
+    pub reason: String,
+
+    /// Last day the suppression applies, `YYYY-MM-DD` (never expires when
+    /// absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+}
+
+impl Suppression {
+    /// Accept a dependency's finding at its installed or locked version and
+    /// application
+    pub fn accept(dep: &ClassifiedDependency, reason: impl Into<String>) -> Self {
+        let version = dep
+            .get_version(Classification::Has)
+            .or_else(|| dep.get_version(Classification::Should))
+            .or_else(|| dep.get_version(Classification::Can));
+        Self {
+            package: dep.name.clone(),
+            version: version.map(str::to_string),
+            path: dep.application_root.clone(),
+            ecosystem: Some(dep.ecosystem),
+            reason: reason.into(),
+            expires: None,
+        }
+    }
+
+    /// Check if the suppression expired before `today` (`YYYY-MM-DD`)
+    pub fn is_expired(&self, today: &str) -> bool {
+        // ISO dates order lexicographically
+        self.expires
+            .as_deref()
+            .is_some_and(|expires| expires < today)
+    }
+
+    /// Check if the suppression covers a dependency
+    pub fn matches(&self, dep: &ClassifiedDependency) -> bool {
+        let paths = dep
+            .application_root
+            .iter()
+            .chain(&dep.installed_path)
+            .chain(dep.source_files.values());
+        self.covers(
+            &dep.name,
+            dep.ecosystem,
+            dep.classifications.values().map(String::as_str),
+            paths,
+        )
+    }
+
+    /// Check if the suppression covers a version inconsistency
+    pub fn matches_inconsistency(&self, inconsistency: &VersionInconsistency) -> bool {
+        let versions = inconsistency
+            .installed
+            .iter()
+            .chain(&inconsistency.locked)
+            .chain(&inconsistency.declared)
+            .map(String::as_str);
+        self.covers(
+            &inconsistency.name,
+            inconsistency.ecosystem,
+            versions,
+            std::iter::once(&inconsistency.application_root),
+        )
+    }
+
+    fn covers<'a>(
+        &self,
+        name: &str,
+        ecosystem: Ecosystem,
+        mut versions: impl Iterator<Item = &'a str>,
+        mut paths: impl Iterator<Item = &'a PathBuf>,
+    ) -> bool {
+        self.package == name
+            && self.ecosystem.is_none_or(|e| e == ecosystem)
+            && self
+                .version
+                .as_deref()
+                .is_none_or(|version| versions.any(|v| v == version))
+            && self
+                .path
+                .as_deref()
+                .is_none_or(|prefix| paths.any(|path| path.starts_with(prefix)))
+    }
+}
+
+/// Suppressions in effect for a scan
+#[derive(Debug, Clone)]
+pub struct Suppressions {
+    entries: Vec<Suppression>,
+    /// Current date, `YYYY-MM-DD`
+    today: String,
+}
+
+impl Suppressions {
+    /// Create an empty set of suppressions dated today
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            today: rfc3339(SystemTime::now())[..10].to_string(),
+        }
+    }
+
+    /// Evaluate expiry dates against another date (`YYYY-MM-DD`)
+    pub fn with_today(mut self, today: impl Into<String>) -> Self {
+        self.today = today.into();
+        self
+    }
+
+    /// Load suppressions from a JSON or YAML file, by extension
+    ///
+    /// Expired entries are kept but logged.
+    pub fn load_from_file(&mut self, path: &Path) -> Result<(), ScanError> {
+        let content = read_text(path).map_err(ScanError::Io)?;
+        let entries: Vec<Suppression> = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str(&content)
+                .map_err(|e| ScanError::json_error(path.to_path_buf(), e))?,
+            _ => serde_yaml::from_str(&content)
+                .map_err(|e| ScanError::yaml_error(path.to_path_buf(), e))?,
+        };
+
+        for entry in entries {
+            if let Some(expires) = entry.expires.as_deref().filter(|e| !is_date(e)) {
+                return Err(ScanError::Parse {
+                    file: path.to_path_buf(),
+                    message: format!(
+                        "Invalid expiry date '{}' for '{}', use YYYY-MM-DD",
+                        expires, entry.package
+                    ),
+                });
+            }
+            if entry.is_expired(&self.today) {
+                warn!(
+                    package = %entry.package,
+                    expires = entry.expires.as_deref().unwrap_or_default(),
+                    reason = %entry.reason,
+                    "Suppression expired, the finding is reported again"
+                );
+            }
+            self.add(entry);
+        }
+        Ok(())
+    }
+
+    /// Write the suppressions as JSON or YAML, by extension
+    pub fn write_to_file(&self, path: &Path) -> Result<(), ScanError> {
+        let content = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::to_string_pretty(&self.entries)
+                .map_err(|e| ScanError::json_error(path.to_path_buf(), e))?,
+            _ => serde_yaml::to_string(&self.entries)
+                .map_err(|e| ScanError::yaml_error(path.to_path_buf(), e))?,
+        };
+        fs::write(path, content).map_err(ScanError::Io)
+    }
+
+    /// Add a suppression
+    pub fn add(&mut self, suppression: Suppression) {
+        if !self.entries.contains(&suppression) {
+            self.entries.push(suppression);
+        }
+    }
+
+    /// Get the unexpired suppression covering a dependency
+    pub fn find(&self, dep: &ClassifiedDependency) -> Option<&Suppression> {
+        self.active().find(|s| s.matches(dep))
+    }
+
+    /// Check if an unexpired suppression covers a dependency
+    pub fn is_suppressed(&self, dep: &ClassifiedDependency) -> bool {
+        self.find(dep).is_some()
+    }
+
+    /// Check if an unexpired suppression covers a version inconsistency
+    pub fn is_inconsistency_suppressed(&self, inconsistency: &VersionInconsistency) -> bool {
+        self.active()
+            .any(|s| s.matches_inconsistency(inconsistency))
+    }
+
+    /// Suppressions accepting every finding, keeping the existing entry when
+    /// one already covers a finding and adding one with `reason` otherwise
+    pub fn baseline<'a>(
+        &self,
+        findings: impl IntoIterator<Item = &'a ClassifiedDependency>,
+        reason: &str,
+    ) -> Suppressions {
+        let mut baseline = Suppressions {
+            entries: Vec::new(),
+            today: self.today.clone(),
+        };
+        for dep in findings {
+            let entry = self
+                .find(dep)
+                .cloned()
+                .unwrap_or_else(|| Suppression::accept(dep, reason));
+            baseline.add(entry);
+        }
+        baseline
+    }
+
+    /// Get all entries, expired ones included
+    pub fn entries(&self) -> &[Suppression] {
+        &self.entries
+    }
+
+    /// Get the number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if there are no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn active(&self) -> impl Iterator<Item = &Suppression> {
+        self.entries.iter().filter(|s| !s.is_expired(&self.today))
+    }
+}
+
+impl Default for Suppressions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Check a `YYYY-MM-DD` date
+fn is_date(value: &str) -> bool {
+    value.len() == 10
+        && value.char_indices().all(|(i, c)| match i {
+            4 | 7 => c == '-',
+            _ => c.is_ascii_digit(),
+        })
+}
+
+/// Ecosystems by their command-line names
+mod ecosystem_name {
+    use crate::models::Ecosystem;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        ecosystem: &Option<Ecosystem>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match ecosystem {
+            Some(ecosystem) => serializer.collect_str(ecosystem),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Ecosystem>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|name| name.parse().map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn lodash() -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new("lodash".to_string(), Ecosystem::Node);
+        dep.add_classification(
+            Classification::Has,
+            "4.17.20".to_string(),
+            PathBuf::from("/srv/app/node_modules/lodash"),
+        );
+        dep.application_root = Some(PathBuf::from("/srv/app"));
+        dep
+    }
+
+    #[test]
+    fn test_suppression_matches() {
+        let dep = lodash();
+        let mut suppression = Suppression::accept(&dep, "accepted");
+        assert_eq!(suppression.version.as_deref(), Some("4.17.20"));
+        assert!(suppression.matches(&dep));
+
+        suppression.path = Some(PathBuf::from("/srv"));
+        assert!(suppression.matches(&dep));
+        suppression.path = Some(PathBuf::from("/opt"));
+        assert!(!suppression.matches(&dep));
+
+        suppression.path = None;
+        suppression.version = Some("4.17.21".to_string());
+        assert!(!suppression.matches(&dep));
+        suppression.version = None;
+        suppression.ecosystem = Some(Ecosystem::Python);
+        assert!(!suppression.matches(&dep));
+    }
+
+    #[test]
+    fn test_suppressions_expire() {
+        let dep = lodash();
+        let mut suppression = Suppression::accept(&dep, "accepted");
+        suppression.expires = Some("2026-06-30".to_string());
+
+        let mut suppressions = Suppressions::new().with_today("2026-06-30");
+        suppressions.add(suppression);
+        assert!(suppressions.is_suppressed(&dep));
+
+        let suppressions = suppressions.with_today("2026-07-01");
+        assert!(!suppressions.is_suppressed(&dep));
+        assert_eq!(suppressions.len(), 1);
+    }
+
+    #[test]
+    fn test_suppressions_file_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("suppressions.yaml");
+        fs::write(
+            &path,
+            "- package: lodash\n  version: 4.17.20\n  ecosystem: node\n  reason: not reachable\n  expires: 2099-01-01\n- package: requests\n  version: 2.0\n  reason: pinned by vendor\n",
+        )
+        .unwrap();
+
+        let mut suppressions = Suppressions::new();
+        suppressions.load_from_file(&path).unwrap();
+        assert_eq!(suppressions.len(), 2);
+        assert_eq!(suppressions.entries()[0].ecosystem, Some(Ecosystem::Node));
+        assert_eq!(suppressions.entries()[1].version.as_deref(), Some("2.0"));
+        assert!(suppressions.is_suppressed(&lodash()));
+
+        let json = temp_dir.path().join("suppressions.json");
+        suppressions.write_to_file(&json).unwrap();
+        let mut reloaded = Suppressions::new();
+        reloaded.load_from_file(&json).unwrap();
+        assert_eq!(reloaded.entries(), suppressions.entries());
+
+        fs::write(
+            &path,
+            "- package: lodash\n  reason: x\n  expires: 31/12/2026\n",
+        )
+        .unwrap();
+        let err = Suppressions::new().load_from_file(&path).unwrap_err();
+        assert!(err.to_string().contains("YYYY-MM-DD"));
+    }
+
+    #[test]
+    fn test_baseline_keeps_existing_entries() {
+        let dep = lodash();
+        let mut axios = ClassifiedDependency::new("axios".to_string(), Ecosystem::Node);
+        axios.add_classification(
+            Classification::Should,
+            "1.6.0".to_string(),
+            PathBuf::from("/srv/app/package-lock.json"),
+        );
+
+        let mut existing = Suppression::accept(&dep, "reviewed");
+        existing.version = None;
+        let mut suppressions = Suppressions::new();
+        suppressions.add(existing.clone());
+
+        let baseline = suppressions.baseline([&dep, &axios, &dep], "baseline");
+        assert_eq!(baseline.len(), 2);
+        assert_eq!(baseline.entries()[0], existing);
+        assert_eq!(baseline.entries()[1].package, "axios");
+        assert_eq!(baseline.entries()[1].version.as_deref(), Some("1.6.0"));
+        assert_eq!(baseline.entries()[1].reason, "baseline");
+    }
+}
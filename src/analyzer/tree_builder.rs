@@ -2,19 +2,69 @@
 //!
 //! This module builds dependency trees from classified dependencies,
 //! showing parent-child relationships and detecting circular dependencies.
-
+//!
+//! Trees of large applications repeat the same subtrees many times. The
+//! builder can cut them down:
+//!
+//! - [`with_max_depth`](TreeBuilder::with_max_depth) stops expanding below a
+//!   depth and marks the cut nodes `truncated`
+//! - [`with_dedupe`](TreeBuilder::with_dedupe) expands each package once per
+//!   tree; later occurrences are marked `duplicate` and have no children, like
+//!   `cargo tree`'s `(*)`
+//! - [`with_flagged_only`](TreeBuilder::with_flagged_only) keeps only the
+//!   branches leading to flagged packages (see
+//!   [`ClassifiedDependency::is_flagged`])
+//! - [`with_focus`](TreeBuilder::with_focus) keeps only the branches leading
+//!   to one package
+
+use crate::models::purl::normalize_pypi_name;
 use crate::models::{
-    Application, Classification, ClassifiedDependency, DependencyNode, DependencyTree,
+    Application, Classification, ClassifiedDependency, DependencyNode, DependencyTree, Ecosystem,
 };
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
+use tracing::warn;
 
 /// Tree builder for constructing dependency trees
-pub struct TreeBuilder;
+pub struct TreeBuilder {
+    max_depth: Option<usize>,
+    dedupe: bool,
+    flagged_only: bool,
+    focus: Option<String>,
+}
 
 impl TreeBuilder {
     /// Create a new TreeBuilder
     pub fn new() -> Self {
-        Self
+        Self {
+            max_depth: None,
+            dedupe: false,
+            flagged_only: false,
+            focus: None,
+        }
+    }
+
+    /// Limit the tree depth; direct dependencies have depth 1
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Expand each package only once per tree
+    pub fn with_dedupe(mut self, dedupe: bool) -> Self {
+        self.dedupe = dedupe;
+        self
+    }
+
+    /// Keep only branches that lead to flagged packages
+    pub fn with_flagged_only(mut self, flagged_only: bool) -> Self {
+        self.flagged_only = flagged_only;
+        self
+    }
+
+    /// Keep only branches that lead to a package
+    pub fn with_focus(mut self, package: impl Into<String>) -> Self {
+        self.focus = Some(package.into());
+        self
     }
 
     /// Build dependency trees for all applications
@@ -27,6 +77,10 @@ impl TreeBuilder {
 
     /// Build a dependency tree for a single application
     pub fn build_tree(&self, application: Application) -> DependencyTree {
+        // Nodes are expanded in output order, so the first occurrence of a
+        // deduplicated package is the one printed first
+        let mut application = application;
+        application.sort_dependencies();
         let mut tree = DependencyTree::new(application.clone());
 
         // Create a lookup map for dependencies
@@ -35,12 +89,31 @@ impl TreeBuilder {
             .iter()
             .map(|d| (d.name.clone(), d))
             .collect();
+        let pruned = self.flagged_only || self.focus.is_some();
+        let relevant = pruned.then(|| {
+            leading_to(&application.dependencies, |dep| {
+                (!self.flagged_only || dep.is_flagged())
+                    && self.focus.as_ref().is_none_or(|focus| {
+                        key(dep.ecosystem, &dep.name) == key(dep.ecosystem, focus)
+                    })
+            })
+        });
+        let mut expanded = HashSet::new();
 
         // Build root nodes (direct dependencies with HAS classification)
         for dep in &application.dependencies {
             if dep.has_classification(Classification::Has) {
+                if relevant.as_ref().is_some_and(|r| !r.contains(&dep.name)) {
+                    continue;
+                }
                 let mut visited = HashSet::new();
-                if let Some(node) = Self::build_node(dep, &dep_map, true, &mut visited) {
+                let mut context = NodeContext {
+                    dep_map: &dep_map,
+                    relevant: relevant.as_ref(),
+                    visited: &mut visited,
+                    expanded: &mut expanded,
+                };
+                if let Some(node) = self.build_node(dep, &mut context, 1) {
                     tree.add_root(node);
                 }
             }
@@ -51,22 +124,17 @@ impl TreeBuilder {
 
     /// Build a dependency node recursively
     fn build_node(
+        &self,
         dep: &ClassifiedDependency,
-        dep_map: &HashMap<String, &ClassifiedDependency>,
-        is_direct: bool,
-        visited: &mut HashSet<String>,
+        context: &mut NodeContext<'_>,
+        depth: usize,
     ) -> Option<DependencyNode> {
         // Detect circular dependencies
-        if visited.contains(&dep.name) {
-            eprintln!(
-                "[warn] Circular dependency detected: {} (breaking cycle)",
-                dep.name
-            );
+        if context.visited.contains(&dep.name) {
+            warn!(package = %dep.name, "Circular dependency detected (breaking cycle)");
             return None;
         }
 
-        visited.insert(dep.name.clone());
-
         // Get the version from the primary classification
         let version = dep
             .primary_classification()
@@ -76,18 +144,35 @@ impl TreeBuilder {
 
         let classification = dep.primary_classification().unwrap_or(Classification::Can);
 
-        let mut node = DependencyNode::new(dep.name.clone(), version, classification, is_direct);
+        let mut node = DependencyNode::new(dep.name.clone(), version, classification, depth == 1);
+
+        let mut children: Vec<&ClassifiedDependency> = dep
+            .dependencies
+            .iter()
+            .filter(|name| context.relevant.is_none_or(|r| r.contains(*name)))
+            .filter_map(|name| context.dep_map.get(name).copied())
+            .collect();
+        children.sort_by(|a, b| a.output_order(b));
+        if children.is_empty() {
+            return Some(node);
+        }
+        if self.max_depth.is_some_and(|max| depth >= max) {
+            node.truncated = true;
+            return Some(node);
+        }
+        if self.dedupe && !context.expanded.insert(dep.name.clone()) {
+            node.duplicate = true;
+            return Some(node);
+        }
 
         // Build child nodes for dependencies
-        for child_name in &dep.dependencies {
-            if let Some(child_dep) = dep_map.get(child_name) {
-                if let Some(child_node) = Self::build_node(child_dep, dep_map, false, visited) {
-                    node.add_dependency(child_node);
-                }
+        context.visited.insert(dep.name.clone());
+        for child_dep in children {
+            if let Some(child_node) = self.build_node(child_dep, context, depth + 1) {
+                node.add_dependency(child_node);
             }
         }
-
-        visited.remove(&dep.name);
+        context.visited.remove(&dep.name);
 
         Some(node)
     }
@@ -99,10 +184,51 @@ impl Default for TreeBuilder {
     }
 }
 
+/// Lookups and state shared while building one tree
+struct NodeContext<'a> {
+    dep_map: &'a HashMap<String, &'a ClassifiedDependency>,
+    relevant: Option<&'a HashSet<String>>,
+    visited: &'a mut HashSet<String>,
+    expanded: &'a mut HashSet<String>,
+}
+
+/// Names of the target packages and of the packages depending on them
+fn leading_to<F>(dependencies: &[ClassifiedDependency], is_target: F) -> HashSet<String>
+where
+    F: Fn(&ClassifiedDependency) -> bool,
+{
+    let mut parents: HashMap<&str, Vec<&str>> = HashMap::new();
+    for dep in dependencies {
+        for child in &dep.dependencies {
+            parents.entry(child).or_default().push(&dep.name);
+        }
+    }
+
+    let mut relevant: HashSet<String> = HashSet::new();
+    let mut queue: VecDeque<&str> = dependencies
+        .iter()
+        .filter(|d| is_target(d))
+        .map(|d| d.name.as_str())
+        .collect();
+    while let Some(name) = queue.pop_front() {
+        if relevant.insert(name.to_string()) {
+            queue.extend(parents.get(name).into_iter().flatten());
+        }
+    }
+    relevant
+}
+
+/// Name used to match packages (normalized for PyPI)
+fn key(ecosystem: Ecosystem, name: &str) -> String {
+    match ecosystem {
+        Ecosystem::Python => normalize_pypi_name(name),
+        _ => name.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::Ecosystem;
     use std::path::PathBuf;
 
     #[test]
@@ -311,4 +437,97 @@ mod tests {
         // Should not include in tree since it's not installed (no HAS)
         assert_eq!(tree.roots.len(), 0);
     }
+
+    /// App where a and b both depend on shared, which depends on leaf
+    fn shared_app() -> Application {
+        let mut app = Application::new(
+            "myapp".to_string(),
+            PathBuf::from("/app"),
+            PathBuf::from("/app/package.json"),
+            Ecosystem::Node,
+        );
+        for (name, children) in [
+            ("a", vec!["shared"]),
+            ("b", vec!["shared"]),
+            ("shared", vec!["leaf"]),
+            ("leaf", vec![]),
+        ] {
+            let mut dep = ClassifiedDependency::new(name.to_string(), Ecosystem::Node);
+            dep.add_classification(
+                Classification::Has,
+                "1.0.0".to_string(),
+                PathBuf::from(format!("/app/node_modules/{}", name)),
+            );
+            dep.dependencies = children.into_iter().map(String::from).collect();
+            app.add_dependency(dep);
+        }
+        app
+    }
+
+    #[test]
+    fn test_max_depth_truncates() {
+        let tree = TreeBuilder::new()
+            .with_max_depth(2)
+            .build_tree(shared_app());
+
+        let a = tree.roots.iter().find(|n| n.name == "a").unwrap();
+        assert_eq!(a.dependencies[0].name, "shared");
+        assert!(a.dependencies[0].truncated);
+        assert!(a.dependencies[0].dependencies.is_empty());
+        let leaf = tree.roots.iter().find(|n| n.name == "leaf").unwrap();
+        assert!(!leaf.truncated);
+    }
+
+    #[test]
+    fn test_dedupe_marks_repeated_subtrees() {
+        let tree = TreeBuilder::new()
+            .with_dedupe(true)
+            .build_tree(shared_app());
+
+        let a = tree.roots.iter().find(|n| n.name == "a").unwrap();
+        let b = tree.roots.iter().find(|n| n.name == "b").unwrap();
+        assert!(!a.dependencies[0].duplicate);
+        assert_eq!(a.dependencies[0].dependencies.len(), 1);
+        assert!(b.dependencies[0].duplicate);
+        assert!(b.dependencies[0].dependencies.is_empty());
+        assert_eq!(tree.count_total_dependencies(), 7);
+    }
+
+    #[test]
+    fn test_flagged_only_keeps_branches_to_flagged() {
+        let mut app = shared_app();
+        app.add_dependency({
+            let mut dep = ClassifiedDependency::new("c".to_string(), Ecosystem::Node);
+            dep.add_classification(
+                Classification::Has,
+                "1.0.0".to_string(),
+                PathBuf::from("/app/node_modules/c"),
+            );
+            dep
+        });
+        let leaf = app
+            .dependencies
+            .iter_mut()
+            .find(|d| d.name == "leaf")
+            .unwrap();
+        leaf.security = Some("INFECTED".to_string());
+
+        let tree = TreeBuilder::new().with_flagged_only(true).build_tree(app);
+
+        let roots: Vec<_> = tree.roots.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(roots, vec!["a", "b", "leaf", "shared"]);
+        assert!(tree.find_dependency("c").is_none());
+        assert_eq!(tree.max_depth(), 2);
+    }
+
+    #[test]
+    fn test_focus_keeps_branches_to_package() {
+        let tree = TreeBuilder::new()
+            .with_focus("shared")
+            .build_tree(shared_app());
+
+        let roots: Vec<_> = tree.roots.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(roots, vec!["a", "b", "shared"]);
+        assert!(tree.roots[0].dependencies[0].dependencies.is_empty());
+    }
 }
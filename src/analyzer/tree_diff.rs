@@ -0,0 +1,435 @@
+//! Structural diff of the dependency trees of two scans
+//!
+//! A flat comparison tells that `cookie@0.6.0` appeared, not why. The
+//! [`TreeDiffer`] walks the trees of each application side by side and
+//! reports every change at the top-most node where the two trees part: a
+//! package added or removed, or a package whose version changed, with the
+//! packages the change brought into its subtree and the ones it dropped.
+//! An `express` upgrade pulling in a new `cookie` is then reported as one
+//! change of `express`, listing `cookie@0.6.0` as introduced.
+//!
+//! Applications are matched by ecosystem, name and root directory, the root
+//! taken relative to the scan root when the results carry one. Applications
+//! left unmatched are then matched by ecosystem and name alone when only one
+//! of the name is left in each scan, so scans of two checkouts in different
+//! directories compare. Subtrees omitted from a tree (expanded earlier or cut
+//! by the depth limit) are not compared.
+
+use crate::models::{DependencyNode, DependencyTree, Ecosystem};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// How an application differs between the two scans
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TreeDiffStatus {
+    /// Only in the new scan
+    Added,
+    /// Only in the old scan
+    Removed,
+    /// In both scans, with different trees
+    Changed,
+}
+
+/// What happened to a package of a tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// The package was added under its parent
+    Added,
+    /// The package was removed from under its parent
+    Removed,
+    /// The package is there in another version
+    VersionChanged,
+}
+
+/// A changed subtree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SubtreeChange {
+    /// Packages leading to the changed one, from the direct dependency
+    /// (empty for a direct dependency)
+    pub path: Vec<String>,
+
+    /// Package name
+    pub name: String,
+
+    /// What happened to the package
+    pub kind: ChangeKind,
+
+    /// Version in the old scan
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_version: Option<String>,
+
+    /// Version in the new scan
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_version: Option<String>,
+
+    /// Packages (`name@version`) below the package in the new scan only
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub introduced: Vec<String>,
+
+    /// Packages (`name@version`) below the package in the old scan only
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dropped: Vec<String>,
+}
+
+impl SubtreeChange {
+    /// Whether the package is a direct dependency of the application
+    pub fn is_direct(&self) -> bool {
+        self.path.is_empty()
+    }
+}
+
+/// Differences of one application's dependency tree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ApplicationTreeDiff {
+    /// Application name
+    pub name: String,
+
+    /// Application root directory, in the new scan unless removed
+    pub root: PathBuf,
+
+    /// Ecosystem
+    pub ecosystem: Ecosystem,
+
+    /// How the application differs
+    pub status: TreeDiffStatus,
+
+    /// Changed subtrees, in tree order (empty for added and removed
+    /// applications)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<SubtreeChange>,
+}
+
+/// Differ of the dependency trees of two scans
+#[derive(Debug, Default)]
+pub struct TreeDiffer {
+    old_root: Option<PathBuf>,
+    new_root: Option<PathBuf>,
+}
+
+impl TreeDiffer {
+    /// Create a differ matching applications by their root path
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match application roots relative to the scan roots of the two scans
+    pub fn with_scan_roots(mut self, old_root: Option<PathBuf>, new_root: Option<PathBuf>) -> Self {
+        self.old_root = old_root;
+        self.new_root = new_root;
+        self
+    }
+
+    /// Differences between the trees of two scans, ordered by application;
+    /// unchanged applications are left out
+    pub fn diff(&self, old: &[DependencyTree], new: &[DependencyTree]) -> Vec<ApplicationTreeDiff> {
+        let old = index_trees(old, self.old_root.as_deref());
+        let mut new = index_trees(new, self.new_root.as_deref());
+
+        let mut pairs = Vec::new();
+        let mut removed = Vec::new();
+        for (key, old_tree) in old {
+            match new.remove(&key) {
+                Some(new_tree) => pairs.push((old_tree, new_tree)),
+                None => removed.push(old_tree),
+            }
+        }
+
+        // Roots that still differ are paired by name when only one
+        // application of the name is left on each side
+        let old_names = count_names(removed.iter().copied());
+        let new_names = count_names(new.values().copied());
+        let mut still_removed = Vec::new();
+        for old_tree in removed {
+            let key = name_key(old_tree);
+            let unique = old_names.get(&key) == Some(&1) && new_names.get(&key) == Some(&1);
+            let moved = new
+                .iter()
+                .find(|(_, new_tree)| unique && name_key(new_tree) == key)
+                .map(|(key, _)| key.clone());
+            match moved.and_then(|key| new.remove(&key)) {
+                Some(new_tree) => pairs.push((old_tree, new_tree)),
+                None => still_removed.push(old_tree),
+            }
+        }
+
+        let mut diffs: Vec<ApplicationTreeDiff> = pairs
+            .into_iter()
+            .filter_map(|(old_tree, new_tree)| {
+                let mut changes = Vec::new();
+                diff_nodes(&old_tree.roots, &new_tree.roots, &[], &mut changes);
+                (!changes.is_empty())
+                    .then(|| application_diff(new_tree, TreeDiffStatus::Changed, changes))
+            })
+            .collect();
+        diffs.extend(
+            still_removed
+                .into_iter()
+                .map(|tree| application_diff(tree, TreeDiffStatus::Removed, Vec::new())),
+        );
+        diffs.extend(
+            new.into_values()
+                .map(|tree| application_diff(tree, TreeDiffStatus::Added, Vec::new())),
+        );
+        diffs.sort_by(|a, b| (a.ecosystem, &a.name, &a.root).cmp(&(b.ecosystem, &b.name, &b.root)));
+        diffs
+    }
+}
+
+fn application_diff(
+    tree: &DependencyTree,
+    status: TreeDiffStatus,
+    changes: Vec<SubtreeChange>,
+) -> ApplicationTreeDiff {
+    ApplicationTreeDiff {
+        name: tree.application.name.clone(),
+        root: tree.application.root_path.clone(),
+        ecosystem: tree.application.ecosystem,
+        status,
+        changes,
+    }
+}
+
+fn name_key(tree: &DependencyTree) -> (Ecosystem, &str) {
+    (tree.application.ecosystem, tree.application.name.as_str())
+}
+
+/// Number of applications of each ecosystem and name
+fn count_names<'a>(
+    trees: impl Iterator<Item = &'a DependencyTree>,
+) -> BTreeMap<(Ecosystem, &'a str), usize> {
+    let mut counts = BTreeMap::new();
+    for tree in trees {
+        *counts.entry(name_key(tree)).or_default() += 1;
+    }
+    counts
+}
+
+/// Application key: ecosystem, name and root relative to the scan root
+type TreeKey = (Ecosystem, String, PathBuf);
+
+fn index_trees<'a>(
+    trees: &'a [DependencyTree],
+    scan_root: Option<&Path>,
+) -> BTreeMap<TreeKey, &'a DependencyTree> {
+    let mut index = BTreeMap::new();
+    for tree in trees {
+        let app = &tree.application;
+        let root = scan_root
+            .and_then(|scan_root| app.root_path.strip_prefix(scan_root).ok())
+            .unwrap_or(&app.root_path)
+            .to_path_buf();
+        index
+            .entry((app.ecosystem, app.name.clone(), root))
+            .or_insert(tree);
+    }
+    index
+}
+
+/// Compare the children of two matching nodes
+fn diff_nodes(
+    old: &[DependencyNode],
+    new: &[DependencyNode],
+    path: &[String],
+    changes: &mut Vec<SubtreeChange>,
+) {
+    let old = by_name(old);
+    let new = by_name(new);
+    let names: BTreeSet<&str> = old.keys().chain(new.keys()).copied().collect();
+
+    for name in names {
+        let change = |kind, old: Option<&DependencyNode>, new: Option<&DependencyNode>| {
+            let old_below = old.map(descendants).unwrap_or_default();
+            let new_below = new.map(descendants).unwrap_or_default();
+            SubtreeChange {
+                path: path.to_vec(),
+                name: name.to_string(),
+                kind,
+                old_version: old.map(|node| node.version.clone()),
+                new_version: new.map(|node| node.version.clone()),
+                introduced: new_below.difference(&old_below).cloned().collect(),
+                dropped: old_below.difference(&new_below).cloned().collect(),
+            }
+        };
+        match (old.get(name), new.get(name)) {
+            (None, Some(node)) => changes.push(change(ChangeKind::Added, None, Some(node))),
+            (Some(node), None) => changes.push(change(ChangeKind::Removed, Some(node), None)),
+            (Some(old), Some(new)) if old.version != new.version => {
+                changes.push(change(ChangeKind::VersionChanged, Some(old), Some(new)))
+            }
+            (Some(old), Some(new)) => {
+                if !is_omitted(old) && !is_omitted(new) {
+                    let mut path = path.to_vec();
+                    path.push(name.to_string());
+                    diff_nodes(&old.dependencies, &new.dependencies, &path, changes);
+                }
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+/// Nodes by name, keeping the first of a name
+fn by_name(nodes: &[DependencyNode]) -> BTreeMap<&str, &DependencyNode> {
+    let mut map = BTreeMap::new();
+    for node in nodes {
+        map.entry(node.name.as_str()).or_insert(node);
+    }
+    map
+}
+
+/// Whether a node's children were left out of its tree
+fn is_omitted(node: &DependencyNode) -> bool {
+    node.duplicate || node.truncated
+}
+
+/// Packages below a node, as `name@version`
+fn descendants(node: &DependencyNode) -> BTreeSet<String> {
+    let mut found = BTreeSet::new();
+    let mut stack: Vec<&DependencyNode> = node.dependencies.iter().collect();
+    while let Some(node) = stack.pop() {
+        found.insert(format!("{}@{}", node.name, node.version));
+        stack.extend(&node.dependencies);
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Application, Classification};
+
+    fn node(name: &str, version: &str, children: Vec<DependencyNode>) -> DependencyNode {
+        let mut node = DependencyNode::new(
+            name.to_string(),
+            version.to_string(),
+            Classification::Has,
+            false,
+        );
+        node.dependencies = children;
+        node
+    }
+
+    fn tree(root: &str, roots: Vec<DependencyNode>) -> DependencyTree {
+        let mut tree = DependencyTree::new(Application::new(
+            "web".to_string(),
+            PathBuf::from(root),
+            PathBuf::from(format!("{}/package.json", root)),
+            Ecosystem::Node,
+        ));
+        tree.roots = roots;
+        tree
+    }
+
+    #[test]
+    fn test_diff_trees() {
+        let old = vec![tree(
+            "/old/web",
+            vec![
+                node("express", "4.18.2", vec![node("cookie", "0.5.0", vec![])]),
+                node(
+                    "webpack",
+                    "5.88.0",
+                    vec![node(
+                        "terser",
+                        "5.1.0",
+                        vec![node("acorn", "8.0.0", vec![])],
+                    )],
+                ),
+                node("left-pad", "1.3.0", vec![]),
+            ],
+        )];
+        let new = vec![tree(
+            "/new/web",
+            vec![
+                node(
+                    "express",
+                    "4.19.0",
+                    vec![
+                        node("cookie", "0.6.0", vec![]),
+                        node("depd", "2.0.0", vec![]),
+                    ],
+                ),
+                node("webpack", "5.88.0", vec![node("terser", "5.2.0", vec![])]),
+                node(
+                    "axios",
+                    "1.6.0",
+                    vec![node("follow-redirects", "1.15.4", vec![])],
+                ),
+            ],
+        )];
+
+        let diffs = TreeDiffer::new()
+            .with_scan_roots(Some(PathBuf::from("/old")), Some(PathBuf::from("/new")))
+            .diff(&old, &new);
+
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].status, TreeDiffStatus::Changed);
+        assert_eq!(diffs[0].root, PathBuf::from("/new/web"));
+        let changes: Vec<(&str, ChangeKind)> = diffs[0]
+            .changes
+            .iter()
+            .map(|c| (c.name.as_str(), c.kind))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("axios", ChangeKind::Added),
+                ("express", ChangeKind::VersionChanged),
+                ("left-pad", ChangeKind::Removed),
+                ("terser", ChangeKind::VersionChanged),
+            ]
+        );
+        let express = &diffs[0].changes[1];
+        assert!(express.is_direct());
+        assert_eq!(express.introduced, vec!["cookie@0.6.0", "depd@2.0.0"]);
+        assert_eq!(express.dropped, vec!["cookie@0.5.0"]);
+        assert_eq!(
+            diffs[0].changes[0].introduced,
+            vec!["follow-redirects@1.15.4"]
+        );
+        let terser = &diffs[0].changes[3];
+        assert_eq!(terser.path, vec!["webpack"]);
+        assert_eq!(terser.old_version.as_deref(), Some("5.1.0"));
+        assert_eq!(terser.dropped, vec!["acorn@8.0.0"]);
+    }
+
+    #[test]
+    fn test_diff_applications() {
+        let old = vec![tree("/srv/web", vec![node("react", "18.2.0", vec![])])];
+        let mut api = tree("/srv/api", vec![]);
+        api.application.name = "api".to_string();
+        let new = vec![tree("/srv/web", vec![node("react", "18.2.0", vec![])]), api];
+
+        let diffs = TreeDiffer::new().diff(&old, &new);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].name, "api");
+        assert_eq!(diffs[0].status, TreeDiffStatus::Added);
+
+        // Moved applications are matched by name when that is unambiguous
+        let moved = vec![tree("/home/web", vec![node("react", "18.3.1", vec![])])];
+        let diffs = TreeDiffer::new().diff(&old, &moved);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].status, TreeDiffStatus::Changed);
+        assert_eq!(diffs[0].root, PathBuf::from("/home/web"));
+
+        let mut moved = moved;
+        moved.push(tree("/home/web2", vec![]));
+        let statuses: Vec<TreeDiffStatus> = TreeDiffer::new()
+            .diff(&old, &moved)
+            .iter()
+            .map(|d| d.status)
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                TreeDiffStatus::Added,
+                TreeDiffStatus::Added,
+                TreeDiffStatus::Removed
+            ]
+        );
+    }
+}
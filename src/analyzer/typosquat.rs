@@ -0,0 +1,322 @@
+//! Typosquatting detection against popular package lists
+//!
+//! Attackers publish packages whose names are one slip away from a popular
+//! package (`lodahs`, `reqeusts`, `serde-json`). The [`TyposquatDetector`]
+//! compares every dependency name with a bundled list of widely used npm,
+//! PyPI and crates.io packages, optionally extended from a file, and reports
+//! names that are not popular themselves but:
+//!
+//! - only differ from a popular name in separators (`-`, `_`, `.`)
+//! - differ by one substitution of a neighbouring key on a QWERTY keyboard
+//! - are one edit (insertion, deletion, substitution or transposition) away
+//!
+//! Names shorter than [`MIN_NAME_LENGTH`] are skipped, as short names are
+//! too often one edit apart by chance.
+
+use crate::models::purl::normalize_pypi_name;
+use crate::models::{ClassifiedDependency, Ecosystem, ScanError};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Shortest name checked for typosquats
+pub const MIN_NAME_LENGTH: usize = 5;
+
+const BUNDLED_NPM: &str = include_str!("data/popular_npm.txt");
+const BUNDLED_PYPI: &str = include_str!("data/popular_pypi.txt");
+const BUNDLED_CRATES: &str = include_str!("data/popular_crates.txt");
+
+/// Rows of a QWERTY keyboard, for adjacency checks
+const KEYBOARD_ROWS: &[&str] = &["1234567890-", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+/// Why a name looks like a typosquat
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TyposquatKind {
+    /// Same name with different separators
+    Separator,
+    /// One character replaced by a neighbouring key
+    KeyboardAdjacent,
+    /// One insertion, deletion, substitution or transposition
+    EditDistance,
+}
+
+impl fmt::Display for TyposquatKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TyposquatKind::Separator => write!(f, "separator"),
+            TyposquatKind::KeyboardAdjacent => write!(f, "keyboard_adjacent"),
+            TyposquatKind::EditDistance => write!(f, "edit_distance"),
+        }
+    }
+}
+
+/// A dependency name that closely resembles a popular package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TyposquatWarning {
+    /// Suspicious package name
+    pub name: String,
+
+    /// Ecosystem
+    pub ecosystem: Ecosystem,
+
+    /// Popular package it resembles
+    pub similar_to: String,
+
+    /// Heuristic that matched
+    pub kind: TyposquatKind,
+}
+
+/// Detector comparing dependency names with popular package names
+pub struct TyposquatDetector {
+    popular: HashMap<Ecosystem, HashSet<String>>,
+}
+
+impl TyposquatDetector {
+    /// Create a detector with the bundled popular package lists
+    pub fn new() -> Self {
+        let mut detector = Self::empty();
+        for (ecosystem, list) in [
+            (Ecosystem::Node, BUNDLED_NPM),
+            (Ecosystem::Python, BUNDLED_PYPI),
+            (Ecosystem::Rust, BUNDLED_CRATES),
+        ] {
+            for name in list.lines().map(str::trim) {
+                if !name.is_empty() && !name.starts_with('#') {
+                    detector.add_popular_package(ecosystem, name);
+                }
+            }
+        }
+        detector
+    }
+
+    /// Create a detector without any popular packages
+    pub fn empty() -> Self {
+        Self {
+            popular: HashMap::new(),
+        }
+    }
+
+    /// Add a popular package name
+    pub fn add_popular_package(&mut self, ecosystem: Ecosystem, name: &str) {
+        self.popular
+            .entry(ecosystem)
+            .or_default()
+            .insert(normalize(ecosystem, name));
+    }
+
+    /// Load additional popular packages from a file
+    ///
+    /// Format: one `ecosystem,package` per line, e.g. `node,left-pad`.
+    /// Returns the number of packages read.
+    pub fn load_from_file(&mut self, path: &Path) -> Result<usize, ScanError> {
+        let content = fs::read_to_string(path).map_err(ScanError::Io)?;
+
+        let mut count = 0;
+        for (line_num, line) in content.lines().enumerate() {
+            let line = line.trim();
+            // Skip empty lines and comments
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((ecosystem, name)) = line.split_once(',') else {
+                return Err(ScanError::Parse {
+                    file: path.to_path_buf(),
+                    message: format!(
+                        "Invalid format at line {}: expected 'ecosystem,package'",
+                        line_num + 1
+                    ),
+                });
+            };
+            let ecosystem = ecosystem.trim().parse::<Ecosystem>()?;
+            self.add_popular_package(ecosystem, name.trim());
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Number of popular packages known for an ecosystem
+    pub fn popular_count(&self, ecosystem: Ecosystem) -> usize {
+        self.popular.get(&ecosystem).map_or(0, HashSet::len)
+    }
+
+    /// Check one name, returning the closest popular package it resembles
+    pub fn check_name(&self, ecosystem: Ecosystem, name: &str) -> Option<(String, TyposquatKind)> {
+        let popular = self.popular.get(&ecosystem)?;
+        let name = normalize(ecosystem, name);
+        if name.chars().count() < MIN_NAME_LENGTH || popular.contains(&name) {
+            return None;
+        }
+
+        popular
+            .iter()
+            .filter_map(|candidate| classify(&name, candidate).map(|kind| (candidate, kind)))
+            .min_by(|(a, ka), (b, kb)| ka.cmp(kb).then_with(|| a.cmp(b)))
+            .map(|(candidate, kind)| (candidate.clone(), kind))
+    }
+
+    /// Check each distinct dependency name once, ordered by ecosystem and name
+    pub fn check(&self, dependencies: &[ClassifiedDependency]) -> Vec<TyposquatWarning> {
+        let names: BTreeMap<(String, String), Ecosystem> = dependencies
+            .iter()
+            .map(|dep| ((dep.ecosystem.to_string(), dep.name.clone()), dep.ecosystem))
+            .collect();
+
+        names
+            .into_iter()
+            .filter_map(|((_, name), ecosystem)| {
+                self.check_name(ecosystem, &name)
+                    .map(|(similar_to, kind)| TyposquatWarning {
+                        name,
+                        ecosystem,
+                        similar_to,
+                        kind,
+                    })
+            })
+            .collect()
+    }
+}
+
+impl Default for TyposquatDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lowercased name (PyPI names are normalized)
+fn normalize(ecosystem: Ecosystem, name: &str) -> String {
+    match ecosystem {
+        Ecosystem::Python => normalize_pypi_name(name),
+        _ => name.to_lowercase(),
+    }
+}
+
+/// Which heuristic, if any, relates a name to a popular name
+fn classify(name: &str, popular: &str) -> Option<TyposquatKind> {
+    let strip = |s: &str| -> String {
+        s.chars()
+            .filter(|c| !matches!(c, '-' | '_' | '.'))
+            .collect()
+    };
+    if strip(name) == strip(popular) {
+        return Some(TyposquatKind::Separator);
+    }
+
+    let a: Vec<char> = name.chars().collect();
+    let b: Vec<char> = popular.chars().collect();
+    if a.len() == b.len() {
+        let diffs: Vec<_> = a.iter().zip(&b).filter(|(x, y)| x != y).collect();
+        if let [(x, y)] = diffs.as_slice() {
+            if keyboard_adjacent(**x, **y) {
+                return Some(TyposquatKind::KeyboardAdjacent);
+            }
+        }
+    }
+
+    (edit_distance(&a, &b) == 1).then_some(TyposquatKind::EditDistance)
+}
+
+/// Whether two keys are next to each other on a QWERTY keyboard
+fn keyboard_adjacent(a: char, b: char) -> bool {
+    let position = |c: char| {
+        KEYBOARD_ROWS
+            .iter()
+            .enumerate()
+            .find_map(|(row, keys)| keys.find(c).map(|col| (row as isize, col as isize)))
+    };
+    match (position(a), position(b)) {
+        (Some((ra, ca)), Some((rb, cb))) => {
+            // Rows are staggered, so the key below-left is also a neighbour
+            let (dr, dc) = (rb - ra, cb - ca);
+            (dr == 0 && dc.abs() == 1)
+                || (dr == 1 && (dc == 0 || dc == -1))
+                || (dr == -1 && (dc == 0 || dc == 1))
+        }
+        _ => false,
+    }
+}
+
+/// Optimal string alignment distance (Levenshtein plus adjacent transpositions)
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    if a.len().abs_diff(b.len()) > 1 {
+        return 2;
+    }
+
+    let mut rows = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_check_name() {
+        let detector = TyposquatDetector::new();
+
+        assert_eq!(
+            detector.check_name(Ecosystem::Node, "lodahs"),
+            Some(("lodash".to_string(), TyposquatKind::EditDistance))
+        );
+        assert_eq!(
+            detector.check_name(Ecosystem::Node, "exprrss"),
+            Some(("express".to_string(), TyposquatKind::KeyboardAdjacent))
+        );
+        assert_eq!(
+            detector.check_name(Ecosystem::Rust, "serde-json"),
+            Some(("serde_json".to_string(), TyposquatKind::Separator))
+        );
+        // Popular names, PyPI spelling variants and short names are not flagged
+        assert_eq!(detector.check_name(Ecosystem::Node, "lodash"), None);
+        assert_eq!(detector.check_name(Ecosystem::Python, "PyYAML"), None);
+        assert_eq!(detector.check_name(Ecosystem::Node, "ws2"), None);
+        assert_eq!(
+            detector.check_name(Ecosystem::Python, "unrelated-name"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_check_and_load_from_file() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "# internal favourites\nnode,acme-widgets").unwrap();
+
+        let mut detector = TyposquatDetector::empty();
+        assert_eq!(detector.load_from_file(file.path()).unwrap(), 1);
+
+        let deps = vec![
+            ClassifiedDependency::new("acme-widget".to_string(), Ecosystem::Node),
+            ClassifiedDependency::new("acme-widget".to_string(), Ecosystem::Node),
+            ClassifiedDependency::new("acme-widgets".to_string(), Ecosystem::Node),
+        ];
+        let warnings = detector.check(&deps);
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].name, "acme-widget");
+        assert_eq!(warnings[0].similar_to, "acme-widgets");
+    }
+}
@@ -4,7 +4,7 @@
 //! including exact matching and range satisfaction checking.
 
 use crate::models::{Ecosystem, ScanError};
-use crate::version::{node_semver, python_pep440, rust_semver};
+use crate::version::{node_semver, os_version, python_pep440, rust_semver, AnyVersion};
 
 /// Version matcher for comparing versions across ecosystems
 pub struct VersionMatcher;
@@ -15,9 +15,13 @@ impl VersionMatcher {
         Self
     }
 
-    /// Check if two versions are exactly equal
-    pub fn exact_match(&self, v1: &str, v2: &str) -> bool {
-        v1.trim() == v2.trim()
+    /// Check if two versions are equal under the rules of an ecosystem
+    ///
+    /// Formatting differences do not count (`1.0` and `1.0.0` in Python,
+    /// `v1.2.3` and `1.2.3` in npm); versions the ecosystem cannot parse are
+    /// compared as text.
+    pub fn exact_match(&self, v1: &str, v2: &str, ecosystem: Ecosystem) -> bool {
+        AnyVersion::parse_lenient(v1, ecosystem) == AnyVersion::parse_lenient(v2, ecosystem)
     }
 
     /// Check if a version satisfies a version range
@@ -37,12 +41,58 @@ impl VersionMatcher {
             Ecosystem::Node => node_semver::satisfies(version, range),
             Ecosystem::Python => python_pep440::satisfies(version, range),
             Ecosystem::Rust => rust_semver::satisfies(version, range),
+            Ecosystem::Os => os_version::satisfies(version, range),
         }
     }
 
+    /// Compare two versions of an ecosystem
+    pub fn compare(
+        &self,
+        a: &str,
+        b: &str,
+        ecosystem: Ecosystem,
+    ) -> Result<std::cmp::Ordering, ScanError> {
+        match ecosystem {
+            Ecosystem::Node => node_semver::compare(a, b),
+            Ecosystem::Python => python_pep440::compare(a, b),
+            Ecosystem::Rust => rust_semver::compare(a, b),
+            Ecosystem::Os => os_version::compare(a, b),
+        }
+    }
+
+    /// Check if a version satisfies every comparator of a compound range
+    ///
+    /// Comparators are separated by whitespace or commas (`>=1.0.0 <1.4.2`,
+    /// `>=2.0,<2.3`); `||` separates alternatives of which one must match.
+    pub fn satisfies_all(
+        &self,
+        version: &str,
+        range: &str,
+        ecosystem: Ecosystem,
+    ) -> Result<bool, ScanError> {
+        for alternative in range.split("||") {
+            let mut satisfied = true;
+            for comparator in comparators(alternative) {
+                if !self.satisfies_range(version, &comparator, ecosystem)? {
+                    satisfied = false;
+                    break;
+                }
+            }
+            if satisfied {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
     /// Detect version mismatch between Has and Should classifications
-    pub fn detect_version_mismatch(&self, has_version: &str, should_version: &str) -> bool {
-        !self.exact_match(has_version, should_version)
+    pub fn detect_version_mismatch(
+        &self,
+        has_version: &str,
+        should_version: &str,
+        ecosystem: Ecosystem,
+    ) -> bool {
+        !self.exact_match(has_version, should_version, ecosystem)
     }
 
     /// Detect constraint violation (Should doesn't satisfy Can range)
@@ -68,6 +118,26 @@ impl Default for VersionMatcher {
     }
 }
 
+/// Split a range into comparators, joining operators separated from their version
+pub(crate) fn comparators(range: &str) -> Vec<String> {
+    let mut comparators: Vec<String> = Vec::new();
+    let mut pending = String::new();
+    for token in range.split(|c: char| c.is_whitespace() || c == ',') {
+        if token.is_empty() {
+            continue;
+        }
+        pending.push_str(token);
+        if token.chars().all(|c| "<>=~^!".contains(c)) {
+            continue;
+        }
+        comparators.push(std::mem::take(&mut pending));
+    }
+    if !pending.is_empty() {
+        comparators.push(pending);
+    }
+    comparators
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,18 +145,22 @@ mod tests {
     #[test]
     fn test_exact_match() {
         let matcher = VersionMatcher::new();
-        assert!(matcher.exact_match("18.2.0", "18.2.0"));
-        assert!(matcher.exact_match(" 18.2.0 ", "18.2.0"));
-        assert!(!matcher.exact_match("18.2.0", "18.2.1"));
-        assert!(!matcher.exact_match("18.2.0", "18.3.0"));
+        assert!(matcher.exact_match("18.2.0", "18.2.0", Ecosystem::Node));
+        assert!(matcher.exact_match(" 18.2.0 ", "18.2.0", Ecosystem::Node));
+        assert!(!matcher.exact_match("18.2.0", "18.2.1", Ecosystem::Node));
+        assert!(!matcher.exact_match("18.2.0", "18.3.0", Ecosystem::Node));
+        assert!(matcher.exact_match("v18.2.0", "18.2.0", Ecosystem::Node));
+        assert!(matcher.exact_match("1.0", "1.0.0", Ecosystem::Python));
+        assert!(!matcher.exact_match("1.0", "1.0.1", Ecosystem::Python));
     }
 
     #[test]
     fn test_detect_version_mismatch() {
         let matcher = VersionMatcher::new();
-        assert!(!matcher.detect_version_mismatch("18.2.0", "18.2.0"));
-        assert!(matcher.detect_version_mismatch("18.2.0", "18.2.1"));
-        assert!(matcher.detect_version_mismatch("18.2.0", "17.0.0"));
+        assert!(!matcher.detect_version_mismatch("18.2.0", "18.2.0", Ecosystem::Node));
+        assert!(matcher.detect_version_mismatch("18.2.0", "18.2.1", Ecosystem::Node));
+        assert!(matcher.detect_version_mismatch("18.2.0", "17.0.0", Ecosystem::Node));
+        assert!(!matcher.detect_version_mismatch("2.31", "2.31.0", Ecosystem::Python));
     }
 
     #[test]
@@ -149,6 +223,24 @@ mod tests {
             .unwrap());
     }
 
+    #[test]
+    fn test_satisfies_all() {
+        let matcher = VersionMatcher::new();
+
+        assert!(matcher
+            .satisfies_all("1.4.1", ">=1.0.0 <1.4.2", Ecosystem::Node)
+            .unwrap());
+        assert!(!matcher
+            .satisfies_all("1.4.2", ">= 1.0.0, < 1.4.2", Ecosystem::Node)
+            .unwrap());
+        assert!(matcher
+            .satisfies_all("2.2.1", ">=2.0,<2.3", Ecosystem::Python)
+            .unwrap());
+        assert!(matcher
+            .satisfies_all("0.9.0", "<0.9.0 || >=0.9.0 <=0.9.0", Ecosystem::Rust)
+            .unwrap());
+    }
+
     #[test]
     fn test_detect_constraint_violation() {
         let matcher = VersionMatcher::new();
@@ -2,63 +2,355 @@
 //!
 //! This module filters classified dependencies to identify matches with
 //! known infected packages (ransomware/worm) and sorts them by priority (HAS > SHOULD > CAN).
+//!
+//! Lists are CSV files of exact versions, or JSON/YAML files whose entries
+//! can also be restricted to an ecosystem and give affected version ranges,
+//! fixed versions and advisory IDs (see
+//! [`InfectedPackageFilter::load_from_structured`]). Ranges are evaluated
+//! with [`VersionMatcher`].
+//!
+//! List entries may carry advisory data: a CVSS score or [`Severity`] and an
+//! EPSS probability. With a minimum severity set, entries rated below it are
+//! ignored; entries without a severity are always kept.
+//!
+//! Dependencies covered by a [`Suppressions`] entry have no security status.
 
-use crate::models::{Classification, ClassifiedDependency, ScanError};
+use crate::analyzer::version_matcher::comparators;
+use crate::analyzer::{Suppressions, VersionMatcher};
+use crate::models::{Classification, ClassifiedDependency, Ecosystem, ScanError};
+use serde::Deserialize;
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::fs;
 use std::path::Path;
+use std::str::FromStr;
+
+/// OSV ecosystems of operating system distributions, without their release
+const OS_DISTRIBUTIONS: &[&str] = &[
+    "alpine",
+    "almalinux",
+    "debian",
+    "homebrew",
+    "opensuse",
+    "red hat",
+    "rocky linux",
+    "suse",
+    "ubuntu",
+];
+
+/// Qualitative severity rating of an advisory (CVSS v3 bands)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// CVSS 0.1 - 3.9
+    Low,
+    /// CVSS 4.0 - 6.9
+    Medium,
+    /// CVSS 7.0 - 8.9
+    High,
+    /// CVSS 9.0 - 10.0
+    Critical,
+}
+
+impl Severity {
+    /// Severity rating of a CVSS base score, or `None` for 0.0
+    pub fn from_cvss(score: f32) -> Option<Self> {
+        match score {
+            s if s >= 9.0 => Some(Severity::Critical),
+            s if s >= 7.0 => Some(Severity::High),
+            s if s >= 4.0 => Some(Severity::Medium),
+            s if s > 0.0 => Some(Severity::Low),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Severity {
+    type Err = ScanError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Severity::Low),
+            "medium" | "moderate" => Ok(Severity::Medium),
+            "high" => Ok(Severity::High),
+            "critical" => Ok(Severity::Critical),
+            _ => Err(ScanError::UnknownSeverity(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Low => write!(f, "LOW"),
+            Severity::Medium => write!(f, "MEDIUM"),
+            Severity::High => write!(f, "HIGH"),
+            Severity::Critical => write!(f, "CRITICAL"),
+        }
+    }
+}
 
 /// An infected package specification with multiple versions
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct InfectedPackage {
     /// Package name
     pub name: String,
-    /// Infected versions (empty set means all versions are infected)
+    /// Ecosystem the entry applies to (all ecosystems if unset)
+    pub ecosystem: Option<Ecosystem>,
+    /// Infected versions (all versions are infected if both this and `ranges` are empty)
     pub versions: HashSet<String>,
+    /// Affected version ranges, e.g. `>=1.0.0 <1.4.2`
+    pub ranges: Vec<String>,
+    /// Versions that fix the issue
+    pub fixed: Vec<String>,
+    /// Advisory identifiers (CVE, GHSA, OSV, ...)
+    pub ids: Vec<String>,
+    /// Severity rating, given directly or derived from the CVSS score
+    pub severity: Option<Severity>,
+    /// CVSS base score (0.0 - 10.0)
+    pub cvss: Option<f32>,
+    /// EPSS exploitation probability (0.0 - 1.0)
+    pub epss: Option<f32>,
 }
 
 impl InfectedPackage {
     /// Create a new infected package with versions
     pub fn new(name: String, versions: HashSet<String>) -> Self {
-        Self { name, versions }
+        Self {
+            name,
+            ecosystem: None,
+            versions,
+            ranges: Vec::new(),
+            fixed: Vec::new(),
+            ids: Vec::new(),
+            severity: None,
+            cvss: None,
+            epss: None,
+        }
     }
 
-    /// Check if this infected package matches a dependency
-    pub fn matches(&self, dep: &ClassifiedDependency) -> bool {
-        if dep.name != self.name {
-            return false;
+    /// Restrict the entry to one ecosystem
+    pub fn with_ecosystem(mut self, ecosystem: Ecosystem) -> Self {
+        self.ecosystem = Some(ecosystem);
+        self
+    }
+
+    /// Add an affected version range
+    pub fn with_range(mut self, range: impl Into<String>) -> Self {
+        self.ranges.push(range.into());
+        self
+    }
+
+    /// Add a fixed version
+    pub fn with_fixed(mut self, version: impl Into<String>) -> Self {
+        self.fixed.push(version.into());
+        self
+    }
+
+    /// Add an advisory identifier
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.ids.push(id.into());
+        self
+    }
+
+    /// Set the severity rating
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = Some(severity);
+        self
+    }
+
+    /// Set the CVSS score, deriving the severity if none is set
+    pub fn with_cvss(mut self, score: f32) -> Self {
+        self.cvss = Some(score);
+        if self.severity.is_none() {
+            self.severity = Severity::from_cvss(score);
         }
+        self
+    }
+
+    /// Set the EPSS probability
+    pub fn with_epss(mut self, probability: f32) -> Self {
+        self.epss = Some(probability);
+        self
+    }
 
-        // If no versions specified, match any version
-        if self.versions.is_empty() {
+    /// Check if the entry is about a dependency's package, regardless of version
+    pub fn applies_to(&self, dep: &ClassifiedDependency) -> bool {
+        dep.name == self.name && self.ecosystem.is_none_or(|eco| eco == dep.ecosystem)
+    }
+
+    /// Check if an exact version is affected
+    ///
+    /// Versions that cannot be compared against a range are not affected by it.
+    pub fn affects(&self, version: &str, ecosystem: Ecosystem) -> bool {
+        if self.versions.is_empty() && self.ranges.is_empty() {
             return true;
         }
+        if self.versions.contains(version) {
+            return true;
+        }
+        let matcher = VersionMatcher::new();
+        self.ranges.iter().any(|range| {
+            matcher
+                .satisfies_all(version, range, ecosystem)
+                .unwrap_or(false)
+        })
+    }
 
-        // Use primary version (Has > Should > Can) for matching
-        if let Some(dep_version) = dep.get_primary_version() {
-            if self.versions.contains(dep_version) {
-                return true;
+    /// Check if a declared range could resolve to an affected version
+    ///
+    /// Ranges are compared by their bounds: the declared range could resolve
+    /// to an affected version if it is satisfied by a listed version, or by
+    /// an affected inclusive bound of an affected range or of the declared
+    /// range itself. Exclusive bounds (`<`, `>`) and `!=` operands name
+    /// versions a range excludes, so they are never candidates.
+    pub fn could_affect(&self, range: &str, ecosystem: Ecosystem) -> bool {
+        if self.versions.is_empty() && self.ranges.is_empty() {
+            return true;
+        }
+        let matcher = VersionMatcher::new();
+        let affected_bounds = self
+            .ranges
+            .iter()
+            .map(String::as_str)
+            .chain([range])
+            .flat_map(inclusive_bounds)
+            .filter(|bound| self.affects(bound, ecosystem));
+        self.versions
+            .iter()
+            .cloned()
+            .chain(affected_bounds)
+            .any(|version| matches!(matcher.satisfies_all(&version, range, ecosystem), Ok(true)))
+    }
+
+    /// Security status of a dependency against this entry alone
+    fn status(&self, dep: &ClassifiedDependency) -> SecurityStatus {
+        // HAS (installed) or SHOULD (lockfile) affected = INFECTED
+        for classification in [Classification::Has, Classification::Should] {
+            if let Some(version) = dep.get_version(classification) {
+                if self.affects(version, dep.ecosystem) {
+                    return SecurityStatus::Infected;
+                }
+            }
+        }
+
+        // CAN (manifest range) could resolve to an affected version = MATCH_VERSION
+        if let Some(range) = dep.get_version(Classification::Can) {
+            if self.could_affect(range, dep.ecosystem) {
+                return SecurityStatus::MatchVersion;
             }
         }
 
-        false
+        // Package name matches but no version match
+        SecurityStatus::MatchPackage
+    }
+
+    /// Check if this infected package matches a dependency
+    pub fn matches(&self, dep: &ClassifiedDependency) -> bool {
+        self.get_matched_version(dep).is_some()
     }
 
     /// Get the matched version from a dependency
     pub fn get_matched_version(&self, dep: &ClassifiedDependency) -> Option<String> {
+        if !self.applies_to(dep) {
+            return None;
+        }
         // Use primary version (Has > Should > Can) for matching
-        if let Some(dep_version) = dep.get_primary_version() {
-            if self.versions.is_empty() || self.versions.contains(dep_version) {
-                return Some(dep_version.to_string());
+        dep.get_primary_version()
+            .filter(|version| self.affects(version, dep.ecosystem))
+            .map(str::to_string)
+    }
+}
+
+/// Versions a range includes at its bounds: those of its comparators, without
+/// exclusive bounds and `!=` operands
+fn inclusive_bounds(range: &str) -> impl Iterator<Item = String> + '_ {
+    range
+        .split("||")
+        .flat_map(comparators)
+        .filter(|comparator| {
+            let operator = comparator
+                .find(|c: char| !"<>=~^!".contains(c))
+                .map_or(comparator.as_str(), |end| &comparator[..end]);
+            !matches!(operator, "<" | ">" | "<<" | ">>" | "!=")
+        })
+        .map(|comparator| {
+            comparator
+                .trim_start_matches(|c: char| "<>=~^!".contains(c))
+                .to_string()
+        })
+}
+
+/// Entry of a JSON or YAML infected list
+#[derive(Debug, Deserialize)]
+struct InfectedListEntry {
+    name: String,
+    #[serde(default)]
+    ecosystem: Option<String>,
+    #[serde(default)]
+    versions: Vec<String>,
+    #[serde(default)]
+    affected: Vec<String>,
+    #[serde(default)]
+    fixed: Vec<String>,
+    #[serde(default)]
+    ids: Vec<String>,
+    #[serde(default)]
+    severity: Option<String>,
+    #[serde(default)]
+    cvss: Option<f32>,
+    #[serde(default)]
+    epss: Option<f32>,
+}
+
+impl InfectedListEntry {
+    /// Validate the entry into an [`InfectedPackage`], describing what is invalid
+    fn into_package(self) -> Result<InfectedPackage, String> {
+        let mut infected = InfectedPackage::new(self.name, self.versions.into_iter().collect());
+        if let Some(ecosystem) = self.ecosystem {
+            infected = infected.with_ecosystem(parse_list_ecosystem(&ecosystem)?);
+        }
+        infected.ranges = self.affected;
+        infected.fixed = self.fixed;
+        infected.ids = self.ids;
+        if let Some(severity) = self.severity {
+            infected = infected.with_severity(severity.parse().map_err(|e| format!("{}", e))?);
+        }
+        if let Some(score) = self.cvss {
+            if !(0.0..=10.0).contains(&score) {
+                return Err(format!("invalid CVSS score {}", score));
+            }
+            infected = infected.with_cvss(score);
+        }
+        if let Some(probability) = self.epss {
+            if !(0.0..=1.0).contains(&probability) {
+                return Err(format!("invalid EPSS probability {}", probability));
             }
+            infected = infected.with_epss(probability);
         }
-        None
+        Ok(infected)
+    }
+}
+
+/// Parse an ecosystem name, accepting OSV names (npm, PyPI, crates.io, and
+/// distributions such as `Debian:12`)
+fn parse_list_ecosystem(name: &str) -> Result<Ecosystem, String> {
+    let name = name.to_lowercase();
+    let distribution = name.split(':').next().unwrap_or_default();
+    match name.as_str() {
+        "npm" => Ok(Ecosystem::Node),
+        "pypi" => Ok(Ecosystem::Python),
+        "crates.io" | "cargo" => Ok(Ecosystem::Rust),
+        _ if OS_DISTRIBUTIONS.contains(&distribution) => Ok(Ecosystem::Os),
+        other => other.parse().map_err(|e: ScanError| e.to_string()),
     }
 }
 
 /// Infected package filter for matching and sorting dependencies
 pub struct InfectedPackageFilter {
-    infected_packages: HashMap<String, InfectedPackage>,
+    infected_packages: HashMap<String, Vec<InfectedPackage>>,
+    min_severity: Option<Severity>,
+    suppressions: Suppressions,
 }
 
 impl InfectedPackageFilter {
@@ -66,15 +358,74 @@ impl InfectedPackageFilter {
     pub fn new() -> Self {
         Self {
             infected_packages: HashMap::new(),
+            min_severity: None,
+            suppressions: Suppressions::new(),
+        }
+    }
+
+    /// Ignore entries rated below a severity (entries without one are kept)
+    pub fn with_min_severity(mut self, severity: Severity) -> Self {
+        self.min_severity = Some(severity);
+        self
+    }
+
+    /// Report no security status for dependencies covered by a suppression
+    pub fn with_suppressions(mut self, suppressions: Suppressions) -> Self {
+        self.suppressions = suppressions;
+        self
+    }
+
+    /// Load infected packages from a file, by extension: JSON, YAML or CSV
+    pub fn load_from_file(&mut self, path: &Path) -> Result<(), ScanError> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") | Some("yaml") | Some("yml") => self.load_from_structured(path),
+            _ => self.load_from_csv(path),
         }
     }
 
+    /// Load infected packages from a JSON or YAML list
+    ///
+    /// Each entry has a name and optionally an ecosystem, exact `versions`,
+    /// `affected` ranges, `fixed` versions, advisory `ids`, and a `severity`,
+    /// `cvss` score and `epss` probability:
+    ///
+    /// ```yaml
+    /// - name: lodash
+    ///   ecosystem: npm
+    ///   affected: [">=4.0.0 <4.17.21"]
+    ///   fixed: ["4.17.21"]
+    ///   ids: [GHSA-35jh-r3h4-6jhm, CVE-2021-23337]
+    ///   cvss: 7.2
+    /// ```
+    pub fn load_from_structured(&mut self, path: &Path) -> Result<(), ScanError> {
+        let content = fs::read_to_string(path).map_err(ScanError::Io)?;
+        let entries: Vec<InfectedListEntry> = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str(&content)
+                .map_err(|e| ScanError::json_error(path.to_path_buf(), e))?,
+            _ => serde_yaml::from_str(&content)
+                .map_err(|e| ScanError::yaml_error(path.to_path_buf(), e))?,
+        };
+
+        for entry in entries {
+            let name = entry.name.clone();
+            let infected = entry.into_package().map_err(|message| ScanError::Parse {
+                file: path.to_path_buf(),
+                message: format!("Invalid entry for '{}': {}", name, message),
+            })?;
+            self.add_infected_package(infected);
+        }
+
+        Ok(())
+    }
+
     /// Load infected packages from a CSV file
     ///
-    /// CSV format: package,version1 | version2 | version3
+    /// CSV format: package,version1 | version2 | version3[,cvss or severity[,epss]]
     /// Example:
     /// webpack-loader-httpfile,0.2.1
     /// zapier-async-storage,1.0.3 | 1.0.2 | 1.0.1
+    /// lodash,4.17.20,7.4,0.02
+    /// left-pad,1.3.0,critical
     pub fn load_from_csv(&mut self, path: &Path) -> Result<(), ScanError> {
         let content = fs::read_to_string(path).map_err(ScanError::Io)?;
 
@@ -85,9 +436,9 @@ impl InfectedPackageFilter {
                 continue;
             }
 
-            // Parse CSV line: package,version1 | version2 | version3
-            let parts: Vec<&str> = line.splitn(2, ',').collect();
-            if parts.len() != 2 {
+            // Parse CSV line: package,version1 | version2 | version3[,cvss[,epss]]
+            let parts: Vec<&str> = line.splitn(4, ',').collect();
+            if parts.len() < 2 {
                 return Err(ScanError::Parse {
                     file: path.to_path_buf(),
                     message: format!(
@@ -107,17 +458,46 @@ impl InfectedPackageFilter {
                 .filter(|v| !v.is_empty())
                 .collect();
 
-            let infected = InfectedPackage::new(package_name.clone(), versions);
-            self.infected_packages.insert(package_name, infected);
+            let invalid = |field: &str, value: &str| ScanError::Parse {
+                file: path.to_path_buf(),
+                message: format!("Invalid {} '{}' at line {}", field, value, line_num + 1),
+            };
+            let mut infected = InfectedPackage::new(package_name, versions);
+            if let Some(rating) = parts.get(2).map(|r| r.trim()).filter(|r| !r.is_empty()) {
+                infected = match rating.parse::<f32>() {
+                    Ok(score) if (0.0..=10.0).contains(&score) => infected.with_cvss(score),
+                    Ok(_) => return Err(invalid("CVSS score", rating)),
+                    Err(_) => infected
+                        .with_severity(rating.parse().map_err(|_| invalid("severity", rating))?),
+                };
+            }
+            if let Some(epss) = parts.get(3).map(|e| e.trim()).filter(|e| !e.is_empty()) {
+                match epss.parse::<f32>() {
+                    Ok(probability) if (0.0..=1.0).contains(&probability) => {
+                        infected = infected.with_epss(probability)
+                    }
+                    _ => return Err(invalid("EPSS probability", epss)),
+                }
+            }
+            self.add_infected_package(infected);
         }
 
         Ok(())
     }
 
     /// Add an infected package manually
+    ///
+    /// Replaces a previous entry for the same package and ecosystem without
+    /// advisory identifiers; entries with identifiers accumulate.
     pub fn add_infected_package(&mut self, infected: InfectedPackage) {
-        self.infected_packages
-            .insert(infected.name.clone(), infected);
+        let entries = self
+            .infected_packages
+            .entry(infected.name.clone())
+            .or_default();
+        if infected.ids.is_empty() {
+            entries.retain(|e| !(e.ids.is_empty() && e.ecosystem == infected.ecosystem));
+        }
+        entries.push(infected);
     }
 
     /// Filter dependencies to only include infected ones
@@ -133,63 +513,116 @@ impl InfectedPackageFilter {
         matches!(self.get_security_status(dep), SecurityStatus::Infected)
     }
 
+    /// Get the most relevant list entry for a dependency
+    ///
+    /// Entries rated below the minimum severity are ignored. The entry giving
+    /// the most severe status wins, then the one with the highest severity.
+    pub fn get_infected_package(&self, dep: &ClassifiedDependency) -> Option<&InfectedPackage> {
+        self.best_match(dep).map(|(infected, _)| infected)
+    }
+
     /// Get the security status for a dependency
     pub fn get_security_status(&self, dep: &ClassifiedDependency) -> SecurityStatus {
-        if let Some(infected) = self.infected_packages.get(&dep.name) {
-            // Check HAS (installed) - exact match = INFECTED
-            if let Some(has_version) = dep.get_version(Classification::Has) {
-                if infected.versions.is_empty() || infected.versions.contains(has_version) {
-                    return SecurityStatus::Infected;
-                }
-            }
-
-            // Check SHOULD (lockfile) - exact match = INFECTED
-            if let Some(should_version) = dep.get_version(Classification::Should) {
-                if infected.versions.is_empty() || infected.versions.contains(should_version) {
-                    return SecurityStatus::Infected;
-                }
-            }
+        self.best_match(dep)
+            .map(|(_, status)| status)
+            .unwrap_or(SecurityStatus::None)
+    }
 
-            // Check CAN (manifest/semver range) - could match = MATCH_VERSION
-            if let Some(can_version) = dep.get_version(Classification::Can) {
-                // Check if any infected version could satisfy the semver range
-                if self.semver_could_match(can_version, &infected.versions, dep.ecosystem) {
-                    return SecurityStatus::MatchVersion;
-                }
-            }
+    /// Entries about a dependency's package, without those below the minimum severity
+    fn applicable<'a>(
+        &'a self,
+        dep: &ClassifiedDependency,
+    ) -> impl Iterator<Item = &'a InfectedPackage> + 'a {
+        let ecosystem = dep.ecosystem;
+        self.infected_packages
+            .get(&dep.name)
+            .into_iter()
+            .flatten()
+            .filter(move |infected| infected.ecosystem.is_none_or(|eco| eco == ecosystem))
+            .filter(|infected| match (self.min_severity, infected.severity) {
+                (Some(min), Some(severity)) => severity >= min,
+                _ => true,
+            })
+    }
 
-            // Package name matches but no version match
-            SecurityStatus::MatchPackage
-        } else {
-            SecurityStatus::None
+    /// Most relevant entry and the status it gives
+    fn best_match(&self, dep: &ClassifiedDependency) -> Option<(&InfectedPackage, SecurityStatus)> {
+        if self.suppressions.is_suppressed(dep) {
+            return None;
         }
+        self.applicable(dep)
+            .map(|infected| (infected, infected.status(dep)))
+            .min_by(|(a, sa), (b, sb)| {
+                sa.priority()
+                    .cmp(&sb.priority())
+                    .then_with(|| b.severity.cmp(&a.severity))
+            })
     }
 
-    /// Check if a semver range could match any of the infected versions
-    fn semver_could_match(
-        &self,
-        range: &str,
-        infected_versions: &HashSet<String>,
-        ecosystem: crate::models::Ecosystem,
-    ) -> bool {
-        use crate::analyzer::VersionMatcher;
-
-        // If no specific versions listed, any range could match
-        if infected_versions.is_empty() {
-            return true;
+    /// Get the list entry whose advisory applies to a dependency
+    ///
+    /// Only infected and possibly infected (MATCH_VERSION) dependencies have one.
+    pub fn get_advisory(&self, dep: &ClassifiedDependency) -> Option<&InfectedPackage> {
+        match self.best_match(dep) {
+            Some((infected, SecurityStatus::Infected | SecurityStatus::MatchVersion)) => {
+                Some(infected)
+            }
+            _ => None,
         }
+    }
 
+    /// Get the nearest fixed version that no list entry affects
+    ///
+    /// Candidates are the fixed versions of the package's entries above its
+    /// installed or locked version. With a declared range, only candidates
+    /// satisfying it are recommended, so the fix needs no manifest change.
+    /// Only infected and possibly infected dependencies get a recommendation.
+    pub fn recommended_version(&self, dep: &ClassifiedDependency) -> Option<String> {
+        self.get_advisory(dep)?;
+        let entries: Vec<_> = self.applicable(dep).collect();
         let matcher = VersionMatcher::new();
+        let ecosystem = dep.ecosystem;
+        let current = dep
+            .get_version(Classification::Has)
+            .or_else(|| dep.get_version(Classification::Should));
+        let declared = dep.get_version(Classification::Can);
+
+        let mut candidates: Vec<&str> = entries
+            .iter()
+            .flat_map(|infected| infected.fixed.iter().map(String::as_str))
+            .filter(|version| entries.iter().all(|e| !e.affects(version, ecosystem)))
+            .filter(|version| {
+                current.is_none_or(|current| {
+                    matches!(
+                        matcher.compare(version, current, ecosystem),
+                        Ok(Ordering::Greater)
+                    )
+                })
+            })
+            .filter(|version| {
+                declared.is_none_or(|range| {
+                    matcher
+                        .satisfies_all(version, range, ecosystem)
+                        .unwrap_or(false)
+                })
+            })
+            .collect();
+        candidates.sort_by(|a, b| matcher.compare(a, b, ecosystem).unwrap_or(Ordering::Equal));
+        candidates.first().map(|version| version.to_string())
+    }
 
-        // Check if any infected version satisfies the range
-        for infected_version in infected_versions {
-            match matcher.satisfies_range(infected_version, range, ecosystem) {
-                Ok(true) => return true,
-                _ => continue,
-            }
-        }
-
-        false
+    /// Set the security status and advisory data of a dependency
+    pub fn annotate(&self, dep: &mut ClassifiedDependency) {
+        dep.security = Some(self.get_security_status(dep).to_string());
+        let advisory = self.get_advisory(dep);
+        dep.severity = advisory
+            .and_then(|a| a.severity)
+            .map(|severity| severity.to_string());
+        dep.cvss = advisory.and_then(|a| a.cvss);
+        dep.epss = advisory.and_then(|a| a.epss);
+        dep.advisory_ids = advisory.map(|a| a.ids.clone()).unwrap_or_default();
+        dep.recommended_version = self.recommended_version(dep);
+        dep.suppressed = self.suppressions.find(dep).map(|s| s.reason.clone());
     }
 
     /// Filter and sort by priority (HAS > SHOULD > CAN)
@@ -227,9 +660,9 @@ impl InfectedPackageFilter {
         }
     }
 
-    /// Get the number of loaded infected packages
+    /// Get the number of loaded infected package entries
     pub fn count(&self) -> usize {
-        self.infected_packages.len()
+        self.infected_packages.values().map(Vec::len).sum()
     }
 }
 
@@ -353,6 +786,201 @@ mod tests {
         assert_eq!(filter.count(), 4);
     }
 
+    #[test]
+    fn test_load_advisory_columns() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        use std::io::Write;
+        writeln!(temp_file, "lodash,4.17.20,7.4,0.02").unwrap();
+        writeln!(temp_file, "left-pad,1.3.0,Critical").unwrap();
+        writeln!(temp_file, "wenk,1.0.9 | 1.0.10,,0.5").unwrap();
+        temp_file.flush().unwrap();
+
+        let mut filter = InfectedPackageFilter::new();
+        filter.load_from_csv(temp_file.path()).unwrap();
+
+        let dep = |name: &str| ClassifiedDependency::new(name.to_string(), Ecosystem::Node);
+        let lodash = filter.get_infected_package(&dep("lodash")).unwrap();
+        assert_eq!(lodash.severity, Some(Severity::High));
+        assert_eq!(lodash.cvss, Some(7.4));
+        assert_eq!(lodash.epss, Some(0.02));
+        let left_pad = filter.get_infected_package(&dep("left-pad")).unwrap();
+        assert_eq!(left_pad.severity, Some(Severity::Critical));
+        assert_eq!(left_pad.cvss, None);
+        let wenk = filter.get_infected_package(&dep("wenk")).unwrap();
+        assert_eq!(wenk.severity, None);
+        assert_eq!(wenk.epss, Some(0.5));
+
+        let mut bad = NamedTempFile::new().unwrap();
+        writeln!(bad, "lodash,4.17.20,severe").unwrap();
+        bad.flush().unwrap();
+        assert!(InfectedPackageFilter::new()
+            .load_from_csv(bad.path())
+            .is_err());
+    }
+
+    #[test]
+    fn test_load_from_structured() {
+        use std::io::Write;
+        let mut json = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+        write!(
+            json,
+            r#"[
+                {{"name": "lodash", "ecosystem": "npm", "affected": [">=4.0.0 <4.17.21"],
+                  "fixed": ["4.17.21"], "ids": ["GHSA-35jh-r3h4-6jhm"], "cvss": 7.2}},
+                {{"name": "lodash", "ecosystem": "npm", "versions": ["4.17.15"],
+                  "ids": ["CVE-2020-8203"], "severity": "critical"}},
+                {{"name": "requests", "ecosystem": "PyPI", "affected": [">=2.3.0,<2.31.0"]}},
+                {{"name": "openssl", "ecosystem": "Debian:12", "affected": ["<<3.0.11-1~deb12u2"]}}
+            ]"#
+        )
+        .unwrap();
+        json.flush().unwrap();
+
+        let mut filter = InfectedPackageFilter::new();
+        filter.load_from_file(json.path()).unwrap();
+        assert_eq!(filter.count(), 4);
+
+        let dep = |name: &str, ecosystem, classification, version: &str| {
+            let mut dep = ClassifiedDependency::new(name.to_string(), ecosystem);
+            dep.add_classification(classification, version.to_string(), PathBuf::from("/app"));
+            dep
+        };
+        let status = |d: &ClassifiedDependency| filter.get_security_status(d);
+
+        let lodash = dep("lodash", Ecosystem::Node, Classification::Has, "4.17.15");
+        assert_eq!(status(&lodash), SecurityStatus::Infected);
+        // Both advisories match; the more severe one is reported
+        let advisory = filter.get_advisory(&lodash).unwrap();
+        assert_eq!(advisory.ids, vec!["CVE-2020-8203"]);
+
+        let fixed = dep("lodash", Ecosystem::Node, Classification::Should, "4.17.21");
+        assert_eq!(status(&fixed), SecurityStatus::MatchPackage);
+        assert!(filter.get_advisory(&fixed).is_none());
+        let declared = dep("lodash", Ecosystem::Node, Classification::Can, "^4.0.0");
+        assert_eq!(status(&declared), SecurityStatus::MatchVersion);
+
+        // Entries are limited to their ecosystem
+        let python_lodash = dep("lodash", Ecosystem::Python, Classification::Has, "4.17.15");
+        assert_eq!(status(&python_lodash), SecurityStatus::None);
+        let requests = dep("requests", Ecosystem::Python, Classification::Has, "2.28.1");
+        assert_eq!(status(&requests), SecurityStatus::Infected);
+        let openssl = dep(
+            "openssl",
+            Ecosystem::Os,
+            Classification::Has,
+            "3.0.11-1~deb12u1",
+        );
+        assert_eq!(status(&openssl), SecurityStatus::Infected);
+        let patched = dep(
+            "openssl",
+            Ecosystem::Os,
+            Classification::Has,
+            "3.0.11-1~deb12u2",
+        );
+        assert_eq!(status(&patched), SecurityStatus::MatchPackage);
+
+        let mut yaml = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        writeln!(
+            yaml,
+            "- name: evil
+  ecosystem: cobol"
+        )
+        .unwrap();
+        yaml.flush().unwrap();
+        assert!(InfectedPackageFilter::new()
+            .load_from_file(yaml.path())
+            .is_err());
+    }
+
+    #[test]
+    fn test_recommended_version() {
+        let mut filter = InfectedPackageFilter::new();
+        filter.add_infected_package(
+            InfectedPackage::new("lodash".to_string(), HashSet::new())
+                .with_range(">=4.0.0 <4.17.12")
+                .with_fixed("4.17.12")
+                .with_id("GHSA-jf85-cpcp-j695"),
+        );
+        filter.add_infected_package(
+            InfectedPackage::new("lodash".to_string(), HashSet::new())
+                .with_range(">=4.0.0 <4.17.21")
+                .with_fixed("4.17.21")
+                .with_fixed("5.0.0")
+                .with_id("GHSA-35jh-r3h4-6jhm"),
+        );
+
+        let lodash = |declared: &str| {
+            let mut dep = ClassifiedDependency::new("lodash".to_string(), Ecosystem::Node);
+            dep.add_classification(
+                Classification::Has,
+                "4.17.11".to_string(),
+                PathBuf::from("/app/node_modules/lodash"),
+            );
+            dep.add_classification(
+                Classification::Can,
+                declared.to_string(),
+                PathBuf::from("/app/package.json"),
+            );
+            dep
+        };
+
+        // 4.17.12 is still affected by the second advisory
+        assert_eq!(
+            filter.recommended_version(&lodash("^4.17.0")).as_deref(),
+            Some("4.17.21")
+        );
+        assert_eq!(
+            filter.recommended_version(&lodash(">=5.0.0")).as_deref(),
+            Some("5.0.0")
+        );
+        assert_eq!(filter.recommended_version(&lodash("~4.16.0")), None);
+
+        let mut safe = ClassifiedDependency::new("lodash".to_string(), Ecosystem::Node);
+        safe.add_classification(
+            Classification::Has,
+            "4.17.21".to_string(),
+            PathBuf::from("/app/node_modules/lodash"),
+        );
+        assert_eq!(filter.recommended_version(&safe), None);
+    }
+
+    #[test]
+    fn test_min_severity() {
+        let versions: HashSet<String> = ["1.0.0".to_string()].into();
+        let mut filter = InfectedPackageFilter::new().with_min_severity(Severity::High);
+        filter.add_infected_package(
+            InfectedPackage::new("low-risk".to_string(), versions.clone()).with_cvss(3.1),
+        );
+        filter.add_infected_package(
+            InfectedPackage::new("critical".to_string(), versions.clone()).with_cvss(9.8),
+        );
+        filter.add_infected_package(InfectedPackage::new("unrated".to_string(), versions));
+
+        let statuses: Vec<_> = ["low-risk", "critical", "unrated"]
+            .iter()
+            .map(|name| {
+                let mut dep = ClassifiedDependency::new(name.to_string(), Ecosystem::Node);
+                dep.add_classification(
+                    Classification::Has,
+                    "1.0.0".to_string(),
+                    PathBuf::from("/app/node_modules"),
+                );
+                filter.get_security_status(&dep)
+            })
+            .collect();
+
+        assert_eq!(
+            statuses,
+            vec![
+                SecurityStatus::None,
+                SecurityStatus::Infected,
+                SecurityStatus::Infected
+            ]
+        );
+        assert_eq!(Severity::from_cvss(0.0), None);
+        assert_eq!("moderate".parse::<Severity>().unwrap(), Severity::Medium);
+    }
+
     #[test]
     fn test_filter() {
         let mut filter = InfectedPackageFilter::new();
@@ -447,6 +1075,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_security_status_excluded_bounds() {
+        let mut filter = InfectedPackageFilter::new();
+        filter.add_infected_package(InfectedPackage::new(
+            "evil".to_string(),
+            HashSet::from(["2.0.0".to_string()]),
+        ));
+
+        let status = |filter: &InfectedPackageFilter, ecosystem, range: &str| {
+            let mut dep = ClassifiedDependency::new("evil".to_string(), ecosystem);
+            dep.add_classification(
+                Classification::Can,
+                range.to_string(),
+                PathBuf::from("/app/manifest"),
+            );
+            filter.get_security_status(&dep)
+        };
+
+        // Ranges naming the infected version only to exclude it
+        assert_eq!(
+            status(&filter, Ecosystem::Node, "<2.0.0"),
+            SecurityStatus::MatchPackage
+        );
+        assert_eq!(
+            status(&filter, Ecosystem::Node, ">=1.0.0 <2.0.0"),
+            SecurityStatus::MatchPackage
+        );
+        assert_eq!(
+            status(&filter, Ecosystem::Python, ">=1.0,<2.0.0"),
+            SecurityStatus::MatchPackage
+        );
+        assert_eq!(
+            status(&filter, Ecosystem::Python, "!=2.0.0"),
+            SecurityStatus::MatchPackage
+        );
+        assert_eq!(
+            status(&filter, Ecosystem::Node, "<=2.0.0"),
+            SecurityStatus::MatchVersion
+        );
+        assert_eq!(
+            status(&filter, Ecosystem::Python, ">=1.0,<=2.0.0"),
+            SecurityStatus::MatchVersion
+        );
+
+        // A declared range inside an affected range, by its own lower bound
+        filter.add_infected_package(
+            InfectedPackage::new("evil".to_string(), HashSet::new()).with_range(">=1.0.0 <2.0.0"),
+        );
+        assert_eq!(
+            status(&filter, Ecosystem::Node, ">=1.5.0 <1.8.0"),
+            SecurityStatus::MatchVersion
+        );
+        assert_eq!(
+            status(&filter, Ecosystem::Node, ">2.0.0"),
+            SecurityStatus::MatchPackage
+        );
+    }
+
     #[test]
     fn test_security_status_infected() {
         let mut filter = InfectedPackageFilter::new();
@@ -0,0 +1,62 @@
+//! Cancellation of running scans
+//!
+//! A [`CancellationToken`] is shared between a scan and whatever may stop it:
+//! the CLI's Ctrl+C handler, or a service shutting down. Once it is cancelled
+//! the walk enters no further directories, no further package files or
+//! installation directories are parsed and registry lookups are skipped.
+//! What was found until then is still classified and returned, with
+//! [`ScanLimit::Cancelled`](crate::models::ScanLimit::Cancelled) in the
+//! truncated limits of the scan metadata, so an interrupted scan keeps its
+//! partial results.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Flag shared by clones, set once to stop the work checking it
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a token that is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the work checking this token or any of its clones
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Check if the token was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Tokens are equal when they are clones of each other
+impl PartialEq for CancellationToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.cancelled, &other.cancelled)
+    }
+}
+
+impl Eq for CancellationToken {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancellation_token() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!clone.is_cancelled());
+        assert_eq!(token, clone);
+        assert_ne!(token, CancellationToken::new());
+
+        token.cancel();
+        assert!(clone.is_cancelled());
+    }
+}
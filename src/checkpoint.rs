@@ -0,0 +1,427 @@
+//! Checkpoints of long scans
+//!
+//! A scan given a checkpoint file saves its progress there while it runs:
+//! the package files and installation directories it discovered, and the
+//! results of those parsed so far. The file is rewritten at the end of
+//! discovery, at most every [`CHECKPOINT_INTERVAL`] while parsing and when
+//! the scan ends, each time through a temporary file renamed over it, so a
+//! scan killed at any point leaves its last complete checkpoint behind.
+//!
+//! A scan resuming from the checkpoint of an earlier scan of the same root
+//! with the same [`CheckpointOptions`] skips the walk when the earlier scan
+//! completed it, and reuses the results of every file and directory already
+//! parsed. Each result carries the [`FileStamp`] (size and modification time)
+//! its file or directory had when it was parsed; one that changed since, as
+//! after an `npm install` during a maintenance window, is parsed again. An
+//! installation directory's stamp changes when packages are added to or
+//! removed from it. A scan that runs to the end removes its checkpoint; one
+//! cut short by a limit or a cancellation keeps it for the next resume.
+
+use crate::indexer::{FileIndex, ScanMode};
+use crate::models::{DependencyRecord, InstalledPackage, ScanErrorEntry};
+use crate::parsers::manifest::RequirementsMode;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tracing::{debug, info, warn};
+
+/// Version of the checkpoint format; checkpoints of another version are
+/// ignored
+pub const CHECKPOINT_VERSION: u32 = 2;
+
+/// Longest time between two saves of a checkpoint while parsing
+pub const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Progress of a scan, as saved to its checkpoint file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Checkpoint format version
+    pub version: u32,
+
+    /// Version of the scanner that wrote the checkpoint
+    pub scanner_version: String,
+
+    /// Scanned directory
+    pub root: PathBuf,
+
+    /// Options that shape the results of the scan; a scan with other
+    /// options does not resume from the checkpoint
+    pub options: CheckpointOptions,
+
+    /// Package files and installation directories, once discovery completed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<FileIndex>,
+
+    /// Results of the package files parsed so far
+    #[serde(default)]
+    pub files: BTreeMap<PathBuf, ParsedFile>,
+
+    /// Packages of the installation directories parsed so far
+    #[serde(default)]
+    pub install_dirs: BTreeMap<PathBuf, ParsedInstallDir>,
+}
+
+/// Options deciding which files a scan finds and what parsing them gives
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckpointOptions {
+    /// What the scan collects
+    pub scan_mode: ScanMode,
+
+    /// Whether installation directories are searched for package files
+    pub include_install_dirs: bool,
+
+    /// Directory names skipped by the walk
+    pub exclude_dirs: Vec<String>,
+
+    /// Whether symlinked directories are followed
+    pub follow_symlinks: bool,
+
+    /// Limit on the number of directories visited
+    pub max_visited_dirs: Option<usize>,
+
+    /// Depth limit of the walk
+    pub max_depth: Option<usize>,
+
+    /// Include globs of the walk
+    pub include_globs: Vec<String>,
+
+    /// Exclude globs of the walk
+    pub exclude_globs: Vec<String>,
+
+    /// Whether installed files are rehashed against their RECORD
+    pub verify_hashes: bool,
+
+    /// Bytes of each package file parsed
+    pub max_file_size: Option<u64>,
+
+    /// How requirements files are read
+    pub requirements_mode: RequirementsMode,
+
+    /// Scanned git revision
+    pub git_rev: Option<String>,
+
+    /// Whether the files come from a path list rather than a walk
+    pub paths: bool,
+
+    /// Whether the root is a snapshot archive
+    pub archive: bool,
+}
+
+/// Size and modification time of a file or directory on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    /// Size in bytes
+    pub size: u64,
+
+    /// Last modification, where the filesystem records it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<SystemTime>,
+}
+
+impl FileStamp {
+    /// Stamp of a path, following symlinks; `None` when it cannot be read
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Self {
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+/// Result of parsing a manifest or lockfile
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParsedFile {
+    /// Stamp of the file when it was parsed (none for files read from a git
+    /// revision or an archive)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stamp: Option<FileStamp>,
+
+    /// Dependency records of the file
+    pub records: Vec<DependencyRecord>,
+
+    /// Why the file could not be read or parsed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ScanErrorEntry>,
+}
+
+/// Result of parsing an installation directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParsedInstallDir {
+    /// Stamp of the directory when it was parsed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stamp: Option<FileStamp>,
+    /// Packages installed in the directory
+    pub packages: Vec<InstalledPackage>,
+
+    /// Why the directory could not be parsed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ScanErrorEntry>,
+}
+
+impl Checkpoint {
+    /// Empty checkpoint of a scan of `root` with the given options
+    pub fn new(root: impl Into<PathBuf>, options: CheckpointOptions) -> Self {
+        Self {
+            version: CHECKPOINT_VERSION,
+            scanner_version: env!("CARGO_PKG_VERSION").to_string(),
+            root: root.into(),
+            options,
+            index: None,
+            files: BTreeMap::new(),
+            install_dirs: BTreeMap::new(),
+        }
+    }
+
+    /// Read a checkpoint file
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(io::Error::from)
+    }
+
+    /// Write the checkpoint, replacing the file only once it is complete
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+        fs::write(&temp, serde_json::to_vec(self)?)?;
+        fs::rename(&temp, path)
+    }
+
+    /// Check if a scan of `root` with the given options can resume from
+    /// the checkpoint
+    pub fn resumes(&self, root: &Path, options: &CheckpointOptions) -> bool {
+        self.version == CHECKPOINT_VERSION
+            && self.scanner_version == env!("CARGO_PKG_VERSION")
+            && self.root == root
+            && self.options == *options
+    }
+}
+
+/// Checkpoint of a running scan, saved as it progresses
+pub(crate) struct Checkpointer {
+    path: PathBuf,
+    state: Mutex<State>,
+}
+
+struct State {
+    checkpoint: Checkpoint,
+    saved_at: Instant,
+}
+
+impl Checkpointer {
+    /// Start the checkpoint of a scan, from the checkpoint file when
+    /// resuming a scan of the same root and options
+    pub(crate) fn start(
+        path: &Path,
+        root: &Path,
+        options: CheckpointOptions,
+        resume: bool,
+    ) -> Self {
+        let mut checkpoint = Checkpoint::new(root, options);
+        if resume {
+            match Checkpoint::load(path) {
+                Ok(saved) if saved.resumes(root, &checkpoint.options) => {
+                    info!(
+                        checkpoint = %path.display(),
+                        files = saved.files.len(),
+                        install_dirs = saved.install_dirs.len(),
+                        "Resuming scan from checkpoint"
+                    );
+                    checkpoint = saved;
+                }
+                Ok(_) => warn!(
+                    checkpoint = %path.display(),
+                    "Checkpoint is of another root, options or scanner version, starting over"
+                ),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    debug!(checkpoint = %path.display(), "No checkpoint to resume from");
+                }
+                Err(e) => warn!(
+                    checkpoint = %path.display(),
+                    error = %e,
+                    "Cannot read checkpoint, starting over"
+                ),
+            }
+        }
+        Self {
+            path: path.to_path_buf(),
+            state: Mutex::new(State {
+                checkpoint,
+                saved_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Discovered files and directories of the resumed scan
+    pub(crate) fn index(&self) -> Option<FileIndex> {
+        self.state.lock().unwrap().checkpoint.index.clone()
+    }
+
+    /// Result of a package file parsed by the resumed scan, if the file
+    /// still has the stamp it was parsed with
+    pub(crate) fn parsed_file(&self, path: &Path, stamp: Option<FileStamp>) -> Option<ParsedFile> {
+        let state = self.state.lock().unwrap();
+        let parsed = state.checkpoint.files.get(path)?;
+        if parsed.stamp != stamp {
+            debug!(file = %path.display(), "File changed since the checkpoint, parsing it again");
+            return None;
+        }
+        Some(parsed.clone())
+    }
+
+    /// Result of an installation directory parsed by the resumed scan, if
+    /// the directory still has the stamp it was parsed with
+    pub(crate) fn parsed_install_dir(
+        &self,
+        path: &Path,
+        stamp: Option<FileStamp>,
+    ) -> Option<ParsedInstallDir> {
+        let state = self.state.lock().unwrap();
+        let parsed = state.checkpoint.install_dirs.get(path)?;
+        if parsed.stamp != stamp {
+            debug!(
+                install_dir = %path.display(),
+                "Installation directory changed since the checkpoint, parsing it again"
+            );
+            return None;
+        }
+        Some(parsed.clone())
+    }
+
+    /// Record the complete discovery of the scan and save
+    pub(crate) fn record_index(&self, index: &FileIndex) {
+        let mut state = self.state.lock().unwrap();
+        state.checkpoint.index = Some(index.clone());
+        self.save(&mut state);
+    }
+
+    /// Record a parsed package file, saving when the last save is old enough
+    pub(crate) fn record_file(&self, path: &Path, parsed: ParsedFile) {
+        let mut state = self.state.lock().unwrap();
+        state.checkpoint.files.insert(path.to_path_buf(), parsed);
+        self.save_periodically(&mut state);
+    }
+
+    /// Record a parsed installation directory, saving when the last save is
+    /// old enough
+    pub(crate) fn record_install_dir(&self, path: &Path, parsed: ParsedInstallDir) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .checkpoint
+            .install_dirs
+            .insert(path.to_path_buf(), parsed);
+        self.save_periodically(&mut state);
+    }
+
+    /// End the checkpoint: removed when the scan completed, saved for the
+    /// next resume otherwise
+    pub(crate) fn finish(self, completed: bool) {
+        if completed {
+            match fs::remove_file(&self.path) {
+                Ok(()) => debug!(checkpoint = %self.path.display(), "Removed checkpoint"),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => warn!(
+                    checkpoint = %self.path.display(),
+                    error = %e,
+                    "Cannot remove checkpoint"
+                ),
+            }
+        } else {
+            let mut state = self.state.lock().unwrap();
+            self.save(&mut state);
+            info!(
+                checkpoint = %self.path.display(),
+                "Scan stopped early, resume it with the checkpoint"
+            );
+        }
+    }
+
+    fn save_periodically(&self, state: &mut State) {
+        if state.saved_at.elapsed() >= CHECKPOINT_INTERVAL {
+            self.save(state);
+        }
+    }
+
+    /// Save the checkpoint; a failure is logged, the scan goes on
+    fn save(&self, state: &mut State) {
+        match state.checkpoint.save(&self.path) {
+            Ok(()) => debug!(
+                checkpoint = %self.path.display(),
+                files = state.checkpoint.files.len(),
+                install_dirs = state.checkpoint.install_dirs.len(),
+                "Saved checkpoint"
+            ),
+            Err(e) => warn!(
+                checkpoint = %self.path.display(),
+                error = %e,
+                "Cannot save checkpoint"
+            ),
+        }
+        state.saved_at = Instant::now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::manifest::PackageJsonParser;
+    use crate::parsers::Parser;
+
+    fn options(scan_mode: ScanMode) -> CheckpointOptions {
+        CheckpointOptions {
+            scan_mode,
+            include_install_dirs: false,
+            exclude_dirs: Vec::new(),
+            follow_symlinks: false,
+            max_visited_dirs: None,
+            max_depth: None,
+            include_globs: Vec::new(),
+            exclude_globs: Vec::new(),
+            verify_hashes: false,
+            max_file_size: None,
+            requirements_mode: RequirementsMode::Auto,
+            git_rev: None,
+            paths: false,
+            archive: false,
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_round_trip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("scan.checkpoint");
+        let full = options(ScanMode::Full);
+
+        let mut checkpoint = Checkpoint::new("/srv", full.clone());
+        checkpoint.files.insert(
+            PathBuf::from("/srv/app/package.json"),
+            ParsedFile {
+                stamp: Some(FileStamp {
+                    size: 41,
+                    modified: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+                }),
+                records: PackageJsonParser
+                    .parse(
+                        r#"{"dependencies": {"lodash": "^4.17.21"}}"#,
+                        Path::new("/srv/app/package.json"),
+                    )
+                    .unwrap(),
+                error: None,
+            },
+        );
+        checkpoint.save(&path).unwrap();
+        assert!(!temp_dir.path().join("scan.checkpoint.tmp").exists());
+
+        let loaded = Checkpoint::load(&path).unwrap();
+        assert_eq!(loaded.files, checkpoint.files);
+        assert!(loaded.resumes(Path::new("/srv"), &full));
+        assert!(!loaded.resumes(Path::new("/srv"), &options(ScanMode::DeclaredOnly)));
+        assert!(!loaded.resumes(Path::new("/opt"), &full));
+    }
+}
@@ -0,0 +1,635 @@
+//! Running containers as scan roots
+//!
+//! Auditing what running workloads have installed needs their filesystems,
+//! which container runtimes keep as overlay mounts. [`list_containers`] asks
+//! a runtime for its running containers:
+//!
+//! - Docker, through the Engine API on its socket (`/var/run/docker.sock`):
+//!   `GET /containers/json`, then `GET /containers/<id>/json` for the image,
+//!   labels, overlay directories (`GraphDriver.Data`) and main process
+//! - containerd, through its socket (`/run/containerd/containerd.sock`) with
+//!   the `ctr` command, which must be installed, as the socket speaks gRPC:
+//!   the running tasks of every namespace, and `containers info` for the
+//!   image and labels. The merged filesystem of a task is the `rootfs` mount
+//!   in the runtime's state directory next to the socket
+//!
+//! The merged directory is the container's whole filesystem: the image
+//! layers and what was written since the container started. The upper
+//! directory only holds the latter, so scanning it finds the packages
+//! installed into a running container. Directories a runtime doesn't report
+//! are read through the container's main process: `/proc/<pid>/root` for the
+//! merged view, and the `upperdir` of its root overlay mount in
+//! `/proc/<pid>/mountinfo`.
+//!
+//! Kubernetes runs each pod as a sandbox (pause) container carrying the pod's
+//! labels, next to the pod's containers, whose labels name the pod.
+//! Sandboxes are not listed; the containers of a pod carry it as
+//! `namespace/name`, with its labels. [`tag_containers`] then records the
+//! container each entry of a scan of the listed directories was found in.
+
+use crate::models::{ClassifiedDependency, ContainerRef, ScanError};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+use tracing::warn;
+
+/// Default Docker Engine API socket
+pub const DOCKER_SOCKET: &str = "/var/run/docker.sock";
+
+/// Default containerd socket
+pub const CONTAINERD_SOCKET: &str = "/run/containerd/containerd.sock";
+
+/// Directory next to the containerd socket holding the state of its tasks
+const CONTAINERD_TASK_STATE: &str = "io.containerd.runtime.v2.task";
+
+const POD_NAME_LABEL: &str = "io.kubernetes.pod.name";
+const POD_NAMESPACE_LABEL: &str = "io.kubernetes.pod.namespace";
+const POD_UID_LABEL: &str = "io.kubernetes.pod.uid";
+const CONTAINER_NAME_LABEL: &str = "io.kubernetes.container.name";
+
+/// Labels set by the Kubernetes runtime integrations rather than the user
+const RUNTIME_LABEL_PREFIXES: &[&str] = &["io.kubernetes.", "io.cri-containerd."];
+
+/// A container runtime, reached through its socket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContainerRuntime {
+    /// Docker Engine
+    Docker { socket: PathBuf },
+    /// containerd
+    Containerd { socket: PathBuf },
+}
+
+impl ContainerRuntime {
+    /// Name of the runtime
+    pub fn name(&self) -> &'static str {
+        match self {
+            ContainerRuntime::Docker { .. } => "docker",
+            ContainerRuntime::Containerd { .. } => "containerd",
+        }
+    }
+}
+
+impl fmt::Display for ContainerRuntime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for ContainerRuntime {
+    type Err = String;
+
+    /// `docker` or `containerd`, with the default socket, or followed by
+    /// `:<socket>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, socket) = match s.split_once(':') {
+            Some((name, socket)) => (name, Some(PathBuf::from(socket))),
+            None => (s, None),
+        };
+        match name {
+            "docker" => Ok(ContainerRuntime::Docker {
+                socket: socket.unwrap_or_else(|| PathBuf::from(DOCKER_SOCKET)),
+            }),
+            "containerd" => Ok(ContainerRuntime::Containerd {
+                socket: socket.unwrap_or_else(|| PathBuf::from(CONTAINERD_SOCKET)),
+            }),
+            _ => Err(format!(
+                "unknown container runtime: {} (expected docker or containerd)",
+                name
+            )),
+        }
+    }
+}
+
+/// Directory of a container that is scanned
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ContainerLayer {
+    /// The container's whole filesystem
+    #[default]
+    Merged,
+    /// What was written since the container started
+    Upper,
+}
+
+impl FromStr for ContainerLayer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "merged" => Ok(ContainerLayer::Merged),
+            "upper" => Ok(ContainerLayer::Upper),
+            _ => Err(format!(
+                "unknown container layer: {} (expected merged or upper)",
+                s
+            )),
+        }
+    }
+}
+
+/// A running container and where its filesystem is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunningContainer {
+    /// The container, as recorded on the entries found in it
+    pub container: ContainerRef,
+
+    /// Process ID of the container's main process
+    pub pid: Option<u32>,
+
+    /// Merged overlay directory, as reported by the runtime
+    pub merged_dir: Option<PathBuf>,
+
+    /// Upper overlay directory, as reported by the runtime
+    pub upper_dir: Option<PathBuf>,
+}
+
+impl RunningContainer {
+    /// Directory of a layer, from the runtime or read through the main
+    /// process
+    pub fn dir(&self, layer: ContainerLayer) -> Option<PathBuf> {
+        match layer {
+            ContainerLayer::Merged => self
+                .merged_dir
+                .clone()
+                .filter(|dir| dir.is_dir())
+                .or_else(|| Some(PathBuf::from(format!("/proc/{}/root", self.pid?)))),
+            ContainerLayer::Upper => self.upper_dir.clone().or_else(|| {
+                let mountinfo =
+                    fs::read_to_string(format!("/proc/{}/mountinfo", self.pid?)).ok()?;
+                overlay_upper_dir(&mountinfo, Path::new("/"))
+            }),
+        }
+    }
+}
+
+/// List the running containers of a runtime, without pod sandboxes
+pub fn list_containers(runtime: &ContainerRuntime) -> Result<Vec<RunningContainer>, ScanError> {
+    let listed = match runtime {
+        ContainerRuntime::Docker { socket } => list_docker_containers(socket)?,
+        ContainerRuntime::Containerd { socket } => list_containerd_containers(socket)?,
+    };
+    Ok(with_pods(listed))
+}
+
+/// Record the container each entry was found in, given the directory
+/// scanned for each container, returning how many entries were found in one
+pub fn tag_containers<'a>(
+    dependencies: impl IntoIterator<Item = &'a mut ClassifiedDependency>,
+    roots: &[(PathBuf, ContainerRef)],
+) -> usize {
+    let mut tagged = 0;
+    for dep in dependencies {
+        let path = dep
+            .installed_path
+            .as_ref()
+            .or_else(|| dep.source_files.values().next());
+        // Container directories don't nest, but the deepest root wins if
+        // they do
+        dep.container = path
+            .and_then(|path| {
+                roots
+                    .iter()
+                    .filter(|(root, _)| path.starts_with(root))
+                    .max_by_key(|(root, _)| root.components().count())
+            })
+            .map(|(_, container)| container.clone());
+        tagged += usize::from(dep.container.is_some());
+    }
+    tagged
+}
+
+/// Set the pod and its labels on the containers of each pod, dropping the
+/// sandboxes
+fn with_pods(listed: Vec<RunningContainer>) -> Vec<RunningContainer> {
+    let pod_labels: HashMap<String, BTreeMap<String, String>> = listed
+        .iter()
+        .filter(|c| is_sandbox(&c.container.labels))
+        .filter_map(|c| {
+            let labels = &c.container.labels;
+            let user_labels = labels
+                .iter()
+                .filter(|(key, _)| !RUNTIME_LABEL_PREFIXES.iter().any(|p| key.starts_with(p)))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            Some((labels.get(POD_UID_LABEL)?.clone(), user_labels))
+        })
+        .collect();
+
+    listed
+        .into_iter()
+        .filter(|c| !is_sandbox(&c.container.labels))
+        .map(|mut c| {
+            let labels = &c.container.labels;
+            if let (Some(namespace), Some(name)) =
+                (labels.get(POD_NAMESPACE_LABEL), labels.get(POD_NAME_LABEL))
+            {
+                c.container.pod = Some(format!("{}/{}", namespace, name));
+            }
+            if let Some(name) = labels.get(CONTAINER_NAME_LABEL) {
+                c.container.name = name.clone();
+            }
+            if let Some(uid) = labels.get(POD_UID_LABEL) {
+                c.container.labels = pod_labels.get(uid).cloned().unwrap_or_default();
+            }
+            c
+        })
+        .collect()
+}
+
+/// Whether labels are those of a pod sandbox (cri-dockerd, containerd)
+fn is_sandbox(labels: &BTreeMap<String, String>) -> bool {
+    labels.get("io.kubernetes.docker.type").map(String::as_str) == Some("podsandbox")
+        || labels.get("io.cri-containerd.kind").map(String::as_str) == Some("sandbox")
+}
+
+/// List the running Docker containers, with the sandboxes; containers that
+/// exit before they are inspected are skipped
+fn list_docker_containers(socket: &Path) -> Result<Vec<RunningContainer>, ScanError> {
+    let listed = docker_get(socket, "/containers/json")?;
+    let mut containers = Vec::new();
+    for id in listed
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|c| c.get("Id")?.as_str())
+    {
+        match docker_get(socket, &format!("/containers/{}/json", id)) {
+            Ok(inspected) => containers.extend(docker_container(&inspected)),
+            Err(e) => warn!(container = %id, error = %e, "Cannot inspect container, skipping it"),
+        }
+    }
+    Ok(containers)
+}
+
+/// A running container from `GET /containers/<id>/json`
+fn docker_container(inspected: &Value) -> Option<RunningContainer> {
+    let state = inspected.get("State")?;
+    if state.get("Running").and_then(Value::as_bool) != Some(true) {
+        return None;
+    }
+    let config = inspected.get("Config");
+    let overlay = inspected.get("GraphDriver").and_then(|g| g.get("Data"));
+    let overlay_dir = |key: &str| {
+        overlay
+            .and_then(|data| data.get(key)?.as_str())
+            .map(PathBuf::from)
+    };
+    Some(RunningContainer {
+        container: ContainerRef {
+            id: inspected.get("Id")?.as_str()?.to_string(),
+            name: inspected
+                .get("Name")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .trim_start_matches('/')
+                .to_string(),
+            image: config
+                .and_then(|c| c.get("Image")?.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            runtime: "docker".to_string(),
+            pod: None,
+            labels: string_map(config.and_then(|c| c.get("Labels"))),
+        },
+        pid: state
+            .get("Pid")
+            .and_then(Value::as_u64)
+            .and_then(|pid| u32::try_from(pid).ok())
+            .filter(|&pid| pid > 0),
+        merged_dir: overlay_dir("MergedDir"),
+        upper_dir: overlay_dir("UpperDir"),
+    })
+}
+
+/// GET a Docker Engine API path, returning its JSON body
+#[cfg(unix)]
+fn docker_get(socket: &Path, path: &str) -> Result<Value, ScanError> {
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+
+    let error = |e: &dyn fmt::Display| {
+        ScanError::Container(format!(
+            "Docker API {} on {}: {}",
+            path,
+            socket.display(),
+            e
+        ))
+    };
+    let mut stream = UnixStream::connect(socket).map_err(|e| error(&e))?;
+    // HTTP/1.0 responses are not chunked and end when the daemon closes
+    write!(stream, "GET {} HTTP/1.0\r\nHost: docker\r\n\r\n", path).map_err(|e| error(&e))?;
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).map_err(|e| error(&e))?;
+    let body = http_body(&response).map_err(|e| error(&e))?;
+    serde_json::from_slice(body).map_err(|e| error(&e))
+}
+
+#[cfg(not(unix))]
+fn docker_get(socket: &Path, _path: &str) -> Result<Value, ScanError> {
+    Err(ScanError::Container(format!(
+        "cannot reach the Docker socket {}: only supported on Unix",
+        socket.display()
+    )))
+}
+
+/// Body of a successful HTTP response
+#[cfg_attr(not(unix), allow(dead_code))]
+fn http_body(response: &[u8]) -> Result<&[u8], String> {
+    let split = response
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or("malformed HTTP response")?;
+    let head = String::from_utf8_lossy(&response[..split]);
+    let status = head.lines().next().unwrap_or_default();
+    if status.split_whitespace().nth(1) != Some("200") {
+        return Err(format!("unexpected response: {}", status));
+    }
+    Ok(&response[split + 4..])
+}
+
+/// List the running containerd tasks of every namespace, with the sandboxes;
+/// containers that exit before they are inspected are skipped
+fn list_containerd_containers(socket: &Path) -> Result<Vec<RunningContainer>, ScanError> {
+    let state_dir = socket
+        .parent()
+        .unwrap_or(Path::new("/"))
+        .join(CONTAINERD_TASK_STATE);
+    let mut containers = Vec::new();
+    for namespace in ctr(socket, None, &["namespaces", "ls", "-q"])?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+    {
+        for (id, pid) in parse_ctr_tasks(&ctr(socket, Some(namespace), &["tasks", "ls"])?) {
+            let info =
+                ctr(socket, Some(namespace), &["containers", "info", &id]).and_then(|info| {
+                    serde_json::from_str::<Value>(&info).map_err(|e| {
+                        ScanError::Container(format!("ctr containers info {}: {}", id, e))
+                    })
+                });
+            let info = match info {
+                Ok(info) => info,
+                Err(e) => {
+                    warn!(container = %id, error = %e, "Cannot inspect container, skipping it");
+                    continue;
+                }
+            };
+            containers.push(RunningContainer {
+                container: ContainerRef {
+                    name: id.clone(),
+                    image: info
+                        .get("Image")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                    runtime: "containerd".to_string(),
+                    pod: None,
+                    labels: string_map(info.get("Labels")),
+                    id: id.clone(),
+                },
+                pid: Some(pid).filter(|&pid| pid > 0),
+                merged_dir: Some(state_dir.join(namespace).join(&id).join("rootfs")),
+                upper_dir: None,
+            });
+        }
+    }
+    Ok(containers)
+}
+
+/// Running tasks of `ctr tasks ls`, by container ID with their process ID
+fn parse_ctr_tasks(output: &str) -> Vec<(String, u32)> {
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+            let id = columns.next()?;
+            let pid = columns.next()?.parse().ok()?;
+            (columns.next()? == "RUNNING").then(|| (id.to_string(), pid))
+        })
+        .collect()
+}
+
+/// Run `ctr` against a containerd socket, returning its output
+fn ctr(socket: &Path, namespace: Option<&str>, args: &[&str]) -> Result<String, ScanError> {
+    let mut command = Command::new("ctr");
+    command.arg("--address").arg(socket);
+    if let Some(namespace) = namespace {
+        command.args(["--namespace", namespace]);
+    }
+    let output = command
+        .args(args)
+        .output()
+        .map_err(|e| ScanError::Container(format!("cannot run ctr: {}", e)))?;
+    if !output.status.success() {
+        return Err(ScanError::Container(format!(
+            "ctr {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Upper directory of the overlay mounted at a mount point, from a
+/// `mountinfo` table; of stacked mounts, the last one is visible
+fn overlay_upper_dir(mountinfo: &str, mount_point: &Path) -> Option<PathBuf> {
+    mountinfo.lines().rev().find_map(|line| {
+        // Optional fields end at `-`, before the type, source and options
+        let (mount, filesystem) = line.split_once(" - ")?;
+        let mut filesystem = filesystem.split_whitespace();
+        if Path::new(mount.split_whitespace().nth(4)?) != mount_point
+            || filesystem.next()? != "overlay"
+        {
+            return None;
+        }
+        filesystem
+            .nth(1)?
+            .split(',')
+            .find_map(|option| option.strip_prefix("upperdir="))
+            .map(PathBuf::from)
+    })
+}
+
+/// String values of a JSON object
+fn string_map(value: Option<&Value>) -> BTreeMap<String, String> {
+    value
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Classification, Ecosystem};
+    use serde_json::json;
+
+    fn container(id: &str, labels: &[(&str, &str)]) -> RunningContainer {
+        RunningContainer {
+            container: ContainerRef {
+                id: id.to_string(),
+                name: id.to_string(),
+                image: format!("{}:latest", id),
+                runtime: "containerd".to_string(),
+                pod: None,
+                labels: labels
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect(),
+            },
+            pid: None,
+            merged_dir: None,
+            upper_dir: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_runtime() {
+        assert_eq!(
+            "docker".parse(),
+            Ok(ContainerRuntime::Docker {
+                socket: PathBuf::from(DOCKER_SOCKET)
+            })
+        );
+        assert_eq!(
+            "containerd:/run/k3s/containerd/containerd.sock".parse(),
+            Ok(ContainerRuntime::Containerd {
+                socket: PathBuf::from("/run/k3s/containerd/containerd.sock")
+            })
+        );
+        assert!("podman".parse::<ContainerRuntime>().is_err());
+        assert_eq!("upper".parse(), Ok(ContainerLayer::Upper));
+    }
+
+    #[test]
+    fn test_pods() {
+        let pod = [
+            (POD_NAME_LABEL, "web-7d9f"),
+            (POD_NAMESPACE_LABEL, "prod"),
+            (POD_UID_LABEL, "uid-1"),
+        ];
+        let sandbox: Vec<_> = pod
+            .iter()
+            .copied()
+            .chain([("io.cri-containerd.kind", "sandbox"), ("app", "web")])
+            .collect();
+        let app: Vec<_> = pod
+            .iter()
+            .copied()
+            .chain([
+                ("io.cri-containerd.kind", "container"),
+                (CONTAINER_NAME_LABEL, "nginx"),
+            ])
+            .collect();
+        let listed = vec![
+            container("pause", &sandbox),
+            container("a1", &app),
+            container("standalone", &[("maintainer", "ops")]),
+        ];
+
+        let containers = with_pods(listed);
+
+        assert_eq!(containers.len(), 2);
+        let web = &containers[0].container;
+        assert_eq!(web.id, "a1");
+        assert_eq!(web.name, "nginx");
+        assert_eq!(web.pod.as_deref(), Some("prod/web-7d9f"));
+        assert_eq!(web.labels_text(), "app=web");
+        let standalone = &containers[1].container;
+        assert_eq!(standalone.pod, None);
+        assert_eq!(standalone.labels_text(), "maintainer=ops");
+    }
+
+    #[test]
+    fn test_docker_container() {
+        let inspected = json!({
+            "Id": "4f2a9c",
+            "Name": "/api",
+            "State": {"Running": true, "Pid": 4242},
+            "Config": {"Image": "api:1.4", "Labels": {"team": "payments"}},
+            "GraphDriver": {
+                "Name": "overlay2",
+                "Data": {
+                    "MergedDir": "/var/lib/docker/overlay2/abc/merged",
+                    "UpperDir": "/var/lib/docker/overlay2/abc/diff"
+                }
+            }
+        });
+        let running = docker_container(&inspected).unwrap();
+        assert_eq!(running.container.name, "api");
+        assert_eq!(running.container.image, "api:1.4");
+        assert_eq!(running.pid, Some(4242));
+        assert_eq!(
+            running.dir(ContainerLayer::Upper),
+            Some(PathBuf::from("/var/lib/docker/overlay2/abc/diff"))
+        );
+        // Not mounted here, so read through the main process
+        assert_eq!(
+            running.dir(ContainerLayer::Merged),
+            Some(PathBuf::from("/proc/4242/root"))
+        );
+
+        let stopped = json!({"Id": "1", "State": {"Running": false, "Pid": 0}});
+        assert_eq!(docker_container(&stopped), None);
+    }
+
+    #[test]
+    fn test_http_body() {
+        let response = b"HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n[]";
+        assert_eq!(http_body(response), Ok(&b"[]"[..]));
+        let response = b"HTTP/1.0 404 Not Found\r\n\r\n{\"message\":\"page not found\"}";
+        assert!(http_body(response).is_err());
+    }
+
+    #[test]
+    fn test_parse_ctr_tasks() {
+        let output = "TASK      PID     STATUS\n\
+                      a1        1201    RUNNING\n\
+                      pause     1100    RUNNING\n\
+                      job       0       STOPPED\n";
+        assert_eq!(
+            parse_ctr_tasks(output),
+            [("a1".to_string(), 1201), ("pause".to_string(), 1100)]
+        );
+    }
+
+    #[test]
+    fn test_overlay_upper_dir() {
+        let mountinfo = "\
+            1021 980 0:52 / / rw,relatime master:1 - overlay overlay rw,lowerdir=/l1:/l2,upperdir=/var/lib/containerd/snapshots/42/fs,workdir=/var/lib/containerd/snapshots/42/work\n\
+            1022 1021 0:55 / /proc rw,nosuid - proc proc rw\n\
+            1023 1021 0:56 / /tmp rw - overlay overlay rw,lowerdir=/x,upperdir=/tmp-upper,workdir=/w\n";
+        assert_eq!(
+            overlay_upper_dir(mountinfo, Path::new("/")),
+            Some(PathBuf::from("/var/lib/containerd/snapshots/42/fs"))
+        );
+        assert_eq!(overlay_upper_dir(mountinfo, Path::new("/proc")), None);
+    }
+
+    #[test]
+    fn test_tag_containers() {
+        let api = ContainerRef {
+            id: "4f2a9c".to_string(),
+            ..ContainerRef::default()
+        };
+        let roots = vec![(PathBuf::from("/proc/4242/root"), api.clone())];
+        let mut installed = ClassifiedDependency::new("lodash".to_string(), Ecosystem::Node);
+        installed.installed_path = Some(PathBuf::from("/proc/4242/root/app/node_modules/lodash"));
+        let mut declared = ClassifiedDependency::new("flask".to_string(), Ecosystem::Python);
+        declared.add_classification(
+            Classification::Can,
+            ">=3".to_string(),
+            PathBuf::from("/srv/requirements.txt"),
+        );
+        let mut deps = vec![installed, declared];
+
+        assert_eq!(tag_containers(&mut deps, &roots), 1);
+        assert_eq!(deps[0].container, Some(api));
+        assert_eq!(deps[1].container, None);
+    }
+}
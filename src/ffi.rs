@@ -0,0 +1,217 @@
+//! C interface for embedding the scanner
+//!
+//! Built with the `ffi` feature, for programs in other languages that embed
+//! the scanner instead of running the CLI. A scan returns the
+//! [`ReportDocument`](crate::output::ReportDocument) of `--push` as a JSON
+//! string owned by the library, released with [`scanner_string_free`]:
+//!
+//! ```c
+//! char *report = scanner_scan("/srv/app");
+//! if (report == NULL) {
+//!     char *error = scanner_last_error();
+//!     fprintf(stderr, "scan failed: %s\n", error);
+//!     scanner_string_free(error);
+//! } else {
+//!     puts(report);
+//!     scanner_string_free(report);
+//! }
+//! ```
+//!
+//! The declarations are in `include/scanner.h`. Build the library with
+//! `cargo rustc --release --lib --features ffi --crate-type staticlib` (or
+//! `cdylib`). Functions never unwind into the caller: a panic fails the call
+//! like an error. Errors are kept per thread, so concurrent scans from
+//! several threads do not see each other's.
+
+use crate::output::build_report;
+use crate::scan::{ScanOptions, Scanner};
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::ptr;
+
+/// Version of the C interface, bumped on incompatible changes
+pub const FFI_API_VERSION: u32 = 1;
+
+thread_local! {
+    /// Error of the last call on this thread, if it failed
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Version of the C interface, to check against the header in use
+#[no_mangle]
+pub extern "C" fn scanner_ffi_api_version() -> u32 {
+    FFI_API_VERSION
+}
+
+/// Scan a directory with the default options, returning the report as a
+/// JSON string, or NULL on failure (see [`scanner_last_error`])
+///
+/// # Safety
+///
+/// `root` must be NULL or a valid NUL-terminated string. The returned string
+/// must be released with [`scanner_string_free`].
+#[no_mangle]
+pub unsafe extern "C" fn scanner_scan(root: *const c_char) -> *mut c_char {
+    if root.is_null() {
+        set_last_error("root is NULL".to_string());
+        return ptr::null_mut();
+    }
+    // SAFETY: checked non-NULL, NUL-terminated by contract
+    let root = match unsafe { CStr::from_ptr(root) }.to_str() {
+        Ok(root) => root.to_string(),
+        Err(_) => {
+            set_last_error("root is not valid UTF-8".to_string());
+            return ptr::null_mut();
+        }
+    };
+
+    match panic::catch_unwind(AssertUnwindSafe(|| scan_report(Path::new(&root)))) {
+        Ok(Ok(json)) => into_c_string(json),
+        Ok(Err(message)) => {
+            set_last_error(message);
+            ptr::null_mut()
+        }
+        Err(_) => {
+            set_last_error(format!("scan of {} panicked", root));
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Error message of the last scan on this thread, or NULL if it succeeded
+/// (or none ran)
+///
+/// The returned string must be released with [`scanner_string_free`].
+#[no_mangle]
+pub extern "C" fn scanner_last_error() -> *mut c_char {
+    LAST_ERROR.with(|error| match error.borrow().as_ref() {
+        Some(message) => message.clone().into_raw(),
+        None => ptr::null_mut(),
+    })
+}
+
+/// Release a string returned by the library
+///
+/// # Safety
+///
+/// `s` must be NULL or a string returned by this library that was not
+/// released yet.
+#[no_mangle]
+pub unsafe extern "C" fn scanner_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY: allocated by CString::into_raw in this library
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// Scan a directory and serialize its report
+fn scan_report(root: &Path) -> Result<String, String> {
+    let output = Scanner::new(ScanOptions::new())
+        .scan(root)
+        .map_err(|e| e.to_string())?;
+    let report = build_report(
+        output.applications,
+        None,
+        output.summary,
+        output.inconsistencies,
+        output.overrides,
+        output.errors,
+        Some(output.metadata),
+    );
+    serde_json::to_string(&report).map_err(|e| e.to_string())
+}
+
+fn set_last_error(message: String) {
+    // Interior NULs would truncate the message on the C side
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|error| *error.borrow_mut() = Some(message));
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|error| *error.borrow_mut() = None);
+}
+
+/// Hand a report to the caller, clearing the last error; fails on an interior
+/// NUL, which JSON escapes and so should never hold
+fn into_c_string(json: String) -> *mut c_char {
+    match CString::new(json) {
+        Ok(json) => {
+            clear_last_error();
+            json.into_raw()
+        }
+        Err(e) => {
+            set_last_error(format!(
+                "report holds a NUL byte at offset {}",
+                e.nul_position()
+            ));
+            ptr::null_mut()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::ReportDocument;
+    use std::fs;
+
+    fn take(s: *mut c_char) -> Option<String> {
+        if s.is_null() {
+            return None;
+        }
+        let text = unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string();
+        unsafe { scanner_string_free(s) };
+        Some(text)
+    }
+
+    #[test]
+    fn test_scanner_scan() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let lodash = temp_dir.path().join("node_modules/lodash");
+        fs::create_dir_all(&lodash).unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{"name": "web", "dependencies": {"lodash": "^4.17.21"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            lodash.join("package.json"),
+            r#"{"name": "lodash", "version": "4.17.21"}"#,
+        )
+        .unwrap();
+        let root = CString::new(temp_dir.path().to_str().unwrap()).unwrap();
+
+        let json = take(unsafe { scanner_scan(root.as_ptr()) }).unwrap();
+        let report: ReportDocument = serde_json::from_str(&json).unwrap();
+        assert_eq!(report.applications.len(), 1);
+        assert_eq!(report.applications[0].dependencies[0].name, "lodash");
+        assert_eq!(scanner_ffi_api_version(), FFI_API_VERSION);
+    }
+
+    #[test]
+    fn test_scanner_scan_errors() {
+        assert!(take(scanner_last_error()).is_none());
+
+        assert!(unsafe { scanner_scan(ptr::null()) }.is_null());
+        assert_eq!(take(scanner_last_error()).unwrap(), "root is NULL");
+
+        let missing = CString::new("/nonexistent/scanner-ffi").unwrap();
+        assert!(unsafe { scanner_scan(missing.as_ptr()) }.is_null());
+        assert!(take(scanner_last_error()).unwrap().contains("scanner-ffi"));
+
+        assert!(into_c_string("{\"a\": \"\0\"}".to_string()).is_null());
+        assert_eq!(
+            take(scanner_last_error()).unwrap(),
+            "report holds a NUL byte at offset 7"
+        );
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = CString::new(temp_dir.path().to_str().unwrap()).unwrap();
+        assert!(take(unsafe { scanner_scan(root.as_ptr()) }).is_some());
+        assert!(take(scanner_last_error()).is_none());
+
+        unsafe { scanner_string_free(ptr::null_mut()) };
+    }
+}
@@ -0,0 +1,355 @@
+//! Package files of a filesystem snapshot archive
+//!
+//! Forensic images and backups arrive as tarballs or zip files, and
+//! extracting one to audit it takes the disk space of the whole snapshot.
+//! [`index_archive`] reads a tar archive, optionally gzip-compressed, or a
+//! zip archive in one streaming pass, keeping the contents of the manifests
+//! and lockfiles it finds and nothing else; [`ArchiveFiles::take`] hands them
+//! to the parsers. The same exclusions and path filters as the directory walk
+//! apply, and so do its deadline and cancellation token, checked between
+//! members. With a size limit, at most one byte more than the limit is kept of
+//! each member, enough for the reader to cut it and warn. The format is
+//! recognized by the leading bytes of the archive, not by its name.
+//!
+//! Members are reported under the archive's path, as if it were a directory
+//! holding the snapshot: `backup.tar.gz/srv/app/package.json`. Only regular
+//! files are read; links and members outside the archive root (`..`) are
+//! skipped, and of a path archived twice the last copy is kept, as `tar`
+//! extracts it. The installed-package parsers read directories on disk, so
+//! installation directories in an archive are not parsed.
+
+use crate::cancel::CancellationToken;
+use crate::indexer::{discover_listed_file, DiscoveredFile, FileClassifier, ScanMode, WalkOptions};
+use crate::models::ScanError;
+use flate2::read::GzDecoder;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek};
+use std::ops::ControlFlow;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::warn;
+
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// Visitor of the regular files of an archive, by relative path; breaks to
+/// stop reading the archive
+type MemberVisitor<'a> = dyn FnMut(&Path, &mut dyn Read) -> io::Result<ControlFlow<()>> + 'a;
+
+/// Manifests and lockfiles of a snapshot archive, with their contents
+#[derive(Debug, Default)]
+pub struct ArchiveFiles {
+    /// Package files, sorted by path
+    pub files: Vec<DiscoveredFile>,
+    contents: Mutex<HashMap<PathBuf, Vec<u8>>>,
+}
+
+impl ArchiveFiles {
+    /// Take the content of a package file of the archive, given its path
+    /// under the archive; each file can be taken once
+    pub fn take(&self, path: &Path) -> Result<Vec<u8>, ScanError> {
+        self.contents
+            .lock()
+            .unwrap()
+            .remove(path)
+            .ok_or_else(|| ScanError::Archive(format!("{} is not in the archive", path.display())))
+    }
+}
+
+/// Find the manifests and lockfiles of a tar or zip archive and read them,
+/// keeping at most `max_file_size + 1` bytes of each, enough for the reader
+/// to tell the ones over the limit
+pub fn index_archive(
+    archive: &Path,
+    exclude_dirs: &[&str],
+    scan_mode: ScanMode,
+    include_install_dirs: bool,
+    walk_options: &WalkOptions,
+    max_file_size: Option<u64>,
+    classify: &FileClassifier<'_>,
+) -> Result<ArchiveFiles, ScanError> {
+    let mut files = Vec::new();
+    let mut contents = HashMap::new();
+    if !scan_mode.collects_files() {
+        return Ok(ArchiveFiles::default());
+    }
+
+    let mut visit = |relative: &Path, member: &mut dyn Read| {
+        if let Some(reason) = stop_reason(walk_options) {
+            warn!(
+                archive = %archive.display(),
+                "{}, skipping remaining archive members",
+                reason
+            );
+            return Ok(ControlFlow::Break(()));
+        }
+        let Some(file) = discover_listed_file(
+            archive,
+            relative,
+            exclude_dirs,
+            include_install_dirs,
+            walk_options,
+            classify,
+        )
+        .filter(|f| scan_mode.collects_file_type(f.file_type)) else {
+            return Ok(ControlFlow::Continue(()));
+        };
+        let mut content = Vec::new();
+        match max_file_size {
+            Some(max) => member
+                .take(max.saturating_add(1))
+                .read_to_end(&mut content)?,
+            None => member.read_to_end(&mut content)?,
+        };
+        if contents.insert(file.path.clone(), content).is_none() {
+            files.push(file);
+        }
+        Ok(ControlFlow::Continue(()))
+    };
+    read_archive(archive, &mut visit)
+        .map_err(|e| ScanError::Archive(format!("cannot read {}: {}", archive.display(), e)))?;
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(ArchiveFiles {
+        files,
+        contents: Mutex::new(contents),
+    })
+}
+
+/// Why reading must stop: the deadline passed or the scan was cancelled
+fn stop_reason(walk_options: &WalkOptions) -> Option<&'static str> {
+    if walk_options
+        .deadline
+        .is_some_and(|deadline| Instant::now() >= deadline)
+    {
+        Some("Scan time budget exhausted")
+    } else if walk_options
+        .cancellation
+        .as_ref()
+        .is_some_and(CancellationToken::is_cancelled)
+    {
+        Some("Scan cancelled")
+    } else {
+        None
+    }
+}
+
+/// Visit the regular files of an archive, in archive order
+fn read_archive(archive: &Path, visit: &mut MemberVisitor<'_>) -> io::Result<()> {
+    let mut file = File::open(archive)?;
+    let mut magic = Vec::new();
+    file.by_ref().take(4).read_to_end(&mut magic)?;
+    file.rewind()?;
+
+    let reader = BufReader::new(file);
+    if magic.starts_with(ZIP_MAGIC) {
+        read_zip(reader, visit)
+    } else if magic.starts_with(GZIP_MAGIC) {
+        read_tar(GzDecoder::new(reader), visit)
+    } else {
+        read_tar(reader, visit)
+    }
+}
+
+fn read_tar(reader: impl Read, visit: &mut MemberVisitor<'_>) -> io::Result<()> {
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        if let Some(relative) = member_path(&entry.path()?) {
+            if visit(&relative, &mut entry)?.is_break() {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_zip(reader: BufReader<File>, visit: &mut MemberVisitor<'_>) -> io::Result<()> {
+    let mut archive = zip::ZipArchive::new(reader).map_err(io::Error::other)?;
+    for index in 0..archive.len() {
+        let mut member = archive.by_index(index).map_err(io::Error::other)?;
+        if !member.is_file() || member.is_symlink() {
+            continue;
+        }
+        if let Some(relative) = member.enclosed_name() {
+            if visit(&relative, &mut member)?.is_break() {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Path of a tar member relative to the archive root, without a leading `/`
+/// or `./`; `None` for members outside the root
+fn member_path(path: &Path) -> Option<PathBuf> {
+    let mut relative = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => relative.push(name),
+            Component::ParentDir => return None,
+            _ => {}
+        }
+    }
+    (!relative.as_os_str().is_empty()).then_some(relative)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::classify_file;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    const MEMBERS: &[(&str, &str)] = &[
+        ("./srv/app/package.json", r#"{"name":"app"}"#),
+        ("./srv/app/package-lock.json", "{}"),
+        ("./srv/app/node_modules/left-pad/package.json", "{}"),
+        ("./srv/app/target/Cargo.lock", ""),
+        ("./srv/app/README.md", "# app"),
+        ("./opt/api/requirements.txt", "flask==3.0.0\n"),
+    ];
+
+    fn index(archive: &Path, scan_mode: ScanMode) -> ArchiveFiles {
+        index_archive(
+            archive,
+            &["target"],
+            scan_mode,
+            false,
+            &WalkOptions::default(),
+            None,
+            &classify_file,
+        )
+        .unwrap()
+    }
+
+    fn assert_indexed(archive: &Path) {
+        let found = index(archive, ScanMode::DeclaredOnly);
+        let paths: Vec<_> = found.files.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(
+            paths,
+            [
+                archive.join("opt/api/requirements.txt"),
+                archive.join("srv/app/package-lock.json"),
+                archive.join("srv/app/package.json"),
+            ]
+        );
+        assert_eq!(found.files[2].directory, archive.join("srv/app"));
+        let package_json = archive.join("srv/app/package.json");
+        assert_eq!(found.take(&package_json).unwrap(), br#"{"name":"app"}"#);
+        // Taken contents are moved out
+        assert!(found.take(&package_json).is_err());
+        assert!(found.take(&archive.join("srv/app/README.md")).is_err());
+        assert_eq!(index(archive, ScanMode::LockfilesOnly).files.len(), 1);
+    }
+
+    #[test]
+    fn test_index_tar_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = temp_dir.path().join("snapshot.tar.gz");
+        let mut builder = tar::Builder::new(GzEncoder::new(
+            File::create(&archive).unwrap(),
+            Compression::default(),
+        ));
+        for (path, content) in MEMBERS {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            builder
+                .append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        // A later copy replaces an earlier one
+        let mut header = tar::Header::new_gnu();
+        header.set_size(2);
+        builder
+            .append_data(&mut header, "srv/app/package-lock.json", &b"[]"[..])
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        assert_indexed(&archive);
+        let found = index(&archive, ScanMode::Full);
+        assert_eq!(
+            found
+                .take(&archive.join("srv/app/package-lock.json"))
+                .unwrap(),
+            b"[]"
+        );
+    }
+
+    #[test]
+    fn test_index_zip_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        // Recognized by content, whatever the name
+        let archive = temp_dir.path().join("snapshot.img");
+        let mut writer = zip::ZipWriter::new(File::create(&archive).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        for (path, content) in MEMBERS {
+            writer
+                .start_file(path.trim_start_matches("./"), options)
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer
+            .add_symlink("srv/web/package.json", "../app/package.json", options)
+            .unwrap();
+        writer.finish().unwrap();
+
+        assert_indexed(&archive);
+
+        // Members are cut one byte past the size limit
+        let found = index_archive(
+            &archive,
+            &[],
+            ScanMode::Full,
+            false,
+            &WalkOptions::default(),
+            Some(5),
+            &classify_file,
+        )
+        .unwrap();
+        assert_eq!(
+            found.take(&archive.join("srv/app/package.json")).unwrap(),
+            br#"{"name"#
+        );
+
+        // Nothing is read once the scan is cancelled
+        let token = CancellationToken::new();
+        token.cancel();
+        let found = index_archive(
+            &archive,
+            &[],
+            ScanMode::Full,
+            false,
+            &WalkOptions::new().with_cancellation(token),
+            None,
+            &classify_file,
+        )
+        .unwrap();
+        assert!(found.files.is_empty());
+    }
+
+    #[test]
+    fn test_index_unreadable_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = temp_dir.path().join("truncated.tar.gz");
+        std::fs::write(&archive, b"\x1f\x8b\x08\x00garbage").unwrap();
+        let result = index_archive(
+            &archive,
+            &[],
+            ScanMode::Full,
+            false,
+            &WalkOptions::default(),
+            None,
+            &classify_file,
+        );
+        assert!(matches!(result, Err(ScanError::Archive(_))));
+    }
+}
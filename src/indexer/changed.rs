@@ -0,0 +1,128 @@
+//! Applications changed since a git revision
+//!
+//! A pull request usually touches one or two applications of a monorepo.
+//! [`changed_files`] asks git which files differ between a revision and the
+//! working tree (`git diff --name-only <rev>`), and [`affected_roots`] turns
+//! the manifests and lockfiles among them into the directories to scan: the
+//! directory of each changed dependency file, dropping those inside another
+//! one. A scan of these directories covers every affected application and
+//! nothing else.
+
+use crate::indexer::classify_file;
+use crate::models::ScanError;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Files of a directory that differ from a revision, relative to the
+/// directory (including deleted and staged ones)
+pub fn changed_files(dir: &Path, since: &str) -> Result<Vec<PathBuf>, ScanError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["diff", "--name-only", "--relative", since, "--"])
+        .output()
+        .map_err(|e| ScanError::Git(format!("cannot run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(ScanError::Git(format!(
+            "git diff {} in {} failed: {}",
+            since,
+            dir.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Directories to scan for the changed files of a root: those holding a
+/// changed manifest or lockfile, outermost first
+pub fn affected_roots(root: &Path, changed: &[PathBuf]) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = changed
+        .iter()
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|name| classify_file(&name.to_string_lossy()).is_some())
+        })
+        .filter_map(|path| path.parent())
+        .map(|dir| root.join(dir))
+        .filter(|dir| dir.is_dir())
+        .collect();
+    dirs.sort_by_key(|dir| dir.components().count());
+
+    let mut roots: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        if !roots.iter().any(|root| dir.starts_with(root)) {
+            roots.push(dir);
+        }
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_affected_roots() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for dir in ["services/api", "services/web/client", "docs"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        let changed: Vec<PathBuf> = [
+            "services/api/package-lock.json",
+            "services/api/src/index.js",
+            "services/web/client/package.json",
+            "services/web/Cargo.toml",
+            "docs/README.md",
+            "removed/package.json",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+
+        assert_eq!(
+            affected_roots(root, &changed),
+            vec![root.join("services/api"), root.join("services/web")]
+        );
+    }
+
+    #[test]
+    fn test_changed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(repo)
+                .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+                .args(args)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        fs::create_dir_all(repo.join("app")).unwrap();
+        fs::write(repo.join("app/package.json"), "{}").unwrap();
+        fs::write(repo.join("README.md"), "").unwrap();
+        git(&["init", "-q"]);
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "initial"]);
+        fs::write(repo.join("app/package.json"), r#"{"name":"app"}"#).unwrap();
+
+        assert_eq!(
+            changed_files(repo, "HEAD").unwrap(),
+            vec![PathBuf::from("app/package.json")]
+        );
+        assert_eq!(
+            changed_files(&repo.join("app"), "HEAD").unwrap(),
+            vec![PathBuf::from("package.json")]
+        );
+        assert!(changed_files(repo, "no-such-rev").is_err());
+    }
+}
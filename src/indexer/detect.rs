@@ -0,0 +1,149 @@
+//! Ecosystem and package manager detection
+//!
+//! [`detect_ecosystems`] summarizes a [`FileIndex`] per ecosystem without
+//! parsing any file, so users can see what a tree contains before deciding
+//! what to scan. Package managers are told apart by their lockfiles and
+//! manager-specific manifests; `package.json` and `pyproject.toml` are shared
+//! by several managers and only count as manifests.
+
+use super::{FileIndex, InstallDirType};
+use crate::models::{Ecosystem, FileType};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Package files and installation directories of one ecosystem
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct EcosystemDetection {
+    /// Ecosystem
+    pub ecosystem: Ecosystem,
+
+    /// Manifests found
+    pub manifests: usize,
+
+    /// Lockfiles found
+    pub lockfiles: usize,
+
+    /// node_modules and site-packages/dist-packages directories found
+    pub install_dirs: usize,
+
+    /// Files per package manager (`npm`, `yarn`, `poetry`, ...)
+    pub package_managers: BTreeMap<String, usize>,
+}
+
+/// Package manager a file belongs to, if only one manager writes it
+pub fn package_manager(filename: &str) -> Option<&'static str> {
+    match filename {
+        "package-lock.json" | "npm-shrinkwrap.json" => Some("npm"),
+        "yarn.lock" => Some("yarn"),
+        "pnpm-lock.yaml" => Some("pnpm"),
+        "bun.lock" => Some("bun"),
+        "deno.json" | "deno.jsonc" | "deno.lock" => Some("deno"),
+        "requirements.txt" | "constraints.txt" => Some("pip"),
+        "requirements.in" => Some("pip-tools"),
+        "setup.py" | "setup.cfg" => Some("setuptools"),
+        "Pipfile" | "Pipfile.lock" => Some("pipenv"),
+        "poetry.lock" => Some("poetry"),
+        "uv.lock" => Some("uv"),
+        "environment.yml" => Some("conda"),
+        "Cargo.toml" | "Cargo.lock" => Some("cargo"),
+        _ => None,
+    }
+}
+
+/// Summarize discovered files and installation directories per ecosystem,
+/// in ecosystem order; ecosystems with nothing found are left out
+pub fn detect_ecosystems(index: &FileIndex) -> Vec<EcosystemDetection> {
+    let mut detections: BTreeMap<String, EcosystemDetection> = BTreeMap::new();
+    for file in &index.files {
+        let detection = entry(&mut detections, file.ecosystem);
+        match file.file_type {
+            FileType::Manifest => detection.manifests += 1,
+            FileType::Lockfile => detection.lockfiles += 1,
+        }
+        if let Some(manager) = package_manager(&file.filename) {
+            *detection
+                .package_managers
+                .entry(manager.to_string())
+                .or_default() += 1;
+        }
+    }
+    // A virtual environment is counted through its site-packages
+    for dir in &index.install_dirs {
+        if dir.dir_type != InstallDirType::VirtualEnv {
+            entry(&mut detections, dir.ecosystem).install_dirs += 1;
+        }
+    }
+
+    detections.into_values().collect()
+}
+
+/// Detection of an ecosystem, keyed by its name so they sort by name
+fn entry(
+    detections: &mut BTreeMap<String, EcosystemDetection>,
+    ecosystem: Ecosystem,
+) -> &mut EcosystemDetection {
+    detections
+        .entry(ecosystem.to_string())
+        .or_insert_with(|| EcosystemDetection {
+            ecosystem,
+            manifests: 0,
+            lockfiles: 0,
+            install_dirs: 0,
+            package_managers: BTreeMap::new(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::{index_tree, ScanMode, WalkOptions};
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_ecosystems() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for file in [
+            "web/package.json",
+            "web/yarn.lock",
+            "web/node_modules/react/package.json",
+            "admin/package.json",
+            "admin/package-lock.json",
+            "api/pyproject.toml",
+            "api/poetry.lock",
+            "api/.venv/pyvenv.cfg",
+        ] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        fs::create_dir_all(root.join("api/.venv/lib/python3.12/site-packages")).unwrap();
+
+        let index = index_tree(root, &[], ScanMode::Full, false, &WalkOptions::default());
+        let detections = detect_ecosystems(&index);
+
+        assert_eq!(detections.len(), 2);
+        let node = &detections[0];
+        assert_eq!(node.ecosystem, Ecosystem::Node);
+        assert_eq!(
+            (node.manifests, node.lockfiles, node.install_dirs),
+            (2, 2, 1)
+        );
+        assert_eq!(
+            node.package_managers,
+            BTreeMap::from([("npm".to_string(), 1), ("yarn".to_string(), 1)])
+        );
+        let python = &detections[1];
+        assert_eq!(python.ecosystem, Ecosystem::Python);
+        assert_eq!(
+            (python.manifests, python.lockfiles, python.install_dirs),
+            (1, 1, 1)
+        );
+        assert_eq!(
+            python.package_managers,
+            BTreeMap::from([("poetry".to_string(), 1)])
+        );
+    }
+}
@@ -1,11 +1,13 @@
 //! File type identification and classification
 
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 
+use super::{FileClassifier, WalkOptions, INSTALL_DIR_NAMES};
 use crate::models::{Ecosystem, FileType};
+use serde::{Deserialize, Serialize};
 
 /// A discovered package management file
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiscoveredFile {
     /// Absolute path to the file
     pub path: PathBuf,
@@ -19,12 +21,73 @@ pub struct DiscoveredFile {
     pub file_type: FileType,
 }
 
+/// Classify a file path as a manifest or lockfile
+pub(crate) fn discover_file(path: &Path, classify: &FileClassifier<'_>) -> Option<DiscoveredFile> {
+    let file_name = path.file_name()?.to_string_lossy();
+    let (ecosystem, file_type) = classify(&file_name)?;
+    let parent = path.parent()?;
+
+    Some(DiscoveredFile {
+        path: path.to_path_buf(),
+        filename: file_name.to_string(),
+        directory: parent.to_path_buf(),
+        ecosystem,
+        file_type,
+    })
+}
+
+/// Classify a file of a listing (a git tree, an archive), given its path
+/// relative to `root`, with the exclusions and path filters of the walk
+pub(crate) fn discover_listed_file(
+    root: &Path,
+    relative: &Path,
+    exclude_dirs: &[&str],
+    include_install_dirs: bool,
+    walk_options: &WalkOptions,
+    classify: &FileClassifier<'_>,
+) -> Option<DiscoveredFile> {
+    let dirs: Vec<&str> = relative
+        .parent()?
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .collect();
+    if dirs.iter().any(|dir| exclude_dirs.contains(dir))
+        || (!include_install_dirs && dirs.iter().any(|d| INSTALL_DIR_NAMES.contains(d)))
+        || walk_options
+            .filter
+            .max_depth
+            .is_some_and(|max| dirs.len() + 1 > max)
+    {
+        return None;
+    }
+
+    let path = root.join(relative);
+    if path
+        .ancestors()
+        .take(dirs.len() + 1)
+        .any(|p| walk_options.filter.is_excluded(root, p))
+        || !walk_options.is_included(root, &path)
+    {
+        return None;
+    }
+    discover_file(&path, classify)
+}
+
 /// Classify a file by its name and return its ecosystem and type
 pub fn classify_file(filename: &str) -> Option<(Ecosystem, FileType)> {
     match filename {
         // Node.js manifest files
         "package.json" => Some((Ecosystem::Node, FileType::Manifest)),
 
+        // Deno manifests and lockfile (reported as Node.js)
+        "deno.json" => Some((Ecosystem::Node, FileType::Manifest)),
+        "deno.jsonc" => Some((Ecosystem::Node, FileType::Manifest)),
+        "import_map.json" => Some((Ecosystem::Node, FileType::Manifest)),
+        "deno.lock" => Some((Ecosystem::Node, FileType::Lockfile)),
+
         // Node.js lockfiles
         "yarn.lock" => Some((Ecosystem::Node, FileType::Lockfile)),
         "package-lock.json" => Some((Ecosystem::Node, FileType::Lockfile)),
@@ -35,6 +98,10 @@ pub fn classify_file(filename: &str) -> Option<(Ecosystem, FileType)> {
         // Python manifest files
         "pyproject.toml" => Some((Ecosystem::Python, FileType::Manifest)),
         "requirements.txt" => Some((Ecosystem::Python, FileType::Manifest)),
+        "requirements.in" => Some((Ecosystem::Python, FileType::Manifest)),
+        "constraints.txt" => Some((Ecosystem::Python, FileType::Manifest)),
+        "setup.py" => Some((Ecosystem::Python, FileType::Manifest)),
+        "setup.cfg" => Some((Ecosystem::Python, FileType::Manifest)),
         "Pipfile" => Some((Ecosystem::Python, FileType::Manifest)),
         "environment.yml" => Some((Ecosystem::Python, FileType::Manifest)),
 
@@ -0,0 +1,277 @@
+//! Path filtering for scoping scans to parts of a directory tree
+//!
+//! A [`PathFilter`] limits traversal depth and applies glob patterns to paths
+//! relative to the scan root:
+//!
+//! - **Exclude globs** prune matching files and directories (and everything below them)
+//! - **Include globs** restrict results to matching paths; directories are still
+//!   traversed so that nested matches are found
+//!
+//! # Glob Syntax
+//!
+//! - `*` matches any characters except `/`
+//! - `?` matches a single character except `/`
+//! - `**` matches any number of path segments (`services/**` also matches `services`)
+//! - `{a,b}` matches either alternative
+//! - Patterns without a `/` match against any path segment (`fixtures` excludes every
+//!   directory named `fixtures`)
+//!
+//! # Example
+//!
+//! ```rust
+//! use scanner::indexer::PathFilter;
+//! use std::path::Path;
+//!
+//! let filter = PathFilter::new()
+//!     .with_include_globs(&["services/**"])
+//!     .unwrap()
+//!     .with_exclude_globs(&["**/legacy/**"])
+//!     .unwrap();
+//!
+//! let root = Path::new("/repo");
+//! assert!(filter.is_included(root, Path::new("/repo/services/api/package.json")));
+//! assert!(!filter.is_included(root, Path::new("/repo/tools/package.json")));
+//! assert!(filter.is_excluded(root, Path::new("/repo/services/legacy")));
+//! ```
+
+use crate::models::ScanError;
+use regex::Regex;
+use std::path::Path;
+
+/// A compiled glob pattern
+#[derive(Debug, Clone)]
+pub struct Glob {
+    pattern: String,
+    regex: Regex,
+}
+
+impl Glob {
+    /// Compile a glob pattern
+    pub fn new(pattern: &str) -> Result<Self, ScanError> {
+        let regex = Regex::new(&glob_to_regex(pattern)).map_err(|e| ScanError::InvalidGlob {
+            pattern: pattern.to_string(),
+            message: e.to_string(),
+        })?;
+
+        Ok(Self {
+            pattern: pattern.to_string(),
+            regex,
+        })
+    }
+
+    /// Get the original pattern
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Check if a relative path (using `/` separators) matches this glob
+    pub fn is_match(&self, relative_path: &str) -> bool {
+        self.regex.is_match(relative_path)
+    }
+}
+
+impl PartialEq for Glob {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+    }
+}
+
+impl Eq for Glob {}
+
+/// Translate a glob pattern into an anchored regular expression
+fn glob_to_regex(pattern: &str) -> String {
+    let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+    let mut regex = String::from("^");
+
+    // Patterns without a separator match any path segment
+    if !pattern.contains('/') {
+        regex.push_str("(?:.*/)?");
+    }
+
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut brace_depth = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                let at_start = i == 0 || chars[i - 1] == '/';
+                let followed_by_slash = chars.get(i + 2) == Some(&'/');
+                let at_end = i + 2 == chars.len();
+
+                if at_start && followed_by_slash {
+                    // "**/" matches zero or more leading segments
+                    regex.push_str("(?:.*/)?");
+                    i += 3;
+                    continue;
+                }
+                if at_end && i > 0 && chars[i - 1] == '/' {
+                    // "/**" matches the directory itself and everything below it
+                    regex.pop();
+                    regex.push_str("(?:/.*)?");
+                    i += 2;
+                    continue;
+                }
+                regex.push_str(".*");
+                i += 2;
+                continue;
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '{' => {
+                brace_depth += 1;
+                regex.push_str("(?:");
+            }
+            '}' if brace_depth > 0 => {
+                brace_depth -= 1;
+                regex.push(')');
+            }
+            ',' if brace_depth > 0 => regex.push('|'),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+
+    regex.push('$');
+    regex
+}
+
+/// Filter controlling which paths the indexer visits and reports
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathFilter {
+    /// Maximum traversal depth below the scan root (unlimited when None)
+    pub max_depth: Option<usize>,
+
+    /// Only report paths matching one of these globs (all paths when empty)
+    pub include: Vec<Glob>,
+
+    /// Skip paths matching any of these globs
+    pub exclude: Vec<Glob>,
+}
+
+impl PathFilter {
+    /// Create an empty filter that accepts every path
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum traversal depth
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Add include glob patterns
+    pub fn with_include_globs<S: AsRef<str>>(mut self, patterns: &[S]) -> Result<Self, ScanError> {
+        for pattern in patterns {
+            self.include.push(Glob::new(pattern.as_ref())?);
+        }
+        Ok(self)
+    }
+
+    /// Add exclude glob patterns
+    pub fn with_exclude_globs<S: AsRef<str>>(mut self, patterns: &[S]) -> Result<Self, ScanError> {
+        for pattern in patterns {
+            self.exclude.push(Glob::new(pattern.as_ref())?);
+        }
+        Ok(self)
+    }
+
+    /// Check if a path should be pruned from traversal
+    pub fn is_excluded(&self, root: &Path, path: &Path) -> bool {
+        if self.exclude.is_empty() {
+            return false;
+        }
+
+        match relative_path(root, path) {
+            Some(relative) => self.exclude.iter().any(|g| g.is_match(&relative)),
+            None => false,
+        }
+    }
+
+    /// Check if a discovered path should be reported
+    pub fn is_included(&self, root: &Path, path: &Path) -> bool {
+        if self.include.is_empty() {
+            return true;
+        }
+
+        match relative_path(root, path) {
+            Some(relative) => self.include.iter().any(|g| g.is_match(&relative)),
+            None => false,
+        }
+    }
+}
+
+/// Get a path relative to the scan root using `/` separators
+fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let parts: Vec<_> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    Some(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_single_star() {
+        let glob = Glob::new("services/*/package.json").unwrap();
+        assert!(glob.is_match("services/api/package.json"));
+        assert!(!glob.is_match("services/api/nested/package.json"));
+    }
+
+    #[test]
+    fn test_glob_double_star() {
+        let glob = Glob::new("services/**").unwrap();
+        assert!(glob.is_match("services"));
+        assert!(glob.is_match("services/api/package.json"));
+        assert!(!glob.is_match("servicesx/api"));
+
+        let glob = Glob::new("**/legacy/**").unwrap();
+        assert!(glob.is_match("legacy"));
+        assert!(glob.is_match("a/b/legacy/c"));
+        assert!(!glob.is_match("a/legacyx"));
+    }
+
+    #[test]
+    fn test_glob_segment_match() {
+        let glob = Glob::new("fixtures").unwrap();
+        assert!(glob.is_match("fixtures"));
+        assert!(glob.is_match("tests/fixtures"));
+        assert!(!glob.is_match("tests/fixtures2"));
+    }
+
+    #[test]
+    fn test_glob_alternatives() {
+        let glob = Glob::new("{apps,libs}/**").unwrap();
+        assert!(glob.is_match("apps/web"));
+        assert!(glob.is_match("libs/core"));
+        assert!(!glob.is_match("tools/cli"));
+    }
+
+    #[test]
+    fn test_filter_include_and_exclude() {
+        let filter = PathFilter::new()
+            .with_include_globs(&["services/**"])
+            .unwrap()
+            .with_exclude_globs(&["**/legacy/**"])
+            .unwrap();
+        let root = Path::new("/repo");
+
+        assert!(filter.is_included(root, Path::new("/repo/services/api/package.json")));
+        assert!(!filter.is_included(root, Path::new("/repo/tools/package.json")));
+        assert!(filter.is_excluded(root, Path::new("/repo/services/legacy")));
+        assert!(!filter.is_excluded(root, Path::new("/repo/services/api")));
+    }
+
+    #[test]
+    fn test_empty_filter_accepts_everything() {
+        let filter = PathFilter::new();
+        let root = Path::new("/repo");
+        assert!(filter.is_included(root, Path::new("/repo/a/b")));
+        assert!(!filter.is_excluded(root, Path::new("/repo/a/b")));
+    }
+}
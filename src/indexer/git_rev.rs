@@ -0,0 +1,185 @@
+//! Package files of a git revision
+//!
+//! Historical audits ask what an application declared at a past release
+//! without checking it out. [`index_git_rev`] lists the manifests and
+//! lockfiles of a revision from the git object database
+//! (`git ls-tree -r <rev>`), applying the same exclusions and path filters as
+//! the directory walk, and [`read_git_file`] reads one of them
+//! (`git cat-file blob <rev>:<path>`). Nothing in the worktree is read, so
+//! the directory only has to be inside a repository, not to have the
+//! revision checked out. Installation directories are never committed and
+//! not looked for.
+
+use crate::indexer::{discover_listed_file, DiscoveredFile, FileClassifier, ScanMode, WalkOptions};
+use crate::models::ScanError;
+use std::path::Path;
+use std::process::Command;
+
+/// Resolve a revision of the repository holding a directory to its commit id
+pub fn resolve_rev(dir: &Path, rev: &str) -> Result<String, ScanError> {
+    let stdout = git(
+        dir,
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("{}^{{commit}}", rev),
+        ],
+    )
+    .map_err(|_| ScanError::Git(format!("unknown revision {} in {}", rev, dir.display())))?;
+    Ok(String::from_utf8_lossy(&stdout).trim().to_string())
+}
+
+/// Find the manifests and lockfiles below a directory at a revision
+///
+/// Paths are reported under `root` as if the revision were checked out
+/// there, sorted by path.
+pub fn index_git_rev(
+    root: &Path,
+    rev: &str,
+    exclude_dirs: &[&str],
+    scan_mode: ScanMode,
+    include_install_dirs: bool,
+    walk_options: &WalkOptions,
+    classify: &FileClassifier<'_>,
+) -> Result<Vec<DiscoveredFile>, ScanError> {
+    if !scan_mode.collects_files() {
+        return Ok(Vec::new());
+    }
+    let stdout = git(root, &["ls-tree", "-r", "-z", "--name-only", rev, "--"])?;
+
+    let mut files: Vec<DiscoveredFile> = stdout
+        .split(|&b| b == 0)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let relative = Path::new(std::str::from_utf8(entry).ok()?);
+            discover_listed_file(
+                root,
+                relative,
+                exclude_dirs,
+                include_install_dirs,
+                walk_options,
+                classify,
+            )
+            .filter(|f| scan_mode.collects_file_type(f.file_type))
+        })
+        .collect();
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Read a file of a revision, given its path under `root`
+pub fn read_git_file(root: &Path, rev: &str, path: &Path) -> Result<Vec<u8>, ScanError> {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let object = format!("{}:./{}", rev, relative.to_string_lossy());
+    git(root, &["cat-file", "blob", &object])
+}
+
+/// Run git in a directory, returning its output
+fn git(dir: &Path, args: &[&str]) -> Result<Vec<u8>, ScanError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| ScanError::Git(format!("cannot run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(ScanError::Git(format!(
+            "git {} in {} failed: {}",
+            args.join(" "),
+            dir.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::{classify_file, PathFilter};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn commit(repo: &Path) {
+        for args in [
+            &["init", "-q"][..],
+            &["add", "."],
+            &["commit", "-q", "-m", "initial"],
+        ] {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(repo)
+                .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+                .args(args)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_index_and_read_git_rev() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path();
+        for dir in ["app/src", "app/node_modules/left-pad", "app/target", "docs"] {
+            fs::create_dir_all(repo.join(dir)).unwrap();
+        }
+        fs::write(repo.join("app/package.json"), r#"{"name":"app"}"#).unwrap();
+        fs::write(repo.join("app/src/index.js"), "").unwrap();
+        fs::write(repo.join("app/node_modules/left-pad/package.json"), "{}").unwrap();
+        fs::write(repo.join("app/target/Cargo.lock"), "").unwrap();
+        fs::write(repo.join("docs/requirements.txt"), "mkdocs\n").unwrap();
+        commit(repo);
+        fs::write(repo.join("app/package.json"), r#"{"name":"changed"}"#).unwrap();
+        fs::remove_file(repo.join("docs/requirements.txt")).unwrap();
+
+        let rev = resolve_rev(repo, "HEAD").unwrap();
+        assert_eq!(rev.len(), 40);
+        let index = |scan_mode, walk_options: &WalkOptions| {
+            index_git_rev(
+                repo,
+                &rev,
+                &["target"],
+                scan_mode,
+                false,
+                walk_options,
+                &classify_file,
+            )
+            .unwrap()
+        };
+
+        let files = index(ScanMode::DeclaredOnly, &WalkOptions::default());
+        let paths: Vec<_> = files.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                repo.join("app/package.json"),
+                repo.join("docs/requirements.txt")
+            ]
+        );
+        assert_eq!(files[0].directory, repo.join("app"));
+        assert!(index(ScanMode::LockfilesOnly, &WalkOptions::default()).is_empty());
+        let shallow = WalkOptions::new().with_filter(PathFilter::new().with_max_depth(1));
+        assert!(index(ScanMode::DeclaredOnly, &shallow).is_empty());
+
+        let content = read_git_file(repo, &rev, &repo.join("app/package.json")).unwrap();
+        assert_eq!(content, br#"{"name":"app"}"#);
+        let files = index_git_rev(
+            &repo.join("app"),
+            &rev,
+            &["target"],
+            ScanMode::DeclaredOnly,
+            false,
+            &WalkOptions::default(),
+            &classify_file,
+        )
+        .unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, repo.join("app/package.json"));
+
+        assert!(resolve_rev(repo, "no-such-rev").is_err());
+    }
+}
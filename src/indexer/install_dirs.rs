@@ -32,12 +32,15 @@
 //! let venvs = find_virtual_envs(root, exclude_dirs);
 //! ```
 
+use super::walk::{par_walk, WalkOptions};
 use crate::models::Ecosystem;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
 
 /// Type of installation directory
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
 pub enum InstallDirType {
     /// Node.js node_modules directory
     NodeModules,
@@ -50,7 +53,7 @@ pub enum InstallDirType {
 }
 
 /// A discovered installation directory
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InstallDir {
     /// Path to the installation directory
     pub path: PathBuf,
@@ -85,167 +88,130 @@ impl InstallDir {
 
 /// Find all node_modules directories in a directory tree
 pub fn find_node_modules(root: &Path, exclude_dirs: &[&str]) -> Vec<InstallDir> {
-    let mut results = Vec::new();
-
-    for entry in WalkDir::new(root)
-        .into_iter()
-        .filter_entry(|e| !should_exclude_for_install_scan(e.path(), exclude_dirs))
-        .filter_map(|e| e.ok())
-    {
-        if entry.file_type().is_dir() {
-            if let Some(name) = entry.file_name().to_str() {
-                if name == "node_modules" {
-                    results.push(InstallDir::new(
-                        entry.path().to_path_buf(),
-                        InstallDirType::NodeModules,
-                        Ecosystem::Node,
-                    ));
-                }
-            }
-        }
-    }
-
-    results
+    find_install_dirs_matching(root, exclude_dirs, &WalkOptions::default(), |d| {
+        d.dir_type == InstallDirType::NodeModules
+    })
 }
 
 /// Find all site-packages and dist-packages directories in a directory tree
 pub fn find_site_packages(root: &Path, exclude_dirs: &[&str]) -> Vec<InstallDir> {
-    let mut results = Vec::new();
-
-    for entry in WalkDir::new(root)
-        .into_iter()
-        .filter_entry(|e| !should_exclude_for_install_scan(e.path(), exclude_dirs))
-        .filter_map(|e| e.ok())
-    {
-        if entry.file_type().is_dir() {
-            if let Some(name) = entry.file_name().to_str() {
-                let (dir_type, is_match) = match name {
-                    "site-packages" => (InstallDirType::SitePackages, true),
-                    "dist-packages" => (InstallDirType::DistPackages, true),
-                    _ => (InstallDirType::SitePackages, false),
-                };
-
-                if is_match {
-                    let mut install_dir =
-                        InstallDir::new(entry.path().to_path_buf(), dir_type, Ecosystem::Python);
-
-                    // Check if this is within a virtual environment
-                    if let Some(venv_root) = find_venv_root(entry.path()) {
-                        install_dir = install_dir.with_venv_root(venv_root);
-                    }
-
-                    results.push(install_dir);
-                }
-            }
-        }
-    }
-
-    results
+    find_install_dirs_matching(root, exclude_dirs, &WalkOptions::default(), |d| {
+        matches!(
+            d.dir_type,
+            InstallDirType::SitePackages | InstallDirType::DistPackages
+        )
+    })
 }
 
 /// Find all Python virtual environments in a directory tree
 pub fn find_virtual_envs(root: &Path, exclude_dirs: &[&str]) -> Vec<InstallDir> {
-    let mut results = Vec::new();
-
-    for entry in WalkDir::new(root)
-        .into_iter()
-        .filter_entry(|e| !should_exclude_for_install_scan(e.path(), exclude_dirs))
-        .filter_map(|e| e.ok())
-    {
-        if entry.file_type().is_dir() {
-            // Check for pyvenv.cfg file (definitive marker of venv)
-            let pyvenv_cfg = entry.path().join("pyvenv.cfg");
-            if pyvenv_cfg.exists() {
-                results.push(InstallDir::new(
-                    entry.path().to_path_buf(),
-                    InstallDirType::VirtualEnv,
-                    Ecosystem::Python,
-                ));
-                continue;
-            }
+    find_install_dirs_matching(root, exclude_dirs, &WalkOptions::default(), |d| {
+        d.dir_type == InstallDirType::VirtualEnv
+    })
+}
+
+/// Walk a directory tree once, collecting installation directories accepted by `keep`
+fn find_install_dirs_matching<F>(
+    root: &Path,
+    exclude_dirs: &[&str],
+    options: &WalkOptions,
+    keep: F,
+) -> Vec<InstallDir>
+where
+    F: Fn(&InstallDir) -> bool + Sync,
+{
+    let mut results = par_walk(root, options, (), |entry, _, out| {
+        if !entry.is_dir {
+            return None;
+        }
+        if entry.depth > 0 && entry.file_name().is_some_and(|n| exclude_dirs.contains(&n)) {
+            return None;
+        }
 
-            // Check for common venv directory names
-            if let Some(name) = entry.file_name().to_str() {
-                if matches!(name, ".venv" | "venv" | "env") {
-                    // Verify it looks like a venv (has bin/activate or Scripts/activate.bat)
-                    let has_activate = entry.path().join("bin/activate").exists()
-                        || entry.path().join("Scripts/activate.bat").exists();
-
-                    if has_activate {
-                        results.push(InstallDir::new(
-                            entry.path().to_path_buf(),
-                            InstallDirType::VirtualEnv,
-                            Ecosystem::Python,
-                        ));
-                    }
-                }
+        if let Some(install_dir) = classify_install_dir(&entry.path) {
+            if keep(&install_dir) && options.is_included(root, &entry.path) {
+                out.push(install_dir);
             }
         }
-    }
+        Some(())
+    });
 
+    results.sort_by(|a, b| a.path.cmp(&b.path));
     results
 }
 
-/// Find the virtual environment root for a given path
-fn find_venv_root(path: &Path) -> Option<PathBuf> {
-    let mut current = path;
-
-    while let Some(parent) = current.parent() {
-        // Check for pyvenv.cfg
-        if parent.join("pyvenv.cfg").exists() {
-            return Some(parent.to_path_buf());
-        }
-
-        // Check for common venv structure
-        if let Some(name) = parent.file_name().and_then(|n| n.to_str()) {
-            if matches!(name, ".venv" | "venv" | "env") {
-                let has_activate = parent.join("bin/activate").exists()
-                    || parent.join("Scripts/activate.bat").exists();
+/// Classify a directory as an installation directory, if it is one
+pub(crate) fn classify_install_dir(path: &Path) -> Option<InstallDir> {
+    let name = path.file_name().and_then(|n| n.to_str())?;
 
-                if has_activate {
-                    return Some(parent.to_path_buf());
-                }
+    match name {
+        "node_modules" => Some(InstallDir::new(
+            path.to_path_buf(),
+            InstallDirType::NodeModules,
+            Ecosystem::Node,
+        )),
+        "site-packages" | "dist-packages" => {
+            let dir_type = if name == "site-packages" {
+                InstallDirType::SitePackages
+            } else {
+                InstallDirType::DistPackages
+            };
+            let install_dir = InstallDir::new(path.to_path_buf(), dir_type, Ecosystem::Python);
+
+            // Check if this is within a virtual environment
+            match find_venv_root(path) {
+                Some(venv_root) => Some(install_dir.with_venv_root(venv_root)),
+                None => Some(install_dir),
             }
         }
-
-        current = parent;
+        _ if is_virtual_env(path) => Some(InstallDir::new(
+            path.to_path_buf(),
+            InstallDirType::VirtualEnv,
+            Ecosystem::Python,
+        )),
+        _ => None,
     }
-
-    None
 }
 
-/// Check if a path should be excluded from installation directory scanning
-fn should_exclude_for_install_scan(path: &Path, exclude_dirs: &[&str]) -> bool {
-    // For installation scanning, we want to find node_modules and site-packages,
-    // but we don't want to traverse INTO them (to avoid nested scans)
-    if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-        // Check custom exclusions
-        if exclude_dirs.contains(&name) {
-            return true;
-        }
+/// Check if a directory is a Python virtual environment
+fn is_virtual_env(path: &Path) -> bool {
+    // Check for pyvenv.cfg file (definitive marker of venv)
+    if path.join("pyvenv.cfg").exists() {
+        return true;
+    }
 
-        // Special handling for installation directories:
-        // We want to discover them but not traverse into them
-        // Note: This function is used with filter_entry which is called BEFORE
-        // yielding the entry, so we need to allow the directory itself through
-        // but prevent descending into it. However, filter_entry doesn't distinguish
-        // between "yield but don't descend" - it's all or nothing.
-        // So we allow these through and rely on the fact that we only care about
-        // the top-level directory, not its contents.
+    // Check for common venv directory names with an activation script
+    match path.file_name().and_then(|n| n.to_str()) {
+        Some(".venv" | "venv" | "env") => {
+            path.join("bin/activate").exists() || path.join("Scripts/activate.bat").exists()
+        }
+        _ => false,
     }
+}
 
-    false
+/// Find the virtual environment root for a given path
+fn find_venv_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .find(|parent| is_virtual_env(parent))
+        .map(Path::to_path_buf)
 }
 
 /// Find all installation directories (convenience function)
 pub fn find_all_install_dirs(root: &Path, exclude_dirs: &[&str]) -> Vec<InstallDir> {
-    let mut results = Vec::new();
-
-    results.extend(find_node_modules(root, exclude_dirs));
-    results.extend(find_site_packages(root, exclude_dirs));
-    results.extend(find_virtual_envs(root, exclude_dirs));
+    find_all_install_dirs_with_options(root, exclude_dirs, &WalkOptions::default())
+}
 
-    results
+/// Find all installation directories using the given walk options
+///
+/// When following symlinks, a directory reachable through several links
+/// (e.g. a symlinked node_modules) is reported only once.
+pub fn find_all_install_dirs_with_options(
+    root: &Path,
+    exclude_dirs: &[&str],
+    options: &WalkOptions,
+) -> Vec<InstallDir> {
+    find_install_dirs_matching(root, exclude_dirs, options, |_| true)
 }
 
 #[cfg(test)]
@@ -396,4 +362,29 @@ mod tests {
             .iter()
             .any(|d| d.dir_type == InstallDirType::VirtualEnv));
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_node_modules() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        fs::create_dir_all(root.join("shared/node_modules")).unwrap();
+        fs::create_dir_all(root.join("app")).unwrap();
+        std::os::unix::fs::symlink(
+            root.join("shared/node_modules"),
+            root.join("app/node_modules"),
+        )
+        .unwrap();
+
+        // Skipped by default: only the real directory is found
+        let results = find_all_install_dirs(root, &[]);
+        assert_eq!(results.len(), 1);
+
+        // Followed: the symlink and its target are the same directory, found once
+        let options = WalkOptions::new().with_follow_symlinks(true);
+        let results = find_all_install_dirs_with_options(root, &[], &options);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].dir_type, InstallDirType::NodeModules);
+    }
 }
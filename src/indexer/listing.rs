@@ -0,0 +1,142 @@
+//! Listing of what a scan would parse
+//!
+//! [`list_index`] turns a [`FileIndex`] into one entry per manifest, lockfile
+//! and installation directory, with its ecosystem and size, without parsing
+//! anything. It backs `--dry-run`, which lets operators check the scope and
+//! exclusions of a long scan before running it. Files are sized in bytes;
+//! installation directories by their number of entries, as summing the
+//! bytes of a node_modules tree would cost a walk of its own.
+
+use super::{FileIndex, InstallDirType};
+use crate::models::{Ecosystem, FileType};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Kind of a listed path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ListedKind {
+    /// Manifest (package.json, pyproject.toml, ...)
+    Manifest,
+    /// Lockfile (package-lock.json, poetry.lock, ...)
+    Lockfile,
+    /// Installation directory (node_modules, site-packages, ...)
+    InstallDir,
+}
+
+impl std::fmt::Display for ListedKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListedKind::Manifest => write!(f, "manifest"),
+            ListedKind::Lockfile => write!(f, "lockfile"),
+            ListedKind::InstallDir => write!(f, "install_dir"),
+        }
+    }
+}
+
+/// A manifest, lockfile or installation directory a scan would parse
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ListedPath {
+    /// Path of the file or directory
+    pub path: PathBuf,
+
+    /// Manifest, lockfile or installation directory
+    pub kind: ListedKind,
+
+    /// Ecosystem
+    pub ecosystem: Ecosystem,
+
+    /// Type of installation directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir_type: Option<InstallDirType>,
+
+    /// Size of the file in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+
+    /// Number of entries of the installation directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entries: Option<usize>,
+}
+
+/// List the package files and installation directories of an index, files
+/// first, each group in path order; sizes of unreadable paths are left out
+pub fn list_index(index: &FileIndex) -> Vec<ListedPath> {
+    let mut files: Vec<ListedPath> = index
+        .files
+        .iter()
+        .map(|file| ListedPath {
+            path: file.path.clone(),
+            kind: match file.file_type {
+                FileType::Manifest => ListedKind::Manifest,
+                FileType::Lockfile => ListedKind::Lockfile,
+            },
+            ecosystem: file.ecosystem,
+            dir_type: None,
+            size: fs::metadata(&file.path).ok().map(|m| m.len()),
+            entries: None,
+        })
+        .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut dirs: Vec<ListedPath> = index
+        .install_dirs
+        .iter()
+        .map(|dir| ListedPath {
+            path: dir.path.clone(),
+            kind: ListedKind::InstallDir,
+            ecosystem: dir.ecosystem,
+            dir_type: Some(dir.dir_type.clone()),
+            size: None,
+            entries: fs::read_dir(&dir.path).ok().map(|entries| entries.count()),
+        })
+        .collect();
+    dirs.sort_by(|a, b| a.path.cmp(&b.path));
+
+    files.extend(dirs);
+    files
+}
+
+/// Total bytes of the listed files
+pub fn listed_bytes(listed: &[ListedPath]) -> u64 {
+    listed.iter().filter_map(|entry| entry.size).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::{index_tree, ScanMode, WalkOptions};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_list_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("package.json"), r#"{"name": "web"}"#).unwrap();
+        fs::write(root.join("yarn.lock"), "").unwrap();
+        for package in ["lodash", "react"] {
+            fs::create_dir_all(root.join("node_modules").join(package)).unwrap();
+        }
+
+        let index = index_tree(root, &[], ScanMode::Full, false, &WalkOptions::default());
+        let listed = list_index(&index);
+
+        assert_eq!(listed.len(), 3);
+        assert_eq!(listed[0].path, root.join("package.json"));
+        assert_eq!(listed[0].kind, ListedKind::Manifest);
+        assert_eq!(listed[0].size, Some(15));
+        assert_eq!(listed[1].kind, ListedKind::Lockfile);
+        assert_eq!(listed[1].size, Some(0));
+        assert_eq!(listed[2].kind, ListedKind::InstallDir);
+        assert_eq!(listed[2].dir_type, Some(InstallDirType::NodeModules));
+        assert_eq!(listed[2].entries, Some(2));
+        assert_eq!(listed_bytes(&listed), 15);
+
+        let json = serde_json::to_value(&listed[2]).unwrap();
+        assert_eq!(json["kind"], "install_dir");
+        assert_eq!(json["dir_type"], "node_modules");
+        assert!(json.get("size").is_none());
+    }
+}
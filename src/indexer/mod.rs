@@ -1,23 +1,47 @@
 //! Filesystem indexing and file discovery
 //!
-//! This module handles recursive directory traversal to identify package management files.
+//! This module handles recursive directory traversal to identify package management files
+//! and installation directories. [`index_tree`] discovers both in a single parallel walk;
+//! [`index_paths`] takes them from a list of paths instead, and
+//! [`index_archive`] reads the package files of a snapshot archive.
 
-use rayon::prelude::*;
+use crate::models::{Ecosystem, FileType};
+use crate::progress::{NoProgress, ProgressReporter, ScanPhase};
+use serde::{Deserialize, Serialize};
 use std::path::Path;
-use std::sync::Mutex;
-use walkdir::WalkDir;
 
+pub mod archive;
+pub mod changed;
+pub mod detect;
 pub mod file_types;
+pub mod filter;
+pub mod git_rev;
 pub mod install_dirs;
+pub mod listing;
+pub mod paths;
+pub mod walk;
 
+pub use archive::{index_archive, ArchiveFiles};
+pub use changed::{affected_roots, changed_files};
+pub use detect::{detect_ecosystems, package_manager, EcosystemDetection};
 pub use file_types::{classify_file, DiscoveredFile};
+use file_types::{discover_file, discover_listed_file};
+pub use filter::{Glob, PathFilter};
+pub use git_rev::{index_git_rev, read_git_file, resolve_rev};
 pub use install_dirs::{
-    find_all_install_dirs, find_node_modules, find_site_packages, find_virtual_envs, InstallDir,
-    InstallDirType,
+    find_all_install_dirs, find_all_install_dirs_with_options, find_node_modules,
+    find_site_packages, find_virtual_envs, InstallDir, InstallDirType,
 };
+pub use listing::{list_index, listed_bytes, ListedKind, ListedPath};
+pub use paths::index_paths;
+pub use walk::WalkOptions;
+
+/// Classifies a file name as a manifest or lockfile of an ecosystem
+pub type FileClassifier<'a> = dyn Fn(&str) -> Option<(Ecosystem, FileType)> + Sync + 'a;
 
 /// Scan mode for directory traversal
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ScanMode {
     /// Scan all files (manifests, lockfiles, and installed packages)
     Full,
@@ -25,6 +49,29 @@ pub enum ScanMode {
     InstalledOnly,
     /// Only scan declared dependencies (manifests and lockfiles)
     DeclaredOnly,
+    /// Only scan lockfiles, skipping manifests and installation directories
+    LockfilesOnly,
+}
+
+impl ScanMode {
+    /// Check if manifests and lockfiles are collected
+    pub fn collects_files(self) -> bool {
+        self != ScanMode::InstalledOnly
+    }
+
+    /// Check if installation directories are collected
+    pub fn collects_install_dirs(self) -> bool {
+        matches!(self, ScanMode::Full | ScanMode::InstalledOnly)
+    }
+
+    /// Check if a file of this type is collected
+    pub fn collects_file_type(self, file_type: FileType) -> bool {
+        match self {
+            ScanMode::Full | ScanMode::DeclaredOnly => true,
+            ScanMode::InstalledOnly => false,
+            ScanMode::LockfilesOnly => file_type == FileType::Lockfile,
+        }
+    }
 }
 
 /// Find all package management files in a directory tree
@@ -38,11 +85,28 @@ pub fn find_files_with_mode(
     exclude_dirs: &[&str],
     scan_mode: ScanMode,
     include_install_dirs: bool,
+) -> Vec<DiscoveredFile> {
+    find_files_with_options(
+        root,
+        exclude_dirs,
+        scan_mode,
+        include_install_dirs,
+        &WalkOptions::default(),
+    )
+}
+
+/// Find all package management files with specified scan mode and walk options
+pub fn find_files_with_options(
+    root: &Path,
+    exclude_dirs: &[&str],
+    scan_mode: ScanMode,
+    include_install_dirs: bool,
+    walk_options: &WalkOptions,
 ) -> Vec<DiscoveredFile> {
     match scan_mode {
         ScanMode::Full => {
             // Scan both declared and installed
-            find_declared_files(root, exclude_dirs, include_install_dirs)
+            find_declared_files(root, exclude_dirs, include_install_dirs, walk_options)
         }
         ScanMode::InstalledOnly => {
             // Only scan installation directories - no manifest/lockfile parsing
@@ -50,69 +114,310 @@ pub fn find_files_with_mode(
         }
         ScanMode::DeclaredOnly => {
             // Only scan manifests and lockfiles
-            find_declared_files(root, exclude_dirs, include_install_dirs)
+            find_declared_files(root, exclude_dirs, include_install_dirs, walk_options)
+        }
+        ScanMode::LockfilesOnly => {
+            find_declared_files(root, exclude_dirs, include_install_dirs, walk_options)
+                .into_iter()
+                .filter(|f| f.file_type == FileType::Lockfile)
+                .collect()
         }
     }
 }
 
+/// Directory names treated as installation directories when collecting declared files
+const INSTALL_DIR_NAMES: &[&str] = &[
+    "node_modules",
+    "site-packages",
+    "dist-packages",
+    ".venv",
+    "venv",
+    "env",
+];
+
 /// Find declared dependency files (manifests and lockfiles)
 fn find_declared_files(
     root: &Path,
     exclude_dirs: &[&str],
     include_install_dirs: bool,
+    walk_options: &WalkOptions,
 ) -> Vec<DiscoveredFile> {
-    // Build exclusion list
-    let mut exclusions = exclude_dirs.to_vec();
-
-    // Add installation directories to exclusions unless explicitly included
-    if !include_install_dirs {
-        exclusions.extend_from_slice(&[
-            "node_modules",
-            "site-packages",
-            "dist-packages",
-            ".venv",
-            "venv",
-            "env",
-        ]);
-    }
-    // Collect all entries first (walkdir doesn't support parallel iteration directly)
-    let entries: Vec<_> = WalkDir::new(root)
-        .into_iter()
-        .filter_entry(|e| !should_exclude(e.path(), &exclusions))
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-        .collect();
-
-    // Process entries in parallel
-    let discovered = Mutex::new(Vec::new());
-
-    entries.par_iter().for_each(|entry| {
-        let file_name = entry.file_name().to_string_lossy();
-
-        if let Some((ecosystem, file_type)) = classify_file(&file_name) {
-            if let Some(parent) = entry.path().parent() {
-                let file = DiscoveredFile {
-                    path: entry.path().to_path_buf(),
-                    filename: file_name.to_string(),
-                    directory: parent.to_path_buf(),
-                    ecosystem,
-                    file_type,
-                };
-                discovered.lock().unwrap().push(file);
+    let options = IndexOptions {
+        exclude_dirs,
+        scan_mode: ScanMode::DeclaredOnly,
+        include_install_dirs,
+        walk_options,
+        classify: &classify_file,
+        progress: &NoProgress,
+    };
+    build_index(root, &options).files
+}
+
+/// Files and installation directories discovered in a single walk
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileIndex {
+    /// Manifests and lockfiles
+    pub files: Vec<DiscoveredFile>,
+
+    /// node_modules, site-packages/dist-packages and virtual environments
+    pub install_dirs: Vec<InstallDir>,
+}
+
+/// Index a directory tree in one parallel pass
+///
+/// Manifests, lockfiles and installation directories are classified during the
+/// same walk instead of traversing the tree once per kind:
+///
+/// - Directories named in `exclude_dirs` are never entered
+/// - Installation directories are reported but only entered when
+///   `include_install_dirs` is set (nested `node_modules` are covered by the
+///   installed package parser); virtual environments are always entered so
+///   their site-packages are found
+/// - `scan_mode` decides whether files, installation directories or both are collected
+///
+/// Results are sorted by path.
+pub fn index_tree(
+    root: &Path,
+    exclude_dirs: &[&str],
+    scan_mode: ScanMode,
+    include_install_dirs: bool,
+    walk_options: &WalkOptions,
+) -> FileIndex {
+    index_tree_with_progress(
+        root,
+        exclude_dirs,
+        scan_mode,
+        include_install_dirs,
+        walk_options,
+        &NoProgress,
+    )
+}
+
+/// Index a directory tree, reporting each discovered item to `progress`
+pub fn index_tree_with_progress(
+    root: &Path,
+    exclude_dirs: &[&str],
+    scan_mode: ScanMode,
+    include_install_dirs: bool,
+    walk_options: &WalkOptions,
+    progress: &dyn ProgressReporter,
+) -> FileIndex {
+    index_tree_with_classifier(
+        root,
+        exclude_dirs,
+        scan_mode,
+        include_install_dirs,
+        walk_options,
+        &classify_file,
+        progress,
+    )
+}
+
+/// Index a directory tree, recognizing package files with `classify`
+/// instead of [`classify_file`] (e.g. to add the files of registered parsers)
+pub fn index_tree_with_classifier(
+    root: &Path,
+    exclude_dirs: &[&str],
+    scan_mode: ScanMode,
+    include_install_dirs: bool,
+    walk_options: &WalkOptions,
+    classify: &FileClassifier<'_>,
+    progress: &dyn ProgressReporter,
+) -> FileIndex {
+    let options = IndexOptions {
+        exclude_dirs,
+        scan_mode,
+        include_install_dirs,
+        walk_options,
+        classify,
+        progress,
+    };
+    build_index(root, &options)
+}
+
+/// Settings shared by every task of an index walk
+struct IndexOptions<'a> {
+    exclude_dirs: &'a [&'a str],
+    scan_mode: ScanMode,
+    include_install_dirs: bool,
+    walk_options: &'a WalkOptions,
+    classify: &'a FileClassifier<'a>,
+    progress: &'a dyn ProgressReporter,
+}
+
+/// Where in the tree a walk task currently is
+#[derive(Debug, Clone, Copy, Default)]
+struct IndexState {
+    /// Inside node_modules, site-packages or dist-packages
+    in_packages: bool,
+    /// Inside a directory named like an installation directory
+    in_install_dir: bool,
+}
+
+/// An item discovered by the index walk
+enum IndexItem {
+    File(DiscoveredFile),
+    InstallDir(InstallDir),
+}
+
+/// Walk the tree once, classifying files and installation directories
+fn build_index(root: &Path, options: &IndexOptions) -> FileIndex {
+    let collect_files = options.scan_mode.collects_files();
+    let collect_install_dirs = options.scan_mode.collects_install_dirs();
+
+    let items = walk::par_walk(
+        root,
+        options.walk_options,
+        IndexState::default(),
+        |entry, state, out| {
+            let name = entry.file_name().unwrap_or("");
+
+            if !entry.is_dir {
+                let wanted =
+                    collect_files && (options.include_install_dirs || !state.in_install_dir);
+                if wanted && options.walk_options.is_included(root, &entry.path) {
+                    if let Some(file) = discover_file(&entry.path, options.classify)
+                        .filter(|f| options.scan_mode.collects_file_type(f.file_type))
+                    {
+                        options.progress.advance(ScanPhase::Discovery, 1);
+                        out.push(IndexItem::File(file));
+                    }
+                }
+                return None;
+            }
+
+            if entry.depth > 0 && options.exclude_dirs.contains(&name) {
+                return None;
+            }
+
+            let mut child = IndexState {
+                in_packages: state.in_packages,
+                in_install_dir: state.in_install_dir
+                    || (entry.depth > 0 && INSTALL_DIR_NAMES.contains(&name)),
+            };
+
+            match install_dirs::classify_install_dir(&entry.path) {
+                Some(install_dir) => {
+                    if collect_install_dirs
+                        && !state.in_packages
+                        && options.walk_options.is_included(root, &entry.path)
+                    {
+                        options.progress.advance(ScanPhase::Discovery, 1);
+                        out.push(IndexItem::InstallDir(install_dir.clone()));
+                    }
+
+                    if install_dir.dir_type == InstallDirType::VirtualEnv {
+                        return Some(child);
+                    }
+
+                    child.in_packages = true;
+                    (collect_files && options.include_install_dirs).then_some(child)
+                }
+                None => Some(child),
             }
+        },
+    );
+
+    let mut index = FileIndex::default();
+    for item in items {
+        match item {
+            IndexItem::File(file) => index.files.push(file),
+            IndexItem::InstallDir(dir) => index.install_dirs.push(dir),
         }
-    });
+    }
 
-    discovered.into_inner().unwrap()
+    index.files.sort_by(|a, b| a.path.cmp(&b.path));
+    index.install_dirs.sort_by(|a, b| a.path.cmp(&b.path));
+    index
 }
 
-/// Check if a path should be excluded from traversal
-fn should_exclude(path: &Path, exclude_dirs: &[&str]) -> bool {
-    path.components().any(|component| {
-        if let Some(name) = component.as_os_str().to_str() {
-            exclude_dirs.contains(&name)
-        } else {
-            false
-        }
-    })
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_index_tree_single_pass() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        write(&root.join("app/package.json"), "{}");
+        write(&root.join("app/node_modules/react/package.json"), "{}");
+        write(
+            &root.join("app/node_modules/react/node_modules/x/package.json"),
+            "{}",
+        );
+        write(&root.join("api/requirements.txt"), "");
+        write(&root.join("api/.venv/pyvenv.cfg"), "home = /usr/bin\n");
+        fs::create_dir_all(root.join("api/.venv/lib/python3.11/site-packages")).unwrap();
+        write(&root.join("target/package.json"), "{}");
+
+        let index = index_tree(
+            root,
+            &["target"],
+            ScanMode::Full,
+            false,
+            &WalkOptions::default(),
+        );
+
+        let files: Vec<_> = index.files.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(
+            files,
+            vec![
+                root.join("api/requirements.txt"),
+                root.join("app/package.json")
+            ]
+        );
+
+        // Nested node_modules are left to the installed package parser
+        let dirs: Vec<_> = index
+            .install_dirs
+            .iter()
+            .map(|d| (d.path.clone(), d.dir_type.clone()))
+            .collect();
+        assert_eq!(
+            dirs,
+            vec![
+                (root.join("api/.venv"), InstallDirType::VirtualEnv),
+                (
+                    root.join("api/.venv/lib/python3.11/site-packages"),
+                    InstallDirType::SitePackages
+                ),
+                (root.join("app/node_modules"), InstallDirType::NodeModules),
+            ]
+        );
+        assert_eq!(
+            index.install_dirs[1].venv_root,
+            Some(root.join("api/.venv"))
+        );
+    }
+
+    #[test]
+    fn test_index_tree_scan_modes() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        write(&root.join("package.json"), "{}");
+        write(&root.join("node_modules/lodash/package.json"), "{}");
+
+        let options = WalkOptions::default();
+
+        let declared = index_tree(root, &[], ScanMode::DeclaredOnly, false, &options);
+        assert_eq!(declared.files.len(), 1);
+        assert!(declared.install_dirs.is_empty());
+
+        let installed = index_tree(root, &[], ScanMode::InstalledOnly, false, &options);
+        assert!(installed.files.is_empty());
+        assert_eq!(installed.install_dirs.len(), 1);
+
+        let included = index_tree(root, &[], ScanMode::Full, true, &options);
+        assert_eq!(included.files.len(), 2);
+        assert_eq!(included.install_dirs.len(), 1);
+    }
 }
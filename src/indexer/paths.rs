@@ -0,0 +1,184 @@
+//! Indexing a list of paths instead of walking a tree
+//!
+//! Integrations that already keep a file inventory (osquery, backup catalogs)
+//! hand it to the scanner instead of having it walk the filesystem.
+//! Listed files are classified by name as manifests and lockfiles. Listed
+//! directories that are installation directories are taken as they are, and
+//! a file inside `node_modules`, `site-packages` or `dist-packages` stands for
+//! the outermost such directory, so a full inventory listing
+//! `app/node_modules/lodash/package.json` yields `app/node_modules`.
+//!
+//! The same rules as the walk apply: files inside installation directories
+//! are only collected with `include_install_dirs`, and paths under a
+//! directory named in `exclude_dirs` are dropped.
+
+use super::file_types::discover_file;
+use super::install_dirs::classify_install_dir;
+use super::{FileClassifier, FileIndex, ScanMode, INSTALL_DIR_NAMES};
+use crate::progress::{ProgressReporter, ScanPhase};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Names of the directories holding installed packages
+const PACKAGES_DIR_NAMES: &[&str] = &["node_modules", "site-packages", "dist-packages"];
+
+/// Index the package files and installation directories of a list of paths
+///
+/// Results are sorted by path; paths listed more than once are kept once.
+pub fn index_paths(
+    paths: &[PathBuf],
+    exclude_dirs: &[&str],
+    scan_mode: ScanMode,
+    include_install_dirs: bool,
+    classify: &FileClassifier<'_>,
+    progress: &dyn ProgressReporter,
+) -> FileIndex {
+    let mut files = BTreeMap::new();
+    let mut install_dirs = BTreeMap::new();
+    for path in paths {
+        let parents = path.parent().map(dir_names).unwrap_or_default();
+        if parents.iter().any(|name| exclude_dirs.contains(name)) {
+            continue;
+        }
+
+        if scan_mode.collects_install_dirs() {
+            let packages_dir = path
+                .ancestors()
+                .filter(|dir| {
+                    dir.file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| PACKAGES_DIR_NAMES.contains(&name))
+                })
+                .last();
+            let install_dir = match packages_dir {
+                Some(dir) => classify_install_dir(dir),
+                None => classify_install_dir(path),
+            };
+            if let Some(install_dir) = install_dir {
+                if !install_dirs.contains_key(&install_dir.path) {
+                    progress.advance(ScanPhase::Discovery, 1);
+                    install_dirs.insert(install_dir.path.clone(), install_dir);
+                }
+            }
+        }
+
+        let in_install_dir = parents.iter().any(|name| INSTALL_DIR_NAMES.contains(name));
+        if scan_mode.collects_files() && (include_install_dirs || !in_install_dir) {
+            if let Some(file) =
+                discover_file(path, classify).filter(|f| scan_mode.collects_file_type(f.file_type))
+            {
+                if !files.contains_key(&file.path) {
+                    progress.advance(ScanPhase::Discovery, 1);
+                    files.insert(file.path.clone(), file);
+                }
+            }
+        }
+    }
+
+    FileIndex {
+        files: files.into_values().collect(),
+        install_dirs: install_dirs.into_values().collect(),
+    }
+}
+
+/// Names of the directories of a path
+fn dir_names(path: &Path) -> Vec<&str> {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::{classify_file, InstallDirType};
+    use crate::progress::NoProgress;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_index_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let venv = root.join("api/.venv");
+        fs::create_dir_all(venv.join("lib/python3.12/site-packages")).unwrap();
+        fs::write(venv.join("pyvenv.cfg"), "home = /usr/bin\n").unwrap();
+
+        // Nothing else needs to exist
+        let paths: Vec<PathBuf> = [
+            "app/package.json",
+            "app/package-lock.json",
+            "app/package.json",
+            "app/README.md",
+            "app/node_modules/lodash/package.json",
+            "app/node_modules/lodash/node_modules/x/index.js",
+            "api/.venv/lib/python3.12/site-packages/yaml/__init__.py",
+            "api/.venv/lib/python3.12/site-packages/yaml-6.0.dist-info/METADATA",
+            "api/requirements.txt",
+            "target/package.json",
+        ]
+        .iter()
+        .map(|path| root.join(path))
+        .collect();
+
+        let index = index_paths(
+            &paths,
+            &["target"],
+            ScanMode::Full,
+            false,
+            &classify_file,
+            &NoProgress,
+        );
+        let files: Vec<_> = index.files.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(
+            files,
+            [
+                root.join("api/requirements.txt"),
+                root.join("app/package-lock.json"),
+                root.join("app/package.json"),
+            ]
+        );
+        let dirs: Vec<_> = index
+            .install_dirs
+            .iter()
+            .map(|d| (d.path.clone(), d.dir_type.clone()))
+            .collect();
+        assert_eq!(
+            dirs,
+            [
+                (
+                    venv.join("lib/python3.12/site-packages"),
+                    InstallDirType::SitePackages
+                ),
+                (root.join("app/node_modules"), InstallDirType::NodeModules),
+            ]
+        );
+        assert_eq!(index.install_dirs[0].venv_root, Some(venv.clone()));
+
+        // Listed directories are taken as installation directories
+        let index = index_paths(
+            std::slice::from_ref(&venv),
+            &[],
+            ScanMode::InstalledOnly,
+            false,
+            &classify_file,
+            &NoProgress,
+        );
+        assert!(index.files.is_empty());
+        assert_eq!(index.install_dirs[0].dir_type, InstallDirType::VirtualEnv);
+
+        let index = index_paths(
+            &paths,
+            &[],
+            ScanMode::LockfilesOnly,
+            false,
+            &classify_file,
+            &NoProgress,
+        );
+        assert_eq!(index.files.len(), 1);
+        assert!(index.install_dirs.is_empty());
+    }
+}
@@ -0,0 +1,500 @@
+//! Parallel directory walking with symlink handling and loop protection
+//!
+//! All indexer traversals go through [`par_walk`], a work-stealing walker built on
+//! rayon scopes: every directory is read on its own task, so wide trees (and slow
+//! network filesystems) are traversed concurrently. On top of that it provides:
+//!
+//! - **Symlink policy**: symlinked directories are either skipped (default) or followed
+//! - **Cycle detection**: when following symlinks, every directory is identified by its
+//!   device/inode pair and visited at most once, so `a/link -> a` loops and symlinked
+//!   `node_modules` that point at the same target are traversed only once
+//! - **Visited-directory guard**: an optional upper bound on the number of directories
+//!   visited, protecting against pathological or hostile filesystems
+//! - **Path filtering**: maximum depth and exclude globs from the configured [`PathFilter`]
+//! - **Deadline**: an optional point in time after which no further directories are entered
+//! - **Cancellation**: an optional [`CancellationToken`] stopping the walk the same way
+//! - **I/O throttle**: an optional [`IoThrottle`] holding every directory read to its rate
+
+use super::filter::PathFilter;
+use crate::cancel::CancellationToken;
+use crate::throttle::IoThrottle;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::warn;
+
+/// Options controlling how the indexer walks a directory tree
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WalkOptions {
+    /// Follow symlinked directories (skipped when false)
+    pub follow_symlinks: bool,
+
+    /// Maximum number of distinct directories to visit (unlimited when None)
+    pub max_visited_dirs: Option<usize>,
+
+    /// Depth limit and include/exclude globs
+    pub filter: PathFilter,
+
+    /// Stop entering directories after this point in time
+    pub deadline: Option<Instant>,
+
+    /// Stop entering directories once this token is cancelled
+    pub cancellation: Option<CancellationToken>,
+
+    /// Read directories at the rate of this throttle
+    pub io_throttle: Option<IoThrottle>,
+}
+
+impl WalkOptions {
+    /// Create walk options with default settings (skip symlinks, no limit)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether symlinked directories are followed
+    pub fn with_follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /// Set the maximum number of directories to visit
+    pub fn with_max_visited_dirs(mut self, max_visited_dirs: usize) -> Self {
+        self.max_visited_dirs = Some(max_visited_dirs);
+        self
+    }
+
+    /// Set the point in time after which no directories are entered
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Set the token that stops the walk when cancelled
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+
+    /// Set the throttle holding directory reads to its rate
+    pub fn with_io_throttle(mut self, io_throttle: IoThrottle) -> Self {
+        self.io_throttle = Some(io_throttle);
+        self
+    }
+
+    /// Set the path filter
+    pub fn with_filter(mut self, filter: PathFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Check if a discovered path passes the include globs
+    pub fn is_included(&self, root: &Path, path: &Path) -> bool {
+        self.filter.is_included(root, path)
+    }
+}
+
+/// A file or directory encountered during a walk
+#[derive(Debug, Clone)]
+pub(crate) struct WalkEntry {
+    /// Full path to the entry
+    pub path: PathBuf,
+    /// Depth below the walk root (root is 0)
+    pub depth: usize,
+    /// Whether the entry is a directory (after resolving followed symlinks)
+    pub is_dir: bool,
+}
+
+impl WalkEntry {
+    /// Get the entry's file name
+    pub fn file_name(&self) -> Option<&str> {
+        self.path.file_name().and_then(|n| n.to_str())
+    }
+}
+
+/// Unique identity of a directory on disk
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum DirId {
+    #[cfg(unix)]
+    Inode(u64, u64),
+    #[cfg(not(unix))]
+    Path(PathBuf),
+}
+
+/// Get the on-disk identity of a directory (resolving symlinks)
+fn dir_id(path: &Path) -> Option<DirId> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let metadata = fs::metadata(path).ok()?;
+        Some(DirId::Inode(metadata.dev(), metadata.ino()))
+    }
+
+    #[cfg(not(unix))]
+    {
+        fs::canonicalize(path).ok().map(DirId::Path)
+    }
+}
+
+/// Shared state for one parallel walk
+struct WalkContext<'a, T, F> {
+    root: &'a Path,
+    options: &'a WalkOptions,
+    visit: &'a F,
+    visited: Mutex<HashSet<DirId>>,
+    /// Directories entered, after symlinked duplicates are skipped
+    visited_count: AtomicUsize,
+    /// Each limit warns once, on the first directory it skips
+    deadline_reported: AtomicBool,
+    cancellation_reported: AtomicBool,
+    max_dirs_reported: AtomicBool,
+    /// Each directory task sends its items as one batch
+    results: Sender<Vec<T>>,
+}
+
+impl<T, F> WalkContext<'_, T, F> {
+    /// Register a directory before yielding it; false means skip it entirely
+    fn enter_dir(&self, path: &Path) -> bool {
+        if self
+            .options
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            if !self.deadline_reported.swap(true, Ordering::Relaxed) {
+                warn!(
+                    path = %path.display(),
+                    "Scan time budget exhausted, skipping remaining directories"
+                );
+            }
+            return false;
+        }
+
+        if self
+            .options
+            .cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            if !self.cancellation_reported.swap(true, Ordering::Relaxed) {
+                warn!(
+                    path = %path.display(),
+                    "Scan cancelled, skipping remaining directories"
+                );
+            }
+            return false;
+        }
+
+        // Without following symlinks a directory can only be reached once
+        if self.options.follow_symlinks {
+            if let Some(id) = dir_id(path) {
+                if !self.visited.lock().unwrap().insert(id) {
+                    return false;
+                }
+            }
+        }
+
+        if let Some(max) = self.options.max_visited_dirs {
+            if self.visited_count.fetch_add(1, Ordering::Relaxed) >= max {
+                if !self.max_dirs_reported.swap(true, Ordering::Relaxed) {
+                    warn!(
+                        limit = max,
+                        path = %path.display(),
+                        "Visited directory limit reached, skipping remaining directories"
+                    );
+                }
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Walk a directory tree in parallel, collecting items produced by `visit`
+///
+/// `visit` is called once per entry (including the root) with the state of the
+/// parent directory and a buffer to push results into. For directories it returns
+/// the state to descend with, or `None` to yield the directory without
+/// descending into it. Directories already visited (by inode), paths matching an
+/// exclude glob, and entries below the maximum depth are never yielded. Include
+/// globs are not applied here; visitors check [`WalkOptions::is_included`].
+///
+/// Results are returned in no particular order.
+pub(crate) fn par_walk<S, T, F>(
+    root: &Path,
+    options: &WalkOptions,
+    root_state: S,
+    visit: F,
+) -> Vec<T>
+where
+    S: Send,
+    T: Send,
+    F: Fn(&WalkEntry, &S, &mut Vec<T>) -> Option<S> + Sync,
+{
+    let (results, batches) = mpsc::channel();
+    let ctx = WalkContext {
+        root,
+        options,
+        visit: &visit,
+        visited: Mutex::new(HashSet::new()),
+        visited_count: AtomicUsize::new(0),
+        deadline_reported: AtomicBool::new(false),
+        cancellation_reported: AtomicBool::new(false),
+        max_dirs_reported: AtomicBool::new(false),
+        results,
+    };
+
+    let root_entry = WalkEntry {
+        path: root.to_path_buf(),
+        depth: 0,
+        is_dir: root.is_dir(),
+    };
+
+    if !root_entry.is_dir || ctx.enter_dir(root) {
+        let mut local = Vec::new();
+        let state = visit(&root_entry, &root_state, &mut local);
+        let _ = ctx.results.send(local);
+
+        if let (true, Some(state)) = (root_entry.is_dir, state) {
+            rayon::scope(|scope| walk_dir(scope, &ctx, root_entry.path, 0, state));
+        }
+    }
+
+    drop(ctx);
+    batches.into_iter().flatten().collect()
+}
+
+/// Read one directory and spawn tasks for its subdirectories
+fn walk_dir<'s, S, T, F>(
+    scope: &rayon::Scope<'s>,
+    ctx: &'s WalkContext<'s, T, F>,
+    dir: PathBuf,
+    depth: usize,
+    state: S,
+) where
+    S: Send + 's,
+    T: Send,
+    F: Fn(&WalkEntry, &S, &mut Vec<T>) -> Option<S> + Sync,
+{
+    if let Some(throttle) = &ctx.options.io_throttle {
+        throttle.acquire(1, 0);
+    }
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    let depth = depth + 1;
+    if ctx.options.filter.max_depth.is_some_and(|max| depth > max) {
+        return;
+    }
+
+    let mut local = Vec::new();
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let file_type = match entry.file_type() {
+            Ok(file_type) => file_type,
+            Err(_) => continue,
+        };
+
+        let is_dir = if file_type.is_symlink() {
+            if !ctx.options.follow_symlinks {
+                continue;
+            }
+            match fs::metadata(&path) {
+                Ok(metadata) => metadata.is_dir(),
+                Err(_) => continue,
+            }
+        } else {
+            file_type.is_dir()
+        };
+
+        if ctx.options.filter.is_excluded(ctx.root, &path) {
+            continue;
+        }
+
+        if is_dir && !ctx.enter_dir(&path) {
+            continue;
+        }
+
+        let walk_entry = WalkEntry {
+            path,
+            depth,
+            is_dir,
+        };
+
+        if let Some(child_state) = (ctx.visit)(&walk_entry, &state, &mut local) {
+            if is_dir {
+                let path = walk_entry.path;
+                scope.spawn(move |scope| walk_dir(scope, ctx, path, depth, child_state));
+            }
+        }
+    }
+
+    if !local.is_empty() {
+        let _ = ctx.results.send(local);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Collect all directories below root
+    fn collect_dirs(root: &Path, options: &WalkOptions) -> Vec<PathBuf> {
+        par_walk(root, options, (), |entry, _, out| {
+            if entry.is_dir {
+                out.push(entry.path.clone());
+            }
+            Some(())
+        })
+    }
+
+    #[test]
+    fn test_walk_options_builder() {
+        let options = WalkOptions::new()
+            .with_follow_symlinks(true)
+            .with_max_visited_dirs(10);
+
+        assert!(options.follow_symlinks);
+        assert_eq!(options.max_visited_dirs, Some(10));
+        assert_eq!(WalkOptions::default().max_visited_dirs, None);
+    }
+
+    #[test]
+    fn test_walk_visits_all_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::create_dir_all(root.join("c")).unwrap();
+        fs::write(root.join("a/b/file.txt"), "x").unwrap();
+
+        let files = par_walk(root, &WalkOptions::default(), (), |entry, _, out| {
+            if !entry.is_dir {
+                out.push((entry.path.clone(), entry.depth));
+            }
+            Some(())
+        });
+
+        assert_eq!(files, vec![(root.join("a/b/file.txt"), 3)]);
+        assert_eq!(collect_dirs(root, &WalkOptions::default()).len(), 4);
+    }
+
+    #[test]
+    fn test_walk_state_and_pruning() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("keep/inner")).unwrap();
+        fs::create_dir_all(root.join("prune/inner")).unwrap();
+
+        // State carries the depth of the "keep" subtree; "prune" is yielded but not entered
+        let found = par_walk(
+            root,
+            &WalkOptions::default(),
+            0usize,
+            |entry, level, out| {
+                out.push((entry.file_name().unwrap_or("").to_string(), *level));
+                match entry.file_name() {
+                    Some("prune") => None,
+                    _ => Some(level + 1),
+                }
+            },
+        );
+
+        assert!(found.contains(&("inner".to_string(), 2)));
+        assert!(found.contains(&("prune".to_string(), 1)));
+        assert_eq!(found.iter().filter(|(n, _)| n == "inner").count(), 1);
+    }
+
+    #[test]
+    fn test_filter_max_depth_and_exclude() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("a/b/c")).unwrap();
+        fs::create_dir_all(root.join("skip/me")).unwrap();
+
+        let filter = PathFilter::new()
+            .with_max_depth(2)
+            .with_exclude_globs(&["skip"])
+            .unwrap();
+        let options = WalkOptions::new().with_filter(filter);
+        let paths = collect_dirs(root, &options);
+
+        assert!(paths.contains(&root.join("a/b")));
+        assert!(!paths.contains(&root.join("a/b/c")));
+        assert!(!paths.iter().any(|p| p.starts_with(root.join("skip"))));
+    }
+
+    #[test]
+    fn test_max_visited_dirs_limits_traversal() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("a/b/c/d")).unwrap();
+
+        let options = WalkOptions::new().with_max_visited_dirs(2);
+        assert_eq!(collect_dirs(root, &options).len(), 2);
+    }
+
+    #[test]
+    fn test_deadline_stops_traversal() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("a/b")).unwrap();
+
+        let options = WalkOptions::new().with_deadline(Instant::now());
+        assert!(collect_dirs(root, &options).is_empty());
+    }
+
+    #[test]
+    fn test_cancellation_stops_traversal() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("a/b")).unwrap();
+
+        let token = CancellationToken::new();
+        let options = WalkOptions::new().with_cancellation(token.clone());
+        assert_eq!(collect_dirs(root, &options).len(), 3);
+        token.cancel();
+        assert!(collect_dirs(root, &options).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_loop_terminates() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("a")).unwrap();
+        std::os::unix::fs::symlink(root.join("a"), root.join("a/loop")).unwrap();
+
+        let options = WalkOptions::new().with_follow_symlinks(true);
+
+        // root and a only; the loop back to a is pruned
+        assert_eq!(collect_dirs(root, &options).len(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_max_visited_dirs_skips_symlinked_duplicates() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        for link in ["l1", "l2", "l3"] {
+            std::os::unix::fs::symlink(root, root.join(link)).unwrap();
+        }
+
+        // The links lead back to root and don't use up the limit
+        let options = WalkOptions::new()
+            .with_follow_symlinks(true)
+            .with_max_visited_dirs(3);
+        let mut paths = collect_dirs(root, &options);
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![root.to_path_buf(), root.join("a"), root.join("a/b")]
+        );
+    }
+}
@@ -5,16 +5,43 @@
 //! This library provides functionality to discover, parse, and analyze package dependencies
 //! across different package management systems.
 
+#[cfg(feature = "native")]
 pub mod analyzer;
+#[cfg(feature = "native")]
+pub mod cancel;
+#[cfg(feature = "native")]
+pub mod checkpoint;
+#[cfg(feature = "native")]
+pub mod containers;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "native")]
 pub mod indexer;
+#[cfg(feature = "native")]
+pub mod logging;
 pub mod models;
+#[cfg(feature = "native")]
 pub mod output;
 pub mod parsers;
+#[cfg(feature = "native")]
+pub mod progress;
+#[cfg(feature = "native")]
+pub mod scan;
+#[cfg(feature = "native")]
+pub mod throttle;
+#[cfg(feature = "native")]
+pub mod unblock;
 pub mod version;
 
 // Re-export commonly used types
+#[cfg(feature = "native")]
+pub use cancel::CancellationToken;
 pub use models::{DependencyRecord, DependencyType, Ecosystem, FileType, ScanResult};
 pub use parsers::{Parser, ParserRegistry};
+#[cfg(feature = "native")]
+pub use progress::{ProgressReporter, ScanPhase};
+#[cfg(feature = "native")]
+pub use scan::{ScanLimits, ScanOptions, ScanOutput, Scanner};
 
 /// Result type for scanner operations
 pub type Result<T> = std::result::Result<T, models::ScanError>;
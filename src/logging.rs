@@ -0,0 +1,142 @@
+//! Structured logging setup
+//!
+//! The library emits diagnostics through [`tracing`] events; targets follow the
+//! module path (e.g. `scanner::scan`, `scanner::indexer::walk`,
+//! `scanner::analyzer::tree_builder`), so embedders can subscribe with their
+//! own subscriber and filter per module. Nothing is printed unless a
+//! subscriber is installed.
+//!
+//! [`init`] installs a stderr subscriber for the CLI (or for embedders that
+//! don't need anything custom), with human-readable or JSON lines output and
+//! `RUST_LOG`-style filter directives.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use scanner::logging::{self, LogConfig, LogFormat};
+//!
+//! let config = LogConfig::new()
+//!     .with_format(LogFormat::Json)
+//!     .with_filter("warn,scanner::scan=debug");
+//! logging::init(&config).unwrap();
+//! ```
+
+use crate::models::ScanError;
+use std::io::{self, IsTerminal};
+use std::str::FromStr;
+use tracing_subscriber::EnvFilter;
+
+/// Default filter when none is configured
+pub const DEFAULT_FILTER: &str = "warn";
+
+/// Log line format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = ScanError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(ScanError::InvalidLogConfig(format!(
+                "unknown log format '{}', use: text or json",
+                s
+            ))),
+        }
+    }
+}
+
+/// Logging configuration
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogConfig {
+    /// Output format
+    pub format: LogFormat,
+
+    /// Filter directives such as `info` or `warn,scanner::parsers=debug`
+    ///
+    /// Falls back to `RUST_LOG`, then [`DEFAULT_FILTER`], when unset.
+    pub filter: Option<String>,
+}
+
+impl LogConfig {
+    /// Create a text-format configuration with the default filter
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the output format
+    pub fn with_format(mut self, format: LogFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Set the filter directives
+    pub fn with_filter(mut self, filter: impl Into<String>) -> Self {
+        self.filter = Some(filter.into());
+        self
+    }
+
+    /// Build the event filter for this configuration
+    pub fn env_filter(&self) -> Result<EnvFilter, ScanError> {
+        let directives = match &self.filter {
+            Some(filter) => filter.clone(),
+            None => {
+                std::env::var(EnvFilter::DEFAULT_ENV).unwrap_or_else(|_| DEFAULT_FILTER.to_string())
+            }
+        };
+
+        EnvFilter::try_new(&directives).map_err(|e| {
+            ScanError::InvalidLogConfig(format!("invalid filter '{}': {}", directives, e))
+        })
+    }
+}
+
+/// Install a global subscriber writing to stderr
+///
+/// Fails if the filter is invalid or a global subscriber is already installed.
+pub fn init(config: &LogConfig) -> Result<(), ScanError> {
+    let filter = config.env_filter()?;
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(io::stderr)
+        .with_ansi(io::stderr().is_terminal());
+
+    let result = match config.format {
+        LogFormat::Text => builder.try_init(),
+        LogFormat::Json => builder.json().flatten_event(true).try_init(),
+    };
+
+    result.map_err(|e| ScanError::InvalidLogConfig(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_format() {
+        assert_eq!("text".parse::<LogFormat>().unwrap(), LogFormat::Text);
+        assert_eq!("json".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+
+    #[test]
+    fn test_env_filter_directives() {
+        let config = LogConfig::new().with_filter("warn,scanner::parsers=debug");
+        assert!(config.env_filter().is_ok());
+
+        let config = LogConfig::new().with_filter("scanner=notalevel");
+        assert!(matches!(
+            config.env_filter(),
+            Err(ScanError::InvalidLogConfig(_))
+        ));
+    }
+}
@@ -2,33 +2,113 @@
 //!
 //! A multi-language dependency scanner for Python, Node.js, and Rust ecosystems.
 
-use std::io;
-use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
-use clap::Parser;
-use rayon::prelude::*;
+use clap::{Parser, Subcommand};
+use tracing::{debug, error, warn};
 
 use scanner::analyzer::{
-    ApplicationLinker, Classifier, InfectedPackageFilter, TreeBuilder, VersionMatcher,
+    AppStatsAnalyzer, ApplicationLinker, BundleDetector, BundledPackage, ContentHasher,
+    DependencyConfusionDetector, ExitStatus, FailOn, HostComparer, InfectedPackageFilter,
+    InventoryMerger, InventorySource, OverrideChecker, Policy, ProcessCorrelator,
+    ReachabilityAnalyzer, RegistryClient, RegistryConfig, ReverseDependencyFinder, SecurityStatus,
+    Severity, Staleness, StalenessChecker, Summarizer, Suppressions, TreeBuilder, TreeDiffer,
+    TyposquatDetector,
 };
-use scanner::indexer;
-use scanner::models::{Ecosystem, InstalledPackage, ScanResult};
+use scanner::containers::{self, ContainerLayer, ContainerRuntime};
+use scanner::indexer::{self, detect_ecosystems, list_index, ListedKind, ListedPath};
+use scanner::logging::{self, LogConfig, LogFormat};
+#[cfg(feature = "sqlite")]
+use scanner::models::scan_metadata::rfc3339;
+use scanner::models::{
+    Application, Classification, ClassifiedDependency, ContainerRef, DependencyTree, Ecosystem,
+    ScanError, ScanMetadata,
+};
+#[cfg(feature = "parquet")]
+use scanner::output::write_classified_parquet;
+#[cfg(feature = "sqlite")]
+use scanner::output::HistoryDb;
 use scanner::output::{
-    write_applications_json_with_security, write_classified_csv_with_security,
-    write_trees_json_with_security,
+    app_stats_path, build_report, bundled_path, default_columns, engines_path, errors_path,
+    format_github_annotations, format_host_comparison_json, format_host_comparison_text,
+    format_listing_text, format_summary_table, format_tree_diff_json, format_tree_diff_text,
+    format_trees_text, inconsistencies_path, lockfile_drift_path, multiple_versions_path,
+    overrides_path, parse_columns, parse_header, parse_syslog_fields, peer_conflicts_path,
+    push_report, read_baseline_csv, read_classified_csv, read_header_file, read_results_json,
+    read_results_json_with_metadata, sidecar_path, staleness_path, summary_path,
+    write_applications_json_with_security, write_classified_csv_with_columns,
+    write_host_comparison_json, write_inventory_csv, write_inventory_json, write_listing_csv,
+    write_listing_json, write_markdown_summary, write_split_applications_json, write_split_csv,
+    write_split_trees_json, write_tree_diff_json, write_trees_graph,
+    write_trees_json_with_security, Baseline, CsvColumn, CsvOutput, DetectionDocument, Findings,
+    GraphFormat, JsonOutput, JsonResults, OutputWriter, RecordsDocument, ReportServer, ScanResults,
+    SchemaKind, SplitBy, SyslogFacility, SyslogOutput, TreeCharset, SCHEMA_VERSION,
+};
+#[cfg(feature = "siem")]
+use scanner::output::{OutputWriters, SinkConfig};
+use scanner::parsers::manifest::RequirementsMode;
+use scanner::parsers::{read_text, ParserRegistry};
+use scanner::progress::{
+    format_duration, NoProgress, ProgressReporter, ScanPhase, TerminalProgress,
 };
-use scanner::parsers::lockfile::*;
-use scanner::parsers::manifest::*;
-use scanner::parsers::{NodeModulesParser, ParserRegistry, SitePackagesParser};
+use scanner::throttle::{self, IoThrottle};
+use scanner::version::{NodeVersion, PythonVersion};
+use scanner::{CancellationToken, ScanLimits, ScanOptions, ScanOutput, Scanner};
+
+/// Log filter used for --verbose
+const VERBOSE_FILTER: &str = "warn,scanner=debug";
+
+/// Number of parse errors listed on the terminal
+const MAX_LISTED_ERRORS: usize = 5;
+
+/// Largest size in MiB whose byte count fits in a u64
+const MAX_MIB: u64 = u64::MAX >> 20;
 
 /// Command line arguments for the scanner
 #[derive(Parser, Debug)]
 #[command(author, version, about = "Multi-language dependency scanner", long_about = None)]
 struct Args {
-    /// Directory to start scanning from
-    #[arg(short, long, default_value = ".")]
-    dir: String,
+    /// Directory to start scanning from (default: .); repeat to scan several roots concurrently into one result set
+    #[arg(short, long)]
+    dir: Vec<String>,
+
+    /// Also scan the directories listed in this file, one per line (`#` starts a comment, - for stdin)
+    #[arg(long)]
+    dirs_from: Option<String>,
+
+    /// Parse the package files and installation directories listed in this file, one per line, instead of walking the directory (- for stdin)
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["dirs_from", "changed_since", "git_rev"])]
+    paths_from: Option<String>,
+
+    /// Only scan the applications whose manifests or lockfiles changed since this git revision (e.g. origin/main)
+    #[arg(long, value_name = "REV")]
+    changed_since: Option<String>,
+
+    /// Read manifests and lockfiles from this git revision instead of the working tree, without checking it out (declared dependencies only)
+    #[arg(long, value_name = "REV", conflicts_with = "changed_since")]
+    git_rev: Option<String>,
+
+    /// Only discover: list the manifests, lockfiles and installation directories that would be parsed, with their sizes, without parsing them
+    #[arg(long, conflicts_with = "git_rev")]
+    dry_run: bool,
+
+    /// Scan a target other than a directory: archive:<PATH> reads the manifests and lockfiles of a tar (optionally gzipped) or zip filesystem snapshot in place, without extracting it (declared dependencies only)
+    #[arg(long, value_name = "KIND:PATH", conflicts_with_all = ["dir", "dirs_from", "paths_from", "changed_since", "git_rev", "dry_run"])]
+    target: Option<String>,
+
+    /// Scan the filesystems of the running containers of a runtime instead of a directory: docker or containerd, optionally followed by :<socket>; repeat for several runtimes. Entries are tagged with their container's ID, image and Kubernetes pod
+    #[arg(long, value_name = "RUNTIME[:SOCKET]", conflicts_with_all = ["dir", "dirs_from", "paths_from", "changed_since", "git_rev", "target"])]
+    containers: Vec<String>,
+
+    /// Directory of each container scanned with --containers: merged (its whole filesystem) or upper (only what was written since it started)
+    #[arg(long, value_name = "LAYER", default_value = "merged")]
+    container_layer: String,
 
     /// Number of worker threads to use
     #[arg(short = 'j', long, default_value_t = num_cpus::get())]
@@ -38,14 +118,26 @@ struct Args {
     #[arg(short, long)]
     verbose: bool,
 
-    /// Filter by ecosystem (node, python, rust)
+    /// Log format: text, json
+    #[arg(long, default_value = "text")]
+    log_format: String,
+
+    /// Log filter directives, e.g. "info" or "warn,scanner::parsers=debug" (overrides --verbose and RUST_LOG)
     #[arg(long)]
-    ecosystem: Option<String>,
+    log_level: Option<String>,
+
+    /// Filter by ecosystem (node, python, rust); comma-separated or repeated
+    #[arg(long, value_delimiter = ',')]
+    ecosystem: Vec<String>,
 
     /// Scan mode: full, installed-only, declared-only
     #[arg(long, default_value = "full")]
     scan_mode: String,
 
+    /// Parse only lockfiles, skipping installation directories and manifests; applications are only linked with --apps-from-manifests
+    #[arg(long, conflicts_with = "scan_mode")]
+    lockfiles_only: bool,
+
     /// Output format: csv, json
     #[arg(long, default_value = "csv")]
     format: String,
@@ -54,361 +146,2403 @@ struct Args {
     #[arg(long)]
     include_install_dirs: bool,
 
-    /// Infected package list file (CSV format: package,version1 | version2)
+    /// Follow symlinked directories (with cycle detection)
+    #[arg(long)]
+    follow_symlinks: bool,
+
+    /// Maximum number of directories to visit per traversal
+    #[arg(long)]
+    max_visited_dirs: Option<usize>,
+
+    /// Maximum directory depth below the scan root
+    #[arg(long)]
+    max_depth: Option<usize>,
+
+    /// Skip manifests and lockfiles larger than this many MiB, reporting them as too large
+    #[arg(long, value_name = "MIB", value_parser = clap::value_parser!(u64).range(..=MAX_MIB))]
+    max_file_size: Option<u64>,
+
+    /// Parse at most this many manifests and lockfiles; the results are marked truncated
+    #[arg(long)]
+    max_files: Option<usize>,
+
+    /// Parse at most this many installation directories; the results are marked truncated
+    #[arg(long)]
+    max_install_dirs: Option<usize>,
+
+    /// Keep at most this many packages per installation directory; the results are marked truncated
+    #[arg(long)]
+    max_packages_per_dir: Option<usize>,
+
+    /// Stop walking and parsing after this many seconds and report what was found; the results are marked truncated
+    #[arg(long)]
+    time_budget: Option<u64>,
+
+    /// Read at most this many directories and package files per second, to spare the disks of production hosts
+    #[arg(long, value_name = "N")]
+    io_max_files: Option<u64>,
+
+    /// Read at most this many MiB of package files per second
+    #[arg(long, value_name = "MIB", value_parser = clap::value_parser!(u64).range(..=MAX_MIB))]
+    io_max_mib: Option<u64>,
+
+    /// Run the scan at this niceness (0-19, Unix only); on Linux it also lowers the I/O priority
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(i32).range(0..=19))]
+    io_nice: Option<i32>,
+
+    /// Save the progress of the scan to this file, so an interrupted scan can be resumed; removed once the scan completes
+    #[arg(long, value_name = "FILE")]
+    checkpoint: Option<PathBuf>,
+
+    /// Resume from the --checkpoint file, skipping the files and installation directories it already holds
+    #[arg(long, requires = "checkpoint")]
+    resume: bool,
+
+    /// Give up on a manifest or lockfile whose parser runs longer than this many seconds; each parse then runs on a thread of its own (default: no timeout)
+    #[arg(long, default_value_t = 0, hide_default_value = true)]
+    parse_timeout: u64,
+
+    /// Only scan paths matching this glob, relative to the scan root (repeatable)
+    #[arg(long = "include-glob")]
+    include_globs: Vec<String>,
+
+    /// Skip paths matching this glob, relative to the scan root (repeatable)
+    #[arg(long = "exclude-glob")]
+    exclude_globs: Vec<String>,
+
+    /// Disable the progress bar (shown only when stderr is a terminal)
+    #[arg(long)]
+    no_progress: bool,
+
+    /// Merge HAS/SHOULD/CAN entries of the same package and application into one row
+    #[arg(long)]
+    aggregate: bool,
+
+    /// Create applications from discovered manifests, attaching locked and declared entries too
+    #[arg(long)]
+    apps_from_manifests: bool,
+
+    /// Read requirements.txt and constraints.txt as: auto (pinned or compiled files are lockfiles), manifest, lockfile
+    #[arg(long, default_value = "auto")]
+    requirements_as: String,
+
+    /// Fail the scan if any package file or installation directory fails to parse
+    #[arg(long)]
+    strict: bool,
+
+    /// Exit with a failure code when this finding occurs (infected, match-version, mismatch; repeatable)
+    #[arg(long = "fail-on", value_delimiter = ',')]
+    fail_on: Vec<String>,
+
+    /// Accepted findings that no longer fail the scan or get a security status (JSON/YAML: package, version, path, ecosystem, reason, expires)
+    #[arg(long)]
+    suppressions: Option<String>,
+
+    /// Write the scan's infected, match-version and mismatch findings as a suppression file accepting them, for later scans to pass to --suppressions
+    #[arg(long, value_name = "FILE")]
+    write_baseline: Option<String>,
+
+    /// Infected package list file (CSV: package,version1 | version2; or JSON/YAML with ranges)
     #[arg(long)]
     infected_list: Option<String>,
 
+    /// Ignore infected list entries rated below this severity (low, medium, high, critical)
+    #[arg(long, requires = "infected_list")]
+    min_severity: Option<String>,
+
+    /// Warn about dependency names that closely resemble popular packages
+    #[arg(long)]
+    check_typosquats: bool,
+
+    /// Additional popular packages for --check-typosquats (lines of ecosystem,package)
+    #[arg(long, requires = "check_typosquats")]
+    popular_packages: Option<String>,
+
+    /// Internal package namespace as a glob, e.g. "@acme/*" or "acme-*" (repeatable);
+    /// matching packages resolved from a public registry are reported
+    #[arg(long = "internal-package", value_delimiter = ',')]
+    internal_packages: Vec<String>,
+
+    /// Write dependency counts, duplicate packages and heaviest subtrees of each application next to the results
+    #[arg(long)]
+    app_stats: bool,
+
+    /// Write packages copied into vendor/, third_party/ or Python _vendor directories, or built into JavaScript bundles, next to the results
+    #[arg(long)]
+    detect_bundled: bool,
+
+    /// Report how far installed and locked versions are behind their latest releases on npm, PyPI and crates.io
+    #[arg(long)]
+    check_staleness: bool,
+
+    /// Only use cached registry metadata, never the network
+    #[arg(long)]
+    offline: bool,
+
+    /// Directory caching registry metadata for a day (default: ~/.cache/scanner/registry)
+    #[arg(long)]
+    registry_cache: Option<String>,
+
+    /// Registries to use instead of the public ones (JSON/YAML by ecosystem: url, token, username, password; ${VAR} is expanded)
+    #[arg(long)]
+    registry_config: Option<String>,
+
+    /// Don't take registries and credentials from .npmrc, PIP_INDEX_URL or pip.conf
+    #[arg(long)]
+    no_registry_discovery: bool,
+
+    /// Also take the npm registry from the .npmrc of the first scan root (only for trusted trees; ${VAR} is not expanded)
+    #[arg(long, conflicts_with = "no_registry_discovery")]
+    project_npmrc: bool,
+
+    /// Rehash installed Python packages' files against their RECORD and report modified ones
+    #[arg(long)]
+    verify_hashes: bool,
+
+    /// Mark installed packages loaded by running processes, from /proc (Linux only)
+    #[arg(long)]
+    correlate_processes: bool,
+
+    /// Hash the files of each installed package (content_hash) to compare installs across hosts
+    #[arg(long)]
+    content_hashes: bool,
+
+    /// Mark installed packages the application's code never imports, following its entry points (Node)
+    #[arg(long)]
+    reachability: bool,
+
+    /// Also report OS packages (dpkg, rpm, Homebrew) from the package databases under a system root (--os-packages=/mnt/image; default /)
+    #[arg(long, value_name = "ROOT", num_args = 0..=1, require_equals = true, default_missing_value = "/")]
+    os_packages: Option<String>,
+
+    /// Flag installed npm packages whose engines.node excludes this Node version (e.g. 18.19.0)
+    #[arg(long = "node", value_name = "VERSION")]
+    node_version: Option<String>,
+
+    /// Flag installed Python packages whose Requires-Python excludes this Python version (e.g. 3.11)
+    #[arg(long = "python", value_name = "VERSION")]
+    python_version: Option<String>,
+
+    /// Load a parser plugin from a shared library (repeatable)
+    #[cfg(feature = "dynamic-plugins")]
+    #[arg(long = "plugin")]
+    plugins: Vec<String>,
+
     /// Output file path
     #[arg(short, long)]
     output: Option<String>,
+
+    /// CSV columns to write, in order (default: the full layout); also license, native_extensions, entry_points
+    #[arg(long, value_name = "NAMES", value_delimiter = ',')]
+    columns: Vec<String>,
+
+    /// Also write the results as one file per group into a directory: by-application, by-ecosystem
+    #[arg(long, value_name = "MODE")]
+    split_output: Option<String>,
+
+    /// Directory of the --split-output files (default: the output file path without its extension)
+    #[arg(long, value_name = "DIR", requires = "split_output")]
+    split_dir: Option<String>,
+
+    /// Print the JSON Schema of an output document and exit (applications, trees, summary, errors, inconsistencies, overrides, multiple-versions, peer-conflicts, engines, lockfile-drift, staleness, bundled, app-stats, report, records, detection, listing, inventory, tree-diff, host-comparison)
+    #[arg(long)]
+    print_schema: Option<String>,
+
+    /// POST the results as JSON to a collector URL after the scan
+    #[arg(long)]
+    push: Option<String>,
+
+    /// Header sent with --push, as "Name: value" (repeatable; visible to local users in the process list, use --push-header-file for credentials)
+    #[arg(long = "push-header", requires = "push")]
+    push_headers: Vec<String>,
+
+    /// File of headers sent with --push, one "Name: value" per line (${VAR} is expanded)
+    #[arg(long, value_name = "PATH", requires = "push")]
+    push_header_file: Option<String>,
+
+    /// Serve the results over HTTP on an address (e.g. 0.0.0.0:8080) until stopped
+    #[arg(long)]
+    serve: Option<String>,
+
+    /// Bearer token required by --serve (prefer the SCANNER_SERVE_TOKEN environment variable, which stays out of the process list)
+    #[arg(
+        long,
+        env = "SCANNER_SERVE_TOKEN",
+        hide_env_values = true,
+        requires = "serve"
+    )]
+    serve_token: Option<String>,
+
+    /// Also export dependency trees as a graph (.dot or .graphml, by extension)
+    #[arg(long)]
+    graph: Option<String>,
+
+    /// Limit dependency trees (JSON output and --graph) to this depth; direct dependencies are depth 1
+    #[arg(long)]
+    tree_depth: Option<usize>,
+
+    /// Expand each package once per dependency tree; repeats are marked as duplicates
+    #[arg(long)]
+    tree_dedupe: bool,
+
+    /// Keep only dependency tree branches leading to flagged packages (infected, phantom, mismatched, ...)
+    #[arg(long)]
+    tree_flagged_only: bool,
+
+    /// Also write a Markdown summary for pull request comments to this path
+    #[arg(long)]
+    markdown: Option<String>,
+
+    /// Print findings as GitHub Actions workflow commands, annotating the offending lockfile and manifest lines
+    #[arg(long)]
+    github_annotations: bool,
+
+    /// Also send each security finding as an event to syslog, journald, udp://host[:port] or a syslog socket path
+    #[arg(long, value_name = "TARGET")]
+    syslog: Option<String>,
+
+    /// Facility of the --syslog events: user, daemon, auth, authpriv, local0 to local7
+    #[arg(long, value_name = "NAME", default_value = "user")]
+    syslog_facility: String,
+
+    /// Fields of the --syslog events, in order (default: all): package, version, ecosystem, status, severity, cvss, advisories, recommended_version, purl, application, path, host
+    #[arg(long, value_name = "NAMES", value_delimiter = ',', requires = "syslog")]
+    syslog_fields: Vec<String>,
+
+    /// Post the security findings to the Splunk HEC or Elasticsearch sinks of this file (JSON/YAML: splunk, elasticsearch; ${VAR} is expanded)
+    #[cfg(feature = "siem")]
+    #[arg(long, value_name = "FILE")]
+    sink_config: Option<String>,
+
+    /// Previous CSV results; dependencies missing from it are listed as new in the Markdown summary
+    #[arg(long, value_name = "FILE", requires = "markdown")]
+    markdown_baseline: Option<String>,
+
+    /// Also write the results as Parquet to this path, for data lake ingestion
+    #[cfg(feature = "parquet")]
+    #[arg(long)]
+    parquet: Option<String>,
+
+    /// Append the scan and its findings to this SQLite history database (queried with `history`)
+    #[cfg(feature = "sqlite")]
+    #[arg(long)]
+    history_db: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Queries run on the scan results instead of writing them
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Show the dependency paths from each application to a package
+    Why {
+        /// Package name
+        package: String,
+    },
+    /// Print each application's dependency tree (honors the --tree-* options)
+    Tree {
+        /// Only show branches leading to this package
+        #[arg(long)]
+        filter: Option<String>,
+
+        /// Only show applications of this ecosystem (node, python, rust)
+        #[arg(long)]
+        ecosystem: Option<String>,
+
+        /// Characters used to draw branches: utf8, ascii
+        #[arg(long, default_value = "utf8")]
+        charset: String,
+    },
+    /// Recompute security statuses of earlier results with --infected-list, without rescanning
+    Refilter {
+        /// Results of an earlier scan (JSON if the name ends in .json, CSV otherwise)
+        results: String,
+    },
+    /// Report the ecosystems and package managers present, without parsing files
+    Detect {
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Merge the results of several scans into one deduplicated inventory, attributing each package to the results it was found in
+    Merge {
+        /// Results files (JSON if the name ends in .json, CSV otherwise)
+        #[arg(required = true)]
+        results: Vec<String>,
+    },
+    /// Compare the dependency trees of two JSON results, reporting which packages' changes added or removed subtrees
+    Diff {
+        /// Results of the earlier scan (trees JSON of a full scan)
+        old: String,
+
+        /// Results of the later scan
+        new: String,
+
+        /// Print the differences as JSON (to --output if given)
+        #[arg(long)]
+        json: bool,
+    },
+    /// Compare the results of two hosts by application, reporting package versions present on one host only
+    CompareHosts {
+        /// Results of the first host (JSON if the name ends in .json, CSV otherwise)
+        first: String,
+
+        /// Results of the second host
+        second: String,
+
+        /// Print the differences as JSON (to --output if given)
+        #[arg(long)]
+        json: bool,
+    },
+    /// Parse one manifest or lockfile and print its dependency records as JSON
+    ParseFile {
+        /// Package file, or - for stdin
+        path: String,
+
+        /// Parse as this file name (e.g. package-lock.json) instead of detecting the type
+        #[arg(long = "as")]
+        parse_as: Option<String>,
+    },
+    /// Query the scan history recorded with --history-db
+    #[cfg(feature = "sqlite")]
+    History {
+        #[command(subcommand)]
+        query: HistoryQuery,
+    },
+}
+
+/// Trend queries on the scan history
+#[cfg(feature = "sqlite")]
+#[derive(Subcommand, Debug)]
+enum HistoryQuery {
+    /// List the recorded scans
+    Scans,
+    /// List package versions first seen since a date
+    New {
+        /// Date (YYYY-MM-DD) or number of days ago (e.g. 7d)
+        #[arg(long, default_value = "7d")]
+        since: String,
+    },
+    /// Show when each version of a package was first and last seen
+    FirstSeen {
+        /// Package name (all packages if omitted)
+        package: Option<String>,
+
+        /// Only count scans that found the version infected
+        #[arg(long)]
+        infected: bool,
+    },
 }
 
-fn main() -> io::Result<()> {
+fn main() -> ExitCode {
     let args = Args::parse();
 
-    // Configure thread pool
-    rayon::ThreadPoolBuilder::new()
-        .num_threads(args.jobs)
-        .build_global()
-        .unwrap();
+    match run(args) {
+        Ok(status) | Err(Abort::Exit(status)) => ExitCode::from(status.code()),
+        Err(Abort::Io(e)) => {
+            error!("Failed to write results: {}", e);
+            ExitCode::from(ExitStatus::ScanErrors.code())
+        }
+    }
+}
+
+/// Options whose values are credentials and are left out of the scan metadata
+const SECRET_OPTIONS: &[&str] = &["--push-header", "--serve-token"];
 
-    if args.verbose {
-        eprintln!("[debug] Using {} threads", args.jobs);
-        eprintln!("[debug] Scan mode: {}", args.scan_mode);
-        eprintln!("[debug] Output format: {}", args.format);
+/// Command line arguments for the scan metadata, with credentials redacted
+fn redacted_arguments() -> Vec<String> {
+    let mut arguments = Vec::new();
+    let mut redact_next = false;
+    for argument in std::env::args() {
+        if redact_next {
+            arguments.push("<redacted>".to_string());
+            redact_next = false;
+        } else if let Some((option, _)) = argument
+            .split_once('=')
+            .filter(|(option, _)| SECRET_OPTIONS.contains(option))
+        {
+            arguments.push(format!("{}=<redacted>", option));
+        } else {
+            redact_next = SECRET_OPTIONS.contains(&argument.as_str());
+            arguments.push(argument);
+        }
     }
+    arguments
+}
 
-    println!("Scanning for dependencies across Python, Node.js, and Rust ecosystems...");
+/// Read a list of paths, one per line, from a file or stdin (`-`); empty
+/// lines and lines starting with `#` are skipped
+fn read_path_list(list: &str) -> Result<Vec<PathBuf>, scanner::models::ScanError> {
+    let content = if list == "-" {
+        let mut content = String::new();
+        io::stdin().read_to_string(&mut content)?;
+        content
+    } else {
+        read_text(list)?
+    };
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(PathBuf::from)
+        .collect())
+}
 
-    let scan_path = Path::new(&args.dir);
-    if !scan_path.exists() {
-        eprintln!("[error] Directory does not exist: {}", args.dir);
-        return Ok(());
+/// Register the parsers of plugin libraries with a scanner
+#[cfg(feature = "dynamic-plugins")]
+fn load_plugins(
+    mut scanner: Scanner,
+    paths: &[String],
+) -> Result<Scanner, scanner::models::ScanError> {
+    for path in paths {
+        let name = scanner.registry_mut().load_plugin(Path::new(path))?;
+        debug!(plugin = %name, path = %path, "Loaded parser plugin");
     }
+    Ok(scanner)
+}
 
-    // Determine scan mode
-    let scan_installed = args.scan_mode == "full" || args.scan_mode == "installed-only";
-    let scan_declared = args.scan_mode == "full" || args.scan_mode == "declared-only";
+/// Stop the scan on Ctrl+C, keeping what it found
+///
+/// The first interrupt cancels the scan, which still writes its partial
+/// results; a second one, or any once `finished` is set, exits at once.
+fn handle_interrupts(cancellation: CancellationToken, finished: Arc<AtomicBool>) {
+    let result = ctrlc::set_handler(move || {
+        if cancellation.is_cancelled() || finished.load(Ordering::Relaxed) {
+            std::process::exit(130);
+        }
+        cancellation.cancel();
+        warn!("Interrupted, writing partial results (press Ctrl+C again to abort)");
+    });
+    if let Err(e) = result {
+        warn!("Cannot handle Ctrl+C: {}", e);
+    }
+}
 
-    if !scan_installed && !scan_declared {
-        eprintln!(
-            "[error] Invalid scan mode: {}. Use: full, installed-only, or declared-only",
-            args.scan_mode
-        );
-        return Ok(());
+/// Tree builder configured with the tree options from the command line
+fn tree_builder(args: &Args) -> TreeBuilder {
+    let mut builder = TreeBuilder::new()
+        .with_dedupe(args.tree_dedupe)
+        .with_flagged_only(args.tree_flagged_only);
+    if let Some(depth) = args.tree_depth {
+        builder = builder.with_max_depth(depth);
     }
+    builder
+}
 
-    // Validate output format
-    if args.format != "csv" && args.format != "json" {
-        eprintln!("[error] Invalid format: {}. Use: csv or json", args.format);
-        return Ok(());
+/// Syslog sink for `--syslog`, with the facility and fields from the command line
+fn syslog_output(args: &Args, target: &str) -> Result<SyslogOutput, scanner::models::ScanError> {
+    let mut output = SyslogOutput::new(target.parse()?)
+        .with_facility(args.syslog_facility.parse::<SyslogFacility>()?);
+    if !args.syslog_fields.is_empty() {
+        output = output.with_fields(parse_syslog_fields(&args.syslog_fields)?);
     }
+    Ok(output)
+}
 
-    // Determine output file
-    let output_file = args.output.unwrap_or_else(|| {
-        if args.format == "json" {
-            "output.json".to_string()
-        } else {
-            "output.csv".to_string()
+/// Build dependency trees, annotating entries so infected packages count as flagged
+fn build_trees(
+    builder: TreeBuilder,
+    applications: &[Application],
+    infected_filter: Option<&InfectedPackageFilter>,
+) -> Vec<DependencyTree> {
+    let mut applications = applications.to_vec();
+    if let Some(filter) = infected_filter {
+        for dep in applications
+            .iter_mut()
+            .flat_map(|app| &mut app.dependencies)
+        {
+            filter.annotate(dep);
         }
-    });
+    }
+    builder.build_trees(applications)
+}
 
-    // Initialize parser registry for declared dependencies
-    let mut registry = ParserRegistry::new();
-
-    if scan_declared {
-        // Register Node.js parsers
-        registry.register(Arc::new(PackageJsonParser));
-        registry.register(Arc::new(YarnLockParser));
-        registry.register(Arc::new(PackageLockJsonParser));
-        registry.register(Arc::new(PnpmLockParser));
-
-        // Register Python parsers
-        registry.register(Arc::new(PyprojectTomlParser));
-        registry.register(Arc::new(RequirementsTxtParser));
-        registry.register(Arc::new(PoetryLockParser));
-        registry.register(Arc::new(UvLockParser));
-
-        // Register Rust parsers
-        registry.register(Arc::new(CargoTomlParser));
-        registry.register(Arc::new(CargoLockParser));
-
-        if args.verbose {
-            eprintln!(
-                "[debug] Registered {} parsers",
-                registry.registered_filenames().len()
-            );
+/// Reason a run ends before the end of a scan
+enum Abort {
+    /// Exit with this status; the reason is already logged
+    Exit(ExitStatus),
+    /// Results could not be written
+    Io(io::Error),
+}
+
+impl From<io::Error> for Abort {
+    fn from(e: io::Error) -> Self {
+        Abort::Io(e)
+    }
+}
+
+/// Log why the run can't go on and abort it with a scan error
+fn abort(reason: impl std::fmt::Display) -> Abort {
+    error!("{}", reason);
+    Abort::Exit(ExitStatus::ScanErrors)
+}
+
+/// Options checked before anything is scanned
+struct Settings {
+    /// Archive of `--target archive:<path>`
+    archive: Option<PathBuf>,
+    scan_installed: bool,
+    scan_declared: bool,
+    split_by: Option<SplitBy>,
+    columns: Vec<&'static CsvColumn>,
+    syslog: Option<SyslogOutput>,
+    #[cfg(feature = "siem")]
+    sinks: Option<SinkConfig>,
+    /// Findings that fail the scan
+    policy: Policy,
+    suppressions: Suppressions,
+    min_severity: Option<Severity>,
+    /// Options of the tree subcommand
+    tree_ecosystem: Option<Ecosystem>,
+    tree_charset: TreeCharset,
+}
+
+/// Directories to scan, before `--changed-since` narrows them
+struct Roots {
+    dirs: Vec<PathBuf>,
+    /// Root directories of running containers, with their container
+    containers: Vec<(PathBuf, ContainerRef)>,
+    /// Files of `--paths-from`, indexed instead of walking the directory
+    paths: Option<Vec<PathBuf>>,
+}
+
+/// Results of a scan and of the checks run on them, ready to be written
+struct Outcome {
+    output: ScanOutput,
+    infected_filter: Option<InfectedPackageFilter>,
+    staleness: Vec<Staleness>,
+    bundled: Vec<BundledPackage>,
+}
+
+impl Outcome {
+    /// Dependencies and applications to write, with their security statuses
+    fn results(&self) -> ScanResults<'_> {
+        ScanResults {
+            applications: &self.output.applications,
+            dependencies: &self.output.classified,
+            infected: self.infected_filter.as_ref(),
+            metadata: Some(&self.output.metadata),
+            ..ScanResults::default()
         }
     }
+}
 
-    // Discover files
-    let mut exclude_dirs = vec![".nx", "target", ".git", "__pycache__"];
+/// Run the scan and determine the exit status from its findings
+fn run(mut args: Args) -> Result<ExitStatus, Abort> {
+    init_logging(&args)?;
 
-    // Conditionally exclude installation directories from declared dependency scanning
-    // Note: We still want to find manifests/lockfiles in venvs, so we only exclude
-    // the actual package directories (node_modules, site-packages)
-    if !args.include_install_dirs {
-        exclude_dirs.extend(vec!["node_modules", "site-packages", "dist-packages"]);
+    if let Some(kind) = &args.print_schema {
+        let kind = kind.parse::<SchemaKind>().map_err(abort)?;
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&kind.schema()).map_err(io::Error::from)?
+        );
+        return Ok(ExitStatus::Clean);
     }
 
-    let discovered_files = if scan_declared {
-        // Determine scan mode enum
-        let mode = match args.scan_mode.as_str() {
-            "full" => indexer::ScanMode::Full,
-            "installed-only" => indexer::ScanMode::InstalledOnly,
-            "declared-only" => indexer::ScanMode::DeclaredOnly,
-            _ => indexer::ScanMode::Full,
-        };
+    // Threads inherit the niceness, so set it before the scanner starts its
+    // pool
+    if let Some(niceness) = args.io_nice {
+        if let Err(e) = throttle::set_niceness(niceness) {
+            warn!("Failed to set niceness {}: {}", niceness, e);
+        }
+    }
+
+    debug!(threads = args.jobs, scan_mode = %args.scan_mode, format = %args.format, "Scan settings");
+
+    let mut settings = check_options(&mut args)?;
+    if let Some(status) = run_command(&args, &settings)? {
+        return Ok(status);
+    }
+
+    let detecting = matches!(args.command, Some(Command::Detect { .. })) || args.dry_run;
+    if !detecting {
+        println!("Scanning for dependencies across Python, Node.js, and Rust ecosystems...");
+    }
+
+    let Roots {
+        dirs: mut roots,
+        containers: container_roots,
+        paths,
+    } = resolve_roots(&args, settings.archive.as_deref())?;
+    let project = roots.first().filter(|_| args.project_npmrc);
+    let registries = registry_config(&args, project.map(PathBuf::as_path))?;
+    if let Some(since) = &args.changed_since {
+        roots = changed_roots(&roots, since)?;
+    }
+
+    let confusion_detector = DependencyConfusionDetector::new()
+        .with_internal_patterns(&args.internal_packages)
+        .map_err(abort)?;
+    // Read the baseline before the results can overwrite it
+    let baseline = match &args.markdown_baseline {
+        Some(path) => Some(
+            read_baseline_csv(path)
+                .map_err(|e| abort(format!("Failed to read baseline {}: {}", path, e)))?,
+        ),
+        None => None,
+    };
 
-        indexer::find_files_with_mode(scan_path, &exclude_dirs, mode, args.include_install_dirs)
+    let options = scan_options(&args, settings.archive.is_some(), &roots, paths)?;
+    // Show a progress bar when stderr is an interactive terminal
+    let progress: Arc<dyn ProgressReporter> = if !args.no_progress && io::stderr().is_terminal() {
+        Arc::new(TerminalProgress::new())
     } else {
-        vec![]
+        Arc::new(NoProgress)
     };
+    let cancellation = CancellationToken::new();
+    let scanner = Scanner::new(options)
+        .with_threads(args.jobs)
+        .with_progress(progress)
+        .with_cancellation(cancellation.clone());
+    #[cfg(feature = "dynamic-plugins")]
+    let scanner = load_plugins(scanner, &args.plugins)
+        .map_err(|e| abort(format!("Failed to load plugin: {}", e)))?;
+    if let Some(Command::Detect { json }) = &args.command {
+        let [root] = roots.as_slice() else {
+            return Err(abort("detect scans a single directory"));
+        };
+        return Ok(detect(&scanner, root, *json)?);
+    }
+    if args.dry_run {
+        return Ok(dry_run(
+            &scanner,
+            &roots,
+            &args.format,
+            args.output.as_deref(),
+        )?);
+    }
+
+    let finished = Arc::new(AtomicBool::new(false));
+    handle_interrupts(cancellation.clone(), finished.clone());
+    let mut output = scan(&scanner, &roots, &settings)?;
+    annotate(&args, &scanner, &container_roots, &mut output);
+
+    if let Some(Command::Why { package }) = &args.command {
+        print_why(&output.classified, package);
+        return Ok(ExitStatus::Clean);
+    }
 
-    if args.verbose {
-        eprintln!(
-            "[debug] Discovered {} manifest/lockfiles",
-            discovered_files.len()
+    let infected_filter = security_filter(&args, &settings, &output.classified)?;
+    if let Some(Command::Tree { filter, .. }) = &args.command {
+        let applications = std::mem::take(&mut output.applications);
+        print_trees(
+            &args,
+            &settings,
+            applications,
+            filter.as_deref(),
+            infected_filter.as_ref(),
         );
+        return Ok(ExitStatus::Clean);
     }
 
-    // Filter by ecosystem if specified
-    let discovered_files: Vec<_> = if let Some(ref eco) = args.ecosystem {
-        let filter_eco = match eco.as_str() {
-            "node" => Ecosystem::Node,
-            "python" => Ecosystem::Python,
-            "rust" => Ecosystem::Rust,
-            _ => {
-                eprintln!(
-                    "[error] Unknown ecosystem: {}. Use: node, python, or rust",
-                    eco
-                );
-                return Ok(());
-            }
-        };
-        discovered_files
-            .into_iter()
-            .filter(|f| f.ecosystem == filter_eco)
-            .collect()
+    if let Some(filter) = &infected_filter {
+        Summarizer::new().count_security_statuses(&mut output.summary, &output.classified, filter);
+        OverrideChecker::new().mark_infected(&mut output.overrides, filter);
+    }
+    report_checks(&args, &output);
+    if args.check_typosquats {
+        check_typosquats(&args, &output.classified)?;
+    }
+    if !confusion_detector.is_empty() {
+        check_confusion(&confusion_detector, &output.classified);
+    }
+    let staleness = if args.check_staleness {
+        check_staleness(&args, &registries, &scanner, &output.classified)
     } else {
-        discovered_files
+        Vec::new()
     };
-
-    // Parse declared dependencies
-    let dependency_records = if scan_declared {
-        println!("Found {} package files to parse", discovered_files.len());
-        let scan_result = Arc::new(Mutex::new(ScanResult::new()));
-
-        discovered_files.par_iter().for_each(|file| {
-            if let Some(parser) = registry.get_parser(&file.filename) {
-                match std::fs::read_to_string(&file.path) {
-                    Ok(content) => match parser.parse(&content, &file.path) {
-                        Ok(records) => {
-                            if args.verbose && !records.is_empty() {
-                                eprintln!(
-                                    "[debug] Parsed {} dependencies from {:?}",
-                                    records.len(),
-                                    file.path
-                                );
-                            }
-                            scan_result.lock().unwrap().add_all(records);
-                        }
-                        Err(e) => {
-                            eprintln!("[warn] Failed to parse {:?}: {}", file.path, e);
-                        }
-                    },
-                    Err(e) => {
-                        eprintln!("[warn] Failed to read {:?}: {}", file.path, e);
-                    }
-                }
-            }
-        });
-
-        let result = Arc::try_unwrap(scan_result).unwrap().into_inner().unwrap();
-        result.dependencies
+    let bundled = if args.detect_bundled {
+        BundleDetector::new().detect(&roots)
     } else {
-        vec![]
+        Vec::new()
     };
+    let mut outcome = Outcome {
+        output,
+        infected_filter,
+        staleness,
+        bundled,
+    };
+
+    println!("\nScan complete!");
+    println!(
+        "Total unique dependencies: {}",
+        outcome.output.classified.len()
+    );
+    println!("Applications found: {}", outcome.output.applications.len());
 
-    // Scan for installed packages
-    let installed_packages = if scan_installed {
-        println!("Scanning for installed packages...");
-        let installed = Arc::new(Mutex::new(Vec::<InstalledPackage>::new()));
+    write_results(&args, &settings, &scanner, &mut outcome, baseline.as_ref())?;
+    if let Some(syslog) = &mut settings.syslog {
+        send_syslog(syslog, &outcome)?;
+    }
+    #[cfg(feature = "siem")]
+    if let Some(sinks) = &settings.sinks {
+        send_to_sinks(&args, sinks, &outcome)?;
+    }
 
-        // Find installation directories
-        let install_dirs = indexer::install_dirs::find_all_install_dirs(scan_path, &[]);
+    // Results are written; from here on Ctrl+C stops the process
+    finished.store(true, Ordering::Relaxed);
 
-        if args.verbose {
-            eprintln!(
-                "[debug] Found {} installation directories",
-                install_dirs.len()
-            );
+    if args.push.is_some() || args.serve.is_some() {
+        publish_report(&args, &outcome)?;
+    }
+
+    Ok(exit_status(&args, &settings, &outcome, &cancellation))
+}
+
+/// Configure logging before anything else can emit diagnostics
+fn init_logging(args: &Args) -> Result<(), Abort> {
+    let log_format = args.log_format.parse::<LogFormat>().map_err(|e| {
+        eprintln!("[error] {}", e);
+        Abort::Exit(ExitStatus::ScanErrors)
+    })?;
+    let mut log_config = LogConfig::new().with_format(log_format);
+    if let Some(filter) = &args.log_level {
+        log_config = log_config.with_filter(filter.clone());
+    } else if args.verbose {
+        log_config = log_config.with_filter(VERBOSE_FILTER);
+    }
+    logging::init(&log_config).map_err(|e| {
+        eprintln!("[error] {}", e);
+        Abort::Exit(ExitStatus::ScanErrors)
+    })
+}
+
+/// Check the options of the run and derive its settings
+fn check_options(args: &mut Args) -> Result<Settings, Abort> {
+    let archive = match &args.target {
+        Some(target) => match target.strip_prefix("archive:") {
+            Some(path) if !path.is_empty() => Some(PathBuf::from(path)),
+            _ => {
+                return Err(abort(format!(
+                    "Invalid target: {}. Use: archive:<path>",
+                    target
+                )))
+            }
+        },
+        None => None,
+    };
+
+    // A git revision holds manifests and lockfiles, never installed packages
+    if args.git_rev.is_some() {
+        if args.scan_mode == "installed-only" {
+            return Err(abort("--git-rev only scans declared dependencies"));
+        }
+        args.scan_mode = "declared-only".to_string();
+    }
+    // Installed packages are read from disk, not from archives
+    if archive.is_some() {
+        if args.scan_mode == "installed-only" {
+            return Err(abort("--target archive: only scans declared dependencies"));
         }
+        args.scan_mode = "declared-only".to_string();
+    }
 
-        // Parse installed packages in parallel
-        install_dirs
-            .par_iter()
-            .for_each(|install_dir| match install_dir.dir_type {
-                indexer::install_dirs::InstallDirType::NodeModules => {
-                    let parser = NodeModulesParser;
-                    match parser.parse_installed(&install_dir.path) {
-                        Ok(packages) => {
-                            if args.verbose && !packages.is_empty() {
-                                eprintln!(
-                                    "[debug] Found {} installed packages in {:?}",
-                                    packages.len(),
-                                    install_dir.path
-                                );
-                            }
-                            installed.lock().unwrap().extend(packages);
-                        }
-                        Err(e) => {
-                            eprintln!("[warn] Failed to parse {:?}: {}", install_dir.path, e);
-                        }
-                    }
-                }
-                indexer::install_dirs::InstallDirType::SitePackages
-                | indexer::install_dirs::InstallDirType::DistPackages
-                | indexer::install_dirs::InstallDirType::VirtualEnv => {
-                    let parser = SitePackagesParser;
-                    match parser.parse_installed(&install_dir.path) {
-                        Ok(packages) => {
-                            if args.verbose && !packages.is_empty() {
-                                eprintln!(
-                                    "[debug] Found {} installed packages in {:?}",
-                                    packages.len(),
-                                    install_dir.path
-                                );
-                            }
-                            installed.lock().unwrap().extend(packages);
-                        }
-                        Err(e) => {
-                            eprintln!("[warn] Failed to parse {:?}: {}", install_dir.path, e);
-                        }
-                    }
-                }
-            });
+    // Determine scan mode
+    let scan_installed =
+        !args.lockfiles_only && (args.scan_mode == "full" || args.scan_mode == "installed-only");
+    let scan_declared = args.scan_mode == "full" || args.scan_mode == "declared-only";
+
+    if !scan_installed && !scan_declared {
+        return Err(abort(format!(
+            "Invalid scan mode: {}. Use: full, installed-only, or declared-only",
+            args.scan_mode
+        )));
+    }
 
-        Arc::try_unwrap(installed).unwrap().into_inner().unwrap()
+    // Validate output format
+    if args.format != "csv" && args.format != "json" {
+        return Err(abort(format!(
+            "Invalid format: {}. Use: csv or json",
+            args.format
+        )));
+    }
+    let split_by = args
+        .split_output
+        .as_deref()
+        .map(str::parse::<SplitBy>)
+        .transpose()
+        .map_err(abort)?;
+    let columns = if args.columns.is_empty() {
+        default_columns()
     } else {
-        vec![]
+        parse_columns(&args.columns).map_err(abort)?
     };
+    let syslog = args
+        .syslog
+        .as_deref()
+        .map(|target| syslog_output(args, target))
+        .transpose()
+        .map_err(abort)?;
 
-    println!("Found {} installed packages", installed_packages.len());
-
-    // Classify dependencies
-    let classifier = Classifier::new();
-    let mut classified = classifier.classify(dependency_records, installed_packages);
-
-    if args.verbose {
-        eprintln!(
-            "[debug] Classified {} unique dependencies",
-            classified.len()
-        );
-    }
-
-    // Detect version mismatches
-    let version_matcher = VersionMatcher::new();
-    for dep in &mut classified {
-        if let (Some(has_ver), Some(should_ver)) = (
-            dep.get_version(scanner::models::Classification::Has),
-            dep.get_version(scanner::models::Classification::Should),
-        ) {
-            dep.has_version_mismatch = version_matcher.detect_version_mismatch(has_ver, should_ver);
-        }
-
-        if let (Some(should_ver), Some(can_range)) = (
-            dep.get_version(scanner::models::Classification::Should),
-            dep.get_version(scanner::models::Classification::Can),
-        ) {
-            dep.has_constraint_violation =
-                version_matcher.detect_constraint_violation(should_ver, can_range, dep.ecosystem);
-        }
-    }
-
-    // Load infected package list if provided
-    let infected_filter = if let Some(infected_file) = &args.infected_list {
-        println!("Loading infected package list from {}...", infected_file);
-        let mut filter = InfectedPackageFilter::new();
-        match filter.load_from_csv(Path::new(infected_file)) {
-            Ok(_) => {
-                println!("Loaded {} infected packages", filter.count());
-
-                // Count infected dependencies
-                let infected_count = classified.iter().filter(|d| filter.is_infected(d)).count();
-                let match_package_count = classified
-                    .iter()
-                    .filter(|d| {
-                        matches!(
-                            filter.get_security_status(d),
-                            scanner::analyzer::SecurityStatus::MatchPackage
-                        )
-                    })
-                    .count();
-
-                println!("Found {} infected dependencies", infected_count);
-                if match_package_count > 0 {
-                    println!(
-                        "Found {} dependencies with matching package names (different versions)",
-                        match_package_count
-                    );
+    let mut policy = Policy::new();
+    for condition in &args.fail_on {
+        policy = policy.with_fail_on(condition.parse::<FailOn>().map_err(abort)?);
+    }
+    let mut suppressions = Suppressions::new();
+    if let Some(path) = &args.suppressions {
+        suppressions
+            .load_from_file(Path::new(path))
+            .map_err(|e| abort(format!("Failed to load suppressions {}: {}", path, e)))?;
+    }
+    let policy = policy.with_suppressions(suppressions.clone());
+    if (policy.fails_on(FailOn::Infected) || policy.fails_on(FailOn::MatchVersion))
+        && args.infected_list.is_none()
+    {
+        warn!("--fail-on infected/match-version has no effect without --infected-list");
+    }
+    if syslog.is_some() && args.infected_list.is_none() {
+        warn!("--syslog sends security findings, which need an --infected-list");
+    }
+    #[cfg(feature = "siem")]
+    let sinks = match &args.sink_config {
+        Some(path) => match SinkConfig::load_from_file(Path::new(path)) {
+            Ok(sinks) if sinks.is_empty() => {
+                warn!("{} configures no sink", path);
+                None
+            }
+            Ok(sinks) => {
+                if args.infected_list.is_none() {
+                    warn!("--sink-config sends security findings, which need an --infected-list");
                 }
-
-                Some(filter)
+                Some(sinks)
             }
             Err(e) => {
-                eprintln!("[error] Failed to load infected package list: {}", e);
-                return Ok(());
+                return Err(abort(format!(
+                    "Failed to load sink configuration {}: {}",
+                    path, e
+                )))
             }
-        }
-    } else {
-        None
+        },
+        None => None,
     };
 
-    // Link to applications
-    let linker = ApplicationLinker::new();
-    let applications = linker.link_to_applications(classified.clone());
-
-    if args.verbose {
-        eprintln!(
-            "[debug] Linked dependencies to {} applications",
-            applications.len()
-        );
+    let mut tree_ecosystem = None;
+    let mut tree_charset = TreeCharset::default();
+    if let Some(Command::Tree {
+        ecosystem, charset, ..
+    }) = &args.command
+    {
+        tree_ecosystem = ecosystem
+            .as_deref()
+            .map(str::parse::<Ecosystem>)
+            .transpose()
+            .map_err(abort)?;
+        tree_charset = charset.parse::<TreeCharset>().map_err(abort)?;
     }
 
-    println!("\nScan complete!");
-    println!("Total unique dependencies: {}", classified.len());
-    println!("Applications found: {}", applications.len());
-
-    // Write output
-    match args.format.as_str() {
-        "csv" => {
-            write_classified_csv_with_security(
-                &classified,
-                infected_filter.as_ref(),
-                &output_file,
-            )?;
-            println!("\nResults written to {}", output_file);
-        }
-        "json" => {
-            if args.scan_mode == "full" {
-                // Build dependency trees for full scan
-                let tree_builder = TreeBuilder::new();
-                let trees = tree_builder.build_trees(applications.clone());
-                write_trees_json_with_security(trees, infected_filter.as_ref(), &output_file)?;
-                println!("\nDependency trees written to {}", output_file);
-            } else {
-                // Just write applications without trees
-                write_applications_json_with_security(
-                    applications,
-                    infected_filter.as_ref(),
-                    &output_file,
-                )?;
-                println!("\nResults written to {}", output_file);
-            }
-        }
-        _ => unreachable!(),
+    let min_severity = args
+        .min_severity
+        .as_deref()
+        .map(str::parse::<Severity>)
+        .transpose()
+        .map_err(abort)?;
+
+    Ok(Settings {
+        archive,
+        scan_installed,
+        scan_declared,
+        split_by,
+        columns,
+        syslog,
+        #[cfg(feature = "siem")]
+        sinks,
+        policy,
+        suppressions,
+        min_severity,
+        tree_ecosystem,
+        tree_charset,
+    })
+}
+
+/// Run the subcommands that work on earlier results rather than a scan
+fn run_command(args: &Args, settings: &Settings) -> io::Result<Option<ExitStatus>> {
+    let status = match &args.command {
+        Some(Command::Refilter { results }) => refilter(
+            args,
+            results,
+            settings.min_severity,
+            &settings.policy,
+            &settings.suppressions,
+            &settings.columns,
+        )?,
+        Some(Command::Merge { results }) => merge(args, results)?,
+        Some(Command::Diff { old, new, json }) => diff(old, new, *json, args.output.as_deref())?,
+        Some(Command::CompareHosts {
+            first,
+            second,
+            json,
+        }) => compare_hosts(first, second, *json, args.output.as_deref())?,
+        Some(Command::ParseFile { path, parse_as }) => {
+            parse_file(path, parse_as.as_deref(), args.output.as_deref())?
+        }
+        #[cfg(feature = "sqlite")]
+        Some(Command::History { query }) => history(args.history_db.as_deref(), query)?,
+        _ => return Ok(None),
+    };
+    Ok(Some(status))
+}
+
+/// Collect the directories to scan: `--dir`, `--dirs-from`, the archive and
+/// the running containers
+fn resolve_roots(args: &Args, archive: Option<&Path>) -> Result<Roots, Abort> {
+    let mut roots: Vec<PathBuf> = args.dir.iter().map(PathBuf::from).collect();
+    if let Some(list) = &args.dirs_from {
+        roots.extend(
+            read_path_list(list).map_err(|e| abort(format!("Failed to read {}: {}", list, e)))?,
+        );
+    }
+    roots.extend(archive.map(Path::to_path_buf));
+    let containers = container_roots(args)?;
+    roots.extend(containers.iter().map(|(dir, _)| dir.clone()));
+    if roots.is_empty() && args.containers.is_empty() {
+        roots.push(PathBuf::from("."));
+    }
+    let paths = match &args.paths_from {
+        Some(_) if roots.len() > 1 => return Err(abort("--paths-from scans a single directory")),
+        Some(list) => Some(
+            read_path_list(list).map_err(|e| abort(format!("Failed to read {}: {}", list, e)))?,
+        ),
+        None => None,
+    };
+    if let Some(missing) = roots.iter().find(|root| !root.exists()) {
+        let kind = if archive.is_some() {
+            "Archive"
+        } else {
+            "Directory"
+        };
+        return Err(abort(format!(
+            "{} does not exist: {}",
+            kind,
+            missing.display()
+        )));
+    }
+    Ok(Roots {
+        dirs: roots,
+        containers,
+        paths,
+    })
+}
+
+/// Root directories of the containers running under the runtimes of
+/// `--containers`
+fn container_roots(args: &Args) -> Result<Vec<(PathBuf, ContainerRef)>, Abort> {
+    let mut container_roots = Vec::new();
+    if args.containers.is_empty() {
+        return Ok(container_roots);
+    }
+    let layer = args
+        .container_layer
+        .parse::<ContainerLayer>()
+        .map_err(abort)?;
+    for runtime in &args.containers {
+        let running = runtime
+            .parse::<ContainerRuntime>()
+            .map_err(ScanError::Container)
+            .and_then(|runtime| containers::list_containers(&runtime))
+            .map_err(abort)?;
+        for running in running {
+            match running.dir(layer) {
+                Some(dir) => container_roots.push((dir, running.container)),
+                None => warn!(
+                    container = %running.container.id,
+                    "No {} directory found for the container", args.container_layer
+                ),
+            }
+        }
+    }
+    // Without containers the outputs are still written, empty, for
+    // pipelines that collect them from every node
+    if container_roots.is_empty() {
+        println!("No running containers found");
+    } else {
+        println!("Scanning {} running containers", container_roots.len());
+    }
+    Ok(container_roots)
+}
+
+/// Registries to query, from `--registry-config` and the npm and pip
+/// configuration of the user
+fn registry_config(args: &Args, project: Option<&Path>) -> Result<RegistryConfig, Abort> {
+    let mut registries = RegistryConfig::new();
+    if let Some(path) = &args.registry_config {
+        registries.load_from_file(Path::new(path)).map_err(|e| {
+            abort(format!(
+                "Failed to load registry configuration {}: {}",
+                path, e
+            ))
+        })?;
+    }
+    if !args.no_registry_discovery {
+        registries.discover(project);
+    }
+    for (ecosystem, registry) in registries.iter() {
+        debug!(ecosystem = %ecosystem, url = %registry.url, "Using registry");
+    }
+    Ok(registries)
+}
+
+/// Directories under the roots whose dependency files changed since a git
+/// revision; the run ends clean when there are none
+fn changed_roots(roots: &[PathBuf], since: &str) -> Result<Vec<PathBuf>, Abort> {
+    let mut affected = Vec::new();
+    for root in roots {
+        let changed = indexer::changed_files(root, since).map_err(abort)?;
+        affected.extend(indexer::affected_roots(root, &changed));
+    }
+    if affected.is_empty() {
+        println!("No manifests or lockfiles changed since {}", since);
+        return Err(Abort::Exit(ExitStatus::Clean));
+    }
+    for dir in &affected {
+        debug!(dir = %dir.display(), "Changed since {}", since);
+    }
+    println!(
+        "Scanning {} directories with dependency files changed since {}",
+        affected.len(),
+        since
+    );
+    Ok(affected)
+}
+
+/// Scan options from the command line
+fn scan_options(
+    args: &Args,
+    archive: bool,
+    roots: &[PathBuf],
+    paths: Option<Vec<PathBuf>>,
+) -> Result<ScanOptions, Abort> {
+    // Configure directory traversal
+    let mut walk_options = indexer::WalkOptions::new().with_follow_symlinks(args.follow_symlinks);
+    if let Some(max) = args.max_visited_dirs {
+        walk_options = walk_options.with_max_visited_dirs(max);
+    }
+
+    let mut path_filter = indexer::PathFilter::new()
+        .with_include_globs(&args.include_globs)
+        .and_then(|f| f.with_exclude_globs(&args.exclude_globs))
+        .map_err(abort)?;
+    if let Some(max_depth) = args.max_depth {
+        path_filter = path_filter.with_max_depth(max_depth);
+    }
+    walk_options = walk_options.with_filter(path_filter);
+
+    let mode = match args.scan_mode.as_str() {
+        _ if args.lockfiles_only => indexer::ScanMode::LockfilesOnly,
+        "installed-only" => indexer::ScanMode::InstalledOnly,
+        "declared-only" => indexer::ScanMode::DeclaredOnly,
+        _ => indexer::ScanMode::Full,
+    };
+
+    let requirements_mode = args
+        .requirements_as
+        .parse::<RequirementsMode>()
+        .map_err(abort)?;
+
+    let mut options = ScanOptions::new()
+        .with_scan_mode(mode)
+        .with_include_install_dirs(args.include_install_dirs)
+        .with_aggregate(args.aggregate)
+        .with_manifest_applications(args.apps_from_manifests)
+        .with_requirements_mode(requirements_mode)
+        .with_verify_hashes(args.verify_hashes)
+        .with_walk_options(walk_options);
+    if let Some(max_file_size) = args.max_file_size {
+        options = options.with_max_file_size(max_file_size * 1024 * 1024);
+    }
+    if let Some(paths) = paths {
+        debug!(paths = paths.len(), "Indexing listed paths");
+        options = options.with_paths(paths);
+    }
+    let mut limits = ScanLimits::new();
+    if let Some(max) = args.max_files {
+        limits = limits.with_max_files(max);
+    }
+    if let Some(max) = args.max_install_dirs {
+        limits = limits.with_max_install_dirs(max);
+    }
+    if let Some(max) = args.max_packages_per_dir {
+        limits = limits.with_max_packages_per_dir(max);
+    }
+    if let Some(seconds) = args.time_budget {
+        limits = limits.with_time_budget(Duration::from_secs(seconds));
+    }
+    options = options.with_limits(limits);
+    let io_throttle = IoThrottle::new()
+        .with_max_files_per_sec(args.io_max_files.unwrap_or(0))
+        .with_max_bytes_per_sec(args.io_max_mib.unwrap_or(0) * 1024 * 1024);
+    if io_throttle.is_limited() {
+        options = options.with_io_throttle(io_throttle);
+    }
+    if let Some(path) = &args.checkpoint {
+        if roots.len() > 1 {
+            return Err(abort("--checkpoint scans a single directory"));
+        }
+        options = options.with_checkpoint(path, args.resume);
+    }
+    if args.parse_timeout > 0 {
+        options = options.with_parse_timeout(Duration::from_secs(args.parse_timeout));
+    }
+    if let Some(os_root) = &args.os_packages {
+        options = options.with_os_packages(os_root);
+    }
+    if let Some(rev) = &args.git_rev {
+        options = options.with_git_rev(rev);
+    }
+    if archive {
+        options = options.with_archive(true);
+    }
+    if let Some(version) = &args.node_version {
+        NodeVersion::parse(version).map_err(|e| abort(format!("Invalid --node version: {}", e)))?;
+        options = options.with_runtime("node", version);
+    }
+    if let Some(version) = &args.python_version {
+        PythonVersion::parse(version)
+            .map_err(|e| abort(format!("Invalid --python version: {}", e)))?;
+        options = options.with_runtime("python", version);
+    }
+
+    // Filter by ecosystem if specified
+    for eco in &args.ecosystem {
+        options = options.with_ecosystem(eco.parse::<Ecosystem>().map_err(abort)?);
+    }
+    Ok(options)
+}
+
+/// Scan the roots and report what was found
+///
+/// Without roots (no running containers) the output is empty, so the
+/// results are still written.
+fn scan(scanner: &Scanner, roots: &[PathBuf], settings: &Settings) -> Result<ScanOutput, Abort> {
+    let result = if roots.is_empty() {
+        Ok(ScanOutput {
+            metadata: ScanMetadata::new(Path::new(""), SystemTime::now()),
+            ..ScanOutput::default()
+        })
+    } else {
+        scanner.scan_roots(roots)
+    };
+    let mut output = result.map_err(|e| abort(format!("Scan failed: {}", e)))?;
+    if let Some(io_throttle) = &scanner.options().io_throttle {
+        debug!(
+            files = io_throttle.files(),
+            bytes = io_throttle.bytes(),
+            waited = ?io_throttle.waited(),
+            "Throttled scan reads"
+        );
+    }
+
+    if let Some(commit) = &output.metadata.git_rev {
+        println!("Reading package files at commit {}", commit);
+    }
+    if settings.scan_declared {
+        println!("Parsed {} package files", output.files.len());
+    }
+    if settings.scan_installed {
+        println!("Found {} installed packages", output.installed_count);
+    }
+    if output.metadata.is_truncated() {
+        let limits: Vec<String> = output
+            .metadata
+            .truncated
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        println!(
+            "Scan truncated, results are partial (reached: {})",
+            limits.join(", ")
+        );
+    }
+
+    output.metadata = output.metadata.with_arguments(redacted_arguments());
+    Ok(output)
+}
+
+/// Annotate the scanned entries with their containers and, as asked, the
+/// processes running them, their content hashes and their reachability
+fn annotate(
+    args: &Args,
+    scanner: &Scanner,
+    container_roots: &[(PathBuf, ContainerRef)],
+    output: &mut ScanOutput,
+) {
+    let ScanOutput {
+        classified,
+        applications,
+        summary,
+        ..
+    } = output;
+
+    if !container_roots.is_empty() {
+        let tagged = containers::tag_containers(
+            classified.iter_mut().chain(
+                applications
+                    .iter_mut()
+                    .flat_map(|app| app.dependencies.iter_mut()),
+            ),
+            container_roots,
+        );
+        debug!(tagged, "Tagged entries with their containers");
+    }
+
+    if args.correlate_processes {
+        let correlator = ProcessCorrelator::new();
+        if correlator.is_supported() {
+            let processes = correlator.snapshot();
+            summary.running_packages = correlator.mark_running(&processes, classified);
+            for app in applications.iter_mut() {
+                correlator.mark_running(&processes, &mut app.dependencies);
+            }
+            println!(
+                "Found {} installed packages loaded by running processes",
+                summary.running_packages
+            );
+        } else {
+            warn!("--correlate-processes needs /proc; no process information is available");
+        }
+    }
+
+    if args.content_hashes {
+        let hasher = ContentHasher::new();
+        let io_throttle = scanner.options().io_throttle.as_ref();
+        let hashed = scanner.install(|| {
+            throttle::scoped(io_throttle, || {
+                for app in applications.iter_mut() {
+                    hasher.mark_hashes(&mut app.dependencies);
+                }
+                hasher.mark_hashes(classified)
+            })
+        });
+        println!("Hashed the files of {} installed packages", hashed);
+    }
+
+    if args.reachability {
+        let mut scoped = classified.to_vec();
+        ApplicationLinker::new().assign_application_roots(&mut scoped);
+        let reachability = ReachabilityAnalyzer::new().analyze(&scoped);
+        summary.unreachable_packages = reachability.mark(&mut scoped);
+        for (dep, marked) in classified.iter_mut().zip(scoped) {
+            dep.reachable = marked.reachable;
+        }
+        for app in applications.iter_mut() {
+            reachability.mark(&mut app.dependencies);
+        }
+        println!(
+            "Found {} installed packages unreachable from the code of {} applications",
+            summary.unreachable_packages,
+            reachability.applications()
+        );
+    }
+}
+
+/// Load `--infected-list` and report the infected dependencies, writing
+/// `--write-baseline` before the suppressions apply
+fn security_filter(
+    args: &Args,
+    settings: &Settings,
+    classified: &[ClassifiedDependency],
+) -> Result<Option<InfectedPackageFilter>, Abort> {
+    let infected_filter = match &args.infected_list {
+        Some(infected_file) => {
+            println!("Loading infected package list from {}...", infected_file);
+            let mut filter = InfectedPackageFilter::new();
+            if let Some(severity) = settings.min_severity {
+                filter = filter.with_min_severity(severity);
+            }
+            filter
+                .load_from_file(Path::new(infected_file))
+                .map_err(|e| abort(format!("Failed to load infected package list: {}", e)))?;
+            println!("Loaded {} infected packages", filter.count());
+            Some(filter)
+        }
+        None => None,
+    };
+
+    // Accept the current findings before suppressions hide them
+    if let Some(baseline_file) = &args.write_baseline {
+        let findings = classified.iter().filter(|d| {
+            d.has_version_mismatch
+                || infected_filter.as_ref().is_some_and(|filter| {
+                    matches!(
+                        filter.get_security_status(d),
+                        SecurityStatus::Infected | SecurityStatus::MatchVersion
+                    )
+                })
+        });
+        let baseline = settings
+            .suppressions
+            .baseline(findings, "Accepted in baseline");
+        baseline
+            .write_to_file(Path::new(baseline_file))
+            .map_err(|e| abort(format!("Failed to write baseline {}: {}", baseline_file, e)))?;
+        println!(
+            "Baseline of {} accepted findings written to {}",
+            baseline.len(),
+            baseline_file
+        );
+    }
+
+    let infected_filter =
+        infected_filter.map(|filter| filter.with_suppressions(settings.suppressions.clone()));
+    if let Some(filter) = &infected_filter {
+        // Count infected dependencies
+        let infected_count = classified.iter().filter(|d| filter.is_infected(d)).count();
+        let match_package_count = classified
+            .iter()
+            .filter(|d| filter.get_security_status(d) == SecurityStatus::MatchPackage)
+            .count();
+
+        println!("Found {} infected dependencies", infected_count);
+        if match_package_count > 0 {
+            println!(
+                "Found {} dependencies with matching package names (different versions)",
+                match_package_count
+            );
+        }
+    }
+    Ok(infected_filter)
+}
+
+/// Print the dependency trees of the tree subcommand
+fn print_trees(
+    args: &Args,
+    settings: &Settings,
+    applications: Vec<Application>,
+    package: Option<&str>,
+    infected_filter: Option<&InfectedPackageFilter>,
+) {
+    let applications: Vec<Application> = applications
+        .into_iter()
+        .filter(|app| settings.tree_ecosystem.is_none_or(|e| app.ecosystem == e))
+        .collect();
+    let mut builder = tree_builder(args).with_dedupe(true);
+    if let Some(package) = package {
+        builder = builder.with_focus(package.to_string());
+    }
+    let trees: Vec<DependencyTree> = build_trees(builder, &applications, infected_filter)
+        .into_iter()
+        .filter(|tree| !tree.roots.is_empty())
+        .collect();
+    if trees.is_empty() {
+        println!("\nNo dependency trees to show");
+    } else {
+        print!(
+            "\n{}",
+            format_trees_text(
+                &trees,
+                infected_filter,
+                settings.tree_charset,
+                io::stdout().is_terminal()
+            )
+        );
+    }
+}
+
+/// Report the findings the scan already made: modified installed files,
+/// risky packages and overrides
+fn report_checks(args: &Args, output: &ScanOutput) {
+    if args.verify_hashes {
+        let modified: Vec<_> = output
+            .classified
+            .iter()
+            .filter(|d| !d.modified_files.is_empty())
+            .collect();
+        for dep in &modified {
+            warn!(
+                package = %dep.name,
+                version = dep.get_version(Classification::Has).unwrap_or(""),
+                files = dep.modified_files.len(),
+                "Installed files of {} were modified after install",
+                dep.name
+            );
+        }
+        println!(
+            "Found {} installed packages with modified files",
+            modified.len()
+        );
+    }
+
+    let risky = output
+        .classified
+        .iter()
+        .filter(|d| !d.risk_flags.is_empty())
+        .count();
+    if risky > 0 {
+        println!(
+            "Found {} installed packages with install scripts, native extensions or entry points",
+            risky
+        );
+    }
+
+    for entry in output.overrides.iter().filter(|o| o.infected) {
+        warn!(
+            package = %entry.name,
+            ecosystem = %entry.ecosystem,
+            version = %entry.version,
+            source_file = %entry.source_file.display(),
+            "Override pins {} to infected version {}",
+            entry.name,
+            entry.version
+        );
+    }
+    for entry in output.overrides.iter().filter(|o| o.changes_resolution()) {
+        let requests: Vec<String> = entry
+            .overridden
+            .iter()
+            .map(|request| format!("{} {}", request.requested_by, request.range))
+            .collect();
+        warn!(
+            package = %entry.name,
+            ecosystem = %entry.ecosystem,
+            version = %entry.version,
+            source_file = %entry.source_file.display(),
+            overridden = %requests.join(", "),
+            "Override forces {} to {} outside the requested ranges",
+            entry.name,
+            entry.version
+        );
+    }
+}
+
+/// Warn about names close to popular packages
+fn check_typosquats(args: &Args, classified: &[ClassifiedDependency]) -> Result<(), Abort> {
+    let mut detector = TyposquatDetector::new();
+    if let Some(popular_file) = &args.popular_packages {
+        let count = detector
+            .load_from_file(Path::new(popular_file))
+            .map_err(|e| abort(format!("Failed to load popular package list: {}", e)))?;
+        println!("Loaded {} popular packages from {}", count, popular_file);
+    }
+    let typosquats = detector.check(classified);
+    for warning in &typosquats {
+        warn!(
+            package = %warning.name,
+            ecosystem = %warning.ecosystem,
+            similar_to = %warning.similar_to,
+            kind = %warning.kind,
+            "Possible typosquat of {}: {}",
+            warning.similar_to,
+            warning.name
+        );
+    }
+    println!("Found {} possible typosquats", typosquats.len());
+    Ok(())
+}
+
+/// Warn about internal packages resolved from public registries
+fn check_confusion(detector: &DependencyConfusionDetector, classified: &[ClassifiedDependency]) {
+    let risks = detector.check(classified);
+    for risk in &risks {
+        warn!(
+            package = %risk.name,
+            ecosystem = %risk.ecosystem,
+            version = %risk.version,
+            resolved = %risk.resolved,
+            source_file = %risk.source_file.display(),
+            "Internal package {} ({}) resolved from a public registry",
+            risk.name,
+            risk.pattern
+        );
+    }
+    println!(
+        "Found {} internal packages resolved from public registries",
+        risks.len()
+    );
+}
+
+/// Look up the latest versions of the dependencies, returning the outdated
+/// ones
+fn check_staleness(
+    args: &Args,
+    registries: &RegistryConfig,
+    scanner: &Scanner,
+    classified: &[ClassifiedDependency],
+) -> Vec<Staleness> {
+    let mut client = RegistryClient::new()
+        .with_config(registries)
+        .with_offline(args.offline);
+    if let Some(cache_dir) = args
+        .registry_cache
+        .as_ref()
+        .map(PathBuf::from)
+        .or_else(RegistryClient::default_cache_dir)
+    {
+        client = client.with_cache_dir(cache_dir);
+    }
+    let checker = StalenessChecker::new(client).with_cancellation(scanner.cancellation().clone());
+    let checked = scanner.install(|| checker.check(classified));
+    let checked_count = checked.len();
+    let staleness: Vec<Staleness> = checked.into_iter().filter(Staleness::is_outdated).collect();
+    println!(
+        "Found {} outdated package versions of {} checked ({} a major version behind or more)",
+        staleness.len(),
+        checked_count,
+        staleness.iter().filter(|s| s.major_behind > 0).count()
+    );
+    staleness
+}
+
+/// Write the results, the summary and the findings to their files, with the
+/// extra outputs asked for on the command line
+fn write_results(
+    args: &Args,
+    settings: &Settings,
+    scanner: &Scanner,
+    outcome: &mut Outcome,
+    baseline: Option<&Baseline>,
+) -> io::Result<()> {
+    let output_file = output_file(args);
+    let phase_start = Instant::now();
+    let progress = scanner.progress();
+    progress.phase_started(ScanPhase::Output, None);
+    let mut writer: Box<dyn OutputWriter> = match args.format.as_str() {
+        "csv" => Box::new(CsvOutput::new(&output_file).with_columns(settings.columns.clone())),
+        "json" => Box::new(JsonOutput::new(&output_file)),
+        _ => unreachable!(),
+    };
+    let Outcome {
+        output,
+        infected_filter,
+        staleness,
+        bundled,
+    } = outcome;
+    let infected_filter = infected_filter.as_ref();
+    let metadata = &output.metadata;
+    // JSON results of a full scan are dependency trees
+    let trees = (args.format == "json" && args.scan_mode == "full" && !args.lockfiles_only)
+        .then(|| build_trees(tree_builder(args), &output.applications, infected_filter));
+    writer.write_applications(&ScanResults {
+        applications: &output.applications,
+        dependencies: &output.classified,
+        trees: trees.as_deref(),
+        infected: infected_filter,
+        metadata: Some(metadata),
+    })?;
+    if trees.is_some() {
+        println!("\nDependency trees written to {}", output_file);
+    } else {
+        println!("\nResults written to {}", output_file);
+    }
+    if let Some(split_by) = settings.split_by {
+        let split_dir = args
+            .split_dir
+            .clone()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| Path::new(&output_file).with_extension(""));
+        let split_files = match (args.format.as_str(), &trees) {
+            ("csv", _) => write_split_csv(
+                &output.classified,
+                &output.applications,
+                split_by,
+                infected_filter,
+                &settings.columns,
+                &split_dir,
+            )?,
+            (_, Some(trees)) => write_split_trees_json(
+                trees,
+                split_by,
+                infected_filter,
+                Some(metadata),
+                &split_dir,
+            )?,
+            (_, None) => write_split_applications_json(
+                &output.applications,
+                split_by,
+                infected_filter,
+                Some(metadata),
+                &split_dir,
+            )?,
+        };
+        println!(
+            "Results split {} into {} files in {}",
+            split_by,
+            split_files.len(),
+            split_dir.display()
+        );
+    }
+    let elapsed = phase_start.elapsed();
+    progress.phase_finished(ScanPhase::Output, output.classified.len(), elapsed);
+    output
+        .timings
+        .record(ScanPhase::Output, output.classified.len(), elapsed);
+
+    for timing in output.timings.iter() {
+        debug!(
+            phase = %timing.phase,
+            items = timing.items,
+            elapsed = %format_duration(timing.elapsed),
+            "Phase completed"
+        );
+    }
+    debug!(elapsed = %format_duration(output.timings.total()), "Scan finished");
+
+    // Write the summary next to the results, in the same format
+    Summarizer::new().set_timings(&mut output.summary, &output.timings);
+    let summary_file = summary_path(&output_file);
+    writer.write_summary(&output.summary, Some(metadata))?;
+    println!("Summary written to {}\n", summary_file.display());
+    print!("{}", format_summary_table(&output.summary));
+
+    #[cfg(feature = "sqlite")]
+    if let Some(history_file) = &args.history_db {
+        let scan_id = HistoryDb::open(history_file)?.record_scan(
+            metadata,
+            &output.classified,
+            infected_filter,
+        )?;
+        println!("Scan {} recorded in {}", scan_id, history_file);
+    }
+
+    if let Some(graph_file) = &args.graph {
+        let trees = build_trees(tree_builder(args), &output.applications, infected_filter);
+        write_trees_graph(
+            &trees,
+            infected_filter,
+            GraphFormat::from_path(graph_file),
+            graph_file,
+        )?;
+        println!("Dependency graph written to {}", graph_file);
+    }
+
+    if let Some(markdown_file) = &args.markdown {
+        write_markdown_summary(&output.classified, infected_filter, baseline, markdown_file)?;
+        println!("Markdown summary written to {}", markdown_file);
+    }
+
+    if args.github_annotations {
+        let workspace = std::env::var_os("GITHUB_WORKSPACE")
+            .map(PathBuf::from)
+            .or_else(|| std::env::current_dir().ok())
+            .map(|dir| fs::canonicalize(&dir).unwrap_or(dir));
+        print!(
+            "{}",
+            format_github_annotations(&output.classified, infected_filter, workspace.as_deref())
+        );
+    }
+
+    #[cfg(feature = "parquet")]
+    if let Some(parquet_file) = &args.parquet {
+        write_classified_parquet(
+            &output.classified,
+            infected_filter,
+            Some(metadata),
+            parquet_file,
+        )?;
+        println!("Parquet results written to {}", parquet_file);
+    }
+
+    // Findings and failed files go next to the results so they aren't lost
+    // in CI logs
+    let app_stats = args
+        .app_stats
+        .then(|| AppStatsAnalyzer::new().analyze(&output.applications));
+    let findings = Findings {
+        inconsistencies: &output.inconsistencies,
+        overrides: &output.overrides,
+        multiple_versions: &output.multiple_versions,
+        peer_conflicts: &output.peer_conflicts,
+        incompatible_engines: &output.incompatible_engines,
+        lockfile_drift: &output.lockfile_drift,
+        staleness,
+        bundled,
+        app_stats: app_stats.as_deref(),
+        errors: &output.errors,
+    };
+    writer.write_findings(&findings, Some(metadata))?;
+    print_findings(&output_file, &findings);
+    Ok(())
+}
+
+/// Results file of `--output`, or `output.<format>`
+fn output_file(args: &Args) -> String {
+    args.output.clone().unwrap_or_else(|| {
+        if args.format == "json" {
+            "output.json".to_string()
+        } else {
+            "output.csv".to_string()
+        }
+    })
+}
+
+/// Point to the files holding the findings of the scan
+fn print_findings(output_file: &str, findings: &Findings<'_>) {
+    let inconsistencies = findings.inconsistencies;
+    if !inconsistencies.is_empty() {
+        let inconsistencies_file = inconsistencies_path(output_file);
+        println!(
+            "\n{} version inconsistencies, see {}",
+            inconsistencies.len(),
+            inconsistencies_file.display()
+        );
+    }
+
+    let overrides = findings.overrides;
+    if !overrides.is_empty() {
+        let overrides_file = overrides_path(output_file);
+        println!(
+            "\n{} version overrides ({} overruling requested ranges, {} pinning infected versions), see {}",
+            overrides.len(),
+            overrides.iter().filter(|o| o.changes_resolution()).count(),
+            overrides.iter().filter(|o| o.infected).count(),
+            overrides_file.display()
+        );
+    }
+
+    if !findings.multiple_versions.is_empty() {
+        let multiple_versions_file = multiple_versions_path(output_file);
+        println!(
+            "\n{} packages installed at several versions, see {}",
+            findings.multiple_versions.len(),
+            multiple_versions_file.display()
+        );
+    }
+
+    if !findings.peer_conflicts.is_empty() {
+        let peer_conflicts_file = peer_conflicts_path(output_file);
+        println!(
+            "\n{} peer dependency conflicts, see {}",
+            findings.peer_conflicts.len(),
+            peer_conflicts_file.display()
+        );
+    }
+
+    if !findings.incompatible_engines.is_empty() {
+        let engines_file = engines_path(output_file);
+        println!(
+            "\n{} packages do not support the target runtime, see {}",
+            findings.incompatible_engines.len(),
+            engines_file.display()
+        );
+    }
+
+    if !findings.lockfile_drift.is_empty() {
+        let lockfile_drift_file = lockfile_drift_path(output_file);
+        println!(
+            "\n{} lockfiles out of date with their manifests, see {}",
+            findings.lockfile_drift.len(),
+            lockfile_drift_file.display()
+        );
+    }
+
+    if !findings.staleness.is_empty() {
+        let staleness_file = staleness_path(output_file);
+        println!(
+            "\n{} outdated package versions, see {}",
+            findings.staleness.len(),
+            staleness_file.display()
+        );
     }
 
+    if !findings.bundled.is_empty() {
+        let bundled_file = bundled_path(output_file);
+        println!(
+            "\n{} vendored or bundled packages outside manifests and lockfiles, see {}",
+            findings.bundled.len(),
+            bundled_file.display()
+        );
+    }
+
+    if let Some(stats) = findings.app_stats {
+        let stats_file = app_stats_path(output_file);
+        println!(
+            "
+Dependency stats of {} applications ({} with duplicate packages), see {}",
+            stats.len(),
+            stats.iter().filter(|s| !s.duplicates.is_empty()).count(),
+            stats_file.display()
+        );
+    }
+
+    let errors = findings.errors;
+    if !errors.is_empty() {
+        let errors_file = errors_path(output_file);
+        println!(
+            "\n{} files failed to parse, see {}",
+            errors.len(),
+            errors_file.display()
+        );
+        for entry in errors.iter().take(MAX_LISTED_ERRORS) {
+            println!("  {}: {}", entry.file.display(), entry.reason);
+        }
+        if errors.len() > MAX_LISTED_ERRORS {
+            println!("  ... and {} more", errors.len() - MAX_LISTED_ERRORS);
+        }
+    }
+}
+
+/// Send the security findings to `--syslog`
+fn send_syslog(syslog: &mut SyslogOutput, outcome: &Outcome) -> Result<(), Abort> {
+    syslog.write_applications(&outcome.results()).map_err(|e| {
+        error!(target = %syslog.target(), "Failed to send security findings: {}", e);
+        Abort::Exit(ExitStatus::ScanErrors)
+    })?;
+    println!("Security findings sent to {}", syslog.target());
+    Ok(())
+}
+
+/// Send the security findings to the sinks of `--sink-config`
+#[cfg(feature = "siem")]
+fn send_to_sinks(args: &Args, sinks: &SinkConfig, outcome: &Outcome) -> Result<(), Abort> {
+    let mut writers = OutputWriters::new();
+    for writer in sinks.writers() {
+        writers.register(writer);
+    }
+    writers
+        .write_applications(&outcome.results())
+        .map_err(|e| abort(format!("Failed to send security findings: {}", e)))?;
+    println!(
+        "Security findings sent to {} sinks of {}",
+        writers.len(),
+        args.sink_config.as_deref().unwrap_or_default()
+    );
+    Ok(())
+}
+
+/// Push the report to `--push` and serve it on `--serve`
+fn publish_report(args: &Args, outcome: &Outcome) -> Result<(), Abort> {
+    let output = &outcome.output;
+    let report = build_report(
+        output.applications.clone(),
+        outcome.infected_filter.as_ref(),
+        output.summary.clone(),
+        output.inconsistencies.clone(),
+        output.overrides.clone(),
+        output.errors.clone(),
+        Some(output.metadata.clone()),
+    );
+    if let Some(url) = &args.push {
+        let mut headers = Vec::new();
+        if let Some(path) = &args.push_header_file {
+            let file_headers = read_header_file(Path::new(path))
+                .map_err(|e| abort(format!("Failed to read --push-header-file {}: {}", path, e)))?;
+            headers.extend(file_headers);
+        }
+        for header in &args.push_headers {
+            let header = parse_header(header).ok_or_else(|| {
+                abort(format!(
+                    "Invalid --push-header {:?}, expected \"Name: value\"",
+                    header
+                ))
+            })?;
+            headers.push(header);
+        }
+        push_report(&report, url, &headers).map_err(|e| {
+            error!(url = %url, "Failed to push results: {}", e);
+            Abort::Exit(ExitStatus::ScanErrors)
+        })?;
+        println!("\nResults pushed to {}", url);
+    }
+    if let Some(addr) = &args.serve {
+        let mut server = ReportServer::bind(addr)?;
+        if let Some(token) = &args.serve_token {
+            server = server.with_token(token);
+        }
+        println!(
+            "\nServing results on http://{}/results (Ctrl-C to stop)",
+            server.local_addr()?
+        );
+        server.serve(&report)?;
+    }
     Ok(())
 }
+
+/// Exit status of a finished scan
+///
+/// Findings take precedence over parse errors when choosing the exit code.
+fn exit_status(
+    args: &Args,
+    settings: &Settings,
+    outcome: &Outcome,
+    cancellation: &CancellationToken,
+) -> ExitStatus {
+    let output = &outcome.output;
+    let policy = &settings.policy;
+    let mut status = policy.evaluate(&output.classified, outcome.infected_filter.as_ref());
+    if !status.is_failure() {
+        status = policy.evaluate_inconsistencies(&output.inconsistencies);
+    }
+    if status.is_failure() {
+        error!(
+            exit_code = status.code(),
+            "Scan failed the --fail-on policy"
+        );
+        return status;
+    }
+    if args.strict && !output.errors.is_empty() {
+        error!(
+            "Scan failed: {} parse errors in strict mode",
+            output.errors.len()
+        );
+        return ExitStatus::ScanErrors;
+    }
+    if cancellation.is_cancelled() {
+        error!("Scan was interrupted, results are partial");
+        return ExitStatus::ScanErrors;
+    }
+    ExitStatus::Clean
+}
+
+/// Report the ecosystems, package managers and installation directories of a tree
+fn detect(scanner: &Scanner, root: &Path, json: bool) -> io::Result<ExitStatus> {
+    let detections = detect_ecosystems(&scanner.index(root));
+    if json {
+        let json = serde_json::to_string_pretty(&DetectionDocument {
+            schema_version: SCHEMA_VERSION,
+            ecosystems: detections,
+        })?;
+        println!("{}", json);
+        return Ok(ExitStatus::Clean);
+    }
+
+    if detections.is_empty() {
+        println!("No package files or installation directories found");
+        return Ok(ExitStatus::Clean);
+    }
+    println!("Ecosystems found in {}:", root.display());
+    for detection in &detections {
+        let managers: Vec<String> = detection
+            .package_managers
+            .iter()
+            .map(|(manager, count)| format!("{} ({})", manager, count))
+            .collect();
+        println!(
+            "  {:<8} {} manifests, {} lockfiles, {} install dirs{}",
+            detection.ecosystem.to_string(),
+            detection.manifests,
+            detection.lockfiles,
+            detection.install_dirs,
+            if managers.is_empty() {
+                String::new()
+            } else {
+                format!("; {}", managers.join(", "))
+            }
+        );
+    }
+    Ok(ExitStatus::Clean)
+}
+
+/// List what a scan of the roots would parse, without parsing it
+fn dry_run(
+    scanner: &Scanner,
+    roots: &[PathBuf],
+    format: &str,
+    output: Option<&str>,
+) -> io::Result<ExitStatus> {
+    // The scan only drops the package files of other ecosystems
+    let ecosystems = &scanner.options().ecosystems;
+    let listed: Vec<ListedPath> = roots
+        .iter()
+        .flat_map(|root| list_index(&scanner.index(root)))
+        .filter(|entry| {
+            entry.kind == ListedKind::InstallDir
+                || ecosystems.is_empty()
+                || ecosystems.contains(&entry.ecosystem)
+        })
+        .collect();
+    let Some(path) = output else {
+        print!("{}", format_listing_text(&listed));
+        return Ok(ExitStatus::Clean);
+    };
+    match format {
+        "csv" => write_listing_csv(&listed, path)?,
+        "json" => write_listing_json(&listed, path)?,
+        _ => {
+            error!("--dry-run writes csv or json");
+            return Ok(ExitStatus::ScanErrors);
+        }
+    }
+    println!("Listed {} paths to {}", listed.len(), path);
+    Ok(ExitStatus::Clean)
+}
+
+/// Answer a trend query from the scan history database
+#[cfg(feature = "sqlite")]
+fn history(path: Option<&str>, query: &HistoryQuery) -> io::Result<ExitStatus> {
+    let Some(path) = path else {
+        error!("history needs a --history-db");
+        return Ok(ExitStatus::ScanErrors);
+    };
+    if !Path::new(path).exists() {
+        error!("History database does not exist: {}", path);
+        return Ok(ExitStatus::ScanErrors);
+    }
+    let db = HistoryDb::open(path)?;
+
+    let appearances = match query {
+        HistoryQuery::Scans => {
+            let scans = db.scans()?;
+            if scans.is_empty() {
+                println!("No scans recorded");
+            }
+            for scan in &scans {
+                println!(
+                    "{:>5}  {}  {}{}  {} versions, {} infected",
+                    scan.id,
+                    scan.started_at,
+                    scan.hostname
+                        .as_deref()
+                        .map(|host| format!("{}:", host))
+                        .unwrap_or_default(),
+                    scan.scan_root,
+                    scan.findings,
+                    scan.infected
+                );
+            }
+            return Ok(ExitStatus::Clean);
+        }
+        HistoryQuery::New { since } => {
+            let since = match since.strip_suffix('d').map(str::parse::<u64>) {
+                Some(Ok(days)) => {
+                    rfc3339(SystemTime::now() - Duration::from_secs(days * 24 * 60 * 60))[..10]
+                        .to_string()
+                }
+                _ => since.clone(),
+            };
+            println!("Package versions first seen since {}:", since);
+            db.new_since(&since)?
+        }
+        HistoryQuery::FirstSeen { package, infected } => {
+            db.first_seen(package.as_deref(), *infected)?
+        }
+    };
+
+    if appearances.is_empty() {
+        println!("No matching package versions");
+    }
+    for appearance in &appearances {
+        println!(
+            "  {:<8} {} {}  first seen {} (scan {}), last seen {}, in {} scans",
+            appearance.ecosystem,
+            appearance.name,
+            appearance.version,
+            appearance.first_seen,
+            appearance.first_scan,
+            appearance.last_seen,
+            appearance.scans
+        );
+    }
+    Ok(ExitStatus::Clean)
+}
+
+/// Parse a single package file, from a path or stdin, and write its records as JSON
+/// Merge results files into one inventory (the merge subcommand)
+fn merge(args: &Args, results: &[String]) -> io::Result<ExitStatus> {
+    let mut merger = InventoryMerger::new();
+    for results_file in results {
+        let (dependencies, metadata) = match read_results_entries(results_file) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                error!("Failed to read results {}: {}", results_file, e);
+                return Ok(ExitStatus::ScanErrors);
+            }
+        };
+        debug!(
+            file = %results_file,
+            entries = dependencies.len(),
+            "Read results to merge"
+        );
+        merger.add(
+            InventorySource::new(results_file, metadata.as_ref()),
+            dependencies,
+        );
+    }
+    let inventory = merger.finish();
+
+    let output_file = args.output.clone().unwrap_or_else(|| {
+        if args.format == "json" {
+            "inventory.json".to_string()
+        } else {
+            "inventory.csv".to_string()
+        }
+    });
+    if args.format == "json" {
+        write_inventory_json(&inventory, &output_file)?;
+    } else {
+        write_inventory_csv(&inventory, &output_file)?;
+    }
+    println!(
+        "Merged {} package versions from {} results files into {}",
+        inventory.packages.len(),
+        inventory.sources.len(),
+        output_file
+    );
+    Ok(ExitStatus::Clean)
+}
+
+/// Read the entries of a results file, JSON if the name ends in `.json` and
+/// CSV otherwise (which carries no scan metadata)
+fn read_results_entries(
+    results_file: &str,
+) -> Result<(Vec<ClassifiedDependency>, Option<ScanMetadata>), ScanError> {
+    if results_file.ends_with(".json") {
+        read_results_json_with_metadata(results_file).map(|(json, metadata)| {
+            let dependencies = json
+                .into_applications()
+                .into_iter()
+                .flat_map(|app| app.dependencies)
+                .collect();
+            (dependencies, metadata)
+        })
+    } else {
+        read_classified_csv(results_file).map(|dependencies| (dependencies, None))
+    }
+}
+
+/// Compare the packages of two hosts' results files
+///
+/// The differences are rendered for the terminal, or with `--json` written
+/// as a [`HostComparisonDocument`](scanner::output::HostComparisonDocument).
+fn compare_hosts(
+    first: &str,
+    second: &str,
+    json: bool,
+    output: Option<&str>,
+) -> io::Result<ExitStatus> {
+    let read = |results: &str| match read_results_entries(results) {
+        Ok((dependencies, metadata)) => {
+            let mut source = InventorySource::new(results, metadata.as_ref());
+            source.entries = dependencies.len();
+            Some((source, dependencies))
+        }
+        Err(e) => {
+            error!("Failed to read results {}: {}", results, e);
+            None
+        }
+    };
+    let (Some((first_source, first_deps)), Some((second_source, second_deps))) =
+        (read(first), read(second))
+    else {
+        return Ok(ExitStatus::ScanErrors);
+    };
+
+    let differences = HostComparer::new().compare(&first_deps, &second_deps);
+    if json {
+        match output {
+            Some(output) => {
+                write_host_comparison_json(&first_source, &second_source, &differences, output)?
+            }
+            None => println!(
+                "{}",
+                format_host_comparison_json(&first_source, &second_source, &differences)?
+            ),
+        }
+    } else if differences.is_empty() {
+        println!("No differences between the hosts");
+    } else {
+        print!(
+            "{}",
+            format_host_comparison_text(
+                &first_source,
+                &second_source,
+                &differences,
+                io::stdout().is_terminal()
+            )
+        );
+    }
+    Ok(ExitStatus::Clean)
+}
+
+/// Compare the dependency trees of two results files
+///
+/// The differences are rendered for the terminal, or with `--json` written
+/// as a [`TreeDiffDocument`](scanner::output::TreeDiffDocument).
+fn diff(old: &str, new: &str, json: bool, output: Option<&str>) -> io::Result<ExitStatus> {
+    let read = |results: &str| match read_results_json_with_metadata(results) {
+        Ok((JsonResults::Trees(trees), metadata)) => Some((trees, metadata.map(|m| m.scan_root))),
+        Ok((JsonResults::Applications(_), _)) => {
+            error!(
+                "{} has no dependency trees; write it with a full scan and --format json",
+                results
+            );
+            None
+        }
+        Err(e) => {
+            error!("Failed to read results {}: {}", results, e);
+            None
+        }
+    };
+    let (Some((old_trees, old_root)), Some((new_trees, new_root))) = (read(old), read(new)) else {
+        return Ok(ExitStatus::ScanErrors);
+    };
+
+    let diffs = TreeDiffer::new()
+        .with_scan_roots(old_root, new_root)
+        .diff(&old_trees, &new_trees);
+    if json {
+        match output {
+            Some(output) => write_tree_diff_json(&diffs, output)?,
+            None => println!("{}", format_tree_diff_json(&diffs)?),
+        }
+    } else if diffs.is_empty() {
+        println!("No differences between the dependency trees");
+    } else {
+        print!(
+            "{}",
+            format_tree_diff_text(&diffs, io::stdout().is_terminal())
+        );
+    }
+    Ok(ExitStatus::Clean)
+}
+
+fn parse_file(path: &str, parse_as: Option<&str>, output: Option<&str>) -> io::Result<ExitStatus> {
+    let (content, file_path) = if path == "-" {
+        let mut content = Vec::new();
+        io::stdin().read_to_end(&mut content)?;
+        (String::from_utf8_lossy(&content).into_owned(), None)
+    } else {
+        match read_text(path) {
+            Ok(content) => (content, Some(PathBuf::from(path))),
+            Err(e) => {
+                error!("Failed to read {}: {}", path, e);
+                return Ok(ExitStatus::ScanErrors);
+            }
+        }
+    };
+
+    let filename = parse_as.or_else(|| {
+        file_path
+            .as_deref()
+            .and_then(Path::file_name)
+            .and_then(|name| name.to_str())
+    });
+    let registry = ParserRegistry::with_default_parsers();
+    let Some(parser) = registry.detect(filename, &content) else {
+        error!(
+            "Could not detect the package file type of {}; use --as",
+            path
+        );
+        return Ok(ExitStatus::ScanErrors);
+    };
+    // Records from stdin name the file they were parsed as
+    let file_path = file_path.unwrap_or_else(|| PathBuf::from(parser.filename()));
+    let records = match parser.parse(&content, &file_path) {
+        Ok(records) => records,
+        Err(e) => {
+            error!("{}", e);
+            return Ok(ExitStatus::ScanErrors);
+        }
+    };
+
+    let json = serde_json::to_string_pretty(&RecordsDocument {
+        schema_version: SCHEMA_VERSION,
+        parser: parser.filename().to_string(),
+        records,
+    })?;
+    match output {
+        Some(output) => fs::write(output, json + "\n")?,
+        None => println!("{}", json),
+    }
+    Ok(ExitStatus::Clean)
+}
+
+/// Recompute the security statuses of earlier results and write them again
+///
+/// The results are written in the format they were read in, to `--output` or
+/// next to the input as `<name>.refiltered.<ext>`.
+fn refilter(
+    args: &Args,
+    results: &str,
+    min_severity: Option<Severity>,
+    policy: &Policy,
+    suppressions: &Suppressions,
+    columns: &[&CsvColumn],
+) -> io::Result<ExitStatus> {
+    let Some(infected_file) = &args.infected_list else {
+        error!("refilter needs an --infected-list");
+        return Ok(ExitStatus::ScanErrors);
+    };
+    let mut filter = InfectedPackageFilter::new();
+    if let Some(severity) = min_severity {
+        filter = filter.with_min_severity(severity);
+    }
+    if let Err(e) = filter.load_from_file(Path::new(infected_file)) {
+        error!("Failed to load infected package list: {}", e);
+        return Ok(ExitStatus::ScanErrors);
+    }
+    let filter = filter.with_suppressions(suppressions.clone());
+    println!(
+        "Loaded {} infected packages from {}",
+        filter.count(),
+        infected_file
+    );
+
+    let loaded = if results.ends_with(".json") {
+        read_results_json(results).map(|json| {
+            let classified = json
+                .clone()
+                .into_applications()
+                .into_iter()
+                .flat_map(|app| app.dependencies)
+                .collect();
+            (classified, Some(json))
+        })
+    } else {
+        read_classified_csv(results).map(|classified| (classified, None))
+    };
+    let (classified, json_results): (Vec<ClassifiedDependency>, _) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            error!("Failed to read results {}: {}", results, e);
+            return Ok(ExitStatus::ScanErrors);
+        }
+    };
+
+    let infected_count = classified.iter().filter(|d| filter.is_infected(d)).count();
+    println!(
+        "Found {} infected dependencies among {} entries",
+        infected_count,
+        classified.len()
+    );
+
+    let output_file = args
+        .output
+        .clone()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| sidecar_path(results, "refiltered"));
+    match json_results {
+        Some(JsonResults::Applications(applications)) => {
+            write_applications_json_with_security(applications, Some(&filter), None, &output_file)?
+        }
+        Some(JsonResults::Trees(trees)) => {
+            write_trees_json_with_security(trees, Some(&filter), None, &output_file)?
+        }
+        None => {
+            write_classified_csv_with_columns(&classified, Some(&filter), columns, &output_file)?
+        }
+    }
+    println!("Results written to {}", output_file.display());
+
+    let status = policy.evaluate(&classified, Some(&filter));
+    if status.is_failure() {
+        error!(
+            exit_code = status.code(),
+            "Results failed the --fail-on policy"
+        );
+    }
+    Ok(status)
+}
+
+/// Print the dependency paths leading to a package
+fn print_why(classified: &[ClassifiedDependency], package: &str) {
+    let mut scoped = classified.to_vec();
+    ApplicationLinker::new().assign_application_roots(&mut scoped);
+    let paths = ReverseDependencyFinder::new().find_paths(&scoped, package);
+
+    if paths.is_empty() {
+        if scoped.iter().any(|d| d.name == package) {
+            println!(
+                "\n{} was found, but not on a path from any application's dependencies",
+                package
+            );
+        } else {
+            println!("\nNo dependency on {} found", package);
+        }
+        return;
+    }
+
+    println!("\n{} dependency paths to {}:", paths.len(), package);
+    for path in &paths {
+        println!("  {}", path);
+    }
+}
@@ -2,11 +2,13 @@
 
 use super::classification::ClassifiedDependency;
 use super::dependency::Ecosystem;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::path::PathBuf;
 
 /// An application root representing a project with dependencies
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Application {
     /// Application name (from package.json, pyproject.toml, Cargo.toml, etc.)
     pub name: String,
@@ -22,6 +24,15 @@ pub struct Application {
 
     /// All dependencies associated with this application
     pub dependencies: Vec<ClassifiedDependency>,
+
+    /// Root of the nearest enclosing application of the same ecosystem
+    /// (e.g. the workspace root of a monorepo package)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<PathBuf>,
+
+    /// Roots of the applications nested directly inside this one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<PathBuf>,
 }
 
 impl Application {
@@ -38,6 +49,8 @@ impl Application {
             manifest_path,
             ecosystem,
             dependencies: Vec::new(),
+            parent: None,
+            children: Vec::new(),
         }
     }
 
@@ -66,6 +79,28 @@ impl Application {
         self.dependencies.len()
     }
 
+    /// Sort dependencies by name, location and versions
+    pub fn sort_dependencies(&mut self) {
+        self.dependencies
+            .sort_by(ClassifiedDependency::output_order);
+    }
+
+    /// Order applications by root path, manifest, ecosystem and name
+    pub fn output_order(&self, other: &Self) -> Ordering {
+        (
+            &self.root_path,
+            &self.manifest_path,
+            self.ecosystem,
+            &self.name,
+        )
+            .cmp(&(
+                &other.root_path,
+                &other.manifest_path,
+                other.ecosystem,
+                &other.name,
+            ))
+    }
+
     /// Check if this application has a specific dependency
     pub fn has_dependency(&self, name: &str) -> bool {
         self.dependencies.iter().any(|d| d.name == name)
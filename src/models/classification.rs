@@ -43,13 +43,18 @@
 //! assert_eq!(dep.primary_classification(), Some(Classification::Has));
 //! ```
 
-use super::dependency::Ecosystem;
+use super::container::ContainerRef;
+use super::dependency::{Ecosystem, SourceKind, SourceSpan};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
-/// Classification of a dependency based on its source
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Classification of a dependency based on its source, ordered by priority
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
 #[serde(rename_all = "lowercase")]
 pub enum Classification {
     /// Package is physically installed (found in node_modules, site-packages, etc.)
@@ -70,8 +75,45 @@ impl std::fmt::Display for Classification {
     }
 }
 
+/// Trait of an installed package worth prioritizing during incident response
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskFlag {
+    /// Runs lifecycle scripts on install (npm `preinstall`/`install`/`postinstall`)
+    InstallScript,
+    /// Ships compiled extension modules or shared libraries
+    NativeExtension,
+    /// Installs console or GUI commands
+    EntryPoint,
+}
+
+impl std::fmt::Display for RiskFlag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RiskFlag::InstallScript => write!(f, "install_script"),
+            RiskFlag::NativeExtension => write!(f, "native_extension"),
+            RiskFlag::EntryPoint => write!(f, "entry_point"),
+        }
+    }
+}
+
+impl std::str::FromStr for RiskFlag {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "install_script" => Ok(RiskFlag::InstallScript),
+            "native_extension" => Ok(RiskFlag::NativeExtension),
+            "entry_point" => Ok(RiskFlag::EntryPoint),
+            _ => Err(format!("Unknown risk flag: {}", s)),
+        }
+    }
+}
+
 /// A dependency with multiple classifications and associated metadata
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ClassifiedDependency {
     /// Package name (just the package name, e.g., "make-fetch-happen")
     pub name: String,
@@ -83,7 +125,7 @@ pub struct ClassifiedDependency {
     /// - Has: exact installed version
     /// - Should: exact locked version
     /// - Can: version range from manifest
-    pub classifications: HashMap<Classification, String>,
+    pub classifications: BTreeMap<Classification, String>,
 
     /// Ecosystem (Node, Python, Rust)
     pub ecosystem: Ecosystem,
@@ -94,11 +136,95 @@ pub struct ClassifiedDependency {
     /// Application name (extracted from manifest)
     pub application_name: Option<String>,
 
+    /// Roots of the applications enclosing the entry's application, from
+    /// the nearest outwards
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub enclosing_applications: Vec<PathBuf>,
+
     /// Installed package path (for Has classification)
     pub installed_path: Option<PathBuf>,
 
     /// Source files for each classification
-    pub source_files: HashMap<Classification, PathBuf>,
+    pub source_files: BTreeMap<Classification, PathBuf>,
+
+    /// Where the lockfile entry and manifest declaration are in their
+    /// source files
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub source_spans: BTreeMap<Classification, SourceSpan>,
+
+    /// Package provider that reported the entry, when not found on disk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<String>,
+
+    /// Scan root the entry was found under, for scans of several roots
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scan_root: Option<PathBuf>,
+
+    /// Running container the entry was found in (with `--containers`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<ContainerRef>,
+
+    /// Reason of the suppression accepting the entry's findings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suppressed: Option<String>,
+
+    /// Name the package is installed under when declared as an npm alias
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+
+    /// Where the manifest obtains the package from (for Can classification)
+    #[serde(default, skip_serializing_if = "SourceKind::is_registry")]
+    pub source_kind: SourceKind,
+
+    /// URL the lockfile resolved the package from (for Should classification)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved: Option<String>,
+
+    /// Integrity hash or checksum from the lockfile (for Should classification)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub integrity: Option<String>,
+
+    /// Integrity hash recorded at install time (for Has classification)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub installed_integrity: Option<String>,
+
+    /// Installed files modified since install (for Has classification)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modified_files: Vec<PathBuf>,
+
+    /// Lifecycle scripts run on install (for Has classification)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub install_scripts: Vec<String>,
+
+    /// Compiled extensions shipped by the package (for Has classification)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub native_extensions: Vec<String>,
+
+    /// Commands the package installs (for Has classification)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entry_points: Vec<String>,
+
+    /// License the installed package declares (for Has classification)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+
+    /// Risk traits of the installed package, set by the risk analysis
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub risk_flags: Vec<RiskFlag>,
+
+    /// Processes loading the installed package (with `--correlate-processes`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub running_pids: Vec<u32>,
+
+    /// Whether the application's code imports the installed package, directly
+    /// or through other reachable packages (with `--reachability`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reachable: Option<bool>,
+
+    /// Hash of the files of the installed package, comparable across hosts
+    /// (with `--content-hashes`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
 
     /// Version mismatch between Has and Should
     pub has_version_mismatch: bool,
@@ -106,6 +232,18 @@ pub struct ClassifiedDependency {
     /// Constraint violation (Should doesn't satisfy Can range)
     pub has_constraint_violation: bool,
 
+    /// Installed, but no manifest or lockfile of the application accounts for it
+    #[serde(default)]
+    pub is_phantom: bool,
+
+    /// Locked or declared, but not installed in the application
+    #[serde(default)]
+    pub missing_installation: bool,
+
+    /// Installed from a different tarball than the lockfile's integrity hash
+    #[serde(default)]
+    pub integrity_mismatch: bool,
+
     /// Parent package name (for dependency tree)
     pub parent_package: Option<String>,
 
@@ -115,6 +253,30 @@ pub struct ClassifiedDependency {
     /// Security status (for infected package detection)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub security: Option<String>,
+
+    /// Severity of the matching advisory (for infected package detection)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+
+    /// CVSS base score of the matching advisory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cvss: Option<f32>,
+
+    /// EPSS exploitation probability of the matching advisory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epss: Option<f32>,
+
+    /// Identifiers of the matching advisory (CVE, GHSA, ...)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub advisory_ids: Vec<String>,
+
+    /// Nearest fixed version within the declared range (for infected package detection)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recommended_version: Option<String>,
+
+    /// Package URL (set by the output writers)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purl: Option<String>,
 }
 
 impl ClassifiedDependency {
@@ -123,17 +285,46 @@ impl ClassifiedDependency {
         Self {
             name,
             package_name_path: None,
-            classifications: HashMap::new(),
+            classifications: BTreeMap::new(),
             ecosystem,
             application_root: None,
             application_name: None,
+            enclosing_applications: Vec::new(),
             installed_path: None,
-            source_files: HashMap::new(),
+            source_files: BTreeMap::new(),
+            source_spans: BTreeMap::new(),
+            provenance: None,
+            scan_root: None,
+            container: None,
+            suppressed: None,
+            alias: None,
+            source_kind: SourceKind::Registry,
+            resolved: None,
+            integrity: None,
+            installed_integrity: None,
+            modified_files: Vec::new(),
+            install_scripts: Vec::new(),
+            native_extensions: Vec::new(),
+            entry_points: Vec::new(),
+            license: None,
+            risk_flags: Vec::new(),
+            running_pids: Vec::new(),
+            reachable: None,
+            content_hash: None,
             has_version_mismatch: false,
             has_constraint_violation: false,
+            is_phantom: false,
+            missing_installation: false,
+            integrity_mismatch: false,
             parent_package: None,
             dependencies: Vec::new(),
             security: None,
+            severity: None,
+            cvss: None,
+            epss: None,
+            advisory_ids: Vec::new(),
+            recommended_version: None,
+            purl: None,
         }
     }
 
@@ -144,6 +335,29 @@ impl ClassifiedDependency {
             .or_else(|| self.get_version(Classification::Can))
     }
 
+    /// Order entries by application, name, location and versions, so output
+    /// does not depend on the order files were discovered in
+    pub fn output_order(&self, other: &Self) -> Ordering {
+        (
+            &self.application_root,
+            self.ecosystem,
+            &self.name,
+            &self.package_name_path,
+            &self.installed_path,
+            &self.classifications,
+            &self.source_files,
+        )
+            .cmp(&(
+                &other.application_root,
+                other.ecosystem,
+                &other.name,
+                &other.package_name_path,
+                &other.installed_path,
+                &other.classifications,
+                &other.source_files,
+            ))
+    }
+
     /// Add a classification with version and source file
     pub fn add_classification(
         &mut self,
@@ -167,6 +381,11 @@ impl ClassifiedDependency {
         self.source_files.get(&classification)
     }
 
+    /// Get the line a classification's source file declares the dependency on
+    pub fn get_source_line(&self, classification: Classification) -> Option<u32> {
+        self.source_spans.get(&classification).map(|span| span.line)
+    }
+
     /// Check if this dependency has a specific classification
     pub fn has_classification(&self, classification: Classification) -> bool {
         self.classifications.contains_key(&classification)
@@ -174,20 +393,39 @@ impl ClassifiedDependency {
 
     /// Get all classifications for this dependency
     pub fn get_classifications(&self) -> Vec<Classification> {
-        let mut classifications: Vec<_> = self.classifications.keys().copied().collect();
-        // Sort by priority: Has, Should, Can
-        classifications.sort_by_key(|c| match c {
-            Classification::Has => 0,
-            Classification::Should => 1,
-            Classification::Can => 2,
-        });
-        classifications
+        // Keys are ordered by priority: Has, Should, Can
+        self.classifications.keys().copied().collect()
     }
 
     /// Get the highest priority classification
     pub fn primary_classification(&self) -> Option<Classification> {
         self.get_classifications().first().copied()
     }
+
+    /// Whether any check flagged this entry
+    ///
+    /// Infected list matches only count once the entry has been annotated
+    /// (see [`InfectedPackageFilter::annotate`](crate::analyzer::InfectedPackageFilter::annotate)).
+    pub fn is_flagged(&self) -> bool {
+        self.has_version_mismatch
+            || self.has_constraint_violation
+            || self.is_phantom
+            || self.missing_installation
+            || self.integrity_mismatch
+            || !self.modified_files.is_empty()
+            || matches!(self.security.as_deref(), Some("INFECTED" | "MATCH_VERSION"))
+    }
+
+    /// Package URL for this dependency
+    ///
+    /// Uses the installed or locked version; entries with only a declared
+    /// range get a versionless purl.
+    pub fn purl(&self) -> String {
+        let version = self
+            .get_version(Classification::Has)
+            .or_else(|| self.get_version(Classification::Should));
+        super::purl::purl(self.ecosystem, &self.name, version)
+    }
 }
 
 #[cfg(test)]
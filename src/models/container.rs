@@ -0,0 +1,42 @@
+//! Running containers results are found in
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A running container whose filesystem was scanned
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ContainerRef {
+    /// Container ID
+    pub id: String,
+
+    /// Container name
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+
+    /// Image the container runs
+    pub image: String,
+
+    /// Runtime running the container (`docker`, `containerd`)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub runtime: String,
+
+    /// Kubernetes pod of the container, as `namespace/name`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pod: Option<String>,
+
+    /// Labels of the container's pod, or of the container outside Kubernetes
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+}
+
+impl ContainerRef {
+    /// Labels as `key=value`, space-separated
+    pub fn labels_text(&self) -> String {
+        self.labels
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
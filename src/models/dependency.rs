@@ -1,19 +1,22 @@
 //! Core dependency data structures
 
+use super::{ScanError, Symbol};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::Path;
+use std::sync::Arc;
 
 /// A dependency record representing a package dependency
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct DependencyRecord {
     /// Package name
-    pub name: String,
+    pub name: Symbol,
 
     /// Version specification (range for manifests, exact for lockfiles)
-    pub version: String,
+    pub version: Symbol,
 
-    /// Source file path
-    pub source_file: PathBuf,
+    /// Source file path, shared by all records of the file
+    pub source_file: Arc<Path>,
 
     /// Dependency type (dependencies, devDependencies, build-dependencies, etc.)
     pub dep_type: DependencyType,
@@ -23,10 +26,105 @@ pub struct DependencyRecord {
 
     /// Whether this is from a manifest or lockfile
     pub file_type: FileType,
+
+    /// Name the package is installed under when declared as an npm alias
+    /// (`"pkg": "npm:real-pkg@^1.0.0"`); `name` is then the real package
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+
+    /// Where the manifest obtains the package from (registry unless declared otherwise)
+    #[serde(default, skip_serializing_if = "SourceKind::is_registry")]
+    pub source_kind: SourceKind,
+
+    /// URL the lockfile resolved the package from (registry tarball or git URL)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved: Option<String>,
+
+    /// Integrity hash or checksum as written in the lockfile (space-separated when several)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub integrity: Option<String>,
+
+    /// Names of the packages this locked package depends on (lockfiles only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<Symbol>,
+
+    /// Where in the source file the dependency is declared or locked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<SourceSpan>,
+}
+
+/// Location of a declaration in its source file
+///
+/// Offsets are bytes from the start of the file, the line is 1-based.
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub struct SourceSpan {
+    /// Offset of the first byte of the declaration
+    pub start: u32,
+
+    /// Offset just past the declaration
+    pub end: u32,
+
+    /// Line the declaration starts on
+    pub line: u32,
+}
+
+/// Where a declared dependency is obtained from
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SourceKind {
+    /// Package registry (npm, PyPI, crates.io or a mirror)
+    #[default]
+    Registry,
+    /// Git repository, optionally pinned to a commit, tag or branch
+    Git {
+        /// Repository URL
+        url: String,
+        /// Commit, tag or branch
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        rev: Option<String>,
+    },
+    /// Local directory or archive
+    Path {
+        /// Path as written in the manifest
+        path: String,
+    },
+    /// Direct download URL of an archive
+    Url {
+        /// Archive URL
+        url: String,
+    },
+    /// Resolved through the workspace: a member package (`workspace:*`) or a
+    /// dependency inherited from the workspace root (`workspace = true`)
+    Workspace,
+}
+
+impl SourceKind {
+    /// Whether the package comes from a registry
+    pub fn is_registry(&self) -> bool {
+        matches!(self, SourceKind::Registry)
+    }
+}
+
+impl std::fmt::Display for SourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SourceKind::Registry => write!(f, "registry"),
+            SourceKind::Git {
+                url,
+                rev: Some(rev),
+            } => write!(f, "git+{}#{}", url, rev),
+            SourceKind::Git { url, rev: None } => write!(f, "git+{}", url),
+            SourceKind::Path { path } => write!(f, "path:{}", path),
+            SourceKind::Url { url } => write!(f, "{}", url),
+            SourceKind::Workspace => write!(f, "workspace"),
+        }
+    }
 }
 
 /// Type of dependency
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub enum DependencyType {
     /// Runtime/production dependency
     Runtime,
@@ -38,6 +136,9 @@ pub enum DependencyType {
     Optional,
     /// Build dependency
     Build,
+    /// Version forced by an override (npm `overrides`, Yarn `resolutions`,
+    /// pnpm `pnpm.overrides`)
+    Override,
 }
 
 impl std::fmt::Display for DependencyType {
@@ -48,12 +149,15 @@ impl std::fmt::Display for DependencyType {
             DependencyType::Peer => write!(f, "peer"),
             DependencyType::Optional => write!(f, "optional"),
             DependencyType::Build => write!(f, "build"),
+            DependencyType::Override => write!(f, "override"),
         }
     }
 }
 
 /// Package ecosystem
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
 pub enum Ecosystem {
     /// Node.js/npm/yarn/pnpm
     Node,
@@ -61,6 +165,8 @@ pub enum Ecosystem {
     Python,
     /// Rust/Cargo
     Rust,
+    /// Operating system packages (dpkg, rpm, Homebrew)
+    Os,
 }
 
 impl std::fmt::Display for Ecosystem {
@@ -69,12 +175,27 @@ impl std::fmt::Display for Ecosystem {
             Ecosystem::Node => write!(f, "node"),
             Ecosystem::Python => write!(f, "python"),
             Ecosystem::Rust => write!(f, "rust"),
+            Ecosystem::Os => write!(f, "os"),
+        }
+    }
+}
+
+impl std::str::FromStr for Ecosystem {
+    type Err = ScanError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "node" => Ok(Ecosystem::Node),
+            "python" => Ok(Ecosystem::Python),
+            "rust" => Ok(Ecosystem::Rust),
+            "os" => Ok(Ecosystem::Os),
+            _ => Err(ScanError::UnknownEcosystem(s.to_string())),
         }
     }
 }
 
 /// File type classification
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub enum FileType {
     /// Manifest file (declared dependencies)
     Manifest,
@@ -2,10 +2,11 @@
 
 use super::application::Application;
 use super::classification::Classification;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// A node in the dependency tree
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DependencyNode {
     /// Package name
     pub name: String,
@@ -21,6 +22,15 @@ pub struct DependencyNode {
 
     /// Whether this is a direct dependency of the application
     pub is_direct: bool,
+
+    /// Whether the children were omitted because the package is expanded
+    /// earlier in the tree
+    #[serde(default)]
+    pub duplicate: bool,
+
+    /// Whether the children were omitted because of the depth limit
+    #[serde(default)]
+    pub truncated: bool,
 }
 
 impl DependencyNode {
@@ -37,6 +47,8 @@ impl DependencyNode {
             classification,
             dependencies: Vec::new(),
             is_direct,
+            duplicate: false,
+            truncated: false,
         }
     }
 
@@ -74,6 +86,11 @@ impl DependencyNode {
         None
     }
 
+    /// Sort children by name and version, recursively
+    pub fn sort(&mut self) {
+        sort_nodes(&mut self.dependencies);
+    }
+
     /// Get the depth of this node in the tree
     pub fn max_depth(&self) -> usize {
         if self.dependencies.is_empty() {
@@ -90,7 +107,7 @@ impl DependencyNode {
 }
 
 /// A complete dependency tree for an application
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DependencyTree {
     /// Root application
     pub application: Application,
@@ -141,6 +158,20 @@ impl DependencyTree {
     pub fn max_depth(&self) -> usize {
         self.roots.iter().map(|r| r.max_depth()).max().unwrap_or(0)
     }
+
+    /// Sort the application's dependencies and the nodes of the tree
+    pub fn sort(&mut self) {
+        self.application.sort_dependencies();
+        sort_nodes(&mut self.roots);
+    }
+}
+
+/// Sort nodes by name and version, recursively
+fn sort_nodes(nodes: &mut [DependencyNode]) {
+    nodes.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+    for node in nodes {
+        node.sort();
+    }
 }
 
 #[cfg(test)]
@@ -1,6 +1,10 @@
 //! Error types for the scanner
 
+use crate::models::Ecosystem;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Scanner error types
@@ -22,6 +26,90 @@ pub enum ScanError {
     #[error("Version parse error: {0}")]
     VersionParse(String),
 
+    /// Invalid glob pattern
+    #[error("Invalid glob pattern '{pattern}': {message}")]
+    InvalidGlob { pattern: String, message: String },
+
+    /// Invalid logging configuration
+    #[error("Invalid logging configuration: {0}")]
+    InvalidLogConfig(String),
+
+    /// Unknown ecosystem name
+    #[error("Unknown ecosystem: {0}. Use: node, python, rust, or os")]
+    UnknownEcosystem(String),
+
+    /// Unknown severity name
+    #[error("Unknown severity: {0}. Use: low, medium, high, or critical")]
+    UnknownSeverity(String),
+
+    /// Unknown tree charset name
+    #[error("Unknown tree charset: {0}. Use: utf8 or ascii")]
+    UnknownCharset(String),
+
+    /// Unknown requirements file mode
+    #[error("Unknown requirements mode: {0}. Use: auto, manifest, or lockfile")]
+    UnknownRequirementsMode(String),
+
+    /// Unknown results split
+    #[error("Unknown split: {0}. Use: by-application or by-ecosystem")]
+    UnknownSplit(String),
+
+    /// Unknown CSV column name
+    #[error("Unknown column: {name}. Use: {valid}")]
+    UnknownColumn { name: String, valid: String },
+
+    /// Unknown syslog event field name
+    #[error("Unknown syslog field: {name}. Use: {valid}")]
+    UnknownSyslogField { name: String, valid: String },
+
+    /// Unknown syslog facility name
+    #[error(
+        "Unknown syslog facility: {0}. Use: user, daemon, auth, authpriv, or local0 to local7"
+    )]
+    UnknownFacility(String),
+
+    /// Invalid syslog target
+    #[error(
+        "Invalid syslog target: {0}. Use: syslog, journald, udp://host[:port], or a socket path"
+    )]
+    InvalidSyslogTarget(String),
+
+    /// Unknown output schema name
+    #[error("Unknown schema: {name}. Use: {valid}")]
+    UnknownSchema { name: String, valid: String },
+
+    /// Invalid failure policy
+    #[error("Invalid failure policy: {0}")]
+    InvalidPolicy(String),
+
+    /// A parser ran longer than the parse timeout
+    #[error("Parsing {file:?} timed out after {}s", timeout.as_secs())]
+    Timeout { file: PathBuf, timeout: Duration },
+
+    /// A package file exceeded the size limit and was not read
+    #[error("Skipped {file:?}: too large, over the {limit}-byte limit")]
+    TooLarge { file: PathBuf, limit: u64 },
+
+    /// A parser panicked
+    #[error("Parser panicked on {file:?}: {message}")]
+    Panic { file: PathBuf, message: String },
+
+    /// A package registry request failed
+    #[error("Registry request to {url} failed: {message}")]
+    Registry { url: String, message: String },
+
+    /// A git command failed
+    #[error("Git error: {0}")]
+    Git(String),
+
+    /// A snapshot archive could not be read
+    #[error("Archive error: {0}")]
+    Archive(String),
+
+    /// A container runtime could not be queried
+    #[error("Container runtime error: {0}")]
+    Container(String),
+
     /// JSON parsing error
     #[error("JSON parse error in {file:?}: {source}")]
     Json {
@@ -45,6 +133,21 @@ pub enum ScanError {
 }
 
 impl ScanError {
+    /// Get the kind of failure for error reporting
+    pub fn kind(&self) -> ScanErrorKind {
+        match self {
+            ScanError::Io(_)
+            | ScanError::Registry { .. }
+            | ScanError::Git(_)
+            | ScanError::Archive(_)
+            | ScanError::Container(_)
+            | ScanError::TooLarge { .. } => ScanErrorKind::Read,
+            ScanError::Timeout { .. } => ScanErrorKind::Timeout,
+            ScanError::Panic { .. } => ScanErrorKind::Panic,
+            _ => ScanErrorKind::Parse,
+        }
+    }
+
     /// Create a parse error
     pub fn parse_error(file: PathBuf, message: impl Into<String>) -> Self {
         ScanError::Parse {
@@ -68,3 +171,88 @@ impl ScanError {
         ScanError::Yaml { file, source }
     }
 }
+
+/// Kind of failure recorded during a scan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanErrorKind {
+    /// The file or directory could not be read
+    Read,
+    /// The content could not be parsed
+    Parse,
+    /// Parsing exceeded the parse timeout
+    Timeout,
+    /// The parser panicked
+    Panic,
+}
+
+impl std::fmt::Display for ScanErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScanErrorKind::Read => write!(f, "read"),
+            ScanErrorKind::Parse => write!(f, "parse"),
+            ScanErrorKind::Timeout => write!(f, "timeout"),
+            ScanErrorKind::Panic => write!(f, "panic"),
+        }
+    }
+}
+
+/// A failure recorded during a scan, reported with the results
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ScanErrorEntry {
+    /// File or installation directory that failed
+    pub file: PathBuf,
+
+    /// Ecosystem of the file, if known
+    pub ecosystem: Option<Ecosystem>,
+
+    /// Kind of failure
+    pub kind: ScanErrorKind,
+
+    /// Human-readable reason
+    pub reason: String,
+}
+
+impl ScanErrorEntry {
+    /// Record a scan error for a file
+    pub fn new(file: PathBuf, ecosystem: Option<Ecosystem>, error: &ScanError) -> Self {
+        Self {
+            file,
+            ecosystem,
+            kind: error.kind(),
+            reason: error.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_error_entry_kind() {
+        let io_error = ScanError::Io(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "denied",
+        ));
+        let entry = ScanErrorEntry::new(PathBuf::from("/app/package.json"), None, &io_error);
+        assert_eq!(entry.kind, ScanErrorKind::Read);
+
+        let parse_error = ScanError::parse_error(PathBuf::from("/app/poetry.lock"), "bad");
+        let entry = ScanErrorEntry::new(
+            PathBuf::from("/app/poetry.lock"),
+            Some(Ecosystem::Python),
+            &parse_error,
+        );
+        assert_eq!(entry.kind, ScanErrorKind::Parse);
+        assert!(entry.reason.contains("bad"));
+
+        let timeout = ScanError::Timeout {
+            file: PathBuf::from("/app/yarn.lock"),
+            timeout: Duration::from_secs(60),
+        };
+        let entry = ScanErrorEntry::new(PathBuf::from("/app/yarn.lock"), None, &timeout);
+        assert_eq!(entry.kind, ScanErrorKind::Timeout);
+        assert!(entry.reason.contains("60s"));
+    }
+}
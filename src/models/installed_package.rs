@@ -2,6 +2,7 @@
 
 use super::dependency::Ecosystem;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 /// A dependency specification (name and version constraint)
@@ -24,6 +25,21 @@ impl DependencySpec {
     }
 }
 
+/// A peer dependency: a package the dependent expects its host application
+/// to provide (npm `peerDependencies`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PeerDependency {
+    /// Peer package name
+    pub name: String,
+
+    /// Version constraint the peer must satisfy
+    pub version_constraint: String,
+
+    /// The peer may be absent (`peerDependenciesMeta.<name>.optional`)
+    #[serde(default)]
+    pub optional: bool,
+}
+
 /// An installed package found in the filesystem
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InstalledPackage {
@@ -41,6 +57,42 @@ pub struct InstalledPackage {
 
     /// Direct dependencies declared by this package
     pub dependencies: Vec<DependencySpec>,
+
+    /// Packages this package expects the application to provide
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub peer_dependencies: Vec<PeerDependency>,
+
+    /// Integrity hash recorded by the package manager at install time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub integrity: Option<String>,
+
+    /// Installed files that no longer match the hashes recorded at install time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modified_files: Vec<PathBuf>,
+
+    /// Runtimes the package supports, e.g. `node` → `>=18` (npm `engines`,
+    /// or `python` from Python `Requires-Python`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub engines: BTreeMap<String, String>,
+
+    /// Lifecycle scripts run when the package is installed (`preinstall`,
+    /// `install`, `postinstall`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub install_scripts: Vec<String>,
+
+    /// Compiled extension modules and shared libraries, relative to the
+    /// installation directory (Python, from RECORD)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub native_extensions: Vec<String>,
+
+    /// Console and GUI commands the package installs (Python `entry_points.txt`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entry_points: Vec<String>,
+
+    /// License the package declares (npm `license`, Python
+    /// `License-Expression` or `License`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
 }
 
 impl InstalledPackage {
@@ -52,6 +104,14 @@ impl InstalledPackage {
             path,
             ecosystem,
             dependencies: Vec::new(),
+            peer_dependencies: Vec::new(),
+            integrity: None,
+            modified_files: Vec::new(),
+            engines: BTreeMap::new(),
+            install_scripts: Vec::new(),
+            native_extensions: Vec::new(),
+            entry_points: Vec::new(),
+            license: None,
         }
     }
 
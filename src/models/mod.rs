@@ -2,16 +2,30 @@
 
 pub mod application;
 pub mod classification;
+pub mod container;
 pub mod dependency;
 pub mod dependency_tree;
 pub mod error;
 pub mod installed_package;
+pub mod provided_package;
+pub mod purl;
+pub mod scan_metadata;
 pub mod scan_result;
+pub mod scan_summary;
+pub mod symbols;
 
 pub use application::Application;
-pub use classification::{Classification, ClassifiedDependency};
-pub use dependency::{DependencyRecord, DependencyType, Ecosystem, FileType};
+pub use classification::{Classification, ClassifiedDependency, RiskFlag};
+pub use container::ContainerRef;
+pub use dependency::{
+    DependencyRecord, DependencyType, Ecosystem, FileType, SourceKind, SourceSpan,
+};
 pub use dependency_tree::{DependencyNode, DependencyTree};
-pub use error::ScanError;
-pub use installed_package::{DependencySpec, InstalledPackage};
+pub use error::{ScanError, ScanErrorEntry, ScanErrorKind};
+pub use installed_package::{DependencySpec, InstalledPackage, PeerDependency};
+pub use provided_package::ProvidedPackage;
+pub use purl::purl;
+pub use scan_metadata::{ScanLimit, ScanMetadata};
 pub use scan_result::ScanResult;
+pub use scan_summary::{EcosystemStatus, EcosystemSummary, PhaseDuration, ScanSummary};
+pub use symbols::{Symbol, Symbols};
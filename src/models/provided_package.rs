@@ -0,0 +1,80 @@
+//! Packages reported by package providers
+
+use super::classification::Classification;
+use super::dependency::Ecosystem;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A package reported by a [`PackageProvider`](crate::parsers::PackageProvider)
+/// rather than found in the scanned tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvidedPackage {
+    /// Package name
+    pub name: String,
+
+    /// Installed or locked version
+    pub version: String,
+
+    /// Ecosystem
+    pub ecosystem: Ecosystem,
+
+    /// Has for installed packages, Should for locked ones
+    pub classification: Classification,
+
+    /// Where the package is installed or recorded, e.g. an installation path
+    /// or a location in the provider's store
+    pub location: PathBuf,
+
+    /// Integrity hash of the package
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub integrity: Option<String>,
+
+    /// Names of the package's direct dependencies
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
+
+    /// Name of the provider that reported the package, set by the scanner
+    #[serde(default)]
+    pub provenance: String,
+}
+
+impl ProvidedPackage {
+    /// Create a package installed at `location` (HAS)
+    pub fn installed(
+        name: impl Into<String>,
+        version: impl Into<String>,
+        ecosystem: Ecosystem,
+        location: impl Into<PathBuf>,
+    ) -> Self {
+        Self::new(name, version, ecosystem, Classification::Has, location)
+    }
+
+    /// Create a package locked at `location` (SHOULD)
+    pub fn locked(
+        name: impl Into<String>,
+        version: impl Into<String>,
+        ecosystem: Ecosystem,
+        location: impl Into<PathBuf>,
+    ) -> Self {
+        Self::new(name, version, ecosystem, Classification::Should, location)
+    }
+
+    fn new(
+        name: impl Into<String>,
+        version: impl Into<String>,
+        ecosystem: Ecosystem,
+        classification: Classification,
+        location: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            version: version.into(),
+            ecosystem,
+            classification,
+            location: location.into(),
+            integrity: None,
+            dependencies: Vec::new(),
+            provenance: String::new(),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Package URL (purl) generation
+//!
+//! Generates canonical purls (`pkg:npm/...`, `pkg:pypi/...`, `pkg:cargo/...`)
+//! following the [purl specification](https://github.com/package-url/purl-spec)
+//! so results can be joined with SBOMs and vulnerability databases.
+
+use super::dependency::Ecosystem;
+
+/// purl type for an ecosystem
+pub fn purl_type(ecosystem: Ecosystem) -> &'static str {
+    match ecosystem {
+        Ecosystem::Node => "npm",
+        Ecosystem::Python => "pypi",
+        Ecosystem::Rust => "cargo",
+        // The distribution is not recorded, so deb/rpm namespaces are unknown
+        Ecosystem::Os => "generic",
+    }
+}
+
+/// Build a purl for a package, with an optional exact version
+///
+/// npm scopes become the namespace (`@types/node` → `pkg:npm/%40types/node`)
+/// and PyPI names are normalized (`Flask_Login` → `pkg:pypi/flask-login`).
+pub fn purl(ecosystem: Ecosystem, name: &str, version: Option<&str>) -> String {
+    let path = match ecosystem {
+        Ecosystem::Node => match name.split_once('/') {
+            Some((scope, package)) if scope.starts_with('@') => {
+                format!("{}/{}", percent_encode(scope), percent_encode(package))
+            }
+            _ => percent_encode(name),
+        },
+        Ecosystem::Python => percent_encode(&normalize_pypi_name(name)),
+        Ecosystem::Rust | Ecosystem::Os => percent_encode(name),
+    };
+
+    match version {
+        Some(version) if !version.is_empty() => format!(
+            "pkg:{}/{}@{}",
+            purl_type(ecosystem),
+            path,
+            percent_encode(version)
+        ),
+        _ => format!("pkg:{}/{}", purl_type(ecosystem), path),
+    }
+}
+
+/// Normalize a PyPI project name (lowercase, runs of `-_.` become `-`)
+pub(crate) fn normalize_pypi_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    let mut in_separator = false;
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !in_separator {
+                normalized.push('-');
+            }
+            in_separator = true;
+        } else {
+            normalized.extend(c.to_lowercase());
+            in_separator = false;
+        }
+    }
+    normalized
+}
+
+/// Percent-encode everything except unreserved characters
+fn percent_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'.' | b'-' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Classification, ClassifiedDependency};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_purl() {
+        assert_eq!(
+            purl(Ecosystem::Node, "@types/node", Some("20.1.0")),
+            "pkg:npm/%40types/node@20.1.0"
+        );
+        assert_eq!(purl(Ecosystem::Node, "lodash", None), "pkg:npm/lodash");
+        assert_eq!(
+            purl(Ecosystem::Python, "Flask_Login", Some("0.6.3")),
+            "pkg:pypi/flask-login@0.6.3"
+        );
+        assert_eq!(
+            purl(Ecosystem::Rust, "serde", Some("1.0.0+build")),
+            "pkg:cargo/serde@1.0.0%2Bbuild"
+        );
+    }
+
+    #[test]
+    fn test_dependency_purl_uses_exact_version() {
+        let mut dep = ClassifiedDependency::new("requests".to_string(), Ecosystem::Python);
+        dep.add_classification(
+            Classification::Can,
+            ">=2.0".to_string(),
+            PathBuf::from("/app/requirements.txt"),
+        );
+        assert_eq!(dep.purl(), "pkg:pypi/requests");
+
+        dep.add_classification(
+            Classification::Should,
+            "2.31.0".to_string(),
+            PathBuf::from("/app/poetry.lock"),
+        );
+        assert_eq!(dep.purl(), "pkg:pypi/requests@2.31.0");
+    }
+}
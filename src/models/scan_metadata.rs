@@ -0,0 +1,198 @@
+//! Scan metadata carried by every output document
+//!
+//! Results collected from many hosts are only comparable when each file says
+//! where, when and how it was produced. [`ScanMetadata`] records the scanner
+//! version, host, scan root, command line, start and end time and file
+//! counts; the JSON documents carry it as `metadata` and the summary CSV as
+//! `metadata` rows.
+//!
+//! When `SOURCE_DATE_EPOCH` is set, both times are taken from it instead of
+//! the clock, so repeated scans of an unchanged tree write identical files.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Where, when and how a scan ran
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ScanMetadata {
+    /// Scanner version
+    pub scanner_version: String,
+
+    /// Host name, when it can be determined
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+
+    /// Scanned directory (the first one for scans of several roots)
+    pub scan_root: PathBuf,
+
+    /// All scanned directories, for scans of several roots
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scan_roots: Vec<PathBuf>,
+
+    /// Git commit the package files were read from, for scans of a
+    /// revision rather than the worktree
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_rev: Option<String>,
+
+    /// Command line arguments (empty for library scans)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub arguments: Vec<String>,
+
+    /// Scan start, RFC 3339 UTC
+    pub started_at: String,
+
+    /// Scan end, RFC 3339 UTC
+    pub finished_at: String,
+
+    /// Manifests and lockfiles parsed
+    pub files_scanned: usize,
+
+    /// Installation directories parsed
+    pub install_dirs_scanned: usize,
+
+    /// Package files and installation directories that failed to parse
+    pub parse_errors: usize,
+
+    /// Scan limits that were reached; the results are partial when any was
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub truncated: Vec<ScanLimit>,
+}
+
+/// A scan limit cutting a scan short
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanLimit {
+    /// Maximum number of manifests and lockfiles
+    MaxFiles,
+    /// Maximum number of installation directories
+    MaxInstallDirs,
+    /// Maximum number of packages per installation directory
+    MaxPackagesPerDir,
+    /// Time budget of the whole scan
+    TimeBudget,
+    /// The scan was cancelled (e.g. interrupted with Ctrl+C) and stopped early
+    Cancelled,
+}
+
+impl std::fmt::Display for ScanLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScanLimit::MaxFiles => write!(f, "max_files"),
+            ScanLimit::MaxInstallDirs => write!(f, "max_install_dirs"),
+            ScanLimit::MaxPackagesPerDir => write!(f, "max_packages_per_dir"),
+            ScanLimit::TimeBudget => write!(f, "time_budget"),
+            ScanLimit::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl ScanMetadata {
+    /// Metadata of a scan of `scan_root` that started at `started_at`
+    /// and ends now
+    pub fn new(scan_root: &Path, started_at: SystemTime) -> Self {
+        Self {
+            scanner_version: env!("CARGO_PKG_VERSION").to_string(),
+            hostname: hostname(),
+            scan_root: fs::canonicalize(scan_root).unwrap_or_else(|_| scan_root.to_path_buf()),
+            scan_roots: Vec::new(),
+            git_rev: None,
+            arguments: Vec::new(),
+            started_at: rfc3339(source_date_epoch().unwrap_or(started_at)),
+            finished_at: rfc3339(source_date_epoch().unwrap_or_else(SystemTime::now)),
+            files_scanned: 0,
+            install_dirs_scanned: 0,
+            parse_errors: 0,
+            truncated: Vec::new(),
+        }
+    }
+
+    /// Whether a scan limit cut the scan short
+    pub fn is_truncated(&self) -> bool {
+        !self.truncated.is_empty()
+    }
+
+    /// Record the command line the scan was run with
+    pub fn with_arguments(mut self, arguments: Vec<String>) -> Self {
+        self.arguments = arguments;
+        self
+    }
+}
+
+/// Fixed time from `SOURCE_DATE_EPOCH`, in seconds since the Unix epoch
+fn source_date_epoch() -> Option<SystemTime> {
+    let seconds = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+/// Host name from the kernel or the environment
+fn hostname() -> Option<String> {
+    fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| fs::read_to_string("/etc/hostname"))
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// Format a time as an RFC 3339 UTC timestamp with second precision
+pub fn rfc3339(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs();
+    let (days, rest) = (seconds / 86_400, seconds % 86_400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc3339() {
+        assert_eq!(rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            rfc3339(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+            "2000-02-29T00:00:00Z"
+        );
+        assert_eq!(
+            rfc3339(UNIX_EPOCH + Duration::from_secs(1_791_035_999)),
+            "2026-10-03T13:59:59Z"
+        );
+    }
+}
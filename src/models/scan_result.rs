@@ -51,7 +51,11 @@ impl ScanResult {
 
     /// Get unique package names
     pub fn unique_packages(&self) -> Vec<String> {
-        let mut packages: Vec<String> = self.dependencies.iter().map(|d| d.name.clone()).collect();
+        let mut packages: Vec<String> = self
+            .dependencies
+            .iter()
+            .map(|d| d.name.to_string())
+            .collect();
         packages.sort();
         packages.dedup();
         packages
@@ -0,0 +1,250 @@
+//! Scan summary statistics
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Duration and item count of one scan phase
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PhaseDuration {
+    /// Phase key (e.g. `discovery`, `declared_parsing`)
+    pub phase: String,
+
+    /// Number of work items processed
+    pub items: usize,
+
+    /// Wall-clock time in milliseconds
+    pub elapsed_ms: u64,
+}
+
+/// How the scan of one ecosystem ended
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum EcosystemStatus {
+    /// Parsed and analyzed
+    #[default]
+    Completed,
+    /// Not started: the time budget ran out or the scan was cancelled first
+    Skipped,
+    /// Aborted by a crash; the other ecosystems' results are kept
+    Failed,
+}
+
+impl fmt::Display for EcosystemStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EcosystemStatus::Completed => write!(f, "completed"),
+            EcosystemStatus::Skipped => write!(f, "skipped"),
+            EcosystemStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// Outcome and counts of the scan of one ecosystem
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct EcosystemSummary {
+    /// How the ecosystem's scan ended
+    pub status: EcosystemStatus,
+
+    /// Why it failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// Manifests and lockfiles of the ecosystem
+    pub files_scanned: usize,
+
+    /// Installation directories of the ecosystem
+    pub install_dirs_scanned: usize,
+
+    /// Classified dependency entries
+    pub total_dependencies: usize,
+
+    /// Package files and installation directories that failed to parse
+    pub parse_errors: usize,
+
+    /// Time spent parsing and analyzing the ecosystem, in milliseconds
+    pub elapsed_ms: u64,
+}
+
+impl EcosystemSummary {
+    /// Combine with the summary of the same ecosystem under another root,
+    /// scanned concurrently: counts add up, the worse status wins and the
+    /// time is the longer of the two
+    pub fn merge(&mut self, other: &EcosystemSummary) {
+        if other.status > self.status {
+            self.status = other.status;
+        }
+        if self.error.is_none() {
+            self.error = other.error.clone();
+        }
+        self.files_scanned += other.files_scanned;
+        self.install_dirs_scanned += other.install_dirs_scanned;
+        self.total_dependencies += other.total_dependencies;
+        self.parse_errors += other.parse_errors;
+        self.elapsed_ms = self.elapsed_ms.max(other.elapsed_ms);
+    }
+}
+
+/// Aggregate statistics for a scan
+///
+/// Count maps are keyed by the display form of the counted value (`node`,
+/// `HAS`, `runtime`, `INFECTED`, ...) and ordered by key.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ScanSummary {
+    /// Manifests and lockfiles parsed
+    pub files_scanned: usize,
+
+    /// Installation directories parsed
+    pub install_dirs_scanned: usize,
+
+    /// Classified dependency entries
+    pub total_dependencies: usize,
+
+    /// Applications the dependencies were linked to
+    pub applications: usize,
+
+    /// Dependency entries per ecosystem
+    pub by_ecosystem: BTreeMap<String, usize>,
+
+    /// Dependency entries per classification (HAS/SHOULD/CAN)
+    pub by_classification: BTreeMap<String, usize>,
+
+    /// Declared dependency records per dependency type
+    pub by_dependency_type: BTreeMap<String, usize>,
+
+    /// Dependency entries per security status (empty without an infected list)
+    pub by_security_status: BTreeMap<String, usize>,
+
+    /// Installed entries per risk flag
+    #[serde(default)]
+    pub by_risk_flag: BTreeMap<String, usize>,
+
+    /// Entries whose installed version differs from the locked version
+    pub version_mismatches: usize,
+
+    /// Entries whose locked version violates the declared constraint
+    pub constraint_violations: usize,
+
+    /// Installed entries no manifest or lockfile of their application accounts for
+    pub phantom_dependencies: usize,
+
+    /// Locked or declared entries with no installed copy in their application
+    pub missing_installations: usize,
+
+    /// Installed entries whose integrity differs from the lockfile
+    pub integrity_mismatches: usize,
+
+    /// Installed packages with files modified since install
+    pub modified_packages: usize,
+
+    /// Installed packages loaded by running processes (with `--correlate-processes`)
+    #[serde(default)]
+    pub running_packages: usize,
+
+    /// Installed packages the application's code never reaches (with `--reachability`)
+    #[serde(default)]
+    pub unreachable_packages: usize,
+
+    /// Packages whose versions disagree across sources within an application
+    pub version_inconsistencies: usize,
+
+    /// Packages installed at several versions within an application
+    #[serde(default)]
+    pub multiple_versions: usize,
+
+    /// Peers of Node packages that are missing or do not match the required ranges
+    #[serde(default)]
+    pub peer_conflicts: usize,
+
+    /// Installed packages declaring they do not support a target runtime
+    #[serde(default)]
+    pub incompatible_engines: usize,
+
+    /// Lockfiles out of date with the manifest next to them
+    #[serde(default)]
+    pub stale_lockfiles: usize,
+
+    /// Package files and installation directories that failed to parse
+    pub parse_errors: usize,
+
+    /// Outcome of each ecosystem's part of the scan
+    #[serde(default)]
+    pub ecosystems: BTreeMap<String, EcosystemSummary>,
+
+    /// Per-phase timings, in execution order
+    pub durations: Vec<PhaseDuration>,
+
+    /// Total scan time in milliseconds
+    pub total_duration_ms: u64,
+}
+
+impl ScanSummary {
+    /// Create an empty summary
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Increment a count in one of the count maps
+    pub fn increment(counts: &mut BTreeMap<String, usize>, key: impl ToString) {
+        *counts.entry(key.to_string()).or_insert(0) += 1;
+    }
+
+    /// Record a completed phase
+    pub fn add_duration(&mut self, phase: impl Into<String>, items: usize, elapsed_ms: u64) {
+        self.durations.push(PhaseDuration {
+            phase: phase.into(),
+            items,
+            elapsed_ms,
+        });
+        self.total_duration_ms += elapsed_ms;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_increment_and_durations() {
+        let mut summary = ScanSummary::new();
+        ScanSummary::increment(&mut summary.by_ecosystem, "node");
+        ScanSummary::increment(&mut summary.by_ecosystem, "node");
+        ScanSummary::increment(&mut summary.by_ecosystem, "python");
+
+        summary.add_duration("discovery", 10, 120);
+        summary.add_duration("analysis", 4, 30);
+
+        assert_eq!(summary.by_ecosystem["node"], 2);
+        assert_eq!(summary.by_ecosystem["python"], 1);
+        assert_eq!(summary.durations.len(), 2);
+        assert_eq!(summary.total_duration_ms, 150);
+    }
+
+    #[test]
+    fn test_merge_ecosystem_summaries() {
+        let mut summary = EcosystemSummary {
+            files_scanned: 2,
+            elapsed_ms: 30,
+            ..EcosystemSummary::default()
+        };
+        summary.merge(&EcosystemSummary {
+            status: EcosystemStatus::Failed,
+            error: Some("panicked".to_string()),
+            files_scanned: 1,
+            elapsed_ms: 10,
+            ..EcosystemSummary::default()
+        });
+        summary.merge(&EcosystemSummary {
+            status: EcosystemStatus::Skipped,
+            ..EcosystemSummary::default()
+        });
+
+        assert_eq!(summary.status, EcosystemStatus::Failed);
+        assert_eq!(summary.error.as_deref(), Some("panicked"));
+        assert_eq!(summary.files_scanned, 3);
+        assert_eq!(summary.elapsed_ms, 30);
+    }
+}
@@ -0,0 +1,192 @@
+//! Interned strings for package names and versions
+//!
+//! Monorepo scans produce millions of [`DependencyRecord`]s that repeat the
+//! same few thousand names and versions. A [`Symbol`] is a cheaply cloned,
+//! shared string, and the [`Symbols`] table hands out one [`Symbol`] per
+//! distinct string, so repeated names and versions share a single allocation.
+//! Records of one file already share their source path.
+
+use super::DependencyRecord;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// A shared, immutable string
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Symbol(Arc<str>);
+
+impl Symbol {
+    /// The string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Symbol {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(s: &str) -> Self {
+        Symbol(Arc::from(s))
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(s: String) -> Self {
+        Symbol(Arc::from(s))
+    }
+}
+
+impl From<&String> for Symbol {
+    fn from(s: &String) -> Self {
+        Symbol(Arc::from(s.as_str()))
+    }
+}
+
+impl From<Symbol> for String {
+    fn from(symbol: Symbol) -> Self {
+        symbol.0.to_string()
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for Symbol {
+    fn eq(&self, other: &String) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl Serialize for Symbol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Symbol {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Symbol::from)
+    }
+}
+
+impl JsonSchema for Symbol {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        String::schema_name()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        String::json_schema(gen)
+    }
+}
+
+/// Interning table handing out one [`Symbol`] per distinct string
+#[derive(Debug, Default)]
+pub struct Symbols {
+    table: HashSet<Symbol>,
+}
+
+impl Symbols {
+    /// Create an empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the shared symbol for a string
+    pub fn intern(&mut self, s: &str) -> Symbol {
+        if let Some(symbol) = self.table.get(s) {
+            return symbol.clone();
+        }
+        let symbol = Symbol::from(s);
+        self.table.insert(symbol.clone());
+        symbol
+    }
+
+    /// Replace the names, versions and edges of records with shared symbols
+    pub fn intern_records(&mut self, records: &mut [DependencyRecord]) {
+        for record in records {
+            record.name = self.intern(&record.name);
+            record.version = self.intern(&record.version);
+            for dependency in &mut record.dependencies {
+                *dependency = self.intern(dependency);
+            }
+        }
+    }
+
+    /// Number of distinct strings
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    /// Whether the table is empty
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern_shares_allocations() {
+        let mut symbols = Symbols::new();
+        let a = symbols.intern("lodash");
+        let b = symbols.intern(&String::from("lodash"));
+
+        assert!(Arc::ptr_eq(&a.0, &b.0));
+        assert_eq!(a, "lodash");
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(serde_json::to_string(&a).unwrap(), "\"lodash\"");
+        let parsed: Symbol = serde_json::from_str("\"lodash\"").unwrap();
+        assert_eq!(parsed, a);
+    }
+}
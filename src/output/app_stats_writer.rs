@@ -0,0 +1,134 @@
+//! Per-application stats output
+//!
+//! The stats are written next to the results in the same format
+//! (`output.csv` → `output.app-stats.csv`). JSON keeps every duplicate with
+//! its locations; CSV has one row per application, listing duplicates as
+//! `name (version, version)` and subtrees as `name (size)`, separated by `; `.
+
+use super::schema::{AppStatsDocument, SCHEMA_VERSION};
+use super::sidecar_path;
+use crate::analyzer::ApplicationStats;
+use crate::models::ScanMetadata;
+use csv::Writer;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Get the application stats path for a results file
+pub fn app_stats_path(output_path: impl AsRef<Path>) -> PathBuf {
+    sidecar_path(output_path, "app-stats")
+}
+
+/// Write application stats as a JSON document
+pub fn write_app_stats_json(
+    stats: &[ApplicationStats],
+    metadata: Option<&ScanMetadata>,
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(&AppStatsDocument {
+        schema_version: SCHEMA_VERSION,
+        metadata: metadata.cloned(),
+        application_stats: stats.to_vec(),
+    })?;
+    let mut file = File::create(output_path)?;
+    file.write_all(json.as_bytes())?;
+    Ok(())
+}
+
+/// Write application stats as CSV rows, one per application
+pub fn write_app_stats_csv(
+    stats: &[ApplicationStats],
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let mut writer = Writer::from_path(output_path)?;
+    writer.write_record([
+        "application_name",
+        "application_root",
+        "ecosystem",
+        "total",
+        "direct",
+        "transitive",
+        "duplicate_packages",
+        "duplicates",
+        "heaviest_subtrees",
+    ])?;
+
+    for app in stats {
+        let duplicates: Vec<String> = app
+            .duplicates
+            .iter()
+            .map(|dup| {
+                let versions: Vec<&str> = dup.versions.iter().map(|v| v.version.as_str()).collect();
+                format!("{} ({})", dup.name, versions.join(", "))
+            })
+            .collect();
+        let subtrees: Vec<String> = app
+            .heaviest_subtrees
+            .iter()
+            .map(|subtree| format!("{} ({})", subtree.name, subtree.size))
+            .collect();
+        writer.write_record([
+            &app.application_name,
+            &app.application_root.to_string_lossy().to_string(),
+            &app.ecosystem.to_string(),
+            &app.total.to_string(),
+            &app.direct.to_string(),
+            &app.transitive.to_string(),
+            &app.duplicates.len().to_string(),
+            &duplicates.join("; "),
+            &subtrees.join("; "),
+        ])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{DuplicatePackage, DuplicateVersion, Subtree};
+    use crate::models::Ecosystem;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_app_stats() {
+        let temp_dir = TempDir::new().unwrap();
+        let version = |version: &str, path: &str| DuplicateVersion {
+            version: version.to_string(),
+            locations: vec![PathBuf::from(path)],
+        };
+        let stats = vec![ApplicationStats {
+            application_name: "web".to_string(),
+            application_root: PathBuf::from("/app"),
+            ecosystem: Ecosystem::Node,
+            total: 4,
+            direct: 2,
+            transitive: 2,
+            duplicates: vec![DuplicatePackage {
+                name: "qs".to_string(),
+                versions: vec![
+                    version("6.11.0", "/app/node_modules/qs"),
+                    version("6.5.3", "/app/node_modules/body-parser/node_modules/qs"),
+                ],
+            }],
+            heaviest_subtrees: vec![Subtree {
+                name: "express".to_string(),
+                version: Some("4.18.2".to_string()),
+                size: 2,
+            }],
+        }];
+
+        let csv_path = temp_dir.path().join("out.app-stats.csv");
+        write_app_stats_csv(&stats, &csv_path).unwrap();
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(content.contains("web,/app,node,4,2,2,1,\"qs (6.11.0, 6.5.3)\",express (2)\n"));
+
+        let json_path = temp_dir.path().join("out.app-stats.json");
+        write_app_stats_json(&stats, None, &json_path).unwrap();
+        let parsed: AppStatsDocument =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(parsed.schema_version, SCHEMA_VERSION);
+        assert_eq!(parsed.application_stats, stats);
+    }
+}
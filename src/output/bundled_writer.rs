@@ -0,0 +1,100 @@
+//! Bundled package output
+//!
+//! Vendored and bundled copies of packages are written next to the results in
+//! the same format (`output.csv` → `output.bundled.csv`). Unknown versions are
+//! empty in CSV.
+
+use super::schema::{BundledDocument, SCHEMA_VERSION};
+use super::sidecar_path;
+use crate::analyzer::BundledPackage;
+use crate::models::ScanMetadata;
+use csv::Writer;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Get the bundled packages path for a results file
+pub fn bundled_path(output_path: impl AsRef<Path>) -> PathBuf {
+    sidecar_path(output_path, "bundled")
+}
+
+/// Write bundled packages as a JSON document
+pub fn write_bundled_json(
+    bundled: &[BundledPackage],
+    metadata: Option<&ScanMetadata>,
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(&BundledDocument {
+        schema_version: SCHEMA_VERSION,
+        metadata: metadata.cloned(),
+        bundled: bundled.to_vec(),
+    })?;
+    let mut file = File::create(output_path)?;
+    file.write_all(json.as_bytes())?;
+    Ok(())
+}
+
+/// Write bundled packages as CSV rows
+pub fn write_bundled_csv(
+    bundled: &[BundledPackage],
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let mut writer = Writer::from_path(output_path)?;
+    writer.write_record(["package_name", "version", "ecosystem", "kind", "path"])?;
+
+    for package in bundled {
+        writer.write_record([
+            &package.name,
+            package.version.as_deref().unwrap_or(""),
+            &package.ecosystem.to_string(),
+            &package.kind.to_string(),
+            package.path.to_string_lossy().as_ref(),
+        ])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::BundleKind;
+    use crate::models::Ecosystem;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_bundled() {
+        let temp_dir = TempDir::new().unwrap();
+        let bundled = vec![
+            BundledPackage {
+                name: "jquery".to_string(),
+                version: Some("3.6.0".to_string()),
+                ecosystem: Ecosystem::Node,
+                kind: BundleKind::JsBanner,
+                path: PathBuf::from("/app/dist/app.js"),
+            },
+            BundledPackage {
+                name: "requests".to_string(),
+                version: None,
+                ecosystem: Ecosystem::Python,
+                kind: BundleKind::PythonVendor,
+                path: PathBuf::from("/app/pip/_vendor/requests"),
+            },
+        ];
+
+        let csv_path = temp_dir.path().join("out.bundled.csv");
+        write_bundled_csv(&bundled, &csv_path).unwrap();
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(content.starts_with("package_name,version,ecosystem,kind,path\n"));
+        assert!(content.contains("jquery,3.6.0,node,js_banner,/app/dist/app.js\n"));
+        assert!(content.contains("requests,,python,python_vendor,/app/pip/_vendor/requests\n"));
+
+        let json_path = temp_dir.path().join("out.bundled.json");
+        write_bundled_json(&bundled, None, &json_path).unwrap();
+        let parsed: BundledDocument =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(parsed.schema_version, SCHEMA_VERSION);
+        assert_eq!(parsed.bundled, bundled);
+    }
+}
@@ -0,0 +1,222 @@
+//! Reading previously written CSV results
+//!
+//! Rebuilds classified dependencies from the columns written by
+//! [`write_classified_csv_with_columns`](super::write_classified_csv_with_columns),
+//! so a scan can be re-filtered or exported again without rescanning.
+//! Columns are matched by header name; only `package_name` and `ecosystem`
+//! are required, so results written with `--columns` are read too. The CSV only records how many files were modified and how
+//! many dependencies a package has, so `modified_files` and `dependencies`
+//! come back empty. Security columns are not read, since they are recomputed
+//! from an infected list.
+
+use crate::models::{Classification, ClassifiedDependency, ContainerRef, ScanError};
+use csv::{Reader, StringRecord};
+use std::path::{Path, PathBuf};
+
+/// Read classified dependencies from a CSV results file
+pub fn read_classified_csv(path: impl AsRef<Path>) -> Result<Vec<ClassifiedDependency>, ScanError> {
+    let path = path.as_ref();
+    let csv_error = |e: csv::Error| ScanError::parse_error(path.to_path_buf(), e.to_string());
+
+    let mut reader = Reader::from_path(path).map_err(csv_error)?;
+    let headers = reader.headers().map_err(csv_error)?.clone();
+    let columns = Columns { headers: &headers };
+    for required in ["package_name", "ecosystem"] {
+        if columns.position(required).is_none() {
+            return Err(ScanError::parse_error(
+                path.to_path_buf(),
+                format!("results CSV has no '{}' column", required),
+            ));
+        }
+    }
+
+    let mut dependencies = Vec::new();
+    for (line, record) in reader.records().enumerate() {
+        let record = record.map_err(csv_error)?;
+        let field = |name: &str| columns.get(&record, name);
+        let ecosystem = field("ecosystem").parse().map_err(|_| {
+            ScanError::parse_error(
+                path.to_path_buf(),
+                format!(
+                    "Invalid ecosystem '{}' at line {}",
+                    field("ecosystem"),
+                    line + 2
+                ),
+            )
+        })?;
+
+        let mut dep = ClassifiedDependency::new(field("package_name").to_string(), ecosystem);
+        for (classification, label) in [
+            (Classification::Has, "has"),
+            (Classification::Should, "should"),
+            (Classification::Can, "can"),
+        ] {
+            let version = field(&format!("{}_version", label));
+            if !version.is_empty() {
+                dep.add_classification(
+                    classification,
+                    version.to_string(),
+                    PathBuf::from(field(&format!("{}_path", label))),
+                );
+            }
+        }
+        dep.installed_path = dep.get_source_file(Classification::Has).cloned();
+        dep.package_name_path = optional(field("package_name_path"));
+        dep.application_name = optional(field("application_name"));
+        dep.application_root = optional(field("application_root")).map(PathBuf::from);
+        dep.has_version_mismatch = field("version_mismatch") == "true";
+        dep.has_constraint_violation = field("constraint_violation") == "true";
+        dep.is_phantom = field("phantom") == "true";
+        dep.missing_installation = field("missing_installation") == "true";
+        dep.integrity_mismatch = field("integrity_mismatch") == "true";
+        dep.parent_package = optional(field("parent_package"));
+        dep.resolved = optional(field("resolved"));
+        dep.integrity = optional(field("integrity"));
+        dep.install_scripts = field("install_scripts")
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        dep.risk_flags = field("risk_flags")
+            .split_whitespace()
+            .filter_map(|flag| flag.parse().ok())
+            .collect();
+        dep.running_pids = field("running_pids")
+            .split_whitespace()
+            .filter_map(|pid| pid.parse().ok())
+            .collect();
+        dep.provenance = optional(field("provenance"));
+        dep.scan_root = optional(field("scan_root")).map(PathBuf::from);
+        dep.suppressed = optional(field("suppressed"));
+        dep.reachable = optional(field("reachable")).map(|reachable| reachable == "true");
+        dep.content_hash = optional(field("content_hash"));
+        dep.container = optional(field("container_id")).map(|id| ContainerRef {
+            id,
+            image: field("container_image").to_string(),
+            pod: optional(field("pod")),
+            labels: field("container_labels")
+                .split_whitespace()
+                .filter_map(|label| label.split_once('='))
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            ..ContainerRef::default()
+        });
+        dep.license = optional(field("license"));
+        dep.native_extensions = field("native_extensions")
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        dep.entry_points = field("entry_points")
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        dependencies.push(dep);
+    }
+    Ok(dependencies)
+}
+
+/// Header lookup for the columns of a results CSV
+struct Columns<'a> {
+    headers: &'a StringRecord,
+}
+
+impl Columns<'_> {
+    fn position(&self, name: &str) -> Option<usize> {
+        self.headers.iter().position(|h| h == name)
+    }
+
+    /// Value of a column, or an empty string if the column is missing
+    fn get<'r>(&self, record: &'r StringRecord, name: &str) -> &'r str {
+        self.position(name)
+            .and_then(|i| record.get(i))
+            .unwrap_or("")
+    }
+}
+
+fn optional(value: &str) -> Option<String> {
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Ecosystem;
+    use crate::output::write_classified_csv;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_read_classified_csv_round_trip() {
+        let mut dep = ClassifiedDependency::new("requests".to_string(), Ecosystem::Python);
+        dep.add_classification(
+            Classification::Has,
+            "2.31.0".to_string(),
+            PathBuf::from("/app/.venv/lib/requests-2.31.0.dist-info"),
+        );
+        dep.add_classification(
+            Classification::Should,
+            "2.32.0".to_string(),
+            PathBuf::from("/app/poetry.lock"),
+        );
+        dep.application_name = Some("api".to_string());
+        dep.application_root = Some(PathBuf::from("/app"));
+        dep.has_version_mismatch = true;
+        dep.scan_root = Some(PathBuf::from("/srv"));
+        dep.container = Some(ContainerRef {
+            id: "4f2a9c".to_string(),
+            image: "api:1.4".to_string(),
+            pod: Some("prod/api-7d9f".to_string()),
+            ..ContainerRef::default()
+        });
+        let temp_file = NamedTempFile::new().unwrap();
+        write_classified_csv(&[dep], temp_file.path()).unwrap();
+
+        let deps = read_classified_csv(temp_file.path()).unwrap();
+
+        assert_eq!(deps.len(), 1);
+        let dep = &deps[0];
+        assert_eq!(dep.name, "requests");
+        assert_eq!(dep.ecosystem, Ecosystem::Python);
+        assert_eq!(dep.get_version(Classification::Has), Some("2.31.0"));
+        assert_eq!(
+            dep.get_source_file(Classification::Should),
+            Some(&PathBuf::from("/app/poetry.lock"))
+        );
+        assert!(!dep.has_classification(Classification::Can));
+        assert_eq!(dep.application_name.as_deref(), Some("api"));
+        assert!(dep.has_version_mismatch);
+        assert!(!dep.is_phantom);
+        assert_eq!(dep.parent_package, None);
+        assert_eq!(dep.scan_root, Some(PathBuf::from("/srv")));
+        let container = dep.container.as_ref().unwrap();
+        assert_eq!(container.image, "api:1.4");
+        assert_eq!(container.pod.as_deref(), Some("prod/api-7d9f"));
+    }
+
+    #[test]
+    fn test_read_selected_columns() {
+        let temp_file = NamedTempFile::new().unwrap();
+        std::fs::write(
+            temp_file.path(),
+            "ecosystem,package_name,license,entry_points\npython,black,MIT,black blackd\n",
+        )
+        .unwrap();
+
+        let deps = read_classified_csv(temp_file.path()).unwrap();
+
+        assert_eq!(deps[0].name, "black");
+        assert_eq!(deps[0].license.as_deref(), Some("MIT"));
+        assert_eq!(deps[0].entry_points, vec!["black", "blackd"]);
+        assert_eq!(deps[0].application_root, None);
+    }
+
+    #[test]
+    fn test_read_classified_csv_invalid() {
+        let temp_file = NamedTempFile::new().unwrap();
+        std::fs::write(temp_file.path(), "package,version\nreact,18.2.0\n").unwrap();
+        let err = read_classified_csv(temp_file.path()).unwrap_err();
+        assert!(err.to_string().contains("package_name"));
+
+        std::fs::write(temp_file.path(), "package_name,ecosystem\nreact,cobol\n").unwrap();
+        let err = read_classified_csv(temp_file.path()).unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+}
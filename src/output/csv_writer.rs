@@ -1,9 +1,20 @@
 //! CSV output writer
+//!
+//! Classified dependencies are written through a table of [`CsvColumn`]s,
+//! each a header name and the function producing its cell. The default
+//! layout is the columns of [`DEFAULT_COLUMNS`], in that order, which
+//! [`read_classified_csv`](super::read_classified_csv) reads back; with
+//! `--columns` a list of names selects and orders the columns instead, from
+//! the default ones and the extra columns not written by default
+//! (`license`, `native_extensions`, `entry_points`).
 
 use csv::Writer;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use crate::models::{Classification, ClassifiedDependency, DependencyRecord};
+use crate::analyzer::vuln_filter::{InfectedPackage, InfectedPackageFilter};
+use crate::models::{
+    Classification, ClassifiedDependency, ContainerRef, DependencyRecord, ScanError,
+};
 
 /// Write dependency records to a CSV file (legacy format)
 pub fn write_csv(
@@ -49,107 +60,338 @@ pub fn write_classified_csv(
 /// Write classified dependencies to a CSV file with security status
 pub fn write_classified_csv_with_security(
     dependencies: &[ClassifiedDependency],
-    security_filter: Option<&crate::analyzer::InfectedPackageFilter>,
+    security_filter: Option<&InfectedPackageFilter>,
     output_path: impl AsRef<Path>,
 ) -> std::io::Result<()> {
-    let mut writer = Writer::from_path(output_path)?;
+    write_classified_csv_with_columns(
+        dependencies,
+        security_filter,
+        &default_columns(),
+        output_path,
+    )
+}
 
-    // Write header
-    writer.write_record([
-        "package_name",
-        "package_name_path",
-        "version",
-        "ecosystem",
-        "application_name",
-        "application_root",
-        "has_version",
-        "has_path",
-        "should_version",
-        "should_path",
-        "can_version",
-        "can_path",
-        "version_mismatch",
-        "constraint_violation",
-        "parent_package",
-        "is_direct",
-        "dependency_count",
-        "security",
-    ])?;
+/// Write classified dependencies to a CSV file with the given columns
+pub fn write_classified_csv_with_columns(
+    dependencies: &[ClassifiedDependency],
+    security_filter: Option<&InfectedPackageFilter>,
+    columns: &[&CsvColumn],
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let mut writer = Writer::from_path(output_path)?;
+    writer.write_record(columns.iter().map(|column| column.name))?;
 
-    // Write records
+    // Write records in output order
+    let mut dependencies: Vec<&ClassifiedDependency> = dependencies.iter().collect();
+    dependencies.sort_by(|a, b| a.output_order(b));
     for dep in dependencies {
-        let has_version = dep
-            .get_version(Classification::Has)
-            .unwrap_or("")
-            .to_string();
-        let has_path = dep
-            .get_source_file(Classification::Has)
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_default();
-
-        let should_version = dep
-            .get_version(Classification::Should)
-            .unwrap_or("")
-            .to_string();
-        let should_path = dep
-            .get_source_file(Classification::Should)
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_default();
-
-        let can_version = dep
-            .get_version(Classification::Can)
-            .unwrap_or("")
-            .to_string();
-        let can_path = dep
-            .get_source_file(Classification::Can)
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_default();
-
-        let application_name = dep.application_name.as_deref().unwrap_or("");
-        let application_root = dep
-            .application_root
-            .as_ref()
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_default();
-
-        let parent_package = dep.parent_package.as_deref().unwrap_or("");
-        let is_direct = if dep.parent_package.is_none() {
-            "true"
-        } else {
-            "false"
-        };
-
-        let security = if let Some(filter) = security_filter {
-            filter.get_security_status(dep).to_string()
-        } else {
-            "NONE".to_string()
-        };
-
-        let package_name_path = dep.package_name_path.as_deref().unwrap_or("");
-        let version = dep.get_primary_version().unwrap_or("");
-
-        writer.write_record([
-            &dep.name,
-            package_name_path,
-            version,
-            &dep.ecosystem.to_string(),
-            application_name,
-            &application_root,
-            &has_version,
-            &has_path,
-            &should_version,
-            &should_path,
-            &can_version,
-            &can_path,
-            &dep.has_version_mismatch.to_string(),
-            &dep.has_constraint_violation.to_string(),
-            parent_package,
-            is_direct,
-            &dep.dependencies.len().to_string(),
-            &security,
-        ])?;
+        writer.write_record(
+            columns
+                .iter()
+                .map(|column| (column.value)(dep, security_filter)),
+        )?;
     }
 
     writer.flush()?;
     Ok(())
 }
+
+/// A column of the classified CSV
+#[derive(Debug)]
+pub struct CsvColumn {
+    /// Header name
+    pub name: &'static str,
+
+    /// Cell of a dependency
+    value: fn(&ClassifiedDependency, Option<&InfectedPackageFilter>) -> String,
+}
+
+/// Columns written by default, in order
+pub const DEFAULT_COLUMNS: [&str; 43] = [
+    "package_name",
+    "package_name_path",
+    "version",
+    "ecosystem",
+    "application_name",
+    "application_root",
+    "has_version",
+    "has_path",
+    "should_version",
+    "should_path",
+    "should_line",
+    "can_version",
+    "can_path",
+    "can_line",
+    "version_mismatch",
+    "constraint_violation",
+    "phantom",
+    "missing_installation",
+    "integrity_mismatch",
+    "modified_files",
+    "parent_package",
+    "is_direct",
+    "dependency_count",
+    "security",
+    "severity",
+    "cvss",
+    "epss",
+    "advisories",
+    "recommended_version",
+    "purl",
+    "resolved",
+    "integrity",
+    "install_scripts",
+    "risk_flags",
+    "running_pids",
+    "provenance",
+    "scan_root",
+    "suppressed",
+    "reachable",
+    "content_hash",
+    "container_id",
+    "container_image",
+    "pod",
+];
+
+/// Other names accepted by `--columns`
+const ALIASES: [(&str, &str); 2] = [("name", "package_name"), ("advisory_ids", "advisories")];
+
+/// Every column of the classified CSV
+pub const COLUMNS: &[CsvColumn] = &[
+    column("package_name", |dep, _| dep.name.clone()),
+    column("package_name_path", |dep, _| text(&dep.package_name_path)),
+    column("version", |dep, _| {
+        dep.get_primary_version().unwrap_or("").to_string()
+    }),
+    column("ecosystem", |dep, _| dep.ecosystem.to_string()),
+    column("application_name", |dep, _| text(&dep.application_name)),
+    column("application_root", |dep, _| path(&dep.application_root)),
+    column("has_version", |dep, _| version(dep, Classification::Has)),
+    column("has_path", |dep, _| source(dep, Classification::Has)),
+    column("should_version", |dep, _| {
+        version(dep, Classification::Should)
+    }),
+    column("should_path", |dep, _| source(dep, Classification::Should)),
+    column("should_line", |dep, _| line(dep, Classification::Should)),
+    column("can_version", |dep, _| version(dep, Classification::Can)),
+    column("can_path", |dep, _| source(dep, Classification::Can)),
+    column("can_line", |dep, _| line(dep, Classification::Can)),
+    column("version_mismatch", |dep, _| {
+        dep.has_version_mismatch.to_string()
+    }),
+    column("constraint_violation", |dep, _| {
+        dep.has_constraint_violation.to_string()
+    }),
+    column("phantom", |dep, _| dep.is_phantom.to_string()),
+    column("missing_installation", |dep, _| {
+        dep.missing_installation.to_string()
+    }),
+    column("integrity_mismatch", |dep, _| {
+        dep.integrity_mismatch.to_string()
+    }),
+    column("modified_files", |dep, _| {
+        dep.modified_files.len().to_string()
+    }),
+    column("parent_package", |dep, _| text(&dep.parent_package)),
+    column("is_direct", |dep, _| {
+        dep.parent_package.is_none().to_string()
+    }),
+    column("dependency_count", |dep, _| {
+        dep.dependencies.len().to_string()
+    }),
+    column("security", |dep, filter| match filter {
+        Some(filter) => filter.get_security_status(dep).to_string(),
+        None => "NONE".to_string(),
+    }),
+    column("severity", |dep, filter| {
+        advisory(dep, filter, |a| a.severity.map(|s| s.to_string()))
+    }),
+    column("cvss", |dep, filter| {
+        advisory(dep, filter, |a| a.cvss.map(|s| s.to_string()))
+    }),
+    column("epss", |dep, filter| {
+        advisory(dep, filter, |a| a.epss.map(|p| p.to_string()))
+    }),
+    column("advisories", |dep, filter| {
+        advisory(dep, filter, |a| Some(a.ids.join(" ")))
+    }),
+    column("recommended_version", |dep, filter| {
+        filter
+            .and_then(|filter| filter.recommended_version(dep))
+            .unwrap_or_default()
+    }),
+    column("purl", |dep, _| dep.purl()),
+    column("resolved", |dep, _| text(&dep.resolved)),
+    column("integrity", |dep, _| text(&dep.integrity)),
+    column("install_scripts", |dep, _| dep.install_scripts.join(" ")),
+    column("risk_flags", |dep, _| join(&dep.risk_flags)),
+    column("running_pids", |dep, _| join(&dep.running_pids)),
+    column("provenance", |dep, _| text(&dep.provenance)),
+    column("scan_root", |dep, _| path(&dep.scan_root)),
+    column("suppressed", |dep, _| text(&dep.suppressed)),
+    column("reachable", |dep, _| {
+        dep.reachable.map(|r| r.to_string()).unwrap_or_default()
+    }),
+    column("content_hash", |dep, _| text(&dep.content_hash)),
+    column("container_id", |dep, _| container(dep, |c| c.id.clone())),
+    column("container_image", |dep, _| {
+        container(dep, |c| c.image.clone())
+    }),
+    column("pod", |dep, _| {
+        container(dep, |c| c.pod.clone().unwrap_or_default())
+    }),
+    column("container_labels", |dep, _| {
+        container(dep, ContainerRef::labels_text)
+    }),
+    column("license", |dep, _| text(&dep.license)),
+    column("native_extensions", |dep, _| {
+        dep.native_extensions.join(" ")
+    }),
+    column("entry_points", |dep, _| dep.entry_points.join(" ")),
+];
+
+/// The default columns, in order
+pub fn default_columns() -> Vec<&'static CsvColumn> {
+    DEFAULT_COLUMNS
+        .iter()
+        .filter_map(|name| find_column(name))
+        .collect()
+}
+
+/// Columns named by `--columns`, in the given order
+pub fn parse_columns<S: AsRef<str>>(names: &[S]) -> Result<Vec<&'static CsvColumn>, ScanError> {
+    names
+        .iter()
+        .map(|name| {
+            let name = name.as_ref().trim();
+            find_column(name).ok_or_else(|| ScanError::UnknownColumn {
+                name: name.to_string(),
+                valid: COLUMNS
+                    .iter()
+                    .map(|column| column.name)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            })
+        })
+        .collect()
+}
+
+fn find_column(name: &str) -> Option<&'static CsvColumn> {
+    let name = ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name, |(_, column)| column);
+    COLUMNS.iter().find(|column| column.name == name)
+}
+
+const fn column(
+    name: &'static str,
+    value: fn(&ClassifiedDependency, Option<&InfectedPackageFilter>) -> String,
+) -> CsvColumn {
+    CsvColumn { name, value }
+}
+
+fn text(value: &Option<String>) -> String {
+    value.clone().unwrap_or_default()
+}
+
+/// A field of the entry's container, or an empty cell
+fn container(dep: &ClassifiedDependency, field: impl Fn(&ContainerRef) -> String) -> String {
+    dep.container.as_ref().map(field).unwrap_or_default()
+}
+
+fn path(value: &Option<PathBuf>) -> String {
+    value
+        .as_ref()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn join<T: ToString>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn version(dep: &ClassifiedDependency, classification: Classification) -> String {
+    dep.get_version(classification).unwrap_or("").to_string()
+}
+
+fn source(dep: &ClassifiedDependency, classification: Classification) -> String {
+    dep.get_source_file(classification)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn line(dep: &ClassifiedDependency, classification: Classification) -> String {
+    dep.get_source_line(classification)
+        .map(|line| line.to_string())
+        .unwrap_or_default()
+}
+
+/// A field of the advisory matching a dependency, empty without one
+fn advisory(
+    dep: &ClassifiedDependency,
+    filter: Option<&InfectedPackageFilter>,
+    field: impl Fn(&InfectedPackage) -> Option<String>,
+) -> String {
+    filter
+        .and_then(|filter| filter.get_advisory(dep))
+        .and_then(field)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Ecosystem;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_default_columns() {
+        let columns = default_columns();
+        assert_eq!(columns.len(), DEFAULT_COLUMNS.len());
+        let names: Vec<&str> = columns.iter().map(|column| column.name).collect();
+        assert_eq!(names, DEFAULT_COLUMNS);
+        for (i, column) in COLUMNS.iter().enumerate() {
+            assert!(
+                COLUMNS[..i].iter().all(|other| other.name != column.name),
+                "duplicate column {}",
+                column.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_selected_columns() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("results.csv");
+        let mut dep = ClassifiedDependency::new("lodash".to_string(), Ecosystem::Node);
+        dep.add_classification(
+            Classification::Has,
+            "4.17.21".to_string(),
+            PathBuf::from("/app/node_modules/lodash"),
+        );
+        dep.license = Some("MIT".to_string());
+
+        let columns = parse_columns(&["purl", "name", " version", "license", "security"]).unwrap();
+        write_classified_csv_with_columns(&[dep], None, &columns, &path).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            content,
+            "purl,package_name,version,license,security\n\
+             pkg:npm/lodash@4.17.21,lodash,4.17.21,MIT,NONE\n"
+        );
+    }
+
+    #[test]
+    fn test_unknown_column() {
+        let err = parse_columns(&["name", "licence"]).unwrap_err();
+        let message = err.to_string();
+        assert!(message.starts_with("Unknown column: licence. Use: package_name, "));
+        assert!(message.ends_with(", license, native_extensions, entry_points"));
+    }
+}
@@ -0,0 +1,102 @@
+//! Runtime incompatibility output
+//!
+//! Installed packages declaring they do not support a target runtime are
+//! written next to the results in the same format
+//! (`output.csv` → `output.engines.csv`), one row per package and runtime.
+
+use super::schema::{EnginesDocument, SCHEMA_VERSION};
+use super::sidecar_path;
+use crate::analyzer::EngineIncompatibility;
+use crate::models::ScanMetadata;
+use csv::Writer;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Get the runtime incompatibility path for a results file
+pub fn engines_path(output_path: impl AsRef<Path>) -> PathBuf {
+    sidecar_path(output_path, "engines")
+}
+
+/// Write runtime incompatibilities as a JSON document
+pub fn write_engines_json(
+    incompatibilities: &[EngineIncompatibility],
+    metadata: Option<&ScanMetadata>,
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(&EnginesDocument {
+        schema_version: SCHEMA_VERSION,
+        metadata: metadata.cloned(),
+        incompatible_engines: incompatibilities.to_vec(),
+    })?;
+    let mut file = File::create(output_path)?;
+    file.write_all(json.as_bytes())?;
+    Ok(())
+}
+
+/// Write runtime incompatibilities as CSV rows
+pub fn write_engines_csv(
+    incompatibilities: &[EngineIncompatibility],
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let mut writer = Writer::from_path(output_path)?;
+    writer.write_record([
+        "package_name",
+        "version",
+        "ecosystem",
+        "path",
+        "runtime",
+        "required",
+        "target",
+    ])?;
+
+    for incompatibility in incompatibilities {
+        writer.write_record([
+            &incompatibility.name,
+            &incompatibility.version,
+            &incompatibility.ecosystem.to_string(),
+            incompatibility.path.to_string_lossy().as_ref(),
+            &incompatibility.runtime,
+            &incompatibility.required,
+            &incompatibility.target,
+        ])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Ecosystem;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_engines() {
+        let temp_dir = TempDir::new().unwrap();
+        let incompatibilities = vec![EngineIncompatibility {
+            name: "undici".to_string(),
+            version: "7.2.0".to_string(),
+            ecosystem: Ecosystem::Node,
+            path: PathBuf::from("/app/node_modules/undici"),
+            runtime: "node".to_string(),
+            required: ">=20.18.1".to_string(),
+            target: "18.19.0".to_string(),
+        }];
+
+        let csv_path = temp_dir.path().join("out.engines.csv");
+        write_engines_csv(&incompatibilities, &csv_path).unwrap();
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(
+            content.contains("undici,7.2.0,node,/app/node_modules/undici,node,>=20.18.1,18.19.0\n")
+        );
+
+        let json_path = temp_dir.path().join("out.engines.json");
+        write_engines_json(&incompatibilities, None, &json_path).unwrap();
+        let parsed: EnginesDocument =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(parsed.schema_version, SCHEMA_VERSION);
+        assert_eq!(parsed.incompatible_engines, incompatibilities);
+    }
+}
@@ -0,0 +1,94 @@
+//! Scan error report output
+//!
+//! Files that failed to read or parse are written next to the results in the
+//! same format (`output.csv` → `output.errors.csv`), so they survive in CI
+//! artifacts instead of scrolling past on stderr.
+
+use super::schema::{ErrorsDocument, SCHEMA_VERSION};
+use super::sidecar_path;
+use crate::models::{ScanErrorEntry, ScanMetadata};
+use csv::Writer;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Get the error report path for a results file (`out.csv` → `out.errors.csv`)
+pub fn errors_path(output_path: impl AsRef<Path>) -> PathBuf {
+    sidecar_path(output_path, "errors")
+}
+
+/// Write scan errors as a JSON document
+pub fn write_errors_json(
+    errors: &[ScanErrorEntry],
+    metadata: Option<&ScanMetadata>,
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(&ErrorsDocument {
+        schema_version: SCHEMA_VERSION,
+        metadata: metadata.cloned(),
+        errors: errors.to_vec(),
+    })?;
+    let mut file = File::create(output_path)?;
+    file.write_all(json.as_bytes())?;
+    Ok(())
+}
+
+/// Write scan errors as CSV rows
+pub fn write_errors_csv(
+    errors: &[ScanErrorEntry],
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let mut writer = Writer::from_path(output_path)?;
+    writer.write_record(["file", "ecosystem", "kind", "reason"])?;
+
+    for error in errors {
+        writer.write_record([
+            error.file.to_string_lossy().as_ref(),
+            &error.ecosystem.map(|e| e.to_string()).unwrap_or_default(),
+            &error.kind.to_string(),
+            &error.reason,
+        ])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Ecosystem, ScanError};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = PathBuf::from("/app/package.json");
+        let errors = vec![ScanErrorEntry::new(
+            file.clone(),
+            Some(Ecosystem::Node),
+            &ScanError::parse_error(file, "unexpected end of input"),
+        )];
+
+        let csv_path = temp_dir.path().join("out.errors.csv");
+        write_errors_csv(&errors, &csv_path).unwrap();
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(content.starts_with("file,ecosystem,kind,reason\n"));
+        assert!(content.contains("/app/package.json,node,parse,"));
+
+        let json_path = temp_dir.path().join("out.errors.json");
+        write_errors_json(&errors, None, &json_path).unwrap();
+        let parsed: ErrorsDocument =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(parsed.schema_version, SCHEMA_VERSION);
+        assert_eq!(parsed.errors, errors);
+    }
+
+    #[test]
+    fn test_errors_path() {
+        assert_eq!(
+            errors_path("/tmp/output.json"),
+            PathBuf::from("/tmp/output.errors.json")
+        );
+    }
+}
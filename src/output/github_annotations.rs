@@ -0,0 +1,253 @@
+//! GitHub Actions annotations
+//!
+//! Findings are printed as workflow commands (`::error file=...,line=...::`)
+//! so a GitHub Actions run annotates the lockfile or manifest line of each
+//! offending dependency:
+//!
+//! - infected versions are errors, on the lockfile entry (or the installed
+//!   package, or the declaration)
+//! - declared ranges that can resolve to an infected version are warnings, on
+//!   the declaration
+//! - installed versions differing from the locked one, and locked versions
+//!   outside the declared range, are warnings, on the lockfile entry
+//!
+//! Paths are made relative to a base directory (the workspace) when they are
+//! inside it, since GitHub resolves annotation paths from the repository root.
+
+use crate::analyzer::{InfectedPackageFilter, SecurityStatus};
+use crate::models::{Classification, ClassifiedDependency};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Annotation level of a workflow command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Error,
+    Warning,
+}
+
+impl Level {
+    fn command(self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warning => "warning",
+        }
+    }
+}
+
+/// Render the findings of a scan as workflow commands, one per line
+pub fn format_github_annotations(
+    dependencies: &[ClassifiedDependency],
+    security_filter: Option<&InfectedPackageFilter>,
+    base: Option<&Path>,
+) -> String {
+    let mut out = String::new();
+    let mut dependencies: Vec<&ClassifiedDependency> = dependencies.iter().collect();
+    dependencies.sort_by(|a, b| a.output_order(b));
+
+    for dep in dependencies {
+        let status = security_filter
+            .map(|filter| filter.get_security_status(dep))
+            .unwrap_or(SecurityStatus::None);
+        let advisory = security_filter.and_then(|filter| filter.get_advisory(dep));
+        let advice = || {
+            let mut advice = String::new();
+            if let Some(advisory) = advisory.filter(|a| !a.ids.is_empty()) {
+                let _ = write!(advice, " ({})", advisory.ids.join(", "));
+            }
+            if let Some(version) =
+                security_filter.and_then(|filter| filter.recommended_version(dep))
+            {
+                let _ = write!(advice, "; upgrade to {}", version);
+            }
+            advice
+        };
+
+        match status {
+            SecurityStatus::Infected => annotate(
+                &mut out,
+                Level::Error,
+                dep,
+                &[
+                    Classification::Should,
+                    Classification::Has,
+                    Classification::Can,
+                ],
+                base,
+                &format!("Infected package {}", dep.name),
+                &format!(
+                    "{} {} is infected{}",
+                    dep.name,
+                    dep.get_primary_version().unwrap_or(""),
+                    advice()
+                ),
+            ),
+            SecurityStatus::MatchVersion => annotate(
+                &mut out,
+                Level::Warning,
+                dep,
+                &[Classification::Can, Classification::Should],
+                base,
+                &format!("Infected versions of {}", dep.name),
+                &format!(
+                    "{} {} can resolve to an infected version{}",
+                    dep.name,
+                    dep.get_version(Classification::Can).unwrap_or(""),
+                    advice()
+                ),
+            ),
+            SecurityStatus::MatchPackage | SecurityStatus::None => {}
+        }
+
+        if dep.has_version_mismatch {
+            annotate(
+                &mut out,
+                Level::Warning,
+                dep,
+                &[Classification::Should, Classification::Has],
+                base,
+                &format!("Version mismatch of {}", dep.name),
+                &format!(
+                    "{} {} is installed but {} is locked",
+                    dep.name,
+                    dep.get_version(Classification::Has).unwrap_or(""),
+                    dep.get_version(Classification::Should).unwrap_or("")
+                ),
+            );
+        }
+        if dep.has_constraint_violation {
+            annotate(
+                &mut out,
+                Level::Warning,
+                dep,
+                &[Classification::Should, Classification::Can],
+                base,
+                &format!("Constraint violation of {}", dep.name),
+                &format!(
+                    "{} {} does not satisfy the declared range {}",
+                    dep.name,
+                    dep.get_version(Classification::Should)
+                        .or_else(|| dep.get_version(Classification::Has))
+                        .unwrap_or(""),
+                    dep.get_version(Classification::Can).unwrap_or("")
+                ),
+            );
+        }
+    }
+    out
+}
+
+/// Write one workflow command, located at the first classification with a
+/// source file
+fn annotate(
+    out: &mut String,
+    level: Level,
+    dep: &ClassifiedDependency,
+    locations: &[Classification],
+    base: Option<&Path>,
+    title: &str,
+    message: &str,
+) {
+    let mut properties = Vec::new();
+    if let Some(classification) = locations
+        .iter()
+        .copied()
+        .find(|c| dep.get_source_file(*c).is_some())
+    {
+        if let Some(file) = dep.get_source_file(classification) {
+            let file = base
+                .and_then(|base| file.strip_prefix(base).ok())
+                .unwrap_or(file);
+            properties.push(format!("file={}", escape_property(&file.to_string_lossy())));
+        }
+        if let Some(line) = dep.get_source_line(classification) {
+            properties.push(format!("line={}", line));
+        }
+    }
+    properties.push(format!("title={}", escape_property(title)));
+    let _ = writeln!(
+        out,
+        "::{} {}::{}",
+        level.command(),
+        properties.join(","),
+        escape_data(message)
+    );
+}
+
+/// Escape the message of a workflow command
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a property value of a workflow command
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::vuln_filter::InfectedPackage;
+    use crate::models::{Ecosystem, SourceSpan};
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_format_github_annotations() {
+        let mut lodash = ClassifiedDependency::new("lodash".to_string(), Ecosystem::Node);
+        lodash.add_classification(
+            Classification::Should,
+            "4.17.20".to_string(),
+            PathBuf::from("/work/repo/package-lock.json"),
+        );
+        lodash.source_spans.insert(
+            Classification::Should,
+            SourceSpan {
+                start: 300,
+                end: 420,
+                line: 12,
+            },
+        );
+        let mut express = ClassifiedDependency::new("express".to_string(), Ecosystem::Node);
+        express.add_classification(
+            Classification::Has,
+            "4.18.1".to_string(),
+            PathBuf::from("/work/repo/node_modules/express/package.json"),
+        );
+        express.add_classification(
+            Classification::Should,
+            "4.18.2".to_string(),
+            PathBuf::from("/work/repo/a,b/package-lock.json"),
+        );
+        express.has_version_mismatch = true;
+
+        let mut filter = InfectedPackageFilter::new();
+        let mut infected =
+            InfectedPackage::new("lodash".to_string(), HashSet::from(["4.17.20".to_string()]));
+        infected.ids = vec!["GHSA-35jh-r3h4-6jhm".to_string()];
+        filter.add_infected_package(infected);
+
+        let out = format_github_annotations(
+            &[lodash, express],
+            Some(&filter),
+            Some(Path::new("/work/repo")),
+        );
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "::warning file=a%2Cb/package-lock.json,title=Version mismatch of express::express 4.18.1 is installed but 4.18.2 is locked",
+                "::error file=package-lock.json,line=12,title=Infected package lodash::lodash 4.17.20 is infected (GHSA-35jh-r3h4-6jhm)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape_data("50%\nnext"), "50%25%0Anext");
+        assert_eq!(escape_property("C:\\a,b"), "C%3A\\a%2Cb");
+    }
+}
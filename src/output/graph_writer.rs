@@ -0,0 +1,452 @@
+//! Graph export of dependency trees (Graphviz DOT and GraphML)
+//!
+//! Each application becomes a root node with edges to its direct
+//! dependencies; packages reached through several paths appear once per
+//! application. Nodes are filled by [`SecurityStatus`] when an infected list
+//! is given and by classification otherwise, and the border style shows the
+//! classification (HAS solid, SHOULD dashed, CAN dotted).
+
+use crate::analyzer::{InfectedPackageFilter, SecurityStatus};
+use crate::models::{Classification, ClassifiedDependency, DependencyNode, DependencyTree};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Graph file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    Dot,
+    /// GraphML (yEd, Gephi, Cytoscape)
+    GraphMl,
+}
+
+impl GraphFormat {
+    /// Pick the format from a file extension (`.graphml` → GraphML, otherwise DOT)
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("graphml") => GraphFormat::GraphMl,
+            _ => GraphFormat::Dot,
+        }
+    }
+}
+
+/// A node of the exported graph
+#[derive(Debug)]
+struct GraphNode {
+    label: String,
+    version: Option<String>,
+    ecosystem: String,
+    classification: Option<Classification>,
+    security: Option<SecurityStatus>,
+}
+
+impl GraphNode {
+    /// Fill color from the security status, or the classification without one
+    fn color(&self) -> &'static str {
+        match (self.security, self.classification) {
+            (Some(SecurityStatus::Infected), _) => "#e53935",
+            (Some(SecurityStatus::MatchVersion), _) => "#fb8c00",
+            (Some(SecurityStatus::MatchPackage), _) => "#fdd835",
+            (Some(SecurityStatus::None), _) => "#e0e0e0",
+            (None, Some(Classification::Has)) => "#a5d6a7",
+            (None, Some(Classification::Should)) => "#90caf9",
+            (None, Some(Classification::Can)) => "#e0e0e0",
+            (None, None) => "#ffffff",
+        }
+    }
+
+    /// DOT border style for the classification
+    fn style(&self) -> &'static str {
+        match self.classification {
+            Some(Classification::Should) => "filled,dashed",
+            Some(Classification::Can) => "filled,dotted",
+            _ => "filled",
+        }
+    }
+}
+
+/// Nodes and edges shared by both formats
+#[derive(Debug, Default)]
+struct Graph {
+    nodes: Vec<GraphNode>,
+    edges: Vec<(usize, usize)>,
+    /// Application node and the nodes belonging to it, per tree
+    clusters: Vec<(usize, Vec<usize>)>,
+}
+
+impl Graph {
+    fn build(trees: &[DependencyTree], security_filter: Option<&InfectedPackageFilter>) -> Self {
+        let mut graph = Graph::default();
+
+        for tree in trees {
+            let app = &tree.application;
+            let app_node = graph.add_node(GraphNode {
+                label: app.name.clone(),
+                version: None,
+                ecosystem: app.ecosystem.to_string(),
+                classification: None,
+                security: None,
+            });
+            let mut members = Vec::new();
+            let mut ids = HashMap::new();
+
+            for root in tree.get_roots() {
+                let id = graph.add_subtree(tree, root, security_filter, &mut ids, &mut members);
+                graph.edges.push((app_node, id));
+            }
+            graph.clusters.push((app_node, members));
+        }
+
+        graph
+    }
+
+    fn add_node(&mut self, node: GraphNode) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    /// Add a node and its descendants, reusing nodes already added for the application
+    fn add_subtree(
+        &mut self,
+        tree: &DependencyTree,
+        node: &DependencyNode,
+        security_filter: Option<&InfectedPackageFilter>,
+        ids: &mut HashMap<(String, String), usize>,
+        members: &mut Vec<usize>,
+    ) -> usize {
+        let key = (node.name.clone(), node.version.clone());
+        if let Some(&id) = ids.get(&key) {
+            return id;
+        }
+
+        let security = security_filter.map(|filter| node_security(tree, node, filter));
+        let id = self.add_node(GraphNode {
+            label: node.name.clone(),
+            version: Some(node.version.clone()),
+            ecosystem: tree.application.ecosystem.to_string(),
+            classification: Some(node.classification),
+            security,
+        });
+        ids.insert(key, id);
+        members.push(id);
+
+        for child in node.get_dependencies() {
+            let child_id = self.add_subtree(tree, child, security_filter, ids, members);
+            self.edges.push((id, child_id));
+        }
+
+        id
+    }
+}
+
+/// Security status of a node's package at the node's version
+///
+/// An application can hold several versions of a package, so its entry is
+/// picked by version: the one whose primary version is the node's, else one
+/// with the node's version in any classification. Without one, the filter is
+/// asked about the name and version alone.
+fn node_security(
+    tree: &DependencyTree,
+    node: &DependencyNode,
+    filter: &InfectedPackageFilter,
+) -> SecurityStatus {
+    let application = &tree.application;
+    let same_name = || {
+        application
+            .dependencies
+            .iter()
+            .filter(|dep| dep.name == node.name)
+    };
+    let entry = same_name()
+        .find(|dep| dep.get_primary_version() == Some(node.version.as_str()))
+        .or_else(|| {
+            same_name().find(|dep| dep.classifications.values().any(|v| *v == node.version))
+        });
+    match entry {
+        Some(dep) => filter.get_security_status(dep),
+        None => {
+            let mut dep = ClassifiedDependency::new(node.name.clone(), application.ecosystem);
+            dep.add_classification(node.classification, node.version.clone(), PathBuf::new());
+            dep.application_root = Some(application.root_path.clone());
+            filter.get_security_status(&dep)
+        }
+    }
+}
+
+/// Render dependency trees as a Graphviz DOT digraph
+pub fn format_trees_dot(
+    trees: &[DependencyTree],
+    security_filter: Option<&InfectedPackageFilter>,
+) -> String {
+    let graph = Graph::build(trees, security_filter);
+    let mut out = String::new();
+
+    let _ = writeln!(out, "digraph dependencies {{");
+    let _ = writeln!(out, "  rankdir=LR;");
+    let _ = writeln!(out, "  node [shape=box, fontname=\"Helvetica\"];");
+
+    for (index, (app_node, members)) in graph.clusters.iter().enumerate() {
+        let app = &graph.nodes[*app_node];
+        let _ = writeln!(out, "  subgraph cluster_{} {{", index);
+        let _ = writeln!(out, "    label=\"{}\";", escape_dot(&app.label));
+        let _ = writeln!(
+            out,
+            "    n{} [label=\"{}\", shape=folder, style=bold];",
+            app_node,
+            escape_dot(&app.label)
+        );
+        for &id in members {
+            let node = &graph.nodes[id];
+            let label = match &node.version {
+                Some(version) => format!("{}\\n{}", escape_dot(&node.label), escape_dot(version)),
+                None => escape_dot(&node.label),
+            };
+            let _ = writeln!(
+                out,
+                "    n{} [label=\"{}\", style=\"{}\", fillcolor=\"{}\"];",
+                id,
+                label,
+                node.style(),
+                node.color()
+            );
+        }
+        let _ = writeln!(out, "  }}");
+    }
+
+    for (from, to) in &graph.edges {
+        let _ = writeln!(out, "  n{} -> n{};", from, to);
+    }
+
+    let _ = writeln!(out, "}}");
+    out
+}
+
+/// Render dependency trees as GraphML
+pub fn format_trees_graphml(
+    trees: &[DependencyTree],
+    security_filter: Option<&InfectedPackageFilter>,
+) -> String {
+    let graph = Graph::build(trees, security_filter);
+    let mut out = String::new();
+
+    let _ = writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+    let _ = writeln!(
+        out,
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"
+    );
+    for key in [
+        "label",
+        "kind",
+        "version",
+        "ecosystem",
+        "classification",
+        "security",
+        "color",
+    ] {
+        let _ = writeln!(
+            out,
+            "  <key id=\"{0}\" for=\"node\" attr.name=\"{0}\" attr.type=\"string\"/>",
+            key
+        );
+    }
+    let _ = writeln!(
+        out,
+        "  <graph id=\"dependencies\" edgedefault=\"directed\">"
+    );
+
+    for (id, node) in graph.nodes.iter().enumerate() {
+        let kind = if node.version.is_some() {
+            "package"
+        } else {
+            "application"
+        };
+        let _ = writeln!(out, "    <node id=\"n{}\">", id);
+        let mut data = vec![
+            ("label", node.label.clone()),
+            ("kind", kind.to_string()),
+            ("ecosystem", node.ecosystem.clone()),
+            ("color", node.color().to_string()),
+        ];
+        if let Some(version) = &node.version {
+            data.push(("version", version.clone()));
+        }
+        if let Some(classification) = node.classification {
+            data.push(("classification", classification.to_string()));
+        }
+        if let Some(security) = node.security {
+            data.push(("security", security.to_string()));
+        }
+        for (key, value) in data {
+            let _ = writeln!(
+                out,
+                "      <data key=\"{}\">{}</data>",
+                key,
+                escape_xml(&value)
+            );
+        }
+        let _ = writeln!(out, "    </node>");
+    }
+
+    for (index, (from, to)) in graph.edges.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "    <edge id=\"e{}\" source=\"n{}\" target=\"n{}\"/>",
+            index, from, to
+        );
+    }
+
+    let _ = writeln!(out, "  </graph>");
+    let _ = writeln!(out, "</graphml>");
+    out
+}
+
+/// Write dependency trees as a graph file
+pub fn write_trees_graph(
+    trees: &[DependencyTree],
+    security_filter: Option<&InfectedPackageFilter>,
+    format: GraphFormat,
+    output_path: impl AsRef<Path>,
+) -> io::Result<()> {
+    let content = match format {
+        GraphFormat::Dot => format_trees_dot(trees, security_filter),
+        GraphFormat::GraphMl => format_trees_graphml(trees, security_filter),
+    };
+    let mut file = File::create(output_path)?;
+    file.write_all(content.as_bytes())?;
+    Ok(())
+}
+
+/// Escape a DOT string literal
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Escape XML character data
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::vuln_filter::InfectedPackage;
+    use crate::models::{Application, Ecosystem};
+    use std::collections::HashSet;
+
+    fn sample_tree() -> DependencyTree {
+        let mut app = Application::new(
+            "web".to_string(),
+            PathBuf::from("/app"),
+            PathBuf::from("/app/package.json"),
+            Ecosystem::Node,
+        );
+        let mut evil = ClassifiedDependency::new("evil".to_string(), Ecosystem::Node);
+        evil.add_classification(
+            Classification::Has,
+            "1.0.0".to_string(),
+            PathBuf::from("/app/node_modules/evil"),
+        );
+        app.add_dependency(evil);
+
+        let mut tree = DependencyTree::new(app);
+        let shared = DependencyNode::new(
+            "shared".to_string(),
+            "2.0.0".to_string(),
+            Classification::Should,
+            false,
+        );
+        let mut evil = DependencyNode::new(
+            "evil".to_string(),
+            "1.0.0".to_string(),
+            Classification::Has,
+            true,
+        );
+        evil.add_dependency(shared.clone());
+        let mut other = DependencyNode::new(
+            "other".to_string(),
+            "3.0.0".to_string(),
+            Classification::Has,
+            true,
+        );
+        other.add_dependency(shared);
+        tree.add_root(evil);
+        tree.add_root(other);
+        tree
+    }
+
+    fn infected_filter() -> InfectedPackageFilter {
+        let mut filter = InfectedPackageFilter::new();
+        filter.add_infected_package(InfectedPackage::new(
+            "evil".to_string(),
+            HashSet::from(["1.0.0".to_string()]),
+        ));
+        filter
+    }
+
+    #[test]
+    fn test_format_trees_dot() {
+        let dot = format_trees_dot(&[sample_tree()], Some(&infected_filter()));
+
+        assert!(dot.starts_with("digraph dependencies {"));
+        assert!(dot.contains("subgraph cluster_0"));
+        assert!(dot.contains("label=\"evil\\n1.0.0\", style=\"filled\", fillcolor=\"#e53935\""));
+        assert!(dot.contains("style=\"filled,dashed\""));
+        // The shared child is emitted once with an edge from each parent
+        assert_eq!(dot.matches("label=\"shared").count(), 1);
+        assert_eq!(dot.matches(" -> n2;").count(), 2);
+    }
+
+    #[test]
+    fn test_node_security_by_version() {
+        let mut tree = sample_tree();
+        let mut fixed = ClassifiedDependency::new("evil".to_string(), Ecosystem::Node);
+        fixed.add_classification(
+            Classification::Has,
+            "1.0.1".to_string(),
+            PathBuf::from("/app/node_modules/other/node_modules/evil"),
+        );
+        // Listed first, so a lookup by name would find it for both nodes
+        tree.application.dependencies.insert(0, fixed);
+        tree.roots[1].add_dependency(DependencyNode::new(
+            "evil".to_string(),
+            "1.0.1".to_string(),
+            Classification::Has,
+            false,
+        ));
+
+        let graph = Graph::build(&[tree], Some(&infected_filter()));
+        let status = |version: &str| {
+            graph
+                .nodes
+                .iter()
+                .find(|node| node.label == "evil" && node.version.as_deref() == Some(version))
+                .and_then(|node| node.security)
+        };
+        assert_eq!(status("1.0.0"), Some(SecurityStatus::Infected));
+        assert_eq!(status("1.0.1"), Some(SecurityStatus::MatchPackage));
+    }
+
+    #[test]
+    fn test_format_trees_graphml() {
+        let graphml = format_trees_graphml(&[sample_tree()], None);
+
+        assert!(graphml.contains("<graph id=\"dependencies\" edgedefault=\"directed\">"));
+        assert!(graphml.contains("<data key=\"kind\">application</data>"));
+        assert!(graphml.contains("<data key=\"classification\">SHOULD</data>"));
+        assert!(!graphml.contains("<data key=\"security\">"));
+        assert_eq!(graphml.matches("<edge ").count(), 4);
+    }
+
+    #[test]
+    fn test_graph_format_from_path() {
+        assert_eq!(GraphFormat::from_path("deps.graphml"), GraphFormat::GraphMl);
+        assert_eq!(GraphFormat::from_path("deps.dot"), GraphFormat::Dot);
+        assert_eq!(escape_xml("a<b&c"), "a&lt;b&amp;c");
+    }
+}
@@ -0,0 +1,154 @@
+//! Host comparison output
+//!
+//! The differences of `scanner compare-hosts` are written as a
+//! [`HostComparisonDocument`], or rendered for the terminal, one block per
+//! application:
+//!
+//! ```text
+//! web
+//!   - lodash 4.17.20 (node)
+//!   + lodash 4.17.21 (node)
+//!   ~ express 4.18.2 (node)
+//! ```
+//!
+//! `-` marks versions only on the first host, `+` versions only on the
+//! second and `~` versions on both with different files. Packages of no
+//! application are listed under `(no application)`. When coloring is enabled,
+//! the markers are printed in red, green and yellow.
+
+use super::schema::{HostComparisonDocument, SCHEMA_VERSION};
+use crate::analyzer::{HostDiffKind, HostDifference, InventorySource};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+/// Host comparison as a JSON document
+pub fn format_host_comparison_json(
+    first: &InventorySource,
+    second: &InventorySource,
+    differences: &[HostDifference],
+) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&HostComparisonDocument {
+        schema_version: SCHEMA_VERSION,
+        first: first.clone(),
+        second: second.clone(),
+        differences: differences.to_vec(),
+    })
+}
+
+/// Write a host comparison as a JSON document
+pub fn write_host_comparison_json(
+    first: &InventorySource,
+    second: &InventorySource,
+    differences: &[HostDifference],
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    fs::write(
+        output_path,
+        format_host_comparison_json(first, second, differences)? + "\n",
+    )
+}
+
+/// Render a host comparison as indented text, one block per application
+pub fn format_host_comparison_text(
+    first: &InventorySource,
+    second: &InventorySource,
+    differences: &[HostDifference],
+    color: bool,
+) -> String {
+    let paint = |color_code: &str, text: String| {
+        if color {
+            format!("{}{}{}", color_code, text, RESET)
+        } else {
+            text
+        }
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(out, "- {}\n+ {}", host_label(first), host_label(second));
+    let mut application = None;
+    for difference in differences {
+        if application != Some(&difference.application) {
+            application = Some(&difference.application);
+            let heading = if difference.application.is_empty() {
+                "(no application)"
+            } else {
+                &difference.application
+            };
+            let _ = writeln!(out, "\n{}", heading);
+        }
+        let (marker, color_code) = match difference.kind {
+            HostDiffKind::OnlyFirst => ("-", RED),
+            HostDiffKind::OnlySecond => ("+", GREEN),
+            HostDiffKind::ContentDiffers => ("~", YELLOW),
+        };
+        let _ = writeln!(
+            out,
+            "  {}",
+            paint(
+                color_code,
+                format!(
+                    "{} {} {} ({})",
+                    marker, difference.name, difference.version, difference.ecosystem
+                )
+            )
+        );
+    }
+    out
+}
+
+/// `hostname (file)`, or the file of results without a hostname
+fn host_label(source: &InventorySource) -> String {
+    match &source.hostname {
+        Some(hostname) => format!("{} ({})", hostname, source.file.display()),
+        None => source.file.display().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Ecosystem;
+
+    fn difference(application: &str, name: &str, kind: HostDiffKind) -> HostDifference {
+        HostDifference {
+            application: application.to_string(),
+            ecosystem: Ecosystem::Node,
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            kind,
+            first_hashes: Vec::new(),
+            second_hashes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_format_host_comparison() {
+        let mut first = InventorySource::new("web-01.json", None);
+        first.hostname = Some("web-01".to_string());
+        let second = InventorySource::new("web-02.csv", None);
+        let differences = vec![
+            difference("", "openssl", HostDiffKind::OnlySecond),
+            difference("web", "express", HostDiffKind::ContentDiffers),
+            difference("web", "lodash", HostDiffKind::OnlyFirst),
+        ];
+
+        assert_eq!(
+            format_host_comparison_text(&first, &second, &differences, false),
+            "- web-01 (web-01.json)\n+ web-02.csv\n\
+             \n(no application)\n  + openssl 1.0.0 (node)\n\
+             \nweb\n  ~ express 1.0.0 (node)\n  - lodash 1.0.0 (node)\n"
+        );
+
+        let json = format_host_comparison_json(&first, &second, &differences).unwrap();
+        let parsed: HostComparisonDocument = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.schema_version, SCHEMA_VERSION);
+        assert_eq!(parsed.first.hostname.as_deref(), Some("web-01"));
+        assert_eq!(parsed.differences, differences);
+    }
+}
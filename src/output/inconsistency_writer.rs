@@ -0,0 +1,104 @@
+//! Version inconsistency report output
+//!
+//! Inconsistencies are written next to the results in the same format
+//! (`output.csv` → `output.inconsistencies.csv`). In CSV, version lists are
+//! joined with ` | `.
+
+use super::schema::{InconsistenciesDocument, SCHEMA_VERSION};
+use super::sidecar_path;
+use crate::analyzer::VersionInconsistency;
+use crate::models::ScanMetadata;
+use csv::Writer;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Get the inconsistency report path for a results file
+pub fn inconsistencies_path(output_path: impl AsRef<Path>) -> PathBuf {
+    sidecar_path(output_path, "inconsistencies")
+}
+
+/// Write version inconsistencies as a JSON document
+pub fn write_inconsistencies_json(
+    inconsistencies: &[VersionInconsistency],
+    metadata: Option<&ScanMetadata>,
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(&InconsistenciesDocument {
+        schema_version: SCHEMA_VERSION,
+        metadata: metadata.cloned(),
+        inconsistencies: inconsistencies.to_vec(),
+    })?;
+    let mut file = File::create(output_path)?;
+    file.write_all(json.as_bytes())?;
+    Ok(())
+}
+
+/// Write version inconsistencies as CSV rows
+pub fn write_inconsistencies_csv(
+    inconsistencies: &[VersionInconsistency],
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let mut writer = Writer::from_path(output_path)?;
+    writer.write_record([
+        "package_name",
+        "ecosystem",
+        "application_name",
+        "application_root",
+        "kind",
+        "installed",
+        "locked",
+        "declared",
+    ])?;
+
+    for finding in inconsistencies {
+        writer.write_record([
+            &finding.name,
+            &finding.ecosystem.to_string(),
+            finding.application_name.as_deref().unwrap_or(""),
+            finding.application_root.to_string_lossy().as_ref(),
+            &finding.kind.to_string(),
+            &finding.installed.join(" | "),
+            &finding.locked.join(" | "),
+            &finding.declared.join(" | "),
+        ])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::InconsistencyKind;
+    use crate::models::Ecosystem;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_inconsistencies() {
+        let temp_dir = TempDir::new().unwrap();
+        let findings = vec![VersionInconsistency {
+            name: "react".to_string(),
+            ecosystem: Ecosystem::Node,
+            application_root: PathBuf::from("/app"),
+            application_name: Some("web".to_string()),
+            kind: InconsistencyKind::VersionMismatch,
+            installed: vec!["18.2.0".to_string(), "18.3.0".to_string()],
+            locked: vec!["18.1.0".to_string()],
+            declared: vec![],
+        }];
+
+        let csv_path = temp_dir.path().join("out.inconsistencies.csv");
+        write_inconsistencies_csv(&findings, &csv_path).unwrap();
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(content.contains("react,node,web,/app,version_mismatch,18.2.0 | 18.3.0,18.1.0,\n"));
+
+        let json_path = temp_dir.path().join("out.inconsistencies.json");
+        write_inconsistencies_json(&findings, None, &json_path).unwrap();
+        let parsed: InconsistenciesDocument =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(parsed.schema_version, SCHEMA_VERSION);
+        assert_eq!(parsed.inconsistencies, findings);
+    }
+}
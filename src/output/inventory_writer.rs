@@ -0,0 +1,132 @@
+//! Merged inventory output
+//!
+//! The inventory of `scanner merge` is written as an [`InventoryDocument`],
+//! or as CSV with one row per package version and place it was found, so a
+//! spreadsheet filter on a package lists every source and application
+//! holding it.
+
+use super::schema::{InventoryDocument, SCHEMA_VERSION};
+use crate::analyzer::Inventory;
+use csv::Writer;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+/// Write an inventory as a JSON document
+pub fn write_inventory_json(
+    inventory: &Inventory,
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(&InventoryDocument {
+        schema_version: SCHEMA_VERSION,
+        sources: inventory.sources.clone(),
+        packages: inventory.packages.clone(),
+    })?;
+    let mut file = File::create(output_path)?;
+    file.write_all(json.as_bytes())?;
+    Ok(())
+}
+
+/// Write an inventory as CSV rows
+pub fn write_inventory_csv(
+    inventory: &Inventory,
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let mut writer = Writer::from_path(output_path)?;
+    writer.write_record([
+        "package_name",
+        "version",
+        "ecosystem",
+        "purl",
+        "source",
+        "hostname",
+        "application_name",
+        "application_root",
+        "classifications",
+        "security",
+    ])?;
+
+    for entry in &inventory.packages {
+        for occurrence in &entry.found_in {
+            let hostname = inventory
+                .sources
+                .iter()
+                .find(|source| source.file == occurrence.source)
+                .and_then(|source| source.hostname.as_deref())
+                .unwrap_or("");
+            let classifications: Vec<String> = occurrence
+                .classifications
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            writer.write_record([
+                &entry.name,
+                &entry.version,
+                &entry.ecosystem.to_string(),
+                &entry.purl,
+                occurrence.source.to_string_lossy().as_ref(),
+                hostname,
+                occurrence.application_name.as_deref().unwrap_or(""),
+                occurrence
+                    .application_root
+                    .as_deref()
+                    .map(|root| root.to_string_lossy())
+                    .unwrap_or_default()
+                    .as_ref(),
+                &classifications.join(";"),
+                occurrence.security.as_deref().unwrap_or(""),
+            ])?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{InventoryEntry, InventoryOccurrence, InventorySource};
+    use crate::models::{Classification, Ecosystem};
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_inventory() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut source = InventorySource::new("web.json", None);
+        source.hostname = Some("web-1".to_string());
+        source.entries = 1;
+        let inventory = Inventory {
+            sources: vec![source],
+            packages: vec![InventoryEntry {
+                name: "lodash".to_string(),
+                version: "4.17.20".to_string(),
+                ecosystem: Ecosystem::Node,
+                purl: "pkg:npm/lodash@4.17.20".to_string(),
+                found_in: vec![InventoryOccurrence {
+                    source: PathBuf::from("web.json"),
+                    application_name: Some("web".to_string()),
+                    application_root: Some(PathBuf::from("/srv/web")),
+                    classifications: vec![Classification::Has, Classification::Should],
+                    security: Some("INFECTED".to_string()),
+                }],
+            }],
+        };
+
+        let csv_path = temp_dir.path().join("inventory.csv");
+        write_inventory_csv(&inventory, &csv_path).unwrap();
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(content.contains(
+            "lodash,4.17.20,node,pkg:npm/lodash@4.17.20,web.json,web-1,web,/srv/web,HAS;SHOULD,INFECTED\n"
+        ));
+
+        let json_path = temp_dir.path().join("inventory.json");
+        write_inventory_json(&inventory, &json_path).unwrap();
+        let parsed: InventoryDocument =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(parsed.schema_version, SCHEMA_VERSION);
+        assert_eq!(parsed.sources, inventory.sources);
+        assert_eq!(parsed.packages, inventory.packages);
+    }
+}
@@ -0,0 +1,237 @@
+//! Reading previously written JSON results
+//!
+//! Results written with `--format json` can be loaded again to diff two
+//! scans, re-filter them with a new infected list or export them to another
+//! format without rescanning. Both documents are accepted by either reader: an
+//! applications document yields no trees, and a trees document yields the
+//! application of every tree.
+//!
+//! Files written before [`SCHEMA_VERSION`] was introduced (a bare array of
+//! applications or trees) are still read. Files with a newer
+//! `schema_version` are rejected, since fields may have changed meaning.
+
+use super::schema::{ApplicationsDocument, TreesDocument, SCHEMA_VERSION};
+use crate::models::{Application, DependencyTree, ScanError, ScanMetadata};
+use serde_json::Value;
+use std::path::Path;
+
+/// Content of a JSON results file
+#[derive(Debug, Clone)]
+pub enum JsonResults {
+    /// An applications document
+    Applications(Vec<Application>),
+    /// A trees document
+    Trees(Vec<DependencyTree>),
+}
+
+impl JsonResults {
+    /// Applications of the results (the application of every tree)
+    pub fn into_applications(self) -> Vec<Application> {
+        match self {
+            JsonResults::Applications(applications) => applications,
+            JsonResults::Trees(trees) => trees.into_iter().map(|tree| tree.application).collect(),
+        }
+    }
+
+    /// Dependency trees of the results (without roots for an applications document)
+    pub fn into_trees(self) -> Vec<DependencyTree> {
+        match self {
+            JsonResults::Applications(applications) => {
+                applications.into_iter().map(DependencyTree::new).collect()
+            }
+            JsonResults::Trees(trees) => trees,
+        }
+    }
+}
+
+/// Read a JSON results file, keeping the kind of document
+pub fn read_results_json(path: impl AsRef<Path>) -> Result<JsonResults, ScanError> {
+    Ok(read_results_json_with_metadata(path)?.0)
+}
+
+/// Read a JSON results file with the metadata of its scan (none for files
+/// written without it)
+pub fn read_results_json_with_metadata(
+    path: impl AsRef<Path>,
+) -> Result<(JsonResults, Option<ScanMetadata>), ScanError> {
+    let path = path.as_ref();
+    let document = read_document(path)?;
+    if document.get("trees").is_some() {
+        let document = parse::<TreesDocument>(path, document)?;
+        return Ok((JsonResults::Trees(document.trees), document.metadata));
+    }
+    let document = parse::<ApplicationsDocument>(path, document)?;
+    Ok((
+        JsonResults::Applications(document.applications),
+        document.metadata,
+    ))
+}
+
+/// Read the applications of a JSON results file
+pub fn read_applications_json(path: impl AsRef<Path>) -> Result<Vec<Application>, ScanError> {
+    Ok(read_results_json(path)?.into_applications())
+}
+
+/// Read the dependency trees of a JSON results file
+///
+/// An applications document yields one tree without roots per application.
+pub fn read_trees_json(path: impl AsRef<Path>) -> Result<Vec<DependencyTree>, ScanError> {
+    Ok(read_results_json(path)?.into_trees())
+}
+
+/// Load a results file as a versioned document object
+fn read_document(path: &Path) -> Result<Value, ScanError> {
+    let content = std::fs::read_to_string(path)?;
+    let value: Value =
+        serde_json::from_str(&content).map_err(|e| ScanError::json_error(path.to_path_buf(), e))?;
+
+    match value {
+        // Unversioned output: a bare array of applications or trees
+        Value::Array(items) => {
+            let key = match items.first() {
+                Some(item) if item.get("application").is_some() => "trees",
+                _ => "applications",
+            };
+            Ok(serde_json::json!({ "schema_version": SCHEMA_VERSION, key: items }))
+        }
+        Value::Object(ref object) => {
+            let version = object.get("schema_version").and_then(Value::as_u64);
+            match version {
+                Some(version) if version <= u64::from(SCHEMA_VERSION) => Ok(value),
+                Some(version) => Err(ScanError::parse_error(
+                    path.to_path_buf(),
+                    format!(
+                        "schema_version {} is newer than the supported version {}",
+                        version, SCHEMA_VERSION
+                    ),
+                )),
+                None => Err(ScanError::parse_error(
+                    path.to_path_buf(),
+                    "missing schema_version",
+                )),
+            }
+        }
+        _ => Err(ScanError::parse_error(
+            path.to_path_buf(),
+            "expected a results document",
+        )),
+    }
+}
+
+fn parse<T: serde::de::DeserializeOwned>(path: &Path, document: Value) -> Result<T, ScanError> {
+    serde_json::from_value(document).map_err(|e| ScanError::json_error(path.to_path_buf(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::vuln_filter::InfectedPackage;
+    use crate::analyzer::InfectedPackageFilter;
+    use crate::models::{Classification, ClassifiedDependency, DependencyNode, Ecosystem};
+    use crate::output::{write_applications_json_with_security, write_trees_json};
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+    use tempfile::NamedTempFile;
+
+    fn application() -> Application {
+        let mut app = Application::new(
+            "myapp".to_string(),
+            PathBuf::from("/app"),
+            PathBuf::from("/app/package.json"),
+            Ecosystem::Node,
+        );
+        let mut dep = ClassifiedDependency::new("react".to_string(), Ecosystem::Node);
+        dep.add_classification(
+            Classification::Has,
+            "18.2.0".to_string(),
+            PathBuf::from("/app/node_modules/react"),
+        );
+        dep.dependencies = vec!["loose-envify".to_string()];
+        app.add_dependency(dep);
+        app
+    }
+
+    #[test]
+    fn test_read_applications_round_trip() {
+        let mut filter = InfectedPackageFilter::new();
+        filter.add_infected_package(InfectedPackage::new(
+            "react".to_string(),
+            HashSet::from(["18.2.0".to_string()]),
+        ));
+        let temp_file = NamedTempFile::new().unwrap();
+        write_applications_json_with_security(
+            vec![application()],
+            Some(&filter),
+            None,
+            temp_file.path(),
+        )
+        .unwrap();
+
+        let applications = read_applications_json(temp_file.path()).unwrap();
+
+        assert_eq!(applications.len(), 1);
+        assert_eq!(applications[0].name, "myapp");
+        let react = applications[0].find_dependency("react").unwrap();
+        assert_eq!(react.get_primary_version(), Some("18.2.0"));
+        assert_eq!(react.dependencies, vec!["loose-envify"]);
+        assert!(react.purl.is_some());
+
+        let trees = read_trees_json(temp_file.path()).unwrap();
+        assert_eq!(trees.len(), 1);
+        assert!(trees[0].get_roots().is_empty());
+
+        let (_, metadata) = read_results_json_with_metadata(temp_file.path()).unwrap();
+        assert!(metadata.is_none());
+        let metadata = ScanMetadata {
+            hostname: Some("web-1".to_string()),
+            ..ScanMetadata::default()
+        };
+        write_applications_json_with_security(
+            vec![application()],
+            None,
+            Some(&metadata),
+            temp_file.path(),
+        )
+        .unwrap();
+        let (_, read) = read_results_json_with_metadata(temp_file.path()).unwrap();
+        assert_eq!(read.unwrap().hostname.as_deref(), Some("web-1"));
+    }
+
+    #[test]
+    fn test_read_trees_round_trip() {
+        let mut tree = DependencyTree::new(application());
+        tree.add_root(DependencyNode::new(
+            "react".to_string(),
+            "18.2.0".to_string(),
+            Classification::Has,
+            false,
+        ));
+        let temp_file = NamedTempFile::new().unwrap();
+        write_trees_json(&[tree], temp_file.path()).unwrap();
+
+        let trees = read_trees_json(temp_file.path()).unwrap();
+        assert_eq!(trees[0].get_roots()[0].name, "react");
+
+        let applications = read_applications_json(temp_file.path()).unwrap();
+        assert_eq!(applications[0].dependency_count(), 1);
+    }
+
+    #[test]
+    fn test_read_unversioned_and_newer_documents() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let legacy = serde_json::to_string(&vec![application()]).unwrap();
+        std::fs::write(temp_file.path(), legacy).unwrap();
+        assert_eq!(read_applications_json(temp_file.path()).unwrap().len(), 1);
+
+        std::fs::write(
+            temp_file.path(),
+            r#"{"schema_version": 99, "applications": []}"#,
+        )
+        .unwrap();
+        let err = read_applications_json(temp_file.path()).unwrap_err();
+        assert!(err.to_string().contains("schema_version 99"));
+
+        std::fs::write(temp_file.path(), r#"{"applications": []}"#).unwrap();
+        assert!(read_applications_json(temp_file.path()).is_err());
+    }
+}
@@ -1,7 +1,8 @@
 //! JSON output writer for dependency trees
 
+use super::schema::{ApplicationsDocument, TreesDocument, SCHEMA_VERSION};
 use crate::analyzer::InfectedPackageFilter;
-use crate::models::{Application, DependencyTree};
+use crate::models::{Application, DependencyTree, ScanMetadata};
 use serde_json;
 use std::fs::File;
 use std::io::Write;
@@ -12,58 +13,90 @@ pub fn write_applications_json(
     applications: &[Application],
     output_path: impl AsRef<Path>,
 ) -> std::io::Result<()> {
-    write_applications_json_with_security(applications.to_vec(), None, output_path)
+    write_applications_json_with_security(applications.to_vec(), None, None, output_path)
 }
 
 /// Write applications with classified dependencies and security status to a JSON file
 pub fn write_applications_json_with_security(
     applications: Vec<Application>,
     security_filter: Option<&InfectedPackageFilter>,
+    metadata: Option<&ScanMetadata>,
     output_path: impl AsRef<Path>,
 ) -> std::io::Result<()> {
     let mut apps = applications;
+    sort_applications(&mut apps);
+    annotate_applications(&mut apps, security_filter);
 
-    // Add security status to all dependencies if filter is provided
-    if let Some(filter) = security_filter {
-        for app in &mut apps {
-            for dep in &mut app.dependencies {
-                dep.security = Some(filter.get_security_status(dep).to_string());
-            }
-        }
-    }
-
-    let json = serde_json::to_string_pretty(&apps)?;
+    let json = serde_json::to_string_pretty(&ApplicationsDocument {
+        schema_version: SCHEMA_VERSION,
+        metadata: metadata.cloned(),
+        applications: apps,
+    })?;
     let mut file = File::create(output_path)?;
     file.write_all(json.as_bytes())?;
     Ok(())
 }
 
+/// Sort applications and their dependencies, so unchanged trees produce
+/// identical output
+pub(crate) fn sort_applications(applications: &mut [Application]) {
+    applications.sort_by(Application::output_order);
+    for app in applications {
+        app.sort_dependencies();
+    }
+}
+
+/// Add purls, and security status if filter is provided
+pub(crate) fn annotate_applications(
+    applications: &mut [Application],
+    security_filter: Option<&InfectedPackageFilter>,
+) {
+    for app in applications {
+        for dep in &mut app.dependencies {
+            dep.purl = Some(dep.purl());
+            if let Some(filter) = security_filter {
+                filter.annotate(dep);
+            }
+        }
+    }
+}
+
 /// Write dependency trees to a JSON file
 pub fn write_trees_json(
     trees: &[DependencyTree],
     output_path: impl AsRef<Path>,
 ) -> std::io::Result<()> {
-    write_trees_json_with_security(trees.to_vec(), None, output_path)
+    write_trees_json_with_security(trees.to_vec(), None, None, output_path)
 }
 
 /// Write dependency trees with security status to a JSON file
 pub fn write_trees_json_with_security(
     trees: Vec<DependencyTree>,
     security_filter: Option<&InfectedPackageFilter>,
+    metadata: Option<&ScanMetadata>,
     output_path: impl AsRef<Path>,
 ) -> std::io::Result<()> {
     let mut tree_vec = trees;
+    tree_vec.sort_by(|a, b| a.application.output_order(&b.application));
+    for tree in &mut tree_vec {
+        tree.sort();
+    }
 
-    // Add security status to all dependencies if filter is provided
-    if let Some(filter) = security_filter {
-        for tree in &mut tree_vec {
-            for dep in &mut tree.application.dependencies {
-                dep.security = Some(filter.get_security_status(dep).to_string());
+    // Add purls, and security status if filter is provided
+    for tree in &mut tree_vec {
+        for dep in &mut tree.application.dependencies {
+            dep.purl = Some(dep.purl());
+            if let Some(filter) = security_filter {
+                filter.annotate(dep);
             }
         }
     }
 
-    let json = serde_json::to_string_pretty(&tree_vec)?;
+    let json = serde_json::to_string_pretty(&TreesDocument {
+        schema_version: SCHEMA_VERSION,
+        metadata: metadata.cloned(),
+        trees: tree_vec,
+    })?;
     let mut file = File::create(output_path)?;
     file.write_all(json.as_bytes())?;
     Ok(())
@@ -133,7 +166,8 @@ mod tests {
         ));
 
         let temp_file = NamedTempFile::new().unwrap();
-        write_applications_json_with_security(vec![app], Some(&filter), temp_file.path()).unwrap();
+        write_applications_json_with_security(vec![app], Some(&filter), None, temp_file.path())
+            .unwrap();
 
         let content = std::fs::read_to_string(temp_file.path()).unwrap();
         assert!(content.contains("myapp"));
@@ -142,6 +176,65 @@ mod tests {
         assert!(content.contains("INFECTED"));
     }
 
+    #[test]
+    fn test_write_applications_json_is_deterministic() {
+        let app = |name: &str, root: &str, deps: &[(&str, &str)]| {
+            let mut app = Application::new(
+                name.to_string(),
+                PathBuf::from(root),
+                PathBuf::from(root).join("package.json"),
+                Ecosystem::Node,
+            );
+            for (dep_name, version) in deps {
+                let mut dep = ClassifiedDependency::new(dep_name.to_string(), Ecosystem::Node);
+                dep.application_root = Some(PathBuf::from(root));
+                dep.add_classification(
+                    Classification::Should,
+                    version.to_string(),
+                    PathBuf::from(root).join("package-lock.json"),
+                );
+                dep.add_classification(
+                    Classification::Can,
+                    format!("^{}", version),
+                    PathBuf::from(root).join("package.json"),
+                );
+                app.add_dependency(dep);
+            }
+            app
+        };
+        let web = [
+            ("react", "18.2.0"),
+            ("lodash", "4.17.21"),
+            ("axios", "1.6.0"),
+        ];
+        let api = [("express", "4.18.2"), ("debug", "4.3.4")];
+
+        let first = NamedTempFile::new().unwrap();
+        write_applications_json(
+            &[app("web", "/srv/web", &web), app("api", "/srv/api", &api)],
+            first.path(),
+        )
+        .unwrap();
+        let reversed: Vec<_> = web.iter().rev().copied().collect();
+        let second = NamedTempFile::new().unwrap();
+        write_applications_json(
+            &[
+                app("api", "/srv/api", &api),
+                app("web", "/srv/web", &reversed),
+            ],
+            second.path(),
+        )
+        .unwrap();
+
+        let first = std::fs::read(first.path()).unwrap();
+        assert_eq!(first, std::fs::read(second.path()).unwrap());
+        let content = String::from_utf8(first).unwrap();
+        let position = |needle: &str| content.find(needle).unwrap();
+        assert!(position("\"api\"") < position("\"web\""));
+        assert!(position("\"axios\"") < position("\"lodash\""));
+        assert!(position("\"should\"") < position("\"can\""));
+    }
+
     #[test]
     fn test_write_trees_json() {
         let app = Application::new(
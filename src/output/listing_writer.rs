@@ -0,0 +1,131 @@
+//! Dry-run listing output
+//!
+//! `--dry-run` writes the manifests, lockfiles and installation directories a
+//! scan would parse instead of parsing them: as a table on stdout, or in the
+//! `--format` of the results when `--output` is given.
+
+use super::schema::{ListingDocument, SCHEMA_VERSION};
+use crate::indexer::{listed_bytes, ListedKind, ListedPath};
+use csv::Writer;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+/// Write a listing as a JSON document
+pub fn write_listing_json(
+    listed: &[ListedPath],
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(&ListingDocument {
+        schema_version: SCHEMA_VERSION,
+        paths: listed.to_vec(),
+    })?;
+    let mut file = File::create(output_path)?;
+    file.write_all(json.as_bytes())?;
+    Ok(())
+}
+
+/// Write a listing as CSV rows
+pub fn write_listing_csv(
+    listed: &[ListedPath],
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let mut writer = Writer::from_path(output_path)?;
+    writer.write_record(["path", "kind", "ecosystem", "size", "entries"])?;
+
+    for entry in listed {
+        writer.write_record([
+            entry.path.to_string_lossy().as_ref(),
+            &entry.kind.to_string(),
+            &entry.ecosystem.to_string(),
+            &entry.size.map(|s| s.to_string()).unwrap_or_default(),
+            &entry.entries.map(|e| e.to_string()).unwrap_or_default(),
+        ])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Format a listing as a table followed by its totals
+pub fn format_listing_text(listed: &[ListedPath]) -> String {
+    let mut out = String::new();
+    for entry in listed {
+        let size = match (entry.size, entry.entries) {
+            (Some(bytes), _) => format!("{} B", bytes),
+            (None, Some(entries)) => format!("{} entries", entries),
+            (None, None) => "-".to_string(),
+        };
+        let _ = writeln!(
+            out,
+            "{:<12} {:<8} {:>14}  {}",
+            entry.kind.to_string(),
+            entry.ecosystem.to_string(),
+            size,
+            entry.path.display()
+        );
+    }
+
+    let count = |kind| listed.iter().filter(|entry| entry.kind == kind).count();
+    let _ = writeln!(
+        out,
+        "\n{} manifests, {} lockfiles ({} bytes to parse), {} install dirs",
+        count(ListedKind::Manifest),
+        count(ListedKind::Lockfile),
+        listed_bytes(listed),
+        count(ListedKind::InstallDir)
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Ecosystem;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_listing() {
+        let listed = vec![
+            ListedPath {
+                path: PathBuf::from("/app/package.json"),
+                kind: ListedKind::Manifest,
+                ecosystem: Ecosystem::Node,
+                dir_type: None,
+                size: Some(120),
+                entries: None,
+            },
+            ListedPath {
+                path: PathBuf::from("/app/node_modules"),
+                kind: ListedKind::InstallDir,
+                ecosystem: Ecosystem::Node,
+                dir_type: Some(crate::indexer::InstallDirType::NodeModules),
+                size: None,
+                entries: Some(42),
+            },
+        ];
+        let temp_dir = TempDir::new().unwrap();
+
+        let csv_path = temp_dir.path().join("listing.csv");
+        write_listing_csv(&listed, &csv_path).unwrap();
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert_eq!(
+            content,
+            "path,kind,ecosystem,size,entries\n\
+             /app/package.json,manifest,node,120,\n\
+             /app/node_modules,install_dir,node,,42\n"
+        );
+
+        let json_path = temp_dir.path().join("listing.json");
+        write_listing_json(&listed, &json_path).unwrap();
+        let parsed: ListingDocument =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(parsed.paths, listed);
+
+        let text = format_listing_text(&listed);
+        assert!(text.contains("42 entries  /app/node_modules"));
+        assert!(text.ends_with("1 manifests, 0 lockfiles (120 bytes to parse), 1 install dirs\n"));
+    }
+}
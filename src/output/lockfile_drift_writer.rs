@@ -0,0 +1,121 @@
+//! Lockfile drift output
+//!
+//! Lockfiles out of date with their manifests are written next to the results
+//! in the same format (`output.csv` → `output.lockfile-drift.csv`). CSV has
+//! one row per offending package, with locked versions separated by ` | `.
+
+use super::schema::{LockfileDriftDocument, SCHEMA_VERSION};
+use super::sidecar_path;
+use crate::analyzer::LockfileDrift;
+use crate::models::ScanMetadata;
+use csv::Writer;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Get the lockfile drift path for a results file
+pub fn lockfile_drift_path(output_path: impl AsRef<Path>) -> PathBuf {
+    sidecar_path(output_path, "lockfile-drift")
+}
+
+/// Write lockfile drift as a JSON document
+pub fn write_lockfile_drift_json(
+    drift: &[LockfileDrift],
+    metadata: Option<&ScanMetadata>,
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(&LockfileDriftDocument {
+        schema_version: SCHEMA_VERSION,
+        metadata: metadata.cloned(),
+        lockfile_drift: drift.to_vec(),
+    })?;
+    let mut file = File::create(output_path)?;
+    file.write_all(json.as_bytes())?;
+    Ok(())
+}
+
+/// Write lockfile drift as CSV rows, one per offending package
+pub fn write_lockfile_drift_csv(
+    drift: &[LockfileDrift],
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let mut writer = Writer::from_path(output_path)?;
+    writer.write_record([
+        "application_root",
+        "ecosystem",
+        "manifest",
+        "lockfile",
+        "package_name",
+        "kind",
+        "declared",
+        "locked",
+    ])?;
+
+    for lockfile in drift {
+        for entry in &lockfile.entries {
+            writer.write_record([
+                lockfile.application_root.to_string_lossy().as_ref(),
+                &lockfile.ecosystem.to_string(),
+                lockfile.manifest.to_string_lossy().as_ref(),
+                lockfile.lockfile.to_string_lossy().as_ref(),
+                &entry.name,
+                &entry.kind.to_string(),
+                entry.declared.as_deref().unwrap_or(""),
+                &entry.locked.join(" | "),
+            ])?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{DriftEntry, DriftKind};
+    use crate::models::Ecosystem;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_lockfile_drift() {
+        let temp_dir = TempDir::new().unwrap();
+        let drift = vec![LockfileDrift {
+            application_root: PathBuf::from("/app"),
+            ecosystem: Ecosystem::Node,
+            manifest: PathBuf::from("/app/package.json"),
+            lockfile: PathBuf::from("/app/package-lock.json"),
+            entries: vec![
+                DriftEntry {
+                    name: "axios".to_string(),
+                    kind: DriftKind::OutsideRange,
+                    declared: Some("^1.6.0".to_string()),
+                    locked: vec!["0.27.2".to_string()],
+                },
+                DriftEntry {
+                    name: "moment".to_string(),
+                    kind: DriftKind::Undeclared,
+                    declared: None,
+                    locked: vec!["2.29.4".to_string()],
+                },
+            ],
+        }];
+
+        let csv_path = temp_dir.path().join("out.lockfile-drift.csv");
+        write_lockfile_drift_csv(&drift, &csv_path).unwrap();
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(content.contains(
+            "/app,node,/app/package.json,/app/package-lock.json,axios,outside_range,^1.6.0,0.27.2\n"
+        ));
+        assert!(content.contains(
+            "/app,node,/app/package.json,/app/package-lock.json,moment,undeclared,,2.29.4\n"
+        ));
+
+        let json_path = temp_dir.path().join("out.lockfile-drift.json");
+        write_lockfile_drift_json(&drift, None, &json_path).unwrap();
+        let parsed: LockfileDriftDocument =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(parsed.schema_version, SCHEMA_VERSION);
+        assert_eq!(parsed.lockfile_drift, drift);
+    }
+}
@@ -0,0 +1,405 @@
+//! Markdown summary output for pull request comments
+//!
+//! Produces a compact report meant to be posted by CI as a GitHub/GitLab
+//! comment: an infected packages table (with an infected list), version
+//! mismatches, and dependencies that are new compared to a baseline scan.
+//! Tables are capped at [`MAX_TABLE_ROWS`] rows to stay within comment limits.
+
+use crate::analyzer::{InfectedPackageFilter, SecurityStatus};
+use crate::models::{Classification, ClassifiedDependency, Ecosystem};
+use csv::Reader;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Maximum number of rows per table
+pub const MAX_TABLE_ROWS: usize = 50;
+
+/// Packages of a previous scan, identified by ecosystem and name
+pub type Baseline = HashSet<(Ecosystem, String)>;
+
+/// Read the packages of a previous scan from its CSV results
+pub fn read_baseline_csv(path: impl AsRef<Path>) -> io::Result<Baseline> {
+    let mut reader = Reader::from_path(path)?;
+    let headers = reader.headers()?.clone();
+    let column = |name: &str| {
+        headers.iter().position(|h| h == name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("baseline CSV has no '{}' column", name),
+            )
+        })
+    };
+    let name_column = column("package_name")?;
+    let ecosystem_column = column("ecosystem")?;
+
+    let mut baseline = Baseline::new();
+    for record in reader.records() {
+        let record = record?;
+        let (Some(name), Some(ecosystem)) = (record.get(name_column), record.get(ecosystem_column))
+        else {
+            continue;
+        };
+        if let Ok(ecosystem) = ecosystem.parse::<Ecosystem>() {
+            baseline.insert((ecosystem, name.to_string()));
+        }
+    }
+    Ok(baseline)
+}
+
+/// Render the Markdown summary
+///
+/// The infected section is only included with a security filter and the new
+/// dependencies section only with a baseline.
+pub fn format_markdown_summary(
+    dependencies: &[ClassifiedDependency],
+    security_filter: Option<&InfectedPackageFilter>,
+    baseline: Option<&Baseline>,
+) -> String {
+    let mut out = String::new();
+    let applications: HashSet<_> = dependencies
+        .iter()
+        .filter_map(|d| d.application_root.as_ref())
+        .collect();
+    let mismatches: Vec<_> = dependencies
+        .iter()
+        .filter(|d| d.has_version_mismatch)
+        .collect();
+
+    let _ = writeln!(out, "## Dependency Scan\n");
+    let _ = writeln!(
+        out,
+        "**{}** dependencies across **{}** applications, **{}** version mismatches",
+        dependencies.len(),
+        applications.len(),
+        mismatches.len()
+    );
+
+    if let Some(filter) = security_filter {
+        let mut flagged: Vec<_> = dependencies
+            .iter()
+            .map(|d| (d, filter.get_security_status(d)))
+            .filter(|(_, status)| {
+                matches!(
+                    status,
+                    SecurityStatus::Infected | SecurityStatus::MatchVersion
+                )
+            })
+            .collect();
+        let severity = |dep: &ClassifiedDependency| {
+            filter
+                .get_advisory(dep)
+                .and_then(|advisory| advisory.severity)
+        };
+        flagged.sort_by(|(a, sa), (b, sb)| {
+            sa.priority()
+                .cmp(&sb.priority())
+                .then_with(|| severity(b).cmp(&severity(a)))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        let _ = writeln!(out, "\n### Infected Packages\n");
+        if flagged.is_empty() {
+            let _ = writeln!(out, "No infected packages found.");
+        } else {
+            let rows = flagged.iter().map(|(dep, status)| {
+                let advisory = filter.get_advisory(dep);
+                let severity = match advisory.map(|a| (a.severity, a.cvss)) {
+                    Some((Some(severity), Some(cvss))) => format!("{} ({})", severity, cvss),
+                    Some((Some(severity), None)) => severity.to_string(),
+                    _ => String::new(),
+                };
+                let epss = advisory
+                    .and_then(|a| a.epss)
+                    .map(|p| format!("{:.1}%", p * 100.0))
+                    .unwrap_or_default();
+                vec![
+                    format!("`{}`", dep.name),
+                    dep.get_primary_version().unwrap_or("").to_string(),
+                    dep.ecosystem.to_string(),
+                    status.to_string(),
+                    severity,
+                    epss,
+                    filter.recommended_version(dep).unwrap_or_default(),
+                    application(dep),
+                ]
+            });
+            write_table(
+                &mut out,
+                &[
+                    "Package",
+                    "Version",
+                    "Ecosystem",
+                    "Status",
+                    "Severity",
+                    "EPSS",
+                    "Recommended",
+                    "Application",
+                ],
+                rows,
+                flagged.len(),
+            );
+        }
+    }
+
+    if !mismatches.is_empty() {
+        let _ = writeln!(out, "\n### Version Mismatches\n");
+        let rows = mismatches.iter().map(|dep| {
+            vec![
+                format!("`{}`", dep.name),
+                dep.get_version(Classification::Has)
+                    .unwrap_or("")
+                    .to_string(),
+                dep.get_version(Classification::Should)
+                    .unwrap_or("")
+                    .to_string(),
+                dep.ecosystem.to_string(),
+                application(dep),
+            ]
+        });
+        write_table(
+            &mut out,
+            &["Package", "Installed", "Locked", "Ecosystem", "Application"],
+            rows,
+            mismatches.len(),
+        );
+    }
+
+    let tampered: Vec<_> = dependencies
+        .iter()
+        .filter(|d| d.integrity_mismatch)
+        .collect();
+    if !tampered.is_empty() {
+        let _ = writeln!(out, "\n### Integrity Mismatches\n");
+        let rows = tampered.iter().map(|dep| {
+            vec![
+                format!("`{}`", dep.name),
+                dep.get_version(Classification::Has)
+                    .unwrap_or("")
+                    .to_string(),
+                dep.installed_path
+                    .as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_default(),
+                application(dep),
+            ]
+        });
+        write_table(
+            &mut out,
+            &["Package", "Installed", "Path", "Application"],
+            rows,
+            tampered.len(),
+        );
+    }
+
+    let modified: Vec<_> = dependencies
+        .iter()
+        .filter(|d| !d.modified_files.is_empty())
+        .collect();
+    if !modified.is_empty() {
+        let _ = writeln!(out, "\n### Modified Installed Files\n");
+        let rows = modified.iter().map(|dep| {
+            vec![
+                format!("`{}`", dep.name),
+                dep.get_version(Classification::Has)
+                    .unwrap_or("")
+                    .to_string(),
+                dep.modified_files.len().to_string(),
+                application(dep),
+            ]
+        });
+        write_table(
+            &mut out,
+            &["Package", "Installed", "Modified Files", "Application"],
+            rows,
+            modified.len(),
+        );
+    }
+
+    let phantoms: Vec<_> = dependencies.iter().filter(|d| d.is_phantom).collect();
+    if !phantoms.is_empty() {
+        let _ = writeln!(out, "\n### Undeclared Installed Packages\n");
+        let rows = phantoms.iter().map(|dep| {
+            vec![
+                format!("`{}`", dep.name),
+                dep.get_version(Classification::Has)
+                    .unwrap_or("")
+                    .to_string(),
+                dep.ecosystem.to_string(),
+                application(dep),
+            ]
+        });
+        write_table(
+            &mut out,
+            &["Package", "Installed", "Ecosystem", "Application"],
+            rows,
+            phantoms.len(),
+        );
+    }
+
+    if let Some(baseline) = baseline {
+        let mut seen = HashSet::new();
+        let mut new_deps: Vec<_> = dependencies
+            .iter()
+            .filter(|d| !baseline.contains(&(d.ecosystem, d.name.clone())))
+            .filter(|d| seen.insert((d.ecosystem, d.name.as_str())))
+            .collect();
+        new_deps.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let _ = writeln!(out, "\n### New Dependencies\n");
+        if new_deps.is_empty() {
+            let _ = writeln!(out, "No new dependencies.");
+        } else {
+            let rows = new_deps.iter().map(|dep| {
+                vec![
+                    format!("`{}`", dep.name),
+                    dep.get_primary_version().unwrap_or("").to_string(),
+                    dep.ecosystem.to_string(),
+                    application(dep),
+                ]
+            });
+            write_table(
+                &mut out,
+                &["Package", "Version", "Ecosystem", "Application"],
+                rows,
+                new_deps.len(),
+            );
+        }
+    }
+
+    out
+}
+
+/// Write the Markdown summary to a file
+pub fn write_markdown_summary(
+    dependencies: &[ClassifiedDependency],
+    security_filter: Option<&InfectedPackageFilter>,
+    baseline: Option<&Baseline>,
+    output_path: impl AsRef<Path>,
+) -> io::Result<()> {
+    let markdown = format_markdown_summary(dependencies, security_filter, baseline);
+    let mut file = File::create(output_path)?;
+    file.write_all(markdown.as_bytes())?;
+    Ok(())
+}
+
+/// Application name, falling back to its root path
+fn application(dep: &ClassifiedDependency) -> String {
+    dep.application_name
+        .clone()
+        .or_else(|| {
+            dep.application_root
+                .as_ref()
+                .map(|p| p.to_string_lossy().to_string())
+        })
+        .unwrap_or_default()
+}
+
+/// Append a table, truncated to [`MAX_TABLE_ROWS`] rows
+fn write_table(
+    out: &mut String,
+    headers: &[&str],
+    rows: impl Iterator<Item = Vec<String>>,
+    total: usize,
+) {
+    let _ = writeln!(out, "| {} |", headers.join(" | "));
+    let _ = writeln!(out, "|{}", "---|".repeat(headers.len()));
+    for row in rows.take(MAX_TABLE_ROWS) {
+        let cells: Vec<_> = row.iter().map(|c| escape_cell(c)).collect();
+        let _ = writeln!(out, "| {} |", cells.join(" | "));
+    }
+    if total > MAX_TABLE_ROWS {
+        let _ = writeln!(out, "\n_...and {} more_", total - MAX_TABLE_ROWS);
+    }
+}
+
+/// Escape characters that would break a table cell
+fn escape_cell(cell: &str) -> String {
+    cell.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::vuln_filter::InfectedPackage;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn dependency(
+        name: &str,
+        classification: Classification,
+        version: &str,
+    ) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new(name.to_string(), Ecosystem::Node);
+        dep.add_classification(classification, version.to_string(), PathBuf::from("/app"));
+        dep.application_name = Some("web".to_string());
+        dep.application_root = Some(PathBuf::from("/app"));
+        dep
+    }
+
+    #[test]
+    fn test_format_markdown_summary() {
+        let mut filter = InfectedPackageFilter::new();
+        filter.add_infected_package(
+            InfectedPackage::new("evil".to_string(), HashSet::from(["1.0.0".to_string()]))
+                .with_cvss(9.8)
+                .with_epss(0.42),
+        );
+        let mut mismatched = dependency("react", Classification::Has, "18.1.0");
+        mismatched.add_classification(
+            Classification::Should,
+            "18.0.0".to_string(),
+            PathBuf::from("/app/package-lock.json"),
+        );
+        mismatched.has_version_mismatch = true;
+        let deps = vec![
+            dependency("evil", Classification::Has, "1.0.0"),
+            mismatched,
+            dependency("lodash", Classification::Should, "4.17.21"),
+        ];
+        let baseline = Baseline::from([
+            (Ecosystem::Node, "react".to_string()),
+            (Ecosystem::Node, "evil".to_string()),
+        ]);
+
+        let markdown = format_markdown_summary(&deps, Some(&filter), Some(&baseline));
+
+        assert!(markdown.contains("**3** dependencies across **1** applications"));
+        assert!(markdown
+            .contains("| `evil` | 1.0.0 | node | INFECTED | CRITICAL (9.8) | 42.0% |  | web |"));
+        assert!(markdown.contains("| `react` | 18.1.0 | 18.0.0 | node | web |"));
+        let new_section = markdown.split("### New Dependencies").nth(1).unwrap();
+        assert!(new_section.contains("`lodash`"));
+        assert!(!new_section.contains("`react`"));
+
+        // Sections without their inputs are omitted
+        let markdown = format_markdown_summary(&deps, None, None);
+        assert!(!markdown.contains("### Infected Packages"));
+        assert!(!markdown.contains("### New Dependencies"));
+    }
+
+    #[test]
+    fn test_read_baseline_csv() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("baseline.csv");
+        std::fs::write(
+            &path,
+            "package_name,version,ecosystem\nreact,18.0.0,node\nrequests,2.31.0,python\n",
+        )
+        .unwrap();
+
+        let baseline = read_baseline_csv(&path).unwrap();
+        assert_eq!(baseline.len(), 2);
+        assert!(baseline.contains(&(Ecosystem::Python, "requests".to_string())));
+
+        std::fs::write(&path, "name,version\nreact,18.0.0\n").unwrap();
+        assert!(read_baseline_csv(&path).is_err());
+    }
+
+    #[test]
+    fn test_escape_cell() {
+        assert_eq!(escape_cell(">=1.0 || <2"), ">=1.0 \\|\\| <2");
+    }
+}
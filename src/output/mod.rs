@@ -1,10 +1,126 @@
 //! Output formatting and export
 
+use std::path::{Path, PathBuf};
+
+pub mod app_stats_writer;
+pub mod bundled_writer;
+pub mod csv_reader;
 pub mod csv_writer;
+pub mod engines_writer;
+pub mod errors_writer;
+pub mod github_annotations;
+pub mod graph_writer;
+pub mod host_comparison_writer;
+pub mod inconsistency_writer;
+pub mod inventory_writer;
+pub mod json_reader;
 pub mod json_writer;
+pub mod listing_writer;
+pub mod lockfile_drift_writer;
+pub mod markdown_writer;
+pub mod multiple_versions_writer;
+pub mod override_writer;
+#[cfg(feature = "parquet")]
+pub mod parquet_writer;
+pub mod peer_conflicts_writer;
+pub mod remote;
+pub mod schema;
+#[cfg(feature = "siem")]
+pub mod siem_writer;
+pub mod split_writer;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_writer;
+pub mod staleness_writer;
+pub mod summary_writer;
+pub mod syslog_writer;
+pub mod tree_diff_writer;
+pub mod tree_writer;
+pub mod writer;
 
-pub use csv_writer::{write_classified_csv, write_classified_csv_with_security, write_csv};
+pub use app_stats_writer::{app_stats_path, write_app_stats_csv, write_app_stats_json};
+pub use bundled_writer::{bundled_path, write_bundled_csv, write_bundled_json};
+pub use csv_reader::read_classified_csv;
+pub use csv_writer::{
+    default_columns, parse_columns, write_classified_csv, write_classified_csv_with_columns,
+    write_classified_csv_with_security, write_csv, CsvColumn, DEFAULT_COLUMNS,
+};
+pub use engines_writer::{engines_path, write_engines_csv, write_engines_json};
+pub use errors_writer::{errors_path, write_errors_csv, write_errors_json};
+pub use github_annotations::format_github_annotations;
+pub use graph_writer::{format_trees_dot, format_trees_graphml, write_trees_graph, GraphFormat};
+pub use host_comparison_writer::{
+    format_host_comparison_json, format_host_comparison_text, write_host_comparison_json,
+};
+pub use inconsistency_writer::{
+    inconsistencies_path, write_inconsistencies_csv, write_inconsistencies_json,
+};
+pub use inventory_writer::{write_inventory_csv, write_inventory_json};
+pub use json_reader::{
+    read_applications_json, read_results_json, read_results_json_with_metadata, read_trees_json,
+    JsonResults,
+};
 pub use json_writer::{
     write_applications_json, write_applications_json_with_security, write_trees_json,
     write_trees_json_with_security,
 };
+pub use listing_writer::{format_listing_text, write_listing_csv, write_listing_json};
+pub use lockfile_drift_writer::{
+    lockfile_drift_path, write_lockfile_drift_csv, write_lockfile_drift_json,
+};
+pub use markdown_writer::{
+    format_markdown_summary, read_baseline_csv, write_markdown_summary, Baseline,
+};
+pub use multiple_versions_writer::{
+    multiple_versions_path, write_multiple_versions_csv, write_multiple_versions_json,
+};
+pub use override_writer::{overrides_path, write_overrides_csv, write_overrides_json};
+#[cfg(feature = "parquet")]
+pub use parquet_writer::write_classified_parquet;
+pub use peer_conflicts_writer::{
+    peer_conflicts_path, write_peer_conflicts_csv, write_peer_conflicts_json,
+};
+pub use remote::{
+    build_report, parse_header, push_report, push_report_async, read_header_file, ReportServer,
+};
+pub use schema::{
+    AppStatsDocument, ApplicationsDocument, BundledDocument, DetectionDocument, EnginesDocument,
+    ErrorsDocument, HostComparisonDocument, InconsistenciesDocument, InventoryDocument,
+    ListingDocument, LockfileDriftDocument, MultipleVersionsDocument, OverridesDocument,
+    PeerConflictsDocument, RecordsDocument, ReportDocument, SchemaKind, StalenessDocument,
+    SummaryDocument, TreeDiffDocument, TreesDocument, SCHEMA_VERSION,
+};
+#[cfg(feature = "siem")]
+pub use siem_writer::{
+    Delivery, ElasticsearchConfig, ElasticsearchOutput, FindingEvent, SinkConfig, SplunkConfig,
+    SplunkHecOutput,
+};
+pub use split_writer::{
+    split_applications, split_classified, split_trees, write_split_applications_json,
+    write_split_csv, write_split_trees_json, SplitBy, SplitGroup,
+};
+#[cfg(feature = "sqlite")]
+pub use sqlite_writer::{HistoryDb, HistoryScan, PackageAppearance};
+pub use staleness_writer::{staleness_path, write_staleness_csv, write_staleness_json};
+pub use summary_writer::{
+    format_summary_table, summary_path, write_summary_csv, write_summary_json,
+};
+pub use syslog_writer::{
+    parse_syslog_fields, SyslogFacility, SyslogField, SyslogOutput, SyslogTarget,
+};
+pub use tree_diff_writer::{format_tree_diff_json, format_tree_diff_text, write_tree_diff_json};
+pub use tree_writer::{format_trees_text, TreeCharset};
+pub use writer::{CsvOutput, Findings, JsonOutput, OutputWriter, OutputWriters, ScanResults};
+
+/// Get the path of a file written next to the results (`out.csv` → `out.<label>.csv`)
+pub fn sidecar_path(output_path: impl AsRef<Path>, label: &str) -> PathBuf {
+    let output_path = output_path.as_ref();
+    let stem = output_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "output".to_string());
+    let file_name = match output_path.extension() {
+        Some(ext) => format!("{}.{}.{}", stem, label, ext.to_string_lossy()),
+        None => format!("{}.{}", stem, label),
+    };
+    output_path.with_file_name(file_name)
+}
@@ -0,0 +1,133 @@
+//! Multiple installed versions report output
+//!
+//! Packages installed at several versions are written next to the results in
+//! the same format (`output.csv` → `output.multiple-versions.csv`). CSV has
+//! one row per version; paths are joined with ` | ` and requests are written
+//! as `requested_by range`.
+
+use super::schema::{MultipleVersionsDocument, SCHEMA_VERSION};
+use super::sidecar_path;
+use crate::analyzer::MultipleVersions;
+use crate::models::ScanMetadata;
+use csv::Writer;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Get the multiple versions report path for a results file
+pub fn multiple_versions_path(output_path: impl AsRef<Path>) -> PathBuf {
+    sidecar_path(output_path, "multiple-versions")
+}
+
+/// Write packages installed at several versions as a JSON document
+pub fn write_multiple_versions_json(
+    multiple_versions: &[MultipleVersions],
+    metadata: Option<&ScanMetadata>,
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(&MultipleVersionsDocument {
+        schema_version: SCHEMA_VERSION,
+        metadata: metadata.cloned(),
+        multiple_versions: multiple_versions.to_vec(),
+    })?;
+    let mut file = File::create(output_path)?;
+    file.write_all(json.as_bytes())?;
+    Ok(())
+}
+
+/// Write packages installed at several versions as CSV rows, one per version
+pub fn write_multiple_versions_csv(
+    multiple_versions: &[MultipleVersions],
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let mut writer = Writer::from_path(output_path)?;
+    writer.write_record([
+        "package_name",
+        "ecosystem",
+        "application_root",
+        "version",
+        "paths",
+        "requested_by",
+    ])?;
+
+    for entry in multiple_versions {
+        for version in &entry.versions {
+            let paths: Vec<String> = version
+                .paths
+                .iter()
+                .map(|path| path.to_string_lossy().to_string())
+                .collect();
+            let requests: Vec<String> = version
+                .requested_by
+                .iter()
+                .map(|request| format!("{} {}", request.requested_by, request.range))
+                .collect();
+            writer.write_record([
+                &entry.name,
+                &entry.ecosystem.to_string(),
+                entry.application_root.to_string_lossy().as_ref(),
+                &version.version,
+                &paths.join(" | "),
+                &requests.join(" | "),
+            ])?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{InstalledVersion, VersionRequest};
+    use crate::models::Ecosystem;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_multiple_versions() {
+        let temp_dir = TempDir::new().unwrap();
+        let version =
+            |version: &str, path: &str, requested_by: &str, range: &str| InstalledVersion {
+                version: version.to_string(),
+                paths: vec![PathBuf::from(path)],
+                requested_by: vec![VersionRequest {
+                    requested_by: requested_by.to_string(),
+                    range: range.to_string(),
+                }],
+            };
+        let multiple_versions = vec![MultipleVersions {
+            name: "qs".to_string(),
+            ecosystem: Ecosystem::Node,
+            application_root: PathBuf::from("/app"),
+            versions: vec![
+                version(
+                    "6.11.0",
+                    "/app/node_modules/qs",
+                    "/app/package.json",
+                    "^6.11.0",
+                ),
+                version(
+                    "6.5.3",
+                    "/app/node_modules/request/node_modules/qs",
+                    "request@2.88.2",
+                    "~6.5.2",
+                ),
+            ],
+        }];
+
+        let csv_path = temp_dir.path().join("out.multiple-versions.csv");
+        write_multiple_versions_csv(&multiple_versions, &csv_path).unwrap();
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(content.contains(
+            "qs,node,/app,6.5.3,/app/node_modules/request/node_modules/qs,request@2.88.2 ~6.5.2\n"
+        ));
+
+        let json_path = temp_dir.path().join("out.multiple-versions.json");
+        write_multiple_versions_json(&multiple_versions, None, &json_path).unwrap();
+        let parsed: MultipleVersionsDocument =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(parsed.schema_version, SCHEMA_VERSION);
+        assert_eq!(parsed.multiple_versions, multiple_versions);
+    }
+}
@@ -0,0 +1,120 @@
+//! Version override report output
+//!
+//! Overrides are written next to the results in the same format
+//! (`output.csv` → `output.overrides.csv`). In CSV, version lists are joined
+//! with ` | ` and overruled requests are written as `requested_by range`.
+
+use super::schema::{OverridesDocument, SCHEMA_VERSION};
+use super::sidecar_path;
+use crate::analyzer::VersionOverride;
+use crate::models::ScanMetadata;
+use csv::Writer;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Get the override report path for a results file
+pub fn overrides_path(output_path: impl AsRef<Path>) -> PathBuf {
+    sidecar_path(output_path, "overrides")
+}
+
+/// Write version overrides as a JSON document
+pub fn write_overrides_json(
+    overrides: &[VersionOverride],
+    metadata: Option<&ScanMetadata>,
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(&OverridesDocument {
+        schema_version: SCHEMA_VERSION,
+        metadata: metadata.cloned(),
+        overrides: overrides.to_vec(),
+    })?;
+    let mut file = File::create(output_path)?;
+    file.write_all(json.as_bytes())?;
+    Ok(())
+}
+
+/// Write version overrides as CSV rows
+pub fn write_overrides_csv(
+    overrides: &[VersionOverride],
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let mut writer = Writer::from_path(output_path)?;
+    writer.write_record([
+        "package_name",
+        "ecosystem",
+        "application_root",
+        "source_file",
+        "version",
+        "forced",
+        "locked",
+        "installed",
+        "overridden",
+        "infected",
+    ])?;
+
+    for entry in overrides {
+        let overridden: Vec<String> = entry
+            .overridden
+            .iter()
+            .map(|request| format!("{} {}", request.requested_by, request.range))
+            .collect();
+        writer.write_record([
+            &entry.name,
+            &entry.ecosystem.to_string(),
+            entry.application_root.to_string_lossy().as_ref(),
+            entry.source_file.to_string_lossy().as_ref(),
+            &entry.version,
+            &entry.forced.join(" | "),
+            &entry.locked.join(" | "),
+            &entry.installed.join(" | "),
+            &overridden.join(" | "),
+            &entry.infected.to_string(),
+        ])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::OverriddenRequest;
+    use crate::models::Ecosystem;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_overrides() {
+        let temp_dir = TempDir::new().unwrap();
+        let overrides = vec![VersionOverride {
+            name: "minimist".to_string(),
+            ecosystem: Ecosystem::Node,
+            application_root: PathBuf::from("/app"),
+            source_file: PathBuf::from("/app/package.json"),
+            version: "1.2.6".to_string(),
+            forced: vec!["1.2.6".to_string()],
+            locked: vec!["1.2.6".to_string()],
+            installed: vec![],
+            overridden: vec![OverriddenRequest {
+                requested_by: "mkdirp@0.5.1".to_string(),
+                range: "0.0.8".to_string(),
+            }],
+            infected: true,
+        }];
+
+        let csv_path = temp_dir.path().join("out.overrides.csv");
+        write_overrides_csv(&overrides, &csv_path).unwrap();
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(content.contains(
+            "minimist,node,/app,/app/package.json,1.2.6,1.2.6,1.2.6,,mkdirp@0.5.1 0.0.8,true\n"
+        ));
+
+        let json_path = temp_dir.path().join("out.overrides.json");
+        write_overrides_json(&overrides, None, &json_path).unwrap();
+        let parsed: OverridesDocument =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(parsed.schema_version, SCHEMA_VERSION);
+        assert_eq!(parsed.overrides, overrides);
+    }
+}
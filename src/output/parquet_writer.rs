@@ -0,0 +1,390 @@
+//! Parquet output writer
+//!
+//! With the `parquet` feature, classified dependencies can be written as a
+//! Snappy-compressed Parquet file for data lake ingestion. The schema is the
+//! CSV column set with typed columns: flags are booleans, counts and line
+//! numbers are 64-bit integers, CVSS and EPSS scores are floats, and empty
+//! CSV cells are nulls. Lists (advisories, install scripts, risk flags,
+//! running PIDs) are space-separated strings as in the CSV. Columns are only
+//! ever appended, so readers keyed on column names keep working.
+//!
+//! The scan metadata, when given, is stored as JSON in the file's key-value
+//! metadata under `scan_metadata`.
+
+use crate::analyzer::InfectedPackageFilter;
+use crate::models::{Classification, ClassifiedDependency, ScanMetadata};
+use parquet::basic::Compression;
+use parquet::column::writer::ColumnWriter;
+use parquet::data_type::ByteArray;
+use parquet::file::metadata::KeyValue;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Rows per row group, bounding the memory used while writing
+const ROW_GROUP_SIZE: usize = 64 * 1024;
+
+/// Key of the scan metadata in the file's key-value metadata
+pub const SCAN_METADATA_KEY: &str = "scan_metadata";
+
+/// Physical and logical type of a column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// UTF-8 string, always present
+    Name,
+    /// UTF-8 string, null when empty
+    Text,
+    /// Boolean
+    Flag,
+    /// Boolean, null when unknown
+    Check,
+    /// 64-bit integer
+    Count,
+    /// 64-bit integer, null when unknown
+    Line,
+    /// 32-bit float, null when unknown
+    Score,
+}
+
+/// Columns of the Parquet schema, in order
+const COLUMNS: &[(&str, Kind)] = &[
+    ("package_name", Kind::Name),
+    ("package_name_path", Kind::Text),
+    ("version", Kind::Text),
+    ("ecosystem", Kind::Name),
+    ("application_name", Kind::Text),
+    ("application_root", Kind::Text),
+    ("has_version", Kind::Text),
+    ("has_path", Kind::Text),
+    ("should_version", Kind::Text),
+    ("should_path", Kind::Text),
+    ("should_line", Kind::Line),
+    ("can_version", Kind::Text),
+    ("can_path", Kind::Text),
+    ("can_line", Kind::Line),
+    ("version_mismatch", Kind::Flag),
+    ("constraint_violation", Kind::Flag),
+    ("phantom", Kind::Flag),
+    ("missing_installation", Kind::Flag),
+    ("integrity_mismatch", Kind::Flag),
+    ("modified_files", Kind::Count),
+    ("parent_package", Kind::Text),
+    ("is_direct", Kind::Flag),
+    ("dependency_count", Kind::Count),
+    ("security", Kind::Name),
+    ("severity", Kind::Text),
+    ("cvss", Kind::Score),
+    ("epss", Kind::Score),
+    ("advisories", Kind::Text),
+    ("recommended_version", Kind::Text),
+    ("purl", Kind::Name),
+    ("resolved", Kind::Text),
+    ("integrity", Kind::Text),
+    ("install_scripts", Kind::Text),
+    ("risk_flags", Kind::Text),
+    ("running_pids", Kind::Text),
+    ("provenance", Kind::Text),
+    ("scan_root", Kind::Text),
+    ("suppressed", Kind::Text),
+    ("reachable", Kind::Check),
+    ("content_hash", Kind::Text),
+    ("container_id", Kind::Text),
+    ("container_image", Kind::Text),
+    ("pod", Kind::Text),
+];
+
+/// Value of one column of a row
+enum Cell {
+    Text(Option<String>),
+    Flag(bool),
+    Check(Option<bool>),
+    Int(Option<i64>),
+    Float(Option<f32>),
+}
+
+/// Parquet message type of the rows
+fn message_type() -> String {
+    let mut schema = String::from("message classified_dependency {\n");
+    for (name, kind) in COLUMNS {
+        let field = match kind {
+            Kind::Name => "REQUIRED BYTE_ARRAY {} (UTF8)",
+            Kind::Text => "OPTIONAL BYTE_ARRAY {} (UTF8)",
+            Kind::Flag => "REQUIRED BOOLEAN {}",
+            Kind::Check => "OPTIONAL BOOLEAN {}",
+            Kind::Count => "REQUIRED INT64 {}",
+            Kind::Line => "OPTIONAL INT64 {}",
+            Kind::Score => "OPTIONAL FLOAT {}",
+        };
+        schema.push_str("  ");
+        schema.push_str(&field.replace("{}", name));
+        schema.push_str(";\n");
+    }
+    schema.push('}');
+    schema
+}
+
+/// Cells of a dependency, in column order
+fn row(dep: &ClassifiedDependency, security_filter: Option<&InfectedPackageFilter>) -> Vec<Cell> {
+    let text = |value: Option<&str>| Cell::Text(value.filter(|v| !v.is_empty()).map(String::from));
+    let path = |classification| {
+        Cell::Text(
+            dep.get_source_file(classification)
+                .map(|p| p.to_string_lossy().to_string()),
+        )
+    };
+    let line = |classification| Cell::Int(dep.get_source_line(classification).map(i64::from));
+    let joined = |values: Vec<String>| text(Some(&values.join(" ")));
+    let container = dep.container.as_ref();
+
+    let security = security_filter
+        .map(|filter| filter.get_security_status(dep).to_string())
+        .unwrap_or_else(|| "NONE".to_string());
+    let advisory = security_filter.and_then(|filter| filter.get_advisory(dep));
+
+    vec![
+        Cell::Text(Some(dep.name.clone())),
+        text(dep.package_name_path.as_deref()),
+        text(dep.get_primary_version()),
+        Cell::Text(Some(dep.ecosystem.to_string())),
+        text(dep.application_name.as_deref()),
+        Cell::Text(
+            dep.application_root
+                .as_ref()
+                .map(|p| p.to_string_lossy().to_string()),
+        ),
+        text(dep.get_version(Classification::Has)),
+        path(Classification::Has),
+        text(dep.get_version(Classification::Should)),
+        path(Classification::Should),
+        line(Classification::Should),
+        text(dep.get_version(Classification::Can)),
+        path(Classification::Can),
+        line(Classification::Can),
+        Cell::Flag(dep.has_version_mismatch),
+        Cell::Flag(dep.has_constraint_violation),
+        Cell::Flag(dep.is_phantom),
+        Cell::Flag(dep.missing_installation),
+        Cell::Flag(dep.integrity_mismatch),
+        Cell::Int(Some(dep.modified_files.len() as i64)),
+        text(dep.parent_package.as_deref()),
+        Cell::Flag(dep.parent_package.is_none()),
+        Cell::Int(Some(dep.dependencies.len() as i64)),
+        Cell::Text(Some(security)),
+        Cell::Text(advisory.and_then(|a| a.severity).map(|s| s.to_string())),
+        Cell::Float(advisory.and_then(|a| a.cvss)),
+        Cell::Float(advisory.and_then(|a| a.epss)),
+        joined(advisory.map(|a| a.ids.clone()).unwrap_or_default()),
+        Cell::Text(security_filter.and_then(|filter| filter.recommended_version(dep))),
+        Cell::Text(Some(dep.purl())),
+        text(dep.resolved.as_deref()),
+        text(dep.integrity.as_deref()),
+        joined(dep.install_scripts.clone()),
+        joined(dep.risk_flags.iter().map(|f| f.to_string()).collect()),
+        joined(dep.running_pids.iter().map(|p| p.to_string()).collect()),
+        text(dep.provenance.as_deref()),
+        Cell::Text(
+            dep.scan_root
+                .as_deref()
+                .map(|root| root.display().to_string()),
+        ),
+        text(dep.suppressed.as_deref()),
+        Cell::Check(dep.reachable),
+        text(dep.content_hash.as_deref()),
+        text(container.map(|c| c.id.as_str())),
+        text(container.map(|c| c.image.as_str())),
+        text(container.and_then(|c| c.pod.as_deref())),
+    ]
+}
+
+/// Write the cells of one column of a row group
+fn write_column<'a>(
+    writer: &mut ColumnWriter<'_>,
+    kind: Kind,
+    cells: impl Iterator<Item = &'a Cell>,
+) -> parquet::errors::Result<()> {
+    // Definition level 1 marks a present value of an optional column
+    let mut levels = Vec::new();
+    let optional = matches!(kind, Kind::Text | Kind::Check | Kind::Line | Kind::Score);
+    match writer {
+        ColumnWriter::ByteArrayColumnWriter(writer) => {
+            let mut values = Vec::new();
+            for cell in cells {
+                if let Cell::Text(value) = cell {
+                    levels.push(i16::from(value.is_some()));
+                    values.extend(value.as_deref().map(ByteArray::from));
+                }
+            }
+            writer.write_batch(&values, optional.then_some(&levels), None)?;
+        }
+        ColumnWriter::BoolColumnWriter(writer) => {
+            let mut values = Vec::new();
+            for cell in cells {
+                match cell {
+                    Cell::Flag(value) => values.push(*value),
+                    Cell::Check(value) => {
+                        levels.push(i16::from(value.is_some()));
+                        values.extend(*value);
+                    }
+                    _ => {}
+                }
+            }
+            writer.write_batch(&values, optional.then_some(&levels), None)?;
+        }
+        ColumnWriter::Int64ColumnWriter(writer) => {
+            let mut values = Vec::new();
+            for cell in cells {
+                if let Cell::Int(value) = cell {
+                    levels.push(i16::from(value.is_some()));
+                    values.extend(*value);
+                }
+            }
+            writer.write_batch(&values, optional.then_some(&levels), None)?;
+        }
+        ColumnWriter::FloatColumnWriter(writer) => {
+            let mut values = Vec::new();
+            for cell in cells {
+                if let Cell::Float(value) = cell {
+                    levels.push(i16::from(value.is_some()));
+                    values.extend(*value);
+                }
+            }
+            writer.write_batch(&values, optional.then_some(&levels), None)?;
+        }
+        _ => unreachable!("no such column type in the schema"),
+    }
+    Ok(())
+}
+
+/// Write classified dependencies to a Parquet file with security status
+pub fn write_classified_parquet(
+    dependencies: &[ClassifiedDependency],
+    security_filter: Option<&InfectedPackageFilter>,
+    metadata: Option<&ScanMetadata>,
+    output_path: impl AsRef<Path>,
+) -> io::Result<()> {
+    let schema = Arc::new(parse_message_type(&message_type()).map_err(io::Error::other)?);
+    let mut key_value_metadata = Vec::new();
+    if let Some(metadata) = metadata {
+        key_value_metadata.push(KeyValue::new(
+            SCAN_METADATA_KEY.to_string(),
+            serde_json::to_string(metadata)?,
+        ));
+    }
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .set_created_by(format!("scanner version {}", env!("CARGO_PKG_VERSION")))
+        .set_key_value_metadata(Some(key_value_metadata))
+        .build();
+    let file = File::create(output_path)?;
+    let mut writer =
+        SerializedFileWriter::new(file, schema, Arc::new(properties)).map_err(io::Error::other)?;
+
+    // Write records in output order, like the CSV
+    let mut dependencies: Vec<&ClassifiedDependency> = dependencies.iter().collect();
+    dependencies.sort_by(|a, b| a.output_order(b));
+    for chunk in dependencies.chunks(ROW_GROUP_SIZE) {
+        let rows: Vec<Vec<Cell>> = chunk.iter().map(|dep| row(dep, security_filter)).collect();
+        let mut row_group = writer.next_row_group().map_err(io::Error::other)?;
+        for (index, (_, kind)) in COLUMNS.iter().enumerate() {
+            let Some(mut column) = row_group.next_column().map_err(io::Error::other)? else {
+                break;
+            };
+            write_column(
+                column.untyped(),
+                *kind,
+                rows.iter().map(|cells| &cells[index]),
+            )
+            .map_err(io::Error::other)?;
+            column.close().map_err(io::Error::other)?;
+        }
+        row_group.close().map_err(io::Error::other)?;
+    }
+
+    writer.close().map_err(io::Error::other)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Ecosystem;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::Field;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_write_classified_parquet() {
+        let mut express = ClassifiedDependency::new("express".to_string(), Ecosystem::Node);
+        express.add_classification(
+            Classification::Has,
+            "4.18.2".to_string(),
+            PathBuf::from("node_modules/express/package.json"),
+        );
+        express.has_version_mismatch = true;
+        let mut requests = ClassifiedDependency::new("requests".to_string(), Ecosystem::Python);
+        requests.add_classification(
+            Classification::Can,
+            ">=2.0".to_string(),
+            PathBuf::from("requirements.in"),
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.parquet");
+        let metadata = ScanMetadata {
+            scan_root: PathBuf::from("/srv/app"),
+            ..ScanMetadata::default()
+        };
+        write_classified_parquet(&[requests, express], None, Some(&metadata), &path).unwrap();
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let file_metadata = reader.metadata().file_metadata();
+        let columns: Vec<&str> = file_metadata
+            .schema_descr()
+            .columns()
+            .iter()
+            .map(|column| column.name())
+            .collect();
+        assert_eq!(
+            columns,
+            COLUMNS.iter().map(|(name, _)| *name).collect::<Vec<_>>()
+        );
+        let stored = file_metadata
+            .key_value_metadata()
+            .unwrap()
+            .iter()
+            .find(|kv| kv.key == SCAN_METADATA_KEY)
+            .and_then(|kv| kv.value.as_deref())
+            .unwrap();
+        assert_eq!(
+            serde_json::from_str::<ScanMetadata>(stored).unwrap(),
+            metadata
+        );
+
+        let rows: Vec<_> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap())
+            .collect();
+        assert_eq!(rows.len(), 2);
+        let field = |row: usize, name: &str| {
+            rows[row]
+                .get_column_iter()
+                .find(|(column, _)| column.as_str() == name)
+                .map(|(_, field)| field.clone())
+                .unwrap()
+        };
+        assert_eq!(field(0, "package_name"), Field::Str("express".to_string()));
+        assert_eq!(field(0, "has_version"), Field::Str("4.18.2".to_string()));
+        assert_eq!(field(0, "version_mismatch"), Field::Bool(true));
+        assert_eq!(field(0, "security"), Field::Str("NONE".to_string()));
+        assert_eq!(field(1, "package_name"), Field::Str("requests".to_string()));
+        assert_eq!(field(1, "has_version"), Field::Null);
+        assert_eq!(field(1, "can_version"), Field::Str(">=2.0".to_string()));
+        assert_eq!(field(1, "cvss"), Field::Null);
+        assert_eq!(field(1, "dependency_count"), Field::Long(0));
+    }
+}
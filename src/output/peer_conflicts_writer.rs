@@ -0,0 +1,113 @@
+//! Peer dependency conflict report output
+//!
+//! Peer conflicts are written next to the results in the same format
+//! (`output.csv` → `output.peer-conflicts.csv`). CSV has one row per
+//! requesting package; a missing peer has an empty `resolved` column.
+
+use super::schema::{PeerConflictsDocument, SCHEMA_VERSION};
+use super::sidecar_path;
+use crate::analyzer::PeerConflict;
+use crate::models::ScanMetadata;
+use csv::Writer;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Get the peer conflict report path for a results file
+pub fn peer_conflicts_path(output_path: impl AsRef<Path>) -> PathBuf {
+    sidecar_path(output_path, "peer-conflicts")
+}
+
+/// Write peer dependency conflicts as a JSON document
+pub fn write_peer_conflicts_json(
+    conflicts: &[PeerConflict],
+    metadata: Option<&ScanMetadata>,
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(&PeerConflictsDocument {
+        schema_version: SCHEMA_VERSION,
+        metadata: metadata.cloned(),
+        peer_conflicts: conflicts.to_vec(),
+    })?;
+    let mut file = File::create(output_path)?;
+    file.write_all(json.as_bytes())?;
+    Ok(())
+}
+
+/// Write peer dependency conflicts as CSV rows, one per requesting package
+pub fn write_peer_conflicts_csv(
+    conflicts: &[PeerConflict],
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let mut writer = Writer::from_path(output_path)?;
+    writer.write_record([
+        "package_name",
+        "ecosystem",
+        "application_root",
+        "kind",
+        "requested_by",
+        "range",
+        "optional",
+        "resolved",
+        "satisfied",
+    ])?;
+
+    for conflict in conflicts {
+        for request in &conflict.requests {
+            writer.write_record([
+                &conflict.name,
+                &conflict.ecosystem.to_string(),
+                conflict.application_root.to_string_lossy().as_ref(),
+                &conflict.kind.to_string(),
+                &request.requested_by,
+                &request.range,
+                &request.optional.to_string(),
+                request.resolved.as_deref().unwrap_or(""),
+                &request.satisfied.to_string(),
+            ])?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{PeerConflictKind, PeerRequest};
+    use crate::models::Ecosystem;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_peer_conflicts() {
+        let temp_dir = TempDir::new().unwrap();
+        let conflicts = vec![PeerConflict {
+            name: "react".to_string(),
+            ecosystem: Ecosystem::Node,
+            application_root: PathBuf::from("/app"),
+            kind: PeerConflictKind::Unsatisfied,
+            requests: vec![PeerRequest {
+                requested_by: "@testing-library/react@14.0.0".to_string(),
+                range: "^18.0.0".to_string(),
+                optional: false,
+                resolved: Some("17.0.2".to_string()),
+                satisfied: false,
+            }],
+        }];
+
+        let csv_path = temp_dir.path().join("out.peer-conflicts.csv");
+        write_peer_conflicts_csv(&conflicts, &csv_path).unwrap();
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(content.contains(
+            "react,node,/app,unsatisfied,@testing-library/react@14.0.0,^18.0.0,false,17.0.2,false\n"
+        ));
+
+        let json_path = temp_dir.path().join("out.peer-conflicts.json");
+        write_peer_conflicts_json(&conflicts, None, &json_path).unwrap();
+        let parsed: PeerConflictsDocument =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(parsed.schema_version, SCHEMA_VERSION);
+        assert_eq!(parsed.peer_conflicts, conflicts);
+    }
+}
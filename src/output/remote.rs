@@ -0,0 +1,424 @@
+//! Scan results over HTTP, for fleet-wide orchestration
+//!
+//! A [`ReportDocument`] bundles the results, summary and findings of one scan.
+//! [`push_report`] POSTs it as JSON to a collector, with any configured
+//! headers (e.g. `Authorization`, kept in a file read by
+//! [`read_header_file`]), or [`push_report_async`] from async code;
+//! [`ReportServer`] serves it to collectors that pull instead:
+//!
+//! - `GET /results`: the [`ReportDocument`]
+//! - `GET /summary`: the [`SummaryDocument`]
+//! - `GET /health`: `ok`, without authentication
+//!
+//! The server is deliberately small: one request at a time, GET only, with
+//! a deadline for receiving the whole request and a write timeout so a
+//! stalled or trickling client cannot hold it, responses closed after
+//! sending, plain HTTP (put it behind a TLS proxy when crossing untrusted
+//! networks). With a token, requests must send `Authorization: Bearer
+//! <token>`, compared in constant time.
+
+use super::json_writer::{annotate_applications, sort_applications};
+use super::schema::{ReportDocument, SummaryDocument, SCHEMA_VERSION};
+use crate::analyzer::registries::expand;
+use crate::analyzer::{
+    AppStatsAnalyzer, InfectedPackageFilter, VersionInconsistency, VersionOverride,
+};
+use crate::models::{Application, ScanError, ScanErrorEntry, ScanMetadata, ScanSummary};
+use crate::parsers::read_text;
+use crate::unblock::unblock;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Time allowed for reading a whole request, sending a response or a push
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Longest request head the server reads
+const MAX_REQUEST_HEAD: u64 = 16 * 1024;
+
+/// Build the report of a scan, annotating dependencies like the JSON output
+/// and adding the stats of each application
+pub fn build_report(
+    applications: Vec<Application>,
+    security_filter: Option<&InfectedPackageFilter>,
+    summary: ScanSummary,
+    inconsistencies: Vec<VersionInconsistency>,
+    overrides: Vec<VersionOverride>,
+    errors: Vec<ScanErrorEntry>,
+    metadata: Option<ScanMetadata>,
+) -> ReportDocument {
+    let mut applications = applications;
+    sort_applications(&mut applications);
+    let application_stats = AppStatsAnalyzer::new().analyze(&applications);
+    annotate_applications(&mut applications, security_filter);
+    ReportDocument {
+        schema_version: SCHEMA_VERSION,
+        metadata,
+        summary,
+        applications,
+        application_stats,
+        inconsistencies,
+        overrides,
+        errors,
+    }
+}
+
+/// Parse a `Name: value` header argument
+pub fn parse_header(header: &str) -> Option<(String, String)> {
+    let (name, value) = header.split_once(':')?;
+    let name = name.trim();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((name.to_string(), value.trim().to_string()))
+}
+
+/// Read `Name: value` headers from a file, one per line; empty lines and
+/// lines starting with `#` are skipped and `${VAR}` references are expanded
+/// from the environment
+pub fn read_header_file(path: &Path) -> Result<Vec<(String, String)>, ScanError> {
+    let content = read_text(path)?;
+    let env = |name: &str| std::env::var(name).ok();
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            parse_header(&expand(line, &env)).ok_or_else(|| ScanError::Parse {
+                file: path.to_path_buf(),
+                message: format!("invalid header {:?}, expected \"Name: value\"", line),
+            })
+        })
+        .collect()
+}
+
+/// POST a report as JSON to a collector URL
+pub fn push_report(
+    report: &ReportDocument,
+    url: &str,
+    headers: &[(String, String)],
+) -> io::Result<()> {
+    let json = serde_json::to_string(report)?;
+    let mut request = ureq::post(url)
+        .timeout(TIMEOUT)
+        .set("Content-Type", "application/json");
+    for (name, value) in headers {
+        request = request.set(name, value);
+    }
+    match request.send_string(&json) {
+        Ok(_) => Ok(()),
+        Err(ureq::Error::Status(code, response)) => Err(io::Error::other(format!(
+            "collector responded {} {}",
+            code,
+            response.status_text()
+        ))),
+        Err(e) => Err(io::Error::other(e)),
+    }
+}
+
+/// [`push_report`] for async callers, on any runtime
+///
+/// The push runs on the shared worker pool of [`unblock`], so pushes awaited
+/// together beyond its size wait for a free thread.
+pub async fn push_report_async(
+    report: &ReportDocument,
+    url: &str,
+    headers: &[(String, String)],
+) -> io::Result<()> {
+    let report = report.clone();
+    let url = url.to_string();
+    let headers = headers.to_vec();
+    unblock(move || push_report(&report, &url, &headers)).await
+}
+
+/// Minimal HTTP server for a scan report
+pub struct ReportServer {
+    listener: TcpListener,
+    token: Option<String>,
+}
+
+impl ReportServer {
+    /// Listen on an address such as `0.0.0.0:8080`
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            token: None,
+        })
+    }
+
+    /// Require `Authorization: Bearer <token>` on result requests
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Address the server listens on
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serve the report until the process is stopped
+    pub fn serve(&self, report: &ReportDocument) -> io::Result<()> {
+        let results = serde_json::to_string(report)?;
+        let summary = serde_json::to_string(&SummaryDocument {
+            schema_version: SCHEMA_VERSION,
+            metadata: report.metadata.clone(),
+            summary: report.summary.clone(),
+        })?;
+
+        for stream in self.listener.incoming() {
+            let result = stream.and_then(|stream| self.handle(stream, &results, &summary));
+            if let Err(e) = result {
+                warn!(error = %e, "Failed to answer a results request");
+            }
+        }
+        Ok(())
+    }
+
+    /// Answer one request
+    fn handle(&self, stream: TcpStream, results: &str, summary: &str) -> io::Result<()> {
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        let request = DeadlineReader {
+            stream: stream.try_clone()?,
+            deadline: Instant::now() + TIMEOUT,
+        };
+        let mut reader = BufReader::new(request.take(MAX_REQUEST_HEAD));
+
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or("");
+        let path = parts.next().unwrap_or("");
+        let path = path.split('?').next().unwrap_or(path);
+
+        let mut authorization = None;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = parse_header(&line) {
+                if name.eq_ignore_ascii_case("authorization") {
+                    authorization = Some(value);
+                }
+            }
+        }
+        debug!(method, path, "Results request");
+
+        let authorized = match &self.token {
+            Some(token) => authorization.is_some_and(|value| {
+                constant_time_eq(value.as_bytes(), format!("Bearer {}", token).as_bytes())
+            }),
+            None => true,
+        };
+        let (status, content_type, body) = match (method, path) {
+            ("GET", "/health") => ("200 OK", "text/plain", "ok"),
+            ("GET", "/results" | "/summary") if !authorized => {
+                ("401 Unauthorized", "text/plain", "unauthorized")
+            }
+            ("GET", "/results") => ("200 OK", "application/json", results),
+            ("GET", "/summary") => ("200 OK", "application/json", summary),
+            ("GET", _) => ("404 Not Found", "text/plain", "not found"),
+            _ => ("405 Method Not Allowed", "text/plain", "method not allowed"),
+        };
+
+        let mut stream = stream;
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            content_type,
+            body.len(),
+            body
+        )?;
+        stream.flush()
+    }
+}
+
+/// Reader of a request that fails once its deadline passes, however the
+/// client spreads its bytes over reads that each beat the read timeout
+struct DeadlineReader {
+    stream: TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "request not received in time",
+            ));
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        self.stream.read(buf)
+    }
+}
+
+/// Compare secrets without returning early on the first differing byte, so
+/// the response time does not reveal how much of a guess was right
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unblock::block_on;
+    use std::thread;
+
+    fn report() -> ReportDocument {
+        let summary = ScanSummary {
+            total_dependencies: 3,
+            ..ScanSummary::default()
+        };
+        build_report(
+            Vec::new(),
+            None,
+            summary,
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            None,
+        )
+    }
+
+    fn get(addr: SocketAddr, path: &str, token: Option<&str>) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let authorization = token
+            .map(|t| format!("Authorization: Bearer {}\r\n", t))
+            .unwrap_or_default();
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: scanner\r\n{}\r\n",
+            path, authorization
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_serve_report() {
+        let server = ReportServer::bind("127.0.0.1:0")
+            .unwrap()
+            .with_token("secret");
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.serve(&report()));
+
+        let response = get(addr, "/results", Some("secret"));
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        let body = response.split("\r\n\r\n").nth(1).unwrap();
+        let parsed: ReportDocument = serde_json::from_str(body).unwrap();
+        assert_eq!(parsed.summary.total_dependencies, 3);
+
+        assert!(get(addr, "/summary", None).starts_with("HTTP/1.1 401"));
+        assert!(get(addr, "/health", None).starts_with("HTTP/1.1 200"));
+        assert!(get(addr, "/other", Some("secret")).starts_with("HTTP/1.1 404"));
+    }
+
+    #[test]
+    fn test_request_deadline() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        let trickle = thread::spawn(move || {
+            // Each byte beats a per-read timeout, the whole request does not
+            for _ in 0..20 {
+                if client.write_all(b"G").is_err() {
+                    break;
+                }
+                thread::sleep(Duration::from_millis(50));
+            }
+        });
+
+        let mut reader = BufReader::new(DeadlineReader {
+            stream,
+            deadline: Instant::now() + Duration::from_millis(200),
+        });
+        let started = Instant::now();
+        let mut line = String::new();
+        let err = reader.read_line(&mut line).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert!(started.elapsed() < Duration::from_millis(500));
+        drop(reader);
+        trickle.join().unwrap();
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"Bearer secret", b"Bearer secret"));
+        assert!(!constant_time_eq(b"Bearer secreT", b"Bearer secret"));
+        assert!(!constant_time_eq(b"Bearer secre", b"Bearer secret"));
+        assert!(constant_time_eq(b"", b""));
+    }
+
+    #[test]
+    fn test_read_header_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("headers");
+        std::fs::write(
+            &path,
+            "# collector\nAuthorization: Bearer ${SCANNER_TEST_UNSET_TOKEN}x\n\nX-Fleet: web\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_header_file(&path).unwrap(),
+            [
+                ("Authorization".to_string(), "Bearer x".to_string()),
+                ("X-Fleet".to_string(), "web".to_string()),
+            ]
+        );
+
+        std::fs::write(&path, "not a header\n").unwrap();
+        assert!(read_header_file(&path).is_err());
+    }
+
+    #[test]
+    fn test_push_report() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/ingest", listener.local_addr().unwrap());
+        let collector = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut head = String::new();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = parse_header(&line) {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.parse().unwrap();
+                    }
+                }
+                head.push_str(&line);
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            stream
+                .write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n")
+                .unwrap();
+            (head, String::from_utf8(body).unwrap())
+        });
+
+        let headers = vec![parse_header("Authorization: Bearer secret").unwrap()];
+        push_report(&report(), &url, &headers).unwrap();
+
+        let (head, body) = collector.join().unwrap();
+        assert!(head.starts_with("POST /ingest HTTP/1.1\r\n"));
+        assert!(head.contains("Authorization: Bearer secret\r\n"));
+        let parsed: ReportDocument = serde_json::from_str(&body).unwrap();
+        assert_eq!(parsed.schema_version, SCHEMA_VERSION);
+        assert_eq!(parse_header("no colon"), None);
+
+        // Nothing listens on the collector's port anymore
+        assert!(block_on(push_report_async(&report(), &url, &headers)).is_err());
+    }
+}
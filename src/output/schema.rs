@@ -0,0 +1,483 @@
+//! Versioned JSON output documents and their JSON Schemas
+//!
+//! Every JSON file the scanner writes is an object carrying a
+//! `schema_version` next to its content:
+//!
+//! ```json
+//! { "schema_version": 1, "metadata": { ... }, "applications": [ ... ] }
+//! ```
+//!
+//! Documents of a scan carry its [`ScanMetadata`] (host, times, arguments).
+//!
+//! [`SCHEMA_VERSION`] is bumped whenever a field is removed, renamed or changes
+//! type; added fields keep the version. [`SchemaKind::schema`] generates the
+//! JSON Schema of each document, so pipelines can validate the output they
+//! consume (`scanner --print-schema trees`).
+
+use crate::analyzer::{
+    ApplicationStats, ApplicationTreeDiff, BundledPackage, EngineIncompatibility, HostDifference,
+    InventoryEntry, InventorySource, LockfileDrift, MultipleVersions, PeerConflict, Staleness,
+    VersionInconsistency, VersionOverride,
+};
+use crate::indexer::{EcosystemDetection, ListedPath};
+use crate::models::{
+    Application, DependencyRecord, DependencyTree, ScanError, ScanErrorEntry, ScanMetadata,
+    ScanSummary,
+};
+use schemars::schema::RootSchema;
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Version of the JSON output documents
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Applications with their classified dependencies (`--format json`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ApplicationsDocument {
+    /// Output schema version
+    pub schema_version: u32,
+
+    /// Where, when and how the scan ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ScanMetadata>,
+
+    /// Applications found
+    pub applications: Vec<Application>,
+}
+
+/// Dependency trees per application (`--format json --scan-mode full`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TreesDocument {
+    /// Output schema version
+    pub schema_version: u32,
+
+    /// Where, when and how the scan ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ScanMetadata>,
+
+    /// One tree per application
+    pub trees: Vec<DependencyTree>,
+}
+
+/// Scan summary (`*.summary.json`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SummaryDocument {
+    /// Output schema version
+    pub schema_version: u32,
+
+    /// Where, when and how the scan ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ScanMetadata>,
+
+    /// Totals and timings
+    pub summary: ScanSummary,
+}
+
+/// Files that failed to parse (`*.errors.json`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ErrorsDocument {
+    /// Output schema version
+    pub schema_version: u32,
+
+    /// Where, when and how the scan ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ScanMetadata>,
+
+    /// Failed files
+    pub errors: Vec<ScanErrorEntry>,
+}
+
+/// Version inconsistencies within applications (`*.inconsistencies.json`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct InconsistenciesDocument {
+    /// Output schema version
+    pub schema_version: u32,
+
+    /// Where, when and how the scan ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ScanMetadata>,
+
+    /// Inconsistent packages
+    pub inconsistencies: Vec<VersionInconsistency>,
+}
+
+/// Version overrides and the ranges they overrule (`*.overrides.json`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct OverridesDocument {
+    /// Output schema version
+    pub schema_version: u32,
+
+    /// Where, when and how the scan ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ScanMetadata>,
+
+    /// Overrides found
+    pub overrides: Vec<VersionOverride>,
+}
+
+/// Packages installed at several versions within an application
+/// (`*.multiple-versions.json`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MultipleVersionsDocument {
+    /// Output schema version
+    pub schema_version: u32,
+
+    /// Where, when and how the scan ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ScanMetadata>,
+
+    /// Packages with their installed versions
+    pub multiple_versions: Vec<MultipleVersions>,
+}
+
+/// Lockfiles out of date with their manifests (`*.lockfile-drift.json`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LockfileDriftDocument {
+    /// Output schema version
+    pub schema_version: u32,
+
+    /// Where, when and how the scan ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ScanMetadata>,
+
+    /// Lockfiles with the packages their manifests disagree on
+    pub lockfile_drift: Vec<LockfileDrift>,
+}
+
+/// Installed packages not supporting a target runtime (`*.engines.json`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EnginesDocument {
+    /// Output schema version
+    pub schema_version: u32,
+
+    /// Where, when and how the scan ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ScanMetadata>,
+
+    /// Packages with the runtime they do not support
+    pub incompatible_engines: Vec<EngineIncompatibility>,
+}
+
+/// Unmet or conflicting peer dependencies (`*.peer-conflicts.json`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PeerConflictsDocument {
+    /// Output schema version
+    pub schema_version: u32,
+
+    /// Where, when and how the scan ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ScanMetadata>,
+
+    /// Peers with their requests
+    pub peer_conflicts: Vec<PeerConflict>,
+}
+
+/// How far dependencies are behind their latest releases (`*.staleness.json`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StalenessDocument {
+    /// Output schema version
+    pub schema_version: u32,
+
+    /// Where, when and how the scan ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ScanMetadata>,
+
+    /// Outdated package versions
+    pub staleness: Vec<Staleness>,
+}
+
+/// Vendored and bundled copies of packages (`*.bundled.json`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BundledDocument {
+    /// Output schema version
+    pub schema_version: u32,
+
+    /// Where, when and how the scan ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ScanMetadata>,
+
+    /// Bundled packages
+    pub bundled: Vec<BundledPackage>,
+}
+
+/// Dependency counts and duplication per application (`*.app-stats.json`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AppStatsDocument {
+    /// Output schema version
+    pub schema_version: u32,
+
+    /// Where, when and how the scan ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ScanMetadata>,
+
+    /// Stats of each application
+    pub application_stats: Vec<ApplicationStats>,
+}
+
+/// Complete scan results served or pushed over HTTP (`--serve`, `--push`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReportDocument {
+    /// Output schema version
+    pub schema_version: u32,
+
+    /// Where, when and how the scan ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ScanMetadata>,
+
+    /// Totals and timings
+    pub summary: ScanSummary,
+
+    /// Applications found
+    pub applications: Vec<Application>,
+
+    /// Dependency counts and duplication of each application
+    #[serde(default)]
+    pub application_stats: Vec<ApplicationStats>,
+
+    /// Inconsistent packages
+    pub inconsistencies: Vec<VersionInconsistency>,
+
+    /// Overrides found
+    pub overrides: Vec<VersionOverride>,
+
+    /// Failed files
+    pub errors: Vec<ScanErrorEntry>,
+}
+
+/// Dependency records of a single package file (`scanner parse-file`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RecordsDocument {
+    /// Output schema version
+    pub schema_version: u32,
+
+    /// Parsed file name the records were read as
+    pub parser: String,
+
+    /// Parsed dependency records
+    pub records: Vec<DependencyRecord>,
+}
+
+/// Ecosystems and package managers present in a tree (`scanner detect --json`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DetectionDocument {
+    /// Output schema version
+    pub schema_version: u32,
+
+    /// Ecosystems found
+    pub ecosystems: Vec<EcosystemDetection>,
+}
+
+/// Package files and installation directories a scan would parse
+/// (`--dry-run`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListingDocument {
+    /// Output schema version
+    pub schema_version: u32,
+
+    /// Manifests and lockfiles, then installation directories
+    pub paths: Vec<ListedPath>,
+}
+
+/// Packages of several scans merged into one inventory (`scanner merge`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct InventoryDocument {
+    /// Output schema version
+    pub schema_version: u32,
+
+    /// Merged results files with the host and root of their scan
+    pub sources: Vec<InventorySource>,
+
+    /// Package versions with every source they were found in
+    pub packages: Vec<InventoryEntry>,
+}
+
+/// Differences of the dependency trees of two scans (`scanner diff`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TreeDiffDocument {
+    /// Output schema version
+    pub schema_version: u32,
+
+    /// Applications added, removed or with changed trees
+    pub applications: Vec<ApplicationTreeDiff>,
+}
+
+/// Package versions differing between two hosts (`scanner compare-hosts`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HostComparisonDocument {
+    /// Output schema version
+    pub schema_version: u32,
+
+    /// Results of the first host
+    pub first: InventorySource,
+
+    /// Results of the second host
+    pub second: InventorySource,
+
+    /// Package versions on one host only or with different files
+    pub differences: Vec<HostDifference>,
+}
+
+/// JSON output document types
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaKind {
+    /// [`ApplicationsDocument`]
+    Applications,
+    /// [`TreesDocument`]
+    Trees,
+    /// [`SummaryDocument`]
+    Summary,
+    /// [`ErrorsDocument`]
+    Errors,
+    /// [`InconsistenciesDocument`]
+    Inconsistencies,
+    /// [`OverridesDocument`]
+    Overrides,
+    /// [`MultipleVersionsDocument`]
+    MultipleVersions,
+    /// [`PeerConflictsDocument`]
+    PeerConflicts,
+    /// [`EnginesDocument`]
+    Engines,
+    /// [`LockfileDriftDocument`]
+    LockfileDrift,
+    /// [`StalenessDocument`]
+    Staleness,
+    /// [`BundledDocument`]
+    Bundled,
+    /// [`AppStatsDocument`]
+    AppStats,
+    /// [`ReportDocument`]
+    Report,
+    /// [`RecordsDocument`]
+    Records,
+    /// [`DetectionDocument`]
+    Detection,
+    /// [`ListingDocument`]
+    Listing,
+    /// [`InventoryDocument`]
+    Inventory,
+    /// [`TreeDiffDocument`]
+    TreeDiff,
+    /// [`HostComparisonDocument`]
+    HostComparison,
+}
+
+impl SchemaKind {
+    /// Every document type, in `--print-schema` help order
+    pub const ALL: [SchemaKind; 20] = [
+        SchemaKind::Applications,
+        SchemaKind::Trees,
+        SchemaKind::Summary,
+        SchemaKind::Errors,
+        SchemaKind::Inconsistencies,
+        SchemaKind::Overrides,
+        SchemaKind::MultipleVersions,
+        SchemaKind::PeerConflicts,
+        SchemaKind::Engines,
+        SchemaKind::LockfileDrift,
+        SchemaKind::Staleness,
+        SchemaKind::Bundled,
+        SchemaKind::AppStats,
+        SchemaKind::Report,
+        SchemaKind::Records,
+        SchemaKind::Detection,
+        SchemaKind::Listing,
+        SchemaKind::Inventory,
+        SchemaKind::TreeDiff,
+        SchemaKind::HostComparison,
+    ];
+
+    /// JSON Schema of the document
+    pub fn schema(self) -> RootSchema {
+        match self {
+            SchemaKind::Applications => schema_for!(ApplicationsDocument),
+            SchemaKind::Trees => schema_for!(TreesDocument),
+            SchemaKind::Summary => schema_for!(SummaryDocument),
+            SchemaKind::Errors => schema_for!(ErrorsDocument),
+            SchemaKind::Inconsistencies => schema_for!(InconsistenciesDocument),
+            SchemaKind::Overrides => schema_for!(OverridesDocument),
+            SchemaKind::MultipleVersions => schema_for!(MultipleVersionsDocument),
+            SchemaKind::PeerConflicts => schema_for!(PeerConflictsDocument),
+            SchemaKind::Engines => schema_for!(EnginesDocument),
+            SchemaKind::LockfileDrift => schema_for!(LockfileDriftDocument),
+            SchemaKind::Staleness => schema_for!(StalenessDocument),
+            SchemaKind::Bundled => schema_for!(BundledDocument),
+            SchemaKind::AppStats => schema_for!(AppStatsDocument),
+            SchemaKind::Report => schema_for!(ReportDocument),
+            SchemaKind::Records => schema_for!(RecordsDocument),
+            SchemaKind::Detection => schema_for!(DetectionDocument),
+            SchemaKind::Listing => schema_for!(ListingDocument),
+            SchemaKind::Inventory => schema_for!(InventoryDocument),
+            SchemaKind::TreeDiff => schema_for!(TreeDiffDocument),
+            SchemaKind::HostComparison => schema_for!(HostComparisonDocument),
+        }
+    }
+}
+
+impl fmt::Display for SchemaKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaKind::Applications => write!(f, "applications"),
+            SchemaKind::Trees => write!(f, "trees"),
+            SchemaKind::Summary => write!(f, "summary"),
+            SchemaKind::Errors => write!(f, "errors"),
+            SchemaKind::Inconsistencies => write!(f, "inconsistencies"),
+            SchemaKind::Overrides => write!(f, "overrides"),
+            SchemaKind::MultipleVersions => write!(f, "multiple-versions"),
+            SchemaKind::PeerConflicts => write!(f, "peer-conflicts"),
+            SchemaKind::Engines => write!(f, "engines"),
+            SchemaKind::LockfileDrift => write!(f, "lockfile-drift"),
+            SchemaKind::Staleness => write!(f, "staleness"),
+            SchemaKind::Bundled => write!(f, "bundled"),
+            SchemaKind::AppStats => write!(f, "app-stats"),
+            SchemaKind::Report => write!(f, "report"),
+            SchemaKind::Records => write!(f, "records"),
+            SchemaKind::Detection => write!(f, "detection"),
+            SchemaKind::Listing => write!(f, "listing"),
+            SchemaKind::Inventory => write!(f, "inventory"),
+            SchemaKind::TreeDiff => write!(f, "tree-diff"),
+            SchemaKind::HostComparison => write!(f, "host-comparison"),
+        }
+    }
+}
+
+impl FromStr for SchemaKind {
+    type Err = ScanError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.to_string() == s)
+            .ok_or_else(|| ScanError::UnknownSchema {
+                name: s.to_string(),
+                valid: Self::ALL
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema() {
+        let schema = serde_json::to_value(SchemaKind::Trees.schema()).unwrap();
+
+        assert_eq!(schema["title"], "TreesDocument");
+        let required = schema["required"].as_array().unwrap();
+        assert!(required.contains(&"schema_version".into()));
+        assert!(schema["definitions"]["DependencyNode"].is_object());
+        let err = "sbom".parse::<SchemaKind>().unwrap_err().to_string();
+        assert!(err.contains("listing"));
+        assert!(err.contains("host-comparison"));
+        for kind in SchemaKind::ALL {
+            assert_eq!(kind.to_string().parse::<SchemaKind>().unwrap(), kind);
+        }
+    }
+}
@@ -0,0 +1,842 @@
+//! Security findings posted to Splunk or Elasticsearch
+//!
+//! [`SplunkHecOutput`] sends the security findings of a scan to a Splunk
+//! HTTP Event Collector and [`ElasticsearchOutput`] indexes them through the
+//! Elasticsearch bulk API, one [`FindingEvent`] per infected or possibly
+//! infected package. Events go in batches; a batch failing on a network
+//! error, a 429 or a 5xx response is retried with exponential backoff, while
+//! other responses fail the delivery at once. Documents the bulk API rejects
+//! one by one are retried the same way when their status allows it.
+//!
+//! Both sinks are set up from a [`SinkConfig`] file (JSON or YAML, by
+//! extension), where `${VAR}` references are expanded from the environment
+//! so tokens can stay out of it:
+//!
+//! ```yaml
+//! splunk:
+//!   url: https://splunk.example.com:8088
+//!   token: ${SPLUNK_HEC_TOKEN}
+//!   index: security
+//! elasticsearch:
+//!   url: https://es.example.com:9200
+//!   index: scanner-findings
+//!   api_key: ${ES_API_KEY}
+//!   batch_size: 1000
+//!   retries: 5
+//! ```
+
+use super::syslog_writer::{finding_message, finding_version, security_finding};
+use super::{Findings, OutputWriter, ScanResults};
+use crate::analyzer::registries::expand;
+use crate::analyzer::SecurityStatus;
+use crate::models::scan_metadata::rfc3339;
+use crate::models::{ClassifiedDependency, ScanError, ScanMetadata, ScanSummary};
+use crate::parsers::read_text;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+
+/// Time allowed for one request
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Wait before the first retry of a batch, doubled for each further one
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// A security finding as sent to Splunk and Elasticsearch
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FindingEvent {
+    /// When the event was sent
+    #[serde(rename = "@timestamp")]
+    pub timestamp: String,
+
+    /// One-line description of the finding
+    pub message: String,
+
+    /// Package name
+    pub package: String,
+
+    /// Installed or locked version, or the declared range of a possible match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    /// Ecosystem
+    pub ecosystem: String,
+
+    /// INFECTED or MATCH_VERSION
+    pub status: String,
+
+    /// Severity of the advisory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+
+    /// CVSS base score of the advisory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cvss: Option<f32>,
+
+    /// EPSS exploitation probability of the advisory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epss: Option<f32>,
+
+    /// Advisory identifiers
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub advisories: Vec<String>,
+
+    /// Nearest version no advisory affects
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recommended_version: Option<String>,
+
+    /// Package URL
+    pub purl: String,
+
+    /// Name of the application
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub application: Option<String>,
+
+    /// Root of the application
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub application_root: Option<PathBuf>,
+
+    /// Installation directory or package file the finding comes from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+
+    /// Scanned host
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+
+    /// Version of the scanner
+    pub scanner_version: String,
+}
+
+impl FindingEvent {
+    /// Event of an annotated finding
+    fn new(
+        dep: &ClassifiedDependency,
+        status: SecurityStatus,
+        metadata: Option<&ScanMetadata>,
+        timestamp: &str,
+    ) -> Self {
+        Self {
+            timestamp: timestamp.to_string(),
+            message: finding_message(dep, status),
+            package: dep.name.clone(),
+            version: finding_version(dep).map(str::to_string),
+            ecosystem: dep.ecosystem.to_string(),
+            status: status.to_string(),
+            severity: dep.severity.clone(),
+            cvss: dep.cvss,
+            epss: dep.epss,
+            advisories: dep.advisory_ids.clone(),
+            recommended_version: dep.recommended_version.clone(),
+            purl: dep.purl(),
+            application: dep.application_name.clone(),
+            application_root: dep.application_root.clone(),
+            path: dep
+                .installed_path
+                .clone()
+                .or_else(|| dep.source_files.values().next().cloned()),
+            host: metadata.and_then(|metadata| metadata.hostname.clone()),
+            scanner_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// Events of the security findings of a scan
+fn finding_events(results: &ScanResults<'_>, timestamp: &str) -> Vec<FindingEvent> {
+    results
+        .dependencies
+        .iter()
+        .filter_map(|dep| security_finding(dep, results.infected))
+        .map(|(finding, status)| FindingEvent::new(&finding, status, results.metadata, timestamp))
+        .collect()
+}
+
+/// Batching and retries of a sink
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct Delivery {
+    /// Events per request
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+
+    /// Retries of a failed request
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+}
+
+impl Default for Delivery {
+    fn default() -> Self {
+        Self {
+            batch_size: default_batch_size(),
+            retries: default_retries(),
+        }
+    }
+}
+
+fn default_batch_size() -> usize {
+    500
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+/// Splunk HTTP Event Collector
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+pub struct SplunkConfig {
+    /// Base URL of the collector (`https://splunk.example.com:8088`)
+    pub url: String,
+
+    /// HEC token
+    pub token: String,
+
+    /// Index of the events, instead of the token's default one
+    #[serde(default)]
+    pub index: Option<String>,
+
+    /// Source of the events
+    #[serde(default)]
+    pub source: Option<String>,
+
+    /// Source type of the events
+    #[serde(default = "default_sourcetype")]
+    pub sourcetype: String,
+
+    /// Batching and retries
+    #[serde(flatten)]
+    pub delivery: Delivery,
+}
+
+fn default_sourcetype() -> String {
+    "scanner:finding".to_string()
+}
+
+/// The token is left out of debug output, which ends up in logs
+impl fmt::Debug for SplunkConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SplunkConfig")
+            .field("url", &self.url)
+            .field("token", &"<redacted>")
+            .field("index", &self.index)
+            .field("source", &self.source)
+            .field("sourcetype", &self.sourcetype)
+            .field("delivery", &self.delivery)
+            .finish()
+    }
+}
+
+/// Elasticsearch cluster
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+pub struct ElasticsearchConfig {
+    /// Base URL of the cluster (`https://es.example.com:9200`)
+    pub url: String,
+
+    /// Index or data stream of the events
+    #[serde(default = "default_index")]
+    pub index: String,
+
+    /// API key, sent as `Authorization: ApiKey <key>`
+    #[serde(default)]
+    pub api_key: Option<String>,
+
+    /// User name for basic authentication
+    #[serde(default)]
+    pub username: Option<String>,
+
+    /// Password for basic authentication
+    #[serde(default)]
+    pub password: Option<String>,
+
+    /// Batching and retries
+    #[serde(flatten)]
+    pub delivery: Delivery,
+}
+
+fn default_index() -> String {
+    "scanner-findings".to_string()
+}
+
+impl ElasticsearchConfig {
+    /// Value of the `Authorization` header, if the cluster needs one
+    fn authorization(&self) -> Option<String> {
+        if let Some(api_key) = &self.api_key {
+            return Some(format!("ApiKey {}", api_key));
+        }
+        let username = self.username.as_deref()?;
+        let credentials = format!("{}:{}", username, self.password.as_deref().unwrap_or(""));
+        Some(format!("Basic {}", STANDARD.encode(credentials)))
+    }
+}
+
+/// Credentials are left out of debug output, which ends up in logs
+impl fmt::Debug for ElasticsearchConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let redacted = |secret: &Option<String>| secret.as_ref().map(|_| "<redacted>");
+        f.debug_struct("ElasticsearchConfig")
+            .field("url", &self.url)
+            .field("index", &self.index)
+            .field("api_key", &redacted(&self.api_key))
+            .field("username", &self.username)
+            .field("password", &redacted(&self.password))
+            .field("delivery", &self.delivery)
+            .finish()
+    }
+}
+
+/// Network sinks of the security findings
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SinkConfig {
+    /// Splunk HTTP Event Collector
+    #[serde(default)]
+    pub splunk: Option<SplunkConfig>,
+
+    /// Elasticsearch cluster
+    #[serde(default)]
+    pub elasticsearch: Option<ElasticsearchConfig>,
+}
+
+impl SinkConfig {
+    /// Load sinks from a JSON or YAML file, by extension
+    pub fn load_from_file(path: &Path) -> Result<Self, ScanError> {
+        let content = read_text(path).map_err(ScanError::Io)?;
+        let mut config: SinkConfig = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str(&content)
+                .map_err(|e| ScanError::json_error(path.to_path_buf(), e))?,
+            _ => serde_yaml::from_str(&content)
+                .map_err(|e| ScanError::yaml_error(path.to_path_buf(), e))?,
+        };
+        config.expand_with(&|name| std::env::var(name).ok());
+        Ok(config)
+    }
+
+    /// Check if no sink is configured
+    pub fn is_empty(&self) -> bool {
+        self.splunk.is_none() && self.elasticsearch.is_none()
+    }
+
+    /// Writers of the configured sinks
+    pub fn writers(&self) -> Vec<Box<dyn OutputWriter>> {
+        let mut writers: Vec<Box<dyn OutputWriter>> = Vec::new();
+        if let Some(splunk) = &self.splunk {
+            writers.push(Box::new(SplunkHecOutput::new(splunk.clone())));
+        }
+        if let Some(elasticsearch) = &self.elasticsearch {
+            writers.push(Box::new(ElasticsearchOutput::new(elasticsearch.clone())));
+        }
+        writers
+    }
+
+    fn expand_with(&mut self, env: &dyn Fn(&str) -> Option<String>) {
+        let expand_opt = |value: &mut Option<String>| {
+            if let Some(value) = value {
+                *value = expand(value, env);
+            }
+        };
+        if let Some(splunk) = &mut self.splunk {
+            splunk.url = expand(&splunk.url, env);
+            splunk.token = expand(&splunk.token, env);
+            expand_opt(&mut splunk.index);
+            expand_opt(&mut splunk.source);
+        }
+        if let Some(elasticsearch) = &mut self.elasticsearch {
+            elasticsearch.url = expand(&elasticsearch.url, env);
+            elasticsearch.index = expand(&elasticsearch.index, env);
+            expand_opt(&mut elasticsearch.api_key);
+            expand_opt(&mut elasticsearch.username);
+            expand_opt(&mut elasticsearch.password);
+        }
+    }
+}
+
+/// Sends the security findings to a Splunk HTTP Event Collector
+#[derive(Debug, Clone)]
+pub struct SplunkHecOutput {
+    config: SplunkConfig,
+    retry_delay: Duration,
+}
+
+impl SplunkHecOutput {
+    /// Send to the collector of a configuration
+    pub fn new(config: SplunkConfig) -> Self {
+        Self {
+            config,
+            retry_delay: RETRY_DELAY,
+        }
+    }
+
+    /// Wait this long before the first retry of a batch
+    pub fn with_retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Endpoint of the events
+    fn endpoint(&self) -> String {
+        format!(
+            "{}/services/collector/event",
+            self.config.url.trim_end_matches('/')
+        )
+    }
+
+    /// Request body of a batch: one HEC envelope per event
+    fn batch_body(&self, events: &[FindingEvent], time: f64) -> io::Result<String> {
+        let mut body = String::new();
+        for event in events {
+            let mut envelope = serde_json::json!({
+                "time": time,
+                "sourcetype": self.config.sourcetype,
+                "event": event,
+            });
+            if let Some(host) = &event.host {
+                envelope["host"] = host.as_str().into();
+            }
+            if let Some(index) = &self.config.index {
+                envelope["index"] = index.as_str().into();
+            }
+            if let Some(source) = &self.config.source {
+                envelope["source"] = source.as_str().into();
+            }
+            body.push_str(&serde_json::to_string(&envelope)?);
+            body.push('\n');
+        }
+        Ok(body)
+    }
+}
+
+impl OutputWriter for SplunkHecOutput {
+    fn write_applications(&mut self, results: &ScanResults<'_>) -> io::Result<()> {
+        let now = SystemTime::now();
+        let time = now
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO)
+            .as_secs_f64();
+        let events = finding_events(results, &rfc3339(now));
+        let endpoint = self.endpoint();
+        let authorization = format!("Splunk {}", self.config.token);
+        let delivery = self.config.delivery;
+        for batch in events.chunks(delivery.batch_size.max(1)) {
+            let body = self.batch_body(batch, time)?;
+            deliver(delivery.retries, self.retry_delay, || {
+                post(&endpoint, "application/json", Some(&authorization), &body).map(|_| ())
+            })?;
+        }
+        debug!(url = %self.config.url, events = events.len(), "Sent security findings to Splunk");
+        Ok(())
+    }
+
+    fn write_findings(
+        &mut self,
+        _findings: &Findings<'_>,
+        _metadata: Option<&ScanMetadata>,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn write_summary(
+        &mut self,
+        _summary: &ScanSummary,
+        _metadata: Option<&ScanMetadata>,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Indexes the security findings through the Elasticsearch bulk API
+#[derive(Debug, Clone)]
+pub struct ElasticsearchOutput {
+    config: ElasticsearchConfig,
+    retry_delay: Duration,
+}
+
+impl ElasticsearchOutput {
+    /// Send to the cluster of a configuration
+    pub fn new(config: ElasticsearchConfig) -> Self {
+        Self {
+            config,
+            retry_delay: RETRY_DELAY,
+        }
+    }
+
+    /// Wait this long before the first retry of a batch
+    pub fn with_retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Index a batch, retrying the documents rejected with a transient status
+    fn index_batch(&self, documents: Vec<String>, authorization: Option<&str>) -> io::Result<()> {
+        let endpoint = format!("{}/_bulk", self.config.url.trim_end_matches('/'));
+        let action = serde_json::to_string(&serde_json::json!({
+            "create": { "_index": self.config.index }
+        }))?;
+        let mut pending = documents;
+        let mut rejected = Vec::new();
+        deliver(self.config.delivery.retries, self.retry_delay, || {
+            let mut body = String::new();
+            for document in &pending {
+                body.push_str(&action);
+                body.push('\n');
+                body.push_str(document);
+                body.push('\n');
+            }
+            let response = post(&endpoint, "application/x-ndjson", authorization, &body)?;
+            let mut retry = Vec::new();
+            for (i, status, reason) in bulk_failures(&response)? {
+                match pending.get(i) {
+                    Some(document) if status == 429 || status >= 500 => {
+                        retry.push(document.clone())
+                    }
+                    _ => rejected.push(reason),
+                }
+            }
+            if retry.is_empty() {
+                return Ok(());
+            }
+            let message = format!("Elasticsearch turned away {} documents", retry.len());
+            pending = retry;
+            Err(Failure::Retry(message))
+        })?;
+        match rejected.first() {
+            None => Ok(()),
+            Some(reason) => Err(io::Error::other(format!(
+                "Elasticsearch rejected {} findings: {}",
+                rejected.len(),
+                reason
+            ))),
+        }
+    }
+}
+
+impl OutputWriter for ElasticsearchOutput {
+    fn write_applications(&mut self, results: &ScanResults<'_>) -> io::Result<()> {
+        let events = finding_events(results, &rfc3339(SystemTime::now()));
+        let authorization = self.config.authorization();
+        for batch in events.chunks(self.config.delivery.batch_size.max(1)) {
+            let documents = batch
+                .iter()
+                .map(serde_json::to_string)
+                .collect::<Result<Vec<_>, _>>()?;
+            self.index_batch(documents, authorization.as_deref())?;
+        }
+        debug!(
+            url = %self.config.url,
+            index = %self.config.index,
+            events = events.len(),
+            "Sent security findings to Elasticsearch"
+        );
+        Ok(())
+    }
+
+    fn write_findings(
+        &mut self,
+        _findings: &Findings<'_>,
+        _metadata: Option<&ScanMetadata>,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn write_summary(
+        &mut self,
+        _summary: &ScanSummary,
+        _metadata: Option<&ScanMetadata>,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Why a request failed
+enum Failure {
+    /// Transient failure, worth retrying
+    Retry(String),
+    /// The request will not succeed as it is
+    Fatal(String),
+}
+
+/// POST a body, returning the response body
+fn post(
+    url: &str,
+    content_type: &str,
+    authorization: Option<&str>,
+    body: &str,
+) -> Result<String, Failure> {
+    let mut request = ureq::post(url)
+        .timeout(TIMEOUT)
+        .set("Content-Type", content_type);
+    if let Some(authorization) = authorization {
+        request = request.set("Authorization", authorization);
+    }
+    match request.send_string(body) {
+        Ok(response) => response
+            .into_string()
+            .map_err(|e| Failure::Retry(e.to_string())),
+        Err(ureq::Error::Status(code, response)) => {
+            let message = format!("{} responded {} {}", url, code, response.status_text());
+            if code == 429 || code >= 500 {
+                Err(Failure::Retry(message))
+            } else {
+                Err(Failure::Fatal(message))
+            }
+        }
+        Err(e) => Err(Failure::Retry(e.to_string())),
+    }
+}
+
+/// Run an attempt until it succeeds, fails for good, or runs out of
+/// retries, doubling the wait after each retry
+fn deliver(
+    retries: u32,
+    delay: Duration,
+    mut attempt: impl FnMut() -> Result<(), Failure>,
+) -> io::Result<()> {
+    let mut retried = 0;
+    loop {
+        match attempt() {
+            Ok(()) => return Ok(()),
+            Err(Failure::Retry(message)) if retried < retries => {
+                let wait = delay.saturating_mul(1 << retried.min(16));
+                warn!(
+                    error = %message,
+                    retry = retried + 1,
+                    "Sink request failed, retrying in {}ms",
+                    wait.as_millis()
+                );
+                thread::sleep(wait);
+                retried += 1;
+            }
+            Err(Failure::Retry(message) | Failure::Fatal(message)) => {
+                return Err(io::Error::other(message))
+            }
+        }
+    }
+}
+
+/// Position, status and reason of the documents a bulk response rejected
+fn bulk_failures(response: &str) -> Result<Vec<(usize, u16, String)>, Failure> {
+    let response: serde_json::Value = serde_json::from_str(response)
+        .map_err(|e| Failure::Fatal(format!("invalid bulk response: {}", e)))?;
+    if response["errors"] != serde_json::Value::Bool(true) {
+        return Ok(Vec::new());
+    }
+    let items = response["items"].as_array().cloned().unwrap_or_default();
+    Ok(items
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| {
+            let result = item.as_object()?.values().next()?;
+            let status = result["status"].as_u64().unwrap_or(0) as u16;
+            if (200..300).contains(&status) {
+                return None;
+            }
+            let reason = result["error"]["reason"]
+                .as_str()
+                .unwrap_or("unknown error")
+                .to_string();
+            Some((i, status, reason))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::vuln_filter::InfectedPackage;
+    use crate::analyzer::InfectedPackageFilter;
+    use crate::models::{Classification, Ecosystem};
+    use std::collections::HashSet;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    /// Collector answering each request with the next status and body,
+    /// returning the request heads and bodies
+    fn collector(
+        responses: Vec<(&'static str, &'static str)>,
+    ) -> (String, thread::JoinHandle<Vec<(String, String)>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for (status, content) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut head = String::new();
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                    head.push_str(&line);
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    content.len(),
+                    content
+                )
+                .unwrap();
+                requests.push((head, String::from_utf8(body).unwrap()));
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    fn infected_results() -> (Vec<ClassifiedDependency>, InfectedPackageFilter) {
+        let dependencies = ["lodash", "minimist", "react"]
+            .into_iter()
+            .map(|name| {
+                let mut dep = ClassifiedDependency::new(name.to_string(), Ecosystem::Node);
+                dep.add_classification(
+                    Classification::Should,
+                    "1.0.0".to_string(),
+                    PathBuf::from("/srv/web/package-lock.json"),
+                );
+                dep
+            })
+            .collect();
+        let mut filter = InfectedPackageFilter::new();
+        for name in ["lodash", "minimist"] {
+            filter.add_infected_package(InfectedPackage::new(
+                name.to_string(),
+                HashSet::from(["1.0.0".to_string()]),
+            ));
+        }
+        (dependencies, filter)
+    }
+
+    #[test]
+    fn test_splunk_hec_output() {
+        let success = r#"{"text":"Success","code":0}"#;
+        let (url, collector) = collector(vec![
+            ("503 Service Unavailable", ""),
+            ("200 OK", success),
+            ("200 OK", success),
+        ]);
+        let config: SplunkConfig = serde_yaml::from_str(&format!(
+            "url: {}\ntoken: secret\nindex: security\nbatch_size: 1",
+            url
+        ))
+        .unwrap();
+        let (dependencies, filter) = infected_results();
+        let mut output = SplunkHecOutput::new(config).with_retry_delay(Duration::from_millis(1));
+        output
+            .write_applications(&ScanResults {
+                dependencies: &dependencies,
+                infected: Some(&filter),
+                ..ScanResults::default()
+            })
+            .unwrap();
+
+        // Two findings in batches of one, the first sent twice
+        let requests = collector.join().unwrap();
+        assert_eq!(requests.len(), 3);
+        let (head, body) = &requests[1];
+        assert!(head.starts_with("POST /services/collector/event HTTP/1.1\r\n"));
+        assert!(head.contains("Authorization: Splunk secret\r\n"));
+        let envelope: serde_json::Value = serde_json::from_str(body.trim()).unwrap();
+        assert_eq!(envelope["index"], "security");
+        assert_eq!(envelope["sourcetype"], "scanner:finding");
+        assert_eq!(envelope["event"]["package"], "lodash");
+        assert_eq!(envelope["event"]["status"], "INFECTED");
+        assert_eq!(requests[0].1, requests[1].1);
+        assert!(requests[2].1.contains("\"package\":\"minimist\""));
+    }
+
+    #[test]
+    fn test_elasticsearch_output() {
+        // The first attempt has one document rejected for overload, the
+        // retry indexes it
+        let (url, collector) = collector(vec![
+            (
+                "200 OK",
+                r#"{"errors":true,"items":[{"create":{"status":201}},{"create":{"status":429,"error":{"reason":"busy"}}}]}"#,
+            ),
+            (
+                "200 OK",
+                r#"{"errors":false,"items":[{"create":{"status":201}}]}"#,
+            ),
+        ]);
+        let (dependencies, filter) = infected_results();
+        let config = ElasticsearchConfig {
+            url,
+            index: default_index(),
+            api_key: Some("key".to_string()),
+            username: None,
+            password: None,
+            delivery: Delivery::default(),
+        };
+        let mut output =
+            ElasticsearchOutput::new(config).with_retry_delay(Duration::from_millis(1));
+        let results = ScanResults {
+            dependencies: &dependencies,
+            infected: Some(&filter),
+            ..ScanResults::default()
+        };
+        output.write_applications(&results).unwrap();
+
+        let requests = collector.join().unwrap();
+        let (head, body) = &requests[0];
+        assert!(head.starts_with("POST /_bulk HTTP/1.1\r\n"));
+        assert!(head.contains("Authorization: ApiKey key\r\n"));
+        assert!(head.contains("Content-Type: application/x-ndjson\r\n"));
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], r#"{"create":{"_index":"scanner-findings"}}"#);
+        assert!(lines[1].contains("\"package\":\"lodash\""));
+        // Only the rejected document is sent again
+        assert_eq!(requests[1].1.lines().count(), 2);
+        assert!(requests[1].1.contains("\"package\":\"minimist\""));
+    }
+
+    #[test]
+    fn test_load_sink_config() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("sinks.yaml");
+        std::fs::write(
+            &path,
+            "splunk:\n  url: https://splunk:8088\n  token: ${HEC_TOKEN}\n\
+             elasticsearch:\n  url: https://es:9200\n  username: ci\n  password: pass\n  retries: 5\n",
+        )
+        .unwrap();
+        let mut config: SinkConfig =
+            serde_yaml::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        config.expand_with(&|name| (name == "HEC_TOKEN").then(|| "abc".to_string()));
+        assert_eq!(config.splunk.as_ref().unwrap().token, "abc");
+        assert_eq!(
+            SinkConfig::load_from_file(&path).unwrap().writers().len(),
+            2
+        );
+        let elasticsearch = config.elasticsearch.as_ref().unwrap();
+        assert_eq!(elasticsearch.index, "scanner-findings");
+        assert_eq!(
+            elasticsearch.delivery,
+            Delivery {
+                batch_size: 500,
+                retries: 5
+            }
+        );
+        assert_eq!(
+            elasticsearch.authorization().as_deref(),
+            Some("Basic Y2k6cGFzcw==")
+        );
+        assert!(!format!("{:?}", config).contains("pass\""));
+
+        std::fs::write(&path, "kafka:\n  url: x\n").unwrap();
+        assert!(SinkConfig::load_from_file(&path).is_err());
+    }
+}
@@ -0,0 +1,380 @@
+//! Results split into one file per application or ecosystem
+//!
+//! Teams owning a service only want its rows. With `--split-output` the
+//! results are also written into a directory, one file per group in the
+//! format of the results: per application (`checkout-api.csv`, entries not
+//! linked to an application in `unassigned.csv`) or per ecosystem
+//! (`node.csv`, `python.csv`). File names are the group names with
+//! characters unsafe in a path replaced by `_`; applications sharing a name
+//! get a `-2`, `-3`, ... suffix in root order.
+
+use super::{
+    write_applications_json_with_security, write_classified_csv_with_columns,
+    write_trees_json_with_security, CsvColumn,
+};
+use crate::analyzer::InfectedPackageFilter;
+use crate::models::{
+    Application, ClassifiedDependency, DependencyTree, Ecosystem, ScanError, ScanMetadata,
+};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::hash::Hash;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// File name of entries not linked to an application
+pub const UNASSIGNED: &str = "unassigned";
+
+/// How results are split into files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitBy {
+    /// One file per application
+    Application,
+    /// One file per ecosystem
+    Ecosystem,
+}
+
+impl fmt::Display for SplitBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SplitBy::Application => write!(f, "by-application"),
+            SplitBy::Ecosystem => write!(f, "by-ecosystem"),
+        }
+    }
+}
+
+impl FromStr for SplitBy {
+    type Err = ScanError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "by-application" => Ok(SplitBy::Application),
+            "by-ecosystem" => Ok(SplitBy::Ecosystem),
+            _ => Err(ScanError::UnknownSplit(s.to_string())),
+        }
+    }
+}
+
+/// Items written to one file of a split
+#[derive(Debug, Clone, PartialEq)]
+pub struct SplitGroup<T> {
+    /// File name without extension
+    pub name: String,
+
+    /// Items of the group, in input order
+    pub items: Vec<T>,
+}
+
+impl<T> SplitGroup<T> {
+    /// Path of the group's file in a directory
+    pub fn path(&self, dir: impl AsRef<Path>, extension: &str) -> PathBuf {
+        dir.as_ref().join(format!("{}.{}", self.name, extension))
+    }
+}
+
+/// Group key: a name and, for applications, the root telling apart
+/// applications sharing a name
+type GroupKey = (String, Option<PathBuf>);
+
+/// Split dependency entries per application or ecosystem
+pub fn split_classified(
+    classified: &[ClassifiedDependency],
+    by: SplitBy,
+) -> Vec<SplitGroup<ClassifiedDependency>> {
+    group(classified, |dep| match by {
+        SplitBy::Ecosystem => ecosystem_key(dep.ecosystem),
+        SplitBy::Application => match &dep.application_root {
+            Some(root) => (
+                dep.application_name
+                    .clone()
+                    .unwrap_or_else(|| file_name(root)),
+                Some(root.clone()),
+            ),
+            None => (UNASSIGNED.to_string(), None),
+        },
+    })
+}
+
+/// Split applications per application or ecosystem
+pub fn split_applications(
+    applications: &[Application],
+    by: SplitBy,
+) -> Vec<SplitGroup<Application>> {
+    group(applications, |app| application_key(app, by))
+}
+
+/// Split dependency trees per application or ecosystem
+pub fn split_trees(trees: &[DependencyTree], by: SplitBy) -> Vec<SplitGroup<DependencyTree>> {
+    group(trees, |tree| application_key(&tree.application, by))
+}
+
+/// Write the entries of each group as a CSV file in a directory, returning
+/// the paths written
+///
+/// Split per application, the entries of each application are written with
+/// their application, and entries linked to none to [`UNASSIGNED`]. Every
+/// file has the given columns.
+pub fn write_split_csv(
+    classified: &[ClassifiedDependency],
+    applications: &[Application],
+    by: SplitBy,
+    security_filter: Option<&InfectedPackageFilter>,
+    columns: &[&CsvColumn],
+    dir: impl AsRef<Path>,
+) -> io::Result<Vec<PathBuf>> {
+    let groups = match by {
+        SplitBy::Ecosystem => split_classified(classified, by),
+        SplitBy::Application => {
+            let linked: HashSet<_> = applications
+                .iter()
+                .flat_map(|app| &app.dependencies)
+                .map(entry_key)
+                .collect();
+            let entries: Vec<ClassifiedDependency> = applications
+                .iter()
+                .flat_map(|app| app.dependencies.iter().cloned())
+                .chain(
+                    classified
+                        .iter()
+                        .filter(|dep| !linked.contains(&entry_key(dep)))
+                        .cloned(),
+                )
+                .collect();
+            split_classified(&entries, by)
+        }
+    };
+
+    fs::create_dir_all(dir.as_ref())?;
+    let mut paths = Vec::new();
+    for group in groups {
+        let path = group.path(&dir, "csv");
+        write_classified_csv_with_columns(&group.items, security_filter, columns, &path)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// Write the applications of each group as a JSON document in a directory,
+/// returning the paths written
+pub fn write_split_applications_json(
+    applications: &[Application],
+    by: SplitBy,
+    security_filter: Option<&InfectedPackageFilter>,
+    metadata: Option<&ScanMetadata>,
+    dir: impl AsRef<Path>,
+) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir.as_ref())?;
+    let mut paths = Vec::new();
+    for group in split_applications(applications, by) {
+        let path = group.path(&dir, "json");
+        write_applications_json_with_security(group.items, security_filter, metadata, &path)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// Write the trees of each group as a JSON document in a directory,
+/// returning the paths written
+pub fn write_split_trees_json(
+    trees: &[DependencyTree],
+    by: SplitBy,
+    security_filter: Option<&InfectedPackageFilter>,
+    metadata: Option<&ScanMetadata>,
+    dir: impl AsRef<Path>,
+) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir.as_ref())?;
+    let mut paths = Vec::new();
+    for group in split_trees(trees, by) {
+        let path = group.path(&dir, "json");
+        write_trees_json_with_security(group.items, security_filter, metadata, &path)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// What identifies an entry whether or not it is linked to an application
+fn entry_key(dep: &ClassifiedDependency) -> impl Eq + Hash + '_ {
+    (
+        dep.ecosystem,
+        &dep.name,
+        &dep.package_name_path,
+        &dep.installed_path,
+        &dep.classifications,
+        &dep.source_files,
+    )
+}
+
+fn application_key(app: &Application, by: SplitBy) -> GroupKey {
+    match by {
+        SplitBy::Ecosystem => ecosystem_key(app.ecosystem),
+        SplitBy::Application => (app.name.clone(), Some(app.root_path.clone())),
+    }
+}
+
+fn ecosystem_key(ecosystem: Ecosystem) -> GroupKey {
+    (ecosystem.to_string(), None)
+}
+
+/// Last component of a path, for applications without a name
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| UNASSIGNED.to_string())
+}
+
+/// Group items by key and name the groups' files
+fn group<T: Clone>(items: &[T], key: impl Fn(&T) -> GroupKey) -> Vec<SplitGroup<T>> {
+    let mut groups: BTreeMap<GroupKey, Vec<T>> = BTreeMap::new();
+    for item in items {
+        groups.entry(key(item)).or_default().push(item.clone());
+    }
+
+    let mut taken: BTreeMap<String, usize> = BTreeMap::new();
+    groups
+        .into_iter()
+        .map(|((name, _), items)| {
+            let base = sanitize(&name);
+            let count = taken.entry(base.clone()).or_default();
+            *count += 1;
+            let name = match *count {
+                1 => base,
+                n => format!("{}-{}", base, n),
+            };
+            SplitGroup { name, items }
+        })
+        .collect()
+}
+
+/// Replace characters unsafe in a file name
+fn sanitize(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '@') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match name.trim_start_matches('.') {
+        "" => "_".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::default_columns;
+
+    fn dep(name: &str, ecosystem: Ecosystem, app: Option<(&str, &str)>) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new(name.to_string(), ecosystem);
+        if let Some((app_name, root)) = app {
+            dep.application_name = Some(app_name.to_string());
+            dep.application_root = Some(PathBuf::from(root));
+        }
+        dep
+    }
+
+    #[test]
+    fn test_split_classified() {
+        let classified = vec![
+            dep("lodash", Ecosystem::Node, Some(("@acme/web", "/srv/web"))),
+            dep("flask", Ecosystem::Python, Some(("api", "/srv/api"))),
+            dep("react", Ecosystem::Node, Some(("@acme/web", "/srv/web"))),
+            dep(
+                "requests",
+                Ecosystem::Python,
+                Some(("api", "/srv/tools/api")),
+            ),
+            dep("left-pad", Ecosystem::Node, None),
+        ];
+
+        let groups = split_classified(&classified, SplitBy::Application);
+        let names: Vec<(&str, usize)> = groups
+            .iter()
+            .map(|g| (g.name.as_str(), g.items.len()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("@acme_web", 2),
+                ("api", 1),
+                ("api-2", 1),
+                ("unassigned", 1)
+            ]
+        );
+        assert_eq!(groups[2].items[0].name, "requests");
+        assert_eq!(
+            groups[0].path("/out", "csv"),
+            PathBuf::from("/out/@acme_web.csv")
+        );
+
+        let groups = split_classified(&classified, SplitBy::Ecosystem);
+        let names: Vec<&str> = groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, vec!["node", "python"]);
+        assert_eq!(groups[0].items.len(), 3);
+    }
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(sanitize("my app/v2"), "my_app_v2");
+        assert_eq!(sanitize(".."), "_");
+        assert_eq!(sanitize("..hidden"), "hidden");
+        assert!("by-team".parse::<SplitBy>().is_err());
+    }
+
+    #[test]
+    fn test_write_split_csv() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dir = temp_dir.path().join("split");
+        let classified = vec![
+            dep("lodash", Ecosystem::Node, Some(("web", "/srv/web"))),
+            dep("flask", Ecosystem::Python, Some(("api", "/srv/api"))),
+        ];
+
+        let paths = write_split_csv(
+            &classified,
+            &[],
+            SplitBy::Ecosystem,
+            None,
+            &default_columns(),
+            &dir,
+        )
+        .unwrap();
+
+        assert_eq!(paths, vec![dir.join("node.csv"), dir.join("python.csv")]);
+        let node = fs::read_to_string(&paths[0]).unwrap();
+        assert!(node.contains("lodash"));
+        assert!(!node.contains("flask"));
+
+        // Entries are linked through the applications holding them
+        let unlinked = vec![
+            dep("lodash", Ecosystem::Node, None),
+            dep("flask", Ecosystem::Python, None),
+        ];
+        let mut web = Application::new(
+            "web".to_string(),
+            PathBuf::from("/srv/web"),
+            PathBuf::from("/srv/web/package.json"),
+            Ecosystem::Node,
+        );
+        web.add_dependency(classified[0].clone());
+        let paths = write_split_csv(
+            &unlinked,
+            &[web],
+            SplitBy::Application,
+            None,
+            &default_columns(),
+            &dir,
+        )
+        .unwrap();
+        assert_eq!(paths, vec![dir.join("unassigned.csv"), dir.join("web.csv")]);
+        let unassigned = fs::read_to_string(&paths[0]).unwrap();
+        assert!(unassigned.contains("flask"));
+        assert!(!unassigned.contains("lodash"));
+    }
+}
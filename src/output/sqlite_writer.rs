@@ -0,0 +1,358 @@
+//! Scan history database
+//!
+//! With the `sqlite` feature, each scan can be appended to a local SQLite
+//! database: one `scans` row with the scan metadata and one `findings` row
+//! per package version found, with its classification, application and
+//! security status. Keeping every scan makes trend questions answerable
+//! without re-reading old result files: which packages first appeared this
+//! week, or in which scan an infected version first showed up.
+//!
+//! Version queries look at installed (HAS) and locked (SHOULD) versions;
+//! declared ranges are recorded but are not versions of their own.
+
+use crate::analyzer::InfectedPackageFilter;
+use crate::models::{Classification, ClassifiedDependency, ScanMetadata};
+use rusqlite::{params, params_from_iter, Connection};
+use serde::Serialize;
+use std::io;
+use std::path::Path;
+
+/// Tables of the history database, created when missing
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS scans (
+        id INTEGER PRIMARY KEY,
+        scanner_version TEXT NOT NULL,
+        hostname TEXT,
+        scan_root TEXT NOT NULL,
+        started_at TEXT NOT NULL,
+        finished_at TEXT NOT NULL,
+        files_scanned INTEGER NOT NULL,
+        install_dirs_scanned INTEGER NOT NULL,
+        parse_errors INTEGER NOT NULL,
+        truncated TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS findings (
+        scan_id INTEGER NOT NULL REFERENCES scans(id),
+        ecosystem TEXT NOT NULL,
+        name TEXT NOT NULL,
+        version TEXT NOT NULL,
+        classification TEXT NOT NULL,
+        application_root TEXT,
+        source_file TEXT,
+        security_status TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS findings_package ON findings (ecosystem, name, version);
+";
+
+/// A scan recorded in the history
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HistoryScan {
+    /// Row id, increasing with each recorded scan
+    pub id: i64,
+    /// Scan start, RFC 3339 UTC
+    pub started_at: String,
+    /// Host the scan ran on
+    pub hostname: Option<String>,
+    /// Scanned directory
+    pub scan_root: String,
+    /// Package versions recorded
+    pub findings: usize,
+    /// Package versions recorded as infected
+    pub infected: usize,
+}
+
+/// When a package version was seen across the recorded scans
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PackageAppearance {
+    /// Ecosystem of the package
+    pub ecosystem: String,
+    /// Package name
+    pub name: String,
+    /// Installed or locked version
+    pub version: String,
+    /// Start of the first scan that found the version
+    pub first_seen: String,
+    /// Start of the last scan that found the version
+    pub last_seen: String,
+    /// Id of the first scan that found the version
+    pub first_scan: i64,
+    /// Number of scans that found the version
+    pub scans: usize,
+}
+
+/// SQLite database of scan results over time
+pub struct HistoryDb {
+    conn: Connection,
+}
+
+impl HistoryDb {
+    /// Open the database at `path`, creating it when missing
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::init(Connection::open(path).map_err(io::Error::other)?)
+    }
+
+    /// Open a database that only lives as long as the value
+    pub fn open_in_memory() -> io::Result<Self> {
+        Self::init(Connection::open_in_memory().map_err(io::Error::other)?)
+    }
+
+    fn init(conn: Connection) -> io::Result<Self> {
+        conn.execute_batch(SCHEMA).map_err(io::Error::other)?;
+        Ok(Self { conn })
+    }
+
+    /// Append a scan and its findings, returning the id of the scan
+    pub fn record_scan(
+        &mut self,
+        metadata: &ScanMetadata,
+        dependencies: &[ClassifiedDependency],
+        security_filter: Option<&InfectedPackageFilter>,
+    ) -> io::Result<i64> {
+        let truncated: Vec<String> = metadata.truncated.iter().map(ToString::to_string).collect();
+        let tx = self.conn.transaction().map_err(io::Error::other)?;
+        tx.execute(
+            "INSERT INTO scans (scanner_version, hostname, scan_root, started_at, finished_at,
+                files_scanned, install_dirs_scanned, parse_errors, truncated)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                metadata.scanner_version,
+                metadata.hostname,
+                metadata.scan_root.to_string_lossy(),
+                metadata.started_at,
+                metadata.finished_at,
+                metadata.files_scanned as i64,
+                metadata.install_dirs_scanned as i64,
+                metadata.parse_errors as i64,
+                truncated.join(","),
+            ],
+        )
+        .map_err(io::Error::other)?;
+        let scan_id = tx.last_insert_rowid();
+
+        {
+            let mut insert = tx
+                .prepare(
+                    "INSERT INTO findings (scan_id, ecosystem, name, version, classification,
+                        application_root, source_file, security_status)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                )
+                .map_err(io::Error::other)?;
+            for dep in dependencies {
+                let status = security_filter
+                    .map(|filter| filter.get_security_status(dep).to_string())
+                    .unwrap_or_else(|| "NONE".to_string());
+                let application_root = dep
+                    .application_root
+                    .as_ref()
+                    .map(|root| root.to_string_lossy().to_string());
+                for (classification, version) in &dep.classifications {
+                    let source_file = dep
+                        .get_source_file(*classification)
+                        .map(|file| file.to_string_lossy().to_string());
+                    insert
+                        .execute(params![
+                            scan_id,
+                            dep.ecosystem.to_string(),
+                            dep.name,
+                            version,
+                            classification.to_string(),
+                            application_root,
+                            source_file,
+                            status,
+                        ])
+                        .map_err(io::Error::other)?;
+                }
+            }
+        }
+
+        tx.commit().map_err(io::Error::other)?;
+        Ok(scan_id)
+    }
+
+    /// Recorded scans, oldest first
+    pub fn scans(&self) -> io::Result<Vec<HistoryScan>> {
+        let mut query = self
+            .conn
+            .prepare(
+                "SELECT s.id, s.started_at, s.hostname, s.scan_root,
+                    COUNT(f.scan_id), COUNT(CASE WHEN f.security_status = 'INFECTED' THEN 1 END)
+                 FROM scans s LEFT JOIN findings f ON f.scan_id = s.id
+                 GROUP BY s.id ORDER BY s.id",
+            )
+            .map_err(io::Error::other)?;
+        let rows = query
+            .query_map([], |row| {
+                Ok(HistoryScan {
+                    id: row.get(0)?,
+                    started_at: row.get(1)?,
+                    hostname: row.get(2)?,
+                    scan_root: row.get(3)?,
+                    findings: row.get::<_, i64>(4)? as usize,
+                    infected: row.get::<_, i64>(5)? as usize,
+                })
+            })
+            .map_err(io::Error::other)?;
+        rows.collect::<Result<_, _>>().map_err(io::Error::other)
+    }
+
+    /// Package versions first seen at or after `since` (`YYYY-MM-DD` or an
+    /// RFC 3339 time)
+    pub fn new_since(&self, since: &str) -> io::Result<Vec<PackageAppearance>> {
+        self.appearances("", "HAVING MIN(s.started_at) >= ?", &[since])
+    }
+
+    /// First and last scan that found each version of a package, or of every
+    /// package; with `infected_only`, only scans finding it infected count
+    pub fn first_seen(
+        &self,
+        package: Option<&str>,
+        infected_only: bool,
+    ) -> io::Result<Vec<PackageAppearance>> {
+        let mut condition = String::new();
+        if package.is_some() {
+            condition.push_str(" AND f.name = ?");
+        }
+        if infected_only {
+            condition.push_str(" AND f.security_status = 'INFECTED'");
+        }
+        self.appearances(&condition, "", package.as_slice())
+    }
+
+    /// Group the findings of installed and locked versions by package version
+    fn appearances(
+        &self,
+        condition: &str,
+        having: &str,
+        values: &[&str],
+    ) -> io::Result<Vec<PackageAppearance>> {
+        let sql = format!(
+            "SELECT f.ecosystem, f.name, f.version, MIN(s.started_at), MAX(s.started_at),
+                MIN(s.id), COUNT(DISTINCT s.id)
+             FROM findings f JOIN scans s ON s.id = f.scan_id
+             WHERE f.classification IN ('{}', '{}'){}
+             GROUP BY f.ecosystem, f.name, f.version {}
+             ORDER BY MIN(s.started_at), f.ecosystem, f.name, f.version",
+            Classification::Has,
+            Classification::Should,
+            condition,
+            having
+        );
+        let mut query = self.conn.prepare(&sql).map_err(io::Error::other)?;
+        let rows = query
+            .query_map(params_from_iter(values), |row| {
+                Ok(PackageAppearance {
+                    ecosystem: row.get(0)?,
+                    name: row.get(1)?,
+                    version: row.get(2)?,
+                    first_seen: row.get(3)?,
+                    last_seen: row.get(4)?,
+                    first_scan: row.get(5)?,
+                    scans: row.get::<_, i64>(6)? as usize,
+                })
+            })
+            .map_err(io::Error::other)?;
+        rows.collect::<Result<_, _>>().map_err(io::Error::other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::vuln_filter::InfectedPackage;
+    use crate::models::Ecosystem;
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+
+    fn installed(name: &str, version: &str) -> ClassifiedDependency {
+        let mut dep = ClassifiedDependency::new(name.to_string(), Ecosystem::Node);
+        dep.add_classification(
+            Classification::Has,
+            version.to_string(),
+            PathBuf::from(format!("node_modules/{}/package.json", name)),
+        );
+        dep.add_classification(
+            Classification::Can,
+            format!("^{}", version),
+            PathBuf::from("package.json"),
+        );
+        dep
+    }
+
+    fn metadata(started_at: &str) -> ScanMetadata {
+        ScanMetadata {
+            scan_root: PathBuf::from("/srv/app"),
+            started_at: started_at.to_string(),
+            finished_at: started_at.to_string(),
+            ..ScanMetadata::default()
+        }
+    }
+
+    #[test]
+    fn test_history_trends() {
+        let mut filter = InfectedPackageFilter::new();
+        filter.add_infected_package(InfectedPackage::new(
+            "lodash".to_string(),
+            HashSet::from(["4.17.20".to_string()]),
+        ));
+        let mut db = HistoryDb::open_in_memory().unwrap();
+
+        let first = db
+            .record_scan(
+                &metadata("2026-06-01T08:00:00Z"),
+                &[installed("express", "4.18.2")],
+                Some(&filter),
+            )
+            .unwrap();
+        let second = db
+            .record_scan(
+                &metadata("2026-06-08T08:00:00Z"),
+                &[
+                    installed("express", "4.18.2"),
+                    installed("lodash", "4.17.20"),
+                ],
+                Some(&filter),
+            )
+            .unwrap();
+        assert!(second > first);
+
+        let scans = db.scans().unwrap();
+        assert_eq!(scans.len(), 2);
+        assert_eq!(scans[1].findings, 4);
+        assert_eq!(scans[1].infected, 2);
+
+        let new = db.new_since("2026-06-05").unwrap();
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].name, "lodash");
+        assert_eq!(new[0].first_scan, second);
+
+        let express = db.first_seen(Some("express"), false).unwrap();
+        assert_eq!(express.len(), 1);
+        assert_eq!(express[0].first_seen, "2026-06-01T08:00:00Z");
+        assert_eq!(express[0].last_seen, "2026-06-08T08:00:00Z");
+        assert_eq!(express[0].scans, 2);
+
+        let infected = db.first_seen(None, true).unwrap();
+        assert_eq!(infected.len(), 1);
+        assert_eq!(infected[0].name, "lodash");
+        assert_eq!(infected[0].version, "4.17.20");
+    }
+
+    #[test]
+    fn test_history_persists_across_opens() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.db");
+        HistoryDb::open(&path)
+            .unwrap()
+            .record_scan(
+                &metadata("2026-06-01T08:00:00Z"),
+                &[installed("express", "4.18.2")],
+                None,
+            )
+            .unwrap();
+
+        let scans = HistoryDb::open(&path).unwrap().scans().unwrap();
+        assert_eq!(scans.len(), 1);
+        assert_eq!(scans[0].scan_root, "/srv/app");
+        assert_eq!(scans[0].infected, 0);
+    }
+}
@@ -0,0 +1,109 @@
+//! Dependency staleness report output
+//!
+//! The staleness report is written next to the results in the same format
+//! (`output.csv` → `output.staleness.csv`). Dates are `YYYY-MM-DD`; unknown
+//! dates and day counts are empty in CSV.
+
+use super::schema::{StalenessDocument, SCHEMA_VERSION};
+use super::sidecar_path;
+use crate::analyzer::Staleness;
+use crate::models::ScanMetadata;
+use csv::Writer;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Get the staleness report path for a results file
+pub fn staleness_path(output_path: impl AsRef<Path>) -> PathBuf {
+    sidecar_path(output_path, "staleness")
+}
+
+/// Write dependency staleness as a JSON document
+pub fn write_staleness_json(
+    staleness: &[Staleness],
+    metadata: Option<&ScanMetadata>,
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(&StalenessDocument {
+        schema_version: SCHEMA_VERSION,
+        metadata: metadata.cloned(),
+        staleness: staleness.to_vec(),
+    })?;
+    let mut file = File::create(output_path)?;
+    file.write_all(json.as_bytes())?;
+    Ok(())
+}
+
+/// Write dependency staleness as CSV rows
+pub fn write_staleness_csv(
+    staleness: &[Staleness],
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let mut writer = Writer::from_path(output_path)?;
+    writer.write_record([
+        "package_name",
+        "ecosystem",
+        "version",
+        "latest",
+        "major_behind",
+        "minor_behind",
+        "released",
+        "latest_released",
+        "days_behind",
+    ])?;
+
+    for entry in staleness {
+        writer.write_record([
+            &entry.name,
+            &entry.ecosystem.to_string(),
+            &entry.version,
+            &entry.latest,
+            &entry.major_behind.to_string(),
+            &entry.minor_behind.to_string(),
+            entry.released.as_deref().unwrap_or(""),
+            entry.latest_released.as_deref().unwrap_or(""),
+            &entry
+                .days_behind
+                .map(|days| days.to_string())
+                .unwrap_or_default(),
+        ])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Ecosystem;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_staleness() {
+        let temp_dir = TempDir::new().unwrap();
+        let staleness = vec![Staleness {
+            name: "express".to_string(),
+            ecosystem: Ecosystem::Node,
+            version: "4.18.2".to_string(),
+            latest: "5.1.0".to_string(),
+            major_behind: 1,
+            minor_behind: 4,
+            released: Some("2022-10-08".to_string()),
+            latest_released: None,
+            days_behind: None,
+        }];
+
+        let csv_path = temp_dir.path().join("out.staleness.csv");
+        write_staleness_csv(&staleness, &csv_path).unwrap();
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(content.contains("express,node,4.18.2,5.1.0,1,4,2022-10-08,,\n"));
+
+        let json_path = temp_dir.path().join("out.staleness.json");
+        write_staleness_json(&staleness, None, &json_path).unwrap();
+        let parsed: StalenessDocument =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(parsed.schema_version, SCHEMA_VERSION);
+        assert_eq!(parsed.staleness, staleness);
+    }
+}
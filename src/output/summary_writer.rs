@@ -0,0 +1,265 @@
+//! Scan summary output
+//!
+//! The summary is written next to the main results in the same format
+//! (`output.csv` → `output.summary.csv`, `output.json` → `output.summary.json`)
+//! and rendered as a table for the terminal.
+
+use super::schema::{SummaryDocument, SCHEMA_VERSION};
+use super::sidecar_path;
+use crate::models::{ScanMetadata, ScanSummary};
+use csv::Writer;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Get the summary file path for a results file (`out.csv` → `out.summary.csv`)
+pub fn summary_path(output_path: impl AsRef<Path>) -> PathBuf {
+    sidecar_path(output_path, "summary")
+}
+
+/// Write the summary as a JSON document
+pub fn write_summary_json(
+    summary: &ScanSummary,
+    metadata: Option<&ScanMetadata>,
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(&SummaryDocument {
+        schema_version: SCHEMA_VERSION,
+        metadata: metadata.cloned(),
+        summary: summary.clone(),
+    })?;
+    let mut file = File::create(output_path)?;
+    file.write_all(json.as_bytes())?;
+    Ok(())
+}
+
+/// Write the summary as `section,key,value` CSV rows, led by the scan metadata
+pub fn write_summary_csv(
+    summary: &ScanSummary,
+    metadata: Option<&ScanMetadata>,
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let mut writer = Writer::from_path(output_path)?;
+    writer.write_record(["section", "key", "value"])?;
+
+    if let Some(metadata) = metadata {
+        for (key, value) in metadata_rows(metadata) {
+            writer.write_record(["metadata", key, &value])?;
+        }
+    }
+
+    for (key, value) in totals(summary) {
+        writer.write_record(["totals", key, &value.to_string()])?;
+    }
+    for (section, counts) in count_sections(summary) {
+        for (key, value) in counts {
+            writer.write_record([section, key, &value.to_string()])?;
+        }
+    }
+    for (ecosystem, scan) in &summary.ecosystems {
+        writer.write_record(["ecosystem_status", ecosystem, &scan.status.to_string()])?;
+    }
+    for duration in &summary.durations {
+        writer.write_record([
+            "duration_ms",
+            &duration.phase,
+            &duration.elapsed_ms.to_string(),
+        ])?;
+    }
+    writer.write_record([
+        "duration_ms",
+        "total",
+        &summary.total_duration_ms.to_string(),
+    ])?;
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Render the summary as a human-readable table
+pub fn format_summary_table(summary: &ScanSummary) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "{:<28} {:>10}", "Summary", "Count");
+    let _ = writeln!(out, "{}", "-".repeat(39));
+
+    for (key, value) in totals(summary) {
+        let _ = writeln!(out, "{:<28} {:>10}", key.replace('_', " "), value);
+    }
+
+    for (section, counts) in count_sections(summary) {
+        if counts.is_empty() {
+            continue;
+        }
+        let _ = writeln!(out, "\n{}", section.replace('_', " "));
+        for (key, value) in counts {
+            let _ = writeln!(out, "  {:<26} {:>10}", key, value);
+        }
+    }
+
+    if !summary.ecosystems.is_empty() {
+        let _ = writeln!(out, "\necosystems");
+        for (ecosystem, scan) in &summary.ecosystems {
+            let _ = writeln!(out, "  {:<26} {:>10}", ecosystem, scan.status);
+        }
+    }
+
+    if !summary.durations.is_empty() {
+        let _ = writeln!(out, "\n{:<28} {:>10}", "duration", "ms");
+        for duration in &summary.durations {
+            let _ = writeln!(
+                out,
+                "  {:<26} {:>10}",
+                duration.phase.replace('_', " "),
+                duration.elapsed_ms
+            );
+        }
+        let _ = writeln!(out, "  {:<26} {:>10}", "total", summary.total_duration_ms);
+    }
+
+    out
+}
+
+/// Metadata as `key,value` rows; lists are joined with spaces
+fn metadata_rows(metadata: &ScanMetadata) -> Vec<(&'static str, String)> {
+    vec![
+        ("scanner_version", metadata.scanner_version.clone()),
+        ("hostname", metadata.hostname.clone().unwrap_or_default()),
+        ("scan_root", metadata.scan_root.display().to_string()),
+        (
+            "scan_roots",
+            metadata
+                .scan_roots
+                .iter()
+                .map(|root| root.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+        ("arguments", metadata.arguments.join(" ")),
+        ("started_at", metadata.started_at.clone()),
+        ("finished_at", metadata.finished_at.clone()),
+        ("files_scanned", metadata.files_scanned.to_string()),
+        (
+            "install_dirs_scanned",
+            metadata.install_dirs_scanned.to_string(),
+        ),
+        ("parse_errors", metadata.parse_errors.to_string()),
+        (
+            "truncated",
+            metadata
+                .truncated
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+    ]
+}
+
+/// Scalar totals in display order
+fn totals(summary: &ScanSummary) -> [(&'static str, usize); 18] {
+    [
+        ("files_scanned", summary.files_scanned),
+        ("install_dirs_scanned", summary.install_dirs_scanned),
+        ("total_dependencies", summary.total_dependencies),
+        ("applications", summary.applications),
+        ("version_mismatches", summary.version_mismatches),
+        ("constraint_violations", summary.constraint_violations),
+        ("version_inconsistencies", summary.version_inconsistencies),
+        ("multiple_versions", summary.multiple_versions),
+        ("peer_conflicts", summary.peer_conflicts),
+        ("incompatible_engines", summary.incompatible_engines),
+        ("stale_lockfiles", summary.stale_lockfiles),
+        ("phantom_dependencies", summary.phantom_dependencies),
+        ("missing_installations", summary.missing_installations),
+        ("integrity_mismatches", summary.integrity_mismatches),
+        ("modified_packages", summary.modified_packages),
+        ("running_packages", summary.running_packages),
+        ("unreachable_packages", summary.unreachable_packages),
+        ("parse_errors", summary.parse_errors),
+    ]
+}
+
+/// Count maps in display order
+fn count_sections(summary: &ScanSummary) -> [(&'static str, &BTreeMap<String, usize>); 5] {
+    [
+        ("by_ecosystem", &summary.by_ecosystem),
+        ("by_classification", &summary.by_classification),
+        ("by_dependency_type", &summary.by_dependency_type),
+        ("by_security_status", &summary.by_security_status),
+        ("by_risk_flag", &summary.by_risk_flag),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{EcosystemStatus, EcosystemSummary};
+    use tempfile::TempDir;
+
+    fn sample_summary() -> ScanSummary {
+        let mut summary = ScanSummary::new();
+        summary.files_scanned = 3;
+        summary.total_dependencies = 12;
+        ScanSummary::increment(&mut summary.by_ecosystem, "node");
+        ScanSummary::increment(&mut summary.by_security_status, "INFECTED");
+        summary.add_duration("discovery", 5, 42);
+        summary.ecosystems.insert(
+            "python".to_string(),
+            EcosystemSummary {
+                status: EcosystemStatus::Failed,
+                ..EcosystemSummary::default()
+            },
+        );
+        summary
+    }
+
+    #[test]
+    fn test_summary_path() {
+        assert_eq!(
+            summary_path("/tmp/output.csv"),
+            PathBuf::from("/tmp/output.summary.csv")
+        );
+        assert_eq!(summary_path("results"), PathBuf::from("results.summary"));
+    }
+
+    #[test]
+    fn test_write_summary_json_and_csv() {
+        let temp_dir = TempDir::new().unwrap();
+        let summary = sample_summary();
+
+        let metadata = ScanMetadata::new(temp_dir.path(), std::time::UNIX_EPOCH)
+            .with_arguments(vec!["scanner".to_string(), "--aggregate".to_string()]);
+
+        let json_path = temp_dir.path().join("out.summary.json");
+        write_summary_json(&summary, Some(&metadata), &json_path).unwrap();
+        let parsed: SummaryDocument =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(parsed.schema_version, SCHEMA_VERSION);
+        assert_eq!(parsed.metadata.as_ref(), Some(&metadata));
+        assert_eq!(parsed.summary, summary);
+
+        let csv_path = temp_dir.path().join("out.summary.csv");
+        write_summary_csv(&summary, Some(&metadata), &csv_path).unwrap();
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(content.starts_with("section,key,value\nmetadata,scanner_version,"));
+        assert!(content.contains("metadata,arguments,scanner --aggregate\n"));
+        assert!(content.contains("metadata,started_at,1970-01-01T00:00:00Z\n"));
+        assert!(content.contains("totals,files_scanned,3\n"));
+        assert!(content.contains("by_security_status,INFECTED,1\n"));
+        assert!(content.contains("ecosystem_status,python,failed\n"));
+        assert!(content.contains("duration_ms,discovery,42\n"));
+    }
+
+    #[test]
+    fn test_format_summary_table() {
+        let table = format_summary_table(&sample_summary());
+        assert!(table.contains("total dependencies"));
+        assert!(table.contains("by ecosystem"));
+        assert!(table.contains("INFECTED"));
+        assert!(table.contains("\necosystems\n  python"));
+        // Empty sections are omitted
+        assert!(!table.contains("by classification"));
+    }
+}
@@ -0,0 +1,704 @@
+//! Security findings as syslog or journald events
+//!
+//! [`SyslogOutput`] sends one event per security finding of a scan: each
+//! infected package, and each declared range that can resolve to an infected
+//! version. A SIEM already collecting the host's logs then picks up the
+//! results without a collector of its own. Suppressed findings are not sent.
+//!
+//! The fields of an event are chosen with [`SyslogField`]. Where they go
+//! depends on the target:
+//!
+//! - the local syslog socket (`/dev/log`) takes traditional messages, with
+//!   the fields appended as `key="value"` pairs, which syslog daemons pass on
+//!   untouched and SIEM parsers split;
+//! - a remote collector over UDP takes RFC 5424 messages, with the fields as
+//!   structured data (`[finding@32473 package="lodash" ...]`);
+//! - journald takes native journal entries, with the fields as upper-case
+//!   journal fields (`PACKAGE=lodash`), as `journalctl -o json` shows them.
+//!
+//! Infected packages are logged at the `err` level, possible matches at
+//! `warning`.
+
+use super::{Findings, OutputWriter, ScanResults};
+use crate::analyzer::{InfectedPackageFilter, SecurityStatus};
+use crate::models::scan_metadata::rfc3339;
+use crate::models::{Classification, ClassifiedDependency, ScanError, ScanMetadata, ScanSummary};
+use std::fmt::Write as _;
+use std::io;
+use std::net::UdpSocket;
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::SystemTime;
+use tracing::debug;
+
+/// Name the events are logged under
+pub const SYSLOG_IDENTIFIER: &str = "scanner";
+
+/// Structured data ID of the RFC 5424 events (32473 is the enterprise number
+/// reserved for examples and private use)
+const STRUCTURED_DATA_ID: &str = "finding@32473";
+
+/// Where the events are sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyslogTarget {
+    /// Local syslog daemon, through its datagram socket
+    Syslog(PathBuf),
+    /// journald, through its native protocol socket
+    Journald(PathBuf),
+    /// Remote collector, as `host:port`
+    Udp(String),
+}
+
+impl SyslogTarget {
+    /// Socket of the local syslog daemon
+    pub const SYSLOG_SOCKET: &'static str = "/dev/log";
+
+    /// Socket of journald's native protocol
+    pub const JOURNALD_SOCKET: &'static str = "/run/systemd/journal/socket";
+}
+
+impl std::fmt::Display for SyslogTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyslogTarget::Syslog(path) => write!(f, "syslog ({})", path.display()),
+            SyslogTarget::Journald(path) => write!(f, "journald ({})", path.display()),
+            SyslogTarget::Udp(address) => write!(f, "udp://{}", address),
+        }
+    }
+}
+
+/// `syslog`, `journald`, `udp://host:port` (port 514 when left out) or the
+/// path of a syslog socket
+impl FromStr for SyslogTarget {
+    type Err = ScanError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "syslog" => Ok(SyslogTarget::Syslog(PathBuf::from(Self::SYSLOG_SOCKET))),
+            "journald" => Ok(SyslogTarget::Journald(PathBuf::from(Self::JOURNALD_SOCKET))),
+            _ => {
+                if let Some(address) = s.strip_prefix("udp://") {
+                    let address = address.trim_end_matches('/');
+                    if address.is_empty() {
+                        return Err(ScanError::InvalidSyslogTarget(s.to_string()));
+                    }
+                    let has_port = address
+                        .rsplit_once(':')
+                        .is_some_and(|(_, port)| port.parse::<u16>().is_ok());
+                    Ok(SyslogTarget::Udp(if has_port {
+                        address.to_string()
+                    } else {
+                        format!("{}:514", address)
+                    }))
+                } else if s.starts_with('/') {
+                    Ok(SyslogTarget::Syslog(PathBuf::from(s)))
+                } else {
+                    Err(ScanError::InvalidSyslogTarget(s.to_string()))
+                }
+            }
+        }
+    }
+}
+
+/// Syslog facility of the events
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SyslogFacility {
+    #[default]
+    User,
+    Daemon,
+    Auth,
+    Authpriv,
+    Local0,
+    Local1,
+    Local2,
+    Local3,
+    Local4,
+    Local5,
+    Local6,
+    Local7,
+}
+
+impl SyslogFacility {
+    /// Facility code (RFC 5424, section 6.2.1)
+    pub fn code(self) -> u8 {
+        match self {
+            SyslogFacility::User => 1,
+            SyslogFacility::Daemon => 3,
+            SyslogFacility::Auth => 4,
+            SyslogFacility::Authpriv => 10,
+            SyslogFacility::Local0 => 16,
+            SyslogFacility::Local1 => 17,
+            SyslogFacility::Local2 => 18,
+            SyslogFacility::Local3 => 19,
+            SyslogFacility::Local4 => 20,
+            SyslogFacility::Local5 => 21,
+            SyslogFacility::Local6 => 22,
+            SyslogFacility::Local7 => 23,
+        }
+    }
+}
+
+impl std::fmt::Display for SyslogFacility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyslogFacility::User => write!(f, "user"),
+            SyslogFacility::Daemon => write!(f, "daemon"),
+            SyslogFacility::Auth => write!(f, "auth"),
+            SyslogFacility::Authpriv => write!(f, "authpriv"),
+            SyslogFacility::Local0 => write!(f, "local0"),
+            SyslogFacility::Local1 => write!(f, "local1"),
+            SyslogFacility::Local2 => write!(f, "local2"),
+            SyslogFacility::Local3 => write!(f, "local3"),
+            SyslogFacility::Local4 => write!(f, "local4"),
+            SyslogFacility::Local5 => write!(f, "local5"),
+            SyslogFacility::Local6 => write!(f, "local6"),
+            SyslogFacility::Local7 => write!(f, "local7"),
+        }
+    }
+}
+
+impl FromStr for SyslogFacility {
+    type Err = ScanError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "user" => Ok(SyslogFacility::User),
+            "daemon" => Ok(SyslogFacility::Daemon),
+            "auth" => Ok(SyslogFacility::Auth),
+            "authpriv" => Ok(SyslogFacility::Authpriv),
+            "local0" => Ok(SyslogFacility::Local0),
+            "local1" => Ok(SyslogFacility::Local1),
+            "local2" => Ok(SyslogFacility::Local2),
+            "local3" => Ok(SyslogFacility::Local3),
+            "local4" => Ok(SyslogFacility::Local4),
+            "local5" => Ok(SyslogFacility::Local5),
+            "local6" => Ok(SyslogFacility::Local6),
+            "local7" => Ok(SyslogFacility::Local7),
+            _ => Err(ScanError::UnknownFacility(s.to_string())),
+        }
+    }
+}
+
+/// A field of a finding event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyslogField {
+    /// Package name
+    Package,
+    /// Installed or locked version, or the declared range of a possible match
+    Version,
+    /// Ecosystem
+    Ecosystem,
+    /// INFECTED or MATCH_VERSION
+    Status,
+    /// Severity of the advisory
+    Severity,
+    /// CVSS base score of the advisory
+    Cvss,
+    /// Advisory identifiers, comma-separated
+    Advisories,
+    /// Nearest version no advisory affects
+    RecommendedVersion,
+    /// Package URL
+    Purl,
+    /// Name of the application, or its root without one
+    Application,
+    /// Installation directory or package file the finding comes from
+    Path,
+    /// Scanned host
+    Host,
+}
+
+impl SyslogField {
+    /// All fields, in the order events list them
+    pub const ALL: [SyslogField; 12] = [
+        SyslogField::Package,
+        SyslogField::Version,
+        SyslogField::Ecosystem,
+        SyslogField::Status,
+        SyslogField::Severity,
+        SyslogField::Cvss,
+        SyslogField::Advisories,
+        SyslogField::RecommendedVersion,
+        SyslogField::Purl,
+        SyslogField::Application,
+        SyslogField::Path,
+        SyslogField::Host,
+    ];
+
+    /// Field name as given to `--syslog-fields` and written in syslog events
+    pub fn name(self) -> &'static str {
+        match self {
+            SyslogField::Package => "package",
+            SyslogField::Version => "version",
+            SyslogField::Ecosystem => "ecosystem",
+            SyslogField::Status => "status",
+            SyslogField::Severity => "severity",
+            SyslogField::Cvss => "cvss",
+            SyslogField::Advisories => "advisories",
+            SyslogField::RecommendedVersion => "recommended_version",
+            SyslogField::Purl => "purl",
+            SyslogField::Application => "application",
+            SyslogField::Path => "path",
+            SyslogField::Host => "host",
+        }
+    }
+
+    /// Value of the field for an annotated finding
+    fn value(self, dep: &ClassifiedDependency, metadata: Option<&ScanMetadata>) -> Option<String> {
+        match self {
+            SyslogField::Package => Some(dep.name.clone()),
+            SyslogField::Version => finding_version(dep).map(str::to_string),
+            SyslogField::Ecosystem => Some(dep.ecosystem.to_string()),
+            SyslogField::Status => dep.security.clone(),
+            SyslogField::Severity => dep.severity.clone(),
+            SyslogField::Cvss => dep.cvss.map(|cvss| cvss.to_string()),
+            SyslogField::Advisories => {
+                Some(dep.advisory_ids.join(",")).filter(|ids| !ids.is_empty())
+            }
+            SyslogField::RecommendedVersion => dep.recommended_version.clone(),
+            SyslogField::Purl => Some(dep.purl()),
+            SyslogField::Application => dep.application_name.clone().or_else(|| {
+                dep.application_root
+                    .as_ref()
+                    .map(|root| root.display().to_string())
+            }),
+            SyslogField::Path => dep
+                .installed_path
+                .as_ref()
+                .or_else(|| dep.source_files.values().next())
+                .map(|path| path.display().to_string()),
+            SyslogField::Host => metadata.and_then(|metadata| metadata.hostname.clone()),
+        }
+    }
+}
+
+impl FromStr for SyslogField {
+    type Err = ScanError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim();
+        Self::ALL
+            .into_iter()
+            .find(|field| field.name() == name)
+            .ok_or_else(|| ScanError::UnknownSyslogField {
+                name: name.to_string(),
+                valid: Self::ALL
+                    .iter()
+                    .map(|field| field.name())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            })
+    }
+}
+
+/// Fields named by `--syslog-fields`, in the given order
+pub fn parse_syslog_fields<S: AsRef<str>>(names: &[S]) -> Result<Vec<SyslogField>, ScanError> {
+    names.iter().map(|name| name.as_ref().parse()).collect()
+}
+
+/// Sends one event per security finding to syslog or journald
+#[derive(Debug)]
+pub struct SyslogOutput {
+    target: SyslogTarget,
+    facility: SyslogFacility,
+    fields: Vec<SyslogField>,
+    socket: Option<Socket>,
+}
+
+#[derive(Debug)]
+enum Socket {
+    #[cfg(unix)]
+    Unix(UnixDatagram),
+    Udp(UdpSocket),
+}
+
+impl SyslogOutput {
+    /// Send to a target with the `user` facility and all fields
+    pub fn new(target: SyslogTarget) -> Self {
+        Self {
+            target,
+            facility: SyslogFacility::default(),
+            fields: SyslogField::ALL.to_vec(),
+            socket: None,
+        }
+    }
+
+    /// Log under this facility
+    pub fn with_facility(mut self, facility: SyslogFacility) -> Self {
+        self.facility = facility;
+        self
+    }
+
+    /// Send these fields, in order
+    pub fn with_fields(mut self, fields: Vec<SyslogField>) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    /// Where the events are sent
+    pub fn target(&self) -> &SyslogTarget {
+        &self.target
+    }
+
+    /// Format the event of an annotated finding
+    fn format_event(
+        &self,
+        dep: &ClassifiedDependency,
+        status: SecurityStatus,
+        metadata: Option<&ScanMetadata>,
+    ) -> Vec<u8> {
+        let severity = match status {
+            SecurityStatus::Infected => 3,
+            _ => 4,
+        };
+        let priority = u32::from(self.facility.code()) * 8 + severity;
+        let message = finding_message(dep, status);
+        let fields: Vec<(SyslogField, String)> = self
+            .fields
+            .iter()
+            .filter_map(|&field| field.value(dep, metadata).map(|value| (field, value)))
+            .collect();
+
+        match &self.target {
+            SyslogTarget::Syslog(_) => {
+                let mut event = format!(
+                    "<{}>{}[{}]: {}",
+                    priority,
+                    SYSLOG_IDENTIFIER,
+                    std::process::id(),
+                    message
+                );
+                for (field, value) in &fields {
+                    let _ = write!(event, " {}=\"{}\"", field.name(), escape_value(value));
+                }
+                event.into_bytes()
+            }
+            SyslogTarget::Udp(_) => {
+                let hostname = metadata
+                    .and_then(|metadata| metadata.hostname.as_deref())
+                    .unwrap_or("-");
+                let mut event = format!(
+                    "<{}>1 {} {} {} {} finding ",
+                    priority,
+                    rfc3339(SystemTime::now()),
+                    hostname,
+                    SYSLOG_IDENTIFIER,
+                    std::process::id()
+                );
+                if fields.is_empty() {
+                    event.push('-');
+                } else {
+                    let _ = write!(event, "[{}", STRUCTURED_DATA_ID);
+                    for (field, value) in &fields {
+                        let _ = write!(event, " {}=\"{}\"", field.name(), escape_value(value));
+                    }
+                    event.push(']');
+                }
+                let _ = write!(event, " {}", message);
+                event.into_bytes()
+            }
+            SyslogTarget::Journald(_) => {
+                let mut event = Vec::new();
+                journal_field(&mut event, "MESSAGE", &message);
+                journal_field(&mut event, "PRIORITY", &severity.to_string());
+                journal_field(
+                    &mut event,
+                    "SYSLOG_FACILITY",
+                    &self.facility.code().to_string(),
+                );
+                journal_field(&mut event, "SYSLOG_IDENTIFIER", SYSLOG_IDENTIFIER);
+                for (field, value) in &fields {
+                    journal_field(&mut event, &field.name().to_uppercase(), value);
+                }
+                event
+            }
+        }
+    }
+
+    /// Send an event, connecting on the first one
+    fn send(&mut self, event: &[u8]) -> io::Result<()> {
+        if self.socket.is_none() {
+            self.socket = Some(connect(&self.target)?);
+        }
+        match self.socket.as_ref() {
+            #[cfg(unix)]
+            Some(Socket::Unix(socket)) => socket.send(event).map(|_| ()),
+            Some(Socket::Udp(socket)) => socket.send(event).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl OutputWriter for SyslogOutput {
+    fn write_applications(&mut self, results: &ScanResults<'_>) -> io::Result<()> {
+        let mut sent = 0;
+        for dep in results.dependencies {
+            let Some((finding, status)) = security_finding(dep, results.infected) else {
+                continue;
+            };
+            let event = self.format_event(&finding, status, results.metadata);
+            self.send(&event)?;
+            sent += 1;
+        }
+        debug!(target = %self.target, events = sent, "Sent security findings");
+        Ok(())
+    }
+
+    fn write_findings(
+        &mut self,
+        _findings: &Findings<'_>,
+        _metadata: Option<&ScanMetadata>,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn write_summary(
+        &mut self,
+        _summary: &ScanSummary,
+        _metadata: Option<&ScanMetadata>,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The dependency annotated with its advisory when it is a security finding
+///
+/// Without an infected list, entries annotated before (results read back
+/// from a file) keep their status.
+pub(super) fn security_finding(
+    dep: &ClassifiedDependency,
+    infected: Option<&InfectedPackageFilter>,
+) -> Option<(ClassifiedDependency, SecurityStatus)> {
+    let status = match infected {
+        Some(filter) => filter.get_security_status(dep),
+        None => match dep.security.as_deref() {
+            Some("INFECTED") if dep.suppressed.is_none() => SecurityStatus::Infected,
+            Some("MATCH_VERSION") if dep.suppressed.is_none() => SecurityStatus::MatchVersion,
+            _ => SecurityStatus::None,
+        },
+    };
+    if !matches!(
+        status,
+        SecurityStatus::Infected | SecurityStatus::MatchVersion
+    ) {
+        return None;
+    }
+    let mut finding = dep.clone();
+    if let Some(filter) = infected {
+        filter.annotate(&mut finding);
+    }
+    Some((finding, status))
+}
+
+/// Version a finding is about: the declared range of a possible match,
+/// the installed or locked version otherwise
+pub(super) fn finding_version(dep: &ClassifiedDependency) -> Option<&str> {
+    match dep.security.as_deref() {
+        Some("MATCH_VERSION") => dep.get_version(Classification::Can),
+        _ => dep.get_primary_version(),
+    }
+}
+
+/// One-line description of an annotated finding
+pub(super) fn finding_message(dep: &ClassifiedDependency, status: SecurityStatus) -> String {
+    let mut message = match status {
+        SecurityStatus::Infected => format!(
+            "{} {} is infected",
+            dep.name,
+            finding_version(dep).unwrap_or("")
+        ),
+        _ => format!(
+            "{} {} can resolve to an infected version",
+            dep.name,
+            finding_version(dep).unwrap_or("")
+        ),
+    };
+    if !dep.advisory_ids.is_empty() {
+        let _ = write!(message, " ({})", dep.advisory_ids.join(", "));
+    }
+    if let Some(version) = &dep.recommended_version {
+        let _ = write!(message, "; upgrade to {}", version);
+    }
+    message
+}
+
+/// Escape a value for a quoted syslog parameter (RFC 5424, section 6.3.3)
+fn escape_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' | '\\' | ']' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' | '\r' => escaped.push(' '),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Append a field in journald's native format; values with a newline are
+/// written with their length instead of a terminator
+fn journal_field(event: &mut Vec<u8>, name: &str, value: &str) {
+    event.extend_from_slice(name.as_bytes());
+    if value.contains('\n') {
+        event.push(b'\n');
+        event.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        event.push(b'=');
+    }
+    event.extend_from_slice(value.as_bytes());
+    event.push(b'\n');
+}
+
+#[cfg(unix)]
+fn connect(target: &SyslogTarget) -> io::Result<Socket> {
+    match target {
+        SyslogTarget::Syslog(path) | SyslogTarget::Journald(path) => {
+            let socket = UnixDatagram::unbound()?;
+            socket.connect(path)?;
+            Ok(Socket::Unix(socket))
+        }
+        SyslogTarget::Udp(address) => connect_udp(address),
+    }
+}
+
+#[cfg(not(unix))]
+fn connect(target: &SyslogTarget) -> io::Result<Socket> {
+    match target {
+        SyslogTarget::Udp(address) => connect_udp(address),
+        _ => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "syslog and journald sockets are only supported on Unix",
+        )),
+    }
+}
+
+fn connect_udp(address: &str) -> io::Result<Socket> {
+    let socket = UdpSocket::bind(if address.starts_with('[') {
+        "[::]:0"
+    } else {
+        "0.0.0.0:0"
+    })?;
+    socket.connect(address)?;
+    Ok(Socket::Udp(socket))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::vuln_filter::InfectedPackage;
+    use crate::models::Ecosystem;
+    use std::collections::HashSet;
+
+    fn infected_results() -> (Vec<ClassifiedDependency>, InfectedPackageFilter) {
+        let installed = PathBuf::from("/srv/web/node_modules/lodash");
+        let mut lodash = ClassifiedDependency::new("lodash".to_string(), Ecosystem::Node);
+        lodash.add_classification(
+            Classification::Has,
+            "4.17.20".to_string(),
+            installed.clone(),
+        );
+        lodash.application_name = Some("web".to_string());
+        lodash.installed_path = Some(installed);
+        let mut react = ClassifiedDependency::new("react".to_string(), Ecosystem::Node);
+        react.add_classification(
+            Classification::Has,
+            "18.2.0".to_string(),
+            PathBuf::from("/srv/web/node_modules/react"),
+        );
+
+        let mut filter = InfectedPackageFilter::new();
+        filter.add_infected_package(InfectedPackage::new(
+            "lodash".to_string(),
+            HashSet::from(["4.17.20".to_string()]),
+        ));
+        (vec![lodash, react], filter)
+    }
+
+    #[test]
+    fn test_parse_syslog_options() {
+        assert_eq!(
+            "journald".parse::<SyslogTarget>().unwrap(),
+            SyslogTarget::Journald(PathBuf::from(SyslogTarget::JOURNALD_SOCKET))
+        );
+        assert_eq!(
+            "udp://siem.internal".parse::<SyslogTarget>().unwrap(),
+            SyslogTarget::Udp("siem.internal:514".to_string())
+        );
+        assert_eq!(
+            "udp://10.0.0.5:1514".parse::<SyslogTarget>().unwrap(),
+            SyslogTarget::Udp("10.0.0.5:1514".to_string())
+        );
+        assert!("tcp://siem".parse::<SyslogTarget>().is_err());
+        assert_eq!(
+            "LOCAL4".parse::<SyslogFacility>().unwrap(),
+            SyslogFacility::Local4
+        );
+        assert!("kern".parse::<SyslogFacility>().is_err());
+        assert_eq!(
+            parse_syslog_fields(&["package", " version"]).unwrap(),
+            [SyslogField::Package, SyslogField::Version]
+        );
+        assert!(parse_syslog_fields(&["name"]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_syslog_output() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let socket_path = temp_dir.path().join("log");
+        let receiver = UnixDatagram::bind(&socket_path).unwrap();
+        let (dependencies, filter) = infected_results();
+
+        let mut output = SyslogOutput::new(SyslogTarget::Syslog(socket_path))
+            .with_facility(SyslogFacility::Local0)
+            .with_fields(vec![SyslogField::Package, SyslogField::Application]);
+        output
+            .write_applications(&ScanResults {
+                dependencies: &dependencies,
+                infected: Some(&filter),
+                ..ScanResults::default()
+            })
+            .unwrap();
+
+        // One event, for the infected package only: local0.err is 16 * 8 + 3
+        let mut buf = [0; 1024];
+        let len = receiver.recv(&mut buf).unwrap();
+        let event = String::from_utf8_lossy(&buf[..len]);
+        assert!(event.starts_with("<131>scanner["), "{}", event);
+        assert!(
+            event.ends_with("]: lodash 4.17.20 is infected package=\"lodash\" application=\"web\"")
+        );
+        receiver.set_nonblocking(true).unwrap();
+        assert!(receiver.recv(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_syslog_event_formats() {
+        let (dependencies, filter) = infected_results();
+        let (finding, status) = security_finding(&dependencies[0], Some(&filter)).unwrap();
+        assert!(security_finding(&dependencies[1], Some(&filter)).is_none());
+
+        let udp = SyslogOutput::new(SyslogTarget::Udp("localhost:514".to_string()))
+            .with_fields(vec![SyslogField::Package, SyslogField::Status]);
+        let event = String::from_utf8(udp.format_event(&finding, status, None)).unwrap();
+        assert!(event.starts_with("<11>1 "), "{}", event);
+        assert!(event.contains("Z - scanner "), "{}", event);
+        assert!(event.ends_with(
+            " finding [finding@32473 package=\"lodash\" status=\"INFECTED\"] lodash 4.17.20 is infected"
+        ));
+
+        let journald = SyslogOutput::new(SyslogTarget::Journald(PathBuf::from("/dev/null")))
+            .with_fields(vec![SyslogField::Path]);
+        let event = String::from_utf8(journald.format_event(&finding, status, None)).unwrap();
+        assert_eq!(
+            event,
+            "MESSAGE=lodash 4.17.20 is infected\nPRIORITY=3\nSYSLOG_FACILITY=1\n\
+             SYSLOG_IDENTIFIER=scanner\nPATH=/srv/web/node_modules/lodash\n"
+        );
+    }
+}
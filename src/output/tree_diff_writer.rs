@@ -0,0 +1,195 @@
+//! Tree diff output
+//!
+//! The differences of `scanner diff` are written as a [`TreeDiffDocument`],
+//! or rendered for the terminal, one block per application:
+//!
+//! ```text
+//! web (node, /srv/web)
+//!   + axios 1.6.0
+//!       + follow-redirects@1.15.4
+//!   ~ express 4.18.2 -> 4.19.0
+//!       + cookie@0.6.0
+//!       - cookie@0.5.0
+//!   ~ webpack > terser 5.1.0 -> 5.2.0
+//! ```
+//!
+//! Each changed package is shown with the packages leading to it, and below
+//! it the packages its change introduced (`+`) and dropped (`-`). When
+//! coloring is enabled, additions are printed in green and removals in red.
+
+use super::schema::{TreeDiffDocument, SCHEMA_VERSION};
+use crate::analyzer::{ApplicationTreeDiff, ChangeKind, SubtreeChange, TreeDiffStatus};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+/// Write tree differences as a JSON document
+pub fn write_tree_diff_json(
+    diffs: &[ApplicationTreeDiff],
+    output_path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    fs::write(output_path, format_tree_diff_json(diffs)? + "\n")
+}
+
+/// Tree differences as a JSON document
+pub fn format_tree_diff_json(diffs: &[ApplicationTreeDiff]) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&TreeDiffDocument {
+        schema_version: SCHEMA_VERSION,
+        applications: diffs.to_vec(),
+    })
+}
+
+/// Render tree differences as indented text, one block per application
+pub fn format_tree_diff_text(diffs: &[ApplicationTreeDiff], color: bool) -> String {
+    let paint = |color_code: &str, text: String| {
+        if color {
+            format!("{}{}{}", color_code, text, RESET)
+        } else {
+            text
+        }
+    };
+
+    let mut out = String::new();
+    for (i, diff) in diffs.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let heading = format!(
+            "{} ({}, {})",
+            diff.name,
+            diff.ecosystem,
+            diff.root.display()
+        );
+        match diff.status {
+            TreeDiffStatus::Added => {
+                let _ = writeln!(out, "{}", paint(GREEN, format!("{}: added", heading)));
+            }
+            TreeDiffStatus::Removed => {
+                let _ = writeln!(out, "{}", paint(RED, format!("{}: removed", heading)));
+            }
+            TreeDiffStatus::Changed => {
+                let _ = writeln!(out, "{}", heading);
+            }
+        }
+
+        for change in &diff.changes {
+            let (marker, color_code) = match change.kind {
+                ChangeKind::Added => ("+", GREEN),
+                ChangeKind::Removed => ("-", RED),
+                ChangeKind::VersionChanged => ("~", YELLOW),
+            };
+            let _ = writeln!(
+                out,
+                "  {}",
+                paint(color_code, format!("{} {}", marker, label(change)))
+            );
+            for package in &change.introduced {
+                let _ = writeln!(out, "      {}", paint(GREEN, format!("+ {}", package)));
+            }
+            for package in &change.dropped {
+                let _ = writeln!(out, "      {}", paint(RED, format!("- {}", package)));
+            }
+        }
+    }
+    out
+}
+
+/// `parent > name old -> new`
+fn label(change: &SubtreeChange) -> String {
+    let mut label = change.path.join(" > ");
+    if !label.is_empty() {
+        label.push_str(" > ");
+    }
+    label.push_str(&change.name);
+    match (&change.old_version, &change.new_version) {
+        (Some(old), Some(new)) => {
+            let _ = write!(label, " {} -> {}", old, new);
+        }
+        (Some(version), None) | (None, Some(version)) => {
+            let _ = write!(label, " {}", version);
+        }
+        (None, None) => {}
+    }
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Ecosystem;
+    use std::path::PathBuf;
+
+    fn diffs() -> Vec<ApplicationTreeDiff> {
+        vec![
+            ApplicationTreeDiff {
+                name: "web".to_string(),
+                root: PathBuf::from("/srv/web"),
+                ecosystem: Ecosystem::Node,
+                status: TreeDiffStatus::Changed,
+                changes: vec![
+                    SubtreeChange {
+                        path: Vec::new(),
+                        name: "express".to_string(),
+                        kind: ChangeKind::VersionChanged,
+                        old_version: Some("4.18.2".to_string()),
+                        new_version: Some("4.19.0".to_string()),
+                        introduced: vec!["cookie@0.6.0".to_string()],
+                        dropped: vec!["cookie@0.5.0".to_string()],
+                    },
+                    SubtreeChange {
+                        path: vec!["webpack".to_string()],
+                        name: "acorn".to_string(),
+                        kind: ChangeKind::Removed,
+                        old_version: Some("8.0.0".to_string()),
+                        new_version: None,
+                        introduced: Vec::new(),
+                        dropped: Vec::new(),
+                    },
+                ],
+            },
+            ApplicationTreeDiff {
+                name: "api".to_string(),
+                root: PathBuf::from("/srv/api"),
+                ecosystem: Ecosystem::Python,
+                status: TreeDiffStatus::Added,
+                changes: Vec::new(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_format_tree_diff_text() {
+        assert_eq!(
+            format_tree_diff_text(&diffs(), false),
+            "web (node, /srv/web)\n\
+             \x20 ~ express 4.18.2 -> 4.19.0\n\
+             \x20     + cookie@0.6.0\n\
+             \x20     - cookie@0.5.0\n\
+             \x20 - webpack > acorn 8.0.0\n\
+             \n\
+             api (python, /srv/api): added\n"
+        );
+        let colored = format_tree_diff_text(&diffs(), true);
+        assert!(colored.contains("\x1b[33m~ express 4.18.2 -> 4.19.0\x1b[0m"));
+    }
+
+    #[test]
+    fn test_write_tree_diff_json() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("diff.json");
+        write_tree_diff_json(&diffs(), &path).unwrap();
+
+        let document: TreeDiffDocument =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(document.schema_version, SCHEMA_VERSION);
+        assert_eq!(document.applications, diffs());
+        let json = fs::read_to_string(&path).unwrap();
+        assert!(json.contains(r#""kind": "version_changed""#));
+        assert!(json.contains(r#""status": "added""#));
+    }
+}
@@ -0,0 +1,227 @@
+//! Terminal rendering of dependency trees, like `cargo tree`
+//!
+//! ```text
+//! web (node, /srv/web)
+//! ├── express@4.18.2
+//! │   ├── body-parser@1.20.1
+//! │   │   └── qs@6.11.0 [INFECTED]
+//! │   └── qs@6.11.0 (*) [INFECTED]
+//! └── lodash@4.17.21
+//! ```
+//!
+//! Packages whose subtree is shown earlier are marked `(*)` and packages cut
+//! by the depth limit `(...)`. With an infected list, matching packages get
+//! their [`SecurityStatus`] appended and, when coloring is enabled, infected
+//! packages are printed in red and matching ranges or names in yellow.
+
+use crate::analyzer::{InfectedPackageFilter, SecurityStatus};
+use crate::models::{DependencyNode, DependencyTree, ScanError};
+use std::fmt::Write as _;
+use std::str::FromStr;
+
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+/// Characters used to draw tree branches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TreeCharset {
+    /// Unicode box-drawing characters
+    #[default]
+    Utf8,
+    /// Plain ASCII
+    Ascii,
+}
+
+impl TreeCharset {
+    /// Prefixes for (last child, other child, continuing line, blank)
+    fn parts(self) -> [&'static str; 4] {
+        match self {
+            TreeCharset::Utf8 => ["└── ", "├── ", "│   ", "    "],
+            TreeCharset::Ascii => ["`-- ", "|-- ", "|   ", "    "],
+        }
+    }
+}
+
+impl FromStr for TreeCharset {
+    type Err = ScanError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "utf8" => Ok(TreeCharset::Utf8),
+            "ascii" => Ok(TreeCharset::Ascii),
+            _ => Err(ScanError::UnknownCharset(s.to_string())),
+        }
+    }
+}
+
+/// Render dependency trees as indented text, one block per application
+pub fn format_trees_text(
+    trees: &[DependencyTree],
+    security_filter: Option<&InfectedPackageFilter>,
+    charset: TreeCharset,
+    color: bool,
+) -> String {
+    let mut out = String::new();
+    for (i, tree) in trees.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let app = &tree.application;
+        let _ = writeln!(
+            out,
+            "{} ({}, {})",
+            app.name,
+            app.ecosystem,
+            app.root_path.display()
+        );
+        let renderer = Renderer {
+            tree,
+            security_filter,
+            parts: charset.parts(),
+            color,
+        };
+        let roots = tree.get_roots();
+        for (j, root) in roots.iter().enumerate() {
+            renderer.render(root, "", j + 1 == roots.len(), &mut out);
+        }
+    }
+    out
+}
+
+/// Settings shared while rendering one tree
+struct Renderer<'a> {
+    tree: &'a DependencyTree,
+    security_filter: Option<&'a InfectedPackageFilter>,
+    parts: [&'static str; 4],
+    color: bool,
+}
+
+impl Renderer<'_> {
+    fn render(&self, node: &DependencyNode, prefix: &str, last: bool, out: &mut String) {
+        let [last_branch, branch, line, blank] = self.parts;
+        let _ = writeln!(
+            out,
+            "{}{}{}",
+            prefix,
+            if last { last_branch } else { branch },
+            self.label(node)
+        );
+
+        let child_prefix = format!("{}{}", prefix, if last { blank } else { line });
+        let children = node.get_dependencies();
+        for (i, child) in children.iter().enumerate() {
+            self.render(child, &child_prefix, i + 1 == children.len(), out);
+        }
+    }
+
+    /// `name@version` with markers and security status
+    fn label(&self, node: &DependencyNode) -> String {
+        let mut label = format!("{}@{}", node.name, node.version);
+        if node.duplicate {
+            label.push_str(" (*)");
+        }
+        if node.truncated {
+            label.push_str(" (...)");
+        }
+
+        let status = self.security_filter.and_then(|filter| {
+            self.tree
+                .application
+                .find_dependency(&node.name)
+                .map(|dep| filter.get_security_status(dep))
+        });
+        let color = match status {
+            Some(SecurityStatus::Infected) => RED,
+            Some(SecurityStatus::MatchVersion | SecurityStatus::MatchPackage) => YELLOW,
+            _ => return label,
+        };
+        let _ = write!(label, " [{}]", status.unwrap_or(SecurityStatus::None));
+        if self.color {
+            label = format!("{}{}{}", color, label, RESET);
+        }
+        label
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::vuln_filter::InfectedPackage;
+    use crate::models::{Application, Classification, ClassifiedDependency, Ecosystem};
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+
+    fn node(name: &str, children: Vec<DependencyNode>) -> DependencyNode {
+        let mut node = DependencyNode::new(
+            name.to_string(),
+            "1.0.0".to_string(),
+            Classification::Has,
+            false,
+        );
+        node.dependencies = children;
+        node
+    }
+
+    fn tree() -> DependencyTree {
+        let mut app = Application::new(
+            "web".to_string(),
+            PathBuf::from("/srv/web"),
+            PathBuf::from("/srv/web/package.json"),
+            Ecosystem::Node,
+        );
+        let mut qs = ClassifiedDependency::new("qs".to_string(), Ecosystem::Node);
+        qs.add_classification(
+            Classification::Has,
+            "1.0.0".to_string(),
+            PathBuf::from("/srv/web/node_modules/qs"),
+        );
+        app.add_dependency(qs);
+
+        let mut tree = DependencyTree::new(app);
+        let mut duplicate = node("qs", vec![]);
+        duplicate.duplicate = true;
+        tree.add_root(node(
+            "express",
+            vec![node("body-parser", vec![node("qs", vec![])]), duplicate],
+        ));
+        tree.add_root(node("lodash", vec![]));
+        tree
+    }
+
+    #[test]
+    fn test_format_trees_text() {
+        let mut filter = InfectedPackageFilter::new();
+        filter.add_infected_package(InfectedPackage::new(
+            "qs".to_string(),
+            HashSet::from(["1.0.0".to_string()]),
+        ));
+
+        let text = format_trees_text(&[tree()], Some(&filter), TreeCharset::Utf8, false);
+
+        assert_eq!(
+            text,
+            "web (node, /srv/web)\n\
+             ├── express@1.0.0\n\
+             │   ├── body-parser@1.0.0\n\
+             │   │   └── qs@1.0.0 [INFECTED]\n\
+             │   └── qs@1.0.0 (*) [INFECTED]\n\
+             └── lodash@1.0.0\n"
+        );
+    }
+
+    #[test]
+    fn test_format_trees_text_ascii_color() {
+        let mut filter = InfectedPackageFilter::new();
+        filter.add_infected_package(InfectedPackage::new(
+            "qs".to_string(),
+            HashSet::from(["1.0.0".to_string()]),
+        ));
+
+        let text = format_trees_text(&[tree()], Some(&filter), TreeCharset::Ascii, true);
+
+        assert!(text.contains("|   |   `-- \x1b[31mqs@1.0.0 [INFECTED]\x1b[0m\n"));
+        assert!(text.ends_with("`-- lodash@1.0.0\n"));
+        assert!("box".parse::<TreeCharset>().is_err());
+    }
+}
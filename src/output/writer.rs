@@ -0,0 +1,490 @@
+//! Output writers: where the results of a scan go
+//!
+//! An [`OutputWriter`] takes the results of a scan in three steps: the
+//! classified dependencies of the applications, the findings of the analyzers
+//! and the summary. [`CsvOutput`] and [`JsonOutput`] write them to a results
+//! file and its sidecar files, as the CLI does with `--format`. Library users
+//! implement the trait to send results elsewhere (a message queue, a
+//! database) and combine sinks with [`OutputWriters`]:
+//!
+//! ```no_run
+//! use scanner::models::{ScanMetadata, ScanSummary};
+//! use scanner::output::{Findings, JsonOutput, OutputWriter, OutputWriters, ScanResults};
+//! use std::io;
+//!
+//! struct CountingSink(usize);
+//!
+//! impl OutputWriter for CountingSink {
+//!     fn write_applications(&mut self, results: &ScanResults<'_>) -> io::Result<()> {
+//!         self.0 += results.dependencies.len();
+//!         Ok(())
+//!     }
+//!
+//!     fn write_findings(
+//!         &mut self,
+//!         _findings: &Findings<'_>,
+//!         _metadata: Option<&ScanMetadata>,
+//!     ) -> io::Result<()> {
+//!         Ok(())
+//!     }
+//!
+//!     fn write_summary(
+//!         &mut self,
+//!         _summary: &ScanSummary,
+//!         _metadata: Option<&ScanMetadata>,
+//!     ) -> io::Result<()> {
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let mut writers = OutputWriters::new()
+//!     .with_writer(Box::new(JsonOutput::new("results.json")))
+//!     .with_writer(Box::new(CountingSink(0)));
+//! writers.write_applications(&ScanResults::default())?;
+//! # Ok::<(), io::Error>(())
+//! ```
+
+use super::{
+    app_stats_path, bundled_path, default_columns, engines_path, errors_path, inconsistencies_path,
+    lockfile_drift_path, multiple_versions_path, overrides_path, peer_conflicts_path,
+    staleness_path, summary_path, write_app_stats_csv, write_app_stats_json,
+    write_applications_json_with_security, write_bundled_csv, write_bundled_json,
+    write_classified_csv_with_columns, write_engines_csv, write_engines_json, write_errors_csv,
+    write_errors_json, write_inconsistencies_csv, write_inconsistencies_json,
+    write_lockfile_drift_csv, write_lockfile_drift_json, write_multiple_versions_csv,
+    write_multiple_versions_json, write_overrides_csv, write_overrides_json,
+    write_peer_conflicts_csv, write_peer_conflicts_json, write_staleness_csv, write_staleness_json,
+    write_summary_csv, write_summary_json, write_trees_json_with_security, CsvColumn,
+};
+use crate::analyzer::{
+    ApplicationStats, BundledPackage, EngineIncompatibility, InfectedPackageFilter, LockfileDrift,
+    MultipleVersions, PeerConflict, Staleness, VersionInconsistency, VersionOverride,
+};
+use crate::models::{
+    Application, ClassifiedDependency, DependencyTree, ScanErrorEntry, ScanMetadata, ScanSummary,
+};
+use std::io;
+use std::path::PathBuf;
+
+/// Classified results of a scan
+#[derive(Clone, Copy, Default)]
+pub struct ScanResults<'a> {
+    /// Applications with their dependencies
+    pub applications: &'a [Application],
+
+    /// Classified dependencies of all applications
+    pub dependencies: &'a [ClassifiedDependency],
+
+    /// Dependency trees per application, when they were built
+    pub trees: Option<&'a [DependencyTree]>,
+
+    /// Known-infected packages to mark the results with
+    pub infected: Option<&'a InfectedPackageFilter>,
+
+    /// Where, when and how the scan ran
+    pub metadata: Option<&'a ScanMetadata>,
+}
+
+/// Findings of the analyzers on a scan; writers skip the empty ones
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Findings<'a> {
+    /// Packages at several versions across applications
+    pub inconsistencies: &'a [VersionInconsistency],
+
+    /// Overrides, resolutions and constraints
+    pub overrides: &'a [VersionOverride],
+
+    /// Packages installed at several versions in one tree
+    pub multiple_versions: &'a [MultipleVersions],
+
+    /// Unmet peer dependencies
+    pub peer_conflicts: &'a [PeerConflict],
+
+    /// Packages not supporting the target runtime
+    pub incompatible_engines: &'a [EngineIncompatibility],
+
+    /// Lockfiles out of date with their manifests
+    pub lockfile_drift: &'a [LockfileDrift],
+
+    /// Outdated package versions
+    pub staleness: &'a [Staleness],
+
+    /// Vendored and bundled copies of packages
+    pub bundled: &'a [BundledPackage],
+
+    /// Dependency stats per application, written even when empty once
+    /// computed
+    pub app_stats: Option<&'a [ApplicationStats]>,
+
+    /// Files that failed to read or parse
+    pub errors: &'a [ScanErrorEntry],
+}
+
+/// Destination of the results of a scan
+pub trait OutputWriter: Send {
+    /// Write the classified dependencies of the scanned applications
+    fn write_applications(&mut self, results: &ScanResults<'_>) -> io::Result<()>;
+
+    /// Write the findings of the analyzers and the files that failed
+    fn write_findings(
+        &mut self,
+        findings: &Findings<'_>,
+        metadata: Option<&ScanMetadata>,
+    ) -> io::Result<()>;
+
+    /// Write the totals and timings of the scan
+    fn write_summary(
+        &mut self,
+        summary: &ScanSummary,
+        metadata: Option<&ScanMetadata>,
+    ) -> io::Result<()>;
+}
+
+/// Results as CSV, findings and summary in sidecar CSV files
+/// (`out.csv` → `out.summary.csv`, `out.errors.csv`, ...)
+#[derive(Debug, Clone)]
+pub struct CsvOutput {
+    path: PathBuf,
+    columns: Vec<&'static CsvColumn>,
+}
+
+impl CsvOutput {
+    /// Write to a results file with the default columns
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            columns: default_columns(),
+        }
+    }
+
+    /// Write these columns, in order
+    pub fn with_columns(mut self, columns: Vec<&'static CsvColumn>) -> Self {
+        self.columns = columns;
+        self
+    }
+}
+
+impl OutputWriter for CsvOutput {
+    fn write_applications(&mut self, results: &ScanResults<'_>) -> io::Result<()> {
+        write_classified_csv_with_columns(
+            results.dependencies,
+            results.infected,
+            &self.columns,
+            &self.path,
+        )
+    }
+
+    fn write_findings(
+        &mut self,
+        findings: &Findings<'_>,
+        _metadata: Option<&ScanMetadata>,
+    ) -> io::Result<()> {
+        let path = &self.path;
+        if !findings.inconsistencies.is_empty() {
+            write_inconsistencies_csv(findings.inconsistencies, inconsistencies_path(path))?;
+        }
+        if !findings.overrides.is_empty() {
+            write_overrides_csv(findings.overrides, overrides_path(path))?;
+        }
+        if !findings.multiple_versions.is_empty() {
+            write_multiple_versions_csv(findings.multiple_versions, multiple_versions_path(path))?;
+        }
+        if !findings.peer_conflicts.is_empty() {
+            write_peer_conflicts_csv(findings.peer_conflicts, peer_conflicts_path(path))?;
+        }
+        if !findings.incompatible_engines.is_empty() {
+            write_engines_csv(findings.incompatible_engines, engines_path(path))?;
+        }
+        if !findings.lockfile_drift.is_empty() {
+            write_lockfile_drift_csv(findings.lockfile_drift, lockfile_drift_path(path))?;
+        }
+        if !findings.staleness.is_empty() {
+            write_staleness_csv(findings.staleness, staleness_path(path))?;
+        }
+        if !findings.bundled.is_empty() {
+            write_bundled_csv(findings.bundled, bundled_path(path))?;
+        }
+        if let Some(stats) = findings.app_stats {
+            write_app_stats_csv(stats, app_stats_path(path))?;
+        }
+        if !findings.errors.is_empty() {
+            write_errors_csv(findings.errors, errors_path(path))?;
+        }
+        Ok(())
+    }
+
+    fn write_summary(
+        &mut self,
+        summary: &ScanSummary,
+        metadata: Option<&ScanMetadata>,
+    ) -> io::Result<()> {
+        write_summary_csv(summary, metadata, summary_path(&self.path))
+    }
+}
+
+/// Results as a JSON document (trees when built, applications otherwise),
+/// findings and summary in sidecar JSON documents
+#[derive(Debug, Clone)]
+pub struct JsonOutput {
+    path: PathBuf,
+}
+
+impl JsonOutput {
+    /// Write to a results file
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl OutputWriter for JsonOutput {
+    fn write_applications(&mut self, results: &ScanResults<'_>) -> io::Result<()> {
+        match results.trees {
+            Some(trees) => write_trees_json_with_security(
+                trees.to_vec(),
+                results.infected,
+                results.metadata,
+                &self.path,
+            ),
+            None => write_applications_json_with_security(
+                results.applications.to_vec(),
+                results.infected,
+                results.metadata,
+                &self.path,
+            ),
+        }
+    }
+
+    fn write_findings(
+        &mut self,
+        findings: &Findings<'_>,
+        metadata: Option<&ScanMetadata>,
+    ) -> io::Result<()> {
+        let path = &self.path;
+        if !findings.inconsistencies.is_empty() {
+            write_inconsistencies_json(
+                findings.inconsistencies,
+                metadata,
+                inconsistencies_path(path),
+            )?;
+        }
+        if !findings.overrides.is_empty() {
+            write_overrides_json(findings.overrides, metadata, overrides_path(path))?;
+        }
+        if !findings.multiple_versions.is_empty() {
+            write_multiple_versions_json(
+                findings.multiple_versions,
+                metadata,
+                multiple_versions_path(path),
+            )?;
+        }
+        if !findings.peer_conflicts.is_empty() {
+            write_peer_conflicts_json(
+                findings.peer_conflicts,
+                metadata,
+                peer_conflicts_path(path),
+            )?;
+        }
+        if !findings.incompatible_engines.is_empty() {
+            write_engines_json(findings.incompatible_engines, metadata, engines_path(path))?;
+        }
+        if !findings.lockfile_drift.is_empty() {
+            write_lockfile_drift_json(
+                findings.lockfile_drift,
+                metadata,
+                lockfile_drift_path(path),
+            )?;
+        }
+        if !findings.staleness.is_empty() {
+            write_staleness_json(findings.staleness, metadata, staleness_path(path))?;
+        }
+        if !findings.bundled.is_empty() {
+            write_bundled_json(findings.bundled, metadata, bundled_path(path))?;
+        }
+        if let Some(stats) = findings.app_stats {
+            write_app_stats_json(stats, metadata, app_stats_path(path))?;
+        }
+        if !findings.errors.is_empty() {
+            write_errors_json(findings.errors, metadata, errors_path(path))?;
+        }
+        Ok(())
+    }
+
+    fn write_summary(
+        &mut self,
+        summary: &ScanSummary,
+        metadata: Option<&ScanMetadata>,
+    ) -> io::Result<()> {
+        write_summary_json(summary, metadata, summary_path(&self.path))
+    }
+}
+
+/// Several output writers fed the same results, in the order they were added
+#[derive(Default)]
+pub struct OutputWriters {
+    writers: Vec<Box<dyn OutputWriter>>,
+}
+
+impl OutputWriters {
+    /// Create an empty set of writers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a writer
+    pub fn with_writer(mut self, writer: Box<dyn OutputWriter>) -> Self {
+        self.register(writer);
+        self
+    }
+
+    /// Add a writer
+    pub fn register(&mut self, writer: Box<dyn OutputWriter>) {
+        self.writers.push(writer);
+    }
+
+    /// Number of writers
+    pub fn len(&self) -> usize {
+        self.writers.len()
+    }
+
+    /// Check if there are no writers
+    pub fn is_empty(&self) -> bool {
+        self.writers.is_empty()
+    }
+}
+
+impl std::fmt::Debug for OutputWriters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OutputWriters")
+            .field("writers", &self.writers.len())
+            .finish()
+    }
+}
+
+/// Each step goes to every writer; the first failure stops it
+impl OutputWriter for OutputWriters {
+    fn write_applications(&mut self, results: &ScanResults<'_>) -> io::Result<()> {
+        for writer in &mut self.writers {
+            writer.write_applications(results)?;
+        }
+        Ok(())
+    }
+
+    fn write_findings(
+        &mut self,
+        findings: &Findings<'_>,
+        metadata: Option<&ScanMetadata>,
+    ) -> io::Result<()> {
+        for writer in &mut self.writers {
+            writer.write_findings(findings, metadata)?;
+        }
+        Ok(())
+    }
+
+    fn write_summary(
+        &mut self,
+        summary: &ScanSummary,
+        metadata: Option<&ScanMetadata>,
+    ) -> io::Result<()> {
+        for writer in &mut self.writers {
+            writer.write_summary(summary, metadata)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Ecosystem;
+    use std::fs;
+    use std::sync::{Arc, Mutex};
+    use tempfile::TempDir;
+
+    /// Sink recording the steps it was given
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl OutputWriter for Recorder {
+        fn write_applications(&mut self, results: &ScanResults<'_>) -> io::Result<()> {
+            let step = format!("applications {}", results.dependencies.len());
+            self.0.lock().unwrap().push(step);
+            Ok(())
+        }
+
+        fn write_findings(
+            &mut self,
+            findings: &Findings<'_>,
+            _metadata: Option<&ScanMetadata>,
+        ) -> io::Result<()> {
+            let step = format!("findings {}", findings.errors.len());
+            self.0.lock().unwrap().push(step);
+            Ok(())
+        }
+
+        fn write_summary(
+            &mut self,
+            summary: &ScanSummary,
+            _metadata: Option<&ScanMetadata>,
+        ) -> io::Result<()> {
+            let step = format!("summary {}", summary.total_dependencies);
+            self.0.lock().unwrap().push(step);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_output_writers() {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("out.csv");
+        let json_path = temp_dir.path().join("out.json");
+        let steps = Arc::new(Mutex::new(Vec::new()));
+        let mut writers = OutputWriters::new()
+            .with_writer(Box::new(CsvOutput::new(&csv_path)))
+            .with_writer(Box::new(JsonOutput::new(&json_path)));
+        writers.register(Box::new(Recorder(steps.clone())));
+        assert_eq!(writers.len(), 3);
+
+        let dependencies = vec![ClassifiedDependency::new(
+            "lodash".to_string(),
+            Ecosystem::Node,
+        )];
+        let errors = vec![ScanErrorEntry::new(
+            PathBuf::from("/app/package.json"),
+            Some(Ecosystem::Node),
+            &crate::models::ScanError::parse_error(PathBuf::from("/app/package.json"), "bad"),
+        )];
+        let summary = ScanSummary {
+            total_dependencies: 1,
+            ..ScanSummary::default()
+        };
+
+        writers
+            .write_applications(&ScanResults {
+                dependencies: &dependencies,
+                ..ScanResults::default()
+            })
+            .unwrap();
+        writers
+            .write_findings(
+                &Findings {
+                    errors: &errors,
+                    ..Findings::default()
+                },
+                None,
+            )
+            .unwrap();
+        writers.write_summary(&summary, None).unwrap();
+
+        assert!(fs::read_to_string(&csv_path).unwrap().contains("lodash"));
+        assert!(temp_dir.path().join("out.errors.csv").exists());
+        assert!(temp_dir.path().join("out.summary.csv").exists());
+        assert!(json_path.exists());
+        assert!(temp_dir.path().join("out.errors.json").exists());
+        assert!(temp_dir.path().join("out.summary.json").exists());
+        // Empty findings leave no sidecar files
+        assert!(!temp_dir.path().join("out.overrides.json").exists());
+        assert_eq!(
+            *steps.lock().unwrap(),
+            ["applications 1", "findings 1", "summary 1"]
+        );
+    }
+}
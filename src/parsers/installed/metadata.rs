@@ -5,7 +5,10 @@
 //! - **METADATA**: Modern format from .dist-info directories (PEP 566)
 //! - **PKG-INFO**: Legacy format from .egg-info directories/files (PEP 314)
 //!
-//! Both formats contain package name, version, and dependency information.
+//! Both formats contain package name, version, dependency information and,
+//! from Metadata-Version 1.2, the supported Python versions (`Requires-Python`).
+//! The license is the SPDX `License-Expression` of Metadata-Version 2.4, else
+//! the first line of the free-form `License` field.
 //! The parser handles various dependency specification formats including:
 //!
 //! - Simple dependencies: `requests`
@@ -36,7 +39,7 @@
 //! ```
 
 use crate::models::error::ScanError;
-use std::fs;
+use crate::parsers::read_text;
 use std::path::Path;
 
 /// Parsed Python package metadata
@@ -50,11 +53,17 @@ pub struct PythonMetadata {
 
     /// Dependencies (from Requires-Dist)
     pub dependencies: Vec<(String, String)>, // (name, version_constraint)
+
+    /// Supported Python versions (from Requires-Python)
+    pub requires_python: Option<String>,
+
+    /// Declared license (from License-Expression, else License)
+    pub license: Option<String>,
 }
 
 /// Parse a METADATA file from a .dist-info directory
 pub fn parse_metadata_file(path: &Path) -> Result<PythonMetadata, ScanError> {
-    let content = fs::read_to_string(path).map_err(ScanError::Io)?;
+    let content = read_text(path).map_err(ScanError::Io)?;
     parse_metadata(&content, path)
 }
 
@@ -63,14 +72,19 @@ pub fn parse_metadata(content: &str, file_path: &Path) -> Result<PythonMetadata,
     let mut name = None;
     let mut version = None;
     let mut dependencies = Vec::new();
+    let mut requires_python = None;
+    let mut license = License::default();
 
     for line in content.lines() {
+        license.read(line);
         let line = line.trim();
 
         if let Some(stripped) = line.strip_prefix("Name:") {
             name = Some(stripped.trim().to_string());
         } else if let Some(stripped) = line.strip_prefix("Version:") {
             version = Some(stripped.trim().to_string());
+        } else if let Some(stripped) = line.strip_prefix("Requires-Python:") {
+            requires_python = Some(stripped.trim().to_string()).filter(|s| !s.is_empty());
         } else if let Some(stripped) = line.strip_prefix("Requires-Dist:") {
             // Parse dependency specification
             let dep_spec = stripped.trim();
@@ -94,12 +108,49 @@ pub fn parse_metadata(content: &str, file_path: &Path) -> Result<PythonMetadata,
         name,
         version,
         dependencies,
+        requires_python,
+        license: license.finish(),
     })
 }
 
+/// License fields of a metadata file
+#[derive(Default)]
+struct License {
+    expression: Option<String>,
+    text: Option<String>,
+    /// Past the headers, in the description
+    in_body: bool,
+}
+
+impl License {
+    /// Record the license fields of a metadata line
+    fn read(&mut self, line: &str) {
+        self.in_body |= line.trim().is_empty();
+        if self.in_body {
+            return;
+        }
+        let value = |field: &str| {
+            line.strip_prefix(field)
+                .map(str::trim)
+                .filter(|v| !v.is_empty() && !v.eq_ignore_ascii_case("UNKNOWN"))
+                .map(str::to_string)
+        };
+        if self.expression.is_none() {
+            self.expression = value("License-Expression:");
+        }
+        if self.text.is_none() {
+            self.text = value("License:");
+        }
+    }
+
+    fn finish(self) -> Option<String> {
+        self.expression.or(self.text)
+    }
+}
+
 /// Parse a PKG-INFO file from a .egg-info directory or file
 pub fn parse_pkg_info_file(path: &Path) -> Result<PythonMetadata, ScanError> {
-    let content = fs::read_to_string(path).map_err(ScanError::Io)?;
+    let content = read_text(path).map_err(ScanError::Io)?;
     parse_pkg_info(&content, path)
 }
 
@@ -109,14 +160,19 @@ pub fn parse_pkg_info(content: &str, file_path: &Path) -> Result<PythonMetadata,
     let mut name = None;
     let mut version = None;
     let mut dependencies = Vec::new();
+    let mut requires_python = None;
+    let mut license = License::default();
 
     for line in content.lines() {
+        license.read(line);
         let line = line.trim();
 
         if let Some(stripped) = line.strip_prefix("Name:") {
             name = Some(stripped.trim().to_string());
         } else if let Some(stripped) = line.strip_prefix("Version:") {
             version = Some(stripped.trim().to_string());
+        } else if let Some(stripped) = line.strip_prefix("Requires-Python:") {
+            requires_python = Some(stripped.trim().to_string()).filter(|s| !s.is_empty());
         } else if let Some(stripped) = line.strip_prefix("Requires:") {
             // Simple dependency name (older format)
             let dep_name = stripped.trim().to_string();
@@ -146,6 +202,8 @@ pub fn parse_pkg_info(content: &str, file_path: &Path) -> Result<PythonMetadata,
         name,
         version,
         dependencies,
+        requires_python,
+        license: license.finish(),
     })
 }
 
@@ -185,6 +243,7 @@ mod tests {
 Name: requests
 Version: 2.31.0
 Summary: Python HTTP for Humans.
+Requires-Python: >=3.7
 Requires-Dist: charset-normalizer (<4,>=2)
 Requires-Dist: idna (<4,>=2.5)
 Requires-Dist: urllib3 (<3,>=1.21.1)
@@ -194,6 +253,7 @@ Requires-Dist: urllib3 (<3,>=1.21.1)
 
         assert_eq!(metadata.name, "requests");
         assert_eq!(metadata.version, "2.31.0");
+        assert_eq!(metadata.requires_python.as_deref(), Some(">=3.7"));
         assert_eq!(metadata.dependencies.len(), 3);
         assert_eq!(metadata.dependencies[0].0, "charset-normalizer");
         assert_eq!(metadata.dependencies[0].1, "<4,>=2");
@@ -201,6 +261,22 @@ Requires-Dist: urllib3 (<3,>=1.21.1)
         assert_eq!(metadata.dependencies[2].0, "urllib3");
     }
 
+    #[test]
+    fn test_parse_license() {
+        let path = PathBuf::from("METADATA");
+        let content = "Metadata-Version: 2.4\nName: a\nVersion: 1.0\nLicense: Apache License\n        Version 2.0\nLicense-Expression: Apache-2.0\n";
+        let metadata = parse_metadata(content, &path).unwrap();
+        assert_eq!(metadata.license.as_deref(), Some("Apache-2.0"));
+
+        let content = "Metadata-Version: 1.1\nName: b\nVersion: 1.0\nLicense: MIT\n";
+        let metadata = parse_pkg_info(content, &path).unwrap();
+        assert_eq!(metadata.license.as_deref(), Some("MIT"));
+
+        let content = "Metadata-Version: 2.1\nName: c\nVersion: 1.0\nLicense: UNKNOWN\n\nLicense: see below\n";
+        let metadata = parse_metadata(content, &path).unwrap();
+        assert_eq!(metadata.license, None);
+    }
+
     #[test]
     fn test_parse_metadata_with_extras() {
         let content = r#"Metadata-Version: 2.1
@@ -2,10 +2,14 @@
 
 pub mod metadata;
 pub mod node_modules;
+pub mod os_packages;
+pub mod record;
 pub mod site_packages;
 
 pub use metadata::{
     parse_metadata, parse_metadata_file, parse_pkg_info, parse_pkg_info_file, PythonMetadata,
 };
 pub use node_modules::NodeModulesParser;
+pub use os_packages::{OsPackageManager, OsPackagesParser};
+pub use record::{parse_record, parse_record_file, verify_record, RecordEntry};
 pub use site_packages::SitePackagesParser;
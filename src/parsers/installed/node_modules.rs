@@ -4,9 +4,19 @@
 //! actually installed packages, including:
 //!
 //! - Package name and version from package.json
-//! - Direct dependencies declared by each package
+//! - Direct and optional dependencies declared by each package
+//! - Peer dependencies, with the optional ones from `peerDependenciesMeta`
+//! - Supported runtimes (`engines`) and the lifecycle scripts run on install
+//!   (`preinstall`, `install`, `postinstall`), including the implicit
+//!   `node-gyp rebuild` of packages with a `binding.gyp`
 //! - Support for scoped packages (@org/package)
-//! - Recursive scanning of nested node_modules (transitive dependencies)
+//! - Nested node_modules of transitive dependencies, walked in parallel up to
+//!   [`DEFAULT_MAX_NESTING`] levels deep
+//! - Packages reachable at several paths (symlinks, pnpm's virtual store) or
+//!   hard-linked from a shared store reported once, at their first path in
+//!   name order
+//! - Install-time integrity hashes, from `_integrity` in package.json (npm 6)
+//!   or the hidden lockfile `node_modules/.package-lock.json` (npm 7+)
 //!
 //! # Example
 //!
@@ -30,11 +40,25 @@
 //! }
 //! ```
 
+use crate::indexer::InstallDirType;
 use crate::models::error::ScanError;
-use crate::models::{Ecosystem, InstalledPackage};
+use crate::models::{Ecosystem, InstalledPackage, PeerDependency};
+use crate::parsers::{read_text, InstalledParser};
+use crate::throttle::{self, IoThrottle};
+use rayon::prelude::*;
 use serde_json::Value;
+use std::collections::HashSet;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Hidden lockfile npm writes into node_modules on install
+const HIDDEN_LOCKFILE: &str = ".package-lock.json";
+
+/// Lifecycle scripts npm runs when installing a package
+const INSTALL_SCRIPTS: &[&str] = &["preinstall", "install", "postinstall"];
+
+/// Levels of nested node_modules followed below a top-level one by default
+pub const DEFAULT_MAX_NESTING: usize = 32;
 
 /// Parser for node_modules directories
 pub struct NodeModulesParser;
@@ -45,60 +69,72 @@ impl NodeModulesParser {
         &self,
         node_modules_path: &Path,
     ) -> Result<Vec<InstalledPackage>, ScanError> {
-        let mut packages = Vec::new();
-
-        // Read all subdirectories in node_modules
-        let entries = fs::read_dir(node_modules_path).map_err(ScanError::Io)?;
-
-        for entry in entries {
-            let entry = entry.map_err(ScanError::Io)?;
-            let path = entry.path();
-
-            if path.is_dir() {
-                let dir_name = entry.file_name();
-                let dir_name_str = dir_name.to_string_lossy();
-
-                // Handle scoped packages (@org/package)
-                if dir_name_str.starts_with('@') {
-                    // This is a scope directory, scan its subdirectories
-                    if let Ok(scoped_entries) = fs::read_dir(&path) {
-                        for scoped_entry in scoped_entries.flatten() {
-                            let scoped_path = scoped_entry.path();
-                            if scoped_path.is_dir() {
-                                if let Ok(pkg) = self.parse_package(&scoped_path) {
-                                    packages.push(pkg);
-                                }
-
-                                // Check for nested node_modules
-                                let nested_nm = scoped_path.join("node_modules");
-                                if nested_nm.exists() {
-                                    if let Ok(nested_pkgs) = self.parse_installed(&nested_nm) {
-                                        packages.extend(nested_pkgs);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                } else {
-                    // Regular package
-                    if let Ok(pkg) = self.parse_package(&path) {
-                        packages.push(pkg);
-                    }
+        self.parse_installed_with_depth(node_modules_path, DEFAULT_MAX_NESTING)
+    }
 
-                    // Check for nested node_modules (transitive dependencies)
-                    let nested_nm = path.join("node_modules");
-                    if nested_nm.exists() {
-                        if let Ok(nested_pkgs) = self.parse_installed(&nested_nm) {
-                            packages.extend(nested_pkgs);
-                        }
-                    }
-                }
-            }
-        }
+    /// Parse installed packages, following at most `max_nesting` levels of
+    /// nested node_modules (0 parses the top level only)
+    pub fn parse_installed_with_depth(
+        &self,
+        node_modules_path: &Path,
+        max_nesting: usize,
+    ) -> Result<Vec<InstalledPackage>, ScanError> {
+        let found = self.walk(node_modules_path, max_nesting)?;
+
+        // The walk keeps name order, so the kept path does not depend on scheduling
+        let mut seen = HashSet::new();
+        let mut packages: Vec<InstalledPackage> = found
+            .into_iter()
+            .filter(|(id, _)| id.as_ref().is_none_or(|id| seen.insert(id.clone())))
+            .map(|(_, package)| package)
+            .collect();
+
+        apply_hidden_lockfile(node_modules_path, &mut packages);
 
         Ok(packages)
     }
 
+    /// Parse the packages of a node_modules directory and, in parallel, their
+    /// nested node_modules
+    fn walk(
+        &self,
+        node_modules_path: &Path,
+        remaining: usize,
+    ) -> Result<Vec<(Option<FileId>, InstalledPackage)>, ScanError> {
+        let package_dirs = package_dirs(node_modules_path)?;
+        // The rayon tasks read on other threads, under the same throttle
+        let io_throttle = IoThrottle::current();
+        Ok(package_dirs
+            .par_iter()
+            .flat_map_iter(|package_path| {
+                throttle::scoped(io_throttle.as_ref(), || {
+                    self.walk_package(package_path, remaining)
+                })
+            })
+            .collect())
+    }
+
+    /// Parse a package directory and its nested node_modules
+    fn walk_package(
+        &self,
+        package_path: &Path,
+        remaining: usize,
+    ) -> Vec<(Option<FileId>, InstalledPackage)> {
+        let mut found = Vec::new();
+        if let Ok(pkg) = self.parse_package(package_path) {
+            found.push((file_id(&package_path.join("package.json")), pkg));
+        }
+
+        // Nested node_modules hold transitive dependencies
+        let nested_nm = package_path.join("node_modules");
+        if remaining > 0 && nested_nm.is_dir() {
+            if let Ok(nested_pkgs) = self.walk(&nested_nm, remaining - 1) {
+                found.extend(nested_pkgs);
+            }
+        }
+        found
+    }
+
     /// Parse a single package directory
     fn parse_package(&self, package_path: &Path) -> Result<InstalledPackage, ScanError> {
         let package_json_path = package_path.join("package.json");
@@ -110,7 +146,7 @@ impl NodeModulesParser {
             });
         }
 
-        let content = fs::read_to_string(&package_json_path).map_err(ScanError::Io)?;
+        let content = read_text(&package_json_path).map_err(ScanError::Io)?;
 
         let json: Value = serde_json::from_str(&content).map_err(|e| ScanError::Parse {
             file: package_json_path.clone(),
@@ -136,20 +172,181 @@ impl NodeModulesParser {
 
         let mut package =
             InstalledPackage::new(name, version, package_path.to_path_buf(), Ecosystem::Node);
-
-        // Extract dependencies
-        if let Some(deps) = json.get("dependencies").and_then(|v| v.as_object()) {
-            for (dep_name, dep_version) in deps {
-                if let Some(version_str) = dep_version.as_str() {
-                    package.add_dependency(dep_name.clone(), version_str.to_string());
+        package.integrity = json
+            .get("_integrity")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        // `license` is an SPDX expression; old packages use `{ "type": ... }`
+        package.license = json
+            .get("license")
+            .and_then(|v| v.as_str().or_else(|| v.get("type")?.as_str()))
+            .map(str::to_string);
+
+        // Extract dependencies; optional ones are often listed in both sections
+        for section in ["dependencies", "optionalDependencies"] {
+            if let Some(deps) = json.get(section).and_then(|v| v.as_object()) {
+                for (dep_name, dep_version) in deps {
+                    if let Some(version_str) = dep_version.as_str() {
+                        if !package.has_dependency(dep_name) {
+                            package.add_dependency(dep_name.clone(), version_str.to_string());
+                        }
+                    }
                 }
             }
         }
 
+        if let Some(peers) = json.get("peerDependencies").and_then(|v| v.as_object()) {
+            let meta = json.get("peerDependenciesMeta");
+            package.peer_dependencies = peers
+                .iter()
+                .filter_map(|(peer, range)| {
+                    Some(PeerDependency {
+                        name: peer.clone(),
+                        version_constraint: range.as_str()?.to_string(),
+                        optional: meta
+                            .and_then(|meta| meta.get(peer))
+                            .and_then(|entry| entry.get("optional"))
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(false),
+                    })
+                })
+                .collect();
+        }
+
+        if let Some(engines) = json.get("engines").and_then(|v| v.as_object()) {
+            package.engines = engines
+                .iter()
+                .filter_map(|(engine, range)| Some((engine.clone(), range.as_str()?.to_string())))
+                .collect();
+        }
+
+        let scripts = json.get("scripts").and_then(|v| v.as_object());
+        let has_script = |name: &str| {
+            scripts
+                .and_then(|scripts| scripts.get(name))
+                .and_then(|v| v.as_str())
+                .is_some_and(|script| !script.trim().is_empty())
+        };
+        package.install_scripts = INSTALL_SCRIPTS
+            .iter()
+            .filter(|name| has_script(name))
+            .map(|name| name.to_string())
+            .collect();
+        // npm runs `node-gyp rebuild` for a binding.gyp without install hooks
+        let gyp = json.get("gypfile").and_then(|v| v.as_bool()) != Some(false)
+            && package_path.join("binding.gyp").is_file();
+        if gyp && !has_script("preinstall") && !has_script("install") {
+            package.install_scripts.insert(0, "install".to_string());
+        }
+
         Ok(package)
     }
 }
 
+impl InstalledParser for NodeModulesParser {
+    fn parse_installed(&self, path: &Path) -> Result<Vec<InstalledPackage>, ScanError> {
+        NodeModulesParser::parse_installed(self, path)
+    }
+
+    fn ecosystem(&self) -> Ecosystem {
+        Ecosystem::Node
+    }
+
+    fn dir_types(&self) -> &[InstallDirType] {
+        &[InstallDirType::NodeModules]
+    }
+}
+
+/// Package directories of a node_modules directory in name order, with the
+/// packages of scope directories (`@org/package`) in place of the scope
+fn package_dirs(node_modules_path: &Path) -> Result<Vec<PathBuf>, ScanError> {
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(node_modules_path).map_err(ScanError::Io)? {
+        let entry = entry.map_err(ScanError::Io)?;
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        if entry.file_name().to_string_lossy().starts_with('@') {
+            if let Ok(scoped_entries) = fs::read_dir(&path) {
+                dirs.extend(
+                    scoped_entries
+                        .flatten()
+                        .map(|scoped_entry| scoped_entry.path())
+                        .filter(|scoped_path| scoped_path.is_dir()),
+                );
+            }
+        } else {
+            dirs.push(path);
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// Unique identity of a file on disk
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum FileId {
+    #[cfg(unix)]
+    Inode(u64, u64),
+    #[cfg(not(unix))]
+    Path(PathBuf),
+}
+
+/// Get the on-disk identity of a file (resolving symlinks)
+///
+/// Hard links share an inode, so a package hard-linked from a store is
+/// identified with its other copies on Unix.
+fn file_id(path: &Path) -> Option<FileId> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let metadata = fs::metadata(path).ok()?;
+        Some(FileId::Inode(metadata.dev(), metadata.ino()))
+    }
+
+    #[cfg(not(unix))]
+    {
+        fs::canonicalize(path).ok().map(FileId::Path)
+    }
+}
+
+/// Fill in missing integrity hashes from the hidden lockfile of a top-level node_modules
+///
+/// Its `packages` keys are paths relative to the project root, e.g.
+/// `node_modules/debug/node_modules/ms`. A missing or unreadable hidden
+/// lockfile is ignored.
+fn apply_hidden_lockfile(node_modules_path: &Path, packages: &mut [InstalledPackage]) {
+    let Some(project_root) = node_modules_path.parent() else {
+        return;
+    };
+    let Ok(content) = read_text(node_modules_path.join(HIDDEN_LOCKFILE)) else {
+        return;
+    };
+    let Ok(json) = serde_json::from_str::<Value>(&content) else {
+        return;
+    };
+    let Some(entries) = json.get("packages").and_then(|v| v.as_object()) else {
+        return;
+    };
+
+    for package in packages.iter_mut().filter(|p| p.integrity.is_none()) {
+        let Ok(relative) = package.path.strip_prefix(project_root) else {
+            continue;
+        };
+        let key = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        package.integrity = entries
+            .get(&key)
+            .and_then(|entry| entry.get("integrity"))
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -239,6 +436,180 @@ mod tests {
         assert!(packages.iter().any(|p| p.name == "loose-envify"));
     }
 
+    #[test]
+    fn test_parse_engines_and_install_scripts() {
+        let temp_dir = TempDir::new().unwrap();
+        let node_modules = temp_dir.path().join("node_modules");
+
+        let sharp_dir = node_modules.join("sharp");
+        fs::create_dir_all(&sharp_dir).unwrap();
+        fs::write(
+            sharp_dir.join("package.json"),
+            r#"{
+                "name": "sharp",
+                "version": "0.33.0",
+                "dependencies": {"color": "^4.2.3"},
+                "optionalDependencies": {"@img/sharp-linux-x64": "0.33.0", "color": "^4.2.3"},
+                "engines": {"node": "^18.17.0 || >=20.3.0"},
+                "license": "Apache-2.0",
+                "peerDependencies": {"react": ">=17", "vue": "^3"},
+                "peerDependenciesMeta": {"vue": {"optional": true}},
+                "scripts": {"install": "node install/check", "postinstall": "", "test": "mocha"}
+            }"#,
+        )
+        .unwrap();
+
+        // A binding.gyp without install hooks is built by node-gyp on install
+        let native_dir = node_modules.join("native");
+        fs::create_dir_all(&native_dir).unwrap();
+        fs::write(
+            native_dir.join("package.json"),
+            r#"{"name": "native", "version": "1.0.0", "license": {"type": "MIT"}, "scripts": {"postinstall": "node setup"}}"#,
+        )
+        .unwrap();
+        fs::write(native_dir.join("binding.gyp"), "{}").unwrap();
+
+        let packages = NodeModulesParser.parse_installed(&node_modules).unwrap();
+
+        let native = &packages[0];
+        assert_eq!(native.install_scripts, vec!["install", "postinstall"]);
+        assert!(native.engines.is_empty());
+        assert_eq!(native.license.as_deref(), Some("MIT"));
+
+        let sharp = &packages[1];
+        assert_eq!(sharp.dependencies.len(), 2);
+        assert!(sharp.has_dependency("@img/sharp-linux-x64"));
+        assert_eq!(sharp.engines["node"], "^18.17.0 || >=20.3.0");
+        assert_eq!(sharp.license.as_deref(), Some("Apache-2.0"));
+        assert_eq!(
+            sharp.peer_dependencies,
+            vec![
+                PeerDependency {
+                    name: "react".to_string(),
+                    version_constraint: ">=17".to_string(),
+                    optional: false,
+                },
+                PeerDependency {
+                    name: "vue".to_string(),
+                    version_constraint: "^3".to_string(),
+                    optional: true,
+                },
+            ]
+        );
+        assert_eq!(sharp.install_scripts, vec!["install"]);
+    }
+
+    #[test]
+    fn test_parse_nesting_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let node_modules = temp_dir.path().join("node_modules");
+
+        // a → b → c, each nested in the previous package's node_modules
+        let mut dir = node_modules.clone();
+        for name in ["a", "b", "c"] {
+            dir = dir.join(name);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(
+                dir.join("package.json"),
+                format!(r#"{{"name": "{}", "version": "1.0.0"}}"#, name),
+            )
+            .unwrap();
+            dir = dir.join("node_modules");
+        }
+
+        let parser = NodeModulesParser;
+        let names = |packages: Vec<InstalledPackage>| {
+            packages.into_iter().map(|p| p.name).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(parser.parse_installed(&node_modules).unwrap()),
+            vec!["a", "b", "c"]
+        );
+        assert_eq!(
+            names(parser.parse_installed_with_depth(&node_modules, 1).unwrap()),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            names(parser.parse_installed_with_depth(&node_modules, 0).unwrap()),
+            vec!["a"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parse_links_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let node_modules = temp_dir.path().join("node_modules");
+
+        // pnpm layout: the top-level package links into the virtual store
+        let stored = node_modules.join(".pnpm/lodash@4.17.21/node_modules/lodash");
+        fs::create_dir_all(&stored).unwrap();
+        fs::write(
+            stored.join("package.json"),
+            r#"{"name": "lodash", "version": "4.17.21"}"#,
+        )
+        .unwrap();
+        std::os::unix::fs::symlink(&stored, node_modules.join("lodash")).unwrap();
+
+        // A hard-linked copy of the same package.json
+        let copy = node_modules.join("vendor/node_modules/lodash");
+        fs::create_dir_all(&copy).unwrap();
+        fs::hard_link(stored.join("package.json"), copy.join("package.json")).unwrap();
+
+        let packages = NodeModulesParser.parse_installed(&node_modules).unwrap();
+
+        // The virtual store is only reached through the link
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].path, node_modules.join("lodash"));
+    }
+
+    #[test]
+    fn test_parse_install_time_integrity() {
+        let temp_dir = TempDir::new().unwrap();
+        let node_modules = temp_dir.path().join("node_modules");
+
+        // npm 6 stores the integrity in the installed package.json
+        fs::create_dir_all(node_modules.join("lodash")).unwrap();
+        fs::write(
+            node_modules.join("lodash/package.json"),
+            r#"{"name": "lodash", "version": "4.17.21", "_integrity": "sha512-old"}"#,
+        )
+        .unwrap();
+
+        // npm 7+ keeps it in the hidden lockfile
+        fs::create_dir_all(node_modules.join("debug/node_modules/ms")).unwrap();
+        fs::write(
+            node_modules.join("debug/package.json"),
+            r#"{"name": "debug", "version": "4.3.4"}"#,
+        )
+        .unwrap();
+        fs::write(
+            node_modules.join("debug/node_modules/ms/package.json"),
+            r#"{"name": "ms", "version": "2.0.0"}"#,
+        )
+        .unwrap();
+        fs::write(
+            node_modules.join(".package-lock.json"),
+            r#"{"packages": {
+                "node_modules/lodash": {"version": "4.17.21", "integrity": "sha512-hidden"},
+                "node_modules/debug/node_modules/ms": {"version": "2.0.0", "integrity": "sha512-ms"}
+            }}"#,
+        )
+        .unwrap();
+
+        let packages = NodeModulesParser.parse_installed(&node_modules).unwrap();
+        let integrity = |name: &str| {
+            packages
+                .iter()
+                .find(|p| p.name == name)
+                .and_then(|p| p.integrity.clone())
+        };
+
+        assert_eq!(integrity("lodash").as_deref(), Some("sha512-old"));
+        assert_eq!(integrity("ms").as_deref(), Some("sha512-ms"));
+        assert_eq!(integrity("debug"), None);
+    }
+
     #[test]
     fn test_parse_multiple_packages() {
         let temp_dir = TempDir::new().unwrap();
@@ -0,0 +1,372 @@
+//! Parser for operating system packages
+//!
+//! Reads the package databases of a system root so host and image scans
+//! report OS-level packages next to language dependencies, in the
+//! [`Ecosystem::Os`] ecosystem:
+//!
+//! - dpkg (Debian, Ubuntu): the `var/lib/dpkg/status` file, or the
+//!   per-package files of `var/lib/dpkg/status.d` in distroless images;
+//!   only packages whose status is `installed` are reported
+//! - rpm (Red Hat, Fedora, SUSE): the database is binary, so it is queried
+//!   with the `rpm` command, which must be installed on the scanning host
+//! - Homebrew: the `Cellar/<formula>/<version>` directories, with runtime
+//!   dependencies from each `INSTALL_RECEIPT.json`
+
+use crate::models::error::ScanError;
+use crate::models::{Ecosystem, InstalledPackage};
+use crate::parsers::read_text;
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// rpm query printing one `name<TAB>[epoch:]version-release` line per package
+const RPM_QUERY_FORMAT: &str = "%{NAME}\\t%|EPOCH?{%{EPOCH}:}:{}|%{VERSION}-%{RELEASE}\\n";
+
+/// OS package managers whose databases are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OsPackageManager {
+    /// Debian package manager
+    Dpkg,
+    /// RPM package manager
+    Rpm,
+    /// Homebrew (macOS and Linux)
+    Homebrew,
+}
+
+impl OsPackageManager {
+    /// Every supported package manager
+    pub const ALL: [OsPackageManager; 3] = [
+        OsPackageManager::Dpkg,
+        OsPackageManager::Rpm,
+        OsPackageManager::Homebrew,
+    ];
+
+    /// Database locations relative to the system root, most specific first
+    fn candidates(self) -> &'static [&'static str] {
+        match self {
+            OsPackageManager::Dpkg => &["var/lib/dpkg/status", "var/lib/dpkg/status.d"],
+            OsPackageManager::Rpm => &["var/lib/rpm", "usr/lib/sysimage/rpm"],
+            OsPackageManager::Homebrew => &[
+                "opt/homebrew/Cellar",
+                "usr/local/Cellar",
+                "home/linuxbrew/.linuxbrew/Cellar",
+            ],
+        }
+    }
+
+    /// The database of this package manager under a system root, if present
+    pub fn database(self, root: &Path) -> Option<PathBuf> {
+        self.candidates()
+            .iter()
+            .map(|candidate| root.join(candidate))
+            .find(|path| match self {
+                OsPackageManager::Dpkg => path.exists(),
+                // An empty rpm directory is left behind by some images
+                OsPackageManager::Rpm => ["rpmdb.sqlite", "Packages", "Packages.db"]
+                    .iter()
+                    .any(|file| path.join(file).is_file()),
+                OsPackageManager::Homebrew => path.is_dir(),
+            })
+    }
+}
+
+impl fmt::Display for OsPackageManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OsPackageManager::Dpkg => write!(f, "dpkg"),
+            OsPackageManager::Rpm => write!(f, "rpm"),
+            OsPackageManager::Homebrew => write!(f, "homebrew"),
+        }
+    }
+}
+
+/// Parser for OS package databases
+pub struct OsPackagesParser;
+
+impl OsPackagesParser {
+    /// Package databases present under a system root (`/` for the host)
+    pub fn databases(&self, root: &Path) -> Vec<(OsPackageManager, PathBuf)> {
+        OsPackageManager::ALL
+            .iter()
+            .filter_map(|manager| Some((*manager, manager.database(root)?)))
+            .collect()
+    }
+
+    /// Parse the installed packages of a package manager's database
+    pub fn parse_installed(
+        &self,
+        manager: OsPackageManager,
+        database: &Path,
+    ) -> Result<Vec<InstalledPackage>, ScanError> {
+        match manager {
+            OsPackageManager::Dpkg => self.parse_dpkg(database),
+            OsPackageManager::Rpm => self.parse_rpm(database),
+            OsPackageManager::Homebrew => self.parse_cellar(database),
+        }
+    }
+
+    /// Parse a dpkg status file or a `status.d` directory
+    fn parse_dpkg(&self, database: &Path) -> Result<Vec<InstalledPackage>, ScanError> {
+        if database.is_file() {
+            let content = read_text(database)?;
+            return Ok(parse_dpkg_status(&content, database));
+        }
+
+        let mut files: Vec<PathBuf> = fs::read_dir(database)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file() && path.extension().is_none_or(|ext| ext != "md5sums"))
+            .collect();
+        files.sort();
+
+        let mut packages = Vec::new();
+        for file in files {
+            let content = read_text(&file)?;
+            packages.extend(parse_dpkg_status(&content, &file));
+        }
+        Ok(packages)
+    }
+
+    /// Query an rpm database with the `rpm` command
+    fn parse_rpm(&self, database: &Path) -> Result<Vec<InstalledPackage>, ScanError> {
+        let output = Command::new("rpm")
+            .arg("--dbpath")
+            .arg(database)
+            .args(["-qa", "--queryformat", RPM_QUERY_FORMAT])
+            .output()
+            .map_err(|e| ScanError::Parse {
+                file: database.to_path_buf(),
+                message: format!("cannot run rpm to read the database: {}", e),
+            })?;
+        if !output.status.success() {
+            return Err(ScanError::Parse {
+                file: database.to_path_buf(),
+                message: format!(
+                    "rpm query failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            });
+        }
+        Ok(parse_rpm_query(
+            &String::from_utf8_lossy(&output.stdout),
+            database,
+        ))
+    }
+
+    /// Parse the formulae of a Homebrew Cellar
+    fn parse_cellar(&self, cellar: &Path) -> Result<Vec<InstalledPackage>, ScanError> {
+        let mut packages = Vec::new();
+        for formula in fs::read_dir(cellar)? {
+            let formula = formula?.path();
+            let Some(name) = formula.file_name().map(|n| n.to_string_lossy().to_string()) else {
+                continue;
+            };
+            if !formula.is_dir() || name.starts_with('.') {
+                continue;
+            }
+            for version in fs::read_dir(&formula)? {
+                let path = version?.path();
+                if !path.is_dir() {
+                    continue;
+                }
+                let version = path.file_name().unwrap_or_default().to_string_lossy();
+                let mut package = InstalledPackage::new(
+                    name.clone(),
+                    version.to_string(),
+                    path.clone(),
+                    Ecosystem::Os,
+                );
+                for (dependency, version) in receipt_dependencies(&path) {
+                    package.add_dependency(dependency, version);
+                }
+                packages.push(package);
+            }
+        }
+        packages.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(packages)
+    }
+}
+
+/// Installed packages of a dpkg status file; each is located at the file
+pub fn parse_dpkg_status(content: &str, file_path: &Path) -> Vec<InstalledPackage> {
+    let mut packages = Vec::new();
+
+    for stanza in content.split("\n\n") {
+        // Continuation lines (descriptions, conffiles) start with whitespace
+        let field = |name: &str| {
+            stanza.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                (key == name).then(|| value.trim())
+            })
+        };
+        let (Some(name), Some(version)) = (field("Package"), field("Version")) else {
+            continue;
+        };
+        // `install ok installed`; removed packages keep `config-files`
+        if field("Status").is_some_and(|status| !status.ends_with(" installed")) {
+            continue;
+        }
+
+        let mut package = InstalledPackage::new(
+            name.to_string(),
+            version.to_string(),
+            file_path.to_path_buf(),
+            Ecosystem::Os,
+        );
+        let depends = [field("Pre-Depends"), field("Depends")];
+        for relation in depends.into_iter().flatten().flat_map(|d| d.split(',')) {
+            // Only the first of alternatives (`a | b`) is recorded
+            let relation = relation.split('|').next().unwrap_or_default().trim();
+            let (name, constraint) = match relation.split_once('(') {
+                Some((name, constraint)) => (
+                    name.trim(),
+                    constraint.trim_end_matches(')').replace(' ', ""),
+                ),
+                None => (relation, "*".to_string()),
+            };
+            let name = name.split(':').next().unwrap_or_default();
+            if !name.is_empty() {
+                package.add_dependency(name.to_string(), constraint);
+            }
+        }
+        packages.push(package);
+    }
+
+    packages
+}
+
+/// Installed packages of `rpm -qa` output in [`RPM_QUERY_FORMAT`]; each is
+/// located at the database
+pub fn parse_rpm_query(output: &str, database: &Path) -> Vec<InstalledPackage> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        // The public key pseudo-packages are not installed software
+        .filter(|(name, _)| !name.is_empty() && *name != "gpg-pubkey")
+        .map(|(name, version)| {
+            InstalledPackage::new(
+                name.to_string(),
+                version.trim().to_string(),
+                database.to_path_buf(),
+                Ecosystem::Os,
+            )
+        })
+        .collect()
+}
+
+/// Runtime dependencies recorded in a keg's `INSTALL_RECEIPT.json`
+fn receipt_dependencies(keg: &Path) -> Vec<(String, String)> {
+    let Ok(content) = read_text(keg.join("INSTALL_RECEIPT.json")) else {
+        return Vec::new();
+    };
+    let Ok(receipt) = serde_json::from_str::<Value>(&content) else {
+        return Vec::new();
+    };
+    receipt
+        .get("runtime_dependencies")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|dep| {
+            let name = dep.get("full_name").and_then(Value::as_str)?;
+            let version = dep.get("version").and_then(Value::as_str).unwrap_or("*");
+            Some((name.to_string(), version.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_dpkg_status() {
+        let content = "\
+Package: libssl3
+Status: install ok installed
+Architecture: amd64
+Version: 3.0.11-1~deb12u2
+Depends: libc6 (>= 2.34), debconf (>= 0.5) | debconf-2.0
+Description: Secure Sockets Layer toolkit
+ This package is part of the OpenSSL project.
+
+Package: old-tool
+Status: deinstall ok config-files
+Version: 1.0-1
+
+Package: python3
+Status: install ok installed
+Version: 3.11.2-1+b1
+Pre-Depends: python3-minimal:any (= 3.11.2-1+b1)
+";
+        let status = Path::new("/var/lib/dpkg/status");
+        let packages = parse_dpkg_status(content, status);
+
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].name, "libssl3");
+        assert_eq!(packages[0].version, "3.0.11-1~deb12u2");
+        assert_eq!(packages[0].ecosystem, Ecosystem::Os);
+        assert_eq!(packages[0].path, status);
+        let deps: Vec<(&str, &str)> = packages[0]
+            .dependencies
+            .iter()
+            .map(|d| (d.name.as_str(), d.version_constraint.as_str()))
+            .collect();
+        assert_eq!(deps, vec![("libc6", ">=2.34"), ("debconf", ">=0.5")]);
+        assert_eq!(packages[1].dependencies[0].name, "python3-minimal");
+        assert_eq!(
+            packages[1].dependencies[0].version_constraint,
+            "=3.11.2-1+b1"
+        );
+    }
+
+    #[test]
+    fn test_parse_rpm_query() {
+        let output = "bash\t5.1.8-6.el9\nopenssl\t1:3.0.7-24.el9\ngpg-pubkey\t8483c65d-5ccc5b19\n";
+        let packages = parse_rpm_query(output, Path::new("/var/lib/rpm"));
+
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[1].name, "openssl");
+        assert_eq!(packages[1].version, "1:3.0.7-24.el9");
+    }
+
+    #[test]
+    fn test_databases_and_cellar() {
+        let root = TempDir::new().unwrap();
+        let dpkg = root.path().join("var/lib/dpkg");
+        fs::create_dir_all(&dpkg).unwrap();
+        fs::write(
+            dpkg.join("status"),
+            "Package: curl\nStatus: install ok installed\nVersion: 7.88.1-10\n",
+        )
+        .unwrap();
+        // An empty rpm directory is not a database
+        fs::create_dir_all(root.path().join("var/lib/rpm")).unwrap();
+        let keg = root.path().join("opt/homebrew/Cellar/wget/1.24.5");
+        fs::create_dir_all(&keg).unwrap();
+        fs::write(
+            keg.join("INSTALL_RECEIPT.json"),
+            r#"{"runtime_dependencies": [{"full_name": "openssl@3", "version": "3.3.0"}]}"#,
+        )
+        .unwrap();
+
+        let parser = OsPackagesParser;
+        let databases = parser.databases(root.path());
+        let managers: Vec<OsPackageManager> = databases.iter().map(|(m, _)| *m).collect();
+        assert_eq!(
+            managers,
+            vec![OsPackageManager::Dpkg, OsPackageManager::Homebrew]
+        );
+
+        let packages = parser
+            .parse_installed(OsPackageManager::Homebrew, &databases[1].1)
+            .unwrap();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].name, "wget");
+        assert_eq!(packages[0].version, "1.24.5");
+        assert_eq!(packages[0].path, keg);
+        assert_eq!(packages[0].dependencies[0].name, "openssl@3");
+    }
+}
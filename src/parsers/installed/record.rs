@@ -0,0 +1,170 @@
+//! Parser and verifier for Python RECORD files
+//!
+//! Every `.dist-info` directory holds a `RECORD` file (PEP 376/627) listing
+//! the files the installer wrote, with their hash and size:
+//!
+//! ```text
+//! requests/__init__.py,sha256=0EvB_3NZ0-OQ5OMNXHjJbyPXAK-BzGrK-NESwQRqAhU,4965
+//! requests-2.31.0.dist-info/RECORD,,
+//! ```
+//!
+//! Paths are relative to the site-packages directory. [`verify_record`]
+//! rehashes the listed files and reports those that changed or disappeared
+//! since the install. Entries without a hash (RECORD itself, bytecode) and
+//! hashes with an unsupported algorithm are skipped.
+//!
+//! # Example
+//!
+//! ```rust
+//! use scanner::parsers::installed::{parse_record_file, verify_record};
+//! use std::path::Path;
+//!
+//! let site_packages = Path::new("/site-packages");
+//! let record_path = site_packages.join("requests-2.31.0.dist-info/RECORD");
+//! if let Ok(entries) = parse_record_file(&record_path) {
+//!     for path in verify_record(site_packages, &entries) {
+//!         println!("modified: {}", path.display());
+//!     }
+//! }
+//! ```
+
+use crate::models::error::ScanError;
+use crate::parsers::read_text;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A file listed in a RECORD file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordEntry {
+    /// Path relative to the site-packages directory
+    pub path: String,
+
+    /// Hash as `algorithm=urlsafe-base64-digest`, if recorded
+    pub hash: Option<String>,
+
+    /// Size in bytes, if recorded
+    pub size: Option<u64>,
+}
+
+/// Parse a RECORD file from a .dist-info directory
+pub fn parse_record_file(path: &Path) -> Result<Vec<RecordEntry>, ScanError> {
+    let content = read_text(path).map_err(ScanError::Io)?;
+    parse_record(&content, path)
+}
+
+/// Parse RECORD content (CSV without header: path,hash,size)
+pub fn parse_record(content: &str, file_path: &Path) -> Result<Vec<RecordEntry>, ScanError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+
+    let mut entries = Vec::new();
+    for row in reader.records() {
+        let row = row.map_err(|e| ScanError::Parse {
+            file: file_path.to_path_buf(),
+            message: format!("Invalid RECORD line: {}", e),
+        })?;
+        let Some(path) = row.get(0).filter(|p| !p.is_empty()) else {
+            continue;
+        };
+        let non_empty = |i: usize| row.get(i).map(str::trim).filter(|v| !v.is_empty());
+        entries.push(RecordEntry {
+            path: path.to_string(),
+            hash: non_empty(1).map(str::to_string),
+            size: non_empty(2).and_then(|s| s.parse().ok()),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Files whose content no longer matches their recorded hash, or that are missing
+pub fn verify_record(site_packages: &Path, entries: &[RecordEntry]) -> Vec<PathBuf> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let (algorithm, expected) = entry.hash.as_deref()?.split_once('=')?;
+            let path = site_packages.join(&entry.path);
+            let Ok(content) = fs::read(&path) else {
+                return Some(path);
+            };
+            let digest = match algorithm {
+                "sha256" => Sha256::digest(&content).to_vec(),
+                "sha384" => Sha384::digest(&content).to_vec(),
+                "sha512" => Sha512::digest(&content).to_vec(),
+                _ => return None,
+            };
+            (URL_SAFE_NO_PAD.encode(digest) != expected.trim_end_matches('=')).then_some(path)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_record() {
+        let content = "requests/__init__.py,sha256=abc,4965\n\
+                       \"requests/a,b.py\",sha256=def,10\n\
+                       requests-2.31.0.dist-info/RECORD,,\n";
+
+        let entries = parse_record(content, Path::new("RECORD")).unwrap();
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].hash.as_deref(), Some("sha256=abc"));
+        assert_eq!(entries[0].size, Some(4965));
+        assert_eq!(entries[1].path, "requests/a,b.py");
+        assert_eq!(entries[2].hash, None);
+        assert_eq!(entries[2].size, None);
+    }
+
+    #[test]
+    fn test_verify_record() {
+        let temp_dir = TempDir::new().unwrap();
+        let site_packages = temp_dir.path();
+        fs::create_dir_all(site_packages.join("pkg")).unwrap();
+        fs::write(site_packages.join("pkg/ok.py"), "hello").unwrap();
+        fs::write(site_packages.join("pkg/changed.py"), "tampered").unwrap();
+
+        // sha256("hello"), urlsafe base64 without padding
+        let hello = "sha256=LPJNul-wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ";
+        let entries = vec![
+            RecordEntry {
+                path: "pkg/ok.py".to_string(),
+                hash: Some(hello.to_string()),
+                size: Some(5),
+            },
+            RecordEntry {
+                path: "pkg/changed.py".to_string(),
+                hash: Some(hello.to_string()),
+                size: Some(5),
+            },
+            RecordEntry {
+                path: "pkg/deleted.py".to_string(),
+                hash: Some(hello.to_string()),
+                size: Some(5),
+            },
+            RecordEntry {
+                path: "pkg/__pycache__/ok.cpython-312.pyc".to_string(),
+                hash: None,
+                size: None,
+            },
+        ];
+
+        let modified = verify_record(site_packages, &entries);
+
+        assert_eq!(
+            modified,
+            vec![
+                site_packages.join("pkg/changed.py"),
+                site_packages.join("pkg/deleted.py")
+            ]
+        );
+    }
+}
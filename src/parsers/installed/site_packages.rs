@@ -1,19 +1,54 @@
 //! Parser for Python installed packages in site-packages directories
+//!
+//! With hash verification enabled, the files listed in each `.dist-info/RECORD`
+//! are rehashed and those modified after install are recorded on the package
+//! (see [`verify_record`](super::record::verify_record)).
+//!
+//! Compiled extensions listed in RECORD (`.so`, `.pyd`, `.dylib`, `.dll`)
+//! and the console and GUI commands of `entry_points.txt` are recorded on
+//! each package for the risk analysis. `Requires-Python` is recorded as the
+//! package's `python` engine.
 
 use super::metadata::{parse_metadata_file, parse_pkg_info_file};
+use super::record::{parse_record_file, verify_record};
+use crate::indexer::InstallDirType;
 use crate::models::error::ScanError;
 use crate::models::{Ecosystem, InstalledPackage};
+use crate::parsers::{read_text, InstalledParser};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Parser for site-packages directories
-pub struct SitePackagesParser;
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SitePackagesParser {
+    verify_hashes: bool,
+}
 
 impl SitePackagesParser {
+    /// Create a parser that does not verify RECORD hashes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Verify installed files against RECORD hashes
+    pub fn with_verify_hashes(mut self, verify_hashes: bool) -> Self {
+        self.verify_hashes = verify_hashes;
+        self
+    }
+
     /// Parse all installed packages in a site-packages directory
     pub fn parse_installed(
         &self,
         site_packages_path: &Path,
+    ) -> Result<Vec<InstalledPackage>, ScanError> {
+        self.parse_installed_with_verification(site_packages_path, self.verify_hashes)
+    }
+
+    /// Parse all installed packages, optionally verifying files against RECORD hashes
+    pub fn parse_installed_with_verification(
+        &self,
+        site_packages_path: &Path,
+        verify_hashes: bool,
     ) -> Result<Vec<InstalledPackage>, ScanError> {
         let mut packages = Vec::new();
 
@@ -28,7 +63,10 @@ impl SitePackagesParser {
 
             // Check for .dist-info directories (modern format)
             if path.is_dir() && name_str.ends_with(".dist-info") {
-                if let Ok(pkg) = self.parse_dist_info(&path) {
+                if let Ok(mut pkg) = self.parse_dist_info(&path) {
+                    if verify_hashes {
+                        pkg.modified_files = self.verify_dist_info(&path, site_packages_path);
+                    }
                     packages.push(pkg);
                 }
             }
@@ -77,15 +115,38 @@ impl SitePackagesParser {
             package_path,
             Ecosystem::Python,
         );
+        if let Some(requires_python) = metadata.requires_python {
+            package
+                .engines
+                .insert("python".to_string(), requires_python);
+        }
+        package.license = metadata.license;
 
         // Add dependencies
         for (dep_name, dep_version) in metadata.dependencies {
             package.add_dependency(dep_name, dep_version);
         }
 
+        if let Ok(entries) = parse_record_file(&dist_info_path.join("RECORD")) {
+            package.native_extensions = entries
+                .into_iter()
+                .map(|entry| entry.path)
+                .filter(|path| is_native_extension(path))
+                .collect();
+        }
+        package.entry_points = read_entry_points(dist_info_path);
+
         Ok(package)
     }
 
+    /// Files of a .dist-info package modified since install (none without a RECORD)
+    fn verify_dist_info(&self, dist_info_path: &Path, site_packages_path: &Path) -> Vec<PathBuf> {
+        match parse_record_file(&dist_info_path.join("RECORD")) {
+            Ok(entries) => verify_record(site_packages_path, &entries),
+            Err(_) => Vec::new(),
+        }
+    }
+
     /// Parse a .egg-info directory
     fn parse_egg_info_dir(&self, egg_info_path: &Path) -> Result<InstalledPackage, ScanError> {
         let pkg_info_path = egg_info_path.join("PKG-INFO");
@@ -114,12 +175,20 @@ impl SitePackagesParser {
             package_path,
             Ecosystem::Python,
         );
+        if let Some(requires_python) = metadata.requires_python {
+            package
+                .engines
+                .insert("python".to_string(), requires_python);
+        }
+        package.license = metadata.license;
 
         // Add dependencies
         for (dep_name, dep_version) in metadata.dependencies {
             package.add_dependency(dep_name, dep_version);
         }
 
+        package.entry_points = read_entry_points(egg_info_path);
+
         Ok(package)
     }
 
@@ -142,6 +211,12 @@ impl SitePackagesParser {
             package_path,
             Ecosystem::Python,
         );
+        if let Some(requires_python) = metadata.requires_python {
+            package
+                .engines
+                .insert("python".to_string(), requires_python);
+        }
+        package.license = metadata.license;
 
         // Add dependencies
         for (dep_name, dep_version) in metadata.dependencies {
@@ -152,6 +227,53 @@ impl SitePackagesParser {
     }
 }
 
+impl InstalledParser for SitePackagesParser {
+    fn parse_installed(&self, path: &Path) -> Result<Vec<InstalledPackage>, ScanError> {
+        SitePackagesParser::parse_installed(self, path)
+    }
+
+    fn ecosystem(&self) -> Ecosystem {
+        Ecosystem::Python
+    }
+
+    fn dir_types(&self) -> &[InstallDirType] {
+        &[
+            InstallDirType::SitePackages,
+            InstallDirType::DistPackages,
+            InstallDirType::VirtualEnv,
+        ]
+    }
+}
+
+/// Whether a RECORD path is a compiled extension or shared library
+fn is_native_extension(path: &str) -> bool {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    [".so", ".pyd", ".dylib", ".dll"]
+        .iter()
+        .any(|ext| file_name.ends_with(ext))
+        // Versioned shared libraries bundled by auditwheel (`libz.so.1.2.13`)
+        || file_name.contains(".so.")
+}
+
+/// Console and GUI command names from a metadata directory's `entry_points.txt`
+fn read_entry_points(metadata_dir: &Path) -> Vec<String> {
+    let Ok(content) = read_text(metadata_dir.join("entry_points.txt")) else {
+        return Vec::new();
+    };
+    let mut commands = Vec::new();
+    let mut in_scripts = false;
+    for line in content.lines().map(str::trim) {
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_scripts = matches!(section.trim(), "console_scripts" | "gui_scripts");
+        } else if in_scripts && !line.is_empty() && !line.starts_with(['#', ';']) {
+            if let Some((name, _)) = line.split_once('=') {
+                commands.push(name.trim().to_string());
+            }
+        }
+    }
+    commands
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -171,24 +293,99 @@ mod tests {
         let metadata = r#"Metadata-Version: 2.1
 Name: requests
 Version: 2.31.0
+Requires-Python: >=3.7
 Requires-Dist: charset-normalizer (<4,>=2)
 Requires-Dist: urllib3 (<3,>=1.21.1)
 "#;
 
         fs::write(dist_info.join("METADATA"), metadata).unwrap();
 
-        let parser = SitePackagesParser;
+        let parser = SitePackagesParser::new();
         let packages = parser.parse_installed(&site_packages).unwrap();
 
         assert_eq!(packages.len(), 1);
         assert_eq!(packages[0].name, "requests");
         assert_eq!(packages[0].version, "2.31.0");
         assert_eq!(packages[0].ecosystem, Ecosystem::Python);
+        assert_eq!(packages[0].engines["python"], ">=3.7");
         assert_eq!(packages[0].dependencies.len(), 2);
         assert_eq!(packages[0].dependencies[0].name, "charset-normalizer");
         assert_eq!(packages[0].dependencies[1].name, "urllib3");
     }
 
+    #[test]
+    fn test_parse_native_extensions_and_entry_points() {
+        let temp_dir = TempDir::new().unwrap();
+        let site_packages = temp_dir.path().join("site-packages");
+        let dist_info = site_packages.join("numpy-1.26.4.dist-info");
+        fs::create_dir_all(&dist_info).unwrap();
+        fs::write(
+            dist_info.join("METADATA"),
+            "Metadata-Version: 2.1\nName: numpy\nVersion: 1.26.4\n",
+        )
+        .unwrap();
+        fs::write(
+            dist_info.join("RECORD"),
+            "numpy/__init__.py,sha256=abc,100\n\
+             numpy/core/_multiarray_umath.cpython-311-x86_64-linux-gnu.so,sha256=def,200\n\
+             numpy.libs/libgfortran-040039e1.so.5.0.0,sha256=ghi,300\n\
+             numpy-1.26.4.dist-info/RECORD,,\n",
+        )
+        .unwrap();
+        fs::write(
+            dist_info.join("entry_points.txt"),
+            "[array_api]\nnumpy = numpy.array_api\n\n\
+             [console_scripts]\nf2py = numpy.f2py.f2py2e:main\n",
+        )
+        .unwrap();
+
+        let packages = SitePackagesParser::new()
+            .parse_installed(&site_packages)
+            .unwrap();
+
+        assert_eq!(
+            packages[0].native_extensions,
+            vec![
+                "numpy/core/_multiarray_umath.cpython-311-x86_64-linux-gnu.so",
+                "numpy.libs/libgfortran-040039e1.so.5.0.0",
+            ]
+        );
+        assert_eq!(packages[0].entry_points, vec!["f2py"]);
+    }
+
+    #[test]
+    fn test_verify_record_hashes() {
+        let temp_dir = TempDir::new().unwrap();
+        let site_packages = temp_dir.path().join("site-packages");
+        let dist_info = site_packages.join("six-1.16.0.dist-info");
+        fs::create_dir_all(&dist_info).unwrap();
+        fs::write(
+            dist_info.join("METADATA"),
+            "Metadata-Version: 2.1\nName: six\nVersion: 1.16.0\n",
+        )
+        .unwrap();
+        // sha256("hello"), urlsafe base64 without padding
+        fs::write(
+            dist_info.join("RECORD"),
+            "six.py,sha256=LPJNul-wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ,5\n\
+             six-1.16.0.dist-info/RECORD,,\n",
+        )
+        .unwrap();
+        fs::write(site_packages.join("six.py"), "patched").unwrap();
+
+        let parser = SitePackagesParser::new();
+        let unverified = parser.parse_installed(&site_packages).unwrap();
+        let verified = parser
+            .parse_installed_with_verification(&site_packages, true)
+            .unwrap();
+
+        assert!(unverified[0].modified_files.is_empty());
+        assert_eq!(
+            verified[0].modified_files,
+            vec![site_packages.join("six.py")]
+        );
+    }
+
     #[test]
     fn test_parse_egg_info_dir() {
         let temp_dir = TempDir::new().unwrap();
@@ -206,7 +403,7 @@ Version: 3.19.1
 
         fs::write(egg_info.join("PKG-INFO"), pkg_info).unwrap();
 
-        let parser = SitePackagesParser;
+        let parser = SitePackagesParser::new();
         let packages = parser.parse_installed(&site_packages).unwrap();
 
         assert_eq!(packages.len(), 1);
@@ -229,7 +426,7 @@ Version: 1.0.0
 
         fs::write(site_packages.join("oldpackage-1.0.0.egg-info"), pkg_info).unwrap();
 
-        let parser = SitePackagesParser;
+        let parser = SitePackagesParser::new();
         let packages = parser.parse_installed(&site_packages).unwrap();
 
         assert_eq!(packages.len(), 1);
@@ -260,7 +457,7 @@ Version: 1.0.0
         )
         .unwrap();
 
-        let parser = SitePackagesParser;
+        let parser = SitePackagesParser::new();
         let packages = parser.parse_installed(&site_packages).unwrap();
 
         assert_eq!(packages.len(), 2);
@@ -299,7 +496,7 @@ Version: 1.0.0
         )
         .unwrap();
 
-        let parser = SitePackagesParser;
+        let parser = SitePackagesParser::new();
         let packages = parser.parse_installed(&site_packages).unwrap();
 
         assert_eq!(packages.len(), 3);
@@ -2,8 +2,12 @@
 
 use serde::Deserialize;
 use std::path::Path;
+use std::sync::Arc;
 
-use crate::models::{DependencyRecord, DependencyType, Ecosystem, FileType, ScanError};
+use crate::models::{
+    DependencyRecord, DependencyType, Ecosystem, FileType, ScanError, SourceKind, Symbol,
+};
+use crate::parsers::span::LineIndex;
 use crate::parsers::Parser;
 
 /// Parser for Cargo.lock lockfiles
@@ -17,25 +21,50 @@ struct CargoLock {
 
 #[derive(Debug, Deserialize)]
 struct Package {
-    name: String,
+    name: toml::Spanned<String>,
     version: String,
+    #[serde(default)]
+    source: Option<String>,
+    #[serde(default)]
+    checksum: Option<String>,
+    /// Entries are `name`, `name version` or `name version (source)`
+    #[serde(default)]
+    dependencies: Vec<String>,
 }
 
 impl Parser for CargoLockParser {
     fn parse(&self, content: &str, file_path: &Path) -> Result<Vec<DependencyRecord>, ScanError> {
+        let source_file: Arc<Path> = Arc::from(file_path);
         let cargo_lock: CargoLock = toml::from_str(content)
             .map_err(|e| ScanError::toml_error(file_path.to_path_buf(), e))?;
 
+        let lines = LineIndex::new(content);
         let mut records = Vec::new();
 
         for package in cargo_lock.package {
+            // Several versions of a crate can be required under one name
+            let mut dependencies: Vec<Symbol> = package
+                .dependencies
+                .iter()
+                .filter_map(|dep| dep.split_whitespace().next())
+                .map(Symbol::from)
+                .collect();
+            dependencies.sort();
+            dependencies.dedup();
+            let span = lines.span(package.name.span());
             records.push(DependencyRecord {
-                name: package.name,
-                version: package.version,
-                source_file: file_path.to_path_buf(),
+                name: package.name.into_inner().into(),
+                version: package.version.into(),
+                source_file: source_file.clone(),
                 dep_type: DependencyType::Runtime,
                 ecosystem: Ecosystem::Rust,
                 file_type: FileType::Lockfile,
+                alias: None,
+                source_kind: SourceKind::Registry,
+                resolved: package.source,
+                integrity: package.checksum,
+                dependencies,
+                span: Some(span),
             });
         }
 
@@ -0,0 +1,172 @@
+//! Parser for deno.lock files
+//!
+//! Reads the locked npm and JSR packages of lockfile versions 2 to 4 (keyed
+//! `name@version`, with their integrity and dependencies) and the remote
+//! modules, whose versions come from their URLs. Entries are reported in the
+//! Node ecosystem, like the `deno.json` imports they resolve.
+
+use serde_json::{Map, Value};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::models::{
+    DependencyRecord, DependencyType, Ecosystem, FileType, ScanError, SourceKind, Symbol,
+};
+use crate::parsers::manifest::deno_json::parse_deno_specifier;
+use crate::parsers::span::LineIndex;
+use crate::parsers::Parser;
+
+/// Parser for deno.lock lockfiles
+pub struct DenoLockParser;
+
+impl Parser for DenoLockParser {
+    fn parse(&self, content: &str, file_path: &Path) -> Result<Vec<DependencyRecord>, ScanError> {
+        let source_file: Arc<Path> = Arc::from(file_path);
+        let lock: Value = serde_json::from_str(content)
+            .map_err(|e| ScanError::json_error(file_path.to_path_buf(), e))?;
+
+        let lines = LineIndex::new(content);
+        let mut records = Vec::new();
+        let record = |name: String, version: String, source_kind, entry: Option<&Value>, key| {
+            let integrity = entry
+                .and_then(|e| e.get("integrity"))
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            let resolved = match &source_kind {
+                SourceKind::Url { url } => Some(format!("{}/{}", url, version)),
+                _ => None,
+            };
+            DependencyRecord {
+                name: name.into(),
+                version: version.into(),
+                source_file: source_file.clone(),
+                dep_type: DependencyType::Runtime,
+                ecosystem: Ecosystem::Node,
+                file_type: FileType::Lockfile,
+                alias: None,
+                source_kind,
+                resolved,
+                integrity,
+                dependencies: entry.map(dependency_names).unwrap_or_default(),
+                span: lines.find_json_key(key, 0),
+            }
+        };
+
+        // v4 keeps packages at the top level, v3 under `packages`, v2 under
+        // `npm.packages`
+        let sections = [
+            (lock.get("npm"), false),
+            (lock.pointer("/packages/npm"), false),
+            (lock.pointer("/npm/packages"), false),
+            (lock.get("jsr"), true),
+            (lock.pointer("/packages/jsr"), true),
+        ];
+        for (section, jsr) in sections {
+            let Some(packages) = section.and_then(Value::as_object) else {
+                continue;
+            };
+            for (key, entry) in packages {
+                let Some((name, version)) = split_locked(key) else {
+                    continue;
+                };
+                let source_kind = if jsr {
+                    SourceKind::Url {
+                        url: format!("https://jsr.io/{}", name),
+                    }
+                } else {
+                    SourceKind::Registry
+                };
+                records.push(record(name, version, source_kind, Some(entry), key));
+            }
+        }
+
+        // A remote module is locked once per file it serves
+        let remote = lock.get("remote").and_then(Value::as_object);
+        for url in remote.into_iter().flat_map(Map::keys) {
+            let Some((name, version, source_kind)) = parse_deno_specifier(url) else {
+                continue;
+            };
+            let locked = records
+                .iter()
+                .any(|r| r.name.as_str() == name && r.version.as_str() == version);
+            if !locked {
+                records.push(DependencyRecord {
+                    resolved: None,
+                    ..record(name, version, source_kind, None, url)
+                });
+            }
+        }
+
+        Ok(records)
+    }
+
+    fn ecosystem(&self) -> Ecosystem {
+        Ecosystem::Node
+    }
+
+    fn file_type(&self) -> FileType {
+        FileType::Lockfile
+    }
+
+    fn filename(&self) -> &str {
+        "deno.lock"
+    }
+}
+
+/// Split a locked `name@version` key; npm peer suffixes (`_react@18.2.0`)
+/// are dropped
+fn split_locked(key: &str) -> Option<(String, String)> {
+    let at = key.get(1..)?.find('@')? + 1;
+    let (name, version) = (&key[..at], &key[at + 1..]);
+    let version = version.split('_').next().unwrap_or_default();
+    if name.is_empty() || version.is_empty() {
+        return None;
+    }
+    Some((name.to_string(), version.to_string()))
+}
+
+/// Names of a locked package's dependencies, from a list of specifiers (v4)
+/// or a name-to-key object (v2, v3)
+fn dependency_names(entry: &Value) -> Vec<Symbol> {
+    match entry.get("dependencies") {
+        Some(Value::Array(deps)) => deps
+            .iter()
+            .filter_map(Value::as_str)
+            .filter_map(|dep| {
+                let dep = dep
+                    .strip_prefix("npm:")
+                    .or_else(|| dep.strip_prefix("jsr:"))
+                    .unwrap_or(dep);
+                let name = match dep.get(1..).and_then(|rest| rest.find('@')) {
+                    Some(at) => &dep[..at + 1],
+                    None => dep,
+                };
+                (!name.is_empty()).then(|| name.into())
+            })
+            .collect(),
+        Some(Value::Object(deps)) => deps.keys().map(|name| name.as_str().into()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_locked() {
+        assert_eq!(
+            split_locked("chalk@5.3.0"),
+            Some(("chalk".to_string(), "5.3.0".to_string()))
+        );
+        assert_eq!(
+            split_locked("@std/assert@1.0.6"),
+            Some(("@std/assert".to_string(), "1.0.6".to_string()))
+        );
+        assert_eq!(
+            split_locked("react-dom@18.2.0_react@18.2.0"),
+            Some(("react-dom".to_string(), "18.2.0".to_string()))
+        );
+        assert_eq!(split_locked("chalk"), None);
+    }
+}
@@ -1,6 +1,7 @@
 //! Lockfile parsers (resolved/installed versions)
 
 pub mod cargo_lock;
+pub mod deno_lock;
 pub mod package_lock_json;
 pub mod pnpm_lock_yaml;
 pub mod poetry_lock;
@@ -8,6 +9,7 @@ pub mod uv_lock;
 pub mod yarn_lock;
 
 pub use cargo_lock::CargoLockParser;
+pub use deno_lock::DenoLockParser;
 pub use package_lock_json::PackageLockJsonParser;
 pub use pnpm_lock_yaml::PnpmLockParser;
 pub use poetry_lock::PoetryLockParser;
@@ -3,8 +3,14 @@
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::path::Path;
-
-use crate::models::{DependencyRecord, DependencyType, Ecosystem, FileType, ScanError};
+use std::sync::Arc;
+
+use crate::models::{
+    DependencyRecord, DependencyType, Ecosystem, FileType, ScanError, SourceKind, SourceSpan,
+    Symbol,
+};
+use crate::parsers::npm_alias::split_alias;
+use crate::parsers::span::LineIndex;
 use crate::parsers::Parser;
 
 /// Parser for package-lock.json lockfiles
@@ -22,35 +28,61 @@ struct PackageLockJson {
 struct DependencyEntry {
     version: String,
     #[serde(default)]
+    resolved: Option<String>,
+    #[serde(default)]
+    integrity: Option<String>,
+    #[serde(default)]
+    requires: HashMap<String, String>,
+    #[serde(default)]
     dependencies: HashMap<String, DependencyEntry>,
 }
 
 #[derive(Debug, Deserialize)]
 struct PackageEntry {
+    /// Real package name, present when the package is installed under an alias
+    #[serde(default)]
+    name: Option<String>,
     #[serde(default)]
     version: Option<String>,
+    #[serde(default)]
+    resolved: Option<String>,
+    #[serde(default)]
+    integrity: Option<String>,
+    #[serde(default)]
+    dependencies: HashMap<String, String>,
+    #[serde(default, rename = "optionalDependencies")]
+    optional_dependencies: HashMap<String, String>,
 }
 
 impl Parser for PackageLockJsonParser {
     fn parse(&self, content: &str, file_path: &Path) -> Result<Vec<DependencyRecord>, ScanError> {
+        let source_file: Arc<Path> = Arc::from(file_path);
         let package_lock: PackageLockJson = serde_json::from_str(content)
             .map_err(|e| ScanError::json_error(file_path.to_path_buf(), e))?;
 
+        let lines = LineIndex::new(content);
         let mut records = Vec::new();
 
         // Parse from dependencies section (v1 format)
-        for (name, entry) in &package_lock.dependencies {
+        for (key, entry) in &package_lock.dependencies {
+            let (name, version, alias) = unalias(key, &entry.version);
             records.push(DependencyRecord {
-                name: name.clone(),
-                version: entry.version.clone(),
-                source_file: file_path.to_path_buf(),
+                name: name.into(),
+                version: version.into(),
+                source_file: source_file.clone(),
                 dep_type: DependencyType::Runtime,
                 ecosystem: Ecosystem::Node,
                 file_type: FileType::Lockfile,
+                alias,
+                source_kind: SourceKind::Registry,
+                resolved: entry.resolved.clone(),
+                integrity: entry.integrity.clone(),
+                dependencies: sorted_names(entry.requires.keys()),
+                span: locate_v1(&lines, key, &entry.version),
             });
 
             // Recursively parse nested dependencies
-            parse_nested_dependencies(&entry.dependencies, file_path, &mut records);
+            parse_nested_dependencies(&entry.dependencies, &source_file, &lines, &mut records);
         }
 
         // Parse from packages section (v2/v3 format)
@@ -62,11 +94,18 @@ impl Parser for PackageLockJsonParser {
 
             if let Some(version) = &entry.version {
                 // Extract package name from key (e.g., "node_modules/react" -> "react")
-                let name = if key.starts_with("node_modules/") {
+                let installed_as = if key.starts_with("node_modules/") {
                     key.strip_prefix("node_modules/").unwrap_or(key)
                 } else {
                     key.as_str()
                 };
+                // Aliased packages carry the real name in a `name` field
+                let (name, alias) = match &entry.name {
+                    Some(real) if real != installed_as => {
+                        (real.as_str(), Some(installed_as.to_string()))
+                    }
+                    _ => (installed_as, None),
+                };
 
                 // Only add if not already present from dependencies section
                 if !records
@@ -74,12 +113,23 @@ impl Parser for PackageLockJsonParser {
                     .any(|r| r.name == name && r.version == *version)
                 {
                     records.push(DependencyRecord {
-                        name: name.to_string(),
-                        version: version.clone(),
-                        source_file: file_path.to_path_buf(),
+                        name: name.to_string().into(),
+                        version: version.clone().into(),
+                        source_file: source_file.clone(),
                         dep_type: DependencyType::Runtime,
                         ecosystem: Ecosystem::Node,
                         file_type: FileType::Lockfile,
+                        alias,
+                        source_kind: SourceKind::Registry,
+                        resolved: entry.resolved.clone(),
+                        integrity: entry.integrity.clone(),
+                        dependencies: sorted_names(
+                            entry
+                                .dependencies
+                                .keys()
+                                .chain(entry.optional_dependencies.keys()),
+                        ),
+                        span: lines.find_json_key(key, 0),
                     });
                 }
             }
@@ -101,28 +151,60 @@ impl Parser for PackageLockJsonParser {
     }
 }
 
+/// Real name, version and alias of a v1 entry (`"pkg": {"version": "npm:real@1.0.0"}`)
+fn unalias<'a>(name: &'a str, version: &'a str) -> (&'a str, &'a str, Option<String>) {
+    match split_alias(version) {
+        Some((real, version)) => (real, version, Some(name.to_string())),
+        None => (name, version, None),
+    }
+}
+
+/// Unique dependency names in sorted order
+fn sorted_names<'a>(names: impl Iterator<Item = &'a String>) -> Vec<Symbol> {
+    let mut names: Vec<Symbol> = names.map(Symbol::from).collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Location of a v1 entry: its `packages` key when the lockfile has both
+/// sections (v2), else the `dependencies` entry with its version
+fn locate_v1(lines: &LineIndex<'_>, key: &str, version: &str) -> Option<SourceSpan> {
+    lines
+        .find_json_key(&format!("node_modules/{}", key), 0)
+        .or_else(|| lines.find_json_entry(key, version, 0))
+}
+
 fn parse_nested_dependencies(
     dependencies: &HashMap<String, DependencyEntry>,
-    file_path: &Path,
+    source_file: &Arc<Path>,
+    lines: &LineIndex<'_>,
     records: &mut Vec<DependencyRecord>,
 ) {
-    for (name, entry) in dependencies {
+    for (key, entry) in dependencies {
+        let (name, version, alias) = unalias(key, &entry.version);
         // Only add if not already present
         if !records
             .iter()
-            .any(|r| r.name == *name && r.version == entry.version)
+            .any(|r| r.name == name && r.version == version)
         {
             records.push(DependencyRecord {
-                name: name.clone(),
-                version: entry.version.clone(),
-                source_file: file_path.to_path_buf(),
+                name: name.into(),
+                version: version.into(),
+                source_file: source_file.clone(),
                 dep_type: DependencyType::Runtime,
                 ecosystem: Ecosystem::Node,
                 file_type: FileType::Lockfile,
+                alias,
+                source_kind: SourceKind::Registry,
+                resolved: entry.resolved.clone(),
+                integrity: entry.integrity.clone(),
+                dependencies: sorted_names(entry.requires.keys()),
+                span: lines.find_json_entry(key, &entry.version, 0),
             });
         }
 
         // Recurse into nested dependencies
-        parse_nested_dependencies(&entry.dependencies, file_path, records);
+        parse_nested_dependencies(&entry.dependencies, source_file, lines, records);
     }
 }
@@ -1,9 +1,15 @@
 //! Parser for pnpm-lock.yaml files
 
 use regex::Regex;
+use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
 
-use crate::models::{DependencyRecord, DependencyType, Ecosystem, FileType, ScanError};
+use crate::models::{
+    DependencyRecord, DependencyType, Ecosystem, FileType, ScanError, SourceKind, SourceSpan,
+    Symbol,
+};
+use crate::parsers::span::LineIndex;
 use crate::parsers::Parser;
 
 /// Parser for pnpm-lock.yaml lockfiles
@@ -11,7 +17,13 @@ pub struct PnpmLockParser;
 
 impl Parser for PnpmLockParser {
     fn parse(&self, content: &str, file_path: &Path) -> Result<Vec<DependencyRecord>, ScanError> {
+        let source_file: Arc<Path> = Arc::from(file_path);
         let mut records = Vec::new();
+        let resolutions = parse_resolutions(content);
+        let edges = parse_edges(content);
+        let aliases = parse_aliases(content);
+        let lines = LineIndex::new(content);
+        let entries = parse_entry_spans(content, &lines);
 
         // Pattern 1: /package/1.2.3
         let pattern1 = Regex::new(r"/([^/@\s]+)/(\d+\.\d+\.\d+[^\s:]*)").unwrap();
@@ -29,13 +41,24 @@ impl Parser for PnpmLockParser {
                 .iter()
                 .any(|r: &DependencyRecord| r.name == name && r.version == version)
             {
+                let key = (name.clone(), version.clone());
+                let resolution = resolutions.get(&key);
                 records.push(DependencyRecord {
-                    name,
-                    version,
-                    source_file: file_path.to_path_buf(),
+                    name: name.into(),
+                    version: version.into(),
+                    source_file: source_file.clone(),
                     dep_type: DependencyType::Runtime,
                     ecosystem: Ecosystem::Node,
                     file_type: FileType::Lockfile,
+                    alias: aliases.get(&key).cloned(),
+                    source_kind: SourceKind::Registry,
+                    resolved: resolution.and_then(|r| r.tarball.clone()),
+                    integrity: resolution.and_then(|r| r.integrity.clone()),
+                    dependencies: edges.get(&key).cloned().unwrap_or_default(),
+                    span: entries
+                        .get(&key)
+                        .copied()
+                        .or_else(|| cap.get(0).map(|m| lines.span(m.range()))),
                 });
             }
         }
@@ -50,13 +73,24 @@ impl Parser for PnpmLockParser {
                 .iter()
                 .any(|r: &DependencyRecord| r.name == name && r.version == version)
             {
+                let key = (name.clone(), version.clone());
+                let resolution = resolutions.get(&key);
                 records.push(DependencyRecord {
-                    name,
-                    version,
-                    source_file: file_path.to_path_buf(),
+                    name: name.into(),
+                    version: version.into(),
+                    source_file: source_file.clone(),
                     dep_type: DependencyType::Runtime,
                     ecosystem: Ecosystem::Node,
                     file_type: FileType::Lockfile,
+                    alias: aliases.get(&key).cloned(),
+                    source_kind: SourceKind::Registry,
+                    resolved: resolution.and_then(|r| r.tarball.clone()),
+                    integrity: resolution.and_then(|r| r.integrity.clone()),
+                    dependencies: edges.get(&key).cloned().unwrap_or_default(),
+                    span: entries
+                        .get(&key)
+                        .copied()
+                        .or_else(|| cap.get(0).map(|m| lines.span(m.range()))),
                 });
             }
         }
@@ -76,3 +110,135 @@ impl Parser for PnpmLockParser {
         "pnpm-lock.yaml"
     }
 }
+
+/// Key of a `packages` or `snapshots` entry, capturing name and version
+const PACKAGE_KEY: &str =
+    r#"^\s{2}['"]?/?((?:@[^/@\s'"]+/)?[^/@\s'"]+)[@/](\d+\.\d+\.\d+[^\s:'"(]*)"#;
+
+/// The `resolution` of a package entry
+#[derive(Debug, Default)]
+struct Resolution {
+    integrity: Option<String>,
+    tarball: Option<String>,
+}
+
+/// Resolutions of the `packages` entries, keyed by name and version
+///
+/// Handles the `/name/1.2.3:` (v5), `/name@1.2.3:` (v6) and `name@1.2.3:` (v9)
+/// key styles. pnpm only records a tarball URL for packages not served by the
+/// configured registry.
+fn parse_resolutions(content: &str) -> HashMap<(String, String), Resolution> {
+    let key_re = Regex::new(PACKAGE_KEY).unwrap();
+    let resolution_re = Regex::new(r"^\s+resolution:\s*\{(.*)\}").unwrap();
+    let integrity_re = Regex::new(r"integrity:\s*([^,}\s]+)").unwrap();
+    let tarball_re = Regex::new(r"tarball:\s*([^,}\s]+)").unwrap();
+
+    let mut resolutions = HashMap::new();
+    let mut current = None;
+    for line in content.lines() {
+        if let Some(cap) = key_re.captures(line) {
+            current = Some((cap[1].to_string(), cap[2].to_string()));
+        } else if let Some(cap) = resolution_re.captures(line) {
+            if let Some(key) = current.take() {
+                let fields = &cap[1];
+                resolutions.insert(
+                    key,
+                    Resolution {
+                        integrity: integrity_re.captures(fields).map(|c| c[1].to_string()),
+                        tarball: tarball_re.captures(fields).map(|c| c[1].to_string()),
+                    },
+                );
+            }
+        } else if !line.starts_with("    ") {
+            // Any other line at package level or above ends the entry
+            current = None;
+        }
+    }
+    resolutions
+}
+
+/// Locations of the `packages` entry keys, keyed by name and version
+fn parse_entry_spans(
+    content: &str,
+    lines: &LineIndex<'_>,
+) -> HashMap<(String, String), SourceSpan> {
+    let key_re = Regex::new(PACKAGE_KEY).unwrap();
+    let mut spans = HashMap::new();
+    for line in content.lines() {
+        if let Some(cap) = key_re.captures(line) {
+            let key = (cap[1].to_string(), cap[2].to_string());
+            spans
+                .entry(key)
+                .or_insert_with(|| lines.span_of(line.trim()));
+        }
+    }
+    spans
+}
+
+/// Aliases of dependencies, keyed by the real name and version they resolve to
+///
+/// An aliased dependency (`"pkg": "npm:real-pkg@^1.0.0"`) resolves to another
+/// package under its key: `pkg: /real-pkg/1.0.0` (v5), `version:
+/// /real-pkg@1.0.0` (v6) or `version: real-pkg@1.0.0` (v9).
+fn parse_aliases(content: &str) -> HashMap<(String, String), String> {
+    let entry_re = Regex::new(r#"^\s+['"]?((?:@[^/\s'"]+/)?[^\s:'"]+)['"]?:\s*(.*)$"#).unwrap();
+    let target_re =
+        Regex::new(r"^/?((?:@[^/@\s]+/)?[^/@\s(\d][^/@\s(]*)[@/](\d+\.\d+\.\d+[^\s(]*)").unwrap();
+
+    let mut aliases = HashMap::new();
+    let mut current: Option<String> = None;
+    for line in content.lines() {
+        let Some(cap) = entry_re.captures(line) else {
+            continue;
+        };
+        let (key, value) = (&cap[1], cap[2].trim());
+        let alias = if value.is_empty() {
+            current = Some(key.to_string());
+            continue;
+        } else if key == "version" {
+            current.clone()
+        } else {
+            Some(key.to_string())
+        };
+        if let (Some(alias), Some(target)) = (alias, target_re.captures(value)) {
+            if target[1] != alias {
+                aliases.insert((target[1].to_string(), target[2].to_string()), alias);
+            }
+        }
+    }
+    aliases
+}
+
+/// Dependency names of the `packages` entries (v5/v6) and `snapshots` entries (v9)
+fn parse_edges(content: &str) -> HashMap<(String, String), Vec<Symbol>> {
+    let key_re = Regex::new(PACKAGE_KEY).unwrap();
+    let dep_re = Regex::new(r#"^\s{6}['"]?((?:@[^/\s'"]+/)?[^\s:'"]+)['"]?:"#).unwrap();
+
+    let mut edges: HashMap<(String, String), Vec<Symbol>> = HashMap::new();
+    let mut current = None;
+    let mut in_dependencies = false;
+    for line in content.lines() {
+        if let Some(cap) = key_re.captures(line) {
+            current = Some((cap[1].to_string(), cap[2].to_string()));
+            in_dependencies = false;
+        } else if line.starts_with("      ") {
+            if let (true, Some(key), Some(cap)) = (in_dependencies, &current, dep_re.captures(line))
+            {
+                let names = edges.entry(key.clone()).or_default();
+                if !names.iter().any(|name| *name == cap[1]) {
+                    names.push(Symbol::from(&cap[1]));
+                }
+            }
+        } else if line.starts_with("    ") {
+            let field = line.trim();
+            in_dependencies = field == "dependencies:" || field == "optionalDependencies:";
+        } else {
+            current = None;
+            in_dependencies = false;
+        }
+    }
+    for names in edges.values_mut() {
+        names.sort();
+    }
+    edges
+}
@@ -1,9 +1,14 @@
 //! Parser for poetry.lock files
 
 use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
+use std::sync::Arc;
 
-use crate::models::{DependencyRecord, DependencyType, Ecosystem, FileType, ScanError};
+use crate::models::{
+    DependencyRecord, DependencyType, Ecosystem, FileType, ScanError, SourceKind, Symbol,
+};
+use crate::parsers::span::LineIndex;
 use crate::parsers::Parser;
 
 /// Parser for poetry.lock lockfiles
@@ -13,29 +18,78 @@ pub struct PoetryLockParser;
 struct PoetryLock {
     #[serde(default)]
     package: Vec<Package>,
+    #[serde(default)]
+    metadata: Option<Metadata>,
+}
+
+/// Lock metadata; Poetry before 1.2 keeps file hashes here, keyed by package name
+#[derive(Debug, Deserialize)]
+struct Metadata {
+    #[serde(default)]
+    files: HashMap<String, Vec<PackageFile>>,
 }
 
 #[derive(Debug, Deserialize)]
 struct Package {
-    name: String,
+    name: toml::Spanned<String>,
     version: String,
+    #[serde(default)]
+    source: Option<PackageSource>,
+    #[serde(default)]
+    files: Vec<PackageFile>,
+    /// `[package.dependencies]`, keyed by name
+    #[serde(default)]
+    dependencies: BTreeMap<String, toml::Value>,
+}
+
+/// Non-default package source (`[package.source]`)
+#[derive(Debug, Deserialize)]
+struct PackageSource {
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PackageFile {
+    hash: String,
 }
 
 impl Parser for PoetryLockParser {
     fn parse(&self, content: &str, file_path: &Path) -> Result<Vec<DependencyRecord>, ScanError> {
+        let source_file: Arc<Path> = Arc::from(file_path);
         let poetry_lock: PoetryLock = toml::from_str(content)
             .map_err(|e| ScanError::toml_error(file_path.to_path_buf(), e))?;
 
+        let lines = LineIndex::new(content);
         let mut records = Vec::new();
+        let legacy_files = poetry_lock
+            .metadata
+            .map(|metadata| metadata.files)
+            .unwrap_or_default();
 
         for package in poetry_lock.package {
+            let files = if package.files.is_empty() {
+                legacy_files
+                    .get(package.name.get_ref())
+                    .map_or(&[][..], Vec::as_slice)
+            } else {
+                package.files.as_slice()
+            };
+            let hashes: Vec<&str> = files.iter().map(|f| f.hash.as_str()).collect();
+            let integrity = (!hashes.is_empty()).then(|| hashes.join(" "));
+            let span = lines.span(package.name.span());
             records.push(DependencyRecord {
-                name: package.name,
-                version: package.version,
-                source_file: file_path.to_path_buf(),
+                name: package.name.into_inner().into(),
+                version: package.version.into(),
+                source_file: source_file.clone(),
                 dep_type: DependencyType::Runtime,
                 ecosystem: Ecosystem::Python,
                 file_type: FileType::Lockfile,
+                alias: None,
+                source_kind: SourceKind::Registry,
+                resolved: package.source.map(|source| source.url),
+                integrity,
+                dependencies: package.dependencies.into_keys().map(Symbol::from).collect(),
+                span: Some(span),
             });
         }
 
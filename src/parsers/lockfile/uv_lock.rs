@@ -2,8 +2,12 @@
 
 use serde::Deserialize;
 use std::path::Path;
+use std::sync::Arc;
 
-use crate::models::{DependencyRecord, DependencyType, Ecosystem, FileType, ScanError};
+use crate::models::{
+    DependencyRecord, DependencyType, Ecosystem, FileType, ScanError, SourceKind, Symbol,
+};
+use crate::parsers::span::LineIndex;
 use crate::parsers::Parser;
 
 /// Parser for uv.lock lockfiles
@@ -17,25 +21,84 @@ struct UvLock {
 
 #[derive(Debug, Deserialize)]
 struct Package {
-    name: String,
+    name: toml::Spanned<String>,
     version: String,
+    #[serde(default)]
+    source: Option<PackageSource>,
+    #[serde(default)]
+    sdist: Option<Artifact>,
+    #[serde(default)]
+    wheels: Vec<Artifact>,
+    #[serde(default)]
+    dependencies: Vec<PackageDependency>,
+}
+
+/// An entry of a package's `dependencies` array
+#[derive(Debug, Deserialize)]
+struct PackageDependency {
+    name: String,
+}
+
+/// Where a package comes from; local sources (`editable`, `virtual`, `path`) have no URL
+#[derive(Debug, Deserialize)]
+struct PackageSource {
+    #[serde(default)]
+    registry: Option<String>,
+    #[serde(default)]
+    git: Option<String>,
+    #[serde(default)]
+    url: Option<String>,
+}
+
+/// A source distribution or wheel
+#[derive(Debug, Deserialize)]
+struct Artifact {
+    #[serde(default)]
+    hash: Option<String>,
 }
 
 impl Parser for UvLockParser {
     fn parse(&self, content: &str, file_path: &Path) -> Result<Vec<DependencyRecord>, ScanError> {
+        let source_file: Arc<Path> = Arc::from(file_path);
         let uv_lock: UvLock = toml::from_str(content)
             .map_err(|e| ScanError::toml_error(file_path.to_path_buf(), e))?;
 
+        let lines = LineIndex::new(content);
         let mut records = Vec::new();
 
         for package in uv_lock.package {
+            let resolved = package
+                .source
+                .and_then(|source| source.registry.or(source.git).or(source.url));
+            let hashes: Vec<String> = package
+                .sdist
+                .into_iter()
+                .chain(package.wheels)
+                .filter_map(|artifact| artifact.hash)
+                .collect();
+            let integrity = (!hashes.is_empty()).then(|| hashes.join(" "));
+            // A package can be listed once per environment marker
+            let mut dependencies: Vec<Symbol> = package
+                .dependencies
+                .into_iter()
+                .map(|d| Symbol::from(d.name))
+                .collect();
+            dependencies.sort();
+            dependencies.dedup();
+            let span = lines.span(package.name.span());
             records.push(DependencyRecord {
-                name: package.name,
-                version: package.version,
-                source_file: file_path.to_path_buf(),
+                name: package.name.into_inner().into(),
+                version: package.version.into(),
+                source_file: source_file.clone(),
                 dep_type: DependencyType::Runtime,
                 ecosystem: Ecosystem::Python,
                 file_type: FileType::Lockfile,
+                alias: None,
+                source_kind: SourceKind::Registry,
+                resolved,
+                integrity,
+                dependencies,
+                span: Some(span),
             });
         }
 
@@ -2,8 +2,13 @@
 
 use regex::Regex;
 use std::path::Path;
+use std::sync::Arc;
 
-use crate::models::{DependencyRecord, DependencyType, Ecosystem, FileType, ScanError};
+use crate::models::{
+    DependencyRecord, DependencyType, Ecosystem, FileType, ScanError, SourceKind, Symbol,
+};
+use crate::parsers::npm_alias::split_alias;
+use crate::parsers::span::LineIndex;
 use crate::parsers::Parser;
 
 /// Parser for yarn.lock lockfiles
@@ -11,15 +16,19 @@ pub struct YarnLockParser;
 
 impl Parser for YarnLockParser {
     fn parse(&self, content: &str, file_path: &Path) -> Result<Vec<DependencyRecord>, ScanError> {
+        let source_file: Arc<Path> = Arc::from(file_path);
+        let index = LineIndex::new(content);
         let mut records = Vec::new();
 
         // Split content into records (separated by blank lines)
         let record_re = Regex::new(r"\n\s*\n").unwrap();
         let records_text: Vec<&str> = record_re.split(content).collect();
 
-        // Regex to extract package name and version
-        let name_re = Regex::new(r#"^["']?([^@\s"']+)@"#).unwrap();
+        // Regex to extract package name (optionally scoped), version, resolved URL and integrity
+        let name_re = Regex::new(r#"^["']?(@?[^@\s"']+)@"#).unwrap();
         let version_re = Regex::new(r#"^\s*version\s+"([^"]+)""#).unwrap();
+        let resolved_re = Regex::new(r#"^\s*resolved\s+"([^"]+)""#).unwrap();
+        let integrity_re = Regex::new(r#"^\s*integrity\s+"?([^"\s]+)"?"#).unwrap();
 
         for record in records_text {
             // Skip empty records
@@ -34,29 +43,66 @@ impl Parser for YarnLockParser {
             }
 
             let first_line = lines[0];
-            let name = if let Some(cap) = name_re.captures(first_line) {
-                cap[1].to_string()
-            } else {
+            let Some(cap) = name_re.captures(first_line) else {
                 continue;
             };
+            // `"pkg@npm:real-pkg@^1.0.0":` installs real-pkg under the alias pkg
+            let spec = first_line[cap.get(0).map_or(0, |m| m.end())..]
+                .split(['"', '\'', ','])
+                .next()
+                .unwrap_or_default()
+                .trim_end_matches(':');
+            let (name, alias) = match split_alias(spec) {
+                Some((real, _)) => (real.to_string(), Some(cap[1].to_string())),
+                None => (cap[1].to_string(), None),
+            };
 
-            // Extract version from the record
+            // Extract version, resolved URL and integrity from the record
             let mut version = String::new();
+            let mut resolved = None;
+            let mut integrity = None;
+            let mut dependencies = Vec::new();
+            let mut in_dependencies = false;
             for line in &lines {
+                let indent = line.len() - line.trim_start().len();
+                if in_dependencies && indent >= 4 {
+                    // `name "^1.0.0"` (classic) or `name: ^1.0.0` (berry)
+                    if let Some(dep) = line.split_whitespace().next() {
+                        let dep = dep
+                            .trim_end_matches(':')
+                            .trim_matches(|c| c == '"' || c == '\'');
+                        dependencies.push(Symbol::from(dep));
+                    }
+                    continue;
+                }
+                let key = line.trim();
+                in_dependencies =
+                    indent == 2 && (key == "dependencies:" || key == "optionalDependencies:");
                 if let Some(cap) = version_re.captures(line) {
                     version = cap[1].to_string();
-                    break;
+                } else if let Some(cap) = resolved_re.captures(line) {
+                    resolved = Some(cap[1].to_string());
+                } else if let Some(cap) = integrity_re.captures(line) {
+                    integrity = Some(cap[1].to_string());
                 }
             }
 
             if !version.is_empty() {
+                dependencies.sort();
+                dependencies.dedup();
                 records.push(DependencyRecord {
-                    name,
-                    version,
-                    source_file: file_path.to_path_buf(),
+                    name: name.into(),
+                    version: version.into(),
+                    source_file: source_file.clone(),
                     dep_type: DependencyType::Runtime,
                     ecosystem: Ecosystem::Node,
                     file_type: FileType::Lockfile,
+                    alias,
+                    source_kind: SourceKind::Registry,
+                    resolved,
+                    integrity,
+                    dependencies,
+                    span: Some(index.span_of(first_line.trim_end_matches(':'))),
                 });
             }
         }
@@ -3,8 +3,10 @@
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
 
-use crate::models::{DependencyRecord, DependencyType, Ecosystem, FileType, ScanError};
+use crate::models::{DependencyRecord, DependencyType, Ecosystem, FileType, ScanError, SourceKind};
+use crate::parsers::span::LineIndex;
 use crate::parsers::Parser;
 
 /// Parser for Cargo.toml manifest files
@@ -22,47 +24,77 @@ struct CargoToml {
 
 impl Parser for CargoTomlParser {
     fn parse(&self, content: &str, file_path: &Path) -> Result<Vec<DependencyRecord>, ScanError> {
+        let source_file: Arc<Path> = Arc::from(file_path);
         let cargo_toml: CargoToml = toml::from_str(content)
             .map_err(|e| ScanError::toml_error(file_path.to_path_buf(), e))?;
 
+        let lines = LineIndex::new(content);
+        let section = |header: &str| lines.find(header, 0).map_or(0, |h| h.start as usize);
         let mut records = Vec::new();
 
         // Parse runtime dependencies
+        let start = section("[dependencies");
         for (name, value) in cargo_toml.dependencies {
+            let span = lines.find_toml_key(&name, start);
             let version = extract_cargo_version(&value);
+            let source_kind = extract_cargo_source(&value);
             records.push(DependencyRecord {
-                name,
-                version,
-                source_file: file_path.to_path_buf(),
+                name: name.into(),
+                version: version.into(),
+                source_file: source_file.clone(),
                 dep_type: DependencyType::Runtime,
                 ecosystem: Ecosystem::Rust,
                 file_type: FileType::Manifest,
+                alias: None,
+                source_kind,
+                resolved: None,
+                integrity: None,
+                dependencies: Vec::new(),
+                span,
             });
         }
 
         // Parse dev dependencies
+        let start = section("[dev-dependencies");
         for (name, value) in cargo_toml.dev_dependencies {
+            let span = lines.find_toml_key(&name, start);
             let version = extract_cargo_version(&value);
+            let source_kind = extract_cargo_source(&value);
             records.push(DependencyRecord {
-                name,
-                version,
-                source_file: file_path.to_path_buf(),
+                name: name.into(),
+                version: version.into(),
+                source_file: source_file.clone(),
                 dep_type: DependencyType::Development,
                 ecosystem: Ecosystem::Rust,
                 file_type: FileType::Manifest,
+                alias: None,
+                source_kind,
+                resolved: None,
+                integrity: None,
+                dependencies: Vec::new(),
+                span,
             });
         }
 
         // Parse build dependencies
+        let start = section("[build-dependencies");
         for (name, value) in cargo_toml.build_dependencies {
+            let span = lines.find_toml_key(&name, start);
             let version = extract_cargo_version(&value);
+            let source_kind = extract_cargo_source(&value);
             records.push(DependencyRecord {
-                name,
-                version,
-                source_file: file_path.to_path_buf(),
+                name: name.into(),
+                version: version.into(),
+                source_file: source_file.clone(),
                 dep_type: DependencyType::Build,
                 ecosystem: Ecosystem::Rust,
                 file_type: FileType::Manifest,
+                alias: None,
+                source_kind,
+                resolved: None,
+                integrity: None,
+                dependencies: Vec::new(),
+                span,
             });
         }
 
@@ -96,3 +128,24 @@ fn extract_cargo_version(value: &toml::Value) -> String {
         _ => "*".to_string(),
     }
 }
+
+/// Extract where a Cargo dependency comes from (`git`, `path` or `workspace = true`)
+fn extract_cargo_source(value: &toml::Value) -> SourceKind {
+    let toml::Value::Table(t) = value else {
+        return SourceKind::Registry;
+    };
+    let string = |key: &str| t.get(key).and_then(toml::Value::as_str).map(str::to_string);
+
+    if let Some(url) = string("git") {
+        let rev = string("rev")
+            .or_else(|| string("tag"))
+            .or_else(|| string("branch"));
+        SourceKind::Git { url, rev }
+    } else if let Some(path) = string("path") {
+        SourceKind::Path { path }
+    } else if t.get("workspace").and_then(toml::Value::as_bool) == Some(true) {
+        SourceKind::Workspace
+    } else {
+        SourceKind::Registry
+    }
+}
@@ -0,0 +1,288 @@
+//! Parser for Deno configuration files and import maps
+//!
+//! Deno declares dependencies as import map entries (`imports` and `scopes`
+//! of `deno.json`, `deno.jsonc` or a standalone `import_map.json`) whose
+//! targets are `npm:`, `jsr:` or remote URL specifiers. Entries are reported
+//! in the Node ecosystem: npm packages come from the registry, JSR packages
+//! and remote modules from their URL. Relative and bare path mappings are
+//! skipped.
+
+use serde_json::Value;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::models::{DependencyRecord, DependencyType, Ecosystem, FileType, ScanError, SourceKind};
+use crate::parsers::span::LineIndex;
+use crate::parsers::Parser;
+
+/// Parser for deno.json, deno.jsonc and import_map.json manifests
+#[derive(Debug, Clone, Copy)]
+pub struct DenoJsonParser {
+    filename: &'static str,
+}
+
+impl DenoJsonParser {
+    /// Parser for `deno.json`
+    pub fn new() -> Self {
+        Self {
+            filename: "deno.json",
+        }
+    }
+
+    /// Parser for `deno.jsonc`
+    pub fn jsonc() -> Self {
+        Self {
+            filename: "deno.jsonc",
+        }
+    }
+
+    /// Parser for a standalone `import_map.json`
+    pub fn import_map() -> Self {
+        Self {
+            filename: "import_map.json",
+        }
+    }
+}
+
+impl Default for DenoJsonParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Parser for DenoJsonParser {
+    fn parse(&self, content: &str, file_path: &Path) -> Result<Vec<DependencyRecord>, ScanError> {
+        let source_file: Arc<Path> = Arc::from(file_path);
+        let config: Value = serde_json::from_str(&strip_jsonc(content))
+            .map_err(|e| ScanError::json_error(file_path.to_path_buf(), e))?;
+
+        let scopes = config
+            .get("scopes")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flat_map(|scopes| scopes.values());
+        let targets = config
+            .get("imports")
+            .into_iter()
+            .chain(scopes)
+            .filter_map(Value::as_object)
+            .flat_map(|imports| imports.values())
+            .filter_map(Value::as_str);
+
+        let lines = LineIndex::new(content);
+        let mut records: Vec<DependencyRecord> = Vec::new();
+        for target in targets {
+            let Some((name, version, source_kind)) = parse_deno_specifier(target) else {
+                continue;
+            };
+            if records
+                .iter()
+                .any(|r| r.name.as_str() == name && r.version.as_str() == version)
+            {
+                continue;
+            }
+            let quoted = serde_json::to_string(target).unwrap_or_default();
+            records.push(DependencyRecord {
+                name: name.into(),
+                version: version.into(),
+                source_file: source_file.clone(),
+                dep_type: DependencyType::Runtime,
+                ecosystem: Ecosystem::Node,
+                file_type: FileType::Manifest,
+                alias: None,
+                source_kind,
+                resolved: None,
+                integrity: None,
+                dependencies: Vec::new(),
+                span: lines.find(&quoted, 0),
+            });
+        }
+
+        Ok(records)
+    }
+
+    fn ecosystem(&self) -> Ecosystem {
+        Ecosystem::Node
+    }
+
+    fn file_type(&self) -> FileType {
+        FileType::Manifest
+    }
+
+    fn filename(&self) -> &str {
+        self.filename
+    }
+}
+
+/// Name, version (or range) and source of an `npm:`, `jsr:` or remote URL
+/// specifier; `None` for local paths and URLs without a version
+pub(crate) fn parse_deno_specifier(specifier: &str) -> Option<(String, String, SourceKind)> {
+    if let Some(package) = specifier.strip_prefix("npm:") {
+        let (name, version) = split_package(package)?;
+        return Some((name, version, SourceKind::Registry));
+    }
+    if let Some(package) = specifier.strip_prefix("jsr:") {
+        let (name, version) = split_package(package)?;
+        let url = format!("https://jsr.io/{}", name);
+        return Some((name, version, SourceKind::Url { url }));
+    }
+    if specifier.starts_with("https://") || specifier.starts_with("http://") {
+        // The versioned segment names the module: `/x/oak@v12.6.1/`,
+        // `/std@0.200.0/`, `/react@18.2.0`, `/@preact/signals@1.2.1`
+        let path = specifier.split_once("://")?.1.split_once('/')?.1;
+        let segments: Vec<&str> = path.split('/').collect();
+        for (i, segment) in segments.iter().enumerate() {
+            let Some((name, version)) = segment.rsplit_once('@') else {
+                continue;
+            };
+            if name.is_empty() || version.is_empty() {
+                continue;
+            }
+            let name = match i.checked_sub(1).map(|p| segments[p]) {
+                Some(scope) if scope.starts_with('@') => format!("{}/{}", scope, name),
+                _ => name.to_string(),
+            };
+            let end = specifier.find(segment)? + segment.len();
+            let url = specifier[..end].to_string();
+            return Some((name, version.to_string(), SourceKind::Url { url }));
+        }
+    }
+    None
+}
+
+/// Split `name@version/subpath` (name possibly scoped) into name and version,
+/// `*` when unversioned
+fn split_package(package: &str) -> Option<(String, String)> {
+    let package = package.trim_start_matches('/');
+    let (scope, rest) = match package.strip_prefix('@') {
+        Some(rest) => {
+            let (scope, rest) = rest.split_once('/')?;
+            (Some(scope), rest)
+        }
+        None => (None, package),
+    };
+    let (name, version) = match rest.split_once('@') {
+        Some((name, version)) => (name, version.split('/').next().unwrap_or_default()),
+        None => (rest.split('/').next().unwrap_or_default(), "*"),
+    };
+    if name.is_empty() {
+        return None;
+    }
+    let name = match scope {
+        Some(scope) => format!("@{}/{}", scope, name),
+        None => name.to_string(),
+    };
+    let version = if version.is_empty() { "*" } else { version };
+    Some((name, version.to_string()))
+}
+
+/// Remove `//` and `/* */` comments and trailing commas outside strings
+fn strip_jsonc(content: &str) -> String {
+    let without_comments = outside_strings(content, |chars, i| match chars[i..] {
+        ['/', '/', ..] => chars[i..]
+            .iter()
+            .position(|&c| c == '\n')
+            .map_or(chars.len(), |end| i + end),
+        ['/', '*', ..] => chars[i + 2..]
+            .windows(2)
+            .position(|w| w == ['*', '/'])
+            .map_or(chars.len(), |end| i + 2 + end + 2),
+        _ => i,
+    });
+    outside_strings(&without_comments, |chars, i| {
+        let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+        if chars[i] == ',' && matches!(next, Some('}') | Some(']')) {
+            i + 1
+        } else {
+            i
+        }
+    })
+}
+
+/// Copy `content`, letting `skip` return the end of a span to drop at each
+/// position outside string literals (or the position itself to keep it)
+fn outside_strings(content: &str, skip: impl Fn(&[char], usize) -> usize) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let mut out = String::with_capacity(content.len());
+    let mut i = 0;
+    let mut in_string = false;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if in_string {
+            out.push(c);
+            if c == '\\' && i + 1 < chars.len() {
+                out.push(chars[i + 1]);
+                i += 1;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else {
+            let end = skip(&chars, i);
+            if end > i {
+                i = end;
+                continue;
+            }
+            in_string = c == '"';
+            out.push(c);
+        }
+        i += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_deno_specifier() {
+        let registry = |name: &str, version: &str| {
+            Some((name.to_string(), version.to_string(), SourceKind::Registry))
+        };
+        let url = |name: &str, version: &str, url: &str| {
+            let url = url.to_string();
+            Some((
+                name.to_string(),
+                version.to_string(),
+                SourceKind::Url { url },
+            ))
+        };
+
+        assert_eq!(parse_deno_specifier("npm:chalk@5"), registry("chalk", "5"));
+        assert_eq!(
+            parse_deno_specifier("npm:@types/node@^20/fs"),
+            registry("@types/node", "^20")
+        );
+        assert_eq!(
+            parse_deno_specifier("npm:express"),
+            registry("express", "*")
+        );
+        assert_eq!(
+            parse_deno_specifier("jsr:@std/assert@^1.0.0"),
+            url("@std/assert", "^1.0.0", "https://jsr.io/@std/assert")
+        );
+        assert_eq!(
+            parse_deno_specifier("https://deno.land/x/oak@v12.6.1/mod.ts"),
+            url("oak", "v12.6.1", "https://deno.land/x/oak@v12.6.1")
+        );
+        assert_eq!(
+            parse_deno_specifier("https://esm.sh/@preact/signals@1.2.1"),
+            url(
+                "@preact/signals",
+                "1.2.1",
+                "https://esm.sh/@preact/signals@1.2.1"
+            )
+        );
+        assert_eq!(parse_deno_specifier("./src/utils.ts"), None);
+        assert_eq!(parse_deno_specifier("https://example.com/mod.ts"), None);
+    }
+
+    #[test]
+    fn test_strip_jsonc() {
+        let content = "{\n  // imports\n  \"imports\": { \"a\": \"npm:a@1\", /* b */ },\n  \"url\": \"https://x//y\",\n}";
+        let value: Value = serde_json::from_str(&strip_jsonc(content)).unwrap();
+        assert_eq!(value["imports"]["a"], "npm:a@1");
+        assert_eq!(value["url"], "https://x//y");
+    }
+}
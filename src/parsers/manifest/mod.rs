@@ -1,11 +1,17 @@
 //! Manifest file parsers (declared dependencies)
 
 pub mod cargo_toml;
+pub mod deno_json;
 pub mod package_json;
 pub mod pyproject_toml;
 pub mod requirements_txt;
+pub mod setup_cfg;
+pub mod setup_py;
 
 pub use cargo_toml::CargoTomlParser;
+pub use deno_json::DenoJsonParser;
 pub use package_json::PackageJsonParser;
 pub use pyproject_toml::PyprojectTomlParser;
-pub use requirements_txt::RequirementsTxtParser;
+pub use requirements_txt::{RequirementsMode, RequirementsTxtParser};
+pub use setup_cfg::SetupCfgParser;
+pub use setup_py::SetupPyParser;
@@ -1,10 +1,21 @@
 //! Parser for package.json files
+//!
+//! Besides the declared dependencies, version overrides are recorded as
+//! [`DependencyType::Override`] records: npm `overrides` (including nested
+//! ones and `$name` references to a direct dependency), Yarn `resolutions`
+//! and pnpm `pnpm.overrides`. Each override is recorded under the package it
+//! targets; selectors naming the parent or the replaced range (`a>b`,
+//! `**/b`, `b@<2`) are reduced to that package.
 
 use serde::Deserialize;
+use serde_json::{Map, Value};
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
 
-use crate::models::{DependencyRecord, DependencyType, Ecosystem, FileType, ScanError};
+use crate::models::{DependencyRecord, DependencyType, Ecosystem, FileType, ScanError, SourceKind};
+use crate::parsers::npm_alias::split_alias;
+use crate::parsers::span::LineIndex;
 use crate::parsers::Parser;
 
 /// Parser for package.json manifest files
@@ -20,61 +31,89 @@ struct PackageJson {
     peer_dependencies: HashMap<String, String>,
     #[serde(default, rename = "optionalDependencies")]
     optional_dependencies: HashMap<String, String>,
+    #[serde(default)]
+    overrides: Map<String, Value>,
+    #[serde(default)]
+    resolutions: Map<String, Value>,
+    #[serde(default)]
+    pnpm: PnpmConfig,
+}
+
+/// The `pnpm` section of a package.json
+#[derive(Debug, Default, Deserialize)]
+struct PnpmConfig {
+    #[serde(default)]
+    overrides: Map<String, Value>,
 }
 
 impl Parser for PackageJsonParser {
     fn parse(&self, content: &str, file_path: &Path) -> Result<Vec<DependencyRecord>, ScanError> {
+        let source_file: Arc<Path> = Arc::from(file_path);
         let package_json: PackageJson = serde_json::from_str(content)
             .map_err(|e| ScanError::json_error(file_path.to_path_buf(), e))?;
 
         let mut records = Vec::new();
 
-        // Parse runtime dependencies
-        for (name, version) in package_json.dependencies {
-            records.push(DependencyRecord {
-                name,
-                version,
-                source_file: file_path.to_path_buf(),
-                dep_type: DependencyType::Runtime,
-                ecosystem: Ecosystem::Node,
-                file_type: FileType::Manifest,
-            });
-        }
-
-        // Parse dev dependencies
-        for (name, version) in package_json.dev_dependencies {
-            records.push(DependencyRecord {
-                name,
-                version,
-                source_file: file_path.to_path_buf(),
-                dep_type: DependencyType::Development,
-                ecosystem: Ecosystem::Node,
-                file_type: FileType::Manifest,
-            });
+        // Overrides first: npm's `$name` references need the direct dependencies
+        let mut overrides = Vec::new();
+        npm_overrides(&package_json, &package_json.overrides, &mut overrides);
+        for (key, value) in package_json
+            .resolutions
+            .iter()
+            .chain(&package_json.pnpm.overrides)
+        {
+            if let Some(version) = value.as_str() {
+                overrides.push((override_target(key).to_string(), version.to_string()));
+            }
         }
-
-        // Parse peer dependencies
-        for (name, version) in package_json.peer_dependencies {
-            records.push(DependencyRecord {
-                name,
-                version,
-                source_file: file_path.to_path_buf(),
-                dep_type: DependencyType::Peer,
-                ecosystem: Ecosystem::Node,
-                file_type: FileType::Manifest,
-            });
+        for (name, version) in overrides {
+            // pnpm removes a dependency overridden with `-`
+            if !name.is_empty() && version != "-" {
+                records.push(record(
+                    &source_file,
+                    name,
+                    version,
+                    DependencyType::Override,
+                ));
+            }
         }
 
-        // Parse optional dependencies
-        for (name, version) in package_json.optional_dependencies {
-            records.push(DependencyRecord {
-                name,
-                version,
-                source_file: file_path.to_path_buf(),
-                dep_type: DependencyType::Optional,
-                ecosystem: Ecosystem::Node,
-                file_type: FileType::Manifest,
-            });
+        // Parse runtime, dev, peer and optional dependencies, locating each
+        // under its section's key
+        let lines = LineIndex::new(content);
+        let sections = [
+            (
+                "dependencies",
+                package_json.dependencies,
+                DependencyType::Runtime,
+            ),
+            (
+                "devDependencies",
+                package_json.dev_dependencies,
+                DependencyType::Development,
+            ),
+            (
+                "peerDependencies",
+                package_json.peer_dependencies,
+                DependencyType::Peer,
+            ),
+            (
+                "optionalDependencies",
+                package_json.optional_dependencies,
+                DependencyType::Optional,
+            ),
+        ];
+        for (section, dependencies, dep_type) in sections {
+            let start = lines
+                .find_json_key(section, 0)
+                .map_or(0, |key| key.end as usize);
+            for (name, version) in dependencies {
+                let span = lines.find_json_key(&name, start);
+                records.push(DependencyRecord {
+                    span,
+                    ..record(&source_file, name, version, dep_type)
+                });
+            }
         }
 
         Ok(records)
@@ -92,3 +131,164 @@ impl Parser for PackageJsonParser {
         "package.json"
     }
 }
+
+/// Record of a declared dependency, under the real name for npm aliases
+fn record(
+    source_file: &Arc<Path>,
+    name: String,
+    version: String,
+    dep_type: DependencyType,
+) -> DependencyRecord {
+    let (name, version, alias) = match split_alias(&version) {
+        Some((real, range)) => (real.to_string(), range.to_string(), Some(name)),
+        None => (name, version, None),
+    };
+    let source_kind = npm_source(&version);
+    DependencyRecord {
+        name: name.into(),
+        version: version.into(),
+        source_file: source_file.clone(),
+        dep_type,
+        ecosystem: Ecosystem::Node,
+        file_type: FileType::Manifest,
+        alias,
+        source_kind,
+        resolved: None,
+        integrity: None,
+        dependencies: Vec::new(),
+        span: None,
+    }
+}
+
+/// Collect npm `overrides` as (package, version) pairs
+///
+/// An object value overrides the dependencies of its key's package and may
+/// override the package itself with a `"."` entry.
+fn npm_overrides(
+    package_json: &PackageJson,
+    overrides: &Map<String, Value>,
+    found: &mut Vec<(String, String)>,
+) {
+    for (key, value) in overrides {
+        let target = override_target(key);
+        let version = match value {
+            Value::String(version) => Some(version.as_str()),
+            Value::Object(nested) => {
+                npm_overrides(package_json, nested, found);
+                nested.get(".").and_then(Value::as_str)
+            }
+            _ => None,
+        };
+        let Some(version) = version else {
+            continue;
+        };
+        // `$name` takes the version of the direct dependency `name`
+        let version = match version.strip_prefix('$') {
+            Some(reference) => {
+                let Some(declared) = [
+                    &package_json.dependencies,
+                    &package_json.dev_dependencies,
+                    &package_json.optional_dependencies,
+                    &package_json.peer_dependencies,
+                ]
+                .into_iter()
+                .find_map(|deps| deps.get(reference)) else {
+                    continue;
+                };
+                declared.as_str()
+            }
+            None => version,
+        };
+        if key != "." {
+            found.push((target.to_string(), version.to_string()));
+        }
+    }
+}
+
+/// Package an override key targets
+///
+/// Keys may name the package's parents (pnpm `a>b`, Yarn `a/b` and `**/b`)
+/// and the range being replaced (`b@<2`); the target is the last package,
+/// without a range.
+fn override_target(key: &str) -> &str {
+    // `>` separates parents unless it belongs to a range (`b@>=2`, `b@1 || >2`)
+    let parent_end = key
+        .rmatch_indices('>')
+        .find(|&(i, _)| {
+            let before = key[..i].chars().next_back();
+            let after = key[i + 1..].chars().next();
+            !matches!(before, None | Some('@' | ' ' | '|'))
+                && after.is_some_and(|c| c != '=' && !c.is_ascii_digit() && c != ' ')
+        })
+        .map_or(0, |(i, _)| i + 1);
+    let key = key[parent_end..].trim();
+    // The last path segment, or the last two for a scoped package
+    let segments: Vec<&str> = key.split('/').collect();
+    let start = match segments.len() {
+        0 | 1 => 0,
+        n if segments[n - 2].starts_with('@') => {
+            segments[..n - 2].iter().map(|s| s.len() + 1).sum()
+        }
+        n => segments[..n - 1].iter().map(|s| s.len() + 1).sum(),
+    };
+    let name = &key[start..];
+    // Skip the `@` of a scope when looking for the range separator
+    match name.char_indices().skip(1).find(|&(_, c)| c == '@') {
+        Some((i, _)) => &name[..i],
+        None => name,
+    }
+}
+
+/// Where an npm dependency comes from, judged by its version specifier
+fn npm_source(spec: &str) -> SourceKind {
+    let spec = spec.trim();
+    if spec.starts_with("workspace:") {
+        return SourceKind::Workspace;
+    }
+    if let Some(path) = spec
+        .strip_prefix("file:")
+        .or_else(|| spec.strip_prefix("link:"))
+    {
+        return SourceKind::Path {
+            path: path.to_string(),
+        };
+    }
+    if spec.starts_with("./") || spec.starts_with("../") || spec.starts_with('/') {
+        return SourceKind::Path {
+            path: spec.to_string(),
+        };
+    }
+
+    const GIT_PREFIXES: [&str; 6] = [
+        "git+",
+        "git://",
+        "github:",
+        "gitlab:",
+        "bitbucket:",
+        "gist:",
+    ];
+    // `user/repo` is GitHub shorthand; version ranges never contain a slash
+    let shorthand = !spec.starts_with('@')
+        && spec
+            .split('#')
+            .next()
+            .is_some_and(|repo| repo.matches('/').count() == 1)
+        && !spec.contains(':')
+        && !spec.contains(char::is_whitespace);
+    if shorthand || GIT_PREFIXES.iter().any(|prefix| spec.starts_with(prefix)) {
+        let (url, rev) = match spec.split_once('#') {
+            Some((url, rev)) => (url, Some(rev.to_string())),
+            None => (spec, None),
+        };
+        return SourceKind::Git {
+            url: url.strip_prefix("git+").unwrap_or(url).to_string(),
+            rev,
+        };
+    }
+    if spec.starts_with("http://") || spec.starts_with("https://") {
+        return SourceKind::Url {
+            url: spec.to_string(),
+        };
+    }
+    SourceKind::Registry
+}
@@ -3,8 +3,10 @@
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
 
-use crate::models::{DependencyRecord, DependencyType, Ecosystem, FileType, ScanError};
+use crate::models::{DependencyRecord, DependencyType, Ecosystem, FileType, ScanError, SourceKind};
+use crate::parsers::span::LineIndex;
 use crate::parsers::Parser;
 
 /// Parser for pyproject.toml manifest files
@@ -21,7 +23,7 @@ struct PyprojectToml {
 #[derive(Debug, Deserialize)]
 struct ProjectSection {
     #[serde(default)]
-    dependencies: Vec<String>,
+    dependencies: Vec<toml::Spanned<String>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -40,22 +42,32 @@ struct PoetrySection {
 
 impl Parser for PyprojectTomlParser {
     fn parse(&self, content: &str, file_path: &Path) -> Result<Vec<DependencyRecord>, ScanError> {
+        let source_file: Arc<Path> = Arc::from(file_path);
         let pyproject: PyprojectToml = toml::from_str(content)
             .map_err(|e| ScanError::toml_error(file_path.to_path_buf(), e))?;
 
+        let lines = LineIndex::new(content);
         let mut records = Vec::new();
 
         // Parse PEP 621 dependencies (project.dependencies)
         if let Some(project) = pyproject.project {
             for dep_spec in project.dependencies {
-                if let Some((name, version)) = parse_pep_508_dependency(&dep_spec) {
+                let span = lines.span(dep_spec.span());
+                let (dep_spec, source_kind) = split_direct_reference(dep_spec.get_ref());
+                if let Some((name, version)) = parse_pep_508_dependency(dep_spec) {
                     records.push(DependencyRecord {
-                        name,
-                        version,
-                        source_file: file_path.to_path_buf(),
+                        name: name.into(),
+                        version: version.into(),
+                        source_file: source_file.clone(),
                         dep_type: DependencyType::Runtime,
                         ecosystem: Ecosystem::Python,
                         file_type: FileType::Manifest,
+                        alias: None,
+                        source_kind,
+                        resolved: None,
+                        integrity: None,
+                        dependencies: Vec::new(),
+                        span: Some(span),
                     });
                 }
             }
@@ -64,6 +76,10 @@ impl Parser for PyprojectTomlParser {
         // Parse Poetry dependencies
         if let Some(tool) = pyproject.tool {
             if let Some(poetry) = tool.poetry {
+                let section = |header: &str| lines.find(header, 0).map(|h| h.end as usize);
+                let runtime = section("[tool.poetry.dependencies]");
+                let dev = section("[tool.poetry.dev-dependencies]");
+
                 // Runtime dependencies
                 for (name, value) in poetry.dependencies {
                     // Skip python itself
@@ -72,26 +88,42 @@ impl Parser for PyprojectTomlParser {
                     }
 
                     let version = extract_poetry_version(&value);
+                    let source_kind = extract_poetry_source(&value);
+                    let span = runtime.and_then(|start| lines.find_toml_key(&name, start));
                     records.push(DependencyRecord {
-                        name,
-                        version,
-                        source_file: file_path.to_path_buf(),
+                        name: name.into(),
+                        version: version.into(),
+                        source_file: source_file.clone(),
                         dep_type: DependencyType::Runtime,
                         ecosystem: Ecosystem::Python,
                         file_type: FileType::Manifest,
+                        alias: None,
+                        source_kind,
+                        resolved: None,
+                        integrity: None,
+                        dependencies: Vec::new(),
+                        span,
                     });
                 }
 
                 // Dev dependencies
                 for (name, value) in poetry.dev_dependencies {
                     let version = extract_poetry_version(&value);
+                    let source_kind = extract_poetry_source(&value);
+                    let span = dev.and_then(|start| lines.find_toml_key(&name, start));
                     records.push(DependencyRecord {
-                        name,
-                        version,
-                        source_file: file_path.to_path_buf(),
+                        name: name.into(),
+                        version: version.into(),
+                        source_file: source_file.clone(),
                         dep_type: DependencyType::Development,
                         ecosystem: Ecosystem::Python,
                         file_type: FileType::Manifest,
+                        alias: None,
+                        source_kind,
+                        resolved: None,
+                        integrity: None,
+                        dependencies: Vec::new(),
+                        span,
                     });
                 }
             }
@@ -114,7 +146,7 @@ impl Parser for PyprojectTomlParser {
 }
 
 /// Parse PEP 508 dependency specification (e.g., "requests>=2.28.0")
-fn parse_pep_508_dependency(spec: &str) -> Option<(String, String)> {
+pub(crate) fn parse_pep_508_dependency(spec: &str) -> Option<(String, String)> {
     // Simple parsing: split on common operators
     let spec = spec.trim();
 
@@ -130,6 +162,65 @@ fn parse_pep_508_dependency(spec: &str) -> Option<(String, String)> {
     Some((spec.to_string(), "*".to_string()))
 }
 
+/// Split a PEP 508 direct reference (`name @ git+https://...@v1.0`) into the
+/// name and the source it points to
+pub(crate) fn split_direct_reference(spec: &str) -> (&str, SourceKind) {
+    let Some((name, reference)) = spec.split_once('@') else {
+        return (spec, SourceKind::Registry);
+    };
+    if name.contains(['<', '>', '=', '!', '~']) {
+        return (spec, SourceKind::Registry);
+    }
+    // Drop environment markers and `#egg=` fragments
+    let url = reference.split(';').next().unwrap_or_default().trim();
+    let url = url.split('#').next().unwrap_or_default();
+
+    let source_kind = if let Some(git) = url.strip_prefix("git+") {
+        // The revision follows an `@` in the path, after any `user@host`
+        let path_start = git.rfind('/').unwrap_or(0);
+        match git[path_start..].find('@') {
+            Some(at) => SourceKind::Git {
+                url: git[..path_start + at].to_string(),
+                rev: Some(git[path_start + at + 1..].to_string()),
+            },
+            None => SourceKind::Git {
+                url: git.to_string(),
+                rev: None,
+            },
+        }
+    } else if let Some(path) = url.strip_prefix("file://") {
+        SourceKind::Path {
+            path: path.to_string(),
+        }
+    } else {
+        SourceKind::Url {
+            url: url.to_string(),
+        }
+    };
+    (name.trim(), source_kind)
+}
+
+/// Extract where a Poetry dependency comes from (`git`, `path` or `url`)
+fn extract_poetry_source(value: &toml::Value) -> SourceKind {
+    let toml::Value::Table(t) = value else {
+        return SourceKind::Registry;
+    };
+    let string = |key: &str| t.get(key).and_then(toml::Value::as_str).map(str::to_string);
+
+    if let Some(url) = string("git") {
+        let rev = string("rev")
+            .or_else(|| string("tag"))
+            .or_else(|| string("branch"));
+        SourceKind::Git { url, rev }
+    } else if let Some(path) = string("path") {
+        SourceKind::Path { path }
+    } else if let Some(url) = string("url") {
+        SourceKind::Url { url }
+    } else {
+        SourceKind::Registry
+    }
+}
+
 /// Extract version from Poetry dependency value
 fn extract_poetry_version(value: &toml::Value) -> String {
     match value {
@@ -1,55 +1,143 @@
-//! Parser for requirements.txt files
+//! Parser for pip requirements files
+//!
+//! Handles `requirements.txt`, pip-tools inputs (`requirements.in`) and
+//! constraint files (`constraints.txt`). A requirements file is either a
+//! hand-written list of ranges or the pinned output of `pip-compile` /
+//! `uv pip compile`, which plays the role of a lockfile; the
+//! [`RequirementsMode`] decides which semantics the records get.
 
+use serde::{Deserialize, Serialize};
 use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
 
-use crate::models::{DependencyRecord, DependencyType, Ecosystem, FileType, ScanError};
+use crate::models::{DependencyRecord, DependencyType, Ecosystem, FileType, ScanError, SourceKind};
+use crate::parsers::span::LineIndex;
 use crate::parsers::Parser;
 
-/// Parser for requirements.txt manifest files
-pub struct RequirementsTxtParser;
+/// Headers written by requirement compilers
+const COMPILED_MARKERS: &[&str] = &["autogenerated by pip-compile", "uv pip compile"];
 
-impl Parser for RequirementsTxtParser {
-    fn parse(&self, content: &str, file_path: &Path) -> Result<Vec<DependencyRecord>, ScanError> {
-        let mut records = Vec::new();
+/// How requirements files map to CAN (manifest) and SHOULD (lockfile)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequirementsMode {
+    /// Lockfile when compiled by pip-tools or uv, or when every requirement
+    /// is pinned with `==`; manifest otherwise
+    #[default]
+    Auto,
+    /// Always a manifest (CAN)
+    Manifest,
+    /// Always a lockfile (SHOULD)
+    Lockfile,
+}
 
-        for line in content.lines() {
-            let line = line.trim();
+impl FromStr for RequirementsMode {
+    type Err = ScanError;
 
-            // Skip empty lines and comments
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(RequirementsMode::Auto),
+            "manifest" => Ok(RequirementsMode::Manifest),
+            "lockfile" => Ok(RequirementsMode::Lockfile),
+            _ => Err(ScanError::UnknownRequirementsMode(s.to_string())),
+        }
+    }
+}
 
-            // Skip -r, -c, --requirement, --constraint flags
-            if line.starts_with("-r ")
-                || line.starts_with("-c ")
-                || line.starts_with("--requirement")
-                || line.starts_with("--constraint")
-            {
-                continue;
-            }
+/// Parser for pip requirements files
+#[derive(Debug, Clone, Copy)]
+pub struct RequirementsTxtParser {
+    filename: &'static str,
+    mode: RequirementsMode,
+}
+
+impl RequirementsTxtParser {
+    /// Parser for `requirements.txt`
+    pub fn new() -> Self {
+        Self {
+            filename: "requirements.txt",
+            mode: RequirementsMode::Auto,
+        }
+    }
 
-            // Skip editable installs and URLs for now
-            if line.starts_with("-e ")
-                || line.starts_with("git+")
-                || line.starts_with("http://")
-                || line.starts_with("https://")
-            {
-                continue;
+    /// Parser for pip-tools `requirements.in` inputs, always manifests
+    pub fn requirements_in() -> Self {
+        Self {
+            filename: "requirements.in",
+            mode: RequirementsMode::Manifest,
+        }
+    }
+
+    /// Parser for `constraints.txt`
+    pub fn constraints() -> Self {
+        Self {
+            filename: "constraints.txt",
+            mode: RequirementsMode::Auto,
+        }
+    }
+
+    /// Set how the file maps to manifest or lockfile records
+    pub fn with_mode(mut self, mode: RequirementsMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Whether a file's records are locked versions
+    fn is_locked(&self, content: &str, requirements: &[(usize, Requirement)]) -> bool {
+        match self.mode {
+            RequirementsMode::Manifest => false,
+            RequirementsMode::Lockfile => true,
+            RequirementsMode::Auto => {
+                COMPILED_MARKERS
+                    .iter()
+                    .any(|marker| content.contains(marker))
+                    || (!requirements.is_empty()
+                        && requirements.iter().all(|(_, r)| r.pinned().is_some()))
             }
+        }
+    }
+}
 
-            // Parse package specification
-            if let Some((name, version)) = parse_requirement_line(line) {
-                records.push(DependencyRecord {
-                    name,
-                    version,
-                    source_file: file_path.to_path_buf(),
+impl Default for RequirementsTxtParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Parser for RequirementsTxtParser {
+    fn parse(&self, content: &str, file_path: &Path) -> Result<Vec<DependencyRecord>, ScanError> {
+        let source_file: Arc<Path> = Arc::from(file_path);
+        let lines = LineIndex::new(content);
+        let requirements: Vec<(usize, Requirement)> = logical_lines(content)
+            .iter()
+            .filter_map(|(number, line)| Some((*number, parse_requirement(line)?)))
+            .collect();
+        let locked = self.is_locked(content, &requirements);
+
+        let records = requirements
+            .into_iter()
+            .map(|(line, requirement)| {
+                let (version, file_type) = match requirement.pinned() {
+                    Some(pinned) if locked => (pinned.to_string(), FileType::Lockfile),
+                    _ => (requirement.version.clone(), FileType::Manifest),
+                };
+                DependencyRecord {
+                    name: requirement.name.into(),
+                    version: version.into(),
+                    source_file: source_file.clone(),
                     dep_type: DependencyType::Runtime,
                     ecosystem: Ecosystem::Python,
-                    file_type: FileType::Manifest,
-                });
-            }
-        }
+                    file_type,
+                    alias: None,
+                    source_kind: SourceKind::Registry,
+                    resolved: None,
+                    integrity: requirement.hash.filter(|_| file_type == FileType::Lockfile),
+                    dependencies: Vec::new(),
+                    span: lines.line(line),
+                }
+            })
+            .collect();
 
         Ok(records)
     }
@@ -63,19 +151,92 @@ impl Parser for RequirementsTxtParser {
     }
 
     fn filename(&self) -> &str {
-        "requirements.txt"
+        self.filename
     }
 }
 
-/// Parse a single requirement line
-fn parse_requirement_line(line: &str) -> Option<(String, String)> {
-    // Remove inline comments first
-    let line = if let Some(pos) = line.find('#') {
-        line[..pos].trim()
-    } else {
-        line.trim()
-    };
+/// A requirement line
+#[derive(Debug)]
+struct Requirement {
+    name: String,
+    version: String,
+    /// First `--hash` of the line, as written
+    hash: Option<String>,
+}
+
+impl Requirement {
+    /// Exact version of an `==` pin (not a `==1.*` prefix match)
+    fn pinned(&self) -> Option<&str> {
+        let version = self.version.strip_prefix("==")?.trim();
+        (!version.is_empty() && !version.contains([',', '*'])).then_some(version)
+    }
+}
+
+/// Lines with comments removed and `\` continuations joined, with the
+/// number of the line each starts on
+fn logical_lines(content: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut first = 1;
+    for (i, line) in content.lines().enumerate() {
+        if current.is_empty() {
+            first = i + 1;
+        }
+        // A `#` starts a comment at the beginning or after whitespace
+        let line = match line.find(" #") {
+            Some(pos) => &line[..pos],
+            None if line.trim_start().starts_with('#') => "",
+            None => line,
+        };
+        match line.trim_end().strip_suffix('\\') {
+            Some(continued) => {
+                current.push_str(continued);
+                current.push(' ');
+            }
+            None => {
+                current.push_str(line);
+                lines.push((first, std::mem::take(&mut current)));
+            }
+        }
+    }
+    if !current.is_empty() {
+        lines.push((first, current));
+    }
+    lines
+}
 
+/// Parse a logical line; options, includes, editable installs and URLs are skipped
+fn parse_requirement(line: &str) -> Option<Requirement> {
+    let line = line.trim();
+    if line.is_empty()
+        || line.starts_with('-')
+        || line.starts_with("git+")
+        || line.starts_with("http://")
+        || line.starts_with("https://")
+    {
+        return None;
+    }
+
+    // Per-requirement options (`--hash=...`) follow the specifier
+    let mut parts = line.split(" --");
+    let spec = parts.next()?;
+    let hash = parts
+        .filter_map(|option| option.trim().strip_prefix("hash"))
+        .map(|value| value.trim_start_matches([' ', '=']).to_string())
+        .next();
+
+    // Environment markers do not change the requirement
+    let spec = spec.split(';').next()?.trim();
+    let (name, version) = parse_requirement_line(spec)?;
+    Some(Requirement {
+        name,
+        version,
+        hash,
+    })
+}
+
+/// Parse a single requirement specifier
+fn parse_requirement_line(line: &str) -> Option<(String, String)> {
     // Parse version specifiers
     for op in &[">=", "<=", "==", "!=", "~=", ">", "<"] {
         if let Some(pos) = line.find(op) {
@@ -105,3 +266,78 @@ fn parse_requirement_line(line: &str) -> Option<(String, String)> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compiled_requirements_are_locked() {
+        let content = "\
+#
+# This file is autogenerated by pip-compile with Python 3.12
+#
+--index-url https://pypi.org/simple
+
+certifi==2024.2.2 \\
+    --hash=sha256:0569859f95fc761b18b45ef421b1290a0f65f147e92a1e5eb3e635f9a5e4e66f
+    # via requests
+requests==2.31.0 ; python_version >= \"3.8\"
+    # via -r requirements.in
+";
+        let parser = RequirementsTxtParser::new();
+        let records = parser
+            .parse(content, Path::new("requirements.txt"))
+            .unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].name.as_str(), "certifi");
+        assert_eq!(records[0].version.as_str(), "2024.2.2");
+        assert_eq!(records[0].file_type, FileType::Lockfile);
+        assert_eq!(
+            records[0].integrity.as_deref(),
+            Some("sha256:0569859f95fc761b18b45ef421b1290a0f65f147e92a1e5eb3e635f9a5e4e66f")
+        );
+        assert_eq!(records[1].version.as_str(), "2.31.0");
+        assert_eq!(records[1].file_type, FileType::Lockfile);
+    }
+
+    #[test]
+    fn test_requirements_modes() {
+        let pinned = "flask==3.0.0\nclick==8.1.7\n";
+        let ranges = "flask>=3.0\nclick==8.1.7\n";
+        let file_types = |parser: RequirementsTxtParser, content: &str| {
+            parser
+                .parse(content, Path::new(parser.filename))
+                .unwrap()
+                .iter()
+                .map(|r| r.file_type)
+                .collect::<Vec<_>>()
+        };
+
+        let auto = RequirementsTxtParser::new();
+        assert_eq!(file_types(auto, pinned), vec![FileType::Lockfile; 2]);
+        assert_eq!(file_types(auto, ranges), vec![FileType::Manifest; 2]);
+        let manifest = auto.with_mode(RequirementsMode::Manifest);
+        assert_eq!(file_types(manifest, pinned), vec![FileType::Manifest; 2]);
+        // Ranges stay declarations even when forced to a lockfile
+        let lockfile = auto.with_mode(RequirementsMode::Lockfile);
+        assert_eq!(
+            file_types(lockfile, ranges),
+            vec![FileType::Manifest, FileType::Lockfile]
+        );
+        assert_eq!(
+            file_types(RequirementsTxtParser::requirements_in(), pinned),
+            vec![FileType::Manifest; 2]
+        );
+        assert_eq!(
+            RequirementsTxtParser::constraints().filename(),
+            "constraints.txt"
+        );
+        assert_eq!(
+            "lockfile".parse::<RequirementsMode>().unwrap(),
+            RequirementsMode::Lockfile
+        );
+        assert!("pinned".parse::<RequirementsMode>().is_err());
+    }
+}
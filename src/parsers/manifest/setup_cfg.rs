@@ -0,0 +1,170 @@
+//! Parser for setuptools setup.cfg files
+//!
+//! Reads `install_requires`, `setup_requires`, `tests_require` from the
+//! `[options]` section and every extra of `[options.extras_require]`. Values
+//! are INI multi-line lists: one requirement per continuation line.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use super::pyproject_toml::{parse_pep_508_dependency, split_direct_reference};
+use crate::models::{DependencyRecord, DependencyType, Ecosystem, FileType, ScanError};
+use crate::parsers::span::LineIndex;
+use crate::parsers::Parser;
+
+/// Parser for setup.cfg manifest files
+pub struct SetupCfgParser;
+
+impl Parser for SetupCfgParser {
+    fn parse(&self, content: &str, file_path: &Path) -> Result<Vec<DependencyRecord>, ScanError> {
+        let source_file: Arc<Path> = Arc::from(file_path);
+        let lines = LineIndex::new(content);
+        let mut records = Vec::new();
+
+        for (section, key, values) in ini_entries(content) {
+            let dep_type = match (section.as_str(), key.as_str()) {
+                ("options", "install_requires") => DependencyType::Runtime,
+                ("options", "setup_requires") => DependencyType::Build,
+                ("options", "tests_require") => DependencyType::Development,
+                ("options.extras_require", _) => DependencyType::Optional,
+                _ => continue,
+            };
+            records.extend(values.iter().filter_map(|spec| {
+                let record = setup_requirement(spec, dep_type, &source_file)?;
+                Some(DependencyRecord {
+                    span: Some(lines.span_of(spec)),
+                    ..record
+                })
+            }));
+        }
+
+        Ok(records)
+    }
+
+    fn ecosystem(&self) -> Ecosystem {
+        Ecosystem::Python
+    }
+
+    fn file_type(&self) -> FileType {
+        FileType::Manifest
+    }
+
+    fn filename(&self) -> &str {
+        "setup.cfg"
+    }
+}
+
+/// Record of a setuptools requirement string, `None` for blank strings and
+/// `file:` directives
+pub(crate) fn setup_requirement(
+    spec: &str,
+    dep_type: DependencyType,
+    source_file: &Arc<Path>,
+) -> Option<DependencyRecord> {
+    let spec = spec.trim();
+    if spec.is_empty() || spec.starts_with("file:") {
+        return None;
+    }
+    let (spec, source_kind) = split_direct_reference(spec);
+    // Environment markers do not change the requirement
+    let spec = if source_kind.is_registry() {
+        spec.split(';').next().unwrap_or_default()
+    } else {
+        spec
+    };
+    let (name, version) = parse_pep_508_dependency(spec)?;
+
+    // Drop extras and the parentheses of `name (>=1.0)`
+    let name = name.split('[').next().unwrap_or_default();
+    let name = name.trim_end_matches(['(', ' ']).trim();
+    let version = version.trim_end_matches(')').trim();
+    if name.is_empty() {
+        return None;
+    }
+
+    Some(DependencyRecord {
+        name: name.into(),
+        version: version.into(),
+        source_file: source_file.clone(),
+        dep_type,
+        ecosystem: Ecosystem::Python,
+        file_type: FileType::Manifest,
+        alias: None,
+        source_kind,
+        resolved: None,
+        integrity: None,
+        dependencies: Vec::new(),
+        span: None,
+    })
+}
+
+/// `(section, key, values)` of an INI file; a value is split into its
+/// first line and indented continuation lines, without comments, borrowed
+/// from the content
+fn ini_entries(content: &str) -> Vec<(String, String, Vec<&str>)> {
+    let mut entries: Vec<(String, String, Vec<&str>)> = Vec::new();
+    let mut section = String::new();
+
+    for raw in content.lines() {
+        let line = match raw.find(" #").or_else(|| raw.find(" ;")) {
+            Some(pos) => &raw[..pos],
+            None => raw,
+        };
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+            continue;
+        }
+
+        let continuation = raw.starts_with([' ', '\t']);
+        if continuation {
+            if let Some((_, _, values)) = entries.last_mut() {
+                values.push(trimmed);
+            }
+        } else if let Some(name) = trimmed
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            section = name.trim().to_string();
+        } else if let Some((key, value)) = trimmed.split_once(['=', ':']) {
+            let value = value.trim();
+            let values = if value.is_empty() {
+                Vec::new()
+            } else {
+                vec![value]
+            };
+            entries.push((section.clone(), key.trim().to_string(), values));
+        }
+    }
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ini_entries() {
+        let content = "\
+[metadata]
+name = app
+
+[options]
+install_requires =
+    requests>=2.28  # HTTP
+    click
+";
+        let entries = ini_entries(content);
+        assert_eq!(
+            entries,
+            vec![
+                ("metadata".to_string(), "name".to_string(), vec!["app"]),
+                (
+                    "options".to_string(),
+                    "install_requires".to_string(),
+                    vec!["requests>=2.28", "click"]
+                ),
+            ]
+        );
+    }
+}
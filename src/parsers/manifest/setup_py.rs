@@ -0,0 +1,243 @@
+//! Parser for setuptools setup.py files
+//!
+//! `setup.py` is a program, so it is never run; the parser reads the
+//! `install_requires`, `setup_requires`, `tests_require` and `extras_require`
+//! arguments when they are literal lists and dicts of strings, or names
+//! assigned such literals elsewhere in the file. Requirements computed at
+//! runtime (read from files, built in loops) are not found.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use super::setup_cfg::setup_requirement;
+use crate::models::{DependencyRecord, DependencyType, Ecosystem, FileType, ScanError};
+use crate::parsers::span::LineIndex;
+use crate::parsers::Parser;
+
+/// Keyword arguments holding requirements, with the type of their entries
+const REQUIREMENT_ARGUMENTS: &[(&str, DependencyType)] = &[
+    ("install_requires", DependencyType::Runtime),
+    ("setup_requires", DependencyType::Build),
+    ("tests_require", DependencyType::Development),
+    ("extras_require", DependencyType::Optional),
+];
+
+/// Parser for setup.py manifest files
+pub struct SetupPyParser;
+
+impl Parser for SetupPyParser {
+    fn parse(&self, content: &str, file_path: &Path) -> Result<Vec<DependencyRecord>, ScanError> {
+        let source_file: Arc<Path> = Arc::from(file_path);
+        let tokens = tokenize(content);
+        let lines = LineIndex::new(content);
+        let mut records = Vec::new();
+
+        for (argument, dep_type) in REQUIREMENT_ARGUMENTS {
+            let Some(specs) = argument_strings(&tokens, argument) else {
+                continue;
+            };
+            records.extend(specs.iter().filter_map(|spec| {
+                let record = setup_requirement(spec, *dep_type, &source_file)?;
+                // Tokens do not keep offsets; the literal is found again
+                Some(DependencyRecord {
+                    span: lines.find(spec, 0),
+                    ..record
+                })
+            }));
+        }
+
+        Ok(records)
+    }
+
+    fn ecosystem(&self) -> Ecosystem {
+        Ecosystem::Python
+    }
+
+    fn file_type(&self) -> FileType {
+        FileType::Manifest
+    }
+
+    fn filename(&self) -> &str {
+        "setup.py"
+    }
+}
+
+/// Python tokens relevant to literal lists and dicts
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Str(String),
+    Ident(String),
+    Punct(char),
+}
+
+/// Split Python source into string literals, identifiers and punctuation,
+/// dropping comments and whitespace
+fn tokenize(source: &str) -> Vec<Token> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '#' {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '"' || c == '\'' {
+            let (literal, end) = string_literal(&chars, i);
+            tokens.push(Token::Str(literal));
+            i = end;
+        } else if c.is_alphanumeric() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let ident: String = chars[start..i].iter().collect();
+            // String prefixes (r"", b"", f"", u"") belong to the literal
+            let is_prefix = ident.len() <= 2
+                && ident.chars().all(|c| "rRbBuUfF".contains(c))
+                && i < chars.len()
+                && (chars[i] == '"' || chars[i] == '\'');
+            if !is_prefix {
+                tokens.push(Token::Ident(ident));
+            }
+        } else {
+            if !c.is_whitespace() {
+                tokens.push(Token::Punct(c));
+            }
+            i += 1;
+        }
+    }
+
+    tokens
+}
+
+/// Read the string literal starting at `start`, returning its content and
+/// the index after it
+fn string_literal(chars: &[char], start: usize) -> (String, usize) {
+    let quote = chars[start];
+    let triple = chars.get(start + 1) == Some(&quote) && chars.get(start + 2) == Some(&quote);
+    let mut i = start + if triple { 3 } else { 1 };
+    let mut literal = String::new();
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\\' && i + 1 < chars.len() {
+            literal.push(chars[i + 1]);
+            i += 2;
+            continue;
+        }
+        if c == quote {
+            if !triple {
+                return (literal, i + 1);
+            }
+            if chars.get(i + 1) == Some(&quote) && chars.get(i + 2) == Some(&quote) {
+                return (literal, i + 3);
+            }
+        }
+        if c == '\n' && !triple {
+            break;
+        }
+        literal.push(c);
+        i += 1;
+    }
+    (literal, i)
+}
+
+/// Strings of the first `name = <literal>` keyword argument or assignment
+/// whose value is a literal or a name assigned one
+fn argument_strings(tokens: &[Token], name: &str) -> Option<Vec<String>> {
+    (0..tokens.len()).find_map(|i| {
+        if tokens[i] != Token::Ident(name.to_string())
+            || tokens.get(i + 1) != Some(&Token::Punct('='))
+        {
+            return None;
+        }
+        match tokens.get(i + 2)? {
+            Token::Ident(variable) => assigned_strings(tokens, variable),
+            _ => literal_strings(tokens, i + 2),
+        }
+    })
+}
+
+/// Strings of the literal assigned to a variable
+fn assigned_strings(tokens: &[Token], variable: &str) -> Option<Vec<String>> {
+    (0..tokens.len()).find_map(|i| {
+        let assigned = tokens[i] == Token::Ident(variable.to_string())
+            && tokens.get(i + 1) == Some(&Token::Punct('='))
+            && (i == 0 || tokens[i - 1] != Token::Punct('.'));
+        if assigned {
+            literal_strings(tokens, i + 2)
+        } else {
+            None
+        }
+    })
+}
+
+/// Strings of the list, tuple or dict literal starting at `start`; dict keys
+/// (strings followed by `:`) are skipped
+fn literal_strings(tokens: &[Token], start: usize) -> Option<Vec<String>> {
+    let Some(Token::Punct('[' | '(' | '{')) = tokens.get(start) else {
+        return None;
+    };
+    let mut strings = Vec::new();
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(start) {
+        match token {
+            Token::Punct('[' | '(' | '{') => depth += 1,
+            Token::Punct(']' | ')' | '}') => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(strings);
+                }
+            }
+            Token::Str(s) if tokens.get(i + 1) != Some(&Token::Punct(':')) => {
+                strings.push(s.clone());
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_argument_strings() {
+        let source = r#"
+from setuptools import setup
+
+REQUIRES = [
+    "requests>=2.28",  # HTTP
+    'click',
+]
+
+setup(
+    name="app",
+    install_requires=REQUIRES,
+    extras_require={
+        "dev": ["pytest>=7", "black"],
+        'docs': ('sphinx',),
+    },
+    entry_points={"console_scripts": ["app=app.cli:main"]},
+)
+"#;
+        let tokens = tokenize(source);
+
+        assert_eq!(
+            argument_strings(&tokens, "install_requires"),
+            Some(vec!["requests>=2.28".to_string(), "click".to_string()])
+        );
+        assert_eq!(
+            argument_strings(&tokens, "extras_require"),
+            Some(vec![
+                "pytest>=7".to_string(),
+                "black".to_string(),
+                "sphinx".to_string()
+            ])
+        );
+        assert_eq!(argument_strings(&tokens, "tests_require"), None);
+    }
+}
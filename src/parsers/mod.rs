@@ -1,15 +1,35 @@
 //! Parser modules for different file formats
+//!
+//! Manifest and lockfile parsers work on the content of a file and never
+//! touch the filesystem, so they also build without the `native` feature
+//! (e.g. for wasm32). Reading installed packages and source files needs it.
 
+#[cfg(feature = "native")]
+use crate::indexer::InstallDirType;
+#[cfg(feature = "native")]
+use crate::models::InstalledPackage;
 use crate::models::{DependencyRecord, Ecosystem, FileType, ScanError};
 use std::path::Path;
 
+#[cfg(feature = "native")]
 pub mod installed;
 pub mod lockfile;
 pub mod manifest;
+pub mod npm_alias;
+pub mod plugin;
+pub mod provider;
 pub mod registry;
+#[cfg(feature = "native")]
+pub mod source;
+mod span;
 
-pub use installed::{NodeModulesParser, SitePackagesParser};
+#[cfg(feature = "native")]
+pub use installed::{NodeModulesParser, OsPackagesParser, SitePackagesParser};
+pub use plugin::ParserPlugin;
+pub use provider::PackageProvider;
 pub use registry::ParserRegistry;
+#[cfg(feature = "native")]
+pub use source::{read_text, BinaryFile, FileContent, FileReader, FileTooLarge};
 
 /// Parser trait for extracting dependencies from files
 pub trait Parser: Send + Sync {
@@ -25,3 +45,16 @@ pub trait Parser: Send + Sync {
     /// Get the filename this parser handles
     fn filename(&self) -> &str;
 }
+
+/// Parser trait for extracting installed packages from installation directories
+#[cfg(feature = "native")]
+pub trait InstalledParser: Send + Sync {
+    /// Parse the packages installed in a directory
+    fn parse_installed(&self, path: &Path) -> Result<Vec<InstalledPackage>, ScanError>;
+
+    /// Get the ecosystem this parser handles
+    fn ecosystem(&self) -> Ecosystem;
+
+    /// Get the kinds of installation directories this parser reads
+    fn dir_types(&self) -> &[InstallDirType];
+}
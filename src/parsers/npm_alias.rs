@@ -0,0 +1,66 @@
+//! npm package aliases
+//!
+//! A dependency declared as `"pkg": "npm:real-pkg@^1.0.0"` installs the
+//! package `real-pkg` under the name `pkg`. Parsers record such dependencies
+//! under the real package name, so security matching runs against the
+//! upstream package, and keep the alias in [`DependencyRecord::alias`].
+//!
+//! [`DependencyRecord::alias`]: crate::models::DependencyRecord::alias
+
+/// Split an alias specifier (`npm:real-pkg@^1.0.0`) into the real package
+/// name and its version range
+///
+/// The range is `*` when the alias names no version (`npm:real-pkg`).
+/// Returns `None` for anything that is not an alias, including Yarn Berry's
+/// `npm:^1.0.0` protocol ranges that name no package.
+pub fn split_alias(spec: &str) -> Option<(&str, &str)> {
+    let target = spec.trim().strip_prefix("npm:")?;
+    // Skip the `@` of a scope when looking for the version separator
+    let separator = target
+        .char_indices()
+        .skip(1)
+        .find(|&(_, c)| c == '@')
+        .map(|(i, _)| i);
+    let (name, range) = match separator {
+        Some(i) => (&target[..i], &target[i + 1..]),
+        None => (target, "*"),
+    };
+    let range = range.strip_prefix("npm:").unwrap_or(range);
+    if !is_package_name(name) {
+        return None;
+    }
+    Some((name, if range.is_empty() { "*" } else { range }))
+}
+
+/// Whether text looks like an npm package name rather than a version range
+fn is_package_name(name: &str) -> bool {
+    let Some(first) = name.chars().next() else {
+        return false;
+    };
+    !first.is_ascii_digit()
+        && !"^~<>=*|".contains(first)
+        && !name.contains(char::is_whitespace)
+        && (!name.starts_with('@') || name.contains('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_alias() {
+        assert_eq!(
+            split_alias("npm:string-width@^4.2.0"),
+            Some(("string-width", "^4.2.0"))
+        );
+        assert_eq!(
+            split_alias("npm:@babel/core@7.24.0"),
+            Some(("@babel/core", "7.24.0"))
+        );
+        assert_eq!(split_alias("npm:lodash"), Some(("lodash", "*")));
+        assert_eq!(split_alias("npm:real@npm:1.0.0"), Some(("real", "1.0.0")));
+        assert_eq!(split_alias("npm:^18.2.0"), None);
+        assert_eq!(split_alias("npm:18.2.0"), None);
+        assert_eq!(split_alias("^4.2.0"), None);
+    }
+}
@@ -0,0 +1,153 @@
+//! Parser plugins
+//!
+//! A [`ParserPlugin`] bundles parsers for additional package files and
+//! registers them with a [`ParserRegistry`] at runtime, so crates embedding
+//! the scanner can support new formats without changing it. Files whose
+//! names a plugin registers are discovered during the walk like the
+//! built-in ones.
+//!
+//! With the `dynamic-plugins` feature, plugins can also be loaded from
+//! shared libraries ([`ParserRegistry::load_plugin`]). A library declares its
+//! plugin with [`declare_parser_plugin!`](crate::declare_parser_plugin); it
+//! must be built with the same compiler and scanner version as the binary
+//! loading it (the feature itself is not needed), since plugins cross the
+//! library boundary as Rust trait objects.
+//!
+//! # Example
+//!
+//! ```rust
+//! use scanner::models::{DependencyRecord, Ecosystem, FileType, ScanError};
+//! use scanner::parsers::{Parser, ParserPlugin, ParserRegistry};
+//! use std::path::Path;
+//! use std::sync::Arc;
+//!
+//! struct BunLockParser;
+//!
+//! impl Parser for BunLockParser {
+//!     fn parse(&self, _content: &str, _path: &Path) -> Result<Vec<DependencyRecord>, ScanError> {
+//!         Ok(Vec::new())
+//!     }
+//!     fn ecosystem(&self) -> Ecosystem {
+//!         Ecosystem::Node
+//!     }
+//!     fn file_type(&self) -> FileType {
+//!         FileType::Lockfile
+//!     }
+//!     fn filename(&self) -> &str {
+//!         "bun.lock"
+//!     }
+//! }
+//!
+//! struct BunPlugin;
+//!
+//! impl ParserPlugin for BunPlugin {
+//!     fn name(&self) -> &str {
+//!         "bun"
+//!     }
+//!     fn register(&self, registry: &mut ParserRegistry) {
+//!         registry.register(Arc::new(BunLockParser));
+//!     }
+//! }
+//!
+//! let mut registry = ParserRegistry::with_default_parsers();
+//! registry.register_plugin(&BunPlugin);
+//! assert!(registry.has_parser("bun.lock"));
+//! ```
+
+use super::ParserRegistry;
+
+/// Version of the interface between the scanner and dynamically loaded
+/// plugins, checked when a library is loaded
+pub const PLUGIN_API_VERSION: u32 = 1;
+
+/// Symbol returning [`PLUGIN_API_VERSION`] in a plugin library
+pub const PLUGIN_API_VERSION_SYMBOL: &[u8] = b"scanner_plugin_api_version";
+
+/// Symbol creating the plugin in a plugin library
+pub const PLUGIN_CONSTRUCTOR_SYMBOL: &[u8] = b"scanner_plugin";
+
+/// A set of parsers registered together
+pub trait ParserPlugin: Send + Sync {
+    /// Name identifying the plugin in logs
+    fn name(&self) -> &str;
+
+    /// Register the plugin's parsers, installed-package parsers and package
+    /// providers; parsers for a filename or installation directory type that
+    /// already has one replace it
+    fn register(&self, registry: &mut ParserRegistry);
+}
+
+/// Export a [`ParserPlugin`] from a shared library for
+/// [`ParserRegistry::load_plugin`]
+///
+/// ```rust,ignore
+/// scanner::declare_parser_plugin!(BunPlugin);
+/// ```
+#[macro_export]
+macro_rules! declare_parser_plugin {
+    ($plugin:expr) => {
+        #[no_mangle]
+        pub extern "C" fn scanner_plugin_api_version() -> u32 {
+            $crate::parsers::plugin::PLUGIN_API_VERSION
+        }
+
+        #[no_mangle]
+        #[allow(improper_ctypes_definitions)]
+        pub extern "C" fn scanner_plugin() -> Box<dyn $crate::parsers::ParserPlugin> {
+            Box::new($plugin)
+        }
+    };
+}
+
+#[cfg(feature = "dynamic-plugins")]
+mod dynamic {
+    use super::*;
+    use crate::models::ScanError;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    impl ParserRegistry {
+        /// Load a plugin from a shared library and register its parsers
+        ///
+        /// The library stays loaded as long as the registry, whose parsers
+        /// run its code.
+        pub fn load_plugin(&mut self, path: &Path) -> Result<String, ScanError> {
+            let error = |message: String| ScanError::Parse {
+                file: path.to_path_buf(),
+                message,
+            };
+
+            // SAFETY: loading a library runs its initializers; plugins are
+            // trusted code chosen by the user
+            let library = unsafe { libloading::Library::new(path) }
+                .map_err(|e| error(format!("cannot load plugin: {}", e)))?;
+            // SAFETY: the symbols have the signatures declare_parser_plugin!
+            // gives them, checked through the API version first
+            let plugin = unsafe {
+                let version = library
+                    .get::<extern "C" fn() -> u32>(PLUGIN_API_VERSION_SYMBOL)
+                    .map_err(|e| error(format!("not a scanner plugin: {}", e)))?;
+                if version() != PLUGIN_API_VERSION {
+                    return Err(error(format!(
+                        "plugin API version {} is not supported (expected {})",
+                        version(),
+                        PLUGIN_API_VERSION
+                    )));
+                }
+                #[allow(improper_ctypes_definitions)]
+                type Constructor = extern "C" fn() -> Box<dyn ParserPlugin>;
+                let constructor = library
+                    .get::<Constructor>(PLUGIN_CONSTRUCTOR_SYMBOL)
+                    .map_err(|e| error(format!("not a scanner plugin: {}", e)))?;
+                constructor()
+            };
+
+            let name = plugin.name().to_string();
+            self.register_plugin(plugin.as_ref());
+            // The plugin object's code lives in the library too
+            drop(plugin);
+            self.keep_library(Arc::new(library));
+            Ok(name)
+        }
+    }
+}
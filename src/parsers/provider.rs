@@ -0,0 +1,50 @@
+//! Package providers
+//!
+//! A [`PackageProvider`] reports packages the scanner cannot find in the
+//! scanned tree, such as the contents of an internal artifact cache or what a
+//! repository manager agent (Nexus, Artifactory) knows to be deployed. Its
+//! packages are classified like installed packages (HAS) or lockfile entries
+//! (SHOULD) and carry the provider's name as their provenance, so they are
+//! matched, aggregated and reported with the packages found on disk.
+//!
+//! # Example
+//!
+//! ```rust
+//! use scanner::models::{Ecosystem, ProvidedPackage, ScanError};
+//! use scanner::parsers::PackageProvider;
+//! # #[cfg(feature = "native")]
+//! use scanner::scan::{ScanOptions, Scanner};
+//! use std::path::Path;
+//! use std::sync::Arc;
+//!
+//! struct ArtifactCache;
+//!
+//! impl PackageProvider for ArtifactCache {
+//!     fn name(&self) -> &str {
+//!         "artifact-cache"
+//!     }
+//!     fn packages(&self, _root: &Path) -> Result<Vec<ProvidedPackage>, ScanError> {
+//!         Ok(vec![ProvidedPackage::installed(
+//!             "lodash",
+//!             "4.17.21",
+//!             Ecosystem::Node,
+//!             "cache://npm/lodash/4.17.21",
+//!         )])
+//!     }
+//! }
+//!
+//! # #[cfg(feature = "native")]
+//! let scanner = Scanner::new(ScanOptions::new()).with_provider(Arc::new(ArtifactCache));
+//! ```
+
+use crate::models::{ProvidedPackage, ScanError};
+use std::path::Path;
+
+/// Source of packages outside the scanned tree
+pub trait PackageProvider: Send + Sync {
+    /// Name recorded as the provenance of the provider's packages
+    fn name(&self) -> &str;
+
+    /// Packages the provider knows of for a scan of `root`
+    fn packages(&self, root: &Path) -> Result<Vec<ProvidedPackage>, ScanError>;
+}
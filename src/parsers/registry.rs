@@ -1,12 +1,45 @@
 //! Parser registry for managing file format parsers
 
-use crate::parsers::Parser;
+#[cfg(feature = "native")]
+use crate::indexer::InstallDirType;
+use crate::models::{Ecosystem, FileType};
+#[cfg(feature = "native")]
+use crate::parsers::installed::{NodeModulesParser, SitePackagesParser};
+use crate::parsers::lockfile::{
+    CargoLockParser, DenoLockParser, PackageLockJsonParser, PnpmLockParser, PoetryLockParser,
+    UvLockParser, YarnLockParser,
+};
+use crate::parsers::manifest::{
+    CargoTomlParser, DenoJsonParser, PackageJsonParser, PyprojectTomlParser, RequirementsTxtParser,
+    SetupCfgParser, SetupPyParser,
+};
+#[cfg(feature = "native")]
+use crate::parsers::InstalledParser;
+use crate::parsers::{PackageProvider, Parser, ParserPlugin};
+use std::any::Any;
 use std::collections::HashMap;
 use std::sync::Arc;
+use tracing::debug;
 
 /// Registry of parsers for different file formats
+///
+/// Parsers are looked up by file name, and installed-package parsers by the
+/// type of installation directory they read. Besides the built-in parsers,
+/// embedding crates can register their own and [`PackageProvider`]s,
+/// directly or bundled as a [`ParserPlugin`]; files with a registered name
+/// are discovered by scans.
 pub struct ParserRegistry {
     parsers: HashMap<String, Arc<dyn Parser>>,
+    #[cfg(feature = "native")]
+    installed: HashMap<InstallDirType, Arc<dyn InstalledParser>>,
+    providers: Vec<Arc<dyn PackageProvider>>,
+    plugins: Vec<String>,
+    /// Shared libraries of loaded plugins; declared last so the parsers
+    /// running their code are dropped first. Kept type-erased so the layout
+    /// does not depend on the `dynamic-plugins` feature, which plugins are
+    /// built without
+    #[cfg_attr(not(feature = "dynamic-plugins"), allow(dead_code))]
+    libraries: Vec<Arc<dyn Any + Send + Sync>>,
 }
 
 impl ParserRegistry {
@@ -14,13 +47,132 @@ impl ParserRegistry {
     pub fn new() -> Self {
         Self {
             parsers: HashMap::new(),
+            #[cfg(feature = "native")]
+            installed: HashMap::new(),
+            providers: Vec::new(),
+            plugins: Vec::new(),
+            libraries: Vec::new(),
         }
     }
 
-    /// Register a parser for a specific filename
-    pub fn register(&mut self, parser: Arc<dyn Parser>) {
+    /// Create a registry with all built-in manifest, lockfile and
+    /// installed-package parsers
+    pub fn with_default_parsers() -> Self {
+        let mut registry = Self::new();
+
+        // Node.js
+        registry.register(Arc::new(PackageJsonParser));
+        registry.register(Arc::new(YarnLockParser));
+        registry.register(Arc::new(PackageLockJsonParser));
+        registry.register(Arc::new(PnpmLockParser));
+
+        // Deno
+        registry.register(Arc::new(DenoJsonParser::new()));
+        registry.register(Arc::new(DenoJsonParser::jsonc()));
+        registry.register(Arc::new(DenoJsonParser::import_map()));
+        registry.register(Arc::new(DenoLockParser));
+
+        // Python
+        registry.register(Arc::new(PyprojectTomlParser));
+        registry.register(Arc::new(RequirementsTxtParser::new()));
+        registry.register(Arc::new(RequirementsTxtParser::requirements_in()));
+        registry.register(Arc::new(RequirementsTxtParser::constraints()));
+        registry.register(Arc::new(SetupCfgParser));
+        registry.register(Arc::new(SetupPyParser));
+        registry.register(Arc::new(PoetryLockParser));
+        registry.register(Arc::new(UvLockParser));
+
+        // Rust
+        registry.register(Arc::new(CargoTomlParser));
+        registry.register(Arc::new(CargoLockParser));
+
+        // Installed packages
+        #[cfg(feature = "native")]
+        {
+            registry.register_installed(Arc::new(NodeModulesParser));
+            registry.register_installed(Arc::new(SitePackagesParser::new()));
+        }
+
+        registry
+    }
+
+    /// Register a parser for the filename it handles, returning the parser
+    /// it replaces
+    pub fn register(&mut self, parser: Arc<dyn Parser>) -> Option<Arc<dyn Parser>> {
         let filename = parser.filename().to_string();
-        self.parsers.insert(filename, parser);
+        self.parsers.insert(filename, parser)
+    }
+
+    /// Register a parser for another filename than its own (e.g.
+    /// `requirements-dev.txt`), returning the parser it replaces
+    pub fn register_as(
+        &mut self,
+        filename: impl Into<String>,
+        parser: Arc<dyn Parser>,
+    ) -> Option<Arc<dyn Parser>> {
+        self.parsers.insert(filename.into(), parser)
+    }
+
+    /// Remove the parser of a filename, so such files are no longer parsed
+    pub fn unregister(&mut self, filename: &str) -> Option<Arc<dyn Parser>> {
+        self.parsers.remove(filename)
+    }
+
+    /// Register an installed-package parser for every installation directory
+    /// type it reads, replacing their previous parsers
+    #[cfg(feature = "native")]
+    pub fn register_installed(&mut self, parser: Arc<dyn InstalledParser>) {
+        for dir_type in parser.dir_types() {
+            self.installed.insert(dir_type.clone(), parser.clone());
+        }
+    }
+
+    /// Get the installed-package parser for an installation directory type
+    #[cfg(feature = "native")]
+    pub fn get_installed_parser(
+        &self,
+        dir_type: &InstallDirType,
+    ) -> Option<Arc<dyn InstalledParser>> {
+        self.installed.get(dir_type).cloned()
+    }
+
+    /// Register a provider of packages outside the scanned tree
+    pub fn register_provider(&mut self, provider: Arc<dyn PackageProvider>) {
+        self.providers.push(provider);
+    }
+
+    /// Registered package providers, in registration order
+    pub fn providers(&self) -> &[Arc<dyn PackageProvider>] {
+        &self.providers
+    }
+
+    /// Register the parsers of a plugin
+    pub fn register_plugin(&mut self, plugin: &dyn ParserPlugin) {
+        let before = self.parsers.len();
+        plugin.register(self);
+        debug!(
+            plugin = plugin.name(),
+            added = self.parsers.len().saturating_sub(before),
+            "Registered parser plugin"
+        );
+        self.plugins.push(plugin.name().to_string());
+    }
+
+    /// Names of the registered plugins, in registration order
+    pub fn plugin_names(&self) -> &[String] {
+        &self.plugins
+    }
+
+    /// Ecosystem and file type of a filename with a registered parser
+    pub fn classify(&self, filename: &str) -> Option<(Ecosystem, FileType)> {
+        let parser = self.parsers.get(filename)?;
+        Some((parser.ecosystem(), parser.file_type()))
+    }
+
+    /// Keep a plugin library loaded for the lifetime of the registry
+    #[cfg(feature = "dynamic-plugins")]
+    pub(crate) fn keep_library(&mut self, library: Arc<dyn Any + Send + Sync>) {
+        self.libraries.push(library);
     }
 
     /// Get a parser for a specific filename
@@ -37,6 +189,92 @@ impl ParserRegistry {
     pub fn has_parser(&self, filename: &str) -> bool {
         self.parsers.contains_key(filename)
     }
+
+    /// Find the parser for a file by its name, or by its content when the
+    /// name is unknown or missing (e.g. when read from stdin)
+    pub fn detect(&self, filename: Option<&str>, content: &str) -> Option<Arc<dyn Parser>> {
+        filename
+            .and_then(|name| self.get_parser(name))
+            .or_else(|| self.get_parser(detect_filename(content)?))
+    }
+}
+
+/// Guess which package file a content is, by the markers each format carries
+pub fn detect_filename(content: &str) -> Option<&'static str> {
+    let trimmed = content.trim_start();
+    let has_line = |prefix: &str| content.lines().any(|line| line.starts_with(prefix));
+
+    if trimmed.starts_with('{') {
+        let json: serde_json::Value = serde_json::from_str(content).ok()?;
+        let object = json.as_object()?;
+        let has = |key: &str| object.contains_key(key);
+        return Some(if has("lockfileVersion") {
+            "package-lock.json"
+        } else if has("version") && !has("name") && (has("remote") || has("specifiers")) {
+            "deno.lock"
+        } else if (has("imports") || has("tasks") || has("importMap"))
+            && !has("dependencies")
+            && !has("devDependencies")
+        {
+            "deno.json"
+        } else {
+            "package.json"
+        });
+    }
+    if has_line("# yarn lockfile v1") || has_line("__metadata:") {
+        return Some("yarn.lock");
+    }
+    if has_line("lockfileVersion:") {
+        return Some("pnpm-lock.yaml");
+    }
+    if has_line("[[package]]") {
+        return Some(
+            if content.contains("@generated by Poetry") || has_line("python-versions") {
+                "poetry.lock"
+            } else if has_line("requires-python") || has_line("source = {") {
+                "uv.lock"
+            } else {
+                "Cargo.lock"
+            },
+        );
+    }
+    if has_line("[project]") || has_line("[tool.poetry") || has_line("[build-system]") {
+        return Some("pyproject.toml");
+    }
+    if has_line("[options]") || has_line("[metadata]") {
+        return Some("setup.cfg");
+    }
+    if content.contains("setup(")
+        && (content.contains("setuptools") || content.contains("distutils"))
+    {
+        return Some("setup.py");
+    }
+    if has_line("[package]") || has_line("[workspace]") || has_line("[dependencies]") {
+        return Some("Cargo.toml");
+    }
+
+    // Requirements files are lines of requirement specifiers, options and
+    // comments; a name followed by `:` or a single `=` is a YAML or TOML key
+    let requirement = |line: &str| {
+        if line.starts_with('-') {
+            return true;
+        }
+        let rest =
+            line.trim_start_matches(|c: char| c.is_ascii_alphanumeric() || "-_.".contains(c));
+        let rest = rest.trim_start();
+        rest.len() < line.len()
+            && !rest.starts_with(':')
+            && (!rest.starts_with('=') || rest.starts_with("=="))
+    };
+    let mut lines = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .peekable();
+    if lines.peek().is_some() && lines.all(requirement) {
+        return Some("requirements.txt");
+    }
+    None
 }
 
 impl Default for ParserRegistry {
@@ -44,3 +282,140 @@ impl Default for ParserRegistry {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_detect_filename() {
+        assert_eq!(
+            detect_filename(r#"{"name": "app", "lockfileVersion": 3}"#),
+            Some("package-lock.json")
+        );
+        assert_eq!(
+            detect_filename(r#"{"name": "app", "dependencies": {}}"#),
+            Some("package.json")
+        );
+        assert_eq!(
+            detect_filename("# yarn lockfile v1\n\nlodash@^4.17.21:\n  version \"4.17.21\"\n"),
+            Some("yarn.lock")
+        );
+        assert_eq!(
+            detect_filename("lockfileVersion: '9.0'\n\nimporters:\n"),
+            Some("pnpm-lock.yaml")
+        );
+        assert_eq!(
+            detect_filename("version = 4\n\n[[package]]\nname = \"serde\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n"),
+            Some("Cargo.lock")
+        );
+        assert_eq!(
+            detect_filename(
+                "version = 1\nrequires-python = \">=3.11\"\n\n[[package]]\nname = \"requests\"\n"
+            ),
+            Some("uv.lock")
+        );
+        assert_eq!(
+            detect_filename("[project]\nname = \"app\"\n"),
+            Some("pyproject.toml")
+        );
+        assert_eq!(
+            detect_filename("[package]\nname = \"app\"\n\n[dependencies]\nserde = \"1\"\n"),
+            Some("Cargo.toml")
+        );
+        assert_eq!(
+            detect_filename("# pinned\nrequests==2.31.0\n-r base.txt\ndjango>=4.2\n"),
+            Some("requirements.txt")
+        );
+        assert_eq!(detect_filename("key: value\n"), None);
+        assert_eq!(detect_filename("name = \"app\"\n"), None);
+
+        // Every fixture is recognized without its name
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        for ecosystem in ["node", "python", "rust"] {
+            for entry in std::fs::read_dir(fixtures.join(ecosystem)).unwrap() {
+                let path = entry.unwrap().path();
+                let content = std::fs::read_to_string(&path).unwrap();
+                let name = path.file_name().unwrap().to_str().unwrap();
+                assert_eq!(detect_filename(&content), Some(name), "{}", name);
+            }
+        }
+
+        let registry = ParserRegistry::with_default_parsers();
+        let parser = registry.detect(None, "flask==3.0.0\n").unwrap();
+        assert_eq!(parser.filename(), "requirements.txt");
+        let parser = registry.detect(Some("yarn.lock"), "").unwrap();
+        assert_eq!(parser.filename(), "yarn.lock");
+    }
+
+    #[test]
+    fn test_register_as_and_unregister() {
+        let mut registry = ParserRegistry::with_default_parsers();
+        assert_eq!(registry.classify("requirements-dev.txt"), None);
+
+        let replaced = registry.register_as(
+            "requirements-dev.txt",
+            Arc::new(RequirementsTxtParser::new()),
+        );
+        assert!(replaced.is_none());
+        assert_eq!(
+            registry.classify("requirements-dev.txt"),
+            Some((Ecosystem::Python, FileType::Manifest))
+        );
+
+        assert!(registry.unregister("yarn.lock").is_some());
+        assert!(!registry.has_parser("yarn.lock"));
+        assert!(registry.register(Arc::new(YarnLockParser)).is_none());
+        assert!(registry.register(Arc::new(YarnLockParser)).is_some());
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn test_installed_parsers() {
+        let mut registry = ParserRegistry::with_default_parsers();
+        let ecosystem = |registry: &ParserRegistry, dir_type| {
+            registry
+                .get_installed_parser(&dir_type)
+                .map(|parser| parser.ecosystem())
+        };
+        assert_eq!(
+            ecosystem(&registry, InstallDirType::NodeModules),
+            Some(Ecosystem::Node)
+        );
+        for dir_type in [
+            InstallDirType::SitePackages,
+            InstallDirType::DistPackages,
+            InstallDirType::VirtualEnv,
+        ] {
+            assert_eq!(ecosystem(&registry, dir_type), Some(Ecosystem::Python));
+        }
+
+        // A parser replaces the previous one for the directory types it reads
+        struct NoPackages;
+        impl InstalledParser for NoPackages {
+            fn parse_installed(
+                &self,
+                _path: &Path,
+            ) -> Result<Vec<crate::models::InstalledPackage>, crate::models::ScanError>
+            {
+                Ok(Vec::new())
+            }
+            fn ecosystem(&self) -> Ecosystem {
+                Ecosystem::Rust
+            }
+            fn dir_types(&self) -> &[InstallDirType] {
+                &[InstallDirType::NodeModules]
+            }
+        }
+        registry.register_installed(Arc::new(NoPackages));
+        assert_eq!(
+            ecosystem(&registry, InstallDirType::NodeModules),
+            Some(Ecosystem::Rust)
+        );
+        assert_eq!(
+            ecosystem(&registry, InstallDirType::SitePackages),
+            Some(Ecosystem::Python)
+        );
+    }
+}
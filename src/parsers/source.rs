@@ -0,0 +1,283 @@
+//! Reading package files, capped in size
+//!
+//! The parsers need the whole text of a manifest or lockfile, so the
+//! [`FileReader`] reads each file into an owned buffer, bounded by its size
+//! when it was opened: a file that is rewritten or truncated while it is
+//! scanned (by an `npm install` on a production host) yields stale text
+//! rather than a crash.
+//!
+//! With a size limit, larger files are not read at all: they fail with a
+//! [`FileTooLarge`] error, which the scanner reports as skipped. Cutting them
+//! instead would turn most lockfiles (JSON, TOML) into parse errors and the
+//! rest into silently partial results.
+//!
+//! Files that are not valid UTF-8 (vendored lockfiles with latin-1 comments)
+//! are decoded lossily with a warning. Files with a NUL byte in their first
+//! [`BINARY_SNIFF_LEN`] bytes are binary and fail with a [`BinaryFile`]
+//! error, which the scanner skips rather than reports.
+//!
+//! A reader with an [`IoThrottle`] waits for its turn before each read.
+//! [`read_text`] takes the throttle of the current thread, so the
+//! installed-package parsers reading through it are throttled too.
+
+use crate::throttle::IoThrottle;
+use std::fs::File;
+use std::io::{self, Read};
+use std::ops::Deref;
+use std::path::Path;
+use tracing::warn;
+
+/// Number of leading bytes searched for a NUL byte to detect binary files
+pub const BINARY_SNIFF_LEN: usize = 8000;
+
+/// Error of reading a binary file as text
+#[derive(Debug, thiserror::Error)]
+#[error("binary file")]
+pub struct BinaryFile;
+
+impl BinaryFile {
+    /// Check if a read failed because the file is binary
+    pub fn is(error: &io::Error) -> bool {
+        error
+            .get_ref()
+            .is_some_and(|inner| inner.is::<BinaryFile>())
+    }
+}
+
+/// Error of reading a file larger than the size limit
+#[derive(Debug, thiserror::Error)]
+#[error("larger than the {limit}-byte limit")]
+pub struct FileTooLarge {
+    /// The size limit, in bytes
+    pub limit: u64,
+}
+
+impl FileTooLarge {
+    /// The size limit a read exceeded, if it failed because the file is too
+    /// large
+    pub fn limit(error: &io::Error) -> Option<u64> {
+        error
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<FileTooLarge>())
+            .map(|e| e.limit)
+    }
+}
+
+/// Read a whole file as text, decoding invalid UTF-8 lossily
+pub fn read_text(path: impl AsRef<Path>) -> io::Result<String> {
+    let mut reader = FileReader::new();
+    if let Some(throttle) = IoThrottle::current() {
+        reader = reader.with_io_throttle(throttle);
+    }
+    reader.read(path.as_ref()).map(FileContent::into_string)
+}
+
+/// Content of a package file
+pub struct FileContent {
+    text: String,
+    lossy: bool,
+}
+
+impl FileContent {
+    /// The text of the file
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Whether invalid UTF-8 was replaced while decoding the file
+    pub fn is_lossy(&self) -> bool {
+        self.lossy
+    }
+
+    /// The text of the file as an owned string
+    pub fn into_string(self) -> String {
+        self.text
+    }
+}
+
+impl Deref for FileContent {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+/// Reader for manifests and lockfiles
+#[derive(Debug, Clone, Default)]
+pub struct FileReader {
+    max_file_size: Option<u64>,
+    io_throttle: Option<IoThrottle>,
+}
+
+impl FileReader {
+    /// Create a reader without a size limit
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refuse files larger than this many bytes
+    pub fn with_max_file_size(mut self, max_file_size: u64) -> Self {
+        self.max_file_size = Some(max_file_size);
+        self
+    }
+
+    /// Hold reads to the rate of a throttle
+    pub fn with_io_throttle(mut self, io_throttle: IoThrottle) -> Self {
+        self.io_throttle = Some(io_throttle);
+        self
+    }
+
+    /// Read a whole file as text, failing if it exceeds the size limit
+    pub fn read(&self, path: &Path) -> io::Result<FileContent> {
+        let file = File::open(path)?;
+        let size = file.metadata()?.len();
+        self.check_size(size)?;
+        if let Some(throttle) = &self.io_throttle {
+            throttle.acquire(1, size);
+        }
+
+        // Bounded by the size at open even if the file grows while it is read
+        let mut bytes = Vec::with_capacity(size as usize);
+        file.take(size).read_to_end(&mut bytes)?;
+        decode_owned(path, bytes)
+    }
+
+    /// Decode the bytes of a file read from elsewhere (a git object, an
+    /// archive member) as text, failing if they exceed the size limit
+    pub fn decode(&self, path: &Path, bytes: Vec<u8>) -> io::Result<FileContent> {
+        self.check_size(bytes.len() as u64)?;
+        decode_owned(path, bytes)
+    }
+
+    /// Fail with [`FileTooLarge`] if a file of `size` bytes exceeds the limit
+    fn check_size(&self, size: u64) -> io::Result<()> {
+        match self.max_file_size {
+            Some(limit) if size > limit => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                FileTooLarge { limit },
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Decode bytes onto the heap; lossily, with a warning, when they are not
+/// valid UTF-8
+fn decode_owned(path: &Path, bytes: Vec<u8>) -> io::Result<FileContent> {
+    check_text(&bytes)?;
+    let (text, lossy) = match String::from_utf8(bytes) {
+        Ok(text) => (text, false),
+        Err(e) => (String::from_utf8_lossy(e.as_bytes()).into_owned(), true),
+    };
+    if lossy {
+        warn!(
+            file = %path.display(),
+            "File is not valid UTF-8, replacing the invalid bytes"
+        );
+    }
+    Ok(FileContent { text, lossy })
+}
+
+/// Fail on binary content: a NUL byte among the first bytes
+fn check_text(bytes: &[u8]) -> io::Result<()> {
+    let head = &bytes[..bytes.len().min(BINARY_SNIFF_LEN)];
+    if head.contains(&0) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, BinaryFile));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    fn file(content: &str) -> NamedTempFile {
+        let file = NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), content).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_read_whole_file() {
+        let temp = file("flask==3.0.0\nrequests==2.31.0\n");
+
+        let content = FileReader::new().read(temp.path()).unwrap();
+        assert_eq!(&*content, "flask==3.0.0\nrequests==2.31.0\n");
+    }
+
+    #[test]
+    fn test_read_too_large() {
+        let temp = file("flask==3.0.0\nrequests==2.31.0\n");
+
+        let err = FileReader::new()
+            .with_max_file_size(20)
+            .read(temp.path())
+            .err()
+            .unwrap();
+        assert_eq!(FileTooLarge::limit(&err), Some(20));
+        assert_eq!(err.to_string(), "larger than the 20-byte limit");
+
+        let content = FileReader::new()
+            .with_max_file_size(30)
+            .read(temp.path())
+            .unwrap();
+        assert_eq!(&*content, "flask==3.0.0\nrequests==2.31.0\n");
+        assert_eq!(
+            FileTooLarge::limit(&io::Error::from(io::ErrorKind::InvalidData)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_read_invalid_utf8() {
+        let temp = NamedTempFile::new().unwrap();
+        // `# caf\xe9` in latin-1
+        std::fs::write(temp.path(), b"# caf\xe9\nflask==3.0.0\n").unwrap();
+
+        let content = FileReader::new().read(temp.path()).unwrap();
+        assert_eq!(&*content, "# caf\u{fffd}\nflask==3.0.0\n");
+        assert!(content.is_lossy());
+        assert_eq!(
+            read_text(temp.path()).unwrap(),
+            "# caf\u{fffd}\nflask==3.0.0\n"
+        );
+    }
+
+    #[test]
+    fn test_decode_bytes() {
+        let path = Path::new("requirements.txt");
+        let bytes = b"flask==3.0.0\nrequests==2.31.0\n".to_vec();
+
+        let content = FileReader::new().decode(path, bytes.clone()).unwrap();
+        assert_eq!(&*content, "flask==3.0.0\nrequests==2.31.0\n");
+
+        let err = FileReader::new()
+            .with_max_file_size(20)
+            .decode(path, bytes)
+            .err()
+            .unwrap();
+        assert_eq!(FileTooLarge::limit(&err), Some(20));
+
+        let err = FileReader::new()
+            .decode(path, b"\x00\x01".to_vec())
+            .err()
+            .unwrap();
+        assert!(BinaryFile::is(&err));
+    }
+
+    #[test]
+    fn test_read_binary_file() {
+        let temp = NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), b"\x7fELF\x02\x01\x01\x00\x00").unwrap();
+
+        let err = FileReader::new().read(temp.path()).err().unwrap();
+        assert!(BinaryFile::is(&err));
+        let err = read_text(temp.path()).unwrap_err();
+        assert!(BinaryFile::is(&err));
+        assert!(!BinaryFile::is(&io::Error::from(
+            io::ErrorKind::InvalidData
+        )));
+    }
+}
@@ -0,0 +1,205 @@
+//! Source locations of parsed declarations
+//!
+//! Parsers record where each dependency is declared so findings can point at
+//! the exact manifest or lockfile line. Line-based and regex parsers know the
+//! offsets they matched; parsers going through serde locate the declaration
+//! again in the content with the lookups of [`LineIndex`].
+
+use crate::models::SourceSpan;
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Byte offset to line mapping of a file's content
+pub(crate) struct LineIndex<'a> {
+    content: &'a str,
+    /// Offset of the first byte of each line
+    starts: Vec<usize>,
+    /// Offsets of the JSON object keys, by their text between the quotes;
+    /// built on the first key lookup
+    json_keys: OnceCell<HashMap<&'a str, Vec<usize>>>,
+}
+
+impl<'a> LineIndex<'a> {
+    /// Index the lines of a content
+    pub fn new(content: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            content,
+            starts,
+            json_keys: OnceCell::new(),
+        }
+    }
+
+    /// Span of a byte range of the content
+    pub fn span(&self, range: Range<usize>) -> SourceSpan {
+        let line = self.starts.partition_point(|&start| start <= range.start);
+        SourceSpan {
+            start: clamp(range.start),
+            end: clamp(range.end),
+            line: clamp(line),
+        }
+    }
+
+    /// Span of a slice borrowed from the content
+    pub fn span_of(&self, part: &str) -> SourceSpan {
+        let start = (part.as_ptr() as usize).saturating_sub(self.content.as_ptr() as usize);
+        self.span(start..start + part.len())
+    }
+
+    /// Span of line `number` (1-based) without its line break
+    pub fn line(&self, number: usize) -> Option<SourceSpan> {
+        let start = *self.starts.get(number.checked_sub(1)?)?;
+        let line = self.content[start..].lines().next().unwrap_or_default();
+        Some(self.span(start..start + line.len()))
+    }
+
+    /// Span of the first occurrence of `needle` at or after `from`
+    pub fn find(&self, needle: &str, from: usize) -> Option<SourceSpan> {
+        let start = from + self.content.get(from..)?.find(needle)?;
+        Some(self.span(start..start + needle.len()))
+    }
+
+    /// Span of the first JSON object key `key` at or after `from`
+    pub fn find_json_key(&self, key: &str, from: usize) -> Option<SourceSpan> {
+        self.json_keys(key, from).into_iter().next()
+    }
+
+    /// Spans of the JSON object keys `key` at or after `from`, in order
+    fn json_keys(&self, key: &str, from: usize) -> Vec<SourceSpan> {
+        // Keys are indexed as written, so look up the escaped form
+        let quoted = serde_json::to_string(key).unwrap_or_default();
+        let escaped = quoted
+            .get(1..quoted.len().saturating_sub(1))
+            .unwrap_or_default();
+        let offsets = self
+            .json_keys
+            .get_or_init(|| index_json_keys(self.content))
+            .get(escaped)
+            .map_or(&[][..], Vec::as_slice);
+        let first = offsets.partition_point(|&offset| offset < from);
+        offsets[first..]
+            .iter()
+            .map(|&offset| self.span(offset..offset + quoted.len()))
+            .collect()
+    }
+
+    /// Span of the first JSON object key `key` at or after `from` whose
+    /// object value has the given `version` field (npm v1 lockfile entries)
+    pub fn find_json_entry(&self, key: &str, version: &str, from: usize) -> Option<SourceSpan> {
+        self.json_keys(key, from).into_iter().find(|span| {
+            let rest = &self.content[span.end as usize..];
+            let Some(object) = rest.trim_start()[1..].trim_start().strip_prefix('{') else {
+                return false;
+            };
+            let fields = &object[..object.find('}').unwrap_or(object.len())];
+            fields.split_once("\"version\"").is_some_and(|(_, value)| {
+                let value = value.trim_start().trim_start_matches(':').trim_start();
+                value
+                    .strip_prefix('"')
+                    .and_then(|value| value.strip_prefix(version))
+                    .is_some_and(|rest| rest.starts_with('"'))
+            })
+        })
+    }
+
+    /// Span of the first TOML key `key` (bare or quoted, possibly dotted as
+    /// in `serde.version`) or `[<table>.key]` header at or after `from`
+    pub fn find_toml_key(&self, key: &str, from: usize) -> Option<SourceSpan> {
+        let first = self.starts.partition_point(|&start| start < from);
+        self.starts[first..].iter().find_map(|&start| {
+            let line = self.content[start..].lines().next().unwrap_or_default();
+            let text = line.trim_start();
+            let offset = start + line.len() - text.len();
+            let found = if text.starts_with('[') {
+                text.find(&format!(".{}]", key)).map(|i| i + 1)
+            } else {
+                let unquoted = text.trim_start_matches(['"', '\'']);
+                let quote = text.len() - unquoted.len();
+                let after = unquoted.strip_prefix(key)?.trim_start_matches(['"', '\'']);
+                after.trim_start().starts_with(['=', '.']).then_some(quote)
+            }?;
+            Some(self.span(offset + found..offset + found + key.len()))
+        })
+    }
+}
+
+/// Offsets of the string literals followed by `:`, by their raw text
+fn index_json_keys(content: &str) -> HashMap<&str, Vec<usize>> {
+    let bytes = content.as_bytes();
+    let mut keys: HashMap<&str, Vec<usize>> = HashMap::new();
+    let mut i = 0;
+    while let Some(open) = bytes[i..].iter().position(|&b| b == b'"') {
+        let start = i + open;
+        let mut end = start + 1;
+        while end < bytes.len() && bytes[end] != b'"' {
+            end += if bytes[end] == b'\\' { 2 } else { 1 };
+        }
+        if end >= bytes.len() {
+            break;
+        }
+        let is_key = content[end + 1..].trim_start().starts_with(':');
+        if is_key {
+            keys.entry(&content[start + 1..end])
+                .or_default()
+                .push(start);
+        }
+        i = end + 1;
+    }
+    keys
+}
+
+/// Offsets and line numbers beyond `u32` are saturated
+fn clamp(value: usize) -> u32 {
+    u32::try_from(value).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spans() {
+        let content =
+            "{\n  \"name\": \"app\",\n  \"dependencies\": {\n    \"react\": \"^18.0.0\"\n  }\n}\n";
+        let index = LineIndex::new(content);
+
+        let react = index.find_json_key("react", 0).unwrap();
+        assert_eq!(react.line, 4);
+        assert_eq!(
+            &content[react.start as usize..react.end as usize],
+            "\"react\""
+        );
+        // Values are not keys
+        assert_eq!(index.find_json_key("app", 0), None);
+
+        let line = index.line(2).unwrap();
+        assert_eq!(
+            &content[line.start as usize..line.end as usize],
+            "  \"name\": \"app\","
+        );
+        assert_eq!(index.line(99), None);
+        assert_eq!(index.span_of(&content[content.len() - 2..]).line, 6);
+    }
+
+    #[test]
+    fn test_find_toml_key() {
+        let content = "[package]\nname = \"serde-app\"\n\n[dependencies]\nserde = \"1\"\n\"tokio\" = { version = \"1\" }\nrand.version = \"0.8\"\n\n[dev-dependencies.proptest]\nversion = \"1\"\n";
+        let index = LineIndex::new(content);
+        let section = content.find("[dependencies]").unwrap();
+
+        assert_eq!(index.find_toml_key("serde", section).unwrap().line, 5);
+        assert_eq!(index.find_toml_key("tokio", section).unwrap().line, 6);
+        assert_eq!(index.find_toml_key("rand", section).unwrap().line, 7);
+        let proptest = index.find_toml_key("proptest", section).unwrap();
+        assert_eq!(proptest.line, 9);
+        assert_eq!(
+            &content[proptest.start as usize..proptest.end as usize],
+            "proptest"
+        );
+        // `name` precedes the section
+        assert_eq!(index.find_toml_key("name", section), None);
+    }
+}
@@ -0,0 +1,453 @@
+//! Progress reporting for long-running scans
+//!
+//! A scan runs through a fixed sequence of [`ScanPhase`]s: discovery, then
+//! the parsing and analysis phases once for each ecosystem found, between
+//! [`ProgressReporter::ecosystem_started`] and
+//! [`ProgressReporter::ecosystem_finished`]. Embedders receive
+//! progress through the [`ProgressReporter`] trait; every method has a no-op
+//! default so implementations only override what they need. The CLI uses
+//! [`TerminalProgress`], which draws a progress bar with an ETA on stderr.
+//!
+//! # Example
+//!
+//! ```rust
+//! use scanner::progress::{ProgressReporter, ScanPhase};
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//!
+//! #[derive(Default)]
+//! struct FileCounter(AtomicUsize);
+//!
+//! impl ProgressReporter for FileCounter {
+//!     fn advance(&self, phase: ScanPhase, count: usize) {
+//!         if phase == ScanPhase::Discovery {
+//!             self.0.fetch_add(count, Ordering::Relaxed);
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::models::{Ecosystem, EcosystemStatus, EcosystemSummary};
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A phase of a scan, in execution order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScanPhase {
+    /// Walking the filesystem for package files and installation directories
+    Discovery,
+    /// Parsing manifests and lockfiles
+    DeclaredParsing,
+    /// Parsing node_modules, site-packages and virtual environments
+    InstalledParsing,
+    /// Classifying dependencies and linking them to applications
+    Analysis,
+    /// Writing results
+    Output,
+}
+
+impl ScanPhase {
+    /// Human-readable label for the phase
+    pub fn label(&self) -> &'static str {
+        match self {
+            ScanPhase::Discovery => "Discovering files",
+            ScanPhase::DeclaredParsing => "Parsing package files",
+            ScanPhase::InstalledParsing => "Parsing install directories",
+            ScanPhase::Analysis => "Analyzing dependencies",
+            ScanPhase::Output => "Writing output",
+        }
+    }
+
+    /// Stable machine-readable key for the phase
+    pub fn key(&self) -> &'static str {
+        match self {
+            ScanPhase::Discovery => "discovery",
+            ScanPhase::DeclaredParsing => "declared_parsing",
+            ScanPhase::InstalledParsing => "installed_parsing",
+            ScanPhase::Analysis => "analysis",
+            ScanPhase::Output => "output",
+        }
+    }
+}
+
+impl fmt::Display for ScanPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Receiver for scan progress events
+///
+/// Methods may be called concurrently from worker threads.
+pub trait ProgressReporter: Send + Sync {
+    /// A phase started; `total` is the number of work items when known up front
+    fn phase_started(&self, _phase: ScanPhase, _total: Option<usize>) {}
+
+    /// `count` more work items of the phase were processed
+    fn advance(&self, _phase: ScanPhase, _count: usize) {}
+
+    /// A phase finished after processing `items` work items
+    fn phase_finished(&self, _phase: ScanPhase, _items: usize, _elapsed: Duration) {}
+
+    /// The parsing and analysis of an ecosystem started
+    fn ecosystem_started(&self, _ecosystem: Ecosystem) {}
+
+    /// An ecosystem was scanned, skipped or failed
+    fn ecosystem_finished(&self, _ecosystem: Ecosystem, _summary: &EcosystemSummary) {}
+}
+
+/// Reporter that ignores all progress events
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl ProgressReporter for NoProgress {}
+
+/// Duration and item count of a completed phase
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhaseTiming {
+    /// The phase
+    pub phase: ScanPhase,
+    /// Number of work items processed
+    pub items: usize,
+    /// Wall-clock time spent in the phase
+    pub elapsed: Duration,
+}
+
+/// Timings of all completed phases, in execution order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PhaseTimings {
+    timings: Vec<PhaseTiming>,
+}
+
+impl PhaseTimings {
+    /// Create an empty set of timings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a completed phase
+    pub fn record(&mut self, phase: ScanPhase, items: usize, elapsed: Duration) {
+        self.timings.push(PhaseTiming {
+            phase,
+            items,
+            elapsed,
+        });
+    }
+
+    /// Get the timing of a phase, if it ran
+    pub fn get(&self, phase: ScanPhase) -> Option<&PhaseTiming> {
+        self.timings.iter().find(|t| t.phase == phase)
+    }
+
+    /// Iterate over completed phases
+    pub fn iter(&self) -> impl Iterator<Item = &PhaseTiming> {
+        self.timings.iter()
+    }
+
+    /// Combine with the timings of a scan that ran concurrently: items add
+    /// up and each phase takes as long as the slower of the two
+    pub fn merge(&mut self, other: &PhaseTimings) {
+        for timing in other.iter() {
+            match self.timings.iter_mut().find(|t| t.phase == timing.phase) {
+                Some(existing) => {
+                    existing.items += timing.items;
+                    existing.elapsed = existing.elapsed.max(timing.elapsed);
+                }
+                None => self.timings.push(timing.clone()),
+            }
+        }
+    }
+
+    /// Combine with the timings of work that ran afterwards: items and
+    /// times add up
+    pub fn add(&mut self, other: &PhaseTimings) {
+        for timing in other.iter() {
+            match self.timings.iter_mut().find(|t| t.phase == timing.phase) {
+                Some(existing) => {
+                    existing.items += timing.items;
+                    existing.elapsed += timing.elapsed;
+                }
+                None => self.timings.push(timing.clone()),
+            }
+        }
+    }
+
+    /// Total time across all phases
+    pub fn total(&self) -> Duration {
+        self.timings.iter().map(|t| t.elapsed).sum()
+    }
+}
+
+/// Tracks one phase: notifies the reporter and records the timing when done
+pub(crate) struct PhaseGuard<'a> {
+    reporter: &'a dyn ProgressReporter,
+    phase: ScanPhase,
+    started: Instant,
+}
+
+impl<'a> PhaseGuard<'a> {
+    /// Start a phase
+    pub(crate) fn start(
+        reporter: &'a dyn ProgressReporter,
+        phase: ScanPhase,
+        total: Option<usize>,
+    ) -> Self {
+        reporter.phase_started(phase, total);
+        Self {
+            reporter,
+            phase,
+            started: Instant::now(),
+        }
+    }
+
+    /// Finish the phase after processing `items` work items
+    pub(crate) fn finish(self, items: usize, timings: &mut PhaseTimings) {
+        let elapsed = self.started.elapsed();
+        self.reporter.phase_finished(self.phase, items, elapsed);
+        timings.record(self.phase, items, elapsed);
+    }
+}
+
+/// Minimum time between redraws of the progress bar
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Width of the progress bar in characters
+const BAR_WIDTH: usize = 30;
+
+/// Progress bar drawn on stderr
+///
+/// Phases with a known total show a bar, percentage and ETA; discovery shows a
+/// running count. Each finished phase leaves a summary line with its timing.
+#[derive(Debug)]
+pub struct TerminalProgress {
+    done: AtomicUsize,
+    state: Mutex<BarState>,
+}
+
+#[derive(Debug)]
+struct BarState {
+    total: Option<usize>,
+    phase: Option<ScanPhase>,
+    ecosystem: Option<Ecosystem>,
+    started: Instant,
+    last_draw: Option<Instant>,
+}
+
+impl TerminalProgress {
+    /// Create a terminal progress bar
+    pub fn new() -> Self {
+        Self {
+            done: AtomicUsize::new(0),
+            state: Mutex::new(BarState {
+                total: None,
+                phase: None,
+                ecosystem: None,
+                started: Instant::now(),
+                last_draw: None,
+            }),
+        }
+    }
+
+    /// Redraw the current line
+    fn draw(&self, state: &mut BarState) {
+        let Some(phase) = state.phase else {
+            return;
+        };
+        let line = render_line(
+            &phase_label(phase, state.ecosystem),
+            self.done.load(Ordering::Relaxed),
+            state.total,
+            state.started.elapsed(),
+        );
+        let mut stderr = io::stderr().lock();
+        let _ = write!(stderr, "\r\x1b[2K{}", line);
+        let _ = stderr.flush();
+        state.last_draw = Some(Instant::now());
+    }
+}
+
+impl Default for TerminalProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressReporter for TerminalProgress {
+    fn phase_started(&self, phase: ScanPhase, total: Option<usize>) {
+        let mut state = self.state.lock().unwrap();
+        self.done.store(0, Ordering::Relaxed);
+        state.phase = Some(phase);
+        state.total = total;
+        state.started = Instant::now();
+        self.draw(&mut state);
+    }
+
+    fn advance(&self, _phase: ScanPhase, count: usize) {
+        self.done.fetch_add(count, Ordering::Relaxed);
+
+        // Never block workers on the bar; a skipped redraw is picked up by the next one
+        if let Ok(mut state) = self.state.try_lock() {
+            if state
+                .last_draw
+                .is_none_or(|last| last.elapsed() >= REDRAW_INTERVAL)
+            {
+                self.draw(&mut state);
+            }
+        }
+    }
+
+    fn phase_finished(&self, phase: ScanPhase, items: usize, elapsed: Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.phase = None;
+        eprintln!(
+            "\r\x1b[2K{:<28} {:>8} items  {}",
+            phase_label(phase, state.ecosystem),
+            items,
+            format_duration(elapsed)
+        );
+    }
+
+    fn ecosystem_started(&self, ecosystem: Ecosystem) {
+        self.state.lock().unwrap().ecosystem = Some(ecosystem);
+    }
+
+    fn ecosystem_finished(&self, ecosystem: Ecosystem, summary: &EcosystemSummary) {
+        let mut state = self.state.lock().unwrap();
+        state.ecosystem = None;
+        if summary.status != EcosystemStatus::Completed {
+            eprintln!(
+                "\r\x1b[2K{:<28} {}",
+                format!("Scanning {}", ecosystem),
+                summary.status
+            );
+        }
+    }
+}
+
+/// Label of a phase, with the ecosystem it runs for
+fn phase_label(phase: ScanPhase, ecosystem: Option<Ecosystem>) -> String {
+    match ecosystem {
+        Some(ecosystem) => format!("{} ({})", phase.label(), ecosystem),
+        None => phase.label().to_string(),
+    }
+}
+
+/// Render a single progress line
+fn render_line(label: &str, done: usize, total: Option<usize>, elapsed: Duration) -> String {
+    match total {
+        Some(total) if total > 0 => {
+            let done = done.min(total);
+            let filled = BAR_WIDTH * done / total;
+            let eta = estimate_remaining(done, total, elapsed)
+                .map(|eta| format!(" ETA {}", format_duration(eta)))
+                .unwrap_or_default();
+            format!(
+                "{:<28} [{}{}] {}/{} ({}%){}",
+                label,
+                "=".repeat(filled),
+                " ".repeat(BAR_WIDTH - filled),
+                done,
+                total,
+                100 * done / total,
+                eta
+            )
+        }
+        _ => format!(
+            "{:<28} {} found ({})",
+            label,
+            done,
+            format_duration(elapsed)
+        ),
+    }
+}
+
+/// Estimate the remaining time from the average rate so far
+fn estimate_remaining(done: usize, total: usize, elapsed: Duration) -> Option<Duration> {
+    if done == 0 || done >= total {
+        return None;
+    }
+    let per_item = elapsed.as_secs_f64() / done as f64;
+    Some(Duration::from_secs_f64(per_item * (total - done) as f64))
+}
+
+/// Format a duration for display (e.g. `850ms`, `12.3s`, `4m05s`)
+pub fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    if millis < 1000 {
+        format!("{}ms", millis)
+    } else if millis < 60_000 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else {
+        let secs = duration.as_secs();
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(850)), "850ms");
+        assert_eq!(format_duration(Duration::from_millis(12_340)), "12.3s");
+        assert_eq!(format_duration(Duration::from_secs(245)), "4m05s");
+    }
+
+    #[test]
+    fn test_estimate_remaining() {
+        let eta = estimate_remaining(25, 100, Duration::from_secs(10)).unwrap();
+        assert_eq!(eta, Duration::from_secs(30));
+        assert!(estimate_remaining(0, 100, Duration::from_secs(1)).is_none());
+        assert!(estimate_remaining(100, 100, Duration::from_secs(1)).is_none());
+    }
+
+    #[test]
+    fn test_render_line() {
+        let line = render_line(
+            &phase_label(ScanPhase::DeclaredParsing, Some(Ecosystem::Node)),
+            50,
+            Some(100),
+            Duration::from_secs(5),
+        );
+        assert!(line.starts_with("Parsing package files (node)"));
+        assert!(line.contains("50/100 (50%)"));
+        assert!(line.contains("ETA 5.0s"));
+
+        let line = render_line(
+            ScanPhase::Discovery.label(),
+            42,
+            None,
+            Duration::from_millis(300),
+        );
+        assert!(line.contains("42 found (300ms)"));
+    }
+
+    #[test]
+    fn test_phase_timings() {
+        let mut timings = PhaseTimings::new();
+        timings.record(ScanPhase::Discovery, 10, Duration::from_secs(1));
+        timings.record(ScanPhase::Analysis, 5, Duration::from_secs(2));
+
+        assert_eq!(timings.get(ScanPhase::Discovery).unwrap().items, 10);
+        assert!(timings.get(ScanPhase::Output).is_none());
+        assert_eq!(timings.total(), Duration::from_secs(3));
+
+        let mut other = PhaseTimings::new();
+        other.record(ScanPhase::Discovery, 4, Duration::from_secs(3));
+        timings.merge(&other);
+        let discovery = timings.get(ScanPhase::Discovery).unwrap();
+        assert_eq!(discovery.items, 14);
+        assert_eq!(discovery.elapsed, Duration::from_secs(3));
+        assert_eq!(timings.total(), Duration::from_secs(5));
+
+        let mut later = PhaseTimings::new();
+        later.record(ScanPhase::Analysis, 3, Duration::from_secs(1));
+        timings.add(&later);
+        let analysis = timings.get(ScanPhase::Analysis).unwrap();
+        assert_eq!(analysis.items, 8);
+        assert_eq!(analysis.elapsed, Duration::from_secs(3));
+    }
+}
@@ -0,0 +1,2619 @@
+//! High-level scan orchestration
+//!
+//! [`Scanner`] runs the full pipeline that the CLI exposes: discover package
+//! files and installation directories, parse them, classify the results and
+//! link them to applications. Each step is reported as a [`ScanPhase`] to the
+//! configured [`ProgressReporter`], and per-phase timings are returned with the
+//! results. Diagnostics are emitted as [`tracing`] events under the
+//! `scanner::scan` target.
+//!
+//! Files are parsed in parallel on rayon. A scanner given a thread count
+//! ([`Scanner::with_threads`]) runs its scans on a pool of its own, which
+//! other parallel work can share through [`Scanner::install`]; otherwise
+//! scans run on the caller's current pool. The global pool is never
+//! configured, so embedders keep whatever setup they chose.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use scanner::indexer::ScanMode;
+//! use scanner::{ScanOptions, Scanner};
+//! use std::path::Path;
+//!
+//! let options = ScanOptions::new().with_scan_mode(ScanMode::DeclaredOnly);
+//! let output = Scanner::new(options).scan(Path::new("/path/to/project")).unwrap();
+//!
+//! println!("{} dependencies", output.classified.len());
+//! for timing in output.timings.iter() {
+//!     println!("{}: {:?}", timing.phase, timing.elapsed);
+//! }
+//! ```
+
+use crate::analyzer::{
+    Aggregator, ApplicationLinker, Classifier, ConsistencyChecker, EngineChecker,
+    EngineIncompatibility, IntegrityVerifier, LockfileDrift, LockfileDriftChecker,
+    MissingInstallationDetector, MultipleVersions, MultipleVersionsDetector, OverrideChecker,
+    PeerConflict, PeerDependencyChecker, PhantomDetector, RiskFlagger, Summarizer,
+    VersionInconsistency, VersionMatcher, VersionOverride,
+};
+use crate::cancel::CancellationToken;
+use crate::checkpoint::{CheckpointOptions, Checkpointer, FileStamp, ParsedFile, ParsedInstallDir};
+use crate::indexer::{
+    self, ArchiveFiles, DiscoveredFile, FileIndex, Glob, InstallDir, ScanMode, WalkOptions,
+};
+use crate::models::{
+    Application, Classification, ClassifiedDependency, DependencyRecord, DependencyType, Ecosystem,
+    EcosystemStatus, EcosystemSummary, FileType, InstalledPackage, ProvidedPackage, ScanError,
+    ScanErrorEntry, ScanLimit, ScanMetadata, ScanSummary, Symbols,
+};
+use crate::parsers::manifest::{RequirementsMode, RequirementsTxtParser};
+use crate::parsers::{
+    BinaryFile, FileContent, FileReader, FileTooLarge, OsPackagesParser, PackageProvider, Parser,
+    ParserPlugin, ParserRegistry, SitePackagesParser,
+};
+use crate::progress::{NoProgress, PhaseGuard, PhaseTimings, ProgressReporter, ScanPhase};
+use crate::throttle::{self, IoThrottle};
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::any::Any;
+use std::collections::{BTreeMap, BTreeSet};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use tracing::{debug, warn};
+
+/// Directories never entered during a scan
+const DEFAULT_EXCLUDE_DIRS: &[&str] = &[".nx", "target", ".git", "__pycache__"];
+
+/// Options controlling what a [`Scanner`] scans
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// Which dependency sources to scan
+    pub scan_mode: ScanMode,
+
+    /// Look for manifests and lockfiles inside installation directories
+    pub include_install_dirs: bool,
+
+    /// Directory names that are never entered
+    pub exclude_dirs: Vec<String>,
+
+    /// Only parse manifests and lockfiles of these ecosystems (all when empty)
+    pub ecosystems: Vec<Ecosystem>,
+
+    /// Symlink, depth and glob settings for the directory walk
+    pub walk_options: WalkOptions,
+
+    /// Merge HAS/SHOULD/CAN entries of the same package and application
+    pub aggregate: bool,
+
+    /// Verify installed Python files against the hashes in their RECORD
+    pub verify_hashes: bool,
+
+    /// Parse at most this many bytes of each manifest or lockfile
+    pub max_file_size: Option<u64>,
+
+    /// Give up on a manifest or lockfile whose parser runs longer than this;
+    /// each parse then runs on a thread of its own
+    pub parse_timeout: Option<Duration>,
+
+    /// Create applications from discovered manifests rather than from
+    /// installed packages (from lockfiles in lockfiles-only scans, which
+    /// otherwise link none)
+    pub manifest_applications: bool,
+
+    /// Whether `requirements.txt` and `constraints.txt` are read as
+    /// manifests or lockfiles
+    pub requirements_mode: RequirementsMode,
+
+    /// Also read the OS package databases (dpkg, rpm, Homebrew) under this
+    /// system root
+    pub os_packages: Option<PathBuf>,
+
+    /// Limits that cut the scan short with partial results
+    pub limits: ScanLimits,
+
+    /// Runtime versions installed packages are checked against (`node` →
+    /// `18.19.0`, `python` → `3.11`)
+    pub runtimes: BTreeMap<String, String>,
+
+    /// Read manifests and lockfiles from this git revision instead of the
+    /// worktree (declared dependencies only)
+    pub git_rev: Option<String>,
+
+    /// Save the progress of the scan to this file (see
+    /// [`checkpoint`](crate::checkpoint))
+    pub checkpoint: Option<PathBuf>,
+
+    /// Resume from the checkpoint file when it holds the progress of an
+    /// earlier scan of the same root with the same options
+    pub resume: bool,
+
+    /// Hold directory and package file reads to the rate of this throttle
+    /// (see [`throttle`](crate::throttle))
+    pub io_throttle: Option<IoThrottle>,
+
+    /// Take the package files and installation directories from this list
+    /// instead of walking the scanned directory (see
+    /// [`index_paths`](crate::indexer::index_paths))
+    pub paths: Option<Vec<PathBuf>>,
+
+    /// Scanned roots are tar or zip snapshot archives, whose manifests and
+    /// lockfiles are read without extracting them (see
+    /// [`index_archive`](crate::indexer::index_archive))
+    pub archive: bool,
+}
+
+impl ScanOptions {
+    /// Create options for a full scan with the default exclusions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the scan mode
+    pub fn with_scan_mode(mut self, scan_mode: ScanMode) -> Self {
+        self.scan_mode = scan_mode;
+        self
+    }
+
+    /// Set whether installation directories are searched for package files
+    pub fn with_include_install_dirs(mut self, include_install_dirs: bool) -> Self {
+        self.include_install_dirs = include_install_dirs;
+        self
+    }
+
+    /// Restrict declared dependency parsing to an ecosystem (repeatable)
+    pub fn with_ecosystem(mut self, ecosystem: Ecosystem) -> Self {
+        if !self.ecosystems.contains(&ecosystem) {
+            self.ecosystems.push(ecosystem);
+        }
+        self
+    }
+
+    /// Set the directory walk options
+    pub fn with_walk_options(mut self, walk_options: WalkOptions) -> Self {
+        self.walk_options = walk_options;
+        self
+    }
+
+    /// Set whether entries are aggregated per package and application
+    pub fn with_aggregate(mut self, aggregate: bool) -> Self {
+        self.aggregate = aggregate;
+        self
+    }
+
+    /// Set whether installed Python files are verified against RECORD hashes
+    pub fn with_verify_hashes(mut self, verify_hashes: bool) -> Self {
+        self.verify_hashes = verify_hashes;
+        self
+    }
+
+    /// Set the size in bytes above which manifests and lockfiles are skipped
+    pub fn with_max_file_size(mut self, max_file_size: u64) -> Self {
+        self.max_file_size = Some(max_file_size);
+        self
+    }
+
+    /// Set how long a manifest or lockfile may take to parse
+    pub fn with_parse_timeout(mut self, parse_timeout: Duration) -> Self {
+        self.parse_timeout = Some(parse_timeout);
+        self
+    }
+
+    /// Set whether applications are created from discovered manifests
+    pub fn with_manifest_applications(mut self, manifest_applications: bool) -> Self {
+        self.manifest_applications = manifest_applications;
+        self
+    }
+
+    /// Set how requirements files map to manifest or lockfile records
+    pub fn with_requirements_mode(mut self, requirements_mode: RequirementsMode) -> Self {
+        self.requirements_mode = requirements_mode;
+        self
+    }
+
+    /// Read the OS package databases under a system root (`/` for the host)
+    pub fn with_os_packages(mut self, root: impl Into<PathBuf>) -> Self {
+        self.os_packages = Some(root.into());
+        self
+    }
+
+    /// Set the limits that cut the scan short
+    pub fn with_limits(mut self, limits: ScanLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Check installed packages against a runtime version (`node`, `18.19.0`)
+    pub fn with_runtime(mut self, runtime: impl Into<String>, version: impl Into<String>) -> Self {
+        self.runtimes.insert(runtime.into(), version.into());
+        self
+    }
+
+    /// Read manifests and lockfiles from a git revision (`v1.2.0`, a commit
+    /// id) instead of the worktree; nothing installed is scanned
+    pub fn with_git_rev(mut self, rev: impl Into<String>) -> Self {
+        self.git_rev = Some(rev.into());
+        self
+    }
+
+    /// Save the progress of the scan to a checkpoint file, and with
+    /// `resume` continue the scan whose progress it holds
+    pub fn with_checkpoint(mut self, path: impl Into<PathBuf>, resume: bool) -> Self {
+        self.checkpoint = Some(path.into());
+        self.resume = resume;
+        self
+    }
+
+    /// Hold the reads of the scan to the rate of a throttle
+    pub fn with_io_throttle(mut self, io_throttle: IoThrottle) -> Self {
+        self.io_throttle = Some(io_throttle);
+        self
+    }
+
+    /// Parse the package files and installation directories of a list of
+    /// paths instead of walking the scanned directory
+    pub fn with_paths(mut self, paths: impl IntoIterator<Item = impl Into<PathBuf>>) -> Self {
+        self.paths = Some(paths.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Read the scanned roots as tar or zip snapshot archives; nothing
+    /// installed is scanned
+    pub fn with_archive(mut self, archive: bool) -> Self {
+        self.archive = archive;
+        self
+    }
+
+    /// Check if manifests and lockfiles are scanned
+    pub fn scans_declared(&self) -> bool {
+        self.scan_mode.collects_files()
+    }
+
+    /// Check if installation directories are scanned
+    pub fn scans_installed(&self) -> bool {
+        self.scan_mode.collects_install_dirs()
+    }
+
+    /// Check if this is the lockfiles-only fast path
+    pub fn lockfiles_only(&self) -> bool {
+        self.scan_mode == ScanMode::LockfilesOnly
+    }
+
+    /// Options deciding which files are found and what parsing them gives,
+    /// which a resumed scan must share with the scan it resumes
+    fn checkpoint_options(&self) -> CheckpointOptions {
+        let globs = |globs: &[Glob]| globs.iter().map(|g| g.as_str().to_string()).collect();
+        let filter = &self.walk_options.filter;
+        CheckpointOptions {
+            scan_mode: self.scan_mode,
+            include_install_dirs: self.include_install_dirs,
+            exclude_dirs: self.exclude_dirs.clone(),
+            follow_symlinks: self.walk_options.follow_symlinks,
+            max_visited_dirs: self.walk_options.max_visited_dirs,
+            max_depth: filter.max_depth,
+            include_globs: globs(&filter.include),
+            exclude_globs: globs(&filter.exclude),
+            verify_hashes: self.verify_hashes,
+            max_file_size: self.max_file_size,
+            requirements_mode: self.requirements_mode,
+            git_rev: self.git_rev.clone(),
+            paths: self.paths.is_some(),
+            archive: self.archive,
+        }
+    }
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            scan_mode: ScanMode::Full,
+            include_install_dirs: false,
+            exclude_dirs: DEFAULT_EXCLUDE_DIRS.iter().map(|d| d.to_string()).collect(),
+            ecosystems: Vec::new(),
+            walk_options: WalkOptions::default(),
+            aggregate: false,
+            verify_hashes: false,
+            max_file_size: None,
+            parse_timeout: None,
+            manifest_applications: false,
+            requirements_mode: RequirementsMode::Auto,
+            os_packages: None,
+            limits: ScanLimits::default(),
+            runtimes: BTreeMap::new(),
+            git_rev: None,
+            checkpoint: None,
+            resume: false,
+            io_throttle: None,
+            paths: None,
+            archive: false,
+        }
+    }
+}
+
+/// Bounds on the work of a scan (unlimited when None)
+///
+/// A scan reaching a limit stops short instead of running unbounded on
+/// hostile or enormous filesystems: it keeps what it found so far and lists
+/// the reached limits in [`ScanMetadata::truncated`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanLimits {
+    /// Maximum number of manifests and lockfiles parsed
+    pub max_files: Option<usize>,
+
+    /// Maximum number of installation directories parsed
+    pub max_install_dirs: Option<usize>,
+
+    /// Maximum number of packages kept per installation directory
+    pub max_packages_per_dir: Option<usize>,
+
+    /// Time after which no further directories are walked and no further
+    /// files or installation directories are parsed
+    pub time_budget: Option<Duration>,
+}
+
+impl ScanLimits {
+    /// Create limits with nothing bounded
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of manifests and lockfiles
+    pub fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = Some(max_files);
+        self
+    }
+
+    /// Set the maximum number of installation directories
+    pub fn with_max_install_dirs(mut self, max_install_dirs: usize) -> Self {
+        self.max_install_dirs = Some(max_install_dirs);
+        self
+    }
+
+    /// Set the maximum number of packages per installation directory
+    pub fn with_max_packages_per_dir(mut self, max_packages_per_dir: usize) -> Self {
+        self.max_packages_per_dir = Some(max_packages_per_dir);
+        self
+    }
+
+    /// Set the time budget of the whole scan
+    pub fn with_time_budget(mut self, time_budget: Duration) -> Self {
+        self.time_budget = Some(time_budget);
+        self
+    }
+}
+
+/// Limits enforced during one scan, and the ones reached
+struct Budget {
+    limits: ScanLimits,
+    deadline: Option<Instant>,
+    cancellation: CancellationToken,
+    reached: Mutex<BTreeSet<ScanLimit>>,
+}
+
+impl Budget {
+    /// Start the budget of a scan starting now, stopped early by `cancellation`
+    fn new(limits: ScanLimits, cancellation: CancellationToken) -> Self {
+        Self {
+            limits,
+            deadline: limits.time_budget.map(|budget| Instant::now() + budget),
+            cancellation,
+            reached: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    /// Record a reached limit, warning the first time
+    fn reach(&self, limit: ScanLimit) {
+        if self.reached.lock().unwrap().insert(limit) {
+            warn!(%limit, "Scan limit reached, results are partial");
+        }
+    }
+
+    /// Check if the time budget is spent or the scan was cancelled
+    fn expired(&self) -> bool {
+        if self.cancellation.is_cancelled() {
+            self.reach(ScanLimit::Cancelled);
+            return true;
+        }
+        let expired = self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline);
+        if expired {
+            self.reach(ScanLimit::TimeBudget);
+        }
+        expired
+    }
+
+    /// Keep at most `max` items
+    fn cap<T>(&self, items: &mut Vec<T>, max: Option<usize>, limit: ScanLimit) {
+        if let Some(max) = max.filter(|&max| items.len() > max) {
+            items.truncate(max);
+            self.reach(limit);
+        }
+    }
+
+    /// Limits reached, in order
+    fn into_reached(self) -> Vec<ScanLimit> {
+        self.reached.into_inner().unwrap().into_iter().collect()
+    }
+}
+
+/// Where the manifests and lockfiles of a scan are read from
+#[derive(Debug, Clone, Copy)]
+enum FileSource<'a> {
+    /// The files on disk
+    Worktree,
+    /// The blobs of a commit, for files under `root`
+    GitRev { root: &'a Path, rev: &'a str },
+    /// The members of a snapshot archive
+    Archive(&'a ArchiveFiles),
+}
+
+/// Everything produced by a scan
+#[derive(Debug, Clone, Default)]
+pub struct ScanOutput {
+    /// Manifests and lockfiles that were parsed
+    pub files: Vec<DiscoveredFile>,
+
+    /// Installation directories that were parsed
+    pub install_dirs: Vec<InstallDir>,
+
+    /// Number of raw dependency records parsed from manifests and lockfiles
+    pub record_count: usize,
+
+    /// Number of installed packages found
+    pub installed_count: usize,
+
+    /// Classified dependencies with version mismatch flags
+    pub classified: Vec<ClassifiedDependency>,
+
+    /// Dependencies grouped by application
+    pub applications: Vec<Application>,
+
+    /// Per-phase timings
+    pub timings: PhaseTimings,
+
+    /// Summary statistics (timings cover the phases run by the scanner)
+    pub summary: ScanSummary,
+
+    /// Package files and installation directories that failed to read or parse
+    pub errors: Vec<ScanErrorEntry>,
+
+    /// Versions that disagree across installed, locked and declared sources
+    pub inconsistencies: Vec<VersionInconsistency>,
+
+    /// Version overrides and the requested ranges they overrule
+    pub overrides: Vec<VersionOverride>,
+
+    /// Packages installed at several versions within an application
+    pub multiple_versions: Vec<MultipleVersions>,
+
+    /// Unmet or conflicting peer dependencies of installed Node packages
+    pub peer_conflicts: Vec<PeerConflict>,
+
+    /// Installed packages declaring they do not support a target runtime
+    pub incompatible_engines: Vec<EngineIncompatibility>,
+
+    /// Lockfiles out of date with the manifest next to them
+    pub lockfile_drift: Vec<LockfileDrift>,
+
+    /// Host, scanner version, times and file counts of the scan
+    pub metadata: ScanMetadata,
+}
+
+/// Scanner running discovery, parsing and analysis
+pub struct Scanner {
+    options: ScanOptions,
+    registry: ParserRegistry,
+    progress: Arc<dyn ProgressReporter>,
+    cancellation: CancellationToken,
+    pool: Option<ThreadPool>,
+}
+
+impl Scanner {
+    /// Create a scanner with the built-in parsers and no progress reporting
+    pub fn new(options: ScanOptions) -> Self {
+        let mut registry = ParserRegistry::with_default_parsers();
+        let mode = options.requirements_mode;
+        registry.register(Arc::new(RequirementsTxtParser::new().with_mode(mode)));
+        registry.register(Arc::new(
+            RequirementsTxtParser::constraints().with_mode(mode),
+        ));
+        registry.register_installed(Arc::new(
+            SitePackagesParser::new().with_verify_hashes(options.verify_hashes),
+        ));
+        Self {
+            options,
+            registry,
+            progress: Arc::new(NoProgress),
+            cancellation: CancellationToken::new(),
+            pool: None,
+        }
+    }
+
+    /// Use a custom parser registry
+    pub fn with_registry(mut self, registry: ParserRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Register the parsers of a plugin; files they handle are discovered
+    /// and parsed along with the built-in ones
+    pub fn with_plugin(mut self, plugin: &dyn ParserPlugin) -> Self {
+        self.registry.register_plugin(plugin);
+        self
+    }
+
+    /// Add the packages of a provider to scans, classified as HAS or
+    /// SHOULD with the provider's name as provenance
+    pub fn with_provider(mut self, provider: Arc<dyn PackageProvider>) -> Self {
+        self.registry.register_provider(provider);
+        self
+    }
+
+    /// Get the parser registry
+    pub fn registry(&self) -> &ParserRegistry {
+        &self.registry
+    }
+
+    /// Get the parser registry to register parsers
+    pub fn registry_mut(&mut self) -> &mut ParserRegistry {
+        &mut self.registry
+    }
+
+    /// Report progress to the given reporter
+    pub fn with_progress(mut self, progress: Arc<dyn ProgressReporter>) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Stop scans early when the token is cancelled, keeping what they
+    /// found so far
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Run scans on a pool of this many worker threads owned by the
+    /// scanner, rather than on the caller's current rayon pool
+    ///
+    /// If the threads can't be started, scans run on the current pool.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        match ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|index| format!("scanner-{}", index))
+            .build()
+        {
+            Ok(pool) => self.pool = Some(pool),
+            Err(e) => warn!(threads, error = %e, "Failed to start the scan thread pool"),
+        }
+        self
+    }
+
+    /// Run `op` on the scanner's thread pool, so the parallel work it does
+    /// shares the scanner's threads (directly without one)
+    pub fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        match &self.pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+
+    /// Get the token cancelling the scans of this scanner
+    pub fn cancellation(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+
+    /// Get the scan options
+    pub fn options(&self) -> &ScanOptions {
+        &self.options
+    }
+
+    /// Get the progress reporter
+    pub fn progress(&self) -> &dyn ProgressReporter {
+        self.progress.as_ref()
+    }
+
+    /// Discover the manifests, lockfiles and installation directories of a
+    /// tree without parsing them
+    pub fn index(&self, root: &Path) -> FileIndex {
+        self.install(|| self.index_with(root, &self.options.walk_options))
+    }
+
+    /// Discover package files with the given walk options, or take them from
+    /// the listed paths
+    fn index_with(&self, root: &Path, walk_options: &WalkOptions) -> FileIndex {
+        let exclude_dirs: Vec<&str> = self
+            .options
+            .exclude_dirs
+            .iter()
+            .map(String::as_str)
+            .collect();
+        // Registered parsers add file names the indexer does not know
+        let classify = |filename: &str| {
+            indexer::classify_file(filename).or_else(|| self.registry.classify(filename))
+        };
+        if let Some(paths) = &self.options.paths {
+            return indexer::index_paths(
+                paths,
+                &exclude_dirs,
+                self.options.scan_mode,
+                self.options.include_install_dirs,
+                &classify,
+                self.progress(),
+            );
+        }
+        let mut walk_options = walk_options.clone();
+        if let Some(throttle) = &self.options.io_throttle {
+            walk_options = walk_options.with_io_throttle(throttle.clone());
+        }
+        indexer::index_tree_with_classifier(
+            root,
+            &exclude_dirs,
+            self.options.scan_mode,
+            self.options.include_install_dirs,
+            &walk_options,
+            &classify,
+            self.progress(),
+        )
+    }
+
+    /// Discover the manifests and lockfiles of a tree at a git revision
+    fn index_git_rev(&self, root: &Path, rev: &str) -> Result<FileIndex, ScanError> {
+        let exclude_dirs: Vec<&str> = self
+            .options
+            .exclude_dirs
+            .iter()
+            .map(String::as_str)
+            .collect();
+        let classify = |filename: &str| {
+            indexer::classify_file(filename).or_else(|| self.registry.classify(filename))
+        };
+        let files = indexer::index_git_rev(
+            root,
+            rev,
+            &exclude_dirs,
+            self.options.scan_mode,
+            self.options.include_install_dirs,
+            &self.options.walk_options,
+            &classify,
+        )?;
+        self.progress.advance(ScanPhase::Discovery, files.len());
+        Ok(FileIndex {
+            files,
+            install_dirs: Vec::new(),
+        })
+    }
+
+    /// Read the manifests and lockfiles of a snapshot archive, until the
+    /// budget's deadline or cancellation
+    fn index_archive(&self, archive: &Path, budget: &Budget) -> Result<ArchiveFiles, ScanError> {
+        let exclude_dirs: Vec<&str> = self
+            .options
+            .exclude_dirs
+            .iter()
+            .map(String::as_str)
+            .collect();
+        let classify = |filename: &str| {
+            indexer::classify_file(filename).or_else(|| self.registry.classify(filename))
+        };
+        let mut walk_options = self.options.walk_options.clone();
+        if let Some(deadline) = budget.deadline {
+            walk_options = walk_options.with_deadline(deadline);
+        }
+        walk_options = walk_options.with_cancellation(self.cancellation.clone());
+        let found = indexer::index_archive(
+            archive,
+            &exclude_dirs,
+            self.options.scan_mode,
+            self.options.include_install_dirs,
+            &walk_options,
+            self.options.max_file_size,
+            &classify,
+        )?;
+        self.progress
+            .advance(ScanPhase::Discovery, found.files.len());
+        Ok(found)
+    }
+
+    /// Scan a directory tree
+    pub fn scan(&self, root: &Path) -> Result<ScanOutput, ScanError> {
+        self.install(|| self.scan_root(root))
+    }
+
+    fn scan_root(&self, root: &Path) -> Result<ScanOutput, ScanError> {
+        if !root.exists() {
+            return Err(ScanError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Directory does not exist: {}", root.display()),
+            )));
+        }
+
+        let started_at = SystemTime::now();
+        let mut timings = PhaseTimings::new();
+        let budget = Budget::new(self.options.limits, self.cancellation.clone());
+        let checkpoint = self.options.checkpoint.as_deref().map(|path| {
+            let root = std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+            Checkpointer::start(
+                path,
+                &root,
+                self.options.checkpoint_options(),
+                self.options.resume,
+            )
+        });
+
+        // Discover manifests, lockfiles and installation directories in one
+        // pass, or list the package files of the scanned revision or archive
+        let phase = PhaseGuard::start(self.progress(), ScanPhase::Discovery, None);
+        let git_rev = match &self.options.git_rev {
+            Some(rev) => Some(indexer::resolve_rev(root, rev)?),
+            None => None,
+        };
+        // Archive contents are not checkpointed, so an archive is read again
+        // when resuming
+        let archive = match self.options.archive {
+            true => Some(self.index_archive(root, &budget)?),
+            false => None,
+        };
+        let resumed_index = checkpoint.as_ref().and_then(Checkpointer::index);
+        let index = match (&archive, resumed_index, &git_rev) {
+            (Some(archive), _, _) => FileIndex {
+                files: archive.files.clone(),
+                install_dirs: Vec::new(),
+            },
+            (None, Some(index), _) => {
+                debug!("Resumed discovery from checkpoint");
+                self.progress.advance(
+                    ScanPhase::Discovery,
+                    index.files.len() + index.install_dirs.len(),
+                );
+                index
+            }
+            (None, None, Some(rev)) => self.index_git_rev(root, rev)?,
+            (None, None, None) => {
+                let mut walk_options = self.options.walk_options.clone();
+                if let Some(deadline) = budget.deadline {
+                    walk_options = walk_options.with_deadline(deadline);
+                }
+                walk_options = walk_options.with_cancellation(self.cancellation.clone());
+                self.index_with(root, &walk_options)
+            }
+        };
+        // A walk cut short is done again when resuming
+        if !budget.expired() {
+            if let Some(checkpoint) = &checkpoint {
+                checkpoint.record_index(&index);
+            }
+        }
+        phase.finish(index.files.len() + index.install_dirs.len(), &mut timings);
+
+        debug!(
+            files = index.files.len(),
+            install_dirs = index.install_dirs.len(),
+            "Discovered manifest/lockfiles and installation directories"
+        );
+
+        let mut files: Vec<DiscoveredFile> = if self.options.ecosystems.is_empty() {
+            index.files
+        } else {
+            index
+                .files
+                .into_iter()
+                .filter(|f| self.options.ecosystems.contains(&f.ecosystem))
+                .collect()
+        };
+        budget.cap(&mut files, budget.limits.max_files, ScanLimit::MaxFiles);
+
+        let mut install_dirs = index.install_dirs;
+        budget.cap(
+            &mut install_dirs,
+            budget.limits.max_install_dirs,
+            ScanLimit::MaxInstallDirs,
+        );
+
+        let (error_tx, error_rx) = mpsc::channel();
+        let source = match (&git_rev, &archive) {
+            (Some(rev), _) => FileSource::GitRev { root, rev },
+            (None, Some(archive)) => FileSource::Archive(archive),
+            (None, None) => FileSource::Worktree,
+        };
+        // The lockfiles-only fast path has nothing installed to read, nothing
+        // installed is committed, and the installed-package parsers only read
+        // from disk
+        let snapshot = git_rev.is_some() || archive.is_some();
+        let read_installed = !self.options.lockfiles_only() && !snapshot;
+        let provided = if !snapshot {
+            self.collect_provided(root, &error_tx)
+        } else {
+            Vec::new()
+        };
+
+        // Each ecosystem is parsed and analyzed on its own, so one that
+        // crashes leaves the others' results intact
+        let mut scanned = EcosystemOutput::default();
+        let mut ecosystems = BTreeMap::new();
+        let units = ecosystem_units(
+            &files,
+            &install_dirs,
+            provided,
+            read_installed && self.options.os_packages.is_some(),
+        );
+        for (ecosystem, unit) in units {
+            let mut summary = EcosystemSummary {
+                files_scanned: unit.files.len(),
+                install_dirs_scanned: unit.install_dirs.len(),
+                ..EcosystemSummary::default()
+            };
+            if budget.expired() {
+                debug!(%ecosystem, "Skipped ecosystem");
+                summary.status = EcosystemStatus::Skipped;
+                self.progress.ecosystem_finished(ecosystem, &summary);
+                ecosystems.insert(ecosystem.to_string(), summary);
+                continue;
+            }
+
+            self.progress.ecosystem_started(ecosystem);
+            let started = Instant::now();
+            let context = UnitContext {
+                source,
+                read_installed,
+                budget: &budget,
+                checkpoint: checkpoint.as_ref(),
+                errors: &error_tx,
+            };
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                self.scan_ecosystem(ecosystem, unit, &context)
+            }));
+            summary.elapsed_ms = started.elapsed().as_millis() as u64;
+            match result {
+                Ok(output) => {
+                    summary.total_dependencies = output.classified.len();
+                    scanned.extend(output);
+                }
+                Err(payload) => {
+                    let message = panic_message(payload.as_ref());
+                    warn!(%ecosystem, %message, "Ecosystem scan panicked, skipping its results");
+                    let error = ScanError::Panic {
+                        file: root.to_path_buf(),
+                        message,
+                    };
+                    summary.status = EcosystemStatus::Failed;
+                    summary.error = Some(error.to_string());
+                    let _ = error_tx.send(ScanErrorEntry::new(
+                        root.to_path_buf(),
+                        Some(ecosystem),
+                        &error,
+                    ));
+                }
+            }
+            self.progress.ecosystem_finished(ecosystem, &summary);
+            ecosystems.insert(ecosystem.to_string(), summary);
+        }
+
+        drop(error_tx);
+        let mut errors: Vec<ScanErrorEntry> = error_rx.into_iter().collect();
+        errors.sort_by(|a, b| a.file.cmp(&b.file));
+        if !errors.is_empty() {
+            warn!(count = errors.len(), "Some files could not be parsed");
+        }
+        for ecosystem in errors.iter().filter_map(|error| error.ecosystem) {
+            if let Some(summary) = ecosystems.get_mut(&ecosystem.to_string()) {
+                summary.parse_errors += 1;
+            }
+        }
+        timings.add(&scanned.timings);
+
+        let EcosystemOutput {
+            record_count,
+            installed_count,
+            mut classified,
+            mut applications,
+            dependency_types,
+            inconsistencies,
+            overrides,
+            multiple_versions,
+            peer_conflicts,
+            incompatible_engines,
+            lockfile_drift,
+            ..
+        } = scanned;
+        classified.sort_by(ClassifiedDependency::output_order);
+        applications.sort_by(|a, b| (&a.root_path, a.ecosystem).cmp(&(&b.root_path, b.ecosystem)));
+
+        let summarizer = Summarizer::new();
+        let mut summary = summarizer.summarize(&classified);
+        summary.files_scanned = files.len();
+        summary.install_dirs_scanned = install_dirs.len();
+        summary.applications = applications.len();
+        summary.by_dependency_type = dependency_types.by_dependency_type;
+        summary.parse_errors = errors.len();
+        summary.version_inconsistencies = inconsistencies.len();
+        summary.multiple_versions = multiple_versions.len();
+        summary.peer_conflicts = peer_conflicts.len();
+        summary.incompatible_engines = incompatible_engines.len();
+        summary.stale_lockfiles = lockfile_drift.len();
+        summary.ecosystems = ecosystems;
+        summarizer.set_timings(&mut summary, &timings);
+
+        let mut metadata = ScanMetadata::new(root, started_at);
+        metadata.files_scanned = files.len();
+        metadata.install_dirs_scanned = install_dirs.len();
+        metadata.parse_errors = errors.len();
+        metadata.truncated = budget.into_reached();
+        metadata.git_rev = git_rev;
+        if let Some(checkpoint) = checkpoint {
+            let stopped_early = metadata
+                .truncated
+                .iter()
+                .any(|limit| matches!(limit, ScanLimit::TimeBudget | ScanLimit::Cancelled));
+            checkpoint.finish(!stopped_early);
+        }
+
+        Ok(ScanOutput {
+            files,
+            install_dirs,
+            record_count,
+            installed_count,
+            classified,
+            applications,
+            timings,
+            summary,
+            errors,
+            inconsistencies,
+            overrides,
+            multiple_versions,
+            peer_conflicts,
+            incompatible_engines,
+            lockfile_drift,
+            metadata,
+        })
+    }
+
+    /// Scan several directory trees concurrently and merge their results
+    ///
+    /// Each root is scanned as by [`scan`](Self::scan), so entries are only
+    /// aggregated within a root. With more than one root, every dependency
+    /// records the root it was found under in `scan_root`. A checkpoint
+    /// holds the progress of one root, so several roots cannot be scanned
+    /// with one.
+    pub fn scan_roots(&self, roots: &[PathBuf]) -> Result<ScanOutput, ScanError> {
+        self.install(|| self.scan_all(roots))
+    }
+
+    fn scan_all(&self, roots: &[PathBuf]) -> Result<ScanOutput, ScanError> {
+        match roots {
+            [] => Err(ScanError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "No directories to scan",
+            ))),
+            [root] => self.scan_root(root),
+            _ if self.options.checkpoint.is_some() => Err(ScanError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "A checkpoint holds the progress of a single directory",
+            ))),
+            _ => {
+                let started_at = SystemTime::now();
+                let outputs = roots
+                    .par_iter()
+                    .map(|root| self.scan_root(root))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(merge_outputs(outputs, started_at))
+            }
+        }
+    }
+
+    /// Parse and analyze the package files, installation directories and
+    /// provided packages of one ecosystem
+    fn scan_ecosystem(
+        &self,
+        ecosystem: Ecosystem,
+        unit: EcosystemUnit,
+        context: &UnitContext<'_>,
+    ) -> EcosystemOutput {
+        let mut timings = PhaseTimings::new();
+        let files = unit.files;
+        let phase = PhaseGuard::start(
+            self.progress(),
+            ScanPhase::DeclaredParsing,
+            Some(files.len()),
+        );
+        let mut records = self.parse_declared(
+            &files,
+            context.source,
+            context.budget,
+            context.checkpoint,
+            context.errors,
+        );
+        // Records are kept until classification; share their repeated strings
+        let mut symbols = Symbols::new();
+        symbols.intern_records(&mut records);
+        debug!(
+            %ecosystem,
+            records = records.len(),
+            symbols = symbols.len(),
+            "Interned record names and versions"
+        );
+        phase.finish(files.len(), &mut timings);
+
+        let mut installed = Vec::new();
+        if context.read_installed {
+            let phase = PhaseGuard::start(
+                self.progress(),
+                ScanPhase::InstalledParsing,
+                Some(unit.install_dirs.len()),
+            );
+            installed = self.parse_installed(
+                &unit.install_dirs,
+                context.budget,
+                context.checkpoint,
+                context.errors,
+            );
+            if unit.os_packages {
+                if let Some(os_root) = &self.options.os_packages {
+                    installed.extend(self.parse_os_packages(os_root, context.errors));
+                }
+            }
+            phase.finish(unit.install_dirs.len(), &mut timings);
+        }
+
+        // Overrides are not dependencies; they are compared with the graph instead
+        let (override_records, records): (Vec<_>, Vec<_>) = records
+            .into_iter()
+            .partition(|r| r.dep_type == DependencyType::Override);
+        let overrides = OverrideChecker::new().check(&override_records, &records, &installed);
+        debug!(
+            %ecosystem,
+            overrides = overrides.len(),
+            "Checked version overrides"
+        );
+        let multiple_versions = MultipleVersionsDetector::new().detect(&records, &installed);
+        debug!(
+            %ecosystem,
+            packages = multiple_versions.len(),
+            "Found packages installed at several versions"
+        );
+        let peer_conflicts = PeerDependencyChecker::new().check(&installed);
+        debug!(
+            %ecosystem,
+            peers = peer_conflicts.len(),
+            "Checked peer dependencies"
+        );
+        let incompatible_engines = EngineChecker::new()
+            .with_targets(&self.options.runtimes)
+            .check(&installed);
+        debug!(
+            %ecosystem,
+            packages = incompatible_engines.len(),
+            "Checked runtime compatibility"
+        );
+        let lockfile_drift = LockfileDriftChecker::new().check(&records);
+        debug!(
+            %ecosystem,
+            lockfiles = lockfile_drift.len(),
+            "Checked lockfiles against manifests"
+        );
+
+        let record_count = records.len();
+        let installed_count = installed.len();
+        let mut dependency_types = ScanSummary::new();
+        Summarizer::new().count_dependency_types(&mut dependency_types, &records);
+
+        let phase = PhaseGuard::start(self.progress(), ScanPhase::Analysis, None);
+        let mut classified = classify(records, installed, unit.provided, self.options.aggregate);
+        classified.sort_by(ClassifiedDependency::output_order);
+        debug!(
+            %ecosystem,
+            dependencies = classified.len(),
+            "Classified dependencies"
+        );
+
+        let risky = RiskFlagger::new().mark_risks(&mut classified);
+        debug!(
+            %ecosystem,
+            risky,
+            "Flagged installed packages that run or ship code"
+        );
+
+        let inconsistencies = check_applications(&mut classified);
+
+        let linker = ApplicationLinker::new();
+        let applications = if self.options.lockfiles_only() {
+            if self.options.manifest_applications {
+                linker.link_from_lockfiles(&files, classified.clone())
+            } else {
+                Vec::new()
+            }
+        } else if self.options.manifest_applications {
+            linker.link_from_manifests(&files, classified.clone())
+        } else {
+            linker.link_to_applications(classified.clone())
+        };
+        debug!(
+            %ecosystem,
+            applications = applications.len(),
+            "Linked dependencies to applications"
+        );
+        phase.finish(classified.len(), &mut timings);
+
+        EcosystemOutput {
+            record_count,
+            installed_count,
+            classified,
+            applications,
+            dependency_types,
+            inconsistencies,
+            overrides,
+            multiple_versions,
+            peer_conflicts,
+            incompatible_engines,
+            lockfile_drift,
+            timings,
+        }
+    }
+
+    /// Parse manifests and lockfiles in parallel, sending failures to
+    /// `errors`; files parsed by a resumed scan are taken from its checkpoint
+    fn parse_declared(
+        &self,
+        files: &[DiscoveredFile],
+        source: FileSource<'_>,
+        budget: &Budget,
+        checkpoint: Option<&Checkpointer>,
+        errors: &Sender<ScanErrorEntry>,
+    ) -> Vec<DependencyRecord> {
+        // Each task returns its records and rayon concatenates them in file
+        // order, so workers never contend on a shared buffer
+        files
+            .par_iter()
+            .flat_map_iter(|file| {
+                // Files of a revision or an archive cannot change on disk
+                let stamp = match (checkpoint, source) {
+                    (Some(_), FileSource::Worktree) => FileStamp::of(&file.path),
+                    _ => None,
+                };
+                let parsed = match checkpoint.and_then(|c| c.parsed_file(&file.path, stamp)) {
+                    Some(parsed) => parsed,
+                    None if budget.expired() => ParsedFile::default(),
+                    None => {
+                        let parsed = match self.parse_file(file, source) {
+                            Ok(records) => ParsedFile {
+                                stamp,
+                                records,
+                                error: None,
+                            },
+                            Err(error) => ParsedFile {
+                                stamp,
+                                records: Vec::new(),
+                                error: Some(error),
+                            },
+                        };
+                        if let Some(checkpoint) = checkpoint {
+                            checkpoint.record_file(&file.path, parsed.clone());
+                        }
+                        parsed
+                    }
+                };
+                if let Some(error) = parsed.error {
+                    let _ = errors.send(error);
+                }
+                self.progress.advance(ScanPhase::DeclaredParsing, 1);
+                parsed.records
+            })
+            .collect()
+    }
+
+    /// Parse one manifest or lockfile, from the worktree, a git revision or
+    /// an archive
+    fn parse_file(
+        &self,
+        file: &DiscoveredFile,
+        source: FileSource<'_>,
+    ) -> Result<Vec<DependencyRecord>, ScanErrorEntry> {
+        let Some(parser) = self.registry.get_parser(&file.filename) else {
+            return Ok(Vec::new());
+        };
+        let mut reader = FileReader::new();
+        if let Some(max_file_size) = self.options.max_file_size {
+            reader = reader.with_max_file_size(max_file_size);
+        }
+        if let Some(throttle) = &self.options.io_throttle {
+            reader = reader.with_io_throttle(throttle.clone());
+        }
+        let content = match source {
+            FileSource::Worktree => reader.read(&file.path).map_err(ScanError::Io),
+            FileSource::GitRev { root, rev } => indexer::read_git_file(root, rev, &file.path)
+                .and_then(|bytes| reader.decode(&file.path, bytes).map_err(ScanError::Io)),
+            FileSource::Archive(archive) => archive
+                .take(&file.path)
+                .and_then(|bytes| reader.decode(&file.path, bytes).map_err(ScanError::Io)),
+        };
+        let result = match content {
+            Err(ScanError::Io(e)) if BinaryFile::is(&e) => {
+                warn!(file = %file.path.display(), "Skipping binary file");
+                return Ok(Vec::new());
+            }
+            Err(ScanError::Io(e)) => match FileTooLarge::limit(&e) {
+                Some(limit) => {
+                    warn!(file = %file.path.display(), limit, "Skipping file over the size limit");
+                    Err(ScanError::TooLarge {
+                        file: file.path.clone(),
+                        limit,
+                    })
+                }
+                None => Err(ScanError::Io(e)),
+            },
+            Err(e) => Err(e),
+            Ok(content) => parse_isolated(parser, content, &file.path, self.options.parse_timeout),
+        };
+        match result {
+            Ok(records) => {
+                debug!(
+                    file = %file.path.display(),
+                    dependencies = records.len(),
+                    "Parsed package file"
+                );
+                Ok(records)
+            }
+            Err(e) => {
+                debug!(file = %file.path.display(), error = %e, "Failed to parse");
+                Err(ScanErrorEntry::new(
+                    file.path.clone(),
+                    Some(file.ecosystem),
+                    &e,
+                ))
+            }
+        }
+    }
+
+    /// Parse installation directories in parallel, sending failures to
+    /// `errors`; directories parsed by a resumed scan are taken from its
+    /// checkpoint
+    fn parse_installed(
+        &self,
+        install_dirs: &[InstallDir],
+        budget: &Budget,
+        checkpoint: Option<&Checkpointer>,
+        errors: &Sender<ScanErrorEntry>,
+    ) -> Vec<InstalledPackage> {
+        install_dirs
+            .par_iter()
+            .flat_map_iter(|install_dir| {
+                let stamp = checkpoint.and_then(|_| FileStamp::of(&install_dir.path));
+                let resumed =
+                    checkpoint.and_then(|c| c.parsed_install_dir(&install_dir.path, stamp));
+                let parsed = match resumed {
+                    Some(parsed) => parsed,
+                    None if budget.expired() => ParsedInstallDir::default(),
+                    None => {
+                        let parsed = ParsedInstallDir {
+                            stamp,
+                            ..self.parse_install_dir(install_dir)
+                        };
+                        if let Some(checkpoint) = checkpoint {
+                            checkpoint.record_install_dir(&install_dir.path, parsed.clone());
+                        }
+                        parsed
+                    }
+                };
+                if let Some(error) = parsed.error {
+                    let _ = errors.send(error);
+                }
+                let mut packages = parsed.packages;
+                budget.cap(
+                    &mut packages,
+                    budget.limits.max_packages_per_dir,
+                    ScanLimit::MaxPackagesPerDir,
+                );
+                self.progress.advance(ScanPhase::InstalledParsing, 1);
+                packages
+            })
+            .collect()
+    }
+
+    /// Parse one installation directory
+    fn parse_install_dir(&self, install_dir: &InstallDir) -> ParsedInstallDir {
+        let Some(parser) = self.registry.get_installed_parser(&install_dir.dir_type) else {
+            debug!(path = %install_dir.path.display(), "No installed-package parser");
+            return ParsedInstallDir::default();
+        };
+        let io_throttle = self.options.io_throttle.as_ref();
+        let parse = || {
+            catch_parse(&install_dir.path, || {
+                parser.parse_installed(&install_dir.path)
+            })
+        };
+        match throttle::scoped(io_throttle, parse) {
+            Ok(packages) => {
+                debug!(
+                    path = %install_dir.path.display(),
+                    packages = packages.len(),
+                    "Parsed installation directory"
+                );
+                ParsedInstallDir {
+                    packages,
+                    ..ParsedInstallDir::default()
+                }
+            }
+            Err(e) => {
+                debug!(path = %install_dir.path.display(), error = %e, "Failed to parse");
+                ParsedInstallDir {
+                    error: Some(ScanErrorEntry::new(
+                        install_dir.path.clone(),
+                        Some(install_dir.ecosystem),
+                        &e,
+                    )),
+                    ..ParsedInstallDir::default()
+                }
+            }
+        }
+    }
+
+    /// Read the OS package databases under a system root, sending failures
+    /// to `errors`
+    fn parse_os_packages(
+        &self,
+        root: &Path,
+        errors: &Sender<ScanErrorEntry>,
+    ) -> Vec<InstalledPackage> {
+        let parser = OsPackagesParser;
+        let mut packages = Vec::new();
+        let io_throttle = self.options.io_throttle.as_ref();
+        for (manager, database) in parser.databases(root) {
+            match throttle::scoped(io_throttle, || parser.parse_installed(manager, &database)) {
+                Ok(found) => {
+                    debug!(
+                        manager = %manager,
+                        database = %database.display(),
+                        packages = found.len(),
+                        "Parsed OS package database"
+                    );
+                    packages.extend(found);
+                }
+                Err(e) => {
+                    debug!(database = %database.display(), error = %e, "Failed to parse");
+                    let _ = errors.send(ScanErrorEntry::new(database, Some(Ecosystem::Os), &e));
+                }
+            }
+        }
+        packages
+    }
+
+    /// Collect the packages of the registered providers that the scan mode
+    /// and ecosystems select, sending failures to `errors`
+    fn collect_provided(
+        &self,
+        root: &Path,
+        errors: &Sender<ScanErrorEntry>,
+    ) -> Vec<ProvidedPackage> {
+        let selected = |pkg: &ProvidedPackage| {
+            let mode = match pkg.classification {
+                Classification::Has => self.options.scans_installed(),
+                Classification::Should => self.options.scans_declared(),
+                Classification::Can => self
+                    .options
+                    .scan_mode
+                    .collects_file_type(FileType::Manifest),
+            };
+            mode && (self.options.ecosystems.is_empty()
+                || self.options.ecosystems.contains(&pkg.ecosystem))
+        };
+
+        let mut packages = Vec::new();
+        for provider in self.registry.providers() {
+            match provider.packages(root) {
+                Ok(found) => {
+                    debug!(
+                        provider = provider.name(),
+                        packages = found.len(),
+                        "Collected provided packages"
+                    );
+                    packages.extend(found.into_iter().filter(selected).map(|mut pkg| {
+                        pkg.provenance = provider.name().to_string();
+                        pkg
+                    }));
+                }
+                Err(e) => {
+                    debug!(provider = provider.name(), error = %e, "Package provider failed");
+                    let _ = errors.send(ScanErrorEntry::new(
+                        PathBuf::from(provider.name()),
+                        None,
+                        &e,
+                    ));
+                }
+            }
+        }
+        packages
+    }
+}
+
+/// Package files, installation directories and provided packages of one
+/// ecosystem, scanned as a unit
+#[derive(Debug, Default)]
+struct EcosystemUnit {
+    files: Vec<DiscoveredFile>,
+    install_dirs: Vec<InstallDir>,
+    provided: Vec<ProvidedPackage>,
+    /// Also read the OS package databases (the OS unit)
+    os_packages: bool,
+}
+
+/// Scan-wide state the ecosystem units share
+struct UnitContext<'a> {
+    source: FileSource<'a>,
+    read_installed: bool,
+    budget: &'a Budget,
+    checkpoint: Option<&'a Checkpointer>,
+    errors: &'a Sender<ScanErrorEntry>,
+}
+
+/// What the scan of one or more ecosystems produced
+#[derive(Debug, Default)]
+struct EcosystemOutput {
+    record_count: usize,
+    installed_count: usize,
+    classified: Vec<ClassifiedDependency>,
+    applications: Vec<Application>,
+    /// Declared records per dependency type
+    dependency_types: ScanSummary,
+    inconsistencies: Vec<VersionInconsistency>,
+    overrides: Vec<VersionOverride>,
+    multiple_versions: Vec<MultipleVersions>,
+    peer_conflicts: Vec<PeerConflict>,
+    incompatible_engines: Vec<EngineIncompatibility>,
+    lockfile_drift: Vec<LockfileDrift>,
+    timings: PhaseTimings,
+}
+
+impl EcosystemOutput {
+    /// Add the output of an ecosystem scanned afterwards
+    fn extend(&mut self, other: EcosystemOutput) {
+        self.record_count += other.record_count;
+        self.installed_count += other.installed_count;
+        self.classified.extend(other.classified);
+        self.applications.extend(other.applications);
+        for (dep_type, count) in other.dependency_types.by_dependency_type {
+            *self
+                .dependency_types
+                .by_dependency_type
+                .entry(dep_type)
+                .or_default() += count;
+        }
+        self.inconsistencies.extend(other.inconsistencies);
+        self.overrides.extend(other.overrides);
+        self.multiple_versions.extend(other.multiple_versions);
+        self.peer_conflicts.extend(other.peer_conflicts);
+        self.incompatible_engines.extend(other.incompatible_engines);
+        self.lockfile_drift.extend(other.lockfile_drift);
+        self.timings.add(&other.timings);
+    }
+}
+
+/// Split the discovered files, installation directories and provided
+/// packages of a scan by ecosystem, in ecosystem order
+fn ecosystem_units(
+    files: &[DiscoveredFile],
+    install_dirs: &[InstallDir],
+    provided: Vec<ProvidedPackage>,
+    os_packages: bool,
+) -> BTreeMap<Ecosystem, EcosystemUnit> {
+    let mut units: BTreeMap<Ecosystem, EcosystemUnit> = BTreeMap::new();
+    for file in files {
+        units
+            .entry(file.ecosystem)
+            .or_default()
+            .files
+            .push(file.clone());
+    }
+    for install_dir in install_dirs {
+        units
+            .entry(install_dir.ecosystem)
+            .or_default()
+            .install_dirs
+            .push(install_dir.clone());
+    }
+    for pkg in provided {
+        units.entry(pkg.ecosystem).or_default().provided.push(pkg);
+    }
+    if os_packages {
+        units.entry(Ecosystem::Os).or_default().os_packages = true;
+    }
+    units
+}
+
+/// Classify records, installed and provided packages and flag version mismatches
+///
+/// With `aggregate`, entries are first merged per package and application so
+/// the flags compare versions across sources.
+fn classify(
+    records: Vec<DependencyRecord>,
+    installed: Vec<InstalledPackage>,
+    provided: Vec<ProvidedPackage>,
+    aggregate: bool,
+) -> Vec<ClassifiedDependency> {
+    let classifier = Classifier::new();
+    let mut classified = classifier.classify(records, installed);
+    classified.extend(classifier.classify_provided(provided));
+    if aggregate {
+        ApplicationLinker::new().assign_application_roots(&mut classified);
+        classified = Aggregator::new().aggregate(classified);
+        debug!(dependencies = classified.len(), "Aggregated dependencies");
+    }
+
+    let version_matcher = VersionMatcher::new();
+    for dep in &mut classified {
+        if let (Some(has_ver), Some(should_ver)) = (
+            dep.get_version(Classification::Has),
+            dep.get_version(Classification::Should),
+        ) {
+            dep.has_version_mismatch =
+                version_matcher.detect_version_mismatch(has_ver, should_ver, dep.ecosystem);
+        }
+
+        // Git, path and workspace dependencies declare no version range
+        if !dep.source_kind.is_registry() {
+            continue;
+        }
+        if let (Some(should_ver), Some(can_range)) = (
+            dep.get_version(Classification::Should),
+            dep.get_version(Classification::Can),
+        ) {
+            dep.has_constraint_violation =
+                version_matcher.detect_constraint_violation(should_ver, can_range, dep.ecosystem);
+        }
+    }
+
+    classified
+}
+
+/// Run the per-application checks and set their flags on the entries
+///
+/// The checks need application roots for declared entries too, so they run
+/// on a copy with roots assigned; the returned entries keep their own roots.
+fn check_applications(classified: &mut [ClassifiedDependency]) -> Vec<VersionInconsistency> {
+    let mut scoped = classified.to_vec();
+    ApplicationLinker::new().assign_application_roots(&mut scoped);
+
+    let inconsistencies = ConsistencyChecker::new().check(&scoped);
+    debug!(
+        inconsistencies = inconsistencies.len(),
+        "Checked version consistency"
+    );
+
+    let phantoms = PhantomDetector::new().mark_phantoms(&mut scoped);
+    debug!(phantoms, "Checked for undeclared installed packages");
+    let missing = MissingInstallationDetector::new().mark_missing(&mut scoped);
+    debug!(
+        missing,
+        "Checked for declared packages that are not installed"
+    );
+
+    let tampered = IntegrityVerifier::new().mark_mismatches(&mut scoped);
+    debug!(
+        tampered,
+        "Checked installed packages against lockfile integrity"
+    );
+
+    for (dep, checked) in classified.iter_mut().zip(&scoped) {
+        dep.is_phantom = checked.is_phantom;
+        dep.missing_installation = checked.missing_installation;
+        dep.integrity_mismatch = checked.integrity_mismatch;
+    }
+
+    inconsistencies
+}
+
+/// Merge the outputs of scans of several roots, tagging each dependency
+/// with its root
+fn merge_outputs(outputs: Vec<ScanOutput>, started_at: SystemTime) -> ScanOutput {
+    let mut merged = ScanOutput::default();
+    let mut dependency_types = ScanSummary::new();
+    let mut ecosystems: BTreeMap<String, EcosystemSummary> = BTreeMap::new();
+    let mut scan_roots = Vec::new();
+    let mut truncated = BTreeSet::new();
+    let git_rev = outputs
+        .first()
+        .and_then(|output| output.metadata.git_rev.clone());
+
+    for mut output in outputs {
+        let root = output.metadata.scan_root.clone();
+        let dependencies = output
+            .applications
+            .iter_mut()
+            .flat_map(|app| app.dependencies.iter_mut())
+            .chain(output.classified.iter_mut());
+        for dep in dependencies {
+            dep.scan_root = Some(root.clone());
+        }
+
+        merged.files.extend(output.files);
+        merged.install_dirs.extend(output.install_dirs);
+        merged.record_count += output.record_count;
+        merged.installed_count += output.installed_count;
+        merged.classified.extend(output.classified);
+        merged.applications.extend(output.applications);
+        merged.timings.merge(&output.timings);
+        merged.errors.extend(output.errors);
+        merged.inconsistencies.extend(output.inconsistencies);
+        merged.overrides.extend(output.overrides);
+        merged.multiple_versions.extend(output.multiple_versions);
+        merged.peer_conflicts.extend(output.peer_conflicts);
+        merged
+            .incompatible_engines
+            .extend(output.incompatible_engines);
+        merged.lockfile_drift.extend(output.lockfile_drift);
+        for (dep_type, count) in output.summary.by_dependency_type {
+            *dependency_types
+                .by_dependency_type
+                .entry(dep_type)
+                .or_default() += count;
+        }
+        for (ecosystem, scan) in &output.summary.ecosystems {
+            ecosystems.entry(ecosystem.clone()).or_default().merge(scan);
+        }
+        truncated.extend(output.metadata.truncated);
+        scan_roots.push(root);
+    }
+    merged
+        .classified
+        .sort_by(ClassifiedDependency::output_order);
+    merged.errors.sort_by(|a, b| a.file.cmp(&b.file));
+
+    let summarizer = Summarizer::new();
+    let mut summary = summarizer.summarize(&merged.classified);
+    summary.files_scanned = merged.files.len();
+    summary.install_dirs_scanned = merged.install_dirs.len();
+    summary.applications = merged.applications.len();
+    summary.by_dependency_type = dependency_types.by_dependency_type;
+    summary.parse_errors = merged.errors.len();
+    summary.version_inconsistencies = merged.inconsistencies.len();
+    summary.multiple_versions = merged.multiple_versions.len();
+    summary.peer_conflicts = merged.peer_conflicts.len();
+    summary.incompatible_engines = merged.incompatible_engines.len();
+    summary.stale_lockfiles = merged.lockfile_drift.len();
+    summary.ecosystems = ecosystems;
+    summarizer.set_timings(&mut summary, &merged.timings);
+    merged.summary = summary;
+
+    let mut metadata = ScanMetadata::new(&scan_roots[0], started_at);
+    metadata.scan_roots = scan_roots;
+    metadata.files_scanned = merged.files.len();
+    metadata.install_dirs_scanned = merged.install_dirs.len();
+    metadata.parse_errors = merged.errors.len();
+    metadata.truncated = truncated.into_iter().collect();
+    metadata.git_rev = git_rev;
+    merged.metadata = metadata;
+    merged
+}
+
+/// Run a parser on a file's content, turning a panic into an error
+///
+/// Without a timeout, the parser runs on the calling worker thread. With one,
+/// it runs on a thread of its own, spawned for the file outside the scan's
+/// pool, and is abandoned when it takes longer; the thread keeps running until
+/// the parser returns, but the scan moves on. That costs a thread per file,
+/// which is why the timeout is opt-in.
+fn parse_isolated(
+    parser: Arc<dyn Parser>,
+    content: FileContent,
+    path: &Path,
+    timeout: Option<Duration>,
+) -> Result<Vec<DependencyRecord>, ScanError> {
+    let Some(timeout) = timeout else {
+        return catch_parse(path, || parser.parse(&content, path));
+    };
+
+    let (sender, receiver) = mpsc::sync_channel(1);
+    let file = path.to_path_buf();
+    thread::Builder::new()
+        .name("scanner-parse".to_string())
+        .spawn(move || {
+            let _ = sender.send(catch_parse(&file, || parser.parse(&content, &file)));
+        })?;
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => {
+            warn!(file = %path.display(), ?timeout, "Parser timed out, skipping file");
+            Err(ScanError::Timeout {
+                file: path.to_path_buf(),
+                timeout,
+            })
+        }
+        Err(RecvTimeoutError::Disconnected) => Err(ScanError::Panic {
+            file: path.to_path_buf(),
+            message: "parser thread exited without a result".to_string(),
+        }),
+    }
+}
+
+/// Run a parser on the file or directory at `path`, turning a panic into an
+/// error
+fn catch_parse<T>(
+    path: &Path,
+    parse: impl FnOnce() -> Result<T, ScanError>,
+) -> Result<T, ScanError> {
+    panic::catch_unwind(AssertUnwindSafe(parse)).unwrap_or_else(|payload| {
+        let message = panic_message(payload.as_ref());
+        warn!(path = %path.display(), %message, "Parser panicked, skipping it");
+        Err(ScanError::Panic {
+            file: path.to_path_buf(),
+            message,
+        })
+    })
+}
+
+/// Text of a panic payload
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkpoint::Checkpoint;
+    use std::fs;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    /// Reporter recording phase events
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<(ScanPhase, Option<usize>, usize)>>,
+    }
+
+    impl ProgressReporter for Recorder {
+        fn phase_started(&self, phase: ScanPhase, total: Option<usize>) {
+            self.events.lock().unwrap().push((phase, total, 0));
+        }
+
+        fn phase_finished(&self, phase: ScanPhase, items: usize, _elapsed: Duration) {
+            self.events.lock().unwrap().push((phase, None, items));
+        }
+    }
+
+    fn create_project(root: &Path) {
+        fs::write(
+            root.join("package.json"),
+            r#"{"name": "app", "dependencies": {"lodash": "^4.17.0"}}"#,
+        )
+        .unwrap();
+        let lodash = root.join("node_modules/lodash");
+        fs::create_dir_all(&lodash).unwrap();
+        fs::write(
+            lodash.join("package.json"),
+            r#"{"name": "lodash", "version": "4.17.21"}"#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_scan_reports_phases() {
+        let temp_dir = TempDir::new().unwrap();
+        create_project(temp_dir.path());
+
+        let recorder = Arc::new(Recorder::default());
+        let output = Scanner::new(ScanOptions::new())
+            .with_progress(recorder.clone())
+            .scan(temp_dir.path())
+            .unwrap();
+
+        assert_eq!(output.files.len(), 1);
+        assert_eq!(output.install_dirs.len(), 1);
+        assert_eq!(output.record_count, 1);
+        assert_eq!(output.installed_count, 1);
+        assert_eq!(output.classified.len(), 2);
+
+        let events = recorder.events.lock().unwrap();
+        assert_eq!(
+            *events,
+            vec![
+                (ScanPhase::Discovery, None, 0),
+                (ScanPhase::Discovery, None, 2),
+                (ScanPhase::DeclaredParsing, Some(1), 0),
+                (ScanPhase::DeclaredParsing, None, 1),
+                (ScanPhase::InstalledParsing, Some(1), 0),
+                (ScanPhase::InstalledParsing, None, 1),
+                (ScanPhase::Analysis, None, 0),
+                (ScanPhase::Analysis, None, 2),
+            ]
+        );
+        assert_eq!(output.timings.iter().count(), 4);
+        assert_eq!(output.summary.files_scanned, 1);
+        assert_eq!(output.summary.by_ecosystem["node"], 2);
+        assert_eq!(output.summary.by_dependency_type["runtime"], 1);
+        assert_eq!(output.summary.durations.len(), 4);
+        assert_eq!(output.summary.parse_errors, 0);
+    }
+
+    #[test]
+    fn test_scan_on_own_thread_pool() {
+        let temp_dir = TempDir::new().unwrap();
+        create_project(temp_dir.path());
+
+        let scanner = Scanner::new(ScanOptions::new()).with_threads(3);
+        assert_eq!(scanner.install(rayon::current_num_threads), 3);
+        assert!(scanner
+            .install(|| thread::current().name().map(str::to_string))
+            .is_some_and(|name| name.starts_with("scanner-")));
+
+        let output = scanner.scan(temp_dir.path()).unwrap();
+        assert_eq!(output.classified.len(), 2);
+        assert_eq!(
+            scanner
+                .scan_roots(&[temp_dir.path().to_path_buf()])
+                .unwrap()
+                .classified
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_scan_declared_only_with_ecosystem() {
+        let temp_dir = TempDir::new().unwrap();
+        create_project(temp_dir.path());
+        fs::write(
+            temp_dir.path().join("requirements.txt"),
+            "requests==2.31.0\n",
+        )
+        .unwrap();
+
+        let options = ScanOptions::new()
+            .with_scan_mode(ScanMode::DeclaredOnly)
+            .with_ecosystem(Ecosystem::Python);
+        let output = Scanner::new(options).scan(temp_dir.path()).unwrap();
+
+        assert_eq!(output.files.len(), 1);
+        assert!(output.install_dirs.is_empty());
+        assert_eq!(output.classified.len(), 1);
+        assert_eq!(output.classified[0].name, "requests");
+        assert_eq!(output.metadata.files_scanned, 1);
+        assert_eq!(output.metadata.scanner_version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn test_scan_lockfiles_only() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        create_project(root);
+        fs::write(
+            root.join("package-lock.json"),
+            r#"{"name": "app", "lockfileVersion": 3, "packages": {
+                "": {"name": "app"},
+                "node_modules/lodash": {"version": "4.17.21"}
+            }}"#,
+        )
+        .unwrap();
+
+        let recorder = Arc::new(Recorder::default());
+        let options = ScanOptions::new().with_scan_mode(ScanMode::LockfilesOnly);
+        let output = Scanner::new(options.clone())
+            .with_progress(recorder.clone())
+            .scan(root)
+            .unwrap();
+
+        assert_eq!(output.files.len(), 1);
+        assert_eq!(output.files[0].filename, "package-lock.json");
+        assert!(output.install_dirs.is_empty());
+        assert_eq!(output.installed_count, 0);
+        assert_eq!(output.classified.len(), 1);
+        assert_eq!(
+            output.classified[0].get_version(Classification::Should),
+            Some("4.17.21")
+        );
+        assert!(output.applications.is_empty());
+        assert!(!recorder
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(phase, _, _)| *phase == ScanPhase::InstalledParsing));
+
+        // Linking is opt-in
+        let output = Scanner::new(options.with_manifest_applications(true))
+            .scan(root)
+            .unwrap();
+        assert_eq!(output.applications.len(), 1);
+        assert_eq!(output.applications[0].name, "app");
+        assert_eq!(output.applications[0].dependencies.len(), 1);
+    }
+
+    #[test]
+    fn test_scan_counts_parse_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("package.json"), "{ not json").unwrap();
+
+        let output = Scanner::new(ScanOptions::new())
+            .scan(temp_dir.path())
+            .unwrap();
+
+        assert_eq!(output.summary.files_scanned, 1);
+        assert_eq!(output.summary.parse_errors, 1);
+        assert_eq!(output.errors.len(), 1);
+        assert_eq!(output.errors[0].ecosystem, Some(Ecosystem::Node));
+        assert_eq!(output.errors[0].kind, crate::models::ScanErrorKind::Parse);
+        assert_eq!(output.summary.total_dependencies, 0);
+    }
+
+    #[test]
+    fn test_scan_skips_binary_files() {
+        let temp_dir = TempDir::new().unwrap();
+        create_project(temp_dir.path());
+        fs::write(temp_dir.path().join("yarn.lock"), b"\x00\x01\x02binary").unwrap();
+        fs::write(
+            temp_dir.path().join("requirements.txt"),
+            b"# d\xe9pendances\nrequests==2.31.0\n",
+        )
+        .unwrap();
+
+        let output = Scanner::new(ScanOptions::new())
+            .scan(temp_dir.path())
+            .unwrap();
+
+        assert!(output.errors.is_empty());
+        assert!(output.classified.iter().any(|d| d.name == "requests"));
+    }
+
+    #[test]
+    fn test_scan_skips_files_over_size_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        create_project(temp_dir.path());
+        fs::write(
+            temp_dir.path().join("requirements.txt"),
+            "requests==2.31.0\n",
+        )
+        .unwrap();
+        let package_json = fs::metadata(temp_dir.path().join("package.json"))
+            .unwrap()
+            .len();
+
+        let output = Scanner::new(ScanOptions::new().with_max_file_size(package_json - 1))
+            .scan(temp_dir.path())
+            .unwrap();
+
+        assert_eq!(output.errors.len(), 1);
+        assert_eq!(output.errors[0].file, temp_dir.path().join("package.json"));
+        assert_eq!(output.errors[0].kind, crate::models::ScanErrorKind::Read);
+        assert!(output.errors[0].reason.contains("too large"));
+        assert!(output.classified.iter().any(|d| d.name == "requests"));
+    }
+
+    #[test]
+    fn test_scan_limits_truncate() {
+        let temp_dir = TempDir::new().unwrap();
+        create_project(temp_dir.path());
+        fs::write(
+            temp_dir.path().join("requirements.txt"),
+            "requests==2.31.0\n",
+        )
+        .unwrap();
+        let underscore = temp_dir.path().join("node_modules/underscore");
+        fs::create_dir_all(&underscore).unwrap();
+        fs::write(
+            underscore.join("package.json"),
+            r#"{"name": "underscore", "version": "1.13.6"}"#,
+        )
+        .unwrap();
+
+        let full = Scanner::new(ScanOptions::new())
+            .scan(temp_dir.path())
+            .unwrap();
+        assert!(!full.metadata.is_truncated());
+        assert_eq!(full.files.len(), 2);
+        assert_eq!(full.installed_count, 2);
+
+        let limits = ScanLimits::new()
+            .with_max_files(1)
+            .with_max_packages_per_dir(1);
+        let output = Scanner::new(ScanOptions::new().with_limits(limits))
+            .scan(temp_dir.path())
+            .unwrap();
+        assert_eq!(output.files.len(), 1);
+        assert_eq!(output.installed_count, 1);
+        assert_eq!(
+            output.metadata.truncated,
+            [ScanLimit::MaxFiles, ScanLimit::MaxPackagesPerDir]
+        );
+
+        let limits = ScanLimits::new()
+            .with_max_install_dirs(0)
+            .with_time_budget(Duration::ZERO);
+        let output = Scanner::new(ScanOptions::new().with_limits(limits))
+            .scan(temp_dir.path())
+            .unwrap();
+        assert!(output.files.is_empty());
+        assert!(output.install_dirs.is_empty());
+        assert_eq!(output.metadata.truncated, [ScanLimit::TimeBudget]);
+    }
+
+    /// Scanner cancelling its scans once a phase finished
+    fn cancelled_after(phase: ScanPhase, options: ScanOptions) -> Scanner {
+        struct CancelAfter(ScanPhase, CancellationToken);
+        impl ProgressReporter for CancelAfter {
+            fn phase_finished(&self, phase: ScanPhase, _items: usize, _elapsed: Duration) {
+                if phase == self.0 {
+                    self.1.cancel();
+                }
+            }
+        }
+        let cancellation = CancellationToken::new();
+        Scanner::new(options)
+            .with_cancellation(cancellation.clone())
+            .with_progress(Arc::new(CancelAfter(phase, cancellation)))
+    }
+
+    #[test]
+    fn test_scan_cancelled() {
+        let temp_dir = TempDir::new().unwrap();
+        create_project(temp_dir.path());
+
+        // Cancelled once discovery is done: the files found are kept,
+        // nothing more is parsed
+        let output = cancelled_after(ScanPhase::Discovery, ScanOptions::new())
+            .scan(temp_dir.path())
+            .unwrap();
+        assert_eq!(output.files.len(), 1);
+        assert_eq!(output.record_count, 0);
+        assert_eq!(output.installed_count, 0);
+        assert_eq!(output.metadata.truncated, [ScanLimit::Cancelled]);
+
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+        let output = Scanner::new(ScanOptions::new())
+            .with_cancellation(cancellation)
+            .scan(temp_dir.path())
+            .unwrap();
+        assert!(output.files.is_empty());
+        assert_eq!(output.metadata.truncated, [ScanLimit::Cancelled]);
+    }
+
+    #[test]
+    fn test_scan_io_throttle() {
+        let temp_dir = TempDir::new().unwrap();
+        create_project(temp_dir.path());
+        let manifest = fs::metadata(temp_dir.path().join("package.json")).unwrap();
+        let installed =
+            fs::metadata(temp_dir.path().join("node_modules/lodash/package.json")).unwrap();
+
+        // Directories, package files and installed packages all count
+        let throttle = IoThrottle::new().with_max_files_per_sec(1000);
+        let output = Scanner::new(ScanOptions::new().with_io_throttle(throttle.clone()))
+            .scan(temp_dir.path())
+            .unwrap();
+        assert_eq!(output.record_count, 1);
+        assert_eq!(output.installed_count, 1);
+        assert!(throttle.files() >= 3);
+        assert_eq!(throttle.bytes(), manifest.len() + installed.len());
+
+        let throttle = IoThrottle::new().with_max_bytes_per_sec(1 << 20);
+        let options = ScanOptions::new()
+            .with_scan_mode(ScanMode::InstalledOnly)
+            .with_io_throttle(throttle.clone());
+        Scanner::new(options).scan(temp_dir.path()).unwrap();
+        assert_eq!(throttle.bytes(), installed.len());
+    }
+
+    #[test]
+    fn test_scan_resumes_from_checkpoint() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("project");
+        fs::create_dir(&root).unwrap();
+        create_project(&root);
+        let checkpoint = temp_dir.path().join("scan.checkpoint");
+        let options = ScanOptions::new().with_checkpoint(&checkpoint, true);
+
+        // Interrupted after the package files were parsed: the checkpoint
+        // keeps the discovery and their results
+        let output = cancelled_after(ScanPhase::DeclaredParsing, options.clone())
+            .scan(&root)
+            .unwrap();
+        assert_eq!(output.record_count, 1);
+        assert_eq!(output.installed_count, 0);
+        let saved = Checkpoint::load(&checkpoint).unwrap();
+        assert_eq!(saved.index.as_ref().unwrap().install_dirs.len(), 1);
+        assert_eq!(saved.files.len(), 1);
+        assert!(saved.install_dirs.is_empty());
+
+        // The resumed scan reuses the discovery, even though a new manifest
+        // appeared, and only parses what is left
+        fs::create_dir(root.join("web")).unwrap();
+        fs::write(root.join("web/package.json"), r#"{"name": "web"}"#).unwrap();
+        let output = Scanner::new(options.clone()).scan(&root).unwrap();
+        assert_eq!(output.files.len(), 1);
+        assert_eq!(output.record_count, 1);
+        assert_eq!(output.installed_count, 1);
+        assert!(!output.metadata.is_truncated());
+        // A completed scan removes its checkpoint
+        assert!(!checkpoint.exists());
+
+        // A file changed since it was checkpointed is parsed again
+        cancelled_after(ScanPhase::DeclaredParsing, options.clone())
+            .scan(&root)
+            .unwrap();
+        fs::write(
+            root.join("package.json"),
+            r#"{"name": "app", "dependencies": {"lodash": "^4.17.0", "express": "^4.0.0"}}"#,
+        )
+        .unwrap();
+        let output = Scanner::new(options.clone()).scan(&root).unwrap();
+        assert_eq!(output.record_count, 2);
+
+        // Without a checkpoint, or from one of other options, the scan
+        // starts over
+        let output = Scanner::new(options.clone()).scan(&root).unwrap();
+        assert_eq!(output.files.len(), 2);
+        assert_eq!(output.record_count, 2);
+        cancelled_after(ScanPhase::DeclaredParsing, options.clone())
+            .scan(&root)
+            .unwrap();
+        let output = Scanner::new(options.with_include_install_dirs(true))
+            .scan(&root)
+            .unwrap();
+        assert_eq!(output.record_count, 2);
+
+        let scanner = Scanner::new(ScanOptions::new().with_checkpoint(&checkpoint, false));
+        let roots = [root.clone(), root.join("web")];
+        assert!(scanner.scan_roots(&roots).is_err());
+    }
+
+    #[test]
+    fn test_scan_os_packages() {
+        let temp_dir = TempDir::new().unwrap();
+        create_project(temp_dir.path());
+        let os_root = TempDir::new().unwrap();
+        let dpkg = os_root.path().join("var/lib/dpkg");
+        fs::create_dir_all(&dpkg).unwrap();
+        fs::write(
+            dpkg.join("status"),
+            "Package: openssl\nStatus: install ok installed\nVersion: 3.0.11-1~deb12u2\n",
+        )
+        .unwrap();
+
+        let options = ScanOptions::new().with_os_packages(os_root.path());
+        let output = Scanner::new(options).scan(temp_dir.path()).unwrap();
+
+        assert_eq!(output.installed_count, 2);
+        let openssl = output
+            .classified
+            .iter()
+            .find(|d| d.ecosystem == Ecosystem::Os)
+            .unwrap();
+        assert_eq!(openssl.name, "openssl");
+        assert_eq!(
+            openssl.get_version(Classification::Has),
+            Some("3.0.11-1~deb12u2")
+        );
+        assert!(openssl.application_root.is_none());
+    }
+
+    /// Plugin parsing `name version` lines of a `vendor.lock`
+    struct VendorLockPlugin;
+
+    struct VendorLockParser;
+
+    impl crate::parsers::Parser for VendorLockParser {
+        fn parse(
+            &self,
+            content: &str,
+            file_path: &Path,
+        ) -> Result<Vec<DependencyRecord>, ScanError> {
+            let source_file: Arc<Path> = Arc::from(file_path);
+            Ok(content
+                .lines()
+                .filter_map(|line| line.split_once(' '))
+                .map(|(name, version)| DependencyRecord {
+                    name: name.into(),
+                    version: version.into(),
+                    source_file: source_file.clone(),
+                    dep_type: DependencyType::Runtime,
+                    ecosystem: Ecosystem::Node,
+                    file_type: crate::models::FileType::Lockfile,
+                    alias: None,
+                    source_kind: crate::models::SourceKind::Registry,
+                    resolved: None,
+                    integrity: None,
+                    dependencies: Vec::new(),
+                    span: None,
+                })
+                .collect())
+        }
+
+        fn ecosystem(&self) -> Ecosystem {
+            Ecosystem::Node
+        }
+
+        fn file_type(&self) -> crate::models::FileType {
+            crate::models::FileType::Lockfile
+        }
+
+        fn filename(&self) -> &str {
+            "vendor.lock"
+        }
+    }
+
+    impl ParserPlugin for VendorLockPlugin {
+        fn name(&self) -> &str {
+            "vendor"
+        }
+
+        fn register(&self, registry: &mut ParserRegistry) {
+            registry.register(Arc::new(VendorLockParser));
+        }
+    }
+
+    #[test]
+    fn test_scan_with_plugin() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("vendor.lock"), "left-pad 1.3.0\n").unwrap();
+
+        let without = Scanner::new(ScanOptions::new())
+            .scan(temp_dir.path())
+            .unwrap();
+        assert!(without.files.is_empty());
+
+        let scanner = Scanner::new(ScanOptions::new()).with_plugin(&VendorLockPlugin);
+        assert_eq!(scanner.registry().plugin_names(), ["vendor"]);
+        let output = scanner.scan(temp_dir.path()).unwrap();
+        assert_eq!(output.files.len(), 1);
+        assert_eq!(output.classified.len(), 1);
+        assert_eq!(output.classified[0].name, "left-pad");
+        assert_eq!(
+            output.classified[0].get_version(Classification::Should),
+            Some("1.3.0")
+        );
+    }
+
+    /// Parser panicking on `broken.lock` and hanging on `slow.lock`
+    struct FaultyParser(&'static str);
+
+    impl crate::parsers::Parser for FaultyParser {
+        fn parse(
+            &self,
+            _content: &str,
+            _file_path: &Path,
+        ) -> Result<Vec<DependencyRecord>, ScanError> {
+            if self.0 == "broken.lock" {
+                panic!("corrupted entry");
+            }
+            thread::sleep(Duration::from_secs(2));
+            Ok(Vec::new())
+        }
+
+        fn ecosystem(&self) -> Ecosystem {
+            Ecosystem::Node
+        }
+
+        fn file_type(&self) -> crate::models::FileType {
+            crate::models::FileType::Lockfile
+        }
+
+        fn filename(&self) -> &str {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_scan_isolates_failing_parsers() {
+        let temp_dir = TempDir::new().unwrap();
+        create_project(temp_dir.path());
+        fs::write(temp_dir.path().join("broken.lock"), "").unwrap();
+        fs::write(temp_dir.path().join("slow.lock"), "").unwrap();
+
+        let options = ScanOptions::new().with_parse_timeout(Duration::from_secs(1));
+        let mut scanner = Scanner::new(options);
+        scanner
+            .registry_mut()
+            .register(Arc::new(FaultyParser("broken.lock")));
+        scanner
+            .registry_mut()
+            .register(Arc::new(FaultyParser("slow.lock")));
+        let output = scanner.scan(temp_dir.path()).unwrap();
+
+        // The other files are still parsed
+        assert_eq!(output.record_count, 1);
+        let mut errors = output.errors.clone();
+        errors.sort_by(|a, b| a.file.cmp(&b.file));
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].kind, crate::models::ScanErrorKind::Panic);
+        assert!(errors[0].reason.contains("corrupted entry"));
+        assert_eq!(errors[1].kind, crate::models::ScanErrorKind::Timeout);
+        assert_eq!(output.summary.parse_errors, 2);
+
+        // Without a timeout, panics are still caught
+        fs::remove_file(temp_dir.path().join("slow.lock")).unwrap();
+        let mut scanner = Scanner::new(ScanOptions::new());
+        scanner
+            .registry_mut()
+            .register(Arc::new(FaultyParser("broken.lock")));
+        let output = scanner.scan(temp_dir.path()).unwrap();
+        assert_eq!(output.errors.len(), 1);
+        assert_eq!(output.errors[0].kind, crate::models::ScanErrorKind::Panic);
+    }
+
+    /// Installed-package parser crashing on every site-packages directory
+    struct CrashingSitePackagesParser;
+
+    impl crate::parsers::InstalledParser for CrashingSitePackagesParser {
+        fn parse_installed(&self, _path: &Path) -> Result<Vec<InstalledPackage>, ScanError> {
+            panic!("corrupted METADATA");
+        }
+
+        fn ecosystem(&self) -> Ecosystem {
+            Ecosystem::Python
+        }
+
+        fn dir_types(&self) -> &[indexer::InstallDirType] {
+            &[indexer::InstallDirType::SitePackages]
+        }
+    }
+
+    #[test]
+    fn test_scan_isolates_failing_installed_parsers() {
+        let temp_dir = TempDir::new().unwrap();
+        create_project(temp_dir.path());
+        fs::write(
+            temp_dir.path().join("requirements.txt"),
+            "requests==2.31.0\n",
+        )
+        .unwrap();
+        fs::create_dir_all(temp_dir.path().join("venv/lib/python3.12/site-packages")).unwrap();
+
+        let mut scanner = Scanner::new(ScanOptions::new());
+        scanner
+            .registry_mut()
+            .register_installed(Arc::new(CrashingSitePackagesParser));
+        let output = scanner.scan(temp_dir.path()).unwrap();
+
+        // Only the directory is lost, not the Python results
+        let python = &output.summary.ecosystems["python"];
+        assert_eq!(python.status, EcosystemStatus::Completed);
+        assert_eq!(python.total_dependencies, 1);
+        assert_eq!(python.parse_errors, 1);
+        assert_eq!(output.errors.len(), 1);
+        assert!(output.errors[0].file.ends_with("site-packages"));
+        assert_eq!(output.errors[0].kind, crate::models::ScanErrorKind::Panic);
+        assert!(output.errors[0].reason.contains("corrupted METADATA"));
+    }
+
+    /// Progress reporter panicking while the Python ecosystem is scanned
+    #[derive(Default)]
+    struct CrashingPythonProgress {
+        current: Mutex<Option<Ecosystem>>,
+    }
+
+    impl ProgressReporter for CrashingPythonProgress {
+        fn ecosystem_started(&self, ecosystem: Ecosystem) {
+            *self.current.lock().unwrap() = Some(ecosystem);
+        }
+
+        fn phase_started(&self, _phase: ScanPhase, _total: Option<usize>) {
+            if *self.current.lock().unwrap() == Some(Ecosystem::Python) {
+                panic!("corrupted METADATA");
+            }
+        }
+    }
+
+    #[test]
+    fn test_scan_isolates_failing_ecosystems() {
+        let temp_dir = TempDir::new().unwrap();
+        create_project(temp_dir.path());
+        fs::write(
+            temp_dir.path().join("requirements.txt"),
+            "requests==2.31.0\n",
+        )
+        .unwrap();
+
+        let output = Scanner::new(ScanOptions::new())
+            .with_progress(Arc::new(CrashingPythonProgress::default()))
+            .scan(temp_dir.path())
+            .unwrap();
+
+        // The Node results are kept
+        assert_eq!(output.classified.len(), 2);
+        assert!(output
+            .classified
+            .iter()
+            .all(|dep| dep.ecosystem == Ecosystem::Node));
+        assert_eq!(output.applications.len(), 1);
+
+        let node = &output.summary.ecosystems["node"];
+        assert_eq!(node.status, EcosystemStatus::Completed);
+        assert_eq!(node.total_dependencies, 2);
+        let python = &output.summary.ecosystems["python"];
+        assert_eq!(python.status, EcosystemStatus::Failed);
+        assert!(python
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("corrupted METADATA"));
+        assert_eq!(python.parse_errors, 1);
+        assert_eq!(output.errors.len(), 1);
+        assert_eq!(output.errors[0].ecosystem, Some(Ecosystem::Python));
+        assert_eq!(output.errors[0].kind, crate::models::ScanErrorKind::Panic);
+
+        // Ecosystems not started before cancellation are skipped
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+        let output = Scanner::new(ScanOptions::new())
+            .with_cancellation(cancellation)
+            .scan(temp_dir.path())
+            .unwrap();
+        assert!(output.classified.is_empty());
+        assert!(output
+            .summary
+            .ecosystems
+            .values()
+            .all(|scan| scan.status == EcosystemStatus::Skipped));
+    }
+
+    struct StaticProvider(Result<Vec<ProvidedPackage>, String>);
+
+    impl PackageProvider for StaticProvider {
+        fn name(&self) -> &str {
+            if self.0.is_ok() {
+                "artifact-cache"
+            } else {
+                "offline-agent"
+            }
+        }
+
+        fn packages(&self, _root: &Path) -> Result<Vec<ProvidedPackage>, ScanError> {
+            self.0.clone().map_err(|message| ScanError::Parse {
+                file: PathBuf::from("agent"),
+                message,
+            })
+        }
+    }
+
+    #[test]
+    fn test_scan_with_providers() {
+        let temp_dir = TempDir::new().unwrap();
+        create_project(temp_dir.path());
+
+        let packages = vec![
+            ProvidedPackage::installed("left-pad", "1.3.0", Ecosystem::Node, "cache://left-pad"),
+            ProvidedPackage::locked("requests", "2.31.0", Ecosystem::Python, "cache://requests"),
+        ];
+        let options = ScanOptions::new().with_ecosystem(Ecosystem::Node);
+        let output = Scanner::new(options)
+            .with_provider(Arc::new(StaticProvider(Ok(packages))))
+            .with_provider(Arc::new(StaticProvider(Err("unreachable".to_string()))))
+            .scan(temp_dir.path())
+            .unwrap();
+
+        let provided: Vec<&ClassifiedDependency> = output
+            .classified
+            .iter()
+            .filter(|dep| dep.provenance.is_some())
+            .collect();
+        assert_eq!(provided.len(), 1);
+        assert_eq!(provided[0].name, "left-pad");
+        assert_eq!(provided[0].provenance.as_deref(), Some("artifact-cache"));
+        assert_eq!(provided[0].get_version(Classification::Has), Some("1.3.0"));
+        assert_eq!(output.errors.len(), 1);
+        assert_eq!(output.errors[0].file, PathBuf::from("offline-agent"));
+    }
+
+    #[test]
+    fn test_scan_roots() {
+        let first = TempDir::new().unwrap();
+        create_project(first.path());
+        let second = TempDir::new().unwrap();
+        fs::write(second.path().join("requirements.txt"), "requests==2.31.0\n").unwrap();
+        fs::write(second.path().join("package.json"), "{ not json").unwrap();
+
+        let roots = [first.path().to_path_buf(), second.path().to_path_buf()];
+        let output = Scanner::new(ScanOptions::new()).scan_roots(&roots).unwrap();
+
+        assert_eq!(output.files.len(), 3);
+        assert_eq!(output.record_count, 2);
+        assert_eq!(output.installed_count, 1);
+        assert_eq!(output.classified.len(), 3);
+        assert_eq!(output.errors.len(), 1);
+        assert_eq!(output.summary.files_scanned, 3);
+        assert_eq!(output.summary.parse_errors, 1);
+        assert_eq!(output.summary.by_dependency_type["runtime"], 2);
+
+        let canonical: Vec<PathBuf> = roots.iter().map(|r| fs::canonicalize(r).unwrap()).collect();
+        assert_eq!(output.metadata.scan_roots, canonical);
+        let requests = output
+            .classified
+            .iter()
+            .find(|d| d.name == "requests")
+            .unwrap();
+        assert_eq!(requests.scan_root.as_ref(), Some(&canonical[1]));
+        assert!(output
+            .classified
+            .iter()
+            .filter(|d| d.name == "lodash")
+            .all(|d| d.scan_root.as_ref() == Some(&canonical[0])));
+
+        // A single root scans as before
+        let single = Scanner::new(ScanOptions::new())
+            .scan_roots(&roots[..1])
+            .unwrap();
+        assert!(single.classified.iter().all(|d| d.scan_root.is_none()));
+        assert!(single.metadata.scan_roots.is_empty());
+    }
+
+    #[test]
+    fn test_scan_git_rev() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path();
+        create_project(repo);
+        for args in [
+            &["init", "-q"][..],
+            &["add", "."],
+            &["commit", "-q", "-m", "initial"],
+        ] {
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(repo)
+                .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+                .args(args)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        }
+        fs::write(
+            repo.join("package.json"),
+            r#"{"name": "app", "dependencies": {"express": "^4.18.0"}}"#,
+        )
+        .unwrap();
+
+        let options = ScanOptions::new()
+            .with_scan_mode(ScanMode::DeclaredOnly)
+            .with_git_rev("HEAD");
+        let output = Scanner::new(options).scan(repo).unwrap();
+
+        assert_eq!(output.files.len(), 1);
+        assert_eq!(output.installed_count, 0);
+        let names: Vec<&str> = output.classified.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["lodash"]);
+        assert_eq!(output.metadata.git_rev.as_ref().map(String::len), Some(40));
+
+        let options = ScanOptions::new().with_git_rev("no-such-rev");
+        let result = Scanner::new(options).scan(repo);
+        assert!(matches!(result, Err(ScanError::Git(_))));
+    }
+
+    #[test]
+    fn test_scan_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("app");
+        fs::create_dir(&project).unwrap();
+        create_project(&project);
+        let archive = temp_dir.path().join("snapshot.tar");
+        let mut builder = tar::Builder::new(fs::File::create(&archive).unwrap());
+        builder.append_dir_all("srv/app", &project).unwrap();
+        builder.finish().unwrap();
+
+        let options = ScanOptions::new().with_archive(true);
+        let output = Scanner::new(options).scan(&archive).unwrap();
+
+        let paths: Vec<_> = output.files.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(paths, [archive.join("srv/app/package.json")]);
+        assert_eq!(output.installed_count, 0);
+        assert_eq!(output.classified.len(), 1);
+        assert_eq!(output.classified[0].name, "lodash");
+        assert!(output.classified[0].has_classification(Classification::Can));
+        assert!(output.errors.is_empty());
+
+        // A directory is not an archive
+        let options = ScanOptions::new().with_archive(true);
+        let result = Scanner::new(options).scan(&project);
+        assert!(matches!(result, Err(ScanError::Archive(_))));
+    }
+
+    #[test]
+    fn test_scan_missing_directory() {
+        let result = Scanner::new(ScanOptions::new()).scan(Path::new("/nonexistent/scan/root"));
+        assert!(matches!(result, Err(ScanError::Io(_))));
+    }
+}
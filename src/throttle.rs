@@ -0,0 +1,219 @@
+//! Rate-limited I/O for scans of production hosts
+//!
+//! A full scan reads every directory of a tree and every package file in it
+//! as fast as the disks allow, which hurts latency-sensitive services on the
+//! same host. An [`IoThrottle`] caps the rate of files and bytes read: the
+//! walk takes one file per directory it lists, and the package file reader
+//! one file and the size of the file per read. Work over the rate sleeps
+//! until its turn, so the scan spreads its reads evenly instead of in bursts.
+//!
+//! A throttle is shared by its clones, so every worker of a scan, and every
+//! scan given a clone, draws on the same rate. Installed-package parsers read
+//! through [`read_text`](crate::parsers::read_text), which takes the throttle
+//! of the current thread (see [`scoped`]); the scanner sets it around each
+//! installation directory it parses.
+//!
+//! [`set_niceness`] lowers the CPU priority of the process. On Linux, the
+//! I/O schedulers derive the I/O priority of threads without an explicit one
+//! from their niceness, so a niced scan also yields the disks to other work.
+
+use std::cell::RefCell;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Cap on the rate of files and bytes read, shared by clones
+#[derive(Debug, Clone, Default)]
+pub struct IoThrottle {
+    max_files_per_sec: Option<u64>,
+    max_bytes_per_sec: Option<u64>,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// Earliest time the next file may be read
+    files_at: Option<Instant>,
+    /// Earliest time the next byte may be read
+    bytes_at: Option<Instant>,
+    files: u64,
+    bytes: u64,
+    waited: Duration,
+}
+
+thread_local! {
+    /// Throttle of the reads made on this thread
+    static CURRENT: RefCell<Option<IoThrottle>> = const { RefCell::new(None) };
+}
+
+impl IoThrottle {
+    /// Create a throttle without limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read at most this many files per second
+    pub fn with_max_files_per_sec(mut self, max: u64) -> Self {
+        self.max_files_per_sec = Some(max).filter(|&max| max > 0);
+        self
+    }
+
+    /// Read at most this many bytes per second
+    pub fn with_max_bytes_per_sec(mut self, max: u64) -> Self {
+        self.max_bytes_per_sec = Some(max).filter(|&max| max > 0);
+        self
+    }
+
+    /// Check if the throttle limits anything
+    pub fn is_limited(&self) -> bool {
+        self.max_files_per_sec.is_some() || self.max_bytes_per_sec.is_some()
+    }
+
+    /// Wait until `files` files of `bytes` bytes in total may be read
+    pub fn acquire(&self, files: u64, bytes: u64) {
+        let start = {
+            let mut state = self.state.lock().unwrap();
+            state.files += files;
+            state.bytes += bytes;
+            let now = Instant::now();
+            let files_start = reserve(&mut state.files_at, now, files, self.max_files_per_sec);
+            let bytes_start = reserve(&mut state.bytes_at, now, bytes, self.max_bytes_per_sec);
+            let start = files_start.max(bytes_start);
+            state.waited += start - now;
+            start
+        };
+        let now = Instant::now();
+        if start > now {
+            thread::sleep(start - now);
+        }
+    }
+
+    /// Number of files read through the throttle
+    pub fn files(&self) -> u64 {
+        self.state.lock().unwrap().files
+    }
+
+    /// Number of bytes read through the throttle
+    pub fn bytes(&self) -> u64 {
+        self.state.lock().unwrap().bytes
+    }
+
+    /// Total time reads were held back
+    pub fn waited(&self) -> Duration {
+        self.state.lock().unwrap().waited
+    }
+
+    /// Throttle of the reads made on this thread, if one is set
+    pub fn current() -> Option<IoThrottle> {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+}
+
+/// Throttles are equal when they are clones of each other
+impl PartialEq for IoThrottle {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+impl Eq for IoThrottle {}
+
+/// Schedule `amount` units at `rate` per second from the earliest free time
+/// of a resource, returning when they may start
+fn reserve(next: &mut Option<Instant>, now: Instant, amount: u64, rate: Option<u64>) -> Instant {
+    let Some(rate) = rate else {
+        return now;
+    };
+    let start = next.map_or(now, |next| next.max(now));
+    *next = Some(start + Duration::from_secs_f64(amount as f64 / rate as f64));
+    start
+}
+
+/// Run `f` with `throttle` as the throttle of the reads made on this thread
+///
+/// Work that `f` hands to other threads (rayon tasks) takes its throttle
+/// along by calling [`IoThrottle::current`] and `scoped` again there.
+pub fn scoped<T>(throttle: Option<&IoThrottle>, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<IoThrottle>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CURRENT.with(|current| *current.borrow_mut() = self.0.take());
+        }
+    }
+
+    let previous = CURRENT.with(|current| current.replace(throttle.cloned()));
+    let _restore = Restore(previous);
+    f()
+}
+
+/// Set the niceness of the process (0 to 19, higher yields more)
+///
+/// Threads started afterwards inherit it, so call it before the thread pool
+/// is built.
+#[cfg(unix)]
+pub fn set_niceness(niceness: i32) -> io::Result<()> {
+    // SAFETY: plain system call on the calling process
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, niceness) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Set the niceness of the process (unsupported on this platform)
+#[cfg(not(unix))]
+pub fn set_niceness(_niceness: i32) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "niceness is only supported on Unix",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_throttle_rates() {
+        // 10 files at 100 per second: the last may start after 90ms
+        let throttle = IoThrottle::new().with_max_files_per_sec(100);
+        let started = Instant::now();
+        for _ in 0..10 {
+            throttle.acquire(1, 0);
+        }
+        assert!(started.elapsed() >= Duration::from_millis(90));
+        assert_eq!(throttle.files(), 10);
+
+        // 3 reads of 50 KB at 1 MB per second: at least 100ms
+        let throttle = IoThrottle::new().with_max_bytes_per_sec(1_000_000);
+        let clone = throttle.clone();
+        let started = Instant::now();
+        for _ in 0..3 {
+            clone.acquire(1, 50_000);
+        }
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        assert_eq!(throttle.bytes(), 150_000);
+        assert!(throttle.waited() >= Duration::from_millis(50));
+
+        // Without limits nothing waits
+        let throttle = IoThrottle::new().with_max_files_per_sec(0);
+        assert!(!throttle.is_limited());
+        for _ in 0..1000 {
+            throttle.acquire(1, 1 << 20);
+        }
+        assert_eq!(throttle.waited(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_scoped_throttle() {
+        let throttle = IoThrottle::new().with_max_files_per_sec(10);
+        assert!(IoThrottle::current().is_none());
+        scoped(Some(&throttle), || {
+            assert_eq!(IoThrottle::current(), Some(throttle.clone()));
+            scoped(None, || assert!(IoThrottle::current().is_none()));
+            assert!(IoThrottle::current().is_some());
+        });
+        assert!(IoThrottle::current().is_none());
+    }
+}
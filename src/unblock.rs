@@ -0,0 +1,237 @@
+//! Blocking work awaited from async code, on any runtime
+//!
+//! Registry lookups and report pushes use blocking HTTP, and the analyzers
+//! built on them run the requests in parallel on rayon. Their `_async`
+//! variants hand the blocking call to [`unblock`], which queues it on a
+//! shared pool of worker threads and returns a future completing with its
+//! result, so async services await them without blocking their executor.
+//! The future only relies on [`Waker`], not on a particular runtime: it
+//! works the same under tokio, async-std or smol, and the synchronous
+//! methods stay the API for everyone else.
+//!
+//! The pool starts threads as work queues up, up to [`MAX_THREADS`], and
+//! stops them after [`IDLE_TIMEOUT`] without work. Past that many calls in
+//! flight, the others wait in the queue: awaiting thousands of lookups at
+//! once holds no more threads than a few. If no thread can be started at
+//! all, the function runs inline on the calling thread instead, with a
+//! warning.
+//!
+//! Dropping the future before it completes does not stop the work; its
+//! result is discarded.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::Duration;
+use tracing::warn;
+
+/// Most threads the pool runs blocking work on at once
+pub const MAX_THREADS: usize = 16;
+
+/// Time a pool thread waits for work before it stops
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Worker threads shared by every [`unblock`] call
+#[derive(Default)]
+struct Pool {
+    state: Mutex<PoolState>,
+    available: Condvar,
+}
+
+#[derive(Default)]
+struct PoolState {
+    queue: VecDeque<Job>,
+    threads: usize,
+    idle: usize,
+}
+
+impl Pool {
+    /// The pool of the process
+    fn global() -> &'static Pool {
+        static POOL: OnceLock<Pool> = OnceLock::new();
+        POOL.get_or_init(Pool::default)
+    }
+
+    /// Queue a job, starting a thread for it when every thread is busy
+    fn submit(&'static self, job: Job) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.queue.push_back(job);
+        if state.queue.len() <= state.idle || state.threads >= MAX_THREADS {
+            drop(state);
+            self.available.notify_one();
+            return;
+        }
+
+        state.threads += 1;
+        let spawned = thread::Builder::new()
+            .name("scanner-unblock".to_string())
+            .spawn(move || self.work());
+        if let Err(e) = spawned {
+            state.threads -= 1;
+            // Other threads will get to the job; without any, run it here
+            if state.threads == 0 {
+                let job = state.queue.pop_back();
+                drop(state);
+                warn!(error = %e, "could not spawn a thread for blocking work; running it inline");
+                if let Some(job) = job {
+                    job();
+                }
+            }
+        }
+    }
+
+    /// Run queued jobs until none comes for [`IDLE_TIMEOUT`]
+    fn work(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            if let Some(job) = state.queue.pop_front() {
+                drop(state);
+                job();
+                state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+                continue;
+            }
+            state.idle += 1;
+            let (next, timeout) = self
+                .available
+                .wait_timeout(state, IDLE_TIMEOUT)
+                .unwrap_or_else(|e| e.into_inner());
+            state = next;
+            state.idle -= 1;
+            if timeout.timed_out() && state.queue.is_empty() {
+                state.threads -= 1;
+                return;
+            }
+        }
+    }
+}
+
+/// Run a blocking function on the shared worker pool, returning a future of
+/// its result
+///
+/// A panic in the function is resumed when the future is polled. When no
+/// thread can be started, the function runs on the calling thread before
+/// `unblock` returns.
+pub fn unblock<T, F>(f: F) -> Unblock<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let shared = Arc::new(Mutex::new(Shared {
+        result: None,
+        waker: None,
+    }));
+    let worker = Arc::clone(&shared);
+    Pool::global().submit(Box::new(move || {
+        let result = panic::catch_unwind(AssertUnwindSafe(f));
+        let mut shared = worker.lock().unwrap_or_else(|e| e.into_inner());
+        shared.result = Some(result);
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    }));
+    Unblock { shared }
+}
+
+/// Future of the result of a function run by [`unblock`]
+#[must_use = "futures do nothing unless awaited"]
+pub struct Unblock<T> {
+    shared: Arc<Mutex<Shared<T>>>,
+}
+
+struct Shared<T> {
+    result: Option<thread::Result<T>>,
+    waker: Option<Waker>,
+}
+
+impl<T> Future for Unblock<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut shared = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+        match shared.result.take() {
+            Some(Ok(value)) => Poll::Ready(value),
+            Some(Err(payload)) => panic::resume_unwind(payload),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Unblock<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Unblock").finish_non_exhaustive()
+    }
+}
+
+/// Minimal executor for tests: poll a future on the current thread until it
+/// completes
+#[cfg(test)]
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    use std::task::Wake;
+
+    struct Unpark(thread::Thread);
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(Unpark(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+        if let Poll::Ready(value) = future.as_mut().poll(&mut cx) {
+            return value;
+        }
+        thread::park();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_unblock() {
+        let value = block_on(unblock(|| {
+            thread::sleep(Duration::from_millis(20));
+            thread::current().name().map(str::to_string)
+        }));
+        assert_eq!(value.as_deref(), Some("scanner-unblock"));
+
+        let both = block_on(async { unblock(|| 1).await + unblock(|| 2).await });
+        assert_eq!(both, 3);
+    }
+
+    #[test]
+    fn test_unblock_bounded_threads() {
+        use std::collections::HashSet;
+
+        // Many calls in flight share at most MAX_THREADS threads
+        let futures: Vec<_> = (0..MAX_THREADS * 4)
+            .map(|_| {
+                unblock(|| {
+                    thread::sleep(Duration::from_millis(5));
+                    thread::current().id()
+                })
+            })
+            .collect();
+        let threads: HashSet<_> = futures.into_iter().map(block_on).collect();
+        assert!(threads.len() <= MAX_THREADS);
+    }
+
+    #[test]
+    fn test_unblock_panic() {
+        let result = panic::catch_unwind(|| block_on(unblock(|| panic!("registry down"))));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,137 @@
+//! Versions of any ecosystem
+//!
+//! [`AnyVersion`] parses a version with the rules of its ecosystem, so
+//! versions that differ only in formatting compare equal: Python `1.0` and
+//! `1.0.0`, npm `v1.2.3` and `1.2.3`, Cargo `1.0.0+build` and `1.0.0`, dpkg
+//! `0:1.2-1` and `1.2-1`.
+//! Versions the ecosystem cannot parse (git revisions, `latest`, local
+//! paths) are kept as trimmed text.
+
+use super::{NodeVersion, OsVersion, PythonVersion, RustVersion};
+use crate::models::{Ecosystem, ScanError};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A version parsed with the rules of its ecosystem
+#[derive(Debug, Clone)]
+pub enum AnyVersion {
+    /// npm semver version
+    Node(NodeVersion),
+    /// PEP 440 version
+    Python(PythonVersion),
+    /// Cargo semver version
+    Rust(RustVersion),
+    /// dpkg or rpm version
+    Os(OsVersion),
+    /// Version the ecosystem cannot parse, compared as text
+    Other(String),
+}
+
+impl AnyVersion {
+    /// Parse a version of an ecosystem
+    pub fn parse(version: &str, ecosystem: Ecosystem) -> Result<Self, ScanError> {
+        Ok(match ecosystem {
+            Ecosystem::Node => AnyVersion::Node(NodeVersion::parse(version)?),
+            Ecosystem::Python => AnyVersion::Python(PythonVersion::parse(version)?),
+            Ecosystem::Rust => AnyVersion::Rust(RustVersion::parse(version)?),
+            Ecosystem::Os => AnyVersion::Os(OsVersion::parse(version)?),
+        })
+    }
+
+    /// Parse a version, keeping it as text if the ecosystem cannot parse it
+    pub fn parse_lenient(version: &str, ecosystem: Ecosystem) -> Self {
+        Self::parse(version, ecosystem)
+            .unwrap_or_else(|_| AnyVersion::Other(version.trim().to_string()))
+    }
+
+    /// Get the version string as written
+    pub fn as_str(&self) -> &str {
+        match self {
+            AnyVersion::Node(v) => v.as_str(),
+            AnyVersion::Python(v) => v.as_str(),
+            AnyVersion::Rust(v) => v.as_str(),
+            AnyVersion::Os(v) => v.as_str(),
+            AnyVersion::Other(v) => v,
+        }
+    }
+
+    /// Normalized form; versions that compare equal normalize alike
+    pub fn normalized(&self) -> String {
+        match self {
+            AnyVersion::Node(v) => v.version().to_string(),
+            AnyVersion::Python(v) => v.canonical(),
+            AnyVersion::Rust(v) => v.version().to_string(),
+            AnyVersion::Os(v) => v.canonical(),
+            AnyVersion::Other(v) => v.clone(),
+        }
+    }
+}
+
+impl PartialEq for AnyVersion {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (AnyVersion::Node(a), AnyVersion::Node(b)) => a == b,
+            (AnyVersion::Python(a), AnyVersion::Python(b)) => a == b,
+            (AnyVersion::Rust(a), AnyVersion::Rust(b)) => a == b,
+            (AnyVersion::Os(a), AnyVersion::Os(b)) => a == b,
+            _ => self.normalized() == other.normalized(),
+        }
+    }
+}
+
+/// Versions of different ecosystems, or unparsed ones, are unordered
+impl PartialOrd for AnyVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (AnyVersion::Node(a), AnyVersion::Node(b)) => Some(a.cmp(b)),
+            (AnyVersion::Python(a), AnyVersion::Python(b)) => Some(a.cmp(b)),
+            (AnyVersion::Rust(a), AnyVersion::Rust(b)) => Some(a.cmp(b)),
+            (AnyVersion::Os(a), AnyVersion::Os(b)) => Some(a.cmp(b)),
+            _ => (self == other).then_some(Ordering::Equal),
+        }
+    }
+}
+
+impl fmt::Display for AnyVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(version: &str, ecosystem: Ecosystem) -> AnyVersion {
+        AnyVersion::parse_lenient(version, ecosystem)
+    }
+
+    #[test]
+    fn test_formatting_differences_compare_equal() {
+        assert_eq!(v("1.0", Ecosystem::Python), v("1.0.0", Ecosystem::Python));
+        assert_eq!(
+            v("2.0-RC1", Ecosystem::Python),
+            v("2.0rc1", Ecosystem::Python)
+        );
+        assert_eq!(v("v1.2.3", Ecosystem::Node), v("1.2.3", Ecosystem::Node));
+        assert_eq!(
+            v("1.0.0+build.1", Ecosystem::Rust),
+            v("1.0.0", Ecosystem::Rust)
+        );
+        assert_ne!(v("1.2.3", Ecosystem::Node), v("1.2.4", Ecosystem::Node));
+        assert!(v("1.9.0", Ecosystem::Node) < v("1.10.0", Ecosystem::Node));
+
+        assert_eq!(v("v1.2.3", Ecosystem::Node).normalized(), "1.2.3");
+        assert_eq!(v("1.10.0", Ecosystem::Python).normalized(), "1.10");
+    }
+
+    #[test]
+    fn test_unparseable_versions_compare_as_text() {
+        let git = v(" github:user/repo#abc ", Ecosystem::Node);
+        assert!(matches!(git, AnyVersion::Other(_)));
+        assert_eq!(git, v("github:user/repo#abc", Ecosystem::Node));
+        assert_ne!(git, v("1.0.0", Ecosystem::Node));
+        assert_eq!(git.partial_cmp(&v("1.0.0", Ecosystem::Node)), None);
+        assert!(AnyVersion::parse("latest", Ecosystem::Python).is_err());
+    }
+}
@@ -1,9 +1,13 @@
 //! Version handling for different ecosystems
 
+pub mod any_version;
 pub mod node_semver;
+pub mod os_version;
 pub mod python_pep440;
 pub mod rust_semver;
 
+pub use any_version::AnyVersion;
 pub use node_semver::NodeVersion;
-pub use python_pep440::PythonVersion;
-pub use rust_semver::RustVersion;
+pub use os_version::OsVersion;
+pub use python_pep440::{PythonVersion, SpecifierSet};
+pub use rust_semver::{RustVersion, RustVersionReq};
@@ -4,17 +4,32 @@
 //! Future: integrate node-semver crate for full npm compatibility.
 
 use crate::models::ScanError;
+use semver::{BuildMetadata, Version};
+use std::cmp::Ordering;
+use std::fmt;
 
 /// Node.js version wrapper
+///
+/// Accepts the loose forms npm prints (`v1.2.3`, `=1.2.3`); build metadata
+/// is ignored when comparing.
+#[derive(Debug, Clone)]
 pub struct NodeVersion {
     raw: String,
+    version: Version,
 }
 
 impl NodeVersion {
     /// Parse a Node.js version string
-    pub fn parse(version: &str) -> Result<Self, String> {
+    pub fn parse(version: &str) -> Result<Self, ScanError> {
+        let raw = version.trim();
+        let stripped = raw.trim_start_matches(['v', 'V', '=']).trim_start();
+        let mut parsed = Version::parse(stripped).map_err(|e| {
+            ScanError::VersionParse(format!("Invalid npm version {}: {}", version, e))
+        })?;
+        parsed.build = BuildMetadata::EMPTY;
         Ok(Self {
-            raw: version.to_string(),
+            raw: raw.to_string(),
+            version: parsed,
         })
     }
 
@@ -22,11 +37,44 @@ impl NodeVersion {
     pub fn as_str(&self) -> &str {
         &self.raw
     }
+
+    /// The parsed version, without prefix and build metadata
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+}
+
+impl Ord for NodeVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.version.cmp(&other.version)
+    }
+}
+
+impl PartialOrd for NodeVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for NodeVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.version == other.version
+    }
+}
+
+impl Eq for NodeVersion {}
+
+impl fmt::Display for NodeVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.raw)
+    }
 }
 
 /// Check if a version satisfies a range
 ///
 /// This is a simplified implementation. For production use, integrate node-semver crate.
+/// Range versions may be partial (`>=18`, `^4`, `18.x`), as in `engines`
+/// fields: missing components match any value.
 pub fn satisfies(version: &str, range: &str) -> Result<bool, ScanError> {
     // Simplified version matching
     let version = version.trim();
@@ -37,46 +85,109 @@ pub fn satisfies(version: &str, range: &str) -> Result<bool, ScanError> {
         return Ok(true);
     }
 
+    // Handle wildcard (*)
+    if range == "*" || range == "x" || range == "X" {
+        return Ok(true);
+    }
+
     // Parse version components
     let version_parts = parse_version_parts(version)?;
 
     // Handle caret ranges (^1.2.3 allows >=1.2.3 <2.0.0)
     if let Some(range_version) = range.strip_prefix('^') {
-        let range_parts = parse_version_parts(range_version)?;
+        let (range_parts, _) = parse_range_parts(range_version.trim())?;
         return Ok(version_parts.0 == range_parts.0
             && (version_parts.1 > range_parts.1
                 || (version_parts.1 == range_parts.1 && version_parts.2 >= range_parts.2)));
     }
 
-    // Handle tilde ranges (~1.2.3 allows >=1.2.3 <1.3.0)
+    // Handle tilde ranges (~1.2.3 allows >=1.2.3 <1.3.0, ~1 allows <2.0.0)
     if let Some(range_version) = range.strip_prefix('~') {
-        let range_parts = parse_version_parts(range_version)?;
+        let (range_parts, precision) = parse_range_parts(range_version.trim())?;
         return Ok(version_parts.0 == range_parts.0
-            && version_parts.1 == range_parts.1
-            && version_parts.2 >= range_parts.2);
+            && (precision < 2
+                || (version_parts.1 == range_parts.1 && version_parts.2 >= range_parts.2)));
     }
 
     // Handle >= ranges
     if let Some(stripped) = range.strip_prefix(">=") {
-        let range_version = &stripped.trim();
-        let range_parts = parse_version_parts(range_version)?;
+        let (range_parts, _) = parse_range_parts(stripped.trim())?;
         return Ok(version_parts >= range_parts);
     }
 
-    // Handle > ranges
+    // Handle > ranges (>1.2 is above every 1.2.x)
     if let Some(stripped) = range.strip_prefix('>') {
-        let range_version = &stripped.trim();
-        let range_parts = parse_version_parts(range_version)?;
-        return Ok(version_parts > range_parts);
+        let (range_parts, precision) = parse_range_parts(stripped.trim())?;
+        return Ok(match precision {
+            3 => version_parts > range_parts,
+            _ => version_parts >= upper_bound(range_parts, precision),
+        });
     }
 
-    // Handle wildcard (*)
-    if range == "*" || range == "x" || range == "X" {
-        return Ok(true);
+    // Handle <= ranges (<=1.2 includes every 1.2.x)
+    if let Some(stripped) = range.strip_prefix("<=") {
+        let (range_parts, precision) = parse_range_parts(stripped.trim())?;
+        return Ok(match precision {
+            3 => version_parts <= range_parts,
+            _ => version_parts < upper_bound(range_parts, precision),
+        });
+    }
+
+    // Handle < ranges
+    if let Some(stripped) = range.strip_prefix('<') {
+        let (range_parts, _) = parse_range_parts(stripped.trim())?;
+        return Ok(version_parts < range_parts);
+    }
+
+    // Handle partial versions (1.2 and 1.2.x allow >=1.2.0 <1.3.0)
+    let exact = range.trim_start_matches(['v', '=']).trim_start();
+    if let Ok((range_parts, precision)) = parse_range_parts(exact) {
+        if precision < 3 {
+            return Ok(
+                version_parts >= range_parts && version_parts < upper_bound(range_parts, precision)
+            );
+        }
     }
 
     // Default: exact match
-    Ok(version == range)
+    Ok(version == exact)
+}
+
+/// Compare two versions by their numeric components
+pub fn compare(a: &str, b: &str) -> Result<std::cmp::Ordering, ScanError> {
+    Ok(parse_version_parts(a.trim())?.cmp(&parse_version_parts(b.trim())?))
+}
+
+/// Parse a possibly partial range version (`1`, `1.2`, `1.x`) into its
+/// lowest version and the number of components given
+fn parse_range_parts(version: &str) -> Result<((u32, u32, u32), usize), ScanError> {
+    let parts: Vec<&str> = version
+        .split('.')
+        .take_while(|part| !matches!(*part, "x" | "X" | "*"))
+        .collect();
+    match parts.len() {
+        0 => Ok(((0, 0, 0), 0)),
+        1 | 2 => {
+            let mut numbers = [0; 2];
+            for (number, part) in numbers.iter_mut().zip(&parts) {
+                *number = part.parse::<u32>().map_err(|_| ScanError::Parse {
+                    file: std::path::PathBuf::from("version"),
+                    message: format!("Invalid version format: {}", version),
+                })?;
+            }
+            Ok(((numbers[0], numbers[1], 0), parts.len()))
+        }
+        _ => Ok((parse_version_parts(version)?, 3)),
+    }
+}
+
+/// Lowest version above every version matching a partial one
+fn upper_bound(parts: (u32, u32, u32), precision: usize) -> (u32, u32, u32) {
+    match precision {
+        0 => (u32::MAX, u32::MAX, u32::MAX),
+        1 => (parts.0 + 1, 0, 0),
+        _ => (parts.0, parts.1 + 1, 0),
+    }
 }
 
 fn parse_version_parts(version: &str) -> Result<(u32, u32, u32), ScanError> {
@@ -110,3 +221,25 @@ fn parse_version_parts(version: &str) -> Result<(u32, u32, u32), ScanError> {
 
     Ok((major, minor, patch))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_satisfies_partial_ranges() {
+        assert!(satisfies("18.19.0", ">=18").unwrap());
+        assert!(!satisfies("16.20.2", ">=18").unwrap());
+        assert!(satisfies("5.0.0", ">4.2").unwrap());
+        assert!(!satisfies("4.2.9", ">4.2").unwrap());
+        assert!(satisfies("4.9.0", "<=4").unwrap());
+        assert!(!satisfies("5.0.0", "<=4").unwrap());
+        assert!(satisfies("4.3.0", "^4").unwrap());
+        assert!(!satisfies("5.0.0", "^4").unwrap());
+        assert!(satisfies("1.9.0", "~1").unwrap());
+        assert!(satisfies("1.2.5", "1.2.x").unwrap());
+        assert!(!satisfies("1.3.0", "1.2").unwrap());
+        assert!(satisfies("1.2.3", "=1.2.3").unwrap());
+        assert!(satisfies("1.2.3", "1.x.x").unwrap());
+    }
+}
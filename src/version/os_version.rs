@@ -0,0 +1,240 @@
+//! Operating system package versions
+//!
+//! dpkg and rpm versions have the form `[epoch:]upstream[-revision]` and are
+//! compared with the dpkg algorithm: the epoch numerically, then the upstream
+//! version and revision as alternating runs of non-digits (letters sort before
+//! other characters, `~` before everything, even the end) and digits
+//! (numerically). rpm orders the common cases the same way. Requirements use
+//! the dpkg operators (`>=`, `<=`, `>>`, `<<`, `=`) and the usual `>`, `<`,
+//! `==` and `!=`; a bare version requires that exact version.
+
+use crate::models::ScanError;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// A dpkg or rpm package version
+#[derive(Debug, Clone)]
+pub struct OsVersion {
+    raw: String,
+    epoch: u64,
+    upstream: String,
+    revision: String,
+}
+
+impl OsVersion {
+    /// Parse an OS package version string
+    pub fn parse(version: &str) -> Result<Self, ScanError> {
+        let raw = version.trim();
+        let invalid = |reason: &str| {
+            ScanError::VersionParse(format!(
+                "Invalid OS package version {}: {}",
+                version, reason
+            ))
+        };
+        if raw.is_empty() || raw.contains(char::is_whitespace) {
+            return Err(invalid("expected [epoch:]upstream[-revision]"));
+        }
+
+        let (epoch, rest) = match raw.split_once(':') {
+            Some((epoch, rest)) => (
+                epoch
+                    .parse()
+                    .map_err(|_| invalid("epoch is not a number"))?,
+                rest,
+            ),
+            None => (0, raw),
+        };
+        let (upstream, revision) = rest.rsplit_once('-').unwrap_or((rest, ""));
+        if upstream.is_empty() {
+            return Err(invalid("empty upstream version"));
+        }
+
+        Ok(Self {
+            raw: raw.to_string(),
+            epoch,
+            upstream: upstream.to_string(),
+            revision: revision.to_string(),
+        })
+    }
+
+    /// Get the raw version string
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    /// Epoch, 0 when absent
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Version without a zero epoch (`0:1.2-1` → `1.2-1`)
+    pub fn canonical(&self) -> String {
+        let mut canonical = if self.epoch > 0 {
+            format!("{}:{}", self.epoch, self.upstream)
+        } else {
+            self.upstream.clone()
+        };
+        if !self.revision.is_empty() {
+            canonical.push('-');
+            canonical.push_str(&self.revision);
+        }
+        canonical
+    }
+}
+
+impl Ord for OsVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.epoch
+            .cmp(&other.epoch)
+            .then_with(|| compare_part(&self.upstream, &other.upstream))
+            .then_with(|| compare_part(&self.revision, &other.revision))
+    }
+}
+
+impl PartialOrd for OsVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for OsVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OsVersion {}
+
+impl FromStr for OsVersion {
+    type Err = ScanError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for OsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+/// Sort weight of a non-digit character, with 0 for the end of the run
+fn weight(c: Option<char>) -> i64 {
+    match c {
+        None => 0,
+        Some('~') => -1,
+        Some(c) if c.is_ascii_alphabetic() => c as i64,
+        Some(c) => c as i64 + 256,
+    }
+}
+
+/// Compare upstream versions or revisions with the dpkg algorithm
+fn compare_part(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    while !a.is_empty() || !b.is_empty() {
+        // Non-digit runs compare character by character
+        let a_len = a.find(|c: char| c.is_ascii_digit()).unwrap_or(a.len());
+        let b_len = b.find(|c: char| c.is_ascii_digit()).unwrap_or(b.len());
+        let (mut a_text, mut b_text) = (a[..a_len].chars(), b[..b_len].chars());
+        loop {
+            let (x, y) = (a_text.next(), b_text.next());
+            if x.is_none() && y.is_none() {
+                break;
+            }
+            match weight(x).cmp(&weight(y)) {
+                Ordering::Equal => {}
+                unequal => return unequal,
+            }
+        }
+        a = &a[a_len..];
+        b = &b[b_len..];
+
+        // Digit runs compare numerically, leading zeros ignored
+        let a_len = a.find(|c: char| !c.is_ascii_digit()).unwrap_or(a.len());
+        let b_len = b.find(|c: char| !c.is_ascii_digit()).unwrap_or(b.len());
+        let a_num = a[..a_len].trim_start_matches('0');
+        let b_num = b[..b_len].trim_start_matches('0');
+        match a_num.len().cmp(&b_num.len()).then_with(|| a_num.cmp(b_num)) {
+            Ordering::Equal => {}
+            unequal => return unequal,
+        }
+        a = &a[a_len..];
+        b = &b[b_len..];
+    }
+    Ordering::Equal
+}
+
+/// Check if a version satisfies a single requirement; `*` and an empty
+/// string match every version
+pub fn satisfies(version: &str, requirement: &str) -> Result<bool, ScanError> {
+    let requirement = requirement.trim();
+    if requirement.is_empty() || requirement == "*" {
+        return Ok(true);
+    }
+    let version = OsVersion::parse(version)?;
+
+    let operators = [">=", "<=", ">>", "<<", "==", "!=", "=", ">", "<"];
+    let (operator, required) = operators
+        .iter()
+        .find_map(|op| Some((*op, requirement.strip_prefix(op)?)))
+        .unwrap_or(("=", requirement));
+    let ordering = version.cmp(&OsVersion::parse(required)?);
+
+    Ok(match operator {
+        ">=" => ordering != Ordering::Less,
+        "<=" => ordering != Ordering::Greater,
+        ">>" | ">" => ordering == Ordering::Greater,
+        "<<" | "<" => ordering == Ordering::Less,
+        "!=" => ordering != Ordering::Equal,
+        _ => ordering == Ordering::Equal,
+    })
+}
+
+/// Compare two versions in dpkg order
+pub fn compare(a: &str, b: &str) -> Result<Ordering, ScanError> {
+    Ok(OsVersion::parse(a)?.cmp(&OsVersion::parse(b)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        let cases = [
+            ("1.0", "1.0", Ordering::Equal),
+            ("1.0-1", "1.0-2", Ordering::Less),
+            ("1.10", "1.9", Ordering::Greater),
+            ("1.0~rc1", "1.0", Ordering::Less),
+            ("1.0a", "1.0", Ordering::Greater),
+            ("1:0.9", "2.0", Ordering::Greater),
+            ("0:1.2-1", "1.2-1", Ordering::Equal),
+            ("3.0.2-0ubuntu1.10", "3.0.2-0ubuntu1.9", Ordering::Greater),
+            ("1.001", "1.1", Ordering::Equal),
+            ("2.34-60.el9", "2.34-100.el9", Ordering::Less),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(compare(a, b).unwrap(), expected, "{} vs {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_satisfies() {
+        assert!(satisfies("3.0.2-0ubuntu1.10", ">=3.0.2-0ubuntu1.9").unwrap());
+        assert!(satisfies("1.2", "<<1.3").unwrap());
+        assert!(!satisfies("1.3", "<<1.3").unwrap());
+        assert!(satisfies("1:2.0", "1:2.0").unwrap());
+        assert!(satisfies("1.0", "*").unwrap());
+        assert!(!satisfies("1.0", "!=1.0").unwrap());
+        assert!(satisfies("", ">=1.0").is_err());
+        assert!(OsVersion::parse("x:1.0").is_err());
+    }
+
+    #[test]
+    fn test_canonical() {
+        assert_eq!(OsVersion::parse("0:1.2-1").unwrap().canonical(), "1.2-1");
+        assert_eq!(OsVersion::parse("2:1.2").unwrap().canonical(), "2:1.2");
+    }
+}
@@ -1,20 +1,165 @@
 //! Python PEP 440 versioning support
 //!
-//! This module provides version parsing and comparison for Python packages.
-//! Future: integrate pep440_rs crate for full PEP 440 compliance.
+//! [`PythonVersion`] parses and orders versions as PEP 440 defines them:
+//! epochs (`1!2.0`), release segments of any length, pre-releases
+//! (`1.0a1`, `1.0rc2`), post-releases (`1.0.post1`, `1.0-1`), development
+//! releases (`1.0.dev3`) and local versions (`1.0+ubuntu1`). Alternative
+//! spellings are normalized (`1.0-ALPHA.1` is `1.0a1`), and `1.0` equals
+//! `1.0.0`.
+//!
+//! [`SpecifierSet`] evaluates comma-separated specifiers with `~=`, `==`
+//! (including `==1.0.*`), `!=`, `<=`, `>=`, `<`, `>` and `===`. Poetry's `^`
+//! and `~` requirements and `*` are accepted as well. Pre-releases satisfy a
+//! specifier like any other version, since the versions checked here are
+//! already installed or locked.
 
 use crate::models::ScanError;
+use regex::Regex;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+/// The version pattern from PEP 440, appendix B
+static VERSION_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?ix)^\s*v?
+        (?:(?P<epoch>[0-9]+)!)?
+        (?P<release>[0-9]+(?:\.[0-9]+)*)
+        (?:[-_.]?(?P<pre_l>alpha|beta|preview|pre|rc|a|b|c)[-_.]?(?P<pre_n>[0-9]+)?)?
+        (?:-(?P<post_n1>[0-9]+)|[-_.]?(?P<post_l>post|rev|r)[-_.]?(?P<post_n2>[0-9]+)?)?
+        (?:[-_.]?(?P<dev_l>dev)[-_.]?(?P<dev_n>[0-9]+)?)?
+        (?:\+(?P<local>[a-z0-9]+(?:[-_.][a-z0-9]+)*))?
+        \s*$",
+    )
+    .unwrap()
+});
+
+/// Pre-release phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PreRelease {
+    /// Alpha (`a`)
+    Alpha,
+    /// Beta (`b`)
+    Beta,
+    /// Release candidate (`rc`)
+    Rc,
+}
+
+impl fmt::Display for PreRelease {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PreRelease::Alpha => write!(f, "a"),
+            PreRelease::Beta => write!(f, "b"),
+            PreRelease::Rc => write!(f, "rc"),
+        }
+    }
+}
+
+/// Segment of a local version label
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LocalSegment {
+    /// Numeric segment, ordered after all alphanumeric ones
+    Number(u64),
+    /// Alphanumeric segment (lowercase)
+    Text(String),
+}
+
+impl Ord for LocalSegment {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (LocalSegment::Number(a), LocalSegment::Number(b)) => a.cmp(b),
+            (LocalSegment::Text(a), LocalSegment::Text(b)) => a.cmp(b),
+            (LocalSegment::Number(_), LocalSegment::Text(_)) => Ordering::Greater,
+            (LocalSegment::Text(_), LocalSegment::Number(_)) => Ordering::Less,
+        }
+    }
+}
+
+impl PartialOrd for LocalSegment {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-/// Python version wrapper
+impl fmt::Display for LocalSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocalSegment::Number(n) => write!(f, "{}", n),
+            LocalSegment::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// A PEP 440 version
+#[derive(Debug, Clone)]
 pub struct PythonVersion {
     raw: String,
+    epoch: u64,
+    release: Vec<u64>,
+    pre: Option<(PreRelease, u64)>,
+    post: Option<u64>,
+    dev: Option<u64>,
+    local: Vec<LocalSegment>,
 }
 
 impl PythonVersion {
     /// Parse a Python version string
-    pub fn parse(version: &str) -> Result<Self, String> {
+    pub fn parse(version: &str) -> Result<Self, ScanError> {
+        let invalid = || ScanError::VersionParse(format!("Invalid PEP 440 version: {}", version));
+        let caps = VERSION_PATTERN.captures(version).ok_or_else(invalid)?;
+        let number = |name: &str| -> Result<Option<u64>, ScanError> {
+            caps.name(name)
+                .map(|m| m.as_str().parse::<u64>().map_err(|_| invalid()))
+                .transpose()
+        };
+
+        let release = caps["release"]
+            .split('.')
+            .map(|part| part.parse::<u64>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let pre = match caps.name("pre_l") {
+            Some(label) => {
+                let phase = match label.as_str().to_ascii_lowercase().as_str() {
+                    "a" | "alpha" => PreRelease::Alpha,
+                    "b" | "beta" => PreRelease::Beta,
+                    _ => PreRelease::Rc,
+                };
+                Some((phase, number("pre_n")?.unwrap_or(0)))
+            }
+            None => None,
+        };
+        let post = match (number("post_n1")?, caps.name("post_l")) {
+            (Some(n), _) => Some(n),
+            (None, Some(_)) => Some(number("post_n2")?.unwrap_or(0)),
+            (None, None) => None,
+        };
+        let dev = match caps.name("dev_l") {
+            Some(_) => Some(number("dev_n")?.unwrap_or(0)),
+            None => None,
+        };
+        let local = caps
+            .name("local")
+            .map(|local| {
+                local
+                    .as_str()
+                    .split(['.', '-', '_'])
+                    .map(|segment| match segment.parse::<u64>() {
+                        Ok(n) => LocalSegment::Number(n),
+                        Err(_) => LocalSegment::Text(segment.to_ascii_lowercase()),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
         Ok(Self {
-            raw: version.to_string(),
+            raw: version.trim().to_string(),
+            epoch: number("epoch")?.unwrap_or(0),
+            release,
+            pre,
+            post,
+            dev,
+            local,
         })
     }
 
@@ -22,103 +167,470 @@ impl PythonVersion {
     pub fn as_str(&self) -> &str {
         &self.raw
     }
-}
 
-/// Check if a version satisfies a PEP 440 specifier
-///
-/// This is a simplified implementation. For production use, integrate pep440_rs crate.
-pub fn satisfies(version: &str, specifier: &str) -> Result<bool, ScanError> {
-    let version = version.trim();
-    let specifier = specifier.trim();
+    /// Epoch (0 unless written as `N!`)
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
 
-    // Exact match
-    if version == specifier {
-        return Ok(true);
+    /// Release segments (`[1, 2, 3]` for `1.2.3`)
+    pub fn release(&self) -> &[u64] {
+        &self.release
     }
 
-    // Parse version
-    let version_parts = parse_version_parts(version)?;
+    /// Whether this is a pre-release or development release
+    pub fn is_prerelease(&self) -> bool {
+        self.pre.is_some() || self.dev.is_some()
+    }
 
-    // Handle >= specifier
-    if let Some(stripped) = specifier.strip_prefix(">=") {
-        let spec_version = &stripped.trim();
-        let spec_parts = parse_version_parts(spec_version)?;
-        return Ok(version_parts >= spec_parts);
+    /// Whether this is a post-release
+    pub fn is_postrelease(&self) -> bool {
+        self.post.is_some()
     }
 
-    // Handle > specifier
-    if let Some(stripped) = specifier.strip_prefix('>') {
-        let spec_version = &stripped.trim();
-        let spec_parts = parse_version_parts(spec_version)?;
-        return Ok(version_parts > spec_parts);
+    /// Whether this version has a local label
+    pub fn is_local(&self) -> bool {
+        !self.local.is_empty()
     }
 
-    // Handle <= specifier
-    if let Some(stripped) = specifier.strip_prefix("<=") {
-        let spec_version = &stripped.trim();
-        let spec_parts = parse_version_parts(spec_version)?;
-        return Ok(version_parts <= spec_parts);
+    /// The version without its local label
+    pub fn public(&self) -> PythonVersion {
+        PythonVersion {
+            local: Vec::new(),
+            ..self.clone()
+        }
     }
 
-    // Handle < specifier
-    if let Some(stripped) = specifier.strip_prefix('<') {
-        let spec_version = &stripped.trim();
-        let spec_parts = parse_version_parts(spec_version)?;
-        return Ok(version_parts < spec_parts);
+    /// Normalized form without trailing zero release segments
+    ///
+    /// Equal versions have the same canonical form (`1.0.0` and `v1.0` are
+    /// both `1`).
+    pub fn canonical(&self) -> String {
+        let mut release = self.release.clone();
+        while release.len() > 1 && release.last() == Some(&0) {
+            release.pop();
+        }
+        PythonVersion {
+            release,
+            ..self.clone()
+        }
+        .to_string()
     }
 
-    // Handle == specifier
-    if let Some(stripped) = specifier.strip_prefix("==") {
-        let spec_version = stripped.trim();
-        return Ok(version == spec_version);
+    /// Release segment `i`, 0 when the release is shorter
+    fn segment(&self, i: usize) -> u64 {
+        self.release.get(i).copied().unwrap_or(0)
     }
 
-    // Handle ~= compatible release (e.g., ~=2.2 matches >=2.2, <3.0)
-    if let Some(stripped) = specifier.strip_prefix("~=") {
-        let spec_version = &stripped.trim();
-        let spec_parts = parse_version_parts(spec_version)?;
-        return Ok(version_parts.0 == spec_parts.0
-            && (version_parts.1 > spec_parts.1
-                || (version_parts.1 == spec_parts.1 && version_parts.2 >= spec_parts.2)));
+    /// Compare release segments, padding the shorter one with zeros
+    fn cmp_release(&self, other: &Self) -> Ordering {
+        let len = self.release.len().max(other.release.len());
+        (0..len)
+            .map(|i| self.segment(i).cmp(&other.segment(i)))
+            .find(|o| o.is_ne())
+            .unwrap_or(Ordering::Equal)
     }
 
-    // Default: exact match
-    Ok(version == specifier)
+    /// Sort key of the pre, post and dev parts
+    ///
+    /// A dev release without pre or post part sorts before all pre-releases,
+    /// and a final release after them.
+    fn suffix_key(&self) -> (i8, Option<(PreRelease, u64)>, i8, u64, i8, u64) {
+        let pre_rank = match (self.pre, self.post, self.dev) {
+            (None, None, Some(_)) => -1,
+            (None, _, _) => 1,
+            (Some(_), _, _) => 0,
+        };
+        let (post_rank, post) = match self.post {
+            Some(n) => (1, n),
+            None => (0, 0),
+        };
+        let (dev_rank, dev) = match self.dev {
+            Some(n) => (0, n),
+            None => (1, 0),
+        };
+        (pre_rank, self.pre, post_rank, post, dev_rank, dev)
+    }
 }
 
-fn parse_version_parts(version: &str) -> Result<(u32, u32, u32), ScanError> {
-    let parts: Vec<&str> = version.split('.').collect();
-    if parts.is_empty() {
-        return Err(ScanError::Parse {
-            file: std::path::PathBuf::from("version"),
-            message: format!("Invalid version format: {}", version),
-        });
+impl Ord for PythonVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.epoch
+            .cmp(&other.epoch)
+            .then_with(|| self.cmp_release(other))
+            .then_with(|| self.suffix_key().cmp(&other.suffix_key()))
+            .then_with(|| self.local.cmp(&other.local))
     }
+}
 
-    let major = parts[0].parse::<u32>().map_err(|_| ScanError::Parse {
-        file: std::path::PathBuf::from("version"),
-        message: format!("Invalid major version: {}", parts[0]),
-    })?;
+impl PartialOrd for PythonVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-    let minor = if parts.len() > 1 {
-        parts[1].parse::<u32>().map_err(|_| ScanError::Parse {
-            file: std::path::PathBuf::from("version"),
-            message: format!("Invalid minor version: {}", parts[1]),
-        })?
-    } else {
-        0
-    };
+impl PartialEq for PythonVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
 
-    let patch = if parts.len() > 2 {
-        parts[2]
-            .split(|c: char| !c.is_numeric())
-            .next()
-            .unwrap_or("0")
-            .parse::<u32>()
-            .unwrap_or(0)
+impl Eq for PythonVersion {}
+
+impl FromStr for PythonVersion {
+    type Err = ScanError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Normalized form (`1!2.0rc1.post2.dev3+local.1`)
+impl fmt::Display for PythonVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.epoch != 0 {
+            write!(f, "{}!", self.epoch)?;
+        }
+        let release: Vec<String> = self.release.iter().map(u64::to_string).collect();
+        write!(f, "{}", release.join("."))?;
+        if let Some((phase, n)) = self.pre {
+            write!(f, "{}{}", phase, n)?;
+        }
+        if let Some(n) = self.post {
+            write!(f, ".post{}", n)?;
+        }
+        if let Some(n) = self.dev {
+            write!(f, ".dev{}", n)?;
+        }
+        if !self.local.is_empty() {
+            let local: Vec<String> = self.local.iter().map(LocalSegment::to_string).collect();
+            write!(f, "+{}", local.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+/// Comparison operator of a specifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    /// `~=`
+    Compatible,
+    /// `==`
+    Equal,
+    /// `!=`
+    NotEqual,
+    /// `<=`
+    LessEqual,
+    /// `>=`
+    GreaterEqual,
+    /// `<`
+    Less,
+    /// `>`
+    Greater,
+    /// `===`
+    Arbitrary,
+}
+
+/// A single version specifier such as `>=2.0` or `==1.4.*`
+#[derive(Debug, Clone)]
+pub struct Specifier {
+    operator: Operator,
+    version: PythonVersion,
+    /// `==` or `!=` with a trailing `.*`
+    wildcard: bool,
+    /// Version text for `===`
+    literal: String,
+}
+
+impl Specifier {
+    /// Parse a specifier; a bare version means `==`
+    pub fn parse(specifier: &str) -> Result<Self, ScanError> {
+        let specifier = specifier.trim();
+        let (operator, rest) = [
+            ("===", Operator::Arbitrary),
+            ("~=", Operator::Compatible),
+            ("==", Operator::Equal),
+            ("!=", Operator::NotEqual),
+            ("<=", Operator::LessEqual),
+            (">=", Operator::GreaterEqual),
+            ("<", Operator::Less),
+            (">", Operator::Greater),
+        ]
+        .into_iter()
+        .find_map(|(prefix, operator)| specifier.strip_prefix(prefix).map(|r| (operator, r)))
+        .unwrap_or((Operator::Equal, specifier));
+        let rest = rest.trim();
+
+        if operator == Operator::Arbitrary {
+            return Ok(Self {
+                operator,
+                // Unused: `===` compares the text only
+                version: PythonVersion {
+                    raw: rest.to_string(),
+                    epoch: 0,
+                    release: vec![0],
+                    pre: None,
+                    post: None,
+                    dev: None,
+                    local: Vec::new(),
+                },
+                wildcard: false,
+                literal: rest.to_string(),
+            });
+        }
+
+        let (text, wildcard) = match rest.strip_suffix(".*") {
+            Some(prefix) if matches!(operator, Operator::Equal | Operator::NotEqual) => {
+                (prefix, true)
+            }
+            _ => (rest, false),
+        };
+        let version = PythonVersion::parse(text)?;
+        if operator == Operator::Compatible && version.release.len() < 2 {
+            return Err(ScanError::VersionParse(format!(
+                "~= needs at least two release segments: {}",
+                specifier
+            )));
+        }
+        Ok(Self {
+            operator,
+            version,
+            wildcard,
+            literal: rest.to_string(),
+        })
+    }
+
+    /// Check if a version matches this specifier
+    pub fn contains(&self, version: &PythonVersion) -> bool {
+        let spec = &self.version;
+        // Local labels only matter when the specifier has one
+        let candidate = if spec.is_local() {
+            version.clone()
+        } else {
+            version.public()
+        };
+        match self.operator {
+            Operator::Arbitrary => version.as_str().eq_ignore_ascii_case(&self.literal),
+            Operator::Equal if self.wildcard => prefix_match(&candidate, spec),
+            Operator::NotEqual if self.wildcard => !prefix_match(&candidate, spec),
+            Operator::Equal => candidate == *spec,
+            Operator::NotEqual => candidate != *spec,
+            Operator::LessEqual => candidate <= *spec,
+            Operator::GreaterEqual => candidate >= *spec,
+            Operator::Less => {
+                candidate < *spec
+                    && !(candidate.is_prerelease()
+                        && !spec.is_prerelease()
+                        && same_release(&candidate, spec))
+            }
+            Operator::Greater => {
+                candidate > *spec
+                    && !(candidate.is_postrelease()
+                        && !spec.is_postrelease()
+                        && same_release(&candidate, spec))
+            }
+            Operator::Compatible => {
+                let mut prefix = spec.public();
+                prefix.release.pop();
+                prefix.pre = None;
+                prefix.post = None;
+                prefix.dev = None;
+                candidate >= *spec && prefix_match(&candidate, &prefix)
+            }
+        }
+    }
+}
+
+/// Comma-separated specifiers that must all match
+#[derive(Debug, Clone, Default)]
+pub struct SpecifierSet {
+    specifiers: Vec<Specifier>,
+}
+
+impl SpecifierSet {
+    /// Parse a specifier set
+    ///
+    /// Environment markers after `;` are ignored. Poetry requirements are
+    /// translated: `^1.2` to `>=1.2,<2.0`, `~1.2` to `>=1.2,<1.3`, and `*`
+    /// or an empty string match every version.
+    pub fn parse(specifiers: &str) -> Result<Self, ScanError> {
+        let specifiers = specifiers.split(';').next().unwrap_or_default();
+        let mut parsed = Vec::new();
+        for part in specifiers.split(',') {
+            let part = part.trim();
+            if part.is_empty() || part == "*" {
+                continue;
+            }
+            if let Some(caret) = part.strip_prefix('^') {
+                parsed.extend(poetry_range(caret, true)?);
+            } else if let Some(tilde) = part.strip_prefix('~').filter(|t| !t.starts_with('=')) {
+                parsed.extend(poetry_range(tilde, false)?);
+            } else {
+                parsed.push(Specifier::parse(part)?);
+            }
+        }
+        Ok(Self { specifiers: parsed })
+    }
+
+    /// Check if a version matches every specifier
+    pub fn contains(&self, version: &PythonVersion) -> bool {
+        self.specifiers.iter().all(|s| s.contains(version))
+    }
+
+    /// Whether the set has no specifiers (matches every version)
+    pub fn is_empty(&self) -> bool {
+        self.specifiers.is_empty()
+    }
+}
+
+impl FromStr for SpecifierSet {
+    type Err = ScanError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Lower and upper bound of a Poetry caret or tilde requirement
+fn poetry_range(version: &str, caret: bool) -> Result<[Specifier; 2], ScanError> {
+    let lower = PythonVersion::parse(version)?;
+    let release = &lower.release;
+    let bump = if caret {
+        // First non-zero segment, or the last one given
+        release
+            .iter()
+            .position(|&n| n != 0)
+            .unwrap_or(release.len() - 1)
+    } else if release.len() > 1 {
+        1
     } else {
         0
     };
+    let mut upper: Vec<u64> = release[..=bump].to_vec();
+    upper[bump] += 1;
+    let upper: Vec<String> = upper.iter().map(u64::to_string).collect();
+    Ok([
+        Specifier::parse(&format!(">={}", version.trim()))?,
+        Specifier::parse(&format!("<{}", upper.join(".")))?,
+    ])
+}
+
+/// Whether the release segments are equal (padded with zeros)
+fn same_release(a: &PythonVersion, b: &PythonVersion) -> bool {
+    a.epoch == b.epoch && a.cmp_release(b) == Ordering::Equal
+}
+
+/// Whether `version` starts with the release (and suffixes) of `prefix`
+fn prefix_match(version: &PythonVersion, prefix: &PythonVersion) -> bool {
+    if version.epoch != prefix.epoch {
+        return false;
+    }
+    let release_matches =
+        (0..prefix.release.len()).all(|i| version.segment(i) == prefix.segment(i));
+    if !release_matches {
+        return false;
+    }
+    // `==1.0rc1.*` etc. also fix the suffixes that are given
+    (prefix.pre.is_none() || version.pre == prefix.pre)
+        && (prefix.post.is_none() || version.post == prefix.post)
+        && (prefix.dev.is_none() || version.dev == prefix.dev)
+}
+
+/// Check if a version satisfies a PEP 440 specifier set
+pub fn satisfies(version: &str, specifier: &str) -> Result<bool, ScanError> {
+    let version = PythonVersion::parse(version)?;
+    Ok(SpecifierSet::parse(specifier)?.contains(&version))
+}
 
-    Ok((major, minor, patch))
+/// Compare two versions in PEP 440 order
+pub fn compare(a: &str, b: &str) -> Result<Ordering, ScanError> {
+    Ok(PythonVersion::parse(a)?.cmp(&PythonVersion::parse(b)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(version: &str) -> PythonVersion {
+        PythonVersion::parse(version).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_normalize() {
+        assert_eq!(v("1.0-ALPHA.1").to_string(), "1.0a1");
+        assert_eq!(
+            v("v2!1.2.3-preview2_r3-dev").to_string(),
+            "2!1.2.3rc2.post3.dev0"
+        );
+        assert_eq!(v("1.0-1").to_string(), "1.0.post1");
+        assert_eq!(v("1.0+Ubuntu-1").to_string(), "1.0+ubuntu.1");
+        assert_eq!(v("1.0"), v("1.0.0"));
+        assert_eq!(v("v1.0.0.post0").canonical(), "1.post0");
+        assert!(PythonVersion::parse("1.0-foo").is_err());
+        assert!(PythonVersion::parse("latest").is_err());
+    }
+
+    #[test]
+    fn test_ordering() {
+        let ordered = [
+            "1.0.dev0",
+            "1.0a1.dev1",
+            "1.0a1",
+            "1.0b2",
+            "1.0rc1",
+            "1.0",
+            "1.0+local.abc.2",
+            "1.0+local.1",
+            "1.0.post1.dev0",
+            "1.0.post1",
+            "1.0.1",
+            "1.10",
+            "1!0.1",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(v(pair[0]) < v(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        assert!(v("1.0+abc") < v("1.0+1"));
+    }
+
+    #[test]
+    fn test_specifiers() {
+        let cases = [
+            ("2.2.1", ">=2.0,<2.3", true),
+            ("2.3.0", ">=2.0,<2.3", false),
+            ("1.0.5", "==1.0.*", true),
+            ("1.1", "==1.0.*", false),
+            ("1.1", "!=1.0.*", true),
+            ("2.31.0", "~=2.30", true),
+            ("3.0", "~=2.30", false),
+            ("1.4.5", "~=1.4.2", true),
+            ("1.5.0", "~=1.4.2", false),
+            ("2.0rc1", "<2.0", false),
+            ("1.9", "<2.0", true),
+            ("2.0.post1", ">2.0", false),
+            ("2.0.1", ">2.0", true),
+            ("1.0+cpu", "==1.0", true),
+            ("1.0", "==1.0+cpu", false),
+            ("1.0", "===1.0", true),
+            ("1.0.0", "===1.0", false),
+            ("2.31.0", "^2.28", true),
+            ("3.0.0", "^2.28", false),
+            ("0.2.9", "^0.2.3", true),
+            ("0.3.0", "^0.2.3", false),
+            ("1.2.9", "~1.2", true),
+            ("1.3.0", "~1.2", false),
+            ("5.0", "*", true),
+            ("1.0", ">=0.9; python_version < '3.8'", true),
+        ];
+        for (version, specifier, expected) in cases {
+            assert_eq!(
+                satisfies(version, specifier).unwrap(),
+                expected,
+                "{} {}",
+                version,
+                specifier
+            );
+        }
+        assert!(satisfies("1.0", "~=1").is_err());
+    }
 }
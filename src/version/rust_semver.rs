@@ -1,20 +1,41 @@
 //! Rust semantic versioning support
 //!
-//! This module provides version parsing and comparison for Rust packages.
-//! Future: integrate semver crate for full Cargo compatibility.
+//! Versions and requirements are parsed with the `semver` crate, so every
+//! form Cargo accepts works: comma-separated requirements (`>=1.2, <1.5`),
+//! exact (`=1.2.3`), wildcards (`1.*`), tilde and caret requirements, and
+//! bare versions, which are caret requirements with Cargo's `0.x` rules
+//! (`0.2.3` allows `<0.3.0`, `0.0.3` only `0.0.3`).
+//!
+//! Matching differs from Cargo for pre-releases: Cargo only selects a
+//! pre-release when the requirement names one, but the versions checked here
+//! are already locked or installed, so a pre-release matches whenever it lies
+//! within the requirement's bounds (`1.3.0-beta.1` satisfies `>=1.2, <1.5`).
+//! Build metadata is ignored.
 
 use crate::models::ScanError;
+use semver::{BuildMetadata, Comparator, Op, Prerelease, Version, VersionReq};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
 
-/// Rust version wrapper
+/// A Cargo package version
+#[derive(Debug, Clone)]
 pub struct RustVersion {
     raw: String,
+    version: Version,
 }
 
 impl RustVersion {
     /// Parse a Rust version string
-    pub fn parse(version: &str) -> Result<Self, String> {
+    pub fn parse(version: &str) -> Result<Self, ScanError> {
+        let raw = version.trim();
+        let mut parsed = Version::parse(raw).map_err(|e| {
+            ScanError::VersionParse(format!("Invalid Cargo version {}: {}", version, e))
+        })?;
+        parsed.build = BuildMetadata::EMPTY;
         Ok(Self {
-            raw: version.to_string(),
+            raw: raw.to_string(),
+            version: parsed,
         })
     }
 
@@ -22,93 +43,287 @@ impl RustVersion {
     pub fn as_str(&self) -> &str {
         &self.raw
     }
+
+    /// The parsed version, without build metadata
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+
+    /// Whether this is a pre-release (`1.0.0-rc.1`)
+    pub fn is_prerelease(&self) -> bool {
+        !self.version.pre.is_empty()
+    }
 }
 
-/// Check if a version satisfies a Cargo version requirement
-///
-/// This is a simplified implementation. For production use, integrate semver crate.
-pub fn satisfies(version: &str, requirement: &str) -> Result<bool, ScanError> {
-    let version = version.trim();
-    let requirement = requirement.trim();
+impl Ord for RustVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.version.cmp(&other.version)
+    }
+}
+
+impl PartialOrd for RustVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for RustVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.version == other.version
+    }
+}
+
+impl Eq for RustVersion {}
+
+impl FromStr for RustVersion {
+    type Err = ScanError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
 
-    // Exact match
-    if version == requirement {
-        return Ok(true);
+impl fmt::Display for RustVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.raw)
     }
+}
 
-    // Parse version
-    let version_parts = parse_version_parts(version)?;
+/// A Cargo version requirement
+#[derive(Debug, Clone)]
+pub struct RustVersionReq {
+    req: VersionReq,
+    bounds: Vec<Bounds>,
+}
 
-    // Handle caret requirements (^1.2.3 is default in Cargo)
-    if let Some(req_version) = requirement.strip_prefix('^') {
-        let req_parts = parse_version_parts(req_version)?;
-        return Ok(version_parts.0 == req_parts.0
-            && (version_parts.1 > req_parts.1
-                || (version_parts.1 == req_parts.1 && version_parts.2 >= req_parts.2)));
+impl RustVersionReq {
+    /// Parse a requirement; `*` and an empty string match every version
+    pub fn parse(requirement: &str) -> Result<Self, ScanError> {
+        let trimmed = requirement.trim();
+        let req = if trimmed.is_empty() {
+            VersionReq::STAR
+        } else {
+            VersionReq::parse(trimmed).map_err(|e| {
+                ScanError::VersionParse(format!("Invalid Cargo requirement {}: {}", requirement, e))
+            })?
+        };
+        let bounds = req.comparators.iter().map(Bounds::of).collect();
+        Ok(Self { req, bounds })
     }
 
-    // Handle tilde requirements (~1.2.3)
-    if let Some(req_version) = requirement.strip_prefix('~') {
-        let req_parts = parse_version_parts(req_version)?;
-        return Ok(version_parts.0 == req_parts.0
-            && version_parts.1 == req_parts.1
-            && version_parts.2 >= req_parts.2);
+    /// Check if a version lies within every comparator
+    pub fn matches(&self, version: &RustVersion) -> bool {
+        self.bounds.iter().all(|b| b.contains(&version.version))
     }
+}
+
+impl FromStr for RustVersionReq {
+    type Err = ScanError;
 
-    // Handle >= requirements
-    if let Some(stripped) = requirement.strip_prefix(">=") {
-        let req_version = &stripped.trim();
-        let req_parts = parse_version_parts(req_version)?;
-        return Ok(version_parts >= req_parts);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
     }
+}
 
-    // Handle > requirements
-    if let Some(stripped) = requirement.strip_prefix('>') {
-        let req_version = &stripped.trim();
-        let req_parts = parse_version_parts(req_version)?;
-        return Ok(version_parts > req_parts);
+impl fmt::Display for RustVersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.req)
     }
+}
+
+/// Version interval of a single comparator
+#[derive(Debug, Clone)]
+struct Bounds {
+    /// Lower bound and whether it is inclusive
+    lower: Option<(Version, bool)>,
+    /// Upper bound and whether it is inclusive
+    upper: Option<(Version, bool)>,
+}
+
+impl Bounds {
+    fn of(c: &Comparator) -> Self {
+        let full = c.minor.is_some() && c.patch.is_some();
+        let floor = Version {
+            major: c.major,
+            minor: c.minor.unwrap_or(0),
+            patch: c.patch.unwrap_or(0),
+            pre: c.pre.clone(),
+            build: BuildMetadata::EMPTY,
+        };
+        // First version past everything the given segments describe
+        let past = if c.patch.is_some() {
+            before(c.major, c.minor.unwrap_or(0), c.patch.unwrap_or(0) + 1)
+        } else if let Some(minor) = c.minor {
+            before(c.major, minor + 1, 0)
+        } else {
+            before(c.major + 1, 0, 0)
+        };
+        let at_least = Some((floor.clone(), true));
 
-    // Handle wildcard (*)
-    if requirement == "*" {
-        return Ok(true);
+        match c.op {
+            Op::Exact | Op::Wildcard if full => Self {
+                lower: at_least,
+                upper: Some((floor, true)),
+            },
+            Op::Exact | Op::Wildcard => Self {
+                lower: at_least,
+                upper: Some((past, false)),
+            },
+            Op::Greater if full => Self {
+                lower: Some((floor, false)),
+                upper: None,
+            },
+            Op::Greater => Self {
+                lower: Some((past, true)),
+                upper: None,
+            },
+            Op::GreaterEq => Self {
+                lower: at_least,
+                upper: None,
+            },
+            Op::Less if full => Self {
+                lower: None,
+                upper: Some((floor, false)),
+            },
+            Op::Less => Self {
+                lower: None,
+                upper: Some((before(floor.major, floor.minor, floor.patch), false)),
+            },
+            Op::LessEq if full => Self {
+                lower: None,
+                upper: Some((floor, true)),
+            },
+            Op::LessEq => Self {
+                lower: None,
+                upper: Some((past, false)),
+            },
+            Op::Tilde => {
+                let upper = match c.minor {
+                    Some(minor) => before(c.major, minor + 1, 0),
+                    None => before(c.major + 1, 0, 0),
+                };
+                Self {
+                    lower: at_least,
+                    upper: Some((upper, false)),
+                }
+            }
+            Op::Caret => {
+                let upper = match (c.major, c.minor, c.patch) {
+                    (major, _, _) if major > 0 => before(major + 1, 0, 0),
+                    (_, None, _) => before(1, 0, 0),
+                    (_, Some(minor), _) if minor > 0 => before(0, minor + 1, 0),
+                    (_, Some(_), None) => before(0, 1, 0),
+                    (_, Some(_), Some(patch)) => before(0, 0, patch + 1),
+                };
+                Self {
+                    lower: at_least,
+                    upper: Some((upper, false)),
+                }
+            }
+            // `Op` is non-exhaustive; treat future operators as exact
+            _ => Self {
+                lower: at_least,
+                upper: Some((past, false)),
+            },
+        }
     }
 
-    // Default: treat as caret requirement (Cargo default)
-    let req_parts = parse_version_parts(requirement)?;
-    Ok(version_parts.0 == req_parts.0
-        && (version_parts.1 > req_parts.1
-            || (version_parts.1 == req_parts.1 && version_parts.2 >= req_parts.2)))
+    fn contains(&self, version: &Version) -> bool {
+        let above = match &self.lower {
+            Some((lower, true)) => version >= lower,
+            Some((lower, false)) => version > lower,
+            None => true,
+        };
+        let below = match &self.upper {
+            Some((upper, true)) => version <= upper,
+            Some((upper, false)) => version < upper,
+            None => true,
+        };
+        above && below
+    }
 }
 
-fn parse_version_parts(version: &str) -> Result<(u32, u32, u32), ScanError> {
-    let parts: Vec<&str> = version.split('.').collect();
-    if parts.len() < 3 {
-        return Err(ScanError::Parse {
-            file: std::path::PathBuf::from("version"),
-            message: format!("Invalid version format: {}", version),
-        });
+/// The lowest pre-release of a version (`1.3.0-0`), so an exclusive bound
+/// also excludes the pre-releases of the version it names
+fn before(major: u64, minor: u64, patch: u64) -> Version {
+    Version {
+        major,
+        minor,
+        patch,
+        pre: Prerelease::new("0").unwrap(),
+        build: BuildMetadata::EMPTY,
     }
+}
+
+/// Check if a version satisfies a Cargo version requirement
+pub fn satisfies(version: &str, requirement: &str) -> Result<bool, ScanError> {
+    let version = RustVersion::parse(version)?;
+    Ok(RustVersionReq::parse(requirement)?.matches(&version))
+}
 
-    let major = parts[0].parse::<u32>().map_err(|_| ScanError::Parse {
-        file: std::path::PathBuf::from("version"),
-        message: format!("Invalid major version: {}", parts[0]),
-    })?;
+/// Compare two versions in semver precedence order
+pub fn compare(a: &str, b: &str) -> Result<Ordering, ScanError> {
+    Ok(RustVersion::parse(a)?.cmp(&RustVersion::parse(b)?))
+}
 
-    let minor = parts[1].parse::<u32>().map_err(|_| ScanError::Parse {
-        file: std::path::PathBuf::from("version"),
-        message: format!("Invalid minor version: {}", parts[1]),
-    })?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let patch = parts[2]
-        .split('-')
-        .next()
-        .unwrap_or(parts[2])
-        .parse::<u32>()
-        .map_err(|_| ScanError::Parse {
-            file: std::path::PathBuf::from("version"),
-            message: format!("Invalid patch version: {}", parts[2]),
-        })?;
+    #[test]
+    fn test_requirements() {
+        let cases = [
+            ("1.3.0", ">=1.2, <1.5", true),
+            ("1.5.0", ">=1.2, <1.5", false),
+            ("1.5.0-alpha.1", ">=1.2, <1.5", false),
+            ("1.3.0-beta.1", ">=1.2, <1.5", true),
+            ("1.2.3", "=1.2.3", true),
+            ("1.2.4", "=1.2.3", false),
+            ("1.2.9", "=1.2", true),
+            ("1.9.0", "1.*", true),
+            ("2.0.0", "1.*", false),
+            ("1.2.7", "1.2.*", true),
+            ("1.4.0", "1.2", true),
+            ("2.0.0", "1.2", false),
+            ("0.2.9", "0.2.3", true),
+            ("0.3.0", "0.2.3", false),
+            ("0.0.3", "^0.0.3", true),
+            ("0.0.4", "^0.0.3", false),
+            ("0.0.9", "^0.0", true),
+            ("0.9.0", "^0", true),
+            ("1.2.9", "~1.2.3", true),
+            ("1.3.0", "~1.2.3", false),
+            ("1.9.0", "~1", true),
+            ("1.3.0", ">1.2", true),
+            ("1.2.9", ">1.2", false),
+            ("1.2.9", "<=1.2", true),
+            ("1.3.0-rc.1", "<=1.2", false),
+            ("1.0.0-rc.2", ">=1.0.0-rc.1", true),
+            ("1.0.0-rc.1", ">=1.0.0", false),
+            ("1.2.3+build.5", "=1.2.3", true),
+            ("5.0.0", "*", true),
+        ];
+        for (version, requirement, expected) in cases {
+            assert_eq!(
+                satisfies(version, requirement).unwrap(),
+                expected,
+                "{} {}",
+                version,
+                requirement
+            );
+        }
+        assert!(satisfies("1.2", "^1.0").is_err());
+        assert!(satisfies("1.2.0", ">>1").is_err());
+    }
 
-    Ok((major, minor, patch))
+    #[test]
+    fn test_compare() {
+        assert_eq!(compare("1.0.0-alpha", "1.0.0").unwrap(), Ordering::Less);
+        assert_eq!(
+            compare("1.0.0-alpha.2", "1.0.0-alpha.10").unwrap(),
+            Ordering::Less
+        );
+        assert_eq!(compare("1.10.0", "1.9.0").unwrap(), Ordering::Greater);
+        assert_eq!(compare("1.0.0+a", "1.0.0+b").unwrap(), Ordering::Equal);
+    }
 }
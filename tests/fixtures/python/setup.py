@@ -0,0 +1,22 @@
+import os
+
+from setuptools import find_packages, setup
+
+here = os.path.abspath(os.path.dirname(__file__))
+
+INSTALL_REQUIRES = [
+    "requests>=2.28.0",
+    "numpy==1.24.0",
+    "celery[redis]>=5.3.0",  # task queue
+]
+
+setup(
+    name="legacy-app",
+    version="1.0.0",
+    packages=find_packages(),
+    install_requires=INSTALL_REQUIRES,
+    tests_require=["pytest"],
+    extras_require={
+        "docs": ["sphinx (>=7.0)"],
+    },
+)
@@ -0,0 +1,66 @@
+use scanner::models::{Ecosystem, FileType, SourceKind, Symbol};
+use scanner::parsers::lockfile::DenoLockParser;
+use scanner::parsers::manifest::DenoJsonParser;
+use scanner::parsers::Parser;
+use std::path::Path;
+
+#[test]
+fn test_parse_deno_json_fixture() {
+    let content = std::fs::read_to_string("tests/fixtures/node/deno.json").unwrap();
+
+    let parser = DenoJsonParser::new();
+    let result = parser
+        .parse(&content, Path::new("tests/fixtures/node/deno.json"))
+        .unwrap();
+
+    assert_eq!(result.len(), 3);
+    assert!(result
+        .iter()
+        .all(|d| d.ecosystem == Ecosystem::Node && d.file_type == FileType::Manifest));
+
+    let chalk = result.iter().find(|d| d.name == "chalk").unwrap();
+    assert_eq!(chalk.version, "^5.3.0");
+    assert_eq!(chalk.source_kind, SourceKind::Registry);
+
+    let assert = result.iter().find(|d| d.name == "@std/assert").unwrap();
+    assert_eq!(assert.version, "^1.0.0");
+    assert_eq!(
+        assert.source_kind,
+        SourceKind::Url {
+            url: "https://jsr.io/@std/assert".to_string()
+        }
+    );
+
+    let oak = result.iter().find(|d| d.name == "oak").unwrap();
+    assert_eq!(oak.version, "v12.6.1");
+}
+
+#[test]
+fn test_parse_deno_lock_fixture() {
+    let content = std::fs::read_to_string("tests/fixtures/node/deno.lock").unwrap();
+
+    let parser = DenoLockParser;
+    let result = parser
+        .parse(&content, Path::new("tests/fixtures/node/deno.lock"))
+        .unwrap();
+
+    assert_eq!(result.len(), 4);
+    assert!(result.iter().all(|d| d.file_type == FileType::Lockfile));
+
+    let chalk = result.iter().find(|d| d.name == "chalk").unwrap();
+    assert_eq!(chalk.version, "5.3.0");
+    assert!(chalk.integrity.as_deref().unwrap().starts_with("sha512-"));
+
+    let assert = result.iter().find(|d| d.name == "@std/assert").unwrap();
+    assert_eq!(assert.version, "1.0.6");
+    assert_eq!(
+        assert.resolved.as_deref(),
+        Some("https://jsr.io/@std/assert/1.0.6")
+    );
+    assert_eq!(assert.dependencies, vec![Symbol::from("@std/internal")]);
+
+    // Remote modules are locked per file but reported once
+    let oak: Vec<_> = result.iter().filter(|d| d.name == "oak").collect();
+    assert_eq!(oak.len(), 1);
+    assert_eq!(oak[0].version, "v12.6.1");
+}
@@ -35,6 +35,33 @@ react@^18.2.0:
     assert_eq!(react.version, "18.2.0");
 }
 
+#[test]
+fn test_parse_yarn_lock_resolved_and_scoped() {
+    let content = r#"
+# yarn lockfile v1
+
+"@acme/ui@^1.0.0":
+  version "1.2.0"
+  resolved "https://registry.yarnpkg.com/@acme/ui/-/ui-1.2.0.tgz"
+
+lodash@^4.17.21:
+  version "4.17.21"
+"#;
+
+    let result = YarnLockParser
+        .parse(content, Path::new("yarn.lock"))
+        .unwrap();
+
+    let scoped = result.iter().find(|d| d.name == "@acme/ui").unwrap();
+    assert_eq!(scoped.version, "1.2.0");
+    assert_eq!(
+        scoped.resolved.as_deref(),
+        Some("https://registry.yarnpkg.com/@acme/ui/-/ui-1.2.0.tgz")
+    );
+    let lodash = result.iter().find(|d| d.name == "lodash").unwrap();
+    assert_eq!(lodash.resolved, None);
+}
+
 #[test]
 fn test_parse_yarn_lock_fixture() {
     let content = std::fs::read_to_string("tests/fixtures/node/yarn.lock").unwrap();
@@ -68,7 +95,9 @@ fn test_parse_package_lock_json() {
       "version": "18.2.0"
     },
     "node_modules/lodash": {
-      "version": "4.17.21"
+      "version": "4.17.21",
+      "resolved": "https://registry.npmjs.org/lodash/-/lodash-4.17.21.tgz",
+      "integrity": "sha512-v2kDEe57lecTulaDIuNTPy3Ry4gLGJ6Z1O3vE1krgXZNrsQ+LFTGHVxVjcXPs17LhbZVGedAJv8XZ1tvj5FvSg=="
     }
   }
 }"#;
@@ -87,6 +116,15 @@ fn test_parse_package_lock_json() {
     let lodash = result.iter().find(|d| d.name == "lodash");
     assert!(lodash.is_some());
     assert_eq!(lodash.unwrap().version, "4.17.21");
+    assert_eq!(
+        lodash.unwrap().resolved.as_deref(),
+        Some("https://registry.npmjs.org/lodash/-/lodash-4.17.21.tgz")
+    );
+    assert!(lodash
+        .unwrap()
+        .integrity
+        .as_deref()
+        .is_some_and(|i| i.starts_with("sha512-")));
 }
 
 #[test]
@@ -139,6 +177,103 @@ packages:
     assert!(react.is_some());
 }
 
+#[test]
+fn test_parse_pnpm_lock_yaml_resolution() {
+    let content = r#"
+lockfileVersion: 5.4
+
+packages:
+  /lodash/4.17.21:
+    resolution: {integrity: sha512-v2kDE==}
+
+  /left-pad/1.3.0:
+    resolution: {integrity: sha512-XI5M==, tarball: https://npm.acme.internal/left-pad/-/left-pad-1.3.0.tgz}
+    engines: {node: '>=0.10'}
+"#;
+
+    let result = PnpmLockParser
+        .parse(content, Path::new("pnpm-lock.yaml"))
+        .unwrap();
+
+    let lodash = result.iter().find(|d| d.name == "lodash").unwrap();
+    assert_eq!(lodash.integrity.as_deref(), Some("sha512-v2kDE=="));
+    assert_eq!(lodash.resolved, None);
+
+    let left_pad = result.iter().find(|d| d.name == "left-pad").unwrap();
+    assert_eq!(left_pad.integrity.as_deref(), Some("sha512-XI5M=="));
+    assert_eq!(
+        left_pad.resolved.as_deref(),
+        Some("https://npm.acme.internal/left-pad/-/left-pad-1.3.0.tgz")
+    );
+}
+
+#[test]
+fn test_parse_npm_aliases_in_lockfiles() {
+    let yarn = r#"
+# yarn lockfile v1
+
+"string-width-cjs@npm:string-width@^4.2.0":
+  version "4.2.3"
+
+string-width@^5.0.0:
+  version "5.1.2"
+"#;
+    let result = YarnLockParser.parse(yarn, Path::new("yarn.lock")).unwrap();
+    let aliased = result.iter().find(|d| d.version == "4.2.3").unwrap();
+    assert_eq!(aliased.name, "string-width");
+    assert_eq!(aliased.alias.as_deref(), Some("string-width-cjs"));
+    let direct = result.iter().find(|d| d.version == "5.1.2").unwrap();
+    assert_eq!(direct.alias, None);
+
+    let package_lock = r#"{
+  "lockfileVersion": 3,
+  "packages": {
+    "node_modules/string-width-cjs": {
+      "name": "string-width",
+      "version": "4.2.3"
+    }
+  }
+}"#;
+    let result = PackageLockJsonParser
+        .parse(package_lock, Path::new("package-lock.json"))
+        .unwrap();
+    assert_eq!(result[0].name, "string-width");
+    assert_eq!(result[0].alias.as_deref(), Some("string-width-cjs"));
+
+    let package_lock_v1 = r#"{
+  "lockfileVersion": 1,
+  "dependencies": {
+    "string-width-cjs": {"version": "npm:string-width@4.2.3"}
+  }
+}"#;
+    let result = PackageLockJsonParser
+        .parse(package_lock_v1, Path::new("package-lock.json"))
+        .unwrap();
+    assert_eq!(result[0].name, "string-width");
+    assert_eq!(result[0].version, "4.2.3");
+    assert_eq!(result[0].alias.as_deref(), Some("string-width-cjs"));
+
+    let pnpm = r#"
+lockfileVersion: 5.4
+
+specifiers:
+  string-width-cjs: npm:string-width@^4.2.0
+
+dependencies:
+  string-width-cjs: /string-width/4.2.3
+
+packages:
+  /string-width/4.2.3:
+    resolution: {integrity: sha512-wKyQ==}
+"#;
+    let result = PnpmLockParser
+        .parse(pnpm, Path::new("pnpm-lock.yaml"))
+        .unwrap();
+    let aliased = result.iter().find(|d| d.name == "string-width").unwrap();
+    assert_eq!(aliased.version, "4.2.3");
+    assert_eq!(aliased.alias.as_deref(), Some("string-width-cjs"));
+}
+
 #[test]
 fn test_parse_pnpm_lock_yaml_fixture() {
     let content = std::fs::read_to_string("tests/fixtures/node/pnpm-lock.yaml").unwrap();
@@ -157,6 +292,68 @@ fn test_parse_pnpm_lock_yaml_fixture() {
         .any(|d| d.name == "lodash" && d.version == "4.17.21"));
 }
 
+#[test]
+fn test_parse_node_lockfile_edges() {
+    let yarn = r#"
+# yarn lockfile v1
+
+"@babel/code-frame@^7.0.0":
+  version "7.22.13"
+  dependencies:
+    "@babel/highlight" "^7.22.13"
+    chalk "^2.4.2"
+
+chalk@^2.4.2:
+  version "2.4.2"
+"#;
+    let result = YarnLockParser.parse(yarn, Path::new("yarn.lock")).unwrap();
+    let frame = result
+        .iter()
+        .find(|d| d.name == "@babel/code-frame")
+        .unwrap();
+    assert_eq!(frame.dependencies, vec!["@babel/highlight", "chalk"]);
+    let chalk = result.iter().find(|d| d.name == "chalk").unwrap();
+    assert!(chalk.dependencies.is_empty());
+
+    let package_lock = r#"{
+  "lockfileVersion": 3,
+  "packages": {
+    "": { "dependencies": { "express": "^4.18.2" } },
+    "node_modules/express": {
+      "version": "4.18.2",
+      "dependencies": { "qs": "6.11.0", "body-parser": "1.20.1" }
+    }
+  }
+}"#;
+    let result = PackageLockJsonParser
+        .parse(package_lock, Path::new("package-lock.json"))
+        .unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].dependencies, vec!["body-parser", "qs"]);
+
+    let pnpm = r#"
+lockfileVersion: 5.4
+
+packages:
+  /express/4.18.2:
+    resolution: {integrity: sha512-abc}
+    dependencies:
+      qs: 6.11.0
+      '@types/node': 20.0.0
+    dev: false
+
+  /qs/6.11.0:
+    resolution: {integrity: sha512-def}
+"#;
+    let result = PnpmLockParser
+        .parse(pnpm, Path::new("pnpm-lock.yaml"))
+        .unwrap();
+    let express = result.iter().find(|d| d.name == "express").unwrap();
+    assert_eq!(express.dependencies, vec!["@types/node", "qs"]);
+    let qs = result.iter().find(|d| d.name == "qs").unwrap();
+    assert!(qs.dependencies.is_empty());
+}
+
 #[test]
 fn test_yarn_lock_parser_metadata() {
     let parser = YarnLockParser;
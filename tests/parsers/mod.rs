@@ -1,5 +1,7 @@
+mod deno_tests;
 mod lockfile_tests;
 mod package_json_tests;
 mod python_lockfile_tests;
 mod python_manifest_tests;
 mod rust_tests;
+mod span_tests;
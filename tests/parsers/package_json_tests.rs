@@ -1,4 +1,4 @@
-use scanner::models::{DependencyType, Ecosystem, FileType};
+use scanner::models::{DependencyType, Ecosystem, FileType, SourceKind};
 use scanner::parsers::manifest::PackageJsonParser;
 use scanner::parsers::Parser;
 use std::path::Path;
@@ -122,6 +122,143 @@ fn test_parse_fixture_file() {
         .any(|d| d.name == "fsevents" && d.dep_type == DependencyType::Optional));
 }
 
+#[test]
+fn test_parse_package_json_npm_alias() {
+    let content = r#"{
+        "dependencies": {
+            "string-width-cjs": "npm:string-width@^4.2.0",
+            "core": "npm:@babel/core"
+        }
+    }"#;
+
+    let result = PackageJsonParser
+        .parse(content, Path::new("package.json"))
+        .unwrap();
+
+    let width = result.iter().find(|d| d.name == "string-width").unwrap();
+    assert_eq!(width.version, "^4.2.0");
+    assert_eq!(width.alias.as_deref(), Some("string-width-cjs"));
+
+    let core = result.iter().find(|d| d.name == "@babel/core").unwrap();
+    assert_eq!(core.version, "*");
+    assert_eq!(core.alias.as_deref(), Some("core"));
+}
+
+#[test]
+fn test_parse_package_json_sources() {
+    let content = r#"{
+        "dependencies": {
+            "react": "^18.2.0",
+            "ui": "workspace:*",
+            "utils": "file:../utils",
+            "express": "expressjs/express#4.x",
+            "forked": "git+https://github.com/acme/forked.git#v1.2.0",
+            "tarball": "https://example.com/tarball-1.0.0.tgz"
+        }
+    }"#;
+
+    let result = PackageJsonParser
+        .parse(content, Path::new("package.json"))
+        .unwrap();
+    let source = |name: &str| {
+        result
+            .iter()
+            .find(|d| d.name == name)
+            .map(|d| d.source_kind.clone())
+            .unwrap()
+    };
+
+    assert_eq!(source("react"), SourceKind::Registry);
+    assert_eq!(source("ui"), SourceKind::Workspace);
+    assert_eq!(
+        source("utils"),
+        SourceKind::Path {
+            path: "../utils".to_string()
+        }
+    );
+    assert_eq!(
+        source("express"),
+        SourceKind::Git {
+            url: "expressjs/express".to_string(),
+            rev: Some("4.x".to_string())
+        }
+    );
+    assert_eq!(
+        source("forked"),
+        SourceKind::Git {
+            url: "https://github.com/acme/forked.git".to_string(),
+            rev: Some("v1.2.0".to_string())
+        }
+    );
+    assert_eq!(
+        source("tarball"),
+        SourceKind::Url {
+            url: "https://example.com/tarball-1.0.0.tgz".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_parse_package_json_overrides() {
+    let content = r#"{
+        "dependencies": {
+            "foo": "^1.0.0"
+        },
+        "overrides": {
+            "minimist": "1.2.6",
+            "foo": "$foo",
+            "bar@2": {
+                ".": "2.1.0",
+                "baz": "3.0.0"
+            }
+        },
+        "resolutions": {
+            "**/@types/node": "20.11.0",
+            "webpack/ws": "8.17.1"
+        },
+        "pnpm": {
+            "overrides": {
+                "qux@<2": "2.0.1",
+                "a>@scope/b": "1.0.0",
+                "c@>=2": "2.5.0",
+                "removed": "-"
+            }
+        }
+    }"#;
+
+    let result = PackageJsonParser
+        .parse(content, Path::new("package.json"))
+        .unwrap();
+    let mut overrides: Vec<(&str, &str)> = result
+        .iter()
+        .filter(|d| d.dep_type == DependencyType::Override)
+        .map(|d| (d.name.as_str(), d.version.as_str()))
+        .collect();
+    overrides.sort();
+
+    assert_eq!(
+        overrides,
+        vec![
+            ("@scope/b", "1.0.0"),
+            ("@types/node", "20.11.0"),
+            ("bar", "2.1.0"),
+            ("baz", "3.0.0"),
+            ("c", "2.5.0"),
+            ("foo", "^1.0.0"),
+            ("minimist", "1.2.6"),
+            ("qux", "2.0.1"),
+            ("ws", "8.17.1"),
+        ]
+    );
+    assert_eq!(
+        result
+            .iter()
+            .filter(|d| d.dep_type == DependencyType::Runtime)
+            .count(),
+        1
+    );
+}
+
 #[test]
 fn test_parser_metadata() {
     let parser = PackageJsonParser;
@@ -85,6 +85,95 @@ source = { registry = "https://pypi.org/simple" }
     assert_eq!(click.unwrap().version, "8.1.7");
 }
 
+#[test]
+fn test_parse_lockfile_sources_and_hashes() {
+    let uv = r#"
+version = 1
+
+[[package]]
+name = "flask"
+version = "3.0.0"
+source = { registry = "https://pypi.org/simple" }
+sdist = { url = "https://files.pythonhosted.org/flask-3.0.0.tar.gz", hash = "sha256:aaa", size = 1 }
+wheels = [{ url = "https://files.pythonhosted.org/flask-3.0.0-py3-none-any.whl", hash = "sha256:bbb", size = 1 }]
+
+[[package]]
+name = "app"
+version = "0.1.0"
+source = { editable = "." }
+"#;
+    let result = UvLockParser.parse(uv, Path::new("uv.lock")).unwrap();
+    let flask = result.iter().find(|d| d.name == "flask").unwrap();
+    assert_eq!(flask.resolved.as_deref(), Some("https://pypi.org/simple"));
+    assert_eq!(flask.integrity.as_deref(), Some("sha256:aaa sha256:bbb"));
+    let app = result.iter().find(|d| d.name == "app").unwrap();
+    assert_eq!(app.resolved, None);
+    assert_eq!(app.integrity, None);
+
+    let poetry = r#"
+[[package]]
+name = "acme-client"
+version = "1.0.0"
+files = [{ file = "acme_client-1.0.0.whl", hash = "sha256:ccc" }]
+
+[package.source]
+type = "legacy"
+url = "https://pypi.acme.internal/simple"
+reference = "acme"
+
+[[package]]
+name = "six"
+version = "1.16.0"
+
+[metadata.files]
+six = [{ file = "six-1.16.0.tar.gz", hash = "sha256:ddd" }]
+"#;
+    let result = PoetryLockParser
+        .parse(poetry, Path::new("poetry.lock"))
+        .unwrap();
+    let client = result.iter().find(|d| d.name == "acme-client").unwrap();
+    assert_eq!(
+        client.resolved.as_deref(),
+        Some("https://pypi.acme.internal/simple")
+    );
+    assert_eq!(client.integrity.as_deref(), Some("sha256:ccc"));
+    let six = result.iter().find(|d| d.name == "six").unwrap();
+    assert_eq!(six.resolved, None);
+    assert_eq!(six.integrity.as_deref(), Some("sha256:ddd"));
+}
+
+#[test]
+fn test_parse_python_lockfile_edges() {
+    let uv = r#"
+version = 1
+
+[[package]]
+name = "flask"
+version = "3.0.0"
+dependencies = [
+    { name = "werkzeug" },
+    { name = "colorama", marker = "sys_platform == 'win32'" },
+    { name = "colorama", marker = "platform_system == 'Windows'" },
+]
+"#;
+    let result = UvLockParser.parse(uv, Path::new("uv.lock")).unwrap();
+    assert_eq!(result[0].dependencies, vec!["colorama", "werkzeug"]);
+
+    let poetry = r#"
+[[package]]
+name = "flask"
+version = "3.0.0"
+
+[package.dependencies]
+Werkzeug = ">=3.0.0"
+click = { version = ">=8.1.3", markers = "python_version >= '3.8'" }
+"#;
+    let result = PoetryLockParser
+        .parse(poetry, Path::new("poetry.lock"))
+        .unwrap();
+    assert_eq!(result[0].dependencies, vec!["Werkzeug", "click"]);
+}
+
 #[test]
 fn test_parse_uv_lock_fixture() {
     let content = std::fs::read_to_string("tests/fixtures/python/uv.lock").unwrap();
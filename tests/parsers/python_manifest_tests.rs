@@ -1,5 +1,7 @@
-use scanner::models::{DependencyType, Ecosystem, FileType};
-use scanner::parsers::manifest::{PyprojectTomlParser, RequirementsTxtParser};
+use scanner::models::{DependencyType, Ecosystem, FileType, SourceKind};
+use scanner::parsers::manifest::{
+    PyprojectTomlParser, RequirementsTxtParser, SetupCfgParser, SetupPyParser,
+};
 use scanner::parsers::Parser;
 use std::path::Path;
 
@@ -66,6 +68,62 @@ pytest = "^7.4.0"
     assert_eq!(pytest.dep_type, DependencyType::Development);
 }
 
+#[test]
+fn test_parse_pyproject_toml_sources() {
+    let content = r#"
+[project]
+dependencies = [
+    "requests>=2.28.0",
+    "pip @ git+https://github.com/pypa/pip.git@22.0 ; python_version >= '3.8'",
+    "wheel @ https://example.com/wheel-0.42.0-py3-none-any.whl",
+]
+
+[tool.poetry.dependencies]
+python = "^3.11"
+mylib = { path = "../mylib", develop = true }
+tool = { git = "https://github.com/acme/tool.git", tag = "v2.0" }
+"#;
+
+    let result = PyprojectTomlParser
+        .parse(content, Path::new("pyproject.toml"))
+        .unwrap();
+    let source = |name: &str| {
+        result
+            .iter()
+            .find(|d| d.name == name)
+            .map(|d| d.source_kind.clone())
+            .unwrap()
+    };
+
+    assert_eq!(source("requests"), SourceKind::Registry);
+    assert_eq!(
+        source("pip"),
+        SourceKind::Git {
+            url: "https://github.com/pypa/pip.git".to_string(),
+            rev: Some("22.0".to_string())
+        }
+    );
+    assert_eq!(
+        source("wheel"),
+        SourceKind::Url {
+            url: "https://example.com/wheel-0.42.0-py3-none-any.whl".to_string()
+        }
+    );
+    assert_eq!(
+        source("mylib"),
+        SourceKind::Path {
+            path: "../mylib".to_string()
+        }
+    );
+    assert_eq!(
+        source("tool"),
+        SourceKind::Git {
+            url: "https://github.com/acme/tool.git".to_string(),
+            rev: Some("v2.0".to_string())
+        }
+    );
+}
+
 #[test]
 fn test_parse_pyproject_toml_fixture() {
     let content = std::fs::read_to_string("tests/fixtures/python/pyproject.toml").unwrap();
@@ -106,7 +164,7 @@ numpy==1.24.0
 flask>=3.0.0
 "#;
 
-    let parser = RequirementsTxtParser;
+    let parser = RequirementsTxtParser::new();
     let result = parser
         .parse(content, Path::new("requirements.txt"))
         .unwrap();
@@ -126,7 +184,7 @@ flask>=3.0.0
 fn test_parse_requirements_txt_with_extras() {
     let content = "celery[redis]>=5.3.0\nclick";
 
-    let parser = RequirementsTxtParser;
+    let parser = RequirementsTxtParser::new();
     let result = parser
         .parse(content, Path::new("requirements.txt"))
         .unwrap();
@@ -146,7 +204,7 @@ fn test_parse_requirements_txt_with_extras() {
 fn test_parse_requirements_txt_fixture() {
     let content = std::fs::read_to_string("tests/fixtures/python/requirements.txt").unwrap();
 
-    let parser = RequirementsTxtParser;
+    let parser = RequirementsTxtParser::new();
     let result = parser
         .parse(
             &content,
@@ -177,8 +235,48 @@ fn test_pyproject_toml_parser_metadata() {
 
 #[test]
 fn test_requirements_txt_parser_metadata() {
-    let parser = RequirementsTxtParser;
+    let parser = RequirementsTxtParser::new();
     assert_eq!(parser.ecosystem(), Ecosystem::Python);
     assert_eq!(parser.file_type(), FileType::Manifest);
     assert_eq!(parser.filename(), "requirements.txt");
 }
+
+#[test]
+fn test_parse_setup_cfg_fixture() {
+    let content = std::fs::read_to_string("tests/fixtures/python/setup.cfg").unwrap();
+
+    let parser = SetupCfgParser;
+    let result = parser
+        .parse(&content, Path::new("tests/fixtures/python/setup.cfg"))
+        .unwrap();
+
+    let found = |name: &str| result.iter().find(|d| d.name == name).unwrap();
+    assert_eq!(result.len(), 6);
+    assert_eq!(found("requests").version, ">=2.28.0");
+    assert_eq!(found("requests").dep_type, DependencyType::Runtime);
+    assert_eq!(found("click").version, "*");
+    assert_eq!(found("pywin32").version, ">=305");
+    assert_eq!(found("setuptools_scm").dep_type, DependencyType::Build);
+    assert_eq!(found("pytest").dep_type, DependencyType::Optional);
+    assert_eq!(found("pyOpenSSL").version, ">=23.0");
+    assert!(result.iter().all(|d| d.file_type == FileType::Manifest));
+}
+
+#[test]
+fn test_parse_setup_py_fixture() {
+    let content = std::fs::read_to_string("tests/fixtures/python/setup.py").unwrap();
+
+    let parser = SetupPyParser;
+    let result = parser
+        .parse(&content, Path::new("tests/fixtures/python/setup.py"))
+        .unwrap();
+
+    let found = |name: &str| result.iter().find(|d| d.name == name).unwrap();
+    assert_eq!(result.len(), 5);
+    assert_eq!(found("requests").version, ">=2.28.0");
+    assert_eq!(found("numpy").version, "==1.24.0");
+    assert_eq!(found("celery").version, ">=5.3.0");
+    assert_eq!(found("pytest").dep_type, DependencyType::Development);
+    assert_eq!(found("sphinx").version, ">=7.0");
+    assert_eq!(found("sphinx").dep_type, DependencyType::Optional);
+}
@@ -1,4 +1,4 @@
-use scanner::models::{DependencyType, Ecosystem, FileType};
+use scanner::models::{DependencyType, Ecosystem, FileType, SourceKind};
 use scanner::parsers::lockfile::CargoLockParser;
 use scanner::parsers::manifest::CargoTomlParser;
 use scanner::parsers::Parser;
@@ -71,6 +71,44 @@ fn test_parse_cargo_toml_fixture() {
         .any(|d| d.name == "cc" && d.dep_type == DependencyType::Build));
 }
 
+#[test]
+fn test_parse_cargo_toml_sources() {
+    let content = r#"
+[dependencies]
+serde = "1.0"
+local = { path = "../local" }
+forked = { git = "https://github.com/acme/forked", rev = "4f2a9c1" }
+shared = { workspace = true }
+"#;
+
+    let result = CargoTomlParser
+        .parse(content, Path::new("Cargo.toml"))
+        .unwrap();
+    let source = |name: &str| {
+        result
+            .iter()
+            .find(|d| d.name == name)
+            .map(|d| d.source_kind.clone())
+            .unwrap()
+    };
+
+    assert_eq!(source("serde"), SourceKind::Registry);
+    assert_eq!(
+        source("local"),
+        SourceKind::Path {
+            path: "../local".to_string()
+        }
+    );
+    assert_eq!(
+        source("forked"),
+        SourceKind::Git {
+            url: "https://github.com/acme/forked".to_string(),
+            rev: Some("4f2a9c1".to_string())
+        }
+    );
+    assert_eq!(source("shared"), SourceKind::Workspace);
+}
+
 #[test]
 fn test_parse_cargo_lock() {
     let content = r#"
@@ -81,6 +119,7 @@ version = 3
 name = "serde"
 version = "1.0.188"
 source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "e0e0"
 
 [[package]]
 name = "tokio"
@@ -99,12 +138,39 @@ source = "registry+https://github.com/rust-lang/crates.io-index"
     assert_eq!(serde.version, "1.0.188");
     assert_eq!(serde.ecosystem, Ecosystem::Rust);
     assert_eq!(serde.file_type, FileType::Lockfile);
+    assert_eq!(
+        serde.resolved.as_deref(),
+        Some("registry+https://github.com/rust-lang/crates.io-index")
+    );
+    assert_eq!(serde.integrity.as_deref(), Some("e0e0"));
 
     let tokio = result.iter().find(|d| d.name == "tokio");
     assert!(tokio.is_some());
     assert_eq!(tokio.unwrap().version, "1.32.0");
 }
 
+#[test]
+fn test_parse_cargo_lock_edges() {
+    let content = r#"
+version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = [
+ "serde",
+ "syn 2.0.38",
+ "syn 1.0.109 (registry+https://github.com/rust-lang/crates.io-index)",
+]
+"#;
+
+    let result = CargoLockParser
+        .parse(content, Path::new("Cargo.lock"))
+        .unwrap();
+
+    assert_eq!(result[0].dependencies, vec!["serde", "syn"]);
+}
+
 #[test]
 fn test_parse_cargo_lock_fixture() {
     let content = std::fs::read_to_string("tests/fixtures/rust/Cargo.lock").unwrap();
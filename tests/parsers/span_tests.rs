@@ -0,0 +1,36 @@
+use scanner::parsers::ParserRegistry;
+use std::path::Path;
+
+/// Every record of the fixtures points at a line naming the package
+#[test]
+fn test_fixture_records_have_spans() {
+    let registry = ParserRegistry::with_default_parsers();
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+
+    for ecosystem in ["node", "python", "rust"] {
+        for entry in std::fs::read_dir(fixtures.join(ecosystem)).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_str().unwrap();
+            let content = std::fs::read_to_string(&path).unwrap();
+            let parser = registry.get_parser(name).unwrap();
+            let lines: Vec<&str> = content.lines().collect();
+
+            for record in parser.parse(&content, &path).unwrap() {
+                let span = record
+                    .span
+                    .unwrap_or_else(|| panic!("{}: no span for {}", name, record.name));
+                let line = lines[span.line as usize - 1];
+                let declared = record.alias.as_deref().unwrap_or(&record.name);
+                assert!(
+                    line.contains(declared),
+                    "{}: line {} does not declare {}: {}",
+                    name,
+                    span.line,
+                    declared,
+                    line
+                );
+                assert!(content[span.start as usize..span.end as usize].contains(declared));
+            }
+        }
+    }
+}